@@ -0,0 +1,68 @@
+"""
+Batched FFI call benchmark.
+
+Times a batch of small element-wise ops and sums run through one
+execute_batch call against the same ops as individual calls, to show how
+much of the per-call FFI overhead the batch removes.
+
+Usage:
+    python benchmarks/batch_overhead.py [--size N] [--ops K] [--runs R]
+"""
+
+import argparse
+import array
+import time
+
+import _corepy_rust as rt
+
+
+def median_ms(fn, runs):
+    fn()  # warmup
+    times = []
+    for _ in range(runs):
+        start = time.perf_counter()
+        fn()
+        times.append((time.perf_counter() - start) * 1000)
+    times.sort()
+    return times[len(times) // 2]
+
+
+def main():
+    parser = argparse.ArgumentParser(description=__doc__.splitlines()[1])
+    parser.add_argument("--size", type=int, default=1000)
+    parser.add_argument("--ops", type=int, default=20)
+    parser.add_argument("--runs", type=int, default=200)
+    args = parser.parse_args()
+
+    a = array.array("f", [1.0] * args.size)
+    b = array.array("f", [0.5] * args.size)
+    out = array.array("f", [0.0] * args.size)
+    pa, pb, po = (buf.buffer_info()[0] for buf in (a, b, out))
+
+    kinds = ["add", "mul", "sum"]
+    ops = []
+    for i in range(args.ops):
+        kind = kinds[i % len(kinds)]
+        if kind == "sum":
+            ops.append({"op": "sum", "inputs": [po], "count": args.size})
+        else:
+            ops.append({"op": kind, "inputs": [pa, pb], "out": po, "count": args.size})
+    calls = {"add": rt.tensor_add_f32, "mul": rt.tensor_mul_f32}
+
+    def individual():
+        for op in ops:
+            if op["op"] == "sum":
+                rt.tensor_sum_f32(po, args.size)
+            else:
+                calls[op["op"]](pa, pb, po, args.size)
+
+    batched = median_ms(lambda: rt.execute_batch(ops), args.runs)
+    single = median_ms(individual, args.runs)
+    print(f"{args.ops} ops on {args.size}-element tensors")
+    print(f"{'individual calls':<18} {single * 1000:>10.1f} us")
+    print(f"{'execute_batch':<18} {batched * 1000:>10.1f} us")
+    print(f"{'saved per op':<18} {(single - batched) * 1000 / args.ops:>10.2f} us")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,69 @@
+"""
+Broadcast execution benchmark.
+
+Times tensor_broadcast_binary_f32 on common broadcasting patterns against
+NumPy and reports how many inner runs went to the contiguous kernel versus
+the strided loop, so regressions in the fast path show up as strided runs.
+
+Usage:
+    python benchmarks/broadcast_runs.py [--size N] [--runs R]
+"""
+
+import argparse
+import time
+
+import numpy as np
+import _corepy_rust as rt
+
+
+def patterns(n):
+    return {
+        "(N,N) + (N,)": ((n, n), (n,)),
+        "(N,N) + (N,N)": ((n, n), (n, n)),
+        "(N,1) + (1,N)": ((n, 1), (1, n)),
+        "(N,N) + scalar": ((n, n), ()),
+        "(8,1,N,N) + (4,N,1)": ((8, 1, n // 8, n), (4, n // 8, 1)),
+    }
+
+
+def median_ms(fn, runs):
+    fn()  # warmup
+    times = []
+    for _ in range(runs):
+        start = time.perf_counter()
+        fn()
+        times.append((time.perf_counter() - start) * 1000)
+    times.sort()
+    return times[len(times) // 2]
+
+
+def main():
+    parser = argparse.ArgumentParser(description=__doc__.splitlines()[1])
+    parser.add_argument("--size", type=int, default=1024)
+    parser.add_argument("--runs", type=int, default=20)
+    args = parser.parse_args()
+
+    print(f"{'pattern':<22} {'corepy ms':>10} {'numpy ms':>10} {'run len':>8} {'contig':>8} {'strided':>8}")
+    for name, (a_shape, b_shape) in patterns(args.size).items():
+        a = np.asarray(np.random.random_sample(a_shape), dtype=np.float32)
+        b = np.asarray(np.random.random_sample(b_shape), dtype=np.float32)
+        out = np.empty(np.broadcast_shapes(a.shape, b.shape), dtype=np.float32)
+
+        def run():
+            return rt.tensor_broadcast_binary_f32(
+                a.ctypes.data, list(a.shape), list(a.strides),
+                b.ctypes.data, list(b.shape), list(b.strides),
+                out.ctypes.data, 0,
+            )
+
+        summary = run()
+        ours = median_ms(run, args.runs)
+        numpy = median_ms(lambda: np.add(a, b, out=out), args.runs)
+        print(
+            f"{name:<22} {ours:>10.3f} {numpy:>10.3f} {summary['run_length']:>8} "
+            f"{summary['contiguous_runs']:>8} {summary['strided_runs']:>8}"
+        )
+
+
+if __name__ == "__main__":
+    main()
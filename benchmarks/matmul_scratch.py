@@ -0,0 +1,85 @@
+"""
+Matmul scratch allocation benchmark.
+
+Times a short, deep matmul (few rows, large K), which the native backend
+splits along K with per-task packed A panels and partial C buffers. Runs once
+with the default arena and once with the arena shrunk to 1 KB
+(COREPY_ARENA_SIZE / COREPY_ARENA_MAX_BYTES), so every scratch buffer comes
+from the heap, and reports the heap allocations counted by the scheduler.
+Each configuration runs in its own process because arenas are sized when a
+worker first uses them.
+
+Usage:
+    python benchmarks/matmul_scratch.py [--m M] [--k K] [--n N] [--runs R]
+"""
+
+import argparse
+import json
+import os
+import subprocess
+import sys
+
+WORKER = r"""
+import json, sys, time
+import numpy as np
+import _corepy_rust as rt
+
+m, k, n, runs = map(int, sys.argv[1:])
+a = np.ones((m, k), dtype=np.float32)
+b = np.ones((k, n), dtype=np.float32)
+out = np.empty((m, n), dtype=np.float32)
+for _ in range(3):  # warmup
+    rt.tensor_matmul_2d_f32(a.ctypes.data, b.ctypes.data, out.ctypes.data, m, k, n)
+rt.reset_scheduler_stats()
+times = []
+for _ in range(runs):
+    start = time.perf_counter()
+    rt.tensor_matmul_2d_f32(a.ctypes.data, b.ctypes.data, out.ctypes.data, m, k, n)
+    times.append((time.perf_counter() - start) * 1000)
+times.sort()
+stats = rt.get_scheduler_stats()
+print(json.dumps({
+    "median_ms": times[len(times) // 2],
+    "heap_allocations": stats["arena_heap_fallbacks"],
+    "tasks": stats["tasks_spawned"],
+}))
+"""
+
+CONFIGS = {
+    "arena": {},
+    "heap": {"COREPY_ARENA_SIZE": "1024", "COREPY_ARENA_MAX_BYTES": "1024"},
+}
+
+
+def run(extra_env, args):
+    env = dict(os.environ)
+    for name in ("COREPY_ARENA_SIZE", "COREPY_ARENA_MAX_BYTES"):
+        env.pop(name, None)
+    env.update(extra_env)
+    argv = [str(args.m), str(args.k), str(args.n), str(args.runs)]
+    out = subprocess.run([sys.executable, "-c", WORKER, *argv], env=env,
+                         check=True, capture_output=True, text=True).stdout
+    return json.loads(out)
+
+
+def main():
+    parser = argparse.ArgumentParser(description=__doc__.splitlines()[1])
+    parser.add_argument("--m", type=int, default=4)
+    parser.add_argument("--k", type=int, default=65536)
+    parser.add_argument("--n", type=int, default=256)
+    parser.add_argument("--runs", type=int, default=50)
+    args = parser.parse_args()
+
+    results = {name: run(env, args) for name, env in CONFIGS.items()}
+
+    print(f"matmul {args.m}x{args.k} @ {args.k}x{args.n}, {args.runs} runs")
+    print(f"{'Scratch':<8} {'Tasks':>7} {'Heap allocs':>12} {'Median(ms)':>11}")
+    print("-" * 41)
+    for name, row in results.items():
+        print(f"{name:<8} {row['tasks']:>7} {row['heap_allocations']:>12} {row['median_ms']:>11.3f}")
+    speedup = results["heap"]["median_ms"] / results["arena"]["median_ms"]
+    print(f"arena speedup: {speedup:.2f}x")
+
+
+if __name__ == "__main__":
+    main()
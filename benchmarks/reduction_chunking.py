@@ -0,0 +1,79 @@
+"""
+Parallel reduction chunking benchmark.
+
+Times tensor_sum_f32 just above the parallel threshold and on a large array,
+once with the cache-sized chunk policy and once with one chunk per worker
+(COREPY_CHUNK_BYTES set huge, the previous behaviour). Each configuration runs
+in its own process because the chunk target is read once at startup.
+
+Usage:
+    python benchmarks/reduction_chunking.py [--large-elements N]
+"""
+
+import argparse
+import json
+import os
+import subprocess
+import sys
+
+WORKER = r"""
+import json, sys, time
+import numpy as np
+import _corepy_rust as rt
+
+results = {}
+for count in map(int, sys.argv[1:]):
+    data = np.ones(count, dtype=np.float32)
+    for _ in range(3):  # warmup
+        rt.tensor_sum_f32(data.ctypes.data, count)
+    times = []
+    for _ in range(20):
+        start = time.perf_counter()
+        rt.tensor_sum_f32(data.ctypes.data, count)
+        times.append((time.perf_counter() - start) * 1000)
+    times.sort()
+    results[count] = {
+        "median_ms": times[len(times) // 2],
+        "chunks": rt.get_chunking_info(count, 4)["num_chunks"],
+    }
+print(json.dumps(results))
+"""
+
+CONFIGS = {
+    "cache-sized": None,
+    "per-worker": str(1 << 40),
+}
+
+
+def run(chunk_bytes, counts):
+    env = dict(os.environ)
+    env.pop("COREPY_CHUNK_BYTES", None)
+    if chunk_bytes is not None:
+        env["COREPY_CHUNK_BYTES"] = chunk_bytes
+    out = subprocess.run([sys.executable, "-c", WORKER, *map(str, counts)], env=env,
+                         check=True, capture_output=True, text=True).stdout
+    return json.loads(out)
+
+
+def main():
+    parser = argparse.ArgumentParser(description=__doc__.splitlines()[1])
+    parser.add_argument("--large-elements", type=int, default=256 * 1024 * 1024,
+                        help="element count of the large case (default: 1 GB of f32)")
+    args = parser.parse_args()
+    counts = [1_100_000, args.large_elements]
+
+    results = {name: run(chunk_bytes, counts) for name, chunk_bytes in CONFIGS.items()}
+
+    print(f"{'Elements':<12} {'Policy':<12} {'Chunks':>8} {'Median(ms)':>11}")
+    print("-" * 46)
+    for count in counts:
+        for name in CONFIGS:
+            row = results[name][str(count)]
+            print(f"{count:<12} {name:<12} {row['chunks']:>8} {row['median_ms']:>11.3f}")
+        base = results["per-worker"][str(count)]["median_ms"]
+        new = results["cache-sized"][str(count)]["median_ms"]
+        print(f"{'':<12} speedup: {base / new:.2f}x")
+
+
+if __name__ == "__main__":
+    main()
@@ -4,6 +4,7 @@ from .reference import ReferenceBackend
 from .selector import select_backend
 from .session import Session, get_session
 from .types import BackendType, DataType, OperationProperties, OperationType
+from contextlib import contextmanager
 from enum import IntEnum
 
 class BackendPolicy(IntEnum):
@@ -12,18 +13,38 @@ class BackendPolicy(IntEnum):
     BLAS = 2
     CUDA = 3
 
-def set_backend_policy(policy: BackendPolicy):
-    """Set the global CPU backend selection policy."""
+def set_backend_policy(policy):
+    """Set the global CPU backend selection policy.
+
+    Accepts a BackendPolicy or a case-insensitive name
+    ("auto"/"default", "openblas", "blas", "cuda").
+    """
     from corepy import _corepy_rust
-    _corepy_rust.set_backend_policy(int(policy))
+    if isinstance(policy, str):
+        _corepy_rust.set_backend_policy_name(policy)
+    else:
+        _corepy_rust.set_backend_policy(int(policy))
 
 def get_backend_policy() -> BackendPolicy:
     """Get the current global CPU backend selection policy."""
     from corepy import _corepy_rust
     return BackendPolicy(_corepy_rust.get_backend_policy())
 
+@contextmanager
+def backend_policy(policy):
+    """Temporarily override the backend policy inside a ``with`` block.
+
+    The previous policy is restored on exit, even if the block raises.
+    """
+    from corepy import _corepy_rust
+    _corepy_rust.push_backend_policy(int(policy))
+    try:
+        yield
+    finally:
+        _corepy_rust.pop_backend_policy()
+
 def explain_last_dispatch() -> str:
-    """Returns a string explaining which backend was used for the last operation."""
+    """Returns a string explaining which backend was used for the last operation on the calling thread."""
     from corepy import _corepy_rust
     return _corepy_rust.explain_last_dispatch()
 
@@ -36,6 +57,7 @@ __all__ = [
     "BackendPolicy",
     "set_backend_policy",
     "get_backend_policy",
+    "backend_policy",
     "explain_last_dispatch",
     "BackendError",
     "DeviceNotFoundError",
@@ -8,8 +8,11 @@ from .core import (
     enable_profiling,
     export_profile,
     get_recommendations,
+    profile_marker,
     profile_operation,
     profile_report,
+    set_active_profiler,
+    track_call_sites,
 )
 
 __all__ = [
@@ -17,6 +20,9 @@ __all__ = [
     "disable_profiling",
     "clear_profile",
     "profile_report",
+    "profile_marker",
+    "set_active_profiler",
+    "track_call_sites",
     "export_profile",
     "ProfileContext",
     "profile_operation",
@@ -5,6 +5,7 @@ Core profiling functionality and API.
 import functools
 import json
 import logging
+import sys
 from typing import Any, Dict, List, Optional
 
 logger = logging.getLogger("corepy.profiler")
@@ -20,25 +21,38 @@ try:
     _disable_profiling = _corepy_rust.disable_profiling
     _clear_profile = _corepy_rust.clear_profile
     _get_profile_report = _corepy_rust.get_profile_report
+    _get_profile_report_dict = _corepy_rust.get_profile_report_dict
+    _save_profile_report = _corepy_rust.save_profile_report
     _set_profile_context = _corepy_rust.set_profile_context
+    _set_call_site = _corepy_rust.set_call_site
+    _set_active_profiler = _corepy_rust.set_active_profiler
+    _profile_marker = _corepy_rust.profile_marker
     _RUST_AVAILABLE = True
 except ImportError:
     _RUST_AVAILABLE = False
     logger.warning("Corepy Rust extension not found. Profiling will be disabled.")
     
     # Mock functions for fallback
-    def _enable_profiling(): pass
-    def _disable_profiling(): pass
-    def _clear_profile(): pass
-    def _get_profile_report(ctx=None): return json.dumps({
+    def _enable_profiling(profiler=None): pass
+    def _disable_profiling(profiler=None): pass
+    def _clear_profile(profiler=None): pass
+    def _get_profile_report(ctx=None, include_events=False, operations=None, profiler=None): return json.dumps({
         "metadata": {"session_id": "mock"}, 
         "operations": {}, 
         "total_time_ms": 0.0
     })
+    def _get_profile_report_dict(ctx=None, include_events=False, operations=None, profiler=None):
+        return json.loads(_get_profile_report(ctx, include_events, operations, profiler))
+    def _save_profile_report(path, ctx=None, format="json"):
+        with open(path, 'w') as f:
+            f.write(_get_profile_report(ctx) if format == "json" else json.dumps({"traceEvents": []}))
     def _set_profile_context(ctx=None): pass
+    def _set_call_site(file=None, line=0, function=""): pass
+    def _set_active_profiler(name=None): pass
+    def _profile_marker(name, metadata=None, profiler=None): pass
 
 
-def enable_profiling():
+def enable_profiling(profiler: Optional[str] = None):
     """
     Enable the global performance profiler.
     
@@ -46,42 +60,106 @@ def enable_profiling():
     log timing and device information.
     
     Overhead: <2% when enabled, 0% when disabled.
+
+    Args:
+        profiler: Name of a separate profiler to enable instead of the global
+            one (see set_active_profiler).
+    """
+    _enable_profiling(profiler)
+
+
+def disable_profiling(profiler: Optional[str] = None):
+    """
+    Disable the global (or the named) performance profiler.
+    """
+    _disable_profiling(profiler)
+
+
+def clear_profile(profiler: Optional[str] = None):
     """
-    _enable_profiling()
+    Clear all collected profiling data of the global (or the named) profiler.
+    """
+    _clear_profile(profiler)
+
+
+def set_active_profiler(name: Optional[str]):
+    """
+    Also record every operation into the named profiler.
+
+    Libraries sharing a process can each keep their own session this way:
+    enabling, clearing and reporting on a named profiler leaves the global one
+    (and other names) untouched. Operations still record into the global
+    profiler; pass None to detach.
+    """
+    _set_active_profiler(name)
+
+
+# Off by default: untracked calls skip the frame walk entirely
+_track_call_sites = False
 
 
-def disable_profiling():
+def track_call_sites(enabled: bool = True):
     """
-    Disable the global performance profiler.
+    Record the Python line that issued each tensor operation.
+
+    Reports then list the busiest call sites of every operation under
+    'call_sites'. Each tracked call costs a short frame walk, so leave this
+    off outside of investigations.
     """
-    _disable_profiling()
+    global _track_call_sites
+    _track_call_sites = enabled
+    if not enabled:
+        _set_call_site(None)
+
+
+def _note_call_site():
+    """Hand the first caller frame outside corepy to the profiler."""
+    if not _track_call_sites:
+        return
+    frame = sys._getframe(1)
+    while frame is not None and frame.f_globals.get('__name__', '').startswith('corepy'):
+        frame = frame.f_back
+    if frame is None:
+        _set_call_site(None)
+    else:
+        _set_call_site(frame.f_code.co_filename, frame.f_lineno, frame.f_code.co_name)
 
 
-def clear_profile():
+def profile_marker(name: str, metadata: Optional[str] = None, profiler: Optional[str] = None):
     """
-    Clear all collected profiling data.
+    Drop a named instant marker (e.g. "epoch 3 start") into the profile.
+
+    Markers line op timings up with application phases: reports list them
+    chronologically under 'markers' and Chrome traces show them as instant
+    events. They are not counted in the per-operation metrics.
     """
-    _clear_profile()
+    _profile_marker(name, metadata, profiler)
 
 
-def profile_report(context: Optional[str] = None, format: str = 'table') -> Any:
+def profile_report(context: Optional[str] = None, format: str = 'table',
+                   operations: Optional[List[str]] = None,
+                   profiler: Optional[str] = None) -> Any:
     """
     Get a summary report of profiled operations.
     
     Args:
         context: Optional context name to filter by.
         format: Output format ('table', 'json', 'dict', 'compact').
+        operations: Optional list of operation names to restrict the report to;
+            percentages are then relative to those operations.
+        profiler: Optional named profiler to report on instead of the global one.
         
     Returns:
         String report (table/compact/json) or Dictionary (dict).
     """
-    json_str = _get_profile_report(context)
-    data = json.loads(json_str)
-    
     if format == 'dict':
-        return data
-    elif format == 'json':
+        # Built directly from the Rust report, no JSON round trip
+        return _get_profile_report_dict(context, False, operations, profiler)
+
+    json_str = _get_profile_report(context, False, operations, profiler)
+    if format == 'json':
         return json_str
+    data = json.loads(json_str)
     
     # Text formatting
     lines = []
@@ -120,25 +198,35 @@ def export_profile(filename: str, format: str = 'json', context: Optional[str] =
         format: 'json', 'csv', 'flamegraph', 'chrome_tracing'.
         context: Optional filter.
     """
+    if format in ('json', 'chrome_tracing'):
+        # Serialized and written in Rust, without the GIL or a Python-side copy
+        _save_profile_report(filename, context, 'json' if format == 'json' else 'chrome_trace')
+        return
+
     report = profile_report(context=context, format='dict')
     
-    if format == 'json':
-        with open(filename, 'w') as f:
-            json.dump(report, f, indent=2)
-            
-    elif format == 'csv':
+    if format == 'csv':
         import csv
         ops = report.get('operations', {}).values()
         if not ops:
             return
             
-        keys = ['operation', 'count', 'total_time_ms', 'avg_time_ms', 'min_time_ms', 'max_time_ms', 'primary_backend', 'percent_total']
+        keys = ['operation', 'count', 'total_time_ms', 'avg_time_ms', 'min_time_ms', 'max_time_ms', 'primary_backend', 'percent_total',
+                'p50', 'p90', 'p99', 'stddev_ms',
+                'total_elements', 'avg_elements_per_call', 'elements_per_sec', 'gb_per_s', 'gflops',
+                'max_arena_bytes', 'avg_arena_bytes', 'max_heap_bytes', 'avg_heap_bytes',
+                'warmup_count', 'warmup_total_ms', 'gil_released_fraction']
+        # One column per duration histogram bucket, edges as reported by Rust
+        histogram = next((op['duration_histogram'] for op in ops if op.get('duration_histogram')), [])
+        bucket_keys = _duration_bucket_columns([bound for bound, _ in histogram])
         with open(filename, 'w', newline='') as f:
-            writer = csv.DictWriter(f, fieldnames=keys)
+            writer = csv.DictWriter(f, fieldnames=keys + bucket_keys)
             writer.writeheader()
             for op in ops:
                 # Filter keys
                 row = {k: op.get(k, 0) for k in keys}
+                counts = [count for _, count in op.get('duration_histogram', [])]
+                row.update(zip(bucket_keys, counts or [0] * len(bucket_keys)))
                 writer.writerow(row)
                 
     elif format == 'flamegraph':
@@ -147,12 +235,15 @@ def export_profile(filename: str, format: str = 'json', context: Optional[str] =
         speedscope_data = _convert_to_speedscope(report)
         with open(filename, 'w') as f:
             json.dump(speedscope_data, f)
-            
-    elif format == 'chrome_tracing':
-        # Simple trace events (no hierarchy in basic storage, would need full events list)
-        # Assuming we can get full events (future API)
-        # For now, export summary as instant events
-        pass
+
+
+def _duration_bucket_columns(bounds):
+    """CSV columns for duration histogram buckets: 'lt_1us', ..., 'ge_1000000us'."""
+    columns = [f'lt_{bound}us' for bound in bounds if bound is not None]
+    if bounds and bounds[-1] is None:
+        columns.append(f'ge_{bounds[-2]}us' if len(bounds) > 1 else 'all')
+    return columns
+
 
 def _convert_to_speedscope(report):
     """Convert report to speedscope format (simplified)."""
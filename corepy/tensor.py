@@ -6,9 +6,13 @@ from .backend.errors import BackendError
 from .backend.selector import select_backend
 from .backend.session import get_session
 from .backend.types import BackendType, DataType, OperationProperties, OperationType
+from .profiler.core import _note_call_site
 
 logger = logging.getLogger("corepy.tensor")
 
+# Buffer format for each dtype Tensor.sum() has a native kernel for
+_SUM_BUFFER_CHARS = {"float32": "f4", "int32": "i4"}
+
 class Tensor:
     """
     A multi-dimensional array object that automatically selects the best
@@ -278,6 +282,7 @@ class Tensor:
 
     def all(self) -> 'Tensor':
         """Returns True if all elements evaluate to True."""
+        _note_call_site()
         try:
             from ._corepy_rust import tensor_all
             
@@ -294,6 +299,7 @@ class Tensor:
 
     def any(self) -> 'Tensor':
         """Returns True if any element evaluates to True."""
+        _note_call_site()
         try:
             from ._corepy_rust import tensor_any
             
@@ -308,17 +314,15 @@ class Tensor:
 
     def sum(self) -> 'Tensor':
         """Returns sum of all elements."""
+        _note_call_site()
         try:
-            from ._corepy_rust import tensor_sum_f32, tensor_sum_i32
-            
-            if self._dtype == DataType.INT32:
-                 ptr, count, _ref = self._get_buffer_pointer('i4')
-                 result = tensor_sum_i32(ptr, count)
-            else:
-                 # Default to float32
-                 ptr, count, _ref = self._get_buffer_pointer('f4')
-                 result = tensor_sum_f32(ptr, count)
-                 
+            from ._corepy_rust import dtype_code, tensor_reduce
+
+            # Dtypes without a sum kernel are summed as float32
+            dtype = self._dtype.value if self._dtype.value in _SUM_BUFFER_CHARS else "float32"
+            ptr, count, _ref = self._get_buffer_pointer(_SUM_BUFFER_CHARS[dtype])
+            result = tensor_reduce(ptr, count, "sum", dtype_code(dtype))
+
             return Tensor([result], dtype=self._dtype, backend=self.backend)
         except ImportError:
             from .backend.dispatch import dispatch_kernel
@@ -327,6 +331,7 @@ class Tensor:
 
     def mean(self) -> 'Tensor':
         """Returns arithmetic mean of all elements."""
+        _note_call_site()
         try:
             from ._corepy_rust import tensor_mean_f32
             
@@ -342,6 +347,7 @@ class Tensor:
 
     def _binary_op(self, op: str, other: Any) -> 'Tensor':
         """Helper for binary operations via Rust FFI."""
+        _note_call_site()
         if isinstance(other, (int, float)):
              other = Tensor([float(other)] * self._element_count, device=self._device)
         
@@ -395,6 +401,7 @@ class Tensor:
 
     def matmul(self, other: 'Tensor') -> 'Tensor':
         """Matrix multiplication (handles 1D dot product and 2D matmul)."""
+        _note_call_site()
         if not isinstance(other, Tensor): raise ValueError("matmul requires Tensor")
         
         if self.backend == BackendType.CPU:
@@ -85,10 +85,21 @@ extern "C" {
     float dot_product_f32_cpu(const float* a, const float* b, size_t count);
     void matmul_f32_cpu(const float* a, const float* b, float* c,
                         size_t m, size_t k, size_t n);
+    /// Accumulating matmul: C += A·B (sgemm with beta=1 on BLAS)
+    void matmul_acc_f32_cpu(const float* a, const float* b, float* c,
+                            size_t m, size_t k, size_t n);
+    /// The AVX2 matmuls even in BLAS builds, for callers that parallelize
+    /// them themselves (the native backend, f16 matmul)
+    void matmul_native_f32_cpu(const float* a, const float* b, float* c,
+                               size_t m, size_t k, size_t n);
+    void matmul_native_acc_f32_cpu(const float* a, const float* b, float* c,
+                                   size_t m, size_t k, size_t n);
 
     // ========================================================================
     // Backend Control
     // ========================================================================
     void corepy_set_num_threads(int num_threads);
     bool corepy_is_blas_enabled();
+    /// Name of the linked BLAS library ("none" for native kernels), static string
+    const char* corepy_blas_vendor();
 }
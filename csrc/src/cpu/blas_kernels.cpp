@@ -12,10 +12,10 @@ float dot_product_f32(const float* a, const float* b, size_t count) {
 }
 
 void matmul_f32(const float* a, const float* b, float* c,
-                size_t m, size_t k, size_t n) {
+                size_t m, size_t k, size_t n, float beta = 0.0f) {
     cblas_sgemm(CblasRowMajor, CblasNoTrans, CblasNoTrans,
                 static_cast<int>(m), static_cast<int>(n), static_cast<int>(k),
-                1.0f, a, static_cast<int>(k), b, static_cast<int>(n), 0.0f, c, static_cast<int>(n));
+                1.0f, a, static_cast<int>(k), b, static_cast<int>(n), beta, c, static_cast<int>(n));
 }
 
 void set_num_threads(int num_threads) {
@@ -38,6 +38,11 @@ void matmul_f32_cpu(const float* a, const float* b, float* c,
     corepy::backend::openblas::matmul_f32(a, b, c, m, k, n);
 }
 
+void matmul_acc_f32_cpu(const float* a, const float* b, float* c,
+                        size_t m, size_t k, size_t n) {
+    corepy::backend::openblas::matmul_f32(a, b, c, m, k, n, /*beta=*/1.0f);
+}
+
 void corepy_set_num_threads(int num_threads) {
     corepy::backend::openblas::set_num_threads(num_threads);
 }
@@ -46,6 +51,10 @@ bool corepy_is_blas_enabled() {
     return true;
 }
 
+const char* corepy_blas_vendor() {
+    return "OpenBLAS";
+}
+
 } // extern "C"
 
 #endif // COREPY_USE_OPENBLAS
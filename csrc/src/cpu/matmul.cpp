@@ -17,7 +17,8 @@
 #include <immintrin.h>  // AVX2 intrinsics
 #endif
 
-#ifndef COREPY_USE_OPENBLAS
+// The AVX2 kernels are built in every configuration: BLAS builds still need
+// the matmul_native_* exports for the native backend and its Rayon splits
 
 namespace corepy::backend::avx2 {
 
@@ -74,10 +75,13 @@ float dot_product_f32(const float* a, const float* b, size_t count) {
 
 void matmul_f32(
     const float* a, const float* b, float* c,
-    size_t m, size_t k, size_t n
+    size_t m, size_t k, size_t n,
+    bool accumulate = false
 ) {
-    // Zero-initialize output matrix
-    for (size_t i = 0; i < m * n; ++i) c[i] = 0.0f;
+    // Zero-initialize output matrix (accumulate mode adds into existing C)
+    if (!accumulate) {
+        for (size_t i = 0; i < m * n; ++i) c[i] = 0.0f;
+    }
 
     // Unrolled (i, p, j) implementation
     // This order access row_b[p, :] and row_c[i, :] contiguously.
@@ -137,6 +141,22 @@ void matmul_f32(
 
 extern "C" {
 
+void matmul_native_f32_cpu(const float* a, const float* b, float* c,
+                           size_t m, size_t k, size_t n) {
+    corepy::backend::avx2::matmul_f32(a, b, c, m, k, n);
+}
+
+void matmul_native_acc_f32_cpu(const float* a, const float* b, float* c,
+                               size_t m, size_t k, size_t n) {
+    corepy::backend::avx2::matmul_f32(a, b, c, m, k, n, /*accumulate=*/true);
+}
+
+}
+
+#ifndef COREPY_USE_OPENBLAS
+
+extern "C" {
+
 float dot_product_f32_cpu(const float* a, const float* b, size_t count) {
     return corepy::backend::avx2::dot_product_f32(a, b, count);
 }
@@ -146,6 +166,11 @@ void matmul_f32_cpu(const float* a, const float* b, float* c,
     corepy::backend::avx2::matmul_f32(a, b, c, m, k, n);
 }
 
+void matmul_acc_f32_cpu(const float* a, const float* b, float* c,
+                        size_t m, size_t k, size_t n) {
+    corepy::backend::avx2::matmul_f32(a, b, c, m, k, n, /*accumulate=*/true);
+}
+
 void corepy_set_num_threads(int num_threads) {
     // No-op for native kernels (parallelized via Rust/Rayon)
 }
@@ -153,5 +178,9 @@ void corepy_set_num_threads(int num_threads) {
 bool corepy_is_blas_enabled() {
     return false;
 }
+
+const char* corepy_blas_vendor() {
+    return "none";
+}
 }
 #endif // !COREPY_USE_OPENBLAS
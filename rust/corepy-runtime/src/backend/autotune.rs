@@ -0,0 +1,214 @@
+// ============================================================================
+// Backend Auto-Tuning
+// ============================================================================
+// Opt-in, per-workload backend selection. For the first N calls of each
+// (operation, size bucket) pair the dispatcher alternates between the native
+// kernels and BLAS, timing each call; afterwards the faster backend is locked
+// in for that bucket.
+//
+// Disabled by default: the only cost on the dispatch path is one atomic load.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+
+/// Candidate backends, indexed by backend id (0 = native, 1 = BLAS)
+pub const CANDIDATES: usize = 2;
+
+/// Default number of exploration calls per bucket
+pub const DEFAULT_AUTO_TUNE_TRIALS: usize = 8;
+
+/// Timing samples for one backend in one bucket
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BackendSamples {
+    pub count: u32,
+    pub total: Duration,
+}
+
+impl BackendSamples {
+    /// Mean duration, if any samples were recorded
+    pub fn mean(&self) -> Option<Duration> {
+        if self.count == 0 { None } else { Some(self.total / self.count) }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct BucketState {
+    issued: usize,
+    samples: [BackendSamples; CANDIDATES],
+    choice: Option<u8>,
+}
+
+/// One row of the learned table
+#[derive(Debug, Clone, PartialEq)]
+pub struct AutoTuneEntry {
+    pub operation: String,
+    /// Upper bound (power of two) of the dominant dimension covered by the bucket
+    pub bucket: usize,
+    pub samples: [BackendSamples; CANDIDATES],
+    /// Locked-in backend id, None while still exploring
+    pub choice: Option<u8>,
+}
+
+lazy_static! {
+    static ref AUTO_TUNE_ENABLED: AtomicBool = AtomicBool::new(false);
+    static ref AUTO_TUNE_TRIALS: AtomicUsize = AtomicUsize::new(DEFAULT_AUTO_TUNE_TRIALS);
+    static ref AUTO_TUNE_TABLE: Mutex<HashMap<(String, u32), BucketState>> = Mutex::new(HashMap::new());
+}
+
+/// Bucket index: log2 of the dominant dimension rounded up to a power of two
+pub fn size_bucket(m: usize, n: usize, k: usize) -> u32 {
+    m.max(n).max(k).max(1).next_power_of_two().trailing_zeros()
+}
+
+/// Turn auto-tuning on, exploring `trials` calls per bucket (at least one per backend)
+pub fn enable_auto_tune(trials: usize) {
+    AUTO_TUNE_TRIALS.store(trials.max(CANDIDATES), Ordering::Relaxed);
+    AUTO_TUNE_ENABLED.store(true, Ordering::Release);
+}
+
+/// Turn auto-tuning off (the learned table is kept)
+pub fn disable_auto_tune() {
+    AUTO_TUNE_ENABLED.store(false, Ordering::Release);
+}
+
+/// Whether auto-tuning is active
+#[inline]
+pub fn auto_tune_enabled() -> bool {
+    AUTO_TUNE_ENABLED.load(Ordering::Acquire)
+}
+
+/// Number of exploration calls per bucket
+pub fn auto_tune_trials() -> usize {
+    AUTO_TUNE_TRIALS.load(Ordering::Relaxed)
+}
+
+/// Forget everything learned so far
+pub fn reset_auto_tune() {
+    AUTO_TUNE_TABLE.lock().clear();
+}
+
+/// Pick a backend for this call
+///
+/// Returns (backend_id, exploring). While exploring, the caller must report
+/// the call's duration through record_auto_tune_sample().
+pub fn choose_backend(operation: &str, m: usize, n: usize, k: usize) -> (u8, bool) {
+    let trials = AUTO_TUNE_TRIALS.load(Ordering::Relaxed);
+    let mut table = AUTO_TUNE_TABLE.lock();
+    let state = table.entry((operation.to_string(), size_bucket(m, n, k))).or_default();
+
+    if let Some(choice) = state.choice {
+        return (choice, false);
+    }
+
+    if state.issued >= trials {
+        if let (Some(native), Some(blas)) = (state.samples[0].mean(), state.samples[1].mean()) {
+            // Ties go to the native kernels (no library call overhead)
+            let choice = if blas < native { 1 } else { 0 };
+            state.choice = Some(choice);
+            return (choice, false);
+        }
+        // Samples still in flight on other threads: keep alternating
+    }
+
+    let backend_id = (state.issued % CANDIDATES) as u8;
+    state.issued += 1;
+    (backend_id, true)
+}
+
+/// Report how long an exploring call took on `backend_id`
+///
+/// This is also the seam tests use to inject deterministic timings.
+pub fn record_auto_tune_sample(operation: &str, m: usize, n: usize, k: usize, backend_id: u8, duration: Duration) {
+    let slot = backend_id as usize;
+    if slot >= CANDIDATES {
+        return;
+    }
+    let mut table = AUTO_TUNE_TABLE.lock();
+    let state = table.entry((operation.to_string(), size_bucket(m, n, k))).or_default();
+    state.samples[slot].count += 1;
+    state.samples[slot].total += duration;
+}
+
+/// Snapshot of the learned table, sorted by operation then bucket
+pub fn get_auto_tune_table() -> Vec<AutoTuneEntry> {
+    let table = AUTO_TUNE_TABLE.lock();
+    let mut entries: Vec<AutoTuneEntry> = table
+        .iter()
+        .map(|((operation, bucket), state)| AutoTuneEntry {
+            operation: operation.clone(),
+            bucket: 1usize << bucket,
+            samples: state.samples,
+            choice: state.choice,
+        })
+        .collect();
+    entries.sort_by(|a, b| (&a.operation, a.bucket).cmp(&(&b.operation, b.bucket)));
+    entries
+}
+
+/// Replace the learned table (e.g. restored from a saved configuration)
+pub fn replace_auto_tune_table(entries: Vec<AutoTuneEntry>) {
+    let mut table = AUTO_TUNE_TABLE.lock();
+    table.clear();
+    for entry in entries {
+        let bucket = entry.bucket.max(1).next_power_of_two().trailing_zeros();
+        let issued = entry.samples.iter().map(|s| s.count as usize).sum();
+        table.insert((entry.operation, bucket), BucketState {
+            issued,
+            samples: entry.samples,
+            choice: entry.choice,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size_bucket() {
+        assert_eq!(size_bucket(1, 1, 1), 0);
+        assert_eq!(size_bucket(0, 0, 0), 0);
+        assert_eq!(size_bucket(100, 3, 7), 7);  // 100 -> 128
+        assert_eq!(size_bucket(128, 3, 7), 7);
+        assert_eq!(size_bucket(4, 129, 7), 8);  // 129 -> 256
+    }
+
+    /// Drive one bucket through exploration with fake per-backend timings
+    fn explore(operation: &str, size: usize, native_us: u64, blas_us: u64) -> u8 {
+        loop {
+            let (backend, exploring) = choose_backend(operation, size, size, size);
+            if !exploring {
+                return backend;
+            }
+            let us = if backend == 0 { native_us } else { blas_us };
+            record_auto_tune_sample(operation, size, size, size, backend, Duration::from_micros(us));
+        }
+    }
+
+    #[test]
+    fn test_locks_in_faster_backend_per_bucket() {
+        // Unique operation names keep this independent of the matmul tests
+        assert_eq!(explore("tune_test_small", 32, 10, 50), 0);
+        assert_eq!(explore("tune_test_large", 1024, 900, 300), 1);
+
+        // Locked in: later calls in the bucket no longer alternate
+        for _ in 0..4 {
+            assert_eq!(choose_backend("tune_test_large", 1000, 1000, 1000), (1, false));
+        }
+        // A different bucket of the same operation explores independently
+        assert!(choose_backend("tune_test_large", 8, 8, 8).1);
+
+        let table = get_auto_tune_table();
+        let entry = table.iter().find(|e| e.operation == "tune_test_large" && e.bucket == 1024).unwrap();
+        assert_eq!(entry.choice, Some(1));
+        assert!(entry.samples[0].count > 0 && entry.samples[1].count > 0);
+    }
+
+    #[test]
+    fn test_ties_prefer_native() {
+        assert_eq!(explore("tune_test_tie", 64, 100, 100), 0);
+    }
+}
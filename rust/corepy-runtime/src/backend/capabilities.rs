@@ -0,0 +1,90 @@
+// ============================================================================
+// Backend Capabilities
+// ============================================================================
+// Detects what this build and this machine can do: linked BLAS, SIMD level,
+// thread counts, arena sizing. Detection runs once and is cached.
+
+use lazy_static::lazy_static;
+
+/// Snapshot of backend capabilities (detected once per process)
+#[derive(Debug, Clone)]
+pub struct BackendCapabilities {
+    pub blas_enabled: bool,
+    pub blas_vendor: String,
+    pub simd_level: &'static str,
+    pub rayon_threads: usize,
+    pub arena_size: usize,
+}
+
+lazy_static! {
+    static ref CAPABILITIES: BackendCapabilities = detect();
+}
+
+/// Get the cached capabilities
+pub fn get_capabilities() -> &'static BackendCapabilities {
+    &CAPABILITIES
+}
+
+/// Highest SIMD instruction set available at runtime
+pub fn detect_simd_level() -> &'static str {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        if std::is_x86_feature_detected!("avx512f") {
+            return "avx512";
+        }
+        if std::is_x86_feature_detected!("avx2") && std::is_x86_feature_detected!("fma") {
+            return "avx2";
+        }
+        if std::is_x86_feature_detected!("avx") {
+            return "avx";
+        }
+        if std::is_x86_feature_detected!("sse4.2") {
+            return "sse4.2";
+        }
+        "sse2"
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    {
+        "neon"
+    }
+
+    #[cfg(not(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64")))]
+    {
+        "scalar"
+    }
+}
+
+fn detect() -> BackendCapabilities {
+    use crate::ops::matmul::{corepy_blas_vendor, corepy_is_blas_enabled};
+
+    let blas_enabled = unsafe { corepy_is_blas_enabled() };
+    let blas_vendor = unsafe {
+        let ptr = corepy_blas_vendor();
+        if ptr.is_null() {
+            "unknown".to_string()
+        } else {
+            std::ffi::CStr::from_ptr(ptr).to_string_lossy().into_owned()
+        }
+    };
+
+    BackendCapabilities {
+        blas_enabled,
+        blas_vendor,
+        simd_level: detect_simd_level(),
+        rayon_threads: crate::scheduler::rayon_pool::num_threads(),
+        arena_size: crate::scheduler::arena::configured_arena_size(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_simd_level_is_stable() {
+        let level = detect_simd_level();
+        assert!(!level.is_empty());
+        assert_eq!(level, detect_simd_level());
+    }
+}
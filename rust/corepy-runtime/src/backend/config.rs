@@ -0,0 +1,293 @@
+// ============================================================================
+// Backend Configuration Persistence
+// ============================================================================
+// Saves and restores calibrated dispatch settings (global policy, per-op
+// overrides, thresholds, auto-tune table) as JSON so they survive restarts.
+//
+// Import is all-or-nothing: the document is parsed and fully validated into
+// a plan before any global state is touched.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+
+use super::autotune::{self, AutoTuneEntry, BackendSamples, CANDIDATES};
+use super::{
+    backend_id_from_name, backend_name, get_dispatch_threshold, get_global_policy,
+    get_operation_policies, replace_operation_policies, set_dispatch_threshold, set_policy,
+    BackendPolicy, POLICY_OPERATIONS, THRESHOLD_OPERATIONS, VALID_POLICY_NAMES,
+};
+
+/// Serialized form of the backend settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BackendConfig {
+    /// Global policy name
+    pub policy: String,
+    /// Per-operation forced policies (operation -> policy name)
+    #[serde(default)]
+    pub operation_policies: BTreeMap<String, String>,
+    /// DEFAULT-policy crossover thresholds (operation -> value)
+    #[serde(default)]
+    pub thresholds: BTreeMap<String, usize>,
+    /// Auto-tune state, present when auto-tuning was enabled or has learned anything
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_tune: Option<AutoTuneConfig>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AutoTuneConfig {
+    pub enabled: bool,
+    pub trials: usize,
+    #[serde(default)]
+    pub table: Vec<AutoTuneRow>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AutoTuneRow {
+    pub operation: String,
+    pub bucket: usize,
+    /// Locked-in backend name, None while still exploring
+    pub choice: Option<String>,
+    /// Backend name -> timing samples
+    pub samples: BTreeMap<String, SampleConfig>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SampleConfig {
+    pub count: u32,
+    pub total_ns: u64,
+}
+
+/// Validated settings, ready to apply
+struct ConfigPlan {
+    policy: BackendPolicy,
+    operation_policies: Vec<(String, BackendPolicy)>,
+    thresholds: Vec<(String, usize)>,
+    auto_tune: Option<(bool, usize, Vec<AutoTuneEntry>)>,
+}
+
+/// Snapshot the current backend settings
+pub fn current_backend_config() -> BackendConfig {
+    let operation_policies = get_operation_policies()
+        .into_iter()
+        .map(|(op, p)| (op, p.name().to_string()))
+        .collect();
+
+    let thresholds = THRESHOLD_OPERATIONS
+        .iter()
+        .filter_map(|&op| get_dispatch_threshold(op).map(|v| (op.to_string(), v)))
+        .collect();
+
+    let table = autotune::get_auto_tune_table();
+    let auto_tune = if autotune::auto_tune_enabled() || !table.is_empty() {
+        Some(AutoTuneConfig {
+            enabled: autotune::auto_tune_enabled(),
+            trials: autotune::auto_tune_trials(),
+            table: table.iter().map(auto_tune_row).collect(),
+        })
+    } else {
+        None
+    };
+
+    BackendConfig {
+        policy: get_global_policy().name().to_string(),
+        operation_policies,
+        thresholds,
+        auto_tune,
+    }
+}
+
+fn auto_tune_row(entry: &AutoTuneEntry) -> AutoTuneRow {
+    let samples = entry.samples
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| s.count > 0)
+        .map(|(id, s)| (
+            backend_name(id as u8).to_string(),
+            SampleConfig { count: s.count, total_ns: s.total.as_nanos().min(u64::MAX as u128) as u64 },
+        ))
+        .collect();
+    AutoTuneRow {
+        operation: entry.operation.clone(),
+        bucket: entry.bucket,
+        choice: entry.choice.map(|id| backend_name(id).to_string()),
+        samples,
+    }
+}
+
+/// Serialize the current backend settings as pretty-printed JSON
+pub fn export_backend_config() -> String {
+    serde_json::to_string_pretty(&current_backend_config())
+        .expect("backend config is always serializable")
+}
+
+/// Validate and apply settings produced by export_backend_config()
+///
+/// Errors name the offending field (e.g. `operation_policies.conv`); nothing
+/// is applied unless the whole document is valid.
+pub fn import_backend_config(json: &str) -> Result<(), String> {
+    let config: BackendConfig = serde_json::from_str(json)
+        .map_err(|e| format!("invalid backend config: {}", e))?;
+    let plan = validate(&config)?;
+
+    set_policy(plan.policy).map_err(|e| format!("policy: {}", e))?;
+    replace_operation_policies(plan.operation_policies);
+    for (op, value) in plan.thresholds {
+        set_dispatch_threshold(&op, value).expect("validated threshold operation");
+    }
+    if let Some((enabled, trials, table)) = plan.auto_tune {
+        autotune::replace_auto_tune_table(table);
+        if enabled {
+            autotune::enable_auto_tune(trials);
+        } else {
+            autotune::disable_auto_tune();
+        }
+    }
+    Ok(())
+}
+
+fn parse_policy(path: &str, name: &str) -> Result<BackendPolicy, String> {
+    BackendPolicy::from_name(name).ok_or_else(|| format!(
+        "{}: unknown backend policy '{}' (valid names: {})", path, name, VALID_POLICY_NAMES.join(", ")
+    ))
+}
+
+fn check_operation(path: &str, op: &str, known: &[&str]) -> Result<(), String> {
+    if known.contains(&op) {
+        Ok(())
+    } else {
+        Err(format!("{}: unknown operation '{}' (expected one of: {})", path, op, known.join(", ")))
+    }
+}
+
+/// Backend name -> auto-tune candidate slot
+fn parse_candidate(path: &str, name: &str) -> Result<u8, String> {
+    match backend_id_from_name(name) {
+        Some(id) if (id as usize) < CANDIDATES => Ok(id),
+        _ => {
+            let valid: Vec<&str> = (0..CANDIDATES as u8).map(backend_name).collect();
+            Err(format!("{}: unknown backend '{}' (expected one of: {})", path, name, valid.join(", ")))
+        }
+    }
+}
+
+fn validate(config: &BackendConfig) -> Result<ConfigPlan, String> {
+    let policy = parse_policy("policy", &config.policy)?;
+    if !super::policy_available(policy) {
+        return Err(format!("policy: {}", super::PolicyError::Unavailable(policy)));
+    }
+
+    let mut operation_policies = Vec::new();
+    for (op, name) in &config.operation_policies {
+        let path = format!("operation_policies.{}", op);
+        check_operation(&path, op, POLICY_OPERATIONS)?;
+        // Forced overrides may name unavailable backends (dispatch fails loudly)
+        operation_policies.push((op.clone(), parse_policy(&path, name)?));
+    }
+
+    let mut thresholds = Vec::new();
+    for (op, &value) in &config.thresholds {
+        check_operation(&format!("thresholds.{}", op), op, THRESHOLD_OPERATIONS)?;
+        thresholds.push((op.clone(), value));
+    }
+
+    let auto_tune = match &config.auto_tune {
+        Some(at) => {
+            let mut table = Vec::new();
+            for (i, row) in at.table.iter().enumerate() {
+                let path = format!("auto_tune.table[{}]", i);
+                check_operation(&format!("{}.operation", path), &row.operation, POLICY_OPERATIONS)?;
+                if !row.bucket.is_power_of_two() {
+                    return Err(format!("{}.bucket: {} is not a power of two", path, row.bucket));
+                }
+                let choice = match &row.choice {
+                    Some(name) => Some(parse_candidate(&format!("{}.choice", path), name)?),
+                    None => None,
+                };
+                let mut samples = [BackendSamples::default(); CANDIDATES];
+                for (name, sample) in &row.samples {
+                    let id = parse_candidate(&format!("{}.samples.{}", path, name), name)?;
+                    samples[id as usize] = BackendSamples {
+                        count: sample.count,
+                        total: Duration::from_nanos(sample.total_ns),
+                    };
+                }
+                table.push(AutoTuneEntry {
+                    operation: row.operation.clone(),
+                    bucket: row.bucket,
+                    samples,
+                    choice,
+                });
+            }
+            Some((at.enabled, at.trials, table))
+        }
+        None => None,
+    };
+
+    Ok(ConfigPlan { policy, operation_policies, thresholds, auto_tune })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(json: &str) -> BackendConfig {
+        serde_json::from_str(json).expect("test config parses")
+    }
+
+    fn validation_error(json: &str) -> String {
+        match validate(&config(json)) {
+            Ok(_) => panic!("expected validation error"),
+            Err(e) => e,
+        }
+    }
+
+    #[test]
+    fn test_valid_config_plan() {
+        let plan = validate(&config(r#"{
+            "policy": "auto",
+            "operation_policies": {"matmul_acc": "cuda"},
+            "thresholds": {"matmul": 128},
+            "auto_tune": {"enabled": true, "trials": 6, "table": [
+                {"operation": "matmul", "bucket": 256, "choice": "OpenBLAS",
+                 "samples": {"Corepy AVX2": {"count": 3, "total_ns": 900}, "OpenBLAS": {"count": 3, "total_ns": 300}}}
+            ]}
+        }"#)).unwrap();
+
+        assert_eq!(plan.policy, BackendPolicy::DEFAULT);
+        assert_eq!(plan.operation_policies, vec![("matmul_acc".to_string(), BackendPolicy::CUDA)]);
+        assert_eq!(plan.thresholds, vec![("matmul".to_string(), 128)]);
+        let (enabled, trials, table) = plan.auto_tune.unwrap();
+        assert!(enabled);
+        assert_eq!(trials, 6);
+        assert_eq!(table[0].choice, Some(1));
+        assert_eq!(table[0].samples[0].total, Duration::from_nanos(900));
+    }
+
+    #[test]
+    fn test_errors_name_the_bad_field() {
+        assert!(validation_error(r#"{"policy": "fast"}"#).starts_with("policy:"));
+        assert!(validation_error(r#"{"policy": "cuda"}"#).starts_with("policy:"));
+        assert!(validation_error(r#"{"policy": "blas", "operation_policies": {"conv": "blas"}}"#)
+            .starts_with("operation_policies.conv:"));
+        assert!(validation_error(r#"{"policy": "blas", "operation_policies": {"matmul": "gpu"}}"#)
+            .starts_with("operation_policies.matmul:"));
+        assert!(validation_error(r#"{"policy": "blas", "thresholds": {"sum": 3}}"#)
+            .starts_with("thresholds.sum:"));
+        assert!(validation_error(r#"{"policy": "blas", "auto_tune": {"enabled": false, "trials": 2, "table": [
+            {"operation": "matmul", "bucket": 64, "choice": null, "samples": {"Metal": {"count": 1, "total_ns": 1}}}
+        ]}}"#).starts_with("auto_tune.table[0].samples.Metal:"));
+        assert!(validation_error(r#"{"policy": "blas", "auto_tune": {"enabled": false, "trials": 2, "table": [
+            {"operation": "matmul", "bucket": 100, "choice": null, "samples": {}}
+        ]}}"#).starts_with("auto_tune.table[0].bucket:"));
+    }
+
+    #[test]
+    fn test_unknown_fields_rejected() {
+        assert!(import_backend_config(r#"{"policy": "blas", "polcy": "auto"}"#).is_err());
+    }
+}
@@ -0,0 +1,225 @@
+// ============================================================================
+// Learned Dispatch Table
+// ============================================================================
+// Per-size-bucket backend choices built from profiler reports collected in
+// production. The DEFAULT policy consults the table first and falls back to
+// the threshold heuristic for buckets the report never saw.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, Ordering};
+use lazy_static::lazy_static;
+use parking_lot::RwLock;
+
+use super::autotune::{size_bucket, CANDIDATES};
+use super::{backend_id_from_name, POLICY_OPERATIONS};
+use crate::profiler::metrics::ProfileReport;
+
+/// One learned routing decision
+#[derive(Debug, Clone, PartialEq)]
+pub struct DispatchTableEntry {
+    pub operation: String,
+    /// Upper bound (power of two) of the dominant dimension covered by the bucket
+    pub bucket: usize,
+    pub backend_id: u8,
+    /// Mean event duration of the chosen backend in this bucket (µs)
+    pub avg_time_us: f64,
+}
+
+lazy_static! {
+    static ref DISPATCH_TABLE: RwLock<HashMap<(String, u32), DispatchTableEntry>> = RwLock::new(HashMap::new());
+
+    /// Fast check so the dispatcher skips the lock while no table is installed
+    static ref DISPATCH_TABLE_ACTIVE: AtomicBool = AtomicBool::new(false);
+}
+
+/// Learned backend for this operation and size, if the table covers it
+#[inline]
+pub fn lookup(operation: &str, m: usize, n: usize, k: usize) -> Option<u8> {
+    if !DISPATCH_TABLE_ACTIVE.load(Ordering::Acquire) {
+        return None;
+    }
+    DISPATCH_TABLE
+        .read()
+        .get(&(operation.to_string(), size_bucket(m, n, k)))
+        .map(|entry| entry.backend_id)
+}
+
+/// Derive per-bucket choices from a report's raw events
+///
+/// Only matrix events with dimensions and a known candidate backend count;
+/// within each (operation, bucket) the backend with the lower mean time wins.
+pub fn table_from_report(report: &ProfileReport) -> Vec<DispatchTableEntry> {
+    // (operation, bucket) -> per-backend (total µs, count)
+    let mut groups: BTreeMap<(String, u32), [(u64, u64); CANDIDATES]> = BTreeMap::new();
+
+    for event in &report.events {
+        if !POLICY_OPERATIONS.contains(&event.operation.as_str()) {
+            continue;
+        }
+        let (m, n, k) = match event.dims {
+            Some(dims) => dims,
+            None => continue,
+        };
+        let slot = match backend_id_from_name(&event.backend) {
+            Some(id) if (id as usize) < CANDIDATES => id as usize,
+            _ => continue,
+        };
+        let stats = groups.entry((event.operation.clone(), size_bucket(m, n, k))).or_default();
+        stats[slot].0 += event.duration_us();
+        stats[slot].1 += 1;
+    }
+
+    groups
+        .into_iter()
+        .filter_map(|((operation, bucket), stats)| {
+            // Ties (and single-backend buckets) keep the lower id: native first
+            let (backend_id, avg_time_us) = stats
+                .iter()
+                .enumerate()
+                .filter(|(_, (_, count))| *count > 0)
+                .map(|(id, (total, count))| (id as u8, *total as f64 / *count as f64))
+                .fold(None, |best: Option<(u8, f64)>, cand| match best {
+                    Some(b) if b.1 <= cand.1 => Some(b),
+                    _ => Some(cand),
+                })?;
+            Some(DispatchTableEntry { operation, bucket: 1usize << bucket, backend_id, avg_time_us })
+        })
+        .collect()
+}
+
+/// Replace the installed table
+pub fn install_dispatch_table(entries: Vec<DispatchTableEntry>) {
+    let mut table = DISPATCH_TABLE.write();
+    table.clear();
+    for entry in entries {
+        let bucket = entry.bucket.max(1).next_power_of_two().trailing_zeros();
+        table.insert((entry.operation.clone(), bucket), entry);
+    }
+    DISPATCH_TABLE_ACTIVE.store(!table.is_empty(), Ordering::Release);
+}
+
+/// Parse a ProfileReport JSON (with raw events) and install the learned table
+///
+/// Returns the number of buckets installed.
+pub fn build_dispatch_table_from_report(json: &str) -> Result<usize, String> {
+    let report: ProfileReport = serde_json::from_str(json)
+        .map_err(|e| format!("invalid profile report: {}", e))?;
+    if report.events.is_empty() {
+        return Err("profile report has no events (export it with include_events=True)".to_string());
+    }
+    let entries = table_from_report(&report);
+    let installed = entries.len();
+    install_dispatch_table(entries);
+    Ok(installed)
+}
+
+/// Installed table, sorted by operation then bucket
+pub fn get_dispatch_table() -> Vec<DispatchTableEntry> {
+    let mut entries: Vec<DispatchTableEntry> = DISPATCH_TABLE.read().values().cloned().collect();
+    entries.sort_by(|a, b| (&a.operation, a.bucket).cmp(&(&b.operation, b.bucket)));
+    entries
+}
+
+/// Remove the installed table (DEFAULT goes back to the heuristic everywhere)
+pub fn clear_dispatch_table() {
+    install_dispatch_table(Vec::new());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profiler::metrics::{EventKind, OperationEvent};
+
+    fn event(operation: &str, backend: &str, size: usize, duration_us: u64) -> OperationEvent {
+        OperationEvent {
+            operation: operation.to_string(),
+            backend: backend.to_string(),
+            data_size: size * size * size,
+            start_time_us: 1_000,
+            end_time_us: 1_000 + duration_us,
+            context: None,
+            dims: Some((size, size, size)),
+            thread_id: 0,
+            thread_name: None,
+            sample_weight: 1,
+            seq: 0,
+            arena_bytes_used: 0,
+            heap_bytes_allocated: 0,
+            kind: EventKind::Operation,
+            metadata: None,
+            warmup: false,
+            attributes: Vec::new(),
+            gil_held_us: 0,
+            gil_released_us: 0,
+            session: None,
+            call_site: None,
+        }
+    }
+
+    #[test]
+    fn test_table_from_synthetic_report() {
+        let mut report = ProfileReport::new("synthetic".to_string(), None);
+        report.events = vec![
+            // Small: native wins
+            event("matmul", "Corepy AVX2", 60, 10),
+            event("matmul", "Corepy AVX2", 64, 12),
+            event("matmul", "OpenBLAS", 50, 40),
+            // Large: BLAS wins
+            event("matmul", "Corepy AVX2", 1000, 900),
+            event("matmul", "OpenBLAS", 1024, 300),
+            // Ignored: legacy backend label, non-matrix op
+            event("matmul", "CPU", 4, 1),
+            event("sum", "CPU-serial", 4, 1),
+        ];
+
+        let table = table_from_report(&report);
+        let routes: Vec<(usize, u8)> = table.iter().map(|e| (e.bucket, e.backend_id)).collect();
+        assert_eq!(routes, vec![(64, 0), (1024, 1)]);
+        assert_eq!(table[0].avg_time_us, 11.0);
+    }
+
+    #[test]
+    fn test_report_round_trips_through_json() {
+        let mut report = ProfileReport::new("synthetic".to_string(), None);
+        report.events = vec![event("matmul_acc", "OpenBLAS", 300, 5)];
+        let json = report.to_json().unwrap();
+
+        let parsed: ProfileReport = serde_json::from_str(&json).unwrap();
+        let table = table_from_report(&parsed);
+        assert_eq!(table.len(), 1);
+        assert_eq!((table[0].operation.as_str(), table[0].bucket, table[0].backend_id), ("matmul_acc", 512, 1));
+    }
+
+    #[test]
+    fn test_table_from_profiled_scopes() {
+        use crate::backend::{record_detailed_dispatch, BackendPolicy, Device};
+        use crate::backend::{BACKEND_NATIVE, BACKEND_OPENBLAS};
+        use crate::profiler::{ProfileScope, Profiler};
+        use std::time::{Duration, Instant};
+
+        // Scopes as the matmul entry points open them: the dispatcher names the backend
+        let profiler = Profiler::new();
+        profiler.enable();
+        for (backend_id, pause) in [(BACKEND_NATIVE, 20), (BACKEND_OPENBLAS, 0)] {
+            let flops = 512 * 512 * 512;
+            let mut scope = ProfileScope::new(profiler.clone(), "matmul".to_string(), "CPU".to_string(), flops);
+            scope.set_dims(512, 512, 512);
+            std::thread::sleep(Duration::from_millis(pause));
+            let policy = BackendPolicy::DEFAULT;
+            record_detailed_dispatch(backend_id, "matmul", 512, 512, 512, policy, Device::Cpu, Instant::now());
+        }
+
+        let json = profiler.export_json(None, None, true).unwrap();
+        let table = table_from_report(&serde_json::from_str(&json).unwrap());
+        assert_eq!(table.len(), 1);
+        let route = (table[0].operation.as_str(), table[0].bucket, table[0].backend_id);
+        assert_eq!(route, ("matmul", 512, BACKEND_OPENBLAS));
+    }
+
+    #[test]
+    fn test_report_without_events_is_rejected() {
+        let json = ProfileReport::new("empty".to_string(), None).to_json().unwrap();
+        assert!(build_dispatch_table_from_report(&json).is_err());
+        assert!(build_dispatch_table_from_report("not json").is_err());
+    }
+}
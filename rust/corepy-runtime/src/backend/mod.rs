@@ -1,9 +1,23 @@
-use std::sync::atomic::{AtomicU8, Ordering};
-use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, AtomicUsize, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
 use lazy_static::lazy_static;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use crate::profiler::core::{now_micros, report_scope_backend};
+use crate::tensor::Device;
+
+pub mod autotune;
+pub mod capabilities;
+pub mod config;
+pub mod dispatch_table;
 
 /// Strategy for selecting the execution backend
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 #[repr(u8)]
 pub enum BackendPolicy {
     DEFAULT = 0,   // Runtime decision based on heuristics (Auto)
@@ -12,14 +26,128 @@ pub enum BackendPolicy {
     CUDA = 3,      // CUDA backend (future)
 }
 
+/// Accepted policy names (case-insensitive); "auto" is an alias for "default"
+pub const VALID_POLICY_NAMES: &[&str] = &["auto", "default", "openblas", "blas", "cuda"];
+
+impl BackendPolicy {
+    /// Convert a stored/FFI numeric value into a policy
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(BackendPolicy::DEFAULT),
+            1 => Some(BackendPolicy::OPENBLAS),
+            2 => Some(BackendPolicy::BLAS),
+            3 => Some(BackendPolicy::CUDA),
+            _ => None,
+        }
+    }
+
+    /// Parse a policy name (case-insensitive, see VALID_POLICY_NAMES)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "auto" | "default" => Some(BackendPolicy::DEFAULT),
+            "openblas" => Some(BackendPolicy::OPENBLAS),
+            "blas" => Some(BackendPolicy::BLAS),
+            "cuda" => Some(BackendPolicy::CUDA),
+            _ => None,
+        }
+    }
+
+    /// Canonical lowercase name
+    pub fn name(&self) -> &'static str {
+        match self {
+            BackendPolicy::DEFAULT => "default",
+            BackendPolicy::OPENBLAS => "openblas",
+            BackendPolicy::BLAS => "blas",
+            BackendPolicy::CUDA => "cuda",
+        }
+    }
+}
+
+/// Backend id: native AVX2/scalar matmul kernels (Rayon row split)
+pub const BACKEND_NATIVE: u8 = 0;
+/// Backend id: linked BLAS library
+pub const BACKEND_OPENBLAS: u8 = 1;
+/// Backend id: CUDA (no implementation registered yet)
+pub const BACKEND_CUDA: u8 = 3;
+/// Backend id: native kernel, single thread (sequential C++ path)
+pub const BACKEND_CPU_SERIAL: u8 = 4;
+/// Backend id: native kernel split across the Rayon pool
+pub const BACKEND_CPU_PARALLEL: u8 = 5;
+
+/// Problem size of a dispatch
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum DispatchDims {
+    /// Matrix product: M, N, K
+    Matrix(usize, usize, usize),
+    /// 1D / element-wise ops: total element count
+    Elements(usize),
+}
+
+impl DispatchDims {
+    /// (M, N, K) view; element counts map to (count, 1, 1)
+    pub fn as_mnk(&self) -> (usize, usize, usize) {
+        match *self {
+            DispatchDims::Matrix(m, n, k) => (m, n, k),
+            DispatchDims::Elements(count) => (count, 1, 1),
+        }
+    }
+}
+
+impl std::fmt::Display for DispatchDims {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DispatchDims::Matrix(m, n, k) => write!(f, "size={}x{}x{}", m, n, k),
+            DispatchDims::Elements(count) => write!(f, "elements={}", count),
+        }
+    }
+}
+
+/// An execution backend the matmul dispatcher can route to
+///
+/// The policy picks a target backend id; the dispatcher then uses the most
+/// recently registered backend with that id that supports the operation.
+/// New devices (CUDA, Metal) plug in through register_backend() without
+/// touching the dispatcher.
+pub trait Backend: Send + Sync {
+    /// Stable id used in dispatch records (see backend_name)
+    fn id(&self) -> u8;
+
+    /// Human-readable name
+    fn name(&self) -> &'static str;
+
+    /// Whether this backend can run `operation` at this size right now
+    fn supports(&self, operation: &str, dims: DispatchDims) -> bool;
+
+    /// C = A·B (or C += A·B when `accumulate`), row-major
+    ///
+    /// # Safety
+    /// Caller must ensure a is valid for m*k, b for k*n and c for m*n f32
+    /// elements, with c not overlapping the inputs.
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn matmul_f32(
+        &self,
+        a: *const f32, b: *const f32, c: *mut f32,
+        m: usize, k: usize, n: usize,
+        accumulate: bool,
+    );
+}
+
 /// Information about a dispatch decision
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DispatchInfo {
     pub backend_id: u8,
     pub operation: String,
-    pub dimensions: (usize, usize, usize), // M, N, K
+    pub dimensions: DispatchDims,
     pub policy: BackendPolicy,
-    pub timestamp: std::time::Instant,
+    pub timestamp_us: u64,        // Completion time (microseconds since the Unix epoch)
+    pub duration: Duration,       // Wall time spent in the kernel
+    pub gflops: f64,              // Achieved GFLOP/s
+    pub gb_per_s: f64,            // Achieved memory bandwidth (GB/s)
+    pub attempted_backend: Option<u8>, // Requested backend that was unavailable (dispatch failed)
+    pub num_threads: usize,       // Threads the kernel ran on (after any per-call cap)
+    pub nested: bool,             // Called from a Rayon worker thread, so no parallel split
+    #[serde(default)]
+    pub device: Device,           // Where the operands live
 }
 
 lazy_static! {
@@ -29,73 +157,523 @@ lazy_static! {
     /// Storage for the last backend ID used (simple tracking)
     static ref LAST_DISPATCH: AtomicU8 = AtomicU8::new(0);
 
-    /// Storage for detailed dispatch info (rich tracking)
-    static ref LAST_DISPATCH_DETAILED: Mutex<Option<DispatchInfo>> = Mutex::new(None);
+    /// Requested BLAS thread count (0 = use all cores)
+    static ref BLAS_NUM_THREADS: AtomicUsize = AtomicUsize::new(0);
+
+    /// Set when BLAS_NUM_THREADS changed and has not been pushed to the C++ backend yet
+    static ref BLAS_NUM_THREADS_PENDING: AtomicBool = AtomicBool::new(false);
+
+    /// DEFAULT-policy crossover: matmuls with any dimension above this prefer BLAS
+    static ref MATMUL_THRESHOLD: AtomicUsize = AtomicUsize::new(
+        std::env::var("COREPY_MATMUL_THRESHOLD")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_MATMUL_THRESHOLD)
+    );
+
+    /// Scoped policy overrides (top = effective policy)
+    static ref POLICY_STACK: Mutex<Vec<BackendPolicy>> = Mutex::new(Vec::new());
+
+    /// Mirror of POLICY_STACK.len() so get_policy() can skip the lock when empty
+    static ref POLICY_STACK_DEPTH: AtomicUsize = AtomicUsize::new(0);
+
+    /// Per-operation forced policies (bypass the global policy and the stack)
+    static ref OPERATION_POLICIES: Mutex<HashMap<String, BackendPolicy>> = Mutex::new(HashMap::new());
+
+    /// Mirror of OPERATION_POLICIES.len() so lookups can skip the lock when empty
+    static ref OPERATION_POLICY_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    /// Registered backends, in registration order
+    static ref BACKEND_REGISTRY: RwLock<Vec<Arc<dyn Backend>>> = RwLock::new(Vec::new());
+
+    /// Registered dispatch observer (see set_dispatch_callback)
+    static ref DISPATCH_CALLBACK: Mutex<Option<DispatchCallback>> = Mutex::new(None);
+
+    /// Fast check so recording skips the event queue when nobody listens
+    static ref DISPATCH_EVENTS_ENABLED: AtomicBool = AtomicBool::new(false);
+
+    /// Bounded queue of dispatches waiting for drain_dispatch_events()
+    static ref DISPATCH_EVENTS: (SyncSender<DispatchInfo>, Mutex<Receiver<DispatchInfo>>) = {
+        let (tx, rx) = sync_channel(DISPATCH_EVENT_CAPACITY);
+        (tx, Mutex::new(rx))
+    };
+
+    /// Events discarded because the queue was full
+    static ref DISPATCH_EVENTS_DROPPED: AtomicU64 = AtomicU64::new(0);
+}
+
+/// Maximum queued dispatch events before new ones are dropped
+pub const DISPATCH_EVENT_CAPACITY: usize = 4096;
+
+/// Observer invoked for each dispatch when events are drained
+pub type DispatchCallback = Arc<dyn Fn(&DispatchInfo) + Send + Sync>;
+
+/// Default matmul crossover point (benchmarked: BLAS wins above ~256x256)
+pub const DEFAULT_MATMUL_THRESHOLD: usize = 256;
+
+/// Operations with a configurable dispatch threshold
+pub const THRESHOLD_OPERATIONS: &[&str] = &["matmul"];
+
+fn threshold_slot(operation: &str) -> Option<&'static AtomicUsize> {
+    match operation {
+        "matmul" => Some(&MATMUL_THRESHOLD),
+        _ => None,
+    }
 }
 
-/// Get the current global backend selection policy
+/// Set the DEFAULT-policy crossover threshold for an operation
+///
+/// 0 means "always prefer BLAS", usize::MAX means "never".
+pub fn set_dispatch_threshold(operation: &str, value: usize) -> Result<(), String> {
+    let slot = threshold_slot(operation).ok_or_else(|| format!(
+        "No dispatch threshold for operation '{}' (supported: {})",
+        operation, THRESHOLD_OPERATIONS.join(", ")
+    ))?;
+    slot.store(value, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Get the DEFAULT-policy crossover threshold for an operation
+pub fn get_dispatch_threshold(operation: &str) -> Option<usize> {
+    threshold_slot(operation).map(|slot| slot.load(Ordering::Relaxed))
+}
+
+/// Whether the DEFAULT policy should prefer BLAS for a matmul of this size
+pub fn matmul_prefers_blas(m: usize, n: usize, k: usize) -> bool {
+    let threshold = MATMUL_THRESHOLD.load(Ordering::Relaxed);
+    threshold == 0 || m.max(n).max(k) > threshold
+}
+
+// Detailed dispatch info (rich tracking), one slot per calling thread.
+// Recording is wait-free and concurrent callers never see each other's records.
+thread_local! {
+    static LAST_DISPATCH_DETAILED: RefCell<Option<DispatchInfo>> = const { RefCell::new(None) };
+}
+
+/// Add a backend to the registry; it takes precedence over earlier ones with the same id
+pub fn register_backend(backend: Arc<dyn Backend>) {
+    BACKEND_REGISTRY.write().push(backend);
+}
+
+/// Snapshot of the registered backends, in registration order
+pub fn registered_backends() -> Vec<Arc<dyn Backend>> {
+    BACKEND_REGISTRY.read().clone()
+}
+
+/// Most recently registered backend with `backend_id` that supports the operation
+pub fn select_backend(backend_id: u8, operation: &str, dims: DispatchDims) -> Option<Arc<dyn Backend>> {
+    BACKEND_REGISTRY
+        .read()
+        .iter()
+        .rev()
+        .find(|b| b.id() == backend_id && b.supports(operation, dims))
+        .cloned()
+}
+
+/// Get the effective backend selection policy
+///
+/// This is the top of the push/pop stack if any override is active,
+/// otherwise the global policy.
 pub fn get_policy() -> BackendPolicy {
-    match CURRENT_POLICY.load(Ordering::Relaxed) {
-        0 => BackendPolicy::DEFAULT,
-        1 => BackendPolicy::OPENBLAS,
-        2 => BackendPolicy::BLAS,
-        3 => BackendPolicy::CUDA,
-        _ => BackendPolicy::DEFAULT,
+    if POLICY_STACK_DEPTH.load(Ordering::Acquire) > 0 {
+        if let Some(&policy) = POLICY_STACK.lock().last() {
+            return policy;
+        }
     }
+    get_global_policy()
+}
+
+/// Operations whose policy can be overridden per operation
+pub const POLICY_OPERATIONS: &[&str] = &["matmul", "matmul_acc"];
+
+/// Current per-operation overrides
+pub fn get_operation_policies() -> Vec<(String, BackendPolicy)> {
+    OPERATION_POLICIES.lock().iter().map(|(op, &p)| (op.clone(), p)).collect()
+}
+
+/// Replace all per-operation overrides at once
+pub fn replace_operation_policies(policies: Vec<(String, BackendPolicy)>) {
+    let mut overrides = OPERATION_POLICIES.lock();
+    overrides.clear();
+    overrides.extend(policies);
+    OPERATION_POLICY_COUNT.store(overrides.len(), Ordering::Release);
+}
+
+/// Get the effective policy for one operation (per-op override, else get_policy())
+pub fn get_policy_for(operation: &str) -> BackendPolicy {
+    if OPERATION_POLICY_COUNT.load(Ordering::Acquire) > 0 {
+        if let Some(&policy) = OPERATION_POLICIES.lock().get(operation) {
+            return policy;
+        }
+    }
+    get_policy()
+}
+
+/// Force (Some) or clear (None) the policy used for one operation
+///
+/// Overrides are not checked against availability: a forced backend that
+/// is missing makes the dispatch fail loudly instead of falling back to CPU.
+pub fn set_operation_policy(operation: &str, policy: Option<BackendPolicy>) {
+    let mut overrides = OPERATION_POLICIES.lock();
+    match policy {
+        Some(p) => { overrides.insert(operation.to_string(), p); }
+        None => { overrides.remove(operation); }
+    }
+    OPERATION_POLICY_COUNT.store(overrides.len(), Ordering::Release);
+}
+
+/// Get the global backend selection policy, ignoring scoped overrides
+pub fn get_global_policy() -> BackendPolicy {
+    BackendPolicy::from_u8(CURRENT_POLICY.load(Ordering::Relaxed))
+        .unwrap_or(BackendPolicy::DEFAULT)
+}
+
+/// Reasons a policy change can be rejected
+#[derive(Debug, Clone, PartialEq)]
+pub enum PolicyError {
+    /// Numeric value outside the BackendPolicy enum
+    Unknown(u8),
+    /// Known policy whose backend is not compiled into this build
+    Unavailable(BackendPolicy),
+    /// pop_policy() without a matching push_policy()
+    EmptyStack,
+}
+
+impl std::fmt::Display for PolicyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PolicyError::Unknown(value) => write!(
+                f, "Unknown backend policy {} (expected 0=DEFAULT, 1=OPENBLAS, 2=BLAS, 3=CUDA)", value
+            ),
+            PolicyError::Unavailable(policy) => write!(
+                f, "Backend policy {:?} is not available: no usable '{}' backend in this build",
+                policy, policy.name()
+            ),
+            PolicyError::EmptyStack => write!(f, "Backend policy stack is empty (pop without push)"),
+        }
+    }
+}
+
+/// Probe for a usable CUDA device
+///
+/// Always false until CUDA kernels land; builds with the `cuda` feature are
+/// where the driver/device probe will go.
+pub fn cuda_available() -> bool {
+    #[cfg(feature = "cuda")]
+    {
+        // TODO: query the CUDA driver for a device once kernels exist
+        false
+    }
+    #[cfg(not(feature = "cuda"))]
+    {
+        false
+    }
+}
+
+/// Check whether a policy's backend can run in this process
+fn policy_available(policy: BackendPolicy) -> bool {
+    policy != BackendPolicy::CUDA || cuda_available()
 }
 
 /// Change the global backend selection policy
-pub fn set_policy(policy: BackendPolicy) {
+///
+/// Rejects policies whose backend is unavailable instead of silently
+/// falling back to CPU dispatch.
+pub fn set_policy(policy: BackendPolicy) -> Result<(), PolicyError> {
+    if !policy_available(policy) {
+        return Err(PolicyError::Unavailable(policy));
+    }
     CURRENT_POLICY.store(policy as u8, Ordering::Relaxed);
+    Ok(())
 }
 
-/// Record which backend was used (called from matmul implementations)
+/// Change the global backend selection policy from its numeric value
+pub fn set_policy_from_u8(value: u8) -> Result<(), PolicyError> {
+    let policy = BackendPolicy::from_u8(value).ok_or(PolicyError::Unknown(value))?;
+    set_policy(policy)
+}
+
+/// Temporarily override the effective policy until the matching pop_policy()
+pub fn push_policy(policy: BackendPolicy) -> Result<(), PolicyError> {
+    if !policy_available(policy) {
+        return Err(PolicyError::Unavailable(policy));
+    }
+    let mut stack = POLICY_STACK.lock();
+    stack.push(policy);
+    POLICY_STACK_DEPTH.store(stack.len(), Ordering::Release);
+    Ok(())
+}
+
+/// Remove the most recent override, returning it
+pub fn pop_policy() -> Result<BackendPolicy, PolicyError> {
+    let mut stack = POLICY_STACK.lock();
+    let policy = stack.pop().ok_or(PolicyError::EmptyStack)?;
+    POLICY_STACK_DEPTH.store(stack.len(), Ordering::Release);
+    Ok(policy)
+}
+
+/// Set the number of threads the BLAS backend may use (0 = all cores)
+///
+/// The value is cached here and applied lazily before the next BLAS dispatch.
+/// This only affects OpenBLAS; the Rayon pool used by the native path is sized
+/// independently (COREPY_NUM_THREADS or set_num_threads). Setting both
+/// lets callers split cores between corepy and their own process-level parallelism.
+pub fn set_blas_num_threads(num_threads: usize) {
+    BLAS_NUM_THREADS.store(num_threads, Ordering::Relaxed);
+    BLAS_NUM_THREADS_PENDING.store(true, Ordering::Release);
+}
+
+/// Get the configured BLAS thread count (0 = all cores)
+pub fn get_blas_num_threads() -> usize {
+    BLAS_NUM_THREADS.load(Ordering::Relaxed)
+}
+
+/// Take the pending BLAS thread count, if it changed since the last dispatch
+///
+/// Returns the effective count (0 resolved to the number of cores).
+pub fn take_pending_blas_num_threads() -> Option<usize> {
+    if !BLAS_NUM_THREADS_PENDING.swap(false, Ordering::Acquire) {
+        return None;
+    }
+    match get_blas_num_threads() {
+        0 => Some(num_cpus::get()),
+        n => Some(n),
+    }
+}
+
+/// Record which backend was used (called from op dispatch implementations)
 pub fn record_dispatch(backend_id: u8) {
     LAST_DISPATCH.store(backend_id, Ordering::Relaxed);
 }
 
+/// Record a native CPU dispatch of a 1D op (serial vs Rayon-parallel path)
+///
+/// `operation` should match the op's ProfileScope name.
+pub fn record_cpu_dispatch(operation: &str, count: usize, parallel: bool, start: Instant) {
+    let backend_id = if parallel { BACKEND_CPU_PARALLEL } else { BACKEND_CPU_SERIAL };
+    record_dispatch(backend_id);
+    record_elements_dispatch(backend_id, operation, count, get_policy(), start);
+}
+
+/// Estimate (flops, bytes) moved by an operation
+///
+/// matmul: 2*m*n*k flops over the A, B and C panels.
+/// Bandwidth-bound ops: one flop per element, dimensions multiply to the element count.
+fn work_estimate(operation: &str, m: usize, n: usize, k: usize) -> (f64, f64) {
+    let elem = std::mem::size_of::<f32>() as f64;
+    let (m, n, k) = (m as f64, n as f64, k as f64);
+    if operation.starts_with("matmul") {
+        // f16 matmul reads half-precision A and B but writes f32 C
+        let in_elem = if operation == "matmul_f16" { std::mem::size_of::<u16>() as f64 } else { elem };
+        (2.0 * m * n * k, (m * k + k * n) * in_elem + m * n * elem)
+    } else {
+        let count = m * n * k;
+        (count, count * elem)
+    }
+}
+
+/// Compute achieved (GFLOP/s, GB/s) for an operation that took `duration`
+pub fn compute_throughput(operation: &str, m: usize, n: usize, k: usize, duration: Duration) -> (f64, f64) {
+    let secs = duration.as_secs_f64();
+    if secs <= 0.0 {
+        return (0.0, 0.0);
+    }
+    let (flops, bytes) = work_estimate(operation, m, n, k);
+    (flops / secs / 1e9, bytes / secs / 1e9)
+}
+
 /// Record detailed dispatch metrics
+///
+/// `start` is taken by the caller before the kernel runs; completion time is now.
+/// `device` is where the operands live (always Cpu until device backends land).
+#[allow(clippy::too_many_arguments)]
 pub fn record_detailed_dispatch(
     backend_id: u8,
     operation: &str,
     m: usize, n: usize, k: usize,
-    policy: BackendPolicy
+    policy: BackendPolicy,
+    device: Device,
+    start: Instant,
+) {
+    store_dispatch(backend_id, operation, DispatchDims::Matrix(m, n, k), policy, device, start);
+}
+
+/// Record detailed dispatch metrics for a 1D op over `count` elements
+pub fn record_elements_dispatch(
+    backend_id: u8,
+    operation: &str,
+    count: usize,
+    policy: BackendPolicy,
+    start: Instant,
+) {
+    store_dispatch(backend_id, operation, DispatchDims::Elements(count), policy, Device::Cpu, start);
+}
+
+/// Threads a dispatch to `backend_id` from this thread runs on
+fn dispatch_threads(backend_id: u8) -> usize {
+    match backend_id {
+        BACKEND_NATIVE | BACKEND_CPU_PARALLEL => crate::scheduler::rayon_pool::effective_threads(),
+        BACKEND_OPENBLAS => match get_blas_num_threads() {
+            0 => num_cpus::get(),
+            n => n,
+        },
+        _ => 1,
+    }
+}
+
+fn store_dispatch(
+    backend_id: u8,
+    operation: &str,
+    dimensions: DispatchDims,
+    policy: BackendPolicy,
+    device: Device,
+    start: Instant,
+) {
+    let duration = start.elapsed();
+    let (m, n, k) = dimensions.as_mnk();
+    let (gflops, gb_per_s) = compute_throughput(operation, m, n, k, duration);
+
+    let info = DispatchInfo {
+        backend_id,
+        operation: operation.to_string(),
+        dimensions,
+        policy,
+        timestamp_us: now_micros(),
+        duration,
+        gflops,
+        gb_per_s,
+        attempted_backend: None,
+        num_threads: dispatch_threads(backend_id),
+        nested: crate::scheduler::rayon_pool::in_worker_thread(),
+        device,
+    };
+
+    report_scope_backend(backend_name(backend_id));
+    publish_dispatch(info);
+}
+
+/// Record a dispatch that failed because the requested backend is unavailable
+pub fn record_unavailable_dispatch(
+    backend_id: u8,
+    operation: &str,
+    m: usize, n: usize, k: usize,
+    policy: BackendPolicy,
 ) {
     let info = DispatchInfo {
         backend_id,
         operation: operation.to_string(),
-        dimensions: (m, n, k),
+        dimensions: DispatchDims::Matrix(m, n, k),
         policy,
-        timestamp: std::time::Instant::now(),
+        timestamp_us: now_micros(),
+        duration: Duration::ZERO,
+        gflops: 0.0,
+        gb_per_s: 0.0,
+        attempted_backend: Some(backend_id),
+        num_threads: 0,
+        nested: crate::scheduler::rayon_pool::in_worker_thread(),
+        device: Device::Cpu,
     };
 
-    if let Ok(mut guard) = LAST_DISPATCH_DETAILED.lock() {
-        *guard = Some(info);
+    publish_dispatch(info);
+}
+
+/// Store a finished dispatch record for this thread and queue it for the observer
+fn publish_dispatch(info: DispatchInfo) {
+    if DISPATCH_EVENTS_ENABLED.load(Ordering::Relaxed) {
+        if let Err(TrySendError::Full(_)) = DISPATCH_EVENTS.0.try_send(info.clone()) {
+            DISPATCH_EVENTS_DROPPED.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+    LAST_DISPATCH_DETAILED.with(|slot| *slot.borrow_mut() = Some(info));
+}
+
+/// Register (Some) or remove (None) the dispatch observer
+///
+/// Dispatches may run on worker threads or with the GIL released, so the
+/// callback is never called from the dispatch itself: events are buffered
+/// (up to DISPATCH_EVENT_CAPACITY) and delivered by drain_dispatch_events().
+/// Unregistering discards anything still queued.
+pub fn set_dispatch_callback(callback: Option<DispatchCallback>) {
+    let mut slot = DISPATCH_CALLBACK.lock();
+    DISPATCH_EVENTS_ENABLED.store(callback.is_some(), Ordering::Relaxed);
+    if callback.is_none() {
+        while DISPATCH_EVENTS.1.lock().try_recv().is_ok() {}
+        DISPATCH_EVENTS_DROPPED.store(0, Ordering::Relaxed);
+    }
+    *slot = callback;
+}
+
+/// Deliver queued dispatch events to the observer, returning how many were delivered
+pub fn drain_dispatch_events() -> usize {
+    let callback = match DISPATCH_CALLBACK.lock().clone() {
+        Some(cb) => cb,
+        None => return 0,
+    };
+    // Collect first so the callback runs without holding the queue lock
+    let events: Vec<DispatchInfo> = DISPATCH_EVENTS.1.lock().try_iter().collect();
+    for event in &events {
+        callback(event);
+    }
+    events.len()
+}
+
+/// Number of events dropped because the queue was full (resets the counter)
+pub fn take_dropped_dispatch_events() -> u64 {
+    DISPATCH_EVENTS_DROPPED.swap(0, Ordering::Relaxed)
+}
+
+/// Get a copy of the last detailed dispatch record made by the current thread, if any
+pub fn get_last_dispatch_info() -> Option<DispatchInfo> {
+    LAST_DISPATCH_DETAILED.with(|slot| slot.borrow().clone())
+}
+
+/// Human-readable name for a backend id
+pub fn backend_name(backend_id: u8) -> &'static str {
+    match backend_id {
+        0 => "Corepy AVX2",
+        1 => "OpenBLAS",
+        2 => "BLAS",
+        3 => "CUDA",
+        BACKEND_CPU_SERIAL => "CPU-serial",
+        BACKEND_CPU_PARALLEL => "CPU-parallel",
+        _ => "Unknown",
     }
 }
 
-/// Get description of last backend used (Simple string)
+/// Inverse of backend_name for the ids with a fixed name
+pub fn backend_id_from_name(name: &str) -> Option<u8> {
+    [BACKEND_NATIVE, BACKEND_OPENBLAS, 2, BACKEND_CUDA, BACKEND_CPU_SERIAL, BACKEND_CPU_PARALLEL]
+        .into_iter()
+        .find(|&id| backend_name(id) == name)
+}
+
+/// Get description of last backend used by the current thread (Simple string)
 pub fn get_last_dispatch() -> String {
     // Check detailed info first
-    if let Ok(guard) = LAST_DISPATCH_DETAILED.lock() {
-        if let Some(info) = &*guard {
-             let (m, n, k) = info.dimensions;
-             let elapsed = info.timestamp.elapsed();
-             return format!(
-                "{} → {} (size={}x{}x{}, policy={:?}, {}µs ago)",
+    if let Some(info) = get_last_dispatch_info() {
+        let elapsed = Duration::from_micros(now_micros().saturating_sub(info.timestamp_us));
+        if let Some(attempted) = info.attempted_backend {
+            return format!(
+                "{} → {} unavailable ({}, policy={:?}, dispatch failed {}µs ago)",
                 info.operation,
-                match info.backend_id {
-                    0 => "Corepy AVX2",
-                    1 => "OpenBLAS",
-                    2 => "BLAS",
-                    3 => "CUDA",
-                    _ => "Unknown",
-                },
-                m, n, k,
+                backend_name(attempted),
+                info.dimensions,
                 info.policy,
                 elapsed.as_micros()
             );
         }
+        return format!(
+            "{} → {} ({}, policy={:?}, {} thread(s), {:.2} GFLOPS, {:.2} GB/s, took {}µs, {}µs ago)",
+            info.operation,
+            backend_name(info.backend_id),
+            info.dimensions,
+            info.policy,
+            info.num_threads,
+            info.gflops,
+            info.gb_per_s,
+            info.duration.as_micros(),
+            elapsed.as_micros()
+        );
     }
 
     // Fallback to simple atomic tracking
@@ -104,6 +682,350 @@ pub fn get_last_dispatch() -> String {
         1 => "OpenBLAS backend".to_string(),
         2 => "BLAS backend".to_string(),
         3 => "CUDA backend".to_string(),
+        BACKEND_CPU_SERIAL => "CPU-serial backend".to_string(),
+        BACKEND_CPU_PARALLEL => "CPU-parallel backend".to_string(),
         id => format!("Unknown backend ({})", id),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_names() {
+        for name in VALID_POLICY_NAMES {
+            let policy = BackendPolicy::from_name(name).expect("valid name");
+            assert_eq!(BackendPolicy::from_name(policy.name()), Some(policy));
+        }
+        assert_eq!(BackendPolicy::from_name("Auto"), Some(BackendPolicy::DEFAULT));
+        assert_eq!(BackendPolicy::from_name("OpenBLAS"), Some(BackendPolicy::OPENBLAS));
+        assert_eq!(BackendPolicy::from_name("openbals"), None);
+    }
+
+    #[test]
+    fn test_policy_from_u8() {
+        for value in 0..=3u8 {
+            assert_eq!(BackendPolicy::from_u8(value).map(|p| p as u8), Some(value));
+        }
+        assert_eq!(BackendPolicy::from_u8(4), None);
+    }
+
+    #[test]
+    fn test_set_policy_rejects_invalid_values() {
+        for value in [4u8, 7, 42, u8::MAX] {
+            assert_eq!(set_policy_from_u8(value), Err(PolicyError::Unknown(value)));
+        }
+    }
+
+    /// Holds TEST_STATE_LOCK and puts the global policy and BLAS thread count
+    /// back when dropped, so a failing assert can't leak them into other tests
+    struct RestoreGlobals {
+        policy: BackendPolicy,
+        blas_threads: usize,
+        _lock: std::sync::MutexGuard<'static, ()>,
+    }
+
+    impl RestoreGlobals {
+        fn take() -> Self {
+            let lock = crate::scheduler::TEST_STATE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            RestoreGlobals { policy: get_global_policy(), blas_threads: get_blas_num_threads(), _lock: lock }
+        }
+    }
+
+    impl Drop for RestoreGlobals {
+        fn drop(&mut self) {
+            // Restoring a policy that was accepted before can't fail
+            let _ = set_policy(self.policy);
+            set_blas_num_threads(self.blas_threads);
+        }
+    }
+
+    #[test]
+    fn test_set_policy_rejects_cuda_when_unavailable() {
+        let _restore = RestoreGlobals::take();
+        assert!(!cuda_available());
+        let before = get_policy();
+        assert_eq!(set_policy(BackendPolicy::CUDA), Err(PolicyError::Unavailable(BackendPolicy::CUDA)));
+        assert_eq!(get_policy(), before);
+    }
+
+    #[test]
+    fn test_matmul_throughput() {
+        // 2*100*100*100 = 2e6 flops in 1ms -> 2 GFLOPS
+        let (gflops, gb_per_s) = compute_throughput("matmul", 100, 100, 100, Duration::from_millis(1));
+        assert!((gflops - 2.0).abs() < 1e-9);
+        // 3 * 100*100 * 4 bytes = 120 KB in 1ms -> 0.12 GB/s
+        assert!((gb_per_s - 0.12).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_f16_matmul_throughput_counts_half_inputs() {
+        let (gflops, gb_per_s) = compute_throughput("matmul_f16", 100, 100, 100, Duration::from_millis(1));
+        assert!((gflops - 2.0).abs() < 1e-9);
+        // 2 * 100*100 * 2 bytes + 100*100 * 4 bytes = 80 KB in 1ms -> 0.08 GB/s
+        assert!((gb_per_s - 0.08).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_zero_duration_throughput() {
+        assert_eq!(compute_throughput("matmul", 10, 10, 10, Duration::ZERO), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_cpu_dispatch_records_element_count() {
+        record_cpu_dispatch("sum", 1000, false, Instant::now());
+        let info = get_last_dispatch_info().unwrap();
+        assert_eq!(info.backend_id, BACKEND_CPU_SERIAL);
+        assert_eq!(info.dimensions, DispatchDims::Elements(1000));
+        assert!(get_last_dispatch().starts_with("sum → CPU-serial (elements=1000"));
+
+        record_cpu_dispatch("sum", 1000, true, Instant::now());
+        assert_eq!(get_last_dispatch_info().unwrap().backend_id, BACKEND_CPU_PARALLEL);
+    }
+
+    #[test]
+    fn test_profile_scope_records_dispatched_backend() {
+        use crate::profiler::{ProfileScope, Profiler};
+
+        let profiler = Profiler::new();
+        profiler.enable();
+        let scope = |operation: &str| ProfileScope::new(profiler.clone(), operation.to_string(), "CPU".to_string(), 1);
+
+        {
+            let _scope = scope("sum");
+            record_cpu_dispatch("sum", 10, false, Instant::now());
+        }
+        {
+            let _scope = scope("sum");
+            record_cpu_dispatch("sum", 10_000_000, true, Instant::now());
+        }
+        {
+            let _scope = scope("matmul");
+            record_detailed_dispatch(BACKEND_OPENBLAS, "matmul", 512, 512, 512, BackendPolicy::BLAS, Device::Cpu, Instant::now());
+        }
+        {
+            let _scope = scope("matmul");
+            record_detailed_dispatch(BACKEND_NATIVE, "matmul", 8, 8, 8, BackendPolicy::DEFAULT, Device::Cpu, Instant::now());
+        }
+
+        let backends: Vec<String> = profiler.get_events().into_iter().map(|e| e.backend).collect();
+        assert_eq!(backends, ["CPU-serial", "CPU-parallel", "OpenBLAS", "Corepy AVX2"]);
+    }
+
+    #[test]
+    fn test_dispatch_callback_receives_queued_events() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        set_dispatch_callback(Some(Arc::new(move |info: &DispatchInfo| {
+            // Other tests may dispatch concurrently; keep only ours
+            if info.operation.starts_with("callback_test") {
+                sink.lock().push((info.operation.clone(), info.dimensions));
+            }
+        })));
+
+        record_cpu_dispatch("callback_test_a", 10, false, Instant::now());
+        record_detailed_dispatch(0, "callback_test_b", 2, 3, 4, BackendPolicy::DEFAULT, Device::Cpu, Instant::now());
+        assert!(seen.lock().is_empty()); // Nothing delivered until drained
+
+        drain_dispatch_events();
+        assert_eq!(*seen.lock(), vec![
+            ("callback_test_a".to_string(), DispatchDims::Elements(10)),
+            ("callback_test_b".to_string(), DispatchDims::Matrix(2, 3, 4)),
+        ]);
+
+        set_dispatch_callback(None);
+        record_cpu_dispatch("callback_test_c", 10, false, Instant::now());
+        assert_eq!(drain_dispatch_events(), 0);
+        assert_eq!(seen.lock().len(), 2);
+    }
+
+    #[test]
+    fn test_dispatch_info_serde_round_trip() {
+        let before = now_micros();
+        record_detailed_dispatch(1, "matmul", 4, 5, 6, BackendPolicy::OPENBLAS, Device::Cuda(1), Instant::now());
+        let info = get_last_dispatch_info().unwrap();
+        assert!(info.timestamp_us >= before && info.timestamp_us <= now_micros());
+
+        let json = serde_json::to_string(&info).unwrap();
+        let back: DispatchInfo = serde_json::from_str(&json).unwrap();
+        // serde_json's default float parsing may be one ulp off, so compare
+        // the throughput figures approximately and everything else exactly
+        let close = |a: f64, b: f64| (a - b).abs() <= 1e-12 * a.abs().max(1.0);
+        assert!(close(back.gflops, info.gflops) && close(back.gb_per_s, info.gb_per_s));
+        assert_eq!(DispatchInfo { gflops: info.gflops, gb_per_s: info.gb_per_s, ..back }, info);
+
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["timestamp_us"].as_u64(), Some(info.timestamp_us));
+        assert_eq!(value["policy"], "OPENBLAS");
+        assert_eq!(back.device.to_string(), "cuda:1");
+    }
+
+    #[test]
+    fn test_last_dispatch_is_per_thread() {
+        let handles: Vec<_> = (1..=4usize)
+            .map(|i| {
+                std::thread::spawn(move || {
+                    let size = i * 16;
+                    for _ in 0..100 {
+                        record_detailed_dispatch(0, "matmul", size, size, size, BackendPolicy::DEFAULT, Device::Cpu, Instant::now());
+                        let info = get_last_dispatch_info().expect("recorded on this thread");
+                        assert_eq!(info.dimensions, DispatchDims::Matrix(size, size, size));
+                    }
+                    get_last_dispatch()
+                })
+            })
+            .collect();
+
+        for (i, handle) in (1..=4usize).zip(handles) {
+            let size = i * 16;
+            let explanation = handle.join().unwrap();
+            assert!(explanation.contains(&format!("size={}x{}x{}", size, size, size)));
+        }
+    }
+
+    #[test]
+    fn test_operation_policy_override() {
+        let base = get_policy();
+        set_operation_policy("override_test_op", Some(BackendPolicy::CUDA));
+        assert_eq!(get_policy_for("override_test_op"), BackendPolicy::CUDA);
+        assert_eq!(get_policy_for("other_op"), base);
+
+        set_operation_policy("override_test_op", None);
+        assert_eq!(get_policy_for("override_test_op"), base);
+    }
+
+    #[test]
+    fn test_unavailable_dispatch_is_recorded() {
+        record_unavailable_dispatch(3, "matmul", 8, 8, 8, BackendPolicy::CUDA);
+        let info = get_last_dispatch_info().unwrap();
+        assert_eq!(info.attempted_backend, Some(3));
+        assert!(get_last_dispatch().contains("CUDA unavailable"));
+
+        record_detailed_dispatch(0, "matmul", 8, 8, 8, BackendPolicy::DEFAULT, Device::Cpu, Instant::now());
+        assert_eq!(get_last_dispatch_info().unwrap().attempted_backend, None);
+    }
+
+    #[test]
+    fn test_policy_stack_nesting() {
+        // The stack is process-wide; other tests dispatch under its top
+        let _guard = crate::scheduler::TEST_STATE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let base = get_global_policy();
+        assert_eq!(get_policy(), base);
+
+        push_policy(BackendPolicy::BLAS).unwrap();
+        assert_eq!(get_policy(), BackendPolicy::BLAS);
+        push_policy(BackendPolicy::OPENBLAS).unwrap();
+        assert_eq!(get_policy(), BackendPolicy::OPENBLAS);
+        assert_eq!(POLICY_STACK_DEPTH.load(Ordering::Acquire), 2);
+
+        assert_eq!(pop_policy(), Ok(BackendPolicy::OPENBLAS));
+        assert_eq!(get_policy(), BackendPolicy::BLAS);
+        assert_eq!(pop_policy(), Ok(BackendPolicy::BLAS));
+        assert_eq!(get_policy(), base);
+
+        assert_eq!(pop_policy(), Err(PolicyError::EmptyStack));
+        assert_eq!(POLICY_STACK_DEPTH.load(Ordering::Acquire), 0);
+    }
+
+    /// Mock backend that counts calls and writes a marker value into C
+    struct MockBackend {
+        id: u8,
+        max_dim: usize,
+        calls: AtomicUsize,
+    }
+
+    impl Backend for MockBackend {
+        fn id(&self) -> u8 { self.id }
+
+        fn name(&self) -> &'static str { "Mock" }
+
+        fn supports(&self, operation: &str, dims: DispatchDims) -> bool {
+            let (m, n, k) = dims.as_mnk();
+            operation == "matmul" && m.max(n).max(k) <= self.max_dim
+        }
+
+        unsafe fn matmul_f32(
+            &self,
+            _a: *const f32, _b: *const f32, c: *mut f32,
+            m: usize, _k: usize, n: usize,
+            _accumulate: bool,
+        ) {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            std::slice::from_raw_parts_mut(c, m * n).fill(42.0);
+        }
+    }
+
+    #[test]
+    fn test_registered_backend_is_selected() {
+        // An id no built-in backend uses, so other tests are unaffected
+        let mock = Arc::new(MockBackend { id: 200, max_dim: 64, calls: AtomicUsize::new(0) });
+        register_backend(mock.clone());
+        assert!(registered_backends().iter().any(|b| b.name() == "Mock"));
+
+        let backend = select_backend(200, "matmul", DispatchDims::Matrix(2, 2, 2)).expect("mock selected");
+        let mut c = [0f32; 4];
+        unsafe { backend.matmul_f32([1.0; 4].as_ptr(), [1.0; 4].as_ptr(), c.as_mut_ptr(), 2, 2, 2, false) };
+        assert_eq!(c, [42.0; 4]);
+        assert_eq!(mock.calls.load(Ordering::Relaxed), 1);
+
+        // Unsupported op or size: not selected
+        assert!(select_backend(200, "sum", DispatchDims::Elements(4)).is_none());
+        assert!(select_backend(200, "matmul", DispatchDims::Matrix(65, 1, 1)).is_none());
+
+        // A later registration with the same id takes precedence
+        let newer = Arc::new(MockBackend { id: 200, max_dim: 1024, calls: AtomicUsize::new(0) });
+        register_backend(newer.clone());
+        let backend = select_backend(200, "matmul", DispatchDims::Matrix(2, 2, 2)).unwrap();
+        unsafe { backend.matmul_f32(std::ptr::null(), std::ptr::null(), c.as_mut_ptr(), 2, 2, 2, false) };
+        assert_eq!(newer.calls.load(Ordering::Relaxed), 1);
+        assert_eq!(mock.calls.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_matmul_threshold() {
+        // The threshold is process-wide; other tests dispatch matmuls
+        let _guard = crate::scheduler::TEST_STATE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        assert!(set_dispatch_threshold("sum", 10).is_err());
+        assert_eq!(get_dispatch_threshold("sum"), None);
+
+        set_dispatch_threshold("matmul", 100).unwrap();
+        assert_eq!(get_dispatch_threshold("matmul"), Some(100));
+        assert!(!matmul_prefers_blas(100, 100, 100));
+        assert!(matmul_prefers_blas(101, 10, 10));
+
+        set_dispatch_threshold("matmul", 0).unwrap();
+        assert!(matmul_prefers_blas(1, 1, 1));
+
+        set_dispatch_threshold("matmul", usize::MAX).unwrap();
+        assert!(!matmul_prefers_blas(usize::MAX, usize::MAX, usize::MAX));
+
+        set_dispatch_threshold("matmul", DEFAULT_MATMUL_THRESHOLD).unwrap();
+    }
+
+    #[test]
+    fn test_blas_num_threads_pending() {
+        let _restore = RestoreGlobals::take();
+        set_blas_num_threads(3);
+        assert_eq!(get_blas_num_threads(), 3);
+        assert_eq!(take_pending_blas_num_threads(), Some(3));
+        // Applied once; nothing pending until the next change
+        assert_eq!(take_pending_blas_num_threads(), None);
+
+        set_blas_num_threads(0);
+        assert_eq!(take_pending_blas_num_threads(), Some(num_cpus::get()));
+    }
+
+    #[test]
+    fn test_record_detailed_dispatch_populates_stats() {
+        let start = Instant::now() - Duration::from_millis(2);
+        record_detailed_dispatch(0, "matmul", 64, 64, 64, BackendPolicy::DEFAULT, Device::Cpu, start);
+
+        let info = get_last_dispatch_info().expect("dispatch recorded");
+        assert_eq!(info.device.to_string(), "cpu");
+        assert!(info.duration >= Duration::from_millis(2));
+        assert!(info.gflops > 0.0);
+        assert!(info.gb_per_s > 0.0);
+        assert!(get_last_dispatch().contains("GFLOPS"));
+    }
+}
@@ -0,0 +1,175 @@
+// ============================================================================
+// Buffer Protocol
+// ============================================================================
+//
+// RESPONSIBILITIES:
+// - Acquire the memory of any buffer-protocol exporter (NumPy arrays,
+//   array.array, memoryview, bytes, ...) without copying
+// - Hold the acquired view until the tensor registered over it is released
+//
+// DESIGN:
+// - PyO3 only offers PyBuffer under abi3 from Python 3.11 on, and this crate
+//   targets abi3-py39, so the two CPython calls are bound directly; both (and
+//   the Py_buffer layout) have been stable since Python 3.3
+// - Windows abi3 builds link python3.dll, which only exports them from 3.11
+//   on, so there they are looked up at runtime in the interpreter's own
+//   python3X.dll (sys.dllhandle) instead of linked
+// - Requested with PyBUF_RECORDS_RO: shape, strides and format, writable or
+//   not. Exporters that need suboffsets (PIL-style indirect arrays) refuse
+// - Releasing takes the GIL, so a BufferView can be dropped from any thread
+
+use pyo3::prelude::*;
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int, c_void};
+
+/// CPython's Py_buffer
+#[repr(C)]
+struct RawBuffer {
+    buf: *mut c_void,
+    obj: *mut pyo3::ffi::PyObject,
+    len: isize,
+    itemsize: isize,
+    readonly: c_int,
+    ndim: c_int,
+    format: *mut c_char,
+    shape: *mut isize,
+    strides: *mut isize,
+    suboffsets: *mut isize,
+    internal: *mut c_void,
+}
+
+const PYBUF_FORMAT: c_int = 0x0004;
+const PYBUF_STRIDES: c_int = 0x0010 | 0x0008;
+const PYBUF_RECORDS_RO: c_int = PYBUF_STRIDES | PYBUF_FORMAT;
+
+type GetBufferFn = unsafe extern "C" fn(*mut pyo3::ffi::PyObject, *mut RawBuffer, c_int) -> c_int;
+type ReleaseBufferFn = unsafe extern "C" fn(*mut RawBuffer);
+
+#[cfg(not(windows))]
+mod capi {
+    use super::*;
+
+    extern "C" {
+        fn PyObject_GetBuffer(obj: *mut pyo3::ffi::PyObject, view: *mut RawBuffer, flags: c_int) -> c_int;
+        fn PyBuffer_Release(view: *mut RawBuffer);
+    }
+
+    pub fn functions(_py: Python) -> PyResult<(GetBufferFn, ReleaseBufferFn)> {
+        Ok((PyObject_GetBuffer, PyBuffer_Release))
+    }
+}
+
+#[cfg(windows)]
+mod capi {
+    use super::*;
+    use std::sync::OnceLock;
+
+    extern "system" {
+        fn GetProcAddress(module: isize, name: *const c_char) -> *mut c_void;
+    }
+
+    static FUNCTIONS: OnceLock<(GetBufferFn, ReleaseBufferFn)> = OnceLock::new();
+
+    fn lookup(module: isize, name: &CStr) -> PyResult<*mut c_void> {
+        let address = unsafe { GetProcAddress(module, name.as_ptr()) };
+        if address.is_null() {
+            return Err(pyo3::exceptions::PyImportError::new_err(format!(
+                "{} not found in the Python DLL",
+                name.to_string_lossy()
+            )));
+        }
+        Ok(address)
+    }
+
+    pub fn functions(py: Python) -> PyResult<(GetBufferFn, ReleaseBufferFn)> {
+        if let Some(functions) = FUNCTIONS.get() {
+            return Ok(*functions);
+        }
+        let module: isize = py.import("sys")?.getattr("dllhandle")?.extract()?;
+        let get = lookup(module, c"PyObject_GetBuffer")?;
+        let release = lookup(module, c"PyBuffer_Release")?;
+        // Both are the documented CPython signatures
+        let get = unsafe { std::mem::transmute::<*mut c_void, GetBufferFn>(get) };
+        let release = unsafe { std::mem::transmute::<*mut c_void, ReleaseBufferFn>(release) };
+        Ok(*FUNCTIONS.get_or_init(|| (get, release)))
+    }
+}
+
+/// An acquired buffer; keeps the exporter alive and its memory in place
+pub struct BufferView {
+    // Boxed so the Py_buffer never moves while acquired
+    raw: Box<RawBuffer>,
+    release: ReleaseBufferFn,
+}
+
+// The exporter's memory is only released, under the GIL, on drop
+unsafe impl Send for BufferView {}
+unsafe impl Sync for BufferView {}
+
+impl BufferView {
+    /// Acquire `obj`'s buffer with shape, strides and format
+    pub fn acquire(obj: &PyAny) -> PyResult<Self> {
+        let mut raw = Box::new(RawBuffer {
+            buf: std::ptr::null_mut(),
+            obj: std::ptr::null_mut(),
+            len: 0,
+            itemsize: 0,
+            readonly: 0,
+            ndim: 0,
+            format: std::ptr::null_mut(),
+            shape: std::ptr::null_mut(),
+            strides: std::ptr::null_mut(),
+            suboffsets: std::ptr::null_mut(),
+            internal: std::ptr::null_mut(),
+        });
+        let (get_buffer, release) = capi::functions(obj.py())?;
+        if unsafe { get_buffer(obj.as_ptr(), &mut *raw, PYBUF_RECORDS_RO) } != 0 {
+            return Err(PyErr::fetch(obj.py()));
+        }
+        Ok(BufferView { raw, release })
+    }
+
+    /// Address of the first element
+    pub fn ptr(&self) -> usize {
+        self.raw.buf as usize
+    }
+
+    pub fn readonly(&self) -> bool {
+        self.raw.readonly != 0
+    }
+
+    pub fn itemsize(&self) -> usize {
+        self.raw.itemsize as usize
+    }
+
+    /// `struct` format of one element; exporters may leave it unset for
+    /// plain bytes
+    pub fn format(&self) -> &str {
+        if self.raw.format.is_null() {
+            return "B";
+        }
+        unsafe { CStr::from_ptr(self.raw.format) }.to_str().unwrap_or("")
+    }
+
+    pub fn shape(&self) -> Vec<usize> {
+        self.dims(self.raw.shape).iter().map(|&dim| dim as usize).collect()
+    }
+
+    /// Byte strides, one per dimension
+    pub fn strides(&self) -> Vec<isize> {
+        self.dims(self.raw.strides).to_vec()
+    }
+
+    fn dims(&self, values: *const isize) -> &[isize] {
+        match self.raw.ndim as usize {
+            0 => &[],
+            ndim => unsafe { std::slice::from_raw_parts(values, ndim) },
+        }
+    }
+}
+
+impl Drop for BufferView {
+    fn drop(&mut self) {
+        Python::with_gil(|_| unsafe { (self.release)(&mut *self.raw) });
+    }
+}
@@ -0,0 +1,162 @@
+// ============================================================================
+// FFI: Errors Raised to Python
+// ============================================================================
+//
+// RESPONSIBILITIES:
+// - One error type (FfiError) that runtime errors convert into
+// - Map each FfiError to its exception class in ffi/python.rs: CorepyError
+//   and its subclasses ShapeError, DTypeError, BackendError,
+//   AllocationError and CancelledError
+//
+// DESIGN:
+// - Entry points return Result<_, FfiError> pieces and let `?` convert: the
+//   runtime's error enums classify themselves here, not at each call site
+// - The message is the runtime error's Display; the class carries the kind,
+//   so Python code can catch by class instead of matching strings
+
+use pyo3::PyErr;
+
+use super::python::{AllocationError, BackendError, CancelledError, CorepyError, DTypeError, ShapeError};
+use crate::backend::PolicyError;
+use crate::ops::registry::KernelError;
+use crate::tensor::buffer::BufferError;
+use crate::tensor::registry::TensorError;
+
+/// A runtime error on its way to Python, by exception class
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FfiError {
+    /// A null data pointer passed to the named entry point (CorepyError)
+    NullPointer(String),
+    /// Any other invalid argument (CorepyError)
+    Invalid(String),
+    /// Shapes or sizes that don't fit the op (ShapeError)
+    Shape(String),
+    /// An unknown dtype, or one the op has no kernel for (DTypeError)
+    DType(String),
+    /// A backend that is unknown, unavailable or on the wrong device
+    /// (BackendError)
+    Backend(String),
+    /// Memory that could not be allocated, found or released
+    /// (AllocationError)
+    Allocation(String),
+    /// Stopped early by request_cancel() (CancelledError)
+    Cancelled(String),
+}
+
+impl std::fmt::Display for FfiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FfiError::NullPointer(fn_name) => write!(f, "Null pointer passed to {}", fn_name),
+            FfiError::Invalid(message)
+            | FfiError::Shape(message)
+            | FfiError::DType(message)
+            | FfiError::Backend(message)
+            | FfiError::Allocation(message)
+            | FfiError::Cancelled(message) => f.write_str(message),
+        }
+    }
+}
+
+impl From<FfiError> for PyErr {
+    fn from(err: FfiError) -> PyErr {
+        let message = err.to_string();
+        match err {
+            FfiError::NullPointer(_) | FfiError::Invalid(_) => CorepyError::new_err(message),
+            FfiError::Shape(_) => ShapeError::new_err(message),
+            FfiError::DType(_) => DTypeError::new_err(message),
+            FfiError::Backend(_) => BackendError::new_err(message),
+            FfiError::Allocation(_) => AllocationError::new_err(message),
+            FfiError::Cancelled(_) => CancelledError::new_err(message),
+        }
+    }
+}
+
+impl From<crate::tensor::shape::ShapeError> for FfiError {
+    fn from(err: crate::tensor::shape::ShapeError) -> Self {
+        FfiError::Shape(err.to_string())
+    }
+}
+
+impl From<PolicyError> for FfiError {
+    fn from(err: PolicyError) -> Self {
+        FfiError::Backend(err.to_string())
+    }
+}
+
+impl From<KernelError> for FfiError {
+    fn from(err: KernelError) -> Self {
+        match err {
+            KernelError::UnknownOp(_) => FfiError::Invalid(err.to_string()),
+            KernelError::UnknownDtype(_) | KernelError::Unsupported { .. } => FfiError::DType(err.to_string()),
+        }
+    }
+}
+
+impl From<BufferError> for FfiError {
+    fn from(err: BufferError) -> Self {
+        match err {
+            BufferError::UnknownDtype(_) => FfiError::DType(err.to_string()),
+            _ => FfiError::Allocation(err.to_string()),
+        }
+    }
+}
+
+impl From<crate::scheduler::buffer_pool::PoolError> for FfiError {
+    fn from(err: crate::scheduler::buffer_pool::PoolError) -> Self {
+        use crate::scheduler::buffer_pool::PoolError;
+        match err {
+            PoolError::UnknownDtype(_) => FfiError::DType(err.to_string()),
+            _ => FfiError::Allocation(err.to_string()),
+        }
+    }
+}
+
+impl From<TensorError> for FfiError {
+    fn from(err: TensorError) -> Self {
+        match err {
+            TensorError::Alloc(err) => err.into(),
+            TensorError::UnknownDtype(_) | TensorError::DtypeMismatch { .. } => FfiError::DType(err.to_string()),
+            TensorError::Shape(_)
+            | TensorError::ShapeMismatch { .. }
+            | TensorError::NotMatrix { .. }
+            | TensorError::TooFewDims { .. }
+            | TensorError::SliceRank { .. }
+            | TensorError::SliceOutOfBounds { .. }
+            | TensorError::NotContiguous { .. }
+            | TensorError::ReshapeSize { .. } => FfiError::Shape(err.to_string()),
+            TensorError::DeviceMismatch { .. } => FfiError::Backend(err.to_string()),
+            TensorError::NullPointer
+            | TensorError::InvalidHandle(_)
+            | TensorError::StaleHandle(_)
+            | TensorError::StaleHandleTraced { .. }
+            | TensorError::ZeroStep { .. }
+            | TensorError::ReadOnly { .. }
+            | TensorError::BadRange(..) => FfiError::Invalid(err.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tensor::Device;
+
+    #[test]
+    fn test_runtime_errors_pick_their_class() {
+        let shape = TensorError::NotMatrix { op: "matmul", shape: vec![3] };
+        assert_eq!(FfiError::from(shape.clone()), FfiError::Shape(shape.to_string()));
+
+        let device = TensorError::DeviceMismatch { op: "add", left: Device::Cpu, right: Device::Cuda(0) };
+        assert!(matches!(FfiError::from(device), FfiError::Backend(_)));
+        assert!(matches!(FfiError::from(TensorError::UnknownDtype(200)), FfiError::DType(_)));
+        assert!(matches!(FfiError::from(TensorError::InvalidHandle(7)), FfiError::Invalid(_)));
+
+        let alloc = TensorError::Alloc(BufferError::TooLarge(usize::MAX));
+        assert!(matches!(FfiError::from(alloc), FfiError::Allocation(_)));
+        assert!(matches!(FfiError::from(BufferError::UnknownDtype("complex64".into())), FfiError::DType(_)));
+        assert!(matches!(FfiError::from(KernelError::UnknownOp(99)), FfiError::Invalid(_)));
+        assert!(matches!(FfiError::from(KernelError::UnknownDtype(99)), FfiError::DType(_)));
+
+        assert_eq!(FfiError::NullPointer("tensor_triu_f32".into()).to_string(), "Null pointer passed to tensor_triu_f32");
+    }
+}
@@ -1,2 +1,4 @@
 // FFI module exports
+pub mod buffer_protocol;
+pub mod errors;
 pub mod python;
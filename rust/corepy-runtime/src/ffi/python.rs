@@ -5,30 +5,199 @@
 
 use pyo3::prelude::*;
 
+use super::errors::FfiError;
+use crate::tensor::dtype::DType;
+
 // Global profiler instance for this module (and the process)
 lazy_static::lazy_static! {
     static ref GLOBAL_PROFILER: crate::profiler::Profiler = crate::profiler::Profiler::new();
 }
 
+// Exceptions raised by the runtime (see ffi/errors.rs for which error maps
+// to which). CorepyError subclasses ValueError, which these errors all used
+// to be, so `except ValueError` keeps working
+pyo3::create_exception!(
+    _corepy_rust,
+    CorepyError,
+    pyo3::exceptions::PyValueError,
+    "Base class of every error raised by the corepy runtime"
+);
+pyo3::create_exception!(_corepy_rust, ShapeError, CorepyError, "Operand shapes or sizes that don't fit the op");
+pyo3::create_exception!(_corepy_rust, DTypeError, CorepyError, "An unknown dtype, or one the op has no kernel for");
+pyo3::create_exception!(
+    _corepy_rust,
+    BackendError,
+    CorepyError,
+    "A backend policy that is unknown or can't run here, or operands on different devices"
+);
+pyo3::create_exception!(
+    _corepy_rust,
+    AllocationError,
+    CorepyError,
+    "Memory that could not be allocated, found or released"
+);
+pyo3::create_exception!(_corepy_rust, CancelledError, CorepyError, "An operation stopped early by request_cancel()");
+
+/// Add the exception classes to `m`. create_exception! takes one base, so
+/// the classes that replaced NotImplementedError / RuntimeError get that
+/// builtin as a second base here
+fn register_exceptions(m: &PyModule) -> PyResult<()> {
+    let py = m.py();
+    let corepy = py.get_type::<CorepyError>();
+    py.get_type::<BackendError>()
+        .setattr("__bases__", (corepy, py.get_type::<pyo3::exceptions::PyNotImplementedError>()))?;
+    py.get_type::<CancelledError>()
+        .setattr("__bases__", (corepy, py.get_type::<pyo3::exceptions::PyRuntimeError>()))?;
+
+    m.add("CorepyError", corepy)?;
+    m.add("ShapeError", py.get_type::<ShapeError>())?;
+    m.add("DTypeError", py.get_type::<DTypeError>())?;
+    m.add("BackendError", py.get_type::<BackendError>())?;
+    m.add("AllocationError", py.get_type::<AllocationError>())?;
+    m.add("CancelledError", py.get_type::<CancelledError>())?;
+    // Name before the hierarchy existed
+    m.add("CorepyCancelled", py.get_type::<CancelledError>())?;
+    Ok(())
+}
+
 /// Export all FFI functions to Python
 pub fn register_functions(m: &PyModule) -> PyResult<()> {
+    register_exceptions(m)?;
+
     // Reduction operations
     m.add_function(wrap_pyfunction!(tensor_all, m)?)?;
     m.add_function(wrap_pyfunction!(tensor_any, m)?)?;
     m.add_function(wrap_pyfunction!(tensor_sum_f32, m)?)?;
     m.add_function(wrap_pyfunction!(tensor_sum_i32, m)?)?;
     m.add_function(wrap_pyfunction!(tensor_mean_f32, m)?)?;
+    m.add_function(wrap_pyfunction!(tensor_reduce, m)?)?;
+    m.add_function(wrap_pyfunction!(list_kernels, m)?)?;
     m.add_function(wrap_pyfunction!(tensor_matmul_f32, m)?)?;
     m.add_function(wrap_pyfunction!(tensor_matmul_2d_f32, m)?)?;
+    m.add_function(wrap_pyfunction!(tensor_matmul_2d_f32_acc, m)?)?;
     m.add_function(wrap_pyfunction!(tensor_dot_product_f32, m)?)?;
+    m.add_function(wrap_pyfunction!(tensor_matmul_2d_f16, m)?)?;
+    
+    // Linear algebra
+    m.add_function(wrap_pyfunction!(tensor_triu_f32, m)?)?;
+    m.add_function(wrap_pyfunction!(tensor_tril_f32, m)?)?;
+    m.add_function(wrap_pyfunction!(tensor_frobenius_norm_f32, m)?)?;
+    m.add_function(wrap_pyfunction!(tensor_row_norms_f32, m)?)?;
+    m.add_function(wrap_pyfunction!(tensor_eye_f32, m)?)?;
+    m.add_function(wrap_pyfunction!(tensor_diag_f32, m)?)?;
+    
+    // Asynchronous submission
+    m.add_function(wrap_pyfunction!(submit_matmul_2d_f32, m)?)?;
+    m.add_function(wrap_pyfunction!(submit_sum_f32, m)?)?;
+    m.add_function(wrap_pyfunction!(wait, m)?)?;
+    m.add_function(wrap_pyfunction!(is_ready, m)?)?;
+    m.add_function(wrap_pyfunction!(get_result_f32, m)?)?;
+    m.add_function(wrap_pyfunction!(release_handle, m)?)?;
+    
+    // Dtype conversion
+    m.add_function(wrap_pyfunction!(tensor_cast_f16_to_f32, m)?)?;
+    m.add_function(wrap_pyfunction!(tensor_cast_f32_to_f16, m)?)?;
+
+    // Batched operations
+    m.add_function(wrap_pyfunction!(execute_batch, m)?)?;
     
     // Backend control
     m.add_function(wrap_pyfunction!(set_backend_policy, m)?)?;
     m.add_function(wrap_pyfunction!(get_backend_policy, m)?)?;
+    m.add_function(wrap_pyfunction!(set_backend_policy_name, m)?)?;
+    m.add_function(wrap_pyfunction!(get_backend_policy_name, m)?)?;
+    m.add_function(wrap_pyfunction!(push_backend_policy, m)?)?;
+    m.add_function(wrap_pyfunction!(pop_backend_policy, m)?)?;
+    m.add_function(wrap_pyfunction!(set_operation_policy, m)?)?;
+    m.add_function(wrap_pyfunction!(cuda_available, m)?)?;
+    m.add_function(wrap_pyfunction!(list_backends, m)?)?;
+    m.add_function(wrap_pyfunction!(export_backend_config, m)?)?;
+    m.add_function(wrap_pyfunction!(build_dispatch_table_from_report, m)?)?;
+    m.add_function(wrap_pyfunction!(get_dispatch_table, m)?)?;
+    m.add_function(wrap_pyfunction!(clear_dispatch_table, m)?)?;
+    m.add_function(wrap_pyfunction!(import_backend_config, m)?)?;
+    m.add_function(wrap_pyfunction!(enable_auto_tune, m)?)?;
+    m.add_function(wrap_pyfunction!(disable_auto_tune, m)?)?;
+    m.add_function(wrap_pyfunction!(reset_auto_tune, m)?)?;
+    m.add_function(wrap_pyfunction!(get_auto_tune_table, m)?)?;
     m.add_function(wrap_pyfunction!(explain_last_dispatch, m)?)?;
+    m.add_function(wrap_pyfunction!(get_last_dispatch_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(get_last_dispatch_json, m)?)?;
+    m.add_function(wrap_pyfunction!(set_dispatch_callback, m)?)?;
+    m.add_function(wrap_pyfunction!(drain_dispatch_events, m)?)?;
+    m.add_function(wrap_pyfunction!(take_dropped_dispatch_events, m)?)?;
+    m.add_function(wrap_pyfunction!(set_backend_num_threads, m)?)?;
+    m.add_function(wrap_pyfunction!(get_backend_num_threads, m)?)?;
+    m.add_function(wrap_pyfunction!(set_num_threads, m)?)?;
+    m.add_function(wrap_pyfunction!(get_num_threads, m)?)?;
+    m.add_function(wrap_pyfunction!(set_arena_size, m)?)?;
+    m.add_function(wrap_pyfunction!(initialize_runtime, m)?)?;
+    m.add_function(wrap_pyfunction!(get_chunking_info, m)?)?;
+    m.add_function(wrap_pyfunction!(get_numa_info, m)?)?;
+    m.add_function(wrap_pyfunction!(get_scheduler_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(reset_scheduler_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(get_arena_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(reset_arena_peak, m)?)?;
+    m.add_function(wrap_pyfunction!(acquire_buffer, m)?)?;
+    m.add_function(wrap_pyfunction!(release_buffer, m)?)?;
+    m.add_function(wrap_pyfunction!(buffer_ptr, m)?)?;
+    m.add_function(wrap_pyfunction!(pool_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(alloc_buffer, m)?)?;
+    m.add_function(wrap_pyfunction!(free_buffer, m)?)?;
+    m.add_function(wrap_pyfunction!(buffer_address, m)?)?;
+    m.add_function(wrap_pyfunction!(np_add_f32, m)?)?;
+    m.add_function(wrap_pyfunction!(np_sum_f32, m)?)?;
+    m.add_function(wrap_pyfunction!(np_matmul_f32, m)?)?;
+    m.add_function(wrap_pyfunction!(tensor_add_buffer, m)?)?;
+    m.add_function(wrap_pyfunction!(tensor_sum_buffer, m)?)?;
+    m.add_function(wrap_pyfunction!(buffer_is_pinned, m)?)?;
+    m.add_function(wrap_pyfunction!(set_pinned_limit, m)?)?;
+    m.add_function(wrap_pyfunction!(pinned_memory_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(dtype_code, m)?)?;
+    m.add_function(wrap_pyfunction!(check_alignment, m)?)?;
+    m.add_function(wrap_pyfunction!(register_tensor, m)?)?;
+    m.add_function(wrap_pyfunction!(unregister_tensor, m)?)?;
+    m.add_function(wrap_pyfunction!(tensor_info, m)?)?;
+    m.add_function(wrap_pyfunction!(list_tensors, m)?)?;
+    m.add_function(wrap_pyfunction!(total_registered_bytes, m)?)?;
+    m.add_function(wrap_pyfunction!(set_handle_debug, m)?)?;
+    m.add_function(wrap_pyfunction!(tensor_from_buffer, m)?)?;
+    m.add_function(wrap_pyfunction!(tensor_release, m)?)?;
+    m.add_function(wrap_pyfunction!(tensor_view, m)?)?;
+    m.add_function(wrap_pyfunction!(tensor_zeros, m)?)?;
+    m.add_function(wrap_pyfunction!(tensor_ones, m)?)?;
+    m.add_function(wrap_pyfunction!(tensor_arange, m)?)?;
+    m.add_function(wrap_pyfunction!(tensor_linspace, m)?)?;
+    m.add_function(wrap_pyfunction!(tensor_random_uniform, m)?)?;
+    m.add_function(wrap_pyfunction!(tensor_random_normal, m)?)?;
+    m.add_function(wrap_pyfunction!(tensor_random_fill, m)?)?;
+    m.add_function(wrap_pyfunction!(save_npy, m)?)?;
+    m.add_function(wrap_pyfunction!(load_npy, m)?)?;
+    m.add_function(wrap_pyfunction!(save_safetensors, m)?)?;
+    m.add_function(wrap_pyfunction!(load_safetensors, m)?)?;
+    m.add_function(wrap_pyfunction!(tensor_reshape, m)?)?;
+    m.add_function(wrap_pyfunction!(tensor_transpose_view, m)?)?;
+    m.add_function(wrap_pyfunction!(check_contiguity, m)?)?;
+    m.add_function(wrap_pyfunction!(op_add, m)?)?;
+    m.add_function(wrap_pyfunction!(op_sub, m)?)?;
+    m.add_function(wrap_pyfunction!(op_mul, m)?)?;
+    m.add_function(wrap_pyfunction!(op_div, m)?)?;
+    m.add_function(wrap_pyfunction!(op_matmul, m)?)?;
+    m.add_function(wrap_pyfunction!(op_reduce, m)?)?;
+    m.add_function(wrap_pyfunction!(set_progress_callback, m)?)?;
+    m.add_function(wrap_pyfunction!(get_operation_progress, m)?)?;
+    m.add_function(wrap_pyfunction!(request_cancel, m)?)?;
+    m.add_function(wrap_pyfunction!(clear_cancel, m)?)?;
+    m.add_function(wrap_pyfunction!(set_thread_affinity, m)?)?;
+    m.add_function(wrap_pyfunction!(get_thread_affinity, m)?)?;
+    m.add_function(wrap_pyfunction!(get_backend_capabilities, m)?)?;
+    m.add_function(wrap_pyfunction!(set_dispatch_threshold, m)?)?;
+    m.add_function(wrap_pyfunction!(get_dispatch_threshold, m)?)?;
     
     // Element-wise operations
     m.add_function(wrap_pyfunction!(tensor_add_f32, m)?)?;
+    m.add_function(wrap_pyfunction!(tensor_binary, m)?)?;
+    m.add_function(wrap_pyfunction!(tensor_broadcast_binary_f32, m)?)?;
     m.add_function(wrap_pyfunction!(tensor_sub_f32, m)?)?;
     m.add_function(wrap_pyfunction!(tensor_mul_f32, m)?)?;
     m.add_function(wrap_pyfunction!(tensor_div_f32, m)?)?;
@@ -38,7 +207,33 @@ pub fn register_functions(m: &PyModule) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(disable_profiling, m)?)?;
     m.add_function(wrap_pyfunction!(clear_profile, m)?)?;
     m.add_function(wrap_pyfunction!(get_profile_report, m)?)?;
+    m.add_function(wrap_pyfunction!(get_profile_report_dict, m)?)?;
+    m.add_function(wrap_pyfunction!(print_profile_summary, m)?)?;
+    m.add_function(wrap_pyfunction!(export_profile_events_binary, m)?)?;
+    m.add_function(wrap_pyfunction!(import_profile_events_binary, m)?)?;
+    m.add_function(wrap_pyfunction!(get_chrome_trace, m)?)?;
+    m.add_function(wrap_pyfunction!(merge_profile_reports, m)?)?;
+    m.add_function(wrap_pyfunction!(save_profile_report, m)?)?;
+    m.add_function(wrap_pyfunction!(drain_profile_events, m)?)?;
+    m.add_function(wrap_pyfunction!(set_profile_capacity, m)?)?;
+    m.add_function(wrap_pyfunction!(get_profile_capacity, m)?)?;
+    m.add_function(wrap_pyfunction!(set_profile_sample_rate, m)?)?;
+    m.add_function(wrap_pyfunction!(get_profile_sample_rate, m)?)?;
+    m.add_function(wrap_pyfunction!(set_profile_warmup, m)?)?;
+    m.add_function(wrap_pyfunction!(get_profile_warmup, m)?)?;
     m.add_function(wrap_pyfunction!(set_profile_context, m)?)?;
+    m.add_function(wrap_pyfunction!(set_call_site, m)?)?;
+    m.add_function(wrap_pyfunction!(set_active_profiler, m)?)?;
+    m.add_function(wrap_pyfunction!(get_active_profiler, m)?)?;
+    m.add_function(wrap_pyfunction!(flush_profile_on_exit, m)?)?;
+    m.add_function(wrap_pyfunction!(profile_marker, m)?)?;
+    m.add_function(wrap_pyfunction!(enable_profile_spill, m)?)?;
+    m.add_function(wrap_pyfunction!(disable_profile_spill, m)?)?;
+    m.add_function(wrap_pyfunction!(start_profile_session, m)?)?;
+    m.add_function(wrap_pyfunction!(end_profile_session, m)?)?;
+    m.add_function(wrap_pyfunction!(get_profile_session_report, m)?)?;
+    m.add_function(wrap_pyfunction!(list_profile_sessions, m)?)?;
+    m.add_function(wrap_pyfunction!(set_profile_session_limit, m)?)?;
     
     // Demo functions (backward compatibility)
     m.add_function(wrap_pyfunction!(sum_as_string, m)?)?;
@@ -50,362 +245,1710 @@ pub fn register_functions(m: &PyModule) -> PyResult<()> {
 // Profiling Control
 // ============================================================================
 
+/// The named profiler (created on first use), or the global one for None
+///
+/// Every profiling control function takes this as its `profiler` argument.
+fn profiler_for(name: Option<&str>) -> crate::profiler::Profiler {
+    match name {
+        Some(name) => crate::profiler::get_or_create_profiler(name),
+        None => GLOBAL_PROFILER.clone(),
+    }
+}
+
 #[pyfunction]
-fn enable_profiling() -> PyResult<()> {
-    GLOBAL_PROFILER.enable();
+#[pyo3(signature = (profiler=None))]
+fn enable_profiling(profiler: Option<String>) -> PyResult<()> {
+    profiler_for(profiler.as_deref()).enable();
     Ok(())
 }
 
 #[pyfunction]
-fn disable_profiling() -> PyResult<()> {
-    GLOBAL_PROFILER.disable();
+#[pyo3(signature = (profiler=None))]
+fn disable_profiling(profiler: Option<String>) -> PyResult<()> {
+    profiler_for(profiler.as_deref()).disable();
     Ok(())
 }
 
 #[pyfunction]
-fn clear_profile() -> PyResult<()> {
-    GLOBAL_PROFILER.clear();
+#[pyo3(signature = (profiler=None))]
+fn clear_profile(profiler: Option<String>) -> PyResult<()> {
+    profiler_for(profiler.as_deref()).clear();
     Ok(())
 }
 
+/// Also record every op into the named profiler (created if needed); None detaches
+///
+/// Ops always record into the global profiler; the attached one gets a copy
+/// of each event while it is enabled.
 #[pyfunction]
-fn get_profile_report(context: Option<String>) -> PyResult<String> {
-    GLOBAL_PROFILER.export_json(context.as_deref())
-        .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e))
+#[pyo3(signature = (name=None))]
+fn set_active_profiler(name: Option<String>) -> PyResult<()> {
+    crate::profiler::set_active_profiler(name.as_deref());
+    Ok(())
 }
 
 #[pyfunction]
-fn set_profile_context(context: Option<String>) -> PyResult<()> {
-    crate::profiler::set_context(context);
+fn get_active_profiler() -> PyResult<Option<String>> {
+    Ok(crate::profiler::active_profiler_name())
+}
+
+/// Cap the number of retained profile events; beyond it the oldest are dropped
+/// (counted in the report's metadata.dropped_events)
+#[pyfunction]
+#[pyo3(signature = (max_events, profiler=None))]
+fn set_profile_capacity(max_events: usize, profiler: Option<String>) -> PyResult<()> {
+    profiler_for(profiler.as_deref()).set_capacity(max_events)
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+#[pyfunction]
+#[pyo3(signature = (profiler=None))]
+fn get_profile_capacity(profiler: Option<String>) -> PyResult<usize> {
+    Ok(profiler_for(profiler.as_deref()).capacity())
+}
+
+/// Record only every Nth call per operation (per thread); reports scale counts
+/// and totals back up and mark them `estimated`. 1 (the default) is exact.
+#[pyfunction]
+#[pyo3(signature = (rate, profiler=None))]
+fn set_profile_sample_rate(rate: u32, profiler: Option<String>) -> PyResult<()> {
+    profiler_for(profiler.as_deref()).set_sample_rate(rate)
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+#[pyfunction]
+#[pyo3(signature = (profiler=None))]
+fn get_profile_sample_rate(profiler: Option<String>) -> PyResult<u32> {
+    Ok(profiler_for(profiler.as_deref()).sample_rate())
+}
+
+/// Flag the first `n` calls of each operation (counted since the last clear)
+/// as warmup: reports leave them out of the timings and count them under
+/// warmup_count / warmup_total_ms. 0 (the default) turns it off.
+#[pyfunction]
+#[pyo3(signature = (n, profiler=None))]
+fn set_profile_warmup(n: usize, profiler: Option<String>) -> PyResult<()> {
+    profiler_for(profiler.as_deref()).set_warmup_skip(n);
     Ok(())
 }
 
-// ============================================================================
-// Reduction Operations
-// ============================================================================
+#[pyfunction]
+#[pyo3(signature = (profiler=None))]
+fn get_profile_warmup(profiler: Option<String>) -> PyResult<usize> {
+    Ok(profiler_for(profiler.as_deref()).warmup_skip())
+}
 
+/// Profiling report as JSON
+///
+/// `operations` restricts the report to those operation names;
+/// `include_events` attaches the raw per-call events.
 #[pyfunction]
-fn tensor_all(data_ptr: usize, count: usize) -> PyResult<bool> {
-    use crate::ops::reduce::all_bool_cpu_dispatch;
-    
-    if data_ptr == 0 {
-        return Err(pyo3::exceptions::PyValueError::new_err("Null pointer passed to tensor_all"));
+#[pyo3(signature = (context=None, include_events=false, operations=None, profiler=None))]
+fn get_profile_report(
+    context: Option<String>,
+    include_events: bool,
+    operations: Option<Vec<String>>,
+    profiler: Option<String>,
+) -> PyResult<String> {
+    profiler_for(profiler.as_deref()).export_json(context.as_deref(), operations.as_deref(), include_events)
+        .map_err(pyo3::exceptions::PyRuntimeError::new_err)
+}
+
+/// Aligned text table of the profiled operations (returned, not printed)
+///
+/// `sort_by` is one of total, calls, avg, p99, percent, name; `top_n` = 0
+/// shows every operation.
+#[pyfunction]
+#[pyo3(signature = (sort_by="total", top_n=20, context=None, profiler=None))]
+fn print_profile_summary(
+    sort_by: &str,
+    top_n: usize,
+    context: Option<String>,
+    profiler: Option<String>,
+) -> PyResult<String> {
+    use crate::profiler::metrics::{format_text_report, ReportSort, VALID_SORT_NAMES};
+
+    let sort_by = ReportSort::from_name(sort_by).ok_or_else(|| {
+        pyo3::exceptions::PyValueError::new_err(format!(
+            "unknown sort column '{}' (expected one of: {})", sort_by, VALID_SORT_NAMES.join(", ")
+        ))
+    })?;
+    let report = profiler_for(profiler.as_deref()).generate_report(context.as_deref(), None);
+    Ok(format_text_report(&report, sort_by, top_n))
+}
+
+/// Raw profile events as compact bytes ("msgpack" or "bincode"), for saving
+/// large sessions; load them back with import_profile_events_binary
+#[pyfunction]
+#[pyo3(signature = (format="msgpack", profiler=None))]
+fn export_profile_events_binary(py: Python, format: &str, profiler: Option<String>) -> PyResult<PyObject> {
+    use crate::profiler::binary::{BinaryFormat, VALID_BINARY_FORMATS};
+
+    let format = BinaryFormat::from_name(format).ok_or_else(|| {
+        pyo3::exceptions::PyValueError::new_err(format!(
+            "unknown binary format '{}' (expected one of: {})", format, VALID_BINARY_FORMATS.join(", ")
+        ))
+    })?;
+    let profiler = profiler_for(profiler.as_deref());
+    let bytes = py.allow_threads(|| profiler.export_events_binary(format))
+        .map_err(pyo3::exceptions::PyRuntimeError::new_err)?;
+    Ok(pyo3::types::PyBytes::new(py, &bytes).into())
+}
+
+/// Append events from export_profile_events_binary() to the profiler (so
+/// reports can be rebuilt offline); returns how many were loaded
+///
+/// Raises ValueError for data that is not a binary export of a supported version.
+#[pyfunction]
+#[pyo3(signature = (data, profiler=None))]
+fn import_profile_events_binary(py: Python, data: &[u8], profiler: Option<String>) -> PyResult<usize> {
+    let profiler = profiler_for(profiler.as_deref());
+    py.allow_threads(|| profiler.import_events_binary(data))
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Profiling report as Python objects (same shape as json.loads(get_profile_report()))
+///
+/// Skips the JSON text round trip; non-finite floats become None, as in the
+/// JSON report.
+#[pyfunction]
+#[pyo3(signature = (context=None, include_events=false, operations=None, profiler=None))]
+fn get_profile_report_dict(
+    py: Python,
+    context: Option<String>,
+    include_events: bool,
+    operations: Option<Vec<String>>,
+    profiler: Option<String>,
+) -> PyResult<PyObject> {
+    let report = profiler_for(profiler.as_deref())
+        .report_with_events(context.as_deref(), operations.as_deref(), include_events);
+    let value = serde_json::to_value(&report)
+        .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("report conversion failed: {}", e)))?;
+    json_to_py(py, &value)
+}
+
+/// Convert a JSON value into the objects json.loads() would build
+fn json_to_py(py: Python, value: &serde_json::Value) -> PyResult<PyObject> {
+    use serde_json::Value;
+
+    Ok(match value {
+        Value::Null => py.None(),
+        Value::Bool(b) => b.into_py(py),
+        Value::Number(n) => match (n.as_u64(), n.as_i64()) {
+            (Some(u), _) => u.into_py(py),
+            (None, Some(i)) => i.into_py(py),
+            _ => n.as_f64().into_py(py),
+        },
+        Value::String(s) => s.into_py(py),
+        Value::Array(items) => {
+            let list = pyo3::types::PyList::empty(py);
+            for item in items {
+                list.append(json_to_py(py, item)?)?;
+            }
+            list.into()
+        }
+        Value::Object(map) => {
+            let dict = pyo3::types::PyDict::new(py);
+            for (key, item) in map {
+                dict.set_item(key, json_to_py(py, item)?)?;
+            }
+            dict.into()
+        }
+    })
+}
+
+/// Write the profile straight to `path` without building the string in Python
+///
+/// `format` is "json" (the report) or "chrome_trace"; the GIL is released
+/// while writing.
+#[pyfunction]
+#[pyo3(signature = (path, context=None, format="json", create_dirs=false, profiler=None))]
+fn save_profile_report(
+    py: Python,
+    path: std::path::PathBuf,
+    context: Option<String>,
+    format: &str,
+    create_dirs: bool,
+    profiler: Option<String>,
+) -> PyResult<()> {
+    let context = context.as_deref();
+    let profiler = profiler_for(profiler.as_deref());
+    let result = match format {
+        "json" => py.allow_threads(|| profiler.export_json_to_file(&path, context, create_dirs)),
+        "chrome_trace" => py.allow_threads(|| profiler.export_chrome_trace_to_file(&path, context, create_dirs)),
+        other => {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "unknown profile format '{}' (expected one of: json, chrome_trace)", other
+            )));
+        }
+    };
+    result.map_err(|e| pyo3::exceptions::PyIOError::new_err(format!("{}: {}", path.display(), e)))
+}
+
+fn profile_event_to_dict<'py>(py: Python<'py>, event: &crate::profiler::metrics::OperationEvent) -> PyResult<&'py pyo3::types::PyDict> {
+    let dict = pyo3::types::PyDict::new(py);
+    dict.set_item("seq", event.seq)?;
+    dict.set_item("operation", &event.operation)?;
+    dict.set_item("backend", &event.backend)?;
+    dict.set_item("data_size", event.data_size)?;
+    dict.set_item("start_time_us", event.start_time_us)?;
+    dict.set_item("end_time_us", event.end_time_us)?;
+    dict.set_item("duration_us", event.duration_us())?;
+    dict.set_item("context", &event.context)?;
+    dict.set_item("dims", event.dims)?;
+    dict.set_item("thread_id", event.thread_id)?;
+    dict.set_item("thread_name", &event.thread_name)?;
+    dict.set_item("sample_weight", event.sample_weight)?;
+    dict.set_item("arena_bytes_used", event.arena_bytes_used)?;
+    dict.set_item("heap_bytes_allocated", event.heap_bytes_allocated)?;
+    dict.set_item("kind", if event.is_marker() { "marker" } else { "operation" })?;
+    dict.set_item("metadata", &event.metadata)?;
+    dict.set_item("warmup", event.warmup)?;
+    dict.set_item("gil_held_us", event.gil_held_us)?;
+    dict.set_item("gil_released_us", event.gil_released_us)?;
+    dict.set_item("session", event.session)?;
+    let attributes = pyo3::types::PyDict::new(py);
+    for (key, value) in &event.attributes {
+        attributes.set_item(key, value)?;
     }
-    
-    if count == 0 {
-        return Ok(true);
+    dict.set_item("attributes", attributes)?;
+    match &event.call_site {
+        Some(site) => dict.set_item("call_site", (&site.file, site.line, &site.function))?,
+        None => dict.set_item("call_site", py.None())?,
     }
-    
-    // PROFILING
-    let _scope = crate::profiler::ProfileScope::new(
-        GLOBAL_PROFILER.clone(),
-        "all".to_string(),
-        "CPU".to_string(),
-        count,
-    );
-    
-    let result = unsafe {
-        all_bool_cpu_dispatch(data_ptr as *const u8, count)
-    };
-    
-    Ok(result)
+    Ok(dict)
 }
 
+/// Remove and return up to `max` of the oldest profile events as dicts
+///
+/// For live consumers: each event is returned exactly once; a jump in `seq`
+/// means events were dropped by the capacity limit in between.
 #[pyfunction]
-fn tensor_any(data_ptr: usize, count: usize) -> PyResult<bool> {
-    use crate::ops::reduce::any_bool_cpu_dispatch;
-    
-    if data_ptr == 0 {
-        return Err(pyo3::exceptions::PyValueError::new_err("Null pointer passed to tensor_any"));
+#[pyo3(signature = (max, profiler=None))]
+fn drain_profile_events(py: Python, max: usize, profiler: Option<String>) -> PyResult<PyObject> {
+    let events = profiler_for(profiler.as_deref()).drain_events(max);
+    let list = pyo3::types::PyList::empty(py);
+    for event in &events {
+        list.append(profile_event_to_dict(py, event)?)?;
     }
-    
-    if count == 0 {
-        return Ok(false);
+    Ok(list.into())
+}
+
+/// Merge report JSONs (e.g. one per worker process) into a single report JSON
+#[pyfunction]
+fn merge_profile_reports(jsons: Vec<String>) -> PyResult<String> {
+    use crate::profiler::metrics::ProfileReport;
+
+    if jsons.is_empty() {
+        return Err(pyo3::exceptions::PyValueError::new_err("merge_profile_reports needs at least one report"));
     }
-    
-    // PROFILING
-    let _scope = crate::profiler::ProfileScope::new(
-        GLOBAL_PROFILER.clone(),
-        "any".to_string(),
-        "CPU".to_string(),
-        count,
-    );
-    
-    let result = unsafe {
-        any_bool_cpu_dispatch(data_ptr as *const u8, count)
-    };
-    
-    Ok(result)
+    let reports = jsons
+        .iter()
+        .enumerate()
+        .map(|(i, json)| serde_json::from_str::<ProfileReport>(json)
+            .map_err(|e| pyo3::exceptions::PyValueError::new_err(format!("report[{}]: invalid profile report: {}", i, e))))
+        .collect::<PyResult<Vec<_>>>()?;
+
+    ProfileReport::merge(&reports)
+        .to_json()
+        .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("JSON serialization failed: {}", e)))
 }
 
+/// Recorded events in Chrome Trace Event Format (load in chrome://tracing or Perfetto)
 #[pyfunction]
-fn tensor_sum_f32(data_ptr: usize, count: usize) -> PyResult<f32> {
-    use crate::ops::reduce::sum_f32_cpu_dispatch;
-    
-    if data_ptr == 0 {
-        return Err(pyo3::exceptions::PyValueError::new_err("Null pointer passed to tensor_sum_f32"));
+#[pyo3(signature = (context=None, profiler=None))]
+fn get_chrome_trace(context: Option<String>, profiler: Option<String>) -> PyResult<String> {
+    profiler_for(profiler.as_deref()).export_chrome_trace(context.as_deref())
+        .map_err(pyo3::exceptions::PyRuntimeError::new_err)
+}
+
+/// Record an instant marker (e.g. "epoch 3 start") in the profile timeline
+#[pyfunction]
+#[pyo3(signature = (name, metadata=None, profiler=None))]
+fn profile_marker(name: &str, metadata: Option<String>, profiler: Option<String>) -> PyResult<()> {
+    profiler_for(profiler.as_deref()).record_marker(name, metadata);
+    Ok(())
+}
+
+/// Spill buffered events to `path` (newline-delimited JSON) every
+/// `threshold_events` events; reports still cover everything recorded
+#[pyfunction]
+#[pyo3(signature = (path, threshold_events, profiler=None))]
+fn enable_profile_spill(path: std::path::PathBuf, threshold_events: usize, profiler: Option<String>) -> PyResult<()> {
+    profiler_for(profiler.as_deref())
+        .enable_spill(&path, threshold_events)
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::InvalidInput => pyo3::exceptions::PyValueError::new_err(e.to_string()),
+            _ => pyo3::exceptions::PyIOError::new_err(format!("{}: {}", path.display(), e)),
+        })
+}
+
+#[pyfunction]
+#[pyo3(signature = (profiler=None))]
+fn disable_profile_spill(profiler: Option<String>) -> PyResult<()> {
+    profiler_for(profiler.as_deref()).disable_spill();
+    Ok(())
+}
+
+/// Start a named profiling session and return its id; events recorded until
+/// end_profile_session() are tagged with it
+#[pyfunction]
+#[pyo3(signature = (name, profiler=None))]
+fn start_profile_session(name: &str, profiler: Option<String>) -> PyResult<u64> {
+    profiler_for(profiler.as_deref()).start_session(name)
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// End the active session; returns its report as JSON (also archived for
+/// get_profile_session_report)
+#[pyfunction]
+#[pyo3(signature = (session_id, profiler=None))]
+fn end_profile_session(session_id: u64, profiler: Option<String>) -> PyResult<String> {
+    let report = profiler_for(profiler.as_deref()).end_session(session_id)
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    report.to_json().map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
+}
+
+/// Report of an archived (or the active) session as JSON; None if unknown
+#[pyfunction]
+#[pyo3(signature = (session_id, profiler=None))]
+fn get_profile_session_report(session_id: u64, profiler: Option<String>) -> PyResult<Option<String>> {
+    profiler_for(profiler.as_deref())
+        .get_session_report(session_id)
+        .map(|report| report.to_json())
+        .transpose()
+        .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
+}
+
+/// Archived sessions (oldest first) and the active one, as dicts
+#[pyfunction]
+#[pyo3(signature = (profiler=None))]
+fn list_profile_sessions(py: Python, profiler: Option<String>) -> PyResult<Vec<PyObject>> {
+    profiler_for(profiler.as_deref())
+        .list_sessions()
+        .into_iter()
+        .map(|info| {
+            let dict = pyo3::types::PyDict::new(py);
+            dict.set_item("id", info.id)?;
+            dict.set_item("name", info.name)?;
+            dict.set_item("start_timestamp", info.start_timestamp)?;
+            dict.set_item("active", info.active)?;
+            Ok(dict.into())
+        })
+        .collect()
+}
+
+/// Keep at most `max_sessions` finished sessions (default 16)
+#[pyfunction]
+#[pyo3(signature = (max_sessions, profiler=None))]
+fn set_profile_session_limit(max_sessions: usize, profiler: Option<String>) -> PyResult<()> {
+    profiler_for(profiler.as_deref()).set_max_sessions(max_sessions)
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Write the JSON report to `path` when the interpreter exits (atexit);
+/// a later call replaces the path
+#[pyfunction]
+#[pyo3(signature = (path, profiler=None))]
+fn flush_profile_on_exit(py: Python, path: std::path::PathBuf, profiler: Option<String>) -> PyResult<()> {
+    crate::profiler::set_exit_report(&profiler_for(profiler.as_deref()), path);
+    register_exit_flush(py)
+}
+
+/// Apply COREPY_PROFILE / COREPY_PROFILE_CONTEXT / COREPY_PROFILE_OUTPUT to
+/// the global profiler (called once at module import)
+pub fn init_profiling_from_env(py: Python) -> PyResult<()> {
+    let config = crate::profiler::EnvProfileConfig::from_env();
+    if let Some(path) = config.apply(&GLOBAL_PROFILER) {
+        crate::profiler::set_exit_report(&GLOBAL_PROFILER, path.to_path_buf());
+        register_exit_flush(py)?;
     }
-    
-    if count == 0 {
-        return Ok(0.0);
+    Ok(())
+}
+
+/// Register the atexit hook that writes the exit report (once per process)
+fn register_exit_flush(py: Python) -> PyResult<()> {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    static REGISTERED: AtomicBool = AtomicBool::new(false);
+
+    if REGISTERED.load(Ordering::Acquire) {
+        return Ok(());
     }
-    
-    // PROFILING
-    let _scope = crate::profiler::ProfileScope::new(
-        GLOBAL_PROFILER.clone(),
-        "sum".to_string(),
-        "CPU".to_string(),
-        count,
-    );
-    
-    let result = unsafe {
-        sum_f32_cpu_dispatch(data_ptr as *const f32, count)
-    };
-    
-    Ok(result)
+    let hook = wrap_pyfunction!(write_profile_at_exit, py)?;
+    py.import("atexit")?.call_method1("register", (hook,))?;
+    REGISTERED.store(true, Ordering::Release);
+    Ok(())
 }
 
 #[pyfunction]
-fn tensor_sum_i32(data_ptr: usize, count: usize) -> PyResult<i32> {
-    use crate::ops::reduce::sum_i32_cpu_dispatch;
-    
+fn write_profile_at_exit(py: Python) -> PyResult<()> {
+    py.allow_threads(crate::profiler::write_exit_report)
+        .map(drop)
+        .map_err(|e| pyo3::exceptions::PyIOError::new_err(e.to_string()))
+}
+
+#[pyfunction]
+fn set_profile_context(context: Option<String>) -> PyResult<()> {
+    crate::profiler::set_context(context);
+    Ok(())
+}
+
+/// Stamp this thread's following operations with a Python call site
+///
+/// Called by the tensor layer only while call-site tracking is on; `file=None`
+/// clears it.
+#[pyfunction]
+#[pyo3(signature = (file=None, line=0, function=String::new()))]
+fn set_call_site(file: Option<String>, line: u32, function: String) -> PyResult<()> {
+    crate::profiler::set_call_site(file.map(|file| crate::profiler::metrics::CallSite { file, line, function }));
+    Ok(())
+}
+
+// ============================================================================
+// Reduction Operations
+// ============================================================================
+
+/// Run the registered reduction for (`op`, `dtype`); `fn_name` names the
+/// calling entry point in errors
+#[allow(clippy::too_many_arguments)]
+fn reduce_impl(
+    py: Python,
+    fn_name: &str,
+    data_ptr: usize, count: usize,
+    op_code: u8, dtype_code: u8,
+    max_threads: Option<usize>,
+    timeout_ms: Option<u64>
+) -> PyResult<crate::ops::registry::Scalar> {
+    use crate::scheduler::cancel::with_deadline;
+
+    let (op, kernel) = crate::ops::registry::kernels().reduce(op_code, dtype_code).map_err(kernel_error_to_py)?;
     if data_ptr == 0 {
-        return Err(pyo3::exceptions::PyValueError::new_err("Null pointer passed to tensor_sum_i32"));
+        return Err(FfiError::NullPointer(fn_name.to_string()).into());
     }
-    
+    let dtype = DType::from_code(dtype_code).expect("registered kernels have known dtypes");
+    check_pointers(fn_name, op.name(), dtype, &[("data_ptr", data_ptr)])?;
+    check_extent(fn_name, count, dtype.size(), &["data_ptr"])?;
+    check_max_threads(max_threads)?;
+    let deadline = deadline_after(timeout_ms);
+
     if count == 0 {
-        return Ok(0);
+        return kernel.empty.ok_or_else(|| {
+            FfiError::Shape(format!("Cannot compute {} of empty tensor", op.name())).into()
+        });
     }
-    
+
     // PROFILING
-    let _scope = crate::profiler::ProfileScope::new(
+    let mut scope = crate::profiler::ProfileScope::new(
         GLOBAL_PROFILER.clone(),
-        "sum".to_string(),
+        op.name().to_string(),
         "CPU".to_string(),
         count,
     );
-    
-    let result = unsafe {
-        sum_i32_cpu_dispatch(data_ptr as *const i32, count)
-    };
-    
-    Ok(result)
+    scope.add_metadata("dtype", dtype.name());
+
+    let result = scope.gil_released(|| allow_threads_with_progress(py, || unsafe {
+        with_deadline(deadline, || (kernel.run)(data_ptr as *const u8, count, max_threads))
+    }));
+
+    result.map_err(|reason| cancelled_to_py(py, reason))
 }
 
-#[pyfunction]
-fn tensor_mean_f32(data_ptr: usize, count: usize) -> PyResult<f32> {
-    use crate::ops::reduce::mean_f32_cpu_dispatch;
-    
-    if data_ptr == 0 {
-        return Err(pyo3::exceptions::PyValueError::new_err("Null pointer passed to tensor_mean_f32"));
+fn kernel_error_to_py(err: crate::ops::registry::KernelError) -> PyErr {
+    FfiError::from(err).into()
+}
+
+fn scalar_to_py(py: Python, value: crate::ops::registry::Scalar) -> PyObject {
+    use crate::ops::registry::Scalar;
+    match value {
+        Scalar::F32(value) => value.into_py(py),
+        Scalar::I32(value) => value.into_py(py),
+        Scalar::Bool(value) => value.into_py(py),
     }
-    
-    if count == 0 {
-        return Err(pyo3::exceptions::PyValueError::new_err("Cannot compute mean of empty tensor"));
+}
+
+/// Code of a reduction given by name ("sum", "mean", "all", "any") or by
+/// code (0 sum, 1 mean, 2 all, 3 any)
+fn reduce_op_code(op: &PyAny) -> PyResult<u8> {
+    use crate::ops::registry::ReduceOp;
+    if let Ok(name) = op.downcast::<pyo3::types::PyString>() {
+        let name = name.to_str()?;
+        return ReduceOp::from_name(name).map(|op| op as u8).ok_or_else(|| {
+            FfiError::Invalid(format!("unknown reduction '{}' (expected sum, mean, all or any)", name)).into()
+        });
     }
-    
-    // PROFILING
-    let _scope = crate::profiler::ProfileScope::new(
-        GLOBAL_PROFILER.clone(),
-        "mean".to_string(),
-        "CPU".to_string(),
-        count,
-    );
-    
-    let result = unsafe {
-        mean_f32_cpu_dispatch(data_ptr as *const f32, count)
-    };
-    
-    Ok(result)
+    op.extract()
+}
+
+/// Reduce `count` elements with any registered (op, dtype) kernel, `op` by
+/// name or code (see reduce_op_code) and `dtype_code` as in dtype_code().
+/// Returns an int for integer sums, a bool for all/any and a float otherwise
+#[pyfunction]
+#[pyo3(signature = (data_ptr, count, op, dtype_code, max_threads=None, timeout_ms=None))]
+fn tensor_reduce(
+    py: Python,
+    data_ptr: usize, count: usize,
+    op: &PyAny, dtype_code: u8,
+    max_threads: Option<usize>,
+    timeout_ms: Option<u64>
+) -> PyResult<PyObject> {
+    let op_code = reduce_op_code(op)?;
+    let value = reduce_impl(py, "tensor_reduce", data_ptr, count, op_code, dtype_code, max_threads, timeout_ms)?;
+    Ok(scalar_to_py(py, value))
+}
+
+/// (kind, op, dtype) of every kernel tensor_reduce()/tensor_binary() can run
+#[pyfunction]
+fn list_kernels() -> Vec<(&'static str, &'static str, &'static str)> {
+    crate::ops::registry::kernels()
+        .entries()
+        .into_iter()
+        .map(|(kind, op, dtype)| (kind, op, dtype.name()))
+        .collect()
+}
+
+#[allow(clippy::too_many_arguments)]
+fn reduce_typed<T>(
+    py: Python,
+    fn_name: &str,
+    data_ptr: usize, count: usize,
+    op: crate::ops::registry::ReduceOp,
+    dtype: crate::tensor::dtype::DType,
+    max_threads: Option<usize>,
+    timeout_ms: Option<u64>,
+    unwrap: fn(crate::ops::registry::Scalar) -> Option<T>
+) -> PyResult<T> {
+    let value = reduce_impl(py, fn_name, data_ptr, count, op as u8, dtype.code(), max_threads, timeout_ms)?;
+    Ok(unwrap(value).expect("registered kernel returns its dtype"))
+}
+
+#[pyfunction]
+fn tensor_all(py: Python, data_ptr: usize, count: usize) -> PyResult<bool> {
+    use crate::ops::registry::{ReduceOp, Scalar};
+    reduce_typed(py, "tensor_all", data_ptr, count, ReduceOp::All, DType::Bool, None, None, Scalar::as_bool)
+}
+
+#[pyfunction]
+fn tensor_any(py: Python, data_ptr: usize, count: usize) -> PyResult<bool> {
+    use crate::ops::registry::{ReduceOp, Scalar};
+    reduce_typed(py, "tensor_any", data_ptr, count, ReduceOp::Any, DType::Bool, None, None, Scalar::as_bool)
+}
+
+#[pyfunction]
+#[pyo3(signature = (data_ptr, count, max_threads=None, timeout_ms=None))]
+fn tensor_sum_f32(py: Python, data_ptr: usize, count: usize, max_threads: Option<usize>, timeout_ms: Option<u64>) -> PyResult<f32> {
+    use crate::ops::registry::{ReduceOp, Scalar};
+    reduce_typed(py, "tensor_sum_f32", data_ptr, count, ReduceOp::Sum, DType::Float32, max_threads, timeout_ms, Scalar::as_f32)
+}
+
+#[pyfunction]
+#[pyo3(signature = (data_ptr, count, max_threads=None, timeout_ms=None))]
+fn tensor_sum_i32(py: Python, data_ptr: usize, count: usize, max_threads: Option<usize>, timeout_ms: Option<u64>) -> PyResult<i32> {
+    use crate::ops::registry::{ReduceOp, Scalar};
+    reduce_typed(py, "tensor_sum_i32", data_ptr, count, ReduceOp::Sum, DType::Int32, max_threads, timeout_ms, Scalar::as_i32)
+}
+
+#[pyfunction]
+#[pyo3(signature = (data_ptr, count, max_threads=None, timeout_ms=None))]
+fn tensor_mean_f32(py: Python, data_ptr: usize, count: usize, max_threads: Option<usize>, timeout_ms: Option<u64>) -> PyResult<f32> {
+    use crate::ops::registry::{ReduceOp, Scalar};
+    reduce_typed(py, "tensor_mean_f32", data_ptr, count, ReduceOp::Mean, DType::Float32, max_threads, timeout_ms, Scalar::as_f32)
 }
 
 #[pyfunction]
-fn tensor_dot_product_f32(a_ptr: usize, b_ptr: usize, count: usize) -> PyResult<f32> {
+fn tensor_dot_product_f32(py: Python, a_ptr: usize, b_ptr: usize, count: usize) -> PyResult<f32> {
     use crate::ops::matmul::dot_product_f32_cpu_dispatch;
     
     if a_ptr == 0 || b_ptr == 0 {
-        return Err(pyo3::exceptions::PyValueError::new_err("Null pointer passed to tensor_dot_product_f32"));
+        return Err(FfiError::NullPointer("tensor_dot_product_f32".to_string()).into());
     }
+    check_pointers("tensor_dot_product_f32", "dot_product", DType::Float32, &[("a_ptr", a_ptr), ("b_ptr", b_ptr)])?;
+    check_extent("tensor_dot_product_f32", count, DType::Float32.size(), &["a_ptr", "b_ptr"])?;
     
     if count == 0 {
         return Ok(0.0);
     }
     
     // PROFILING
-    let _scope = crate::profiler::ProfileScope::new(
+    let mut scope = crate::profiler::ProfileScope::new(
         GLOBAL_PROFILER.clone(),
         "dot_product".to_string(),
         "CPU".to_string(),
         count,
     );
     
-    let result = unsafe {
+    let result = scope.gil_released(|| py.allow_threads(|| unsafe {
         dot_product_f32_cpu_dispatch(a_ptr as *const f32, b_ptr as *const f32, count)
-    };
+    }));
     
     Ok(result)
 }
 
+/// Reject a per-call thread cap of zero
+fn check_max_threads(max_threads: Option<usize>) -> PyResult<()> {
+    match max_threads {
+        Some(0) => Err(FfiError::Invalid("max_threads must be at least 1".to_string()).into()),
+        _ => Ok(()),
+    }
+}
+
+/// Deadline `timeout_ms` from now; the op raises TimeoutError once it passes
+fn deadline_after(timeout_ms: Option<u64>) -> Option<std::time::Instant> {
+    timeout_ms.map(|ms| std::time::Instant::now() + std::time::Duration::from_millis(ms))
+}
+
+/// Reject any (argument name, address) whose `dtype` elements are
+/// misaligned for `op` (see ops::alignment)
+fn check_pointers(fn_name: &str, op: &str, dtype: DType, pointers: &[(&str, usize)]) -> PyResult<()> {
+    for &(arg, address) in pointers {
+        crate::ops::alignment::check_alignment(op, address, dtype).map_err(|err| {
+            FfiError::Invalid(format!("{}: {} {} ({} on {})", fn_name, arg, err, op, dtype))
+        })?;
+    }
+    Ok(())
+}
+
+/// Sizes past the address space are OverflowError; buffers of different
+/// lengths sharing one count are ShapeError
+fn extent_error_to_py(fn_name: &str, err: crate::ops::ExtentError) -> PyErr {
+    use crate::ops::ExtentError;
+    let message = format!("{}: {}", fn_name, err);
+    match err {
+        ExtentError::TooLarge { .. } | ExtentError::Overflow { .. } => {
+            pyo3::exceptions::PyOverflowError::new_err(message)
+        }
+        ExtentError::Mismatch { .. } | ExtentError::OutTooSmall { .. } => FfiError::Shape(message).into(),
+    }
+}
+
+/// Reject a `count` of `elem_size`-byte elements too large to address in
+/// any of the buffers `args` (one count sizes them all)
+fn check_extent(fn_name: &str, count: usize, elem_size: usize, args: &[&str]) -> PyResult<usize> {
+    crate::ops::byte_extent(count, elem_size, args).map_err(|err| extent_error_to_py(fn_name, err))
+}
+
+/// Reject an `out_capacity` (in elements) smaller than the `required` the
+/// op writes to out_ptr; `None` trusts the caller
+fn check_out_capacity(fn_name: &str, required: usize, out_capacity: Option<usize>) -> PyResult<()> {
+    crate::ops::check_capacity("out_ptr", required, out_capacity).map_err(|err| extent_error_to_py(fn_name, err))
+}
+
+/// Raise ValueError unless `ptr` is aligned enough for `op`'s kernels on
+/// `dtype_code` elements (by default, to the element size)
+#[pyfunction]
+#[pyo3(signature = (ptr, dtype_code, op=None))]
+fn check_alignment(ptr: usize, dtype_code: u8, op: Option<&str>) -> PyResult<()> {
+    let dtype = DType::from_code(dtype_code)
+        .ok_or_else(|| tensor_error_to_py(crate::tensor::registry::TensorError::UnknownDtype(dtype_code)))?;
+    check_pointers("check_alignment", op.unwrap_or("element"), dtype, &[("ptr", ptr)])
+}
+
+/// out (m x n) = a (m x k) @ b (k x n); `out_capacity` (elements in out_ptr)
+/// is checked against m * n when given
 #[pyfunction]
-fn tensor_matmul_2d_f32(a_ptr: usize, b_ptr: usize, out_ptr: usize, m: usize, k: usize, n: usize) -> PyResult<()> {
+#[pyo3(signature = (a_ptr, b_ptr, out_ptr, m, k, n, max_threads=None, timeout_ms=None, out_capacity=None))]
+#[allow(clippy::too_many_arguments)]
+fn tensor_matmul_2d_f32(
+    py: Python,
+    a_ptr: usize, b_ptr: usize, out_ptr: usize,
+    m: usize, k: usize, n: usize,
+    max_threads: Option<usize>,
+    timeout_ms: Option<u64>,
+    out_capacity: Option<usize>
+) -> PyResult<()> {
+    matmul_2d_f32_impl(
+        py, "tensor_matmul_2d_f32", "matmul",
+        a_ptr, b_ptr, out_ptr, m, k, n,
+        false, max_threads, timeout_ms, out_capacity
+    )
+}
+
+/// Accumulating matmul: C += A·B without reading C back into Python
+#[pyfunction]
+#[pyo3(signature = (a_ptr, b_ptr, c_ptr, m, k, n, max_threads=None, timeout_ms=None, out_capacity=None))]
+#[allow(clippy::too_many_arguments)]
+fn tensor_matmul_2d_f32_acc(
+    py: Python,
+    a_ptr: usize, b_ptr: usize, c_ptr: usize,
+    m: usize, k: usize, n: usize,
+    max_threads: Option<usize>,
+    timeout_ms: Option<u64>,
+    out_capacity: Option<usize>
+) -> PyResult<()> {
+    matmul_2d_f32_impl(
+        py, "tensor_matmul_2d_f32_acc", "matmul_acc",
+        a_ptr, b_ptr, c_ptr, m, k, n,
+        true, max_threads, timeout_ms, out_capacity
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn matmul_2d_f32_impl(
+    py: Python,
+    fn_name: &str, op_name: &str,
+    a_ptr: usize, b_ptr: usize, out_ptr: usize,
+    m: usize, k: usize, n: usize,
+    accumulate: bool,
+    max_threads: Option<usize>,
+    timeout_ms: Option<u64>,
+    out_capacity: Option<usize>
+) -> PyResult<()> {
     use crate::ops::matmul::matmul_f32_cpu_dispatch;
+    use crate::scheduler::cancel::with_deadline;
     
     if a_ptr == 0 || b_ptr == 0 || out_ptr == 0 {
-        return Err(pyo3::exceptions::PyValueError::new_err("Null pointer passed to tensor_matmul_2d_f32"));
+        return Err(FfiError::NullPointer(fn_name.to_string()).into());
     }
+    check_pointers(fn_name, op_name, DType::Float32, &[("a_ptr", a_ptr), ("b_ptr", b_ptr), ("out_ptr", out_ptr)])?;
+    let flops = matmul_shapes(fn_name, m, k, n, DType::Float32, DType::Float32, out_capacity)?;
+    check_max_threads(max_threads)?;
+    // BLAS sgemm can't be interrupted: its timeout is checked before and after
+    let deadline = deadline_after(timeout_ms);
     
     // PROFILING
-    let _scope = crate::profiler::ProfileScope::new(
+    let mut scope = crate::profiler::ProfileScope::new(
         GLOBAL_PROFILER.clone(),
-        "matmul_2d".to_string(),
+        op_name.to_string(),
         "CPU".to_string(),
-        m * k * n, // FLOPs approximation
+        flops, // FLOPs approximation
     );
+    scope.set_dims(m, n, k);
+    add_matmul_shape(&mut scope, m, k, n, "float32");
     
-    unsafe {
+    // The kernel only touches the caller's buffers, so other Python threads may run
+    let result = scope.gil_released(|| allow_threads_with_progress(py, || with_deadline(deadline, || unsafe {
         matmul_f32_cpu_dispatch(
-            a_ptr as *const f32,
-            b_ptr as *const f32,
-            out_ptr as *mut f32,
-            m, k, n
-        );
-    }
-    
-    Ok(())
+        a_ptr as *const f32,
+        b_ptr as *const f32,
+        out_ptr as *mut f32,
+        m, k, n,
+        accumulate,
+        max_threads
+        )
+    })));
+    // Stop timing before building the Python error, if any
+    scope.finish();
+    result.map_err(|err| dispatch_error_to_py(py, err))
 }
 
-#[pyfunction]
-fn tensor_matmul_f32(a_ptr: usize, b_ptr: usize, count: usize) -> PyResult<f32> {
-    // Legacy/Existing wrapper that calls the same kernel
-    tensor_dot_product_f32(a_ptr, b_ptr, count)
+/// Validate a rows x cols operand of `fn_name`
+fn matrix_shape(fn_name: &str, rows: usize, cols: usize) -> PyResult<crate::tensor::TensorShape> {
+    crate::tensor::TensorShape::matrix(rows, cols)
+        .map_err(|err| FfiError::Shape(format!("{} in {}", err, fn_name)).into())
 }
 
-// ============================================================================
-// Element-wise Operations
-// ============================================================================
+/// Validate the A (m x k), B (k x n) and C (m x n) shapes of a matmul with
+/// `input` operands and an `output` result, C against `out_capacity`
+/// elements if given; returns the m*k*n multiply-adds
+fn matmul_shapes(
+    fn_name: &str,
+    m: usize, k: usize, n: usize,
+    input: DType, output: DType,
+    out_capacity: Option<usize>
+) -> PyResult<usize> {
+    matrix_shape(fn_name, m, k)?;
+    matrix_shape(fn_name, k, n)?;
+    matrix_shape(fn_name, m, n)?;
+    crate::ops::matmul_extent(m, k, n, input.size(), output.size(), out_capacity)
+        .map_err(|err| extent_error_to_py(fn_name, err))
+}
+
+/// Label a matmul profile event with its shape ("m", "k", "n") and input dtype
+fn add_matmul_shape(scope: &mut crate::profiler::ProfileScope, m: usize, k: usize, n: usize, dtype: &str) {
+    scope.add_metadata("m", m);
+    scope.add_metadata("k", k);
+    scope.add_metadata("n", n);
+    scope.add_metadata("dtype", dtype);
+}
 
 #[pyfunction]
-fn tensor_add_f32(a_ptr: usize, b_ptr: usize, out_ptr: usize, count: usize) -> PyResult<()> {
-    use crate::ops::elementwise::add_f32_cpu_dispatch;
+#[pyo3(signature = (a_ptr, b_ptr, out_ptr, m, k, n, out_capacity=None))]
+#[allow(clippy::too_many_arguments)]
+fn tensor_matmul_2d_f16(
+    py: Python,
+    a_ptr: usize, b_ptr: usize, out_ptr: usize,
+    m: usize, k: usize, n: usize,
+    out_capacity: Option<usize>
+) -> PyResult<()> {
+    use crate::ops::matmul::matmul_f16_f32_cpu_dispatch;
     
     if a_ptr == 0 || b_ptr == 0 || out_ptr == 0 {
-        return Err(pyo3::exceptions::PyValueError::new_err("Null pointer passed to tensor_add_f32"));
+        return Err(FfiError::NullPointer("tensor_matmul_2d_f16".to_string()).into());
     }
+    check_pointers("tensor_matmul_2d_f16", "matmul_f16", DType::Float16, &[("a_ptr", a_ptr), ("b_ptr", b_ptr)])?;
+    check_pointers("tensor_matmul_2d_f16", "matmul_f16", DType::Float32, &[("out_ptr", out_ptr)])?;
+    let flops = matmul_shapes("tensor_matmul_2d_f16", m, k, n, DType::Float16, DType::Float32, out_capacity)?;
     
-    if count == 0 {
+    // PROFILING
+    let mut scope = crate::profiler::ProfileScope::new(
+        GLOBAL_PROFILER.clone(),
+        "matmul_f16".to_string(),
+        "CPU".to_string(),
+        flops, // FLOPs approximation
+    );
+    add_matmul_shape(&mut scope, m, k, n, "float16");
+    
+    let result = scope.gil_released(|| allow_threads_with_progress(py, || unsafe {
+        matmul_f16_f32_cpu_dispatch(
+            a_ptr as *const u16,
+            b_ptr as *const u16,
+            out_ptr as *mut f32,
+            m, k, n
+        )
+    }));
+    
+    result.map_err(|reason| cancelled_to_py(py, reason))
+}
+
+#[pyfunction]
+fn tensor_matmul_f32(py: Python, a_ptr: usize, b_ptr: usize, count: usize) -> PyResult<f32> {
+    // Legacy/Existing wrapper that calls the same kernel
+    tensor_dot_product_f32(py, a_ptr, b_ptr, count)
+}
+
+// ============================================================================
+// Element-wise Operations
+// ============================================================================
+
+/// Run the registered element-wise kernel for (`op`, `dtype`) over `count`
+/// elements, checking out_ptr against `out_capacity` if given; `fn_name`
+/// names the calling entry point in errors
+#[allow(clippy::too_many_arguments)]
+fn binary_impl(
+    py: Python,
+    fn_name: &str,
+    a_ptr: usize, b_ptr: usize, out_ptr: usize,
+    count: usize,
+    op_code: u8, dtype_code: u8,
+    out_capacity: Option<usize>
+) -> PyResult<()> {
+    let (op, kernel) = crate::ops::registry::kernels().binary(op_code, dtype_code).map_err(kernel_error_to_py)?;
+    if a_ptr == 0 || b_ptr == 0 || out_ptr == 0 {
+        return Err(FfiError::NullPointer(fn_name.to_string()).into());
+    }
+    let dtype = DType::from_code(dtype_code).expect("registered kernels have known dtypes");
+    check_pointers(fn_name, op.name(), dtype, &[("a_ptr", a_ptr), ("b_ptr", b_ptr), ("out_ptr", out_ptr)])?;
+    check_extent(fn_name, count, dtype.size(), &["a_ptr", "b_ptr", "out_ptr"])?;
+    check_out_capacity(fn_name, count, out_capacity)?;
+
+    if count == 0 {
         return Ok(());
     }
+
+    // PROFILING
+    let mut scope = crate::profiler::ProfileScope::new(
+        GLOBAL_PROFILER.clone(),
+        op.name().to_string(),
+        "CPU".to_string(),
+        count,
+    );
+
+    scope.gil_released(|| py.allow_threads(|| unsafe {
+        kernel(a_ptr as *const u8, b_ptr as *const u8, out_ptr as *mut u8, count);
+    }));
+
+    Ok(())
+}
+
+/// out = a <op> b over `count` elements with any registered (op, dtype)
+/// kernel; op codes are 0 add, 1 sub, 2 mul, 3 div. `out_capacity` is the
+/// number of elements out_ptr holds, checked against `count` when given
+#[pyfunction]
+#[pyo3(signature = (a_ptr, b_ptr, out_ptr, count, op_code, dtype_code, out_capacity=None))]
+#[allow(clippy::too_many_arguments)]
+fn tensor_binary(
+    py: Python,
+    a_ptr: usize, b_ptr: usize, out_ptr: usize,
+    count: usize,
+    op_code: u8, dtype_code: u8,
+    out_capacity: Option<usize>
+) -> PyResult<()> {
+    binary_impl(py, "tensor_binary", a_ptr, b_ptr, out_ptr, count, op_code, dtype_code, out_capacity)
+}
+
+#[pyfunction]
+#[pyo3(signature = (a_ptr, b_ptr, out_ptr, count, out_capacity=None))]
+fn tensor_add_f32(
+    py: Python,
+    a_ptr: usize, b_ptr: usize, out_ptr: usize,
+    count: usize,
+    out_capacity: Option<usize>
+) -> PyResult<()> {
+    use crate::ops::broadcast::BinaryOp;
+    let (op, dtype) = (BinaryOp::Add as u8, DType::Float32.code());
+    binary_impl(py, "tensor_add_f32", a_ptr, b_ptr, out_ptr, count, op, dtype, out_capacity)
+}
+
+/// out = a <op> b with NumPy broadcasting over views given by shape and byte
+/// strides (as in `array.shape` / `array.strides`); op codes are 0 add,
+/// 1 sub, 2 mul, 3 div. `out_ptr` must hold the broadcast shape, C-contiguous;
+/// `out_capacity`, if given, is checked against its element count.
+/// Returns the output shape and how many runs took the contiguous kernel.
+#[pyfunction]
+#[pyo3(signature = (a_ptr, a_shape, a_strides, b_ptr, b_shape, b_strides, out_ptr, op_code, out_capacity=None))]
+#[allow(clippy::too_many_arguments)]
+fn tensor_broadcast_binary_f32(
+    py: Python,
+    a_ptr: usize, a_shape: Vec<usize>, a_strides: Vec<isize>,
+    b_ptr: usize, b_shape: Vec<usize>, b_strides: Vec<isize>,
+    out_ptr: usize,
+    op_code: u8,
+    out_capacity: Option<usize>
+) -> PyResult<PyObject> {
+    use crate::ops::broadcast::{binary_broadcast_f32, BinaryOp};
+    use crate::tensor::Layout;
+
+    let op = BinaryOp::from_code(op_code)
+        .ok_or_else(|| FfiError::Invalid(format!("Unknown binary op code {}", op_code)))?;
+    if a_ptr == 0 || b_ptr == 0 || out_ptr == 0 {
+        return Err(FfiError::NullPointer("tensor_broadcast_binary_f32".to_string()).into());
+    }
+    let pointers = [("a_ptr", a_ptr), ("b_ptr", b_ptr), ("out_ptr", out_ptr)];
+    check_pointers("tensor_broadcast_binary_f32", op.name(), DType::Float32, &pointers)?;
+    let to_py = |err: &dyn std::fmt::Display| -> PyErr {
+        FfiError::Shape(format!("{} in tensor_broadcast_binary_f32", err)).into()
+    };
+    let elem = std::mem::size_of::<f32>();
+    let a_layout = Layout::from_byte_strides(&a_shape, &a_strides, elem).map_err(|err| to_py(&err))?;
+    let b_layout = Layout::from_byte_strides(&b_shape, &b_strides, elem).map_err(|err| to_py(&err))?;
+    let shape = a_layout.shape().broadcast_with(b_layout.shape()).map_err(|err| to_py(&err))?;
+    check_extent("tensor_broadcast_binary_f32", shape.numel(), elem, &["out_ptr"])?;
+    check_out_capacity("tensor_broadcast_binary_f32", shape.numel(), out_capacity)?;
+    let out_layout = Layout::c_contiguous(shape.clone());
+
+    // PROFILING
+    let mut scope = crate::profiler::ProfileScope::new(
+        GLOBAL_PROFILER.clone(),
+        op.name().to_string(),
+        "CPU".to_string(),
+        shape.numel(),
+    );
+    scope.add_metadata("shape", format!("{:?}", shape.dims()));
+
+    let summary = scope.gil_released(|| py.allow_threads(|| unsafe {
+        binary_broadcast_f32(a_ptr as *const f32, &a_layout, b_ptr as *const f32, &b_layout, out_ptr as *mut f32, &out_layout, op)
+    })).map_err(|err| to_py(&err))?;
+
+    let dict = pyo3::types::PyDict::new(py);
+    dict.set_item("shape", pyo3::types::PyTuple::new(py, summary.shape.dims()))?;
+    dict.set_item("run_length", summary.run_length)?;
+    dict.set_item("contiguous_runs", summary.contiguous_runs)?;
+    dict.set_item("strided_runs", summary.strided_runs)?;
+    Ok(dict.into())
+}
+
+#[pyfunction]
+#[pyo3(signature = (a_ptr, b_ptr, out_ptr, count, out_capacity=None))]
+fn tensor_sub_f32(
+    py: Python,
+    a_ptr: usize, b_ptr: usize, out_ptr: usize,
+    count: usize,
+    out_capacity: Option<usize>
+) -> PyResult<()> {
+    use crate::ops::broadcast::BinaryOp;
+    let (op, dtype) = (BinaryOp::Sub as u8, DType::Float32.code());
+    binary_impl(py, "tensor_sub_f32", a_ptr, b_ptr, out_ptr, count, op, dtype, out_capacity)
+}
+
+#[pyfunction]
+#[pyo3(signature = (a_ptr, b_ptr, out_ptr, count, out_capacity=None))]
+fn tensor_mul_f32(
+    py: Python,
+    a_ptr: usize, b_ptr: usize, out_ptr: usize,
+    count: usize,
+    out_capacity: Option<usize>
+) -> PyResult<()> {
+    use crate::ops::broadcast::BinaryOp;
+    let (op, dtype) = (BinaryOp::Mul as u8, DType::Float32.code());
+    binary_impl(py, "tensor_mul_f32", a_ptr, b_ptr, out_ptr, count, op, dtype, out_capacity)
+}
+
+#[pyfunction]
+#[pyo3(signature = (a_ptr, b_ptr, out_ptr, count, out_capacity=None))]
+fn tensor_div_f32(
+    py: Python,
+    a_ptr: usize, b_ptr: usize, out_ptr: usize,
+    count: usize,
+    out_capacity: Option<usize>
+) -> PyResult<()> {
+    use crate::ops::broadcast::BinaryOp;
+    let (op, dtype) = (BinaryOp::Div as u8, DType::Float32.code());
+    binary_impl(py, "tensor_div_f32", a_ptr, b_ptr, out_ptr, count, op, dtype, out_capacity)
+}
+
+// ============================================================================
+// Linear Algebra
+// ============================================================================
+
+#[pyfunction]
+fn tensor_triu_f32(py: Python, ptr: usize, rows: usize, cols: usize, k: isize) -> PyResult<()> {
+    use crate::ops::linalg::triu_f32_cpu_dispatch;
+    
+    if ptr == 0 {
+        return Err(FfiError::NullPointer("tensor_triu_f32".to_string()).into());
+    }
+    check_pointers("tensor_triu_f32", "triu", DType::Float32, &[("ptr", ptr)])?;
+    
+    let count = matrix_shape("tensor_triu_f32", rows, cols)?.numel();
+    check_extent("tensor_triu_f32", count, DType::Float32.size(), &["ptr"])?;
     
     // PROFILING
-    let _scope = crate::profiler::ProfileScope::new(
+    let mut scope = crate::profiler::ProfileScope::new(
         GLOBAL_PROFILER.clone(),
-        "add".to_string(),
+        "triu".to_string(),
         "CPU".to_string(),
         count,
     );
     
-    unsafe {
-        add_f32_cpu_dispatch(a_ptr as *const f32, b_ptr as *const f32, out_ptr as *mut f32, count);
+    scope.gil_released(|| py.allow_threads(|| unsafe {
+        triu_f32_cpu_dispatch(ptr as *mut f32, rows, cols, k);
+    }));
+    
+    Ok(())
+}
+
+#[pyfunction]
+fn tensor_tril_f32(py: Python, ptr: usize, rows: usize, cols: usize, k: isize) -> PyResult<()> {
+    use crate::ops::linalg::tril_f32_cpu_dispatch;
+    
+    if ptr == 0 {
+        return Err(FfiError::NullPointer("tensor_tril_f32".to_string()).into());
     }
+    check_pointers("tensor_tril_f32", "tril", DType::Float32, &[("ptr", ptr)])?;
+    
+    let count = matrix_shape("tensor_tril_f32", rows, cols)?.numel();
+    check_extent("tensor_tril_f32", count, DType::Float32.size(), &["ptr"])?;
+    
+    // PROFILING
+    let mut scope = crate::profiler::ProfileScope::new(
+        GLOBAL_PROFILER.clone(),
+        "tril".to_string(),
+        "CPU".to_string(),
+        count,
+    );
+    
+    scope.gil_released(|| py.allow_threads(|| unsafe {
+        tril_f32_cpu_dispatch(ptr as *mut f32, rows, cols, k);
+    }));
     
     Ok(())
 }
 
 #[pyfunction]
-fn tensor_sub_f32(a_ptr: usize, b_ptr: usize, out_ptr: usize, count: usize) -> PyResult<()> {
-    use crate::ops::elementwise::sub_f32_cpu_dispatch;
+fn tensor_frobenius_norm_f32(py: Python, a_ptr: usize, rows: usize, cols: usize) -> PyResult<f32> {
+    use crate::ops::linalg::frobenius_norm_f32_cpu_dispatch;
     
-    if a_ptr == 0 || b_ptr == 0 || out_ptr == 0 {
-        return Err(pyo3::exceptions::PyValueError::new_err("Null pointer passed to tensor_sub_f32"));
+    if a_ptr == 0 {
+        return Err(FfiError::NullPointer("tensor_frobenius_norm_f32".to_string()).into());
     }
+    check_pointers("tensor_frobenius_norm_f32", "frobenius_norm", DType::Float32, &[("a_ptr", a_ptr)])?;
+    
+    let count = matrix_shape("tensor_frobenius_norm_f32", rows, cols)?.numel();
+    check_extent("tensor_frobenius_norm_f32", count, DType::Float32.size(), &["a_ptr"])?;
     
     if count == 0 {
-        return Ok(());
+        return Ok(0.0);
+    }
+    
+    // PROFILING
+    let mut scope = crate::profiler::ProfileScope::new(
+        GLOBAL_PROFILER.clone(),
+        "frobenius_norm".to_string(),
+        "CPU".to_string(),
+        count,
+    );
+    
+    let result = scope.gil_released(|| py.allow_threads(|| unsafe {
+        frobenius_norm_f32_cpu_dispatch(a_ptr as *const f32, rows, cols)
+    }));
+    
+    Ok(result)
+}
+
+/// Norm of each row of a (rows x cols) into out_ptr (`rows` elements, checked
+/// against `out_capacity` when given)
+#[pyfunction]
+#[pyo3(signature = (a_ptr, out_ptr, rows, cols, ord, out_capacity=None))]
+fn tensor_row_norms_f32(
+    py: Python,
+    a_ptr: usize, out_ptr: usize,
+    rows: usize, cols: usize,
+    ord: u32,
+    out_capacity: Option<usize>
+) -> PyResult<()> {
+    use crate::ops::linalg::row_norms_f32_cpu_dispatch;
+    
+    if a_ptr == 0 || out_ptr == 0 {
+        return Err(FfiError::NullPointer("tensor_row_norms_f32".to_string()).into());
     }
+    check_pointers("tensor_row_norms_f32", "row_norms", DType::Float32, &[("a_ptr", a_ptr), ("out_ptr", out_ptr)])?;
+    
+    let count = matrix_shape("tensor_row_norms_f32", rows, cols)?.numel();
+    check_extent("tensor_row_norms_f32", count, DType::Float32.size(), &["a_ptr"])?;
+    check_out_capacity("tensor_row_norms_f32", rows, out_capacity)?;
     
     // PROFILING
-    let _scope = crate::profiler::ProfileScope::new(
+    let mut scope = crate::profiler::ProfileScope::new(
         GLOBAL_PROFILER.clone(),
-        "sub".to_string(),
+        "row_norms".to_string(),
         "CPU".to_string(),
         count,
     );
     
-    unsafe {
-        sub_f32_cpu_dispatch(a_ptr as *const f32, b_ptr as *const f32, out_ptr as *mut f32, count);
+    scope.gil_released(|| py.allow_threads(|| unsafe {
+        row_norms_f32_cpu_dispatch(a_ptr as *const f32, out_ptr as *mut f32, rows, cols, ord)
+    }))
+    .map_err(|err| FfiError::Invalid(err).into())
+}
+
+#[pyfunction]
+#[pyo3(signature = (out_ptr, n, out_capacity=None))]
+fn tensor_eye_f32(py: Python, out_ptr: usize, n: usize, out_capacity: Option<usize>) -> PyResult<()> {
+    use crate::ops::linalg::{eye_f32_cpu_dispatch, square_matrix_len};
+    
+    if out_ptr == 0 {
+        return Err(FfiError::NullPointer("tensor_eye_f32".to_string()).into());
     }
+    check_pointers("tensor_eye_f32", "eye", DType::Float32, &[("out_ptr", out_ptr)])?;
+    
+    let count = square_matrix_len(n)
+        .ok_or_else(|| FfiError::Shape(format!("Matrix size {}x{} overflows in tensor_eye_f32", n, n)))?;
+    check_extent("tensor_eye_f32", count, DType::Float32.size(), &["out_ptr"])?;
+    check_out_capacity("tensor_eye_f32", count, out_capacity)?;
+    
+    // PROFILING
+    let mut scope = crate::profiler::ProfileScope::new(
+        GLOBAL_PROFILER.clone(),
+        "eye".to_string(),
+        "CPU".to_string(),
+        count,
+    );
+    
+    scope.gil_released(|| py.allow_threads(|| unsafe {
+        eye_f32_cpu_dispatch(out_ptr as *mut f32, n, n);
+    }));
     
     Ok(())
 }
 
 #[pyfunction]
-fn tensor_mul_f32(a_ptr: usize, b_ptr: usize, out_ptr: usize, count: usize) -> PyResult<()> {
-    use crate::ops::elementwise::mul_f32_cpu_dispatch;
+#[pyo3(signature = (v_ptr, out_ptr, n, out_capacity=None))]
+fn tensor_diag_f32(py: Python, v_ptr: usize, out_ptr: usize, n: usize, out_capacity: Option<usize>) -> PyResult<()> {
+    use crate::ops::linalg::{diag_from_vector_f32_cpu_dispatch, square_matrix_len};
+    
+    if v_ptr == 0 || out_ptr == 0 {
+        return Err(FfiError::NullPointer("tensor_diag_f32".to_string()).into());
+    }
+    check_pointers("tensor_diag_f32", "diag", DType::Float32, &[("v_ptr", v_ptr), ("out_ptr", out_ptr)])?;
+    
+    let count = square_matrix_len(n)
+        .ok_or_else(|| FfiError::Shape(format!("Matrix size {}x{} overflows in tensor_diag_f32", n, n)))?;
+    check_extent("tensor_diag_f32", count, DType::Float32.size(), &["out_ptr"])?;
+    check_out_capacity("tensor_diag_f32", count, out_capacity)?;
+    
+    // PROFILING
+    let mut scope = crate::profiler::ProfileScope::new(
+        GLOBAL_PROFILER.clone(),
+        "diag".to_string(),
+        "CPU".to_string(),
+        count,
+    );
+    
+    scope.gil_released(|| py.allow_threads(|| unsafe {
+        diag_from_vector_f32_cpu_dispatch(v_ptr as *const f32, out_ptr as *mut f32, n);
+    }));
     
+    Ok(())
+}
+
+// ============================================================================
+// Asynchronous Submission
+// ============================================================================
+//
+// The pointers must stay valid until the handle is collected: pass the backing
+// arrays as `keep_alive` and they are held until then.
+
+fn async_error_to_py(err: crate::scheduler::async_ops::AsyncError) -> PyErr {
+    use crate::scheduler::async_ops::AsyncError;
+    match err {
+        AsyncError::UnknownHandle(_) => pyo3::exceptions::PyValueError::new_err(err.to_string()),
+        AsyncError::StillRunning(_) => pyo3::exceptions::PyRuntimeError::new_err(err.to_string()),
+    }
+}
+
+/// Queue C = A·B on corepy's pool; returns a handle for wait()/release_handle()
+#[pyfunction]
+#[pyo3(signature = (a_ptr, b_ptr, out_ptr, m, k, n, keep_alive=None, out_capacity=None))]
+#[allow(clippy::too_many_arguments)]
+fn submit_matmul_2d_f32(
+    a_ptr: usize, b_ptr: usize, out_ptr: usize,
+    m: usize, k: usize, n: usize,
+    keep_alive: Option<PyObject>,
+    out_capacity: Option<usize>
+) -> PyResult<u64> {
+    use crate::ops::matmul::matmul_f32_cpu_dispatch;
+    use crate::scheduler::async_ops::{submit, AsyncValue};
+
     if a_ptr == 0 || b_ptr == 0 || out_ptr == 0 {
-        return Err(pyo3::exceptions::PyValueError::new_err("Null pointer passed to tensor_mul_f32"));
+        return Err(FfiError::NullPointer("submit_matmul_2d_f32".to_string()).into());
+    }
+    let pointers = [("a_ptr", a_ptr), ("b_ptr", b_ptr), ("out_ptr", out_ptr)];
+    check_pointers("submit_matmul_2d_f32", "matmul", DType::Float32, &pointers)?;
+    let flops = matmul_shapes("submit_matmul_2d_f32", m, k, n, DType::Float32, DType::Float32, out_capacity)?;
+
+    // Profiled on the worker under the submitting thread's context
+    let context = crate::profiler::get_context();
+    let job = move || crate::profiler::with_context(context, || {
+        let mut scope = crate::profiler::ProfileScope::new(
+            GLOBAL_PROFILER.clone(),
+            "matmul".to_string(),
+            "CPU".to_string(),
+            flops, // FLOPs approximation
+        );
+        scope.set_dims(m, n, k);
+        add_matmul_shape(&mut scope, m, k, n, "float32");
+        unsafe {
+            matmul_f32_cpu_dispatch(a_ptr as *const f32, b_ptr as *const f32, out_ptr as *mut f32, m, k, n, false, None)
+        }
+        .map(|()| AsyncValue::Unit)
+        .map_err(|e| e.to_string())
+    });
+    Ok(submit(job, keep_alive.map(|obj| Box::new(obj) as Box<dyn Send>)))
+}
+
+/// Queue sum() on corepy's pool; returns a handle for wait()/get_result_f32()
+#[pyfunction]
+#[pyo3(signature = (data_ptr, count, keep_alive=None))]
+fn submit_sum_f32(data_ptr: usize, count: usize, keep_alive: Option<PyObject>) -> PyResult<u64> {
+    use crate::ops::reduce::sum_f32_cpu_dispatch;
+    use crate::scheduler::async_ops::{submit, AsyncValue};
+
+    if data_ptr == 0 {
+        return Err(FfiError::NullPointer("submit_sum_f32".to_string()).into());
+    }
+    check_pointers("submit_sum_f32", "sum", DType::Float32, &[("data_ptr", data_ptr)])?;
+    check_extent("submit_sum_f32", count, DType::Float32.size(), &["data_ptr"])?;
+
+    let context = crate::profiler::get_context();
+    let job = move || crate::profiler::with_context(context, || {
+        if count == 0 {
+            return Ok(AsyncValue::F32(0.0));
+        }
+        let _scope = crate::profiler::ProfileScope::new(
+            GLOBAL_PROFILER.clone(),
+            "sum".to_string(),
+            "CPU".to_string(),
+            count,
+        );
+        unsafe { sum_f32_cpu_dispatch(data_ptr as *const f32, count, None) }
+            .map(AsyncValue::F32)
+            .map_err(|e| e.to_string())
+    });
+    Ok(submit(job, keep_alive.map(|obj| Box::new(obj) as Box<dyn Send>)))
+}
+
+/// Block (GIL released) until the operation finishes or `timeout_ms` passes
+///
+/// Returns True when finished; the result stays available either way.
+#[pyfunction]
+#[pyo3(signature = (handle, timeout_ms=None))]
+fn wait(py: Python, handle: u64, timeout_ms: Option<u64>) -> PyResult<bool> {
+    let timeout = timeout_ms.map(std::time::Duration::from_millis);
+    py.allow_threads(|| crate::scheduler::async_ops::wait(handle, timeout))
+        .map_err(async_error_to_py)
+}
+
+#[pyfunction]
+fn is_ready(handle: u64) -> PyResult<bool> {
+    crate::scheduler::async_ops::is_ready(handle).map_err(async_error_to_py)
+}
+
+/// Collect a finished operation's f32 result, releasing the handle
+#[pyfunction]
+fn get_result_f32(handle: u64) -> PyResult<f32> {
+    use crate::scheduler::async_ops::AsyncValue;
+
+    match crate::scheduler::async_ops::take_result(handle).map_err(async_error_to_py)? {
+        Ok(AsyncValue::F32(value)) => Ok(value),
+        Ok(AsyncValue::Unit) => Err(pyo3::exceptions::PyTypeError::new_err(format!(
+            "async operation {} has no f32 result", handle
+        ))),
+        Err(e) => Err(pyo3::exceptions::PyRuntimeError::new_err(e)),
+    }
+}
+
+/// Collect a finished operation without reading a value (e.g. matmul),
+/// raising its error if it failed
+#[pyfunction]
+fn release_handle(handle: u64) -> PyResult<()> {
+    crate::scheduler::async_ops::take_result(handle)
+        .map_err(async_error_to_py)?
+        .map(drop)
+        .map_err(pyo3::exceptions::PyRuntimeError::new_err)
+}
+
+// ============================================================================
+// Dtype Conversion
+// ============================================================================
+
+#[pyfunction]
+fn tensor_cast_f16_to_f32(py: Python, src_ptr: usize, dst_ptr: usize, count: usize) -> PyResult<()> {
+    use crate::ops::cast::cast_f16_to_f32_dispatch;
+    
+    if src_ptr == 0 || dst_ptr == 0 {
+        return Err(FfiError::NullPointer("tensor_cast_f16_to_f32".to_string()).into());
     }
+    check_pointers("tensor_cast_f16_to_f32", "cast_f16_f32", DType::Float16, &[("src_ptr", src_ptr)])?;
+    check_pointers("tensor_cast_f16_to_f32", "cast_f16_f32", DType::Float32, &[("dst_ptr", dst_ptr)])?;
+    check_extent("tensor_cast_f16_to_f32", count, DType::Float32.size(), &["dst_ptr"])?;
     
     if count == 0 {
         return Ok(());
     }
     
     // PROFILING
-    let _scope = crate::profiler::ProfileScope::new(
+    let mut scope = crate::profiler::ProfileScope::new(
         GLOBAL_PROFILER.clone(),
-        "mul".to_string(),
+        "cast_f16_f32".to_string(),
         "CPU".to_string(),
         count,
     );
     
-    unsafe {
-        mul_f32_cpu_dispatch(a_ptr as *const f32, b_ptr as *const f32, out_ptr as *mut f32, count);
-    }
+    scope.gil_released(|| py.allow_threads(|| unsafe {
+        cast_f16_to_f32_dispatch(src_ptr as *const u16, dst_ptr as *mut f32, count);
+    }));
     
     Ok(())
 }
 
 #[pyfunction]
-fn tensor_div_f32(a_ptr: usize, b_ptr: usize, out_ptr: usize, count: usize) -> PyResult<()> {
-    use crate::ops::elementwise::div_f32_cpu_dispatch;
+fn tensor_cast_f32_to_f16(py: Python, src_ptr: usize, dst_ptr: usize, count: usize) -> PyResult<()> {
+    use crate::ops::cast::cast_f32_to_f16_dispatch;
     
-    if a_ptr == 0 || b_ptr == 0 || out_ptr == 0 {
-        return Err(pyo3::exceptions::PyValueError::new_err("Null pointer passed to tensor_div_f32"));
+    if src_ptr == 0 || dst_ptr == 0 {
+        return Err(FfiError::NullPointer("tensor_cast_f32_to_f16".to_string()).into());
     }
+    check_pointers("tensor_cast_f32_to_f16", "cast_f32_f16", DType::Float32, &[("src_ptr", src_ptr)])?;
+    check_pointers("tensor_cast_f32_to_f16", "cast_f32_f16", DType::Float16, &[("dst_ptr", dst_ptr)])?;
+    check_extent("tensor_cast_f32_to_f16", count, DType::Float32.size(), &["src_ptr"])?;
     
     if count == 0 {
         return Ok(());
     }
     
     // PROFILING
-    let _scope = crate::profiler::ProfileScope::new(
+    let mut scope = crate::profiler::ProfileScope::new(
         GLOBAL_PROFILER.clone(),
-        "div".to_string(),
+        "cast_f32_f16".to_string(),
         "CPU".to_string(),
         count,
     );
     
-    unsafe {
-        div_f32_cpu_dispatch(a_ptr as *const f32, b_ptr as *const f32, out_ptr as *mut f32, count);
-    }
+    scope.gil_released(|| py.allow_threads(|| unsafe {
+        cast_f32_to_f16_dispatch(src_ptr as *const f32, dst_ptr as *mut u16, count);
+    }));
     
     Ok(())
 }
 
+// ============================================================================
+// Batched Operations
+// ============================================================================
+// execute_batch() runs a list of element-wise ops, reductions and matmuls in
+// one call, for workloads of many ops on small tensors where each crossing
+// into Rust costs more than the kernel. The whole batch is checked before
+// anything runs; the ops then run in order in one GIL-released region, each
+// with its own profile event.
+
+/// Keys an OpSpec dict may have
+const OP_SPEC_KEYS: [&str; 6] = ["op", "inputs", "out", "count", "dims", "dtype"];
+
+/// One op of a batch, from a dict: "op" (add, sub, mul, div; sum, mean,
+/// all, any; or matmul), "inputs" (data addresses), "out" (output address;
+/// omitted for reductions), "count" (elements; not for matmul), "dims"
+/// ((m, k, n), matmul only) and "dtype" (default "float32")
+struct OpSpec {
+    op: String,
+    inputs: Vec<usize>,
+    out: Option<usize>,
+    count: Option<usize>,
+    dims: Option<(usize, usize, usize)>,
+    dtype: String,
+}
+
+impl<'source> FromPyObject<'source> for OpSpec {
+    fn extract(obj: &'source PyAny) -> PyResult<Self> {
+        let py = obj.py();
+        let dict: &pyo3::types::PyDict = obj.downcast()?;
+        // Keys are interned: batches are meant for many small ops
+        let keys = [
+            pyo3::intern!(py, "op"),
+            pyo3::intern!(py, "inputs"),
+            pyo3::intern!(py, "out"),
+            pyo3::intern!(py, "count"),
+            pyo3::intern!(py, "dims"),
+            pyo3::intern!(py, "dtype"),
+        ];
+        let mut values: [Option<&PyAny>; 6] = [None; 6];
+        for (value, key) in values.iter_mut().zip(keys) {
+            *value = dict.get_item(key)?.filter(|value| !value.is_none());
+        }
+        if dict.len() > values.iter().flatten().count() {
+            if let Some(key) = dict.keys().iter().find(|key| !keys.iter().any(|known| known.eq(key).unwrap_or(false))) {
+                return Err(FfiError::Invalid(format!(
+                    "unknown key {} (expected some of: {})", key.repr()?, OP_SPEC_KEYS.join(", ")
+                )).into());
+            }
+        }
+        let [op, inputs, out, count, dims, dtype] = values;
+        Ok(OpSpec {
+            op: op.ok_or_else(|| FfiError::Invalid("missing 'op'".to_string()))?.extract()?,
+            inputs: inputs.map(PyAny::extract).transpose()?.unwrap_or_default(),
+            out: out.map(PyAny::extract).transpose()?,
+            count: count.map(PyAny::extract).transpose()?,
+            dims: dims.map(PyAny::extract).transpose()?,
+            dtype: dtype.map(PyAny::extract).transpose()?.unwrap_or_else(|| "float32".to_string()),
+        })
+    }
+}
+
+impl OpSpec {
+    /// Require `inputs` inputs, and "out" exactly when the op writes one
+    fn check_arity(&self, inputs: usize, writes_out: bool) -> PyResult<()> {
+        if self.inputs.len() != inputs {
+            return Err(FfiError::Invalid(format!(
+                "{} takes {} input(s), got {}", self.op, inputs, self.inputs.len()
+            )).into());
+        }
+        match (writes_out, self.out) {
+            (true, None) => Err(FfiError::Invalid(format!("{} needs 'out'", self.op)).into()),
+            (false, Some(_)) => Err(FfiError::Invalid(format!(
+                "{} returns its result; omit 'out'", self.op
+            )).into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// "count", which element-wise ops and reductions need instead of "dims"
+    fn count(&self) -> PyResult<usize> {
+        if self.dims.is_some() {
+            return Err(FfiError::Invalid(format!("{} takes 'count', not 'dims'", self.op)).into());
+        }
+        self.count.ok_or_else(|| FfiError::Invalid(format!("{} needs 'count'", self.op)).into())
+    }
+
+    /// Reject null input or output addresses
+    fn check_not_null(&self) -> PyResult<()> {
+        if self.inputs.contains(&0) || self.out == Some(0) {
+            return Err(FfiError::NullPointer("execute_batch".to_string()).into());
+        }
+        Ok(())
+    }
+}
+
+/// A checked batch op
+enum BatchStep {
+    Binary {
+        op: crate::ops::broadcast::BinaryOp,
+        kernel: crate::ops::registry::BinaryFn,
+        a: usize, b: usize, out: usize,
+        count: usize,
+    },
+    Reduce {
+        op: crate::ops::registry::ReduceOp,
+        kernel: crate::ops::registry::ReduceKernel,
+        data: usize,
+        count: usize,
+    },
+    Matmul { a: usize, b: usize, out: usize, m: usize, k: usize, n: usize, flops: usize },
+}
+
+impl BatchStep {
+    /// Check everything about `spec` that could fail before it runs
+    fn plan(spec: &OpSpec) -> PyResult<BatchStep> {
+        use crate::ops::broadcast::BinaryOp;
+        use crate::ops::registry::{kernels, ReduceOp};
+        const FN: &str = "execute_batch";
+
+        let dtype = parse_dtype(&spec.dtype)?;
+        if let Some(op) = (0..4).filter_map(BinaryOp::from_code).find(|op| op.name() == spec.op) {
+            spec.check_arity(2, true)?;
+            let count = spec.count()?;
+            let (_, kernel) = kernels().binary(op as u8, dtype.code()).map_err(kernel_error_to_py)?;
+            spec.check_not_null()?;
+            let (a, b, out) = (spec.inputs[0], spec.inputs[1], spec.out.unwrap_or_default());
+            check_pointers(FN, op.name(), dtype, &[("inputs[0]", a), ("inputs[1]", b), ("out", out)])?;
+            check_extent(FN, count, dtype.size(), &["inputs[0]", "inputs[1]", "out"])?;
+            return Ok(BatchStep::Binary { op, kernel, a, b, out, count });
+        }
+        if let Some(op) = ReduceOp::from_name(&spec.op) {
+            spec.check_arity(1, false)?;
+            let count = spec.count()?;
+            let (_, kernel) = kernels().reduce(op as u8, dtype.code()).map_err(kernel_error_to_py)?;
+            spec.check_not_null()?;
+            let data = spec.inputs[0];
+            check_pointers(FN, op.name(), dtype, &[("inputs[0]", data)])?;
+            check_extent(FN, count, dtype.size(), &["inputs[0]"])?;
+            if count == 0 && kernel.empty.is_none() {
+                return Err(FfiError::Shape(format!("Cannot compute {} of empty tensor", op.name())).into());
+            }
+            return Ok(BatchStep::Reduce { op, kernel, data, count });
+        }
+        if spec.op == "matmul" {
+            spec.check_arity(2, true)?;
+            if dtype != DType::Float32 {
+                return Err(FfiError::DType(format!("matmul takes float32 operands, got {}", dtype)).into());
+            }
+            let (m, k, n) = spec.dims.ok_or_else(|| FfiError::Invalid("matmul needs 'dims' (m, k, n)".to_string()))?;
+            if spec.count.is_some() {
+                return Err(FfiError::Invalid("matmul takes 'dims', not 'count'".to_string()).into());
+            }
+            spec.check_not_null()?;
+            let (a, b, out) = (spec.inputs[0], spec.inputs[1], spec.out.unwrap_or_default());
+            check_pointers(FN, "matmul", dtype, &[("inputs[0]", a), ("inputs[1]", b), ("out", out)])?;
+            let flops = matmul_shapes(FN, m, k, n, DType::Float32, DType::Float32, None)?;
+            return Ok(BatchStep::Matmul { a, b, out, m, k, n, flops });
+        }
+        Err(FfiError::Invalid(format!(
+            "unknown op '{}' (expected add, sub, mul, div, sum, mean, all, any or matmul)", spec.op
+        )).into())
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            BatchStep::Binary { op, .. } => op.name(),
+            BatchStep::Reduce { op, .. } => op.name(),
+            BatchStep::Matmul { .. } => "matmul",
+        }
+    }
+
+    /// Elements (or multiply-adds, for matmul) for the profile event
+    fn work(&self) -> usize {
+        match *self {
+            BatchStep::Binary { count, .. } | BatchStep::Reduce { count, .. } => count,
+            BatchStep::Matmul { flops, .. } => flops,
+        }
+    }
+
+    /// Run the op; a reduction's value is returned
+    unsafe fn run(&self) -> Result<Option<crate::ops::registry::Scalar>, crate::ops::DispatchError> {
+        match *self {
+            BatchStep::Binary { kernel, a, b, out, count, .. } => {
+                if count > 0 {
+                    kernel(a as *const u8, b as *const u8, out as *mut u8, count);
+                }
+                Ok(None)
+            }
+            BatchStep::Reduce { kernel, data, count, .. } => {
+                if count == 0 {
+                    return Ok(kernel.empty);
+                }
+                Ok(Some((kernel.run)(data as *const u8, count, None)?))
+            }
+            BatchStep::Matmul { a, b, out, m, k, n, .. } => {
+                use crate::ops::matmul::matmul_f32_cpu_dispatch;
+                matmul_f32_cpu_dispatch(a as *const f32, b as *const f32, out as *mut f32, m, k, n, false, None)?;
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// `err` raised for op `index` of a batch: same class, message prefixed
+/// with the index (in place of the "execute_batch: " the checks add)
+fn batch_error(py: Python, index: usize, err: PyErr, note: &str) -> PyErr {
+    let message = err.value(py).to_string();
+    let message = message.strip_prefix("execute_batch: ").unwrap_or(&message);
+    PyErr::from_type(err.get_type(py), format!("execute_batch: op {}: {}{}", index, message, note))
+}
+
+/// Run `ops` (dicts, see OpSpec) in order with one call; returns one entry
+/// per op, the value for reductions and None otherwise. Every op is checked
+/// before any runs. If one fails while running, the error gives its index
+/// and every op before it has completed
+#[pyfunction]
+fn execute_batch(py: Python, ops: Vec<&PyAny>) -> PyResult<Vec<PyObject>> {
+    let steps = ops
+        .iter()
+        .enumerate()
+        .map(|(index, spec)| {
+            spec.extract::<OpSpec>()
+                .and_then(|spec| BatchStep::plan(&spec))
+                .map_err(|err| batch_error(py, index, err, ""))
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+
+    let (values, failure) = py.allow_threads(|| {
+        let mut values = Vec::with_capacity(steps.len());
+        // Checked once: building a scope costs about as much as a 1K-element add
+        let profiling = GLOBAL_PROFILER.is_recording();
+        for step in &steps {
+            // PROFILING
+            let _scope = profiling.then(|| {
+                let mut scope = crate::profiler::ProfileScope::new(
+                    GLOBAL_PROFILER.clone(),
+                    step.name().to_string(),
+                    "CPU".to_string(),
+                    step.work(),
+                );
+                if let BatchStep::Matmul { m, k, n, .. } = *step {
+                    scope.set_dims(m, n, k);
+                    add_matmul_shape(&mut scope, m, k, n, "float32");
+                }
+                scope
+            });
+            match unsafe { step.run() } {
+                Ok(value) => values.push(value),
+                Err(err) => return (values, Some(err)),
+            }
+        }
+        (values, None)
+    });
+    if let Some(err) = failure {
+        let index = values.len();
+        let note = format!(" (ops 0..{} completed)", index);
+        return Err(batch_error(py, index, dispatch_error_to_py(py, err), &note));
+    }
+    Ok(values.into_iter().map(|value| value.map_or_else(|| py.None(), |value| scalar_to_py(py, value))).collect())
+}
+
 // ============================================================================
 // Backend Control
 // ============================================================================
 
+/// BackendError, which is also a NotImplementedError and RuntimeError (what
+/// unavailable backends and an empty policy stack raised before)
+fn policy_error_to_py(err: crate::backend::PolicyError) -> PyErr {
+    FfiError::from(err).into()
+}
+
+fn dispatch_error_to_py(py: Python, err: crate::ops::DispatchError) -> PyErr {
+    use crate::ops::DispatchError;
+    match err {
+        DispatchError::Policy(err) => policy_error_to_py(err),
+        DispatchError::Cancelled(reason) => cancelled_to_py(py, reason),
+    }
+}
+
+/// TimeoutError for a passed deadline; KeyboardInterrupt when Ctrl-C stopped
+/// the op, else CancelledError
+fn cancelled_to_py(py: Python, reason: crate::scheduler::cancel::Cancelled) -> PyErr {
+    use crate::scheduler::cancel::Cancelled;
+    match reason {
+        Cancelled::DeadlineExceeded => pyo3::exceptions::PyTimeoutError::new_err(reason.to_string()),
+        Cancelled::Requested => match py.check_signals() {
+            Err(err) => err,
+            Ok(()) => FfiError::Cancelled(reason.to_string()).into(),
+        },
+    }
+}
+
 #[pyfunction]
-fn set_backend_policy(policy: u8) -> PyResult<()> {
-    use crate::backend::{set_policy, BackendPolicy};
-    let p = match policy {
-        1 => BackendPolicy::OPENBLAS,
-        2 => BackendPolicy::BLAS,
-        _ => BackendPolicy::DEFAULT,
-    };
-    set_policy(p);
-    Ok(())
+fn set_backend_policy(policy: i64) -> PyResult<()> {
+    use crate::backend::set_policy_from_u8;
+    let value = u8::try_from(policy).map_err(|_| {
+        FfiError::Backend(format!("Unknown backend policy {}", policy))
+    })?;
+    set_policy_from_u8(value).map_err(policy_error_to_py)
 }
 
 #[pyfunction]
@@ -414,9 +1957,1370 @@ fn get_backend_policy() -> PyResult<u8> {
     Ok(get_policy() as u8)
 }
 
+/// Set the backend policy by name ("auto"/"default", "openblas", "blas", "cuda"; case-insensitive)
 #[pyfunction]
-fn explain_last_dispatch() -> String {
-    crate::backend::get_last_dispatch()
+fn set_backend_policy_name(name: &str) -> PyResult<()> {
+    use crate::backend::{set_policy, BackendPolicy, VALID_POLICY_NAMES};
+    let p = BackendPolicy::from_name(name).ok_or_else(|| {
+        FfiError::Backend(format!(
+            "Unknown backend policy '{}' (valid names: {})", name, VALID_POLICY_NAMES.join(", ")
+        ))
+    })?;
+    set_policy(p).map_err(policy_error_to_py)
+}
+
+/// Canonical lowercase name of the current backend policy
+#[pyfunction]
+fn get_backend_policy_name() -> PyResult<&'static str> {
+    Ok(crate::backend::get_policy().name())
+}
+
+/// Override the effective policy until the matching pop_backend_policy()
+#[pyfunction]
+fn push_backend_policy(policy: i64) -> PyResult<()> {
+    use crate::backend::{push_policy, BackendPolicy, PolicyError};
+    let value = u8::try_from(policy).map_err(|_| {
+        FfiError::Backend(format!("Unknown backend policy {}", policy))
+    })?;
+    let p = BackendPolicy::from_u8(value).ok_or(PolicyError::Unknown(value)).map_err(policy_error_to_py)?;
+    push_policy(p).map_err(policy_error_to_py)
+}
+
+/// Drop the most recent override and return it (RuntimeError if none is active)
+#[pyfunction]
+fn pop_backend_policy() -> PyResult<u8> {
+    crate::backend::pop_policy()
+        .map(|p| p as u8)
+        .map_err(policy_error_to_py)
+}
+
+/// Force a policy for one operation (e.g. "matmul"); None clears the override
+///
+/// Unlike set_backend_policy, unavailable backends are accepted here and make
+/// the operation itself raise NotImplementedError.
+#[pyfunction]
+fn set_operation_policy(op: &str, policy: Option<i64>) -> PyResult<()> {
+    use crate::backend::{BackendPolicy, POLICY_OPERATIONS};
+    if !POLICY_OPERATIONS.contains(&op) {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Unknown operation '{}' (expected one of: {})", op, POLICY_OPERATIONS.join(", ")
+        )));
+    }
+    let p = match policy {
+        Some(value) => Some(
+            u8::try_from(value).ok().and_then(BackendPolicy::from_u8).ok_or_else(|| {
+                FfiError::Backend(format!("Unknown backend policy {}", value))
+            })?
+        ),
+        None => None,
+    };
+    crate::backend::set_operation_policy(op, p);
+    Ok(())
+}
+
+/// Whether a usable CUDA device is present (always False until CUDA kernels land)
+#[pyfunction]
+fn cuda_available() -> PyResult<bool> {
+    Ok(crate::backend::cuda_available())
+}
+
+/// Registered matmul backends: one dict per backend (id, name, available)
+#[pyfunction]
+fn list_backends(py: Python) -> PyResult<PyObject> {
+    use crate::backend::{registered_backends, DispatchDims};
+    use pyo3::types::{PyDict, PyList};
+
+    crate::ops::matmul::register_builtin_backends();
+
+    let rows = PyList::empty(py);
+    for backend in registered_backends() {
+        let row = PyDict::new(py);
+        row.set_item("id", backend.id())?;
+        row.set_item("name", backend.name())?;
+        row.set_item("available", backend.supports("matmul", DispatchDims::Matrix(1, 1, 1)))?;
+        rows.append(row)?;
+    }
+    Ok(rows.into())
+}
+
+/// Learn per-size-bucket matmul routing from a profile report (get_profile_report(include_events=True))
+///
+/// Returns the number of buckets installed.
+#[pyfunction]
+fn build_dispatch_table_from_report(json: &str) -> PyResult<usize> {
+    crate::backend::dispatch_table::build_dispatch_table_from_report(json)
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Installed dispatch table: one dict per (operation, bucket)
+#[pyfunction]
+fn get_dispatch_table(py: Python) -> PyResult<PyObject> {
+    use crate::backend::backend_name;
+    use pyo3::types::{PyDict, PyList};
+
+    let rows = PyList::empty(py);
+    for entry in crate::backend::dispatch_table::get_dispatch_table() {
+        let row = PyDict::new(py);
+        row.set_item("operation", &entry.operation)?;
+        row.set_item("bucket", entry.bucket)?;
+        row.set_item("backend", backend_name(entry.backend_id))?;
+        row.set_item("backend_id", entry.backend_id)?;
+        row.set_item("avg_time_us", entry.avg_time_us)?;
+        rows.append(row)?;
+    }
+    Ok(rows.into())
+}
+
+#[pyfunction]
+fn clear_dispatch_table() -> PyResult<()> {
+    crate::backend::dispatch_table::clear_dispatch_table();
+    Ok(())
+}
+
+/// Current policy, per-op overrides, thresholds and auto-tune table as JSON
+#[pyfunction]
+fn export_backend_config() -> PyResult<String> {
+    Ok(crate::backend::config::export_backend_config())
+}
+
+/// Restore settings from export_backend_config(); nothing is applied if any field is invalid
+#[pyfunction]
+fn import_backend_config(json: &str) -> PyResult<()> {
+    crate::backend::config::import_backend_config(json)
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Learn the faster matmul backend per size bucket (DEFAULT policy only)
+#[pyfunction]
+#[pyo3(signature = (trials=crate::backend::autotune::DEFAULT_AUTO_TUNE_TRIALS))]
+fn enable_auto_tune(trials: usize) -> PyResult<()> {
+    crate::backend::autotune::enable_auto_tune(trials);
+    Ok(())
+}
+
+#[pyfunction]
+fn disable_auto_tune() -> PyResult<()> {
+    crate::backend::autotune::disable_auto_tune();
+    Ok(())
+}
+
+#[pyfunction]
+fn reset_auto_tune() -> PyResult<()> {
+    crate::backend::autotune::reset_auto_tune();
+    Ok(())
+}
+
+/// Learned auto-tune table: one dict per (operation, bucket)
+#[pyfunction]
+fn get_auto_tune_table(py: Python) -> PyResult<PyObject> {
+    use crate::backend::backend_name;
+    use pyo3::types::{PyDict, PyList};
+
+    let rows = PyList::empty(py);
+    for entry in crate::backend::autotune::get_auto_tune_table() {
+        let row = PyDict::new(py);
+        row.set_item("operation", &entry.operation)?;
+        row.set_item("bucket", entry.bucket)?;
+        row.set_item("choice", entry.choice.map(backend_name))?;
+        let samples = PyDict::new(py);
+        for (backend_id, sample) in entry.samples.iter().enumerate() {
+            samples.set_item(
+                backend_name(backend_id as u8),
+                (sample.count, sample.mean().map(|d| d.as_secs_f64() * 1e6)),
+            )?;
+        }
+        row.set_item("samples", samples)?;
+        rows.append(row)?;
+    }
+    Ok(rows.into())
+}
+
+/// Limit the BLAS backend to `n` threads (0 = all cores), applied before the next BLAS dispatch
+#[pyfunction]
+fn set_backend_num_threads(n: i64) -> PyResult<()> {
+    if n < 0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            format!("Thread count must be >= 0 (0 = all cores), got {}", n)
+        ));
+    }
+    if n > i32::MAX as i64 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            format!("Thread count {} is too large", n)
+        ));
+    }
+    crate::backend::set_blas_num_threads(n as usize);
+    Ok(())
+}
+
+#[pyfunction]
+fn get_backend_num_threads() -> PyResult<usize> {
+    Ok(crate::backend::get_blas_num_threads())
+}
+
+/// Resize corepy's thread pool (rebuilt; running operations finish on the old one)
+#[pyfunction]
+fn set_num_threads(n: i64) -> PyResult<()> {
+    if n < 1 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            format!("Thread count must be >= 1, got {}", n)
+        ));
+    }
+    crate::scheduler::rayon_pool::set_num_threads(n as usize)
+        .map_err(pyo3::exceptions::PyRuntimeError::new_err)
+}
+
+/// corepy thread pool size (the size it will be built with, before first use)
+#[pyfunction]
+fn get_num_threads() -> PyResult<usize> {
+    Ok(crate::scheduler::rayon_pool::num_threads())
+}
+
+/// Resize this thread's scratch arena now; pool workers and later threads
+/// switch to `bytes` at their next operation
+#[pyfunction]
+fn set_arena_size(bytes: i64) -> PyResult<()> {
+    use crate::scheduler::arena::ArenaError;
+    if bytes < 1 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            format!("Arena size must be >= 1 byte, got {}", bytes)
+        ));
+    }
+    crate::scheduler::arena::set_arena_size(bytes as usize).map_err(|err| match err {
+        ArenaError::ZeroSize => pyo3::exceptions::PyValueError::new_err(err.to_string()),
+        ArenaError::InUse(_) => pyo3::exceptions::PyRuntimeError::new_err(err.to_string()),
+    })
+}
+
+// Runtime notices forwarded to the "corepy.runtime" logger by a helper thread,
+// so raising one never waits on the GIL
+lazy_static::lazy_static! {
+    static ref RUNTIME_LOG: std::sync::mpsc::Sender<(crate::scheduler::rayon_pool::LogLevel, String)> = {
+        use crate::scheduler::rayon_pool::LogLevel;
+
+        let (tx, rx) = std::sync::mpsc::channel::<(LogLevel, String)>();
+        std::thread::Builder::new()
+            .name("corepy-log".to_string())
+            .spawn(move || {
+                for (level, message) in rx {
+                    Python::with_gil(|py| {
+                        let method = match level {
+                            LogLevel::Info => "info",
+                            LogLevel::Warning => "warning",
+                        };
+                        let _ = py.import("logging")
+                            .and_then(|logging| logging.call_method1("getLogger", ("corepy.runtime",)))
+                            .and_then(|logger| logger.call_method1(method, (message,)));
+                    });
+                }
+            })
+            .expect("failed to start corepy log thread");
+        tx
+    };
+}
+
+/// Build corepy's thread pool now rather than on first use
+///
+/// `num_threads` sizes the pool (default: COREPY_NUM_THREADS or all cores).
+/// With `quiet` runtime notices are dropped; otherwise they go to the
+/// "corepy.runtime" logger. Only the first call builds anything; every call
+/// returns the resulting configuration.
+#[pyfunction]
+#[pyo3(signature = (num_threads=None, quiet=true))]
+fn initialize_runtime(py: Python, num_threads: Option<usize>, quiet: bool) -> PyResult<PyObject> {
+    use crate::scheduler::rayon_pool;
+
+    if num_threads == Some(0) {
+        return Err(pyo3::exceptions::PyValueError::new_err("num_threads must be at least 1"));
+    }
+    let hook: rayon_pool::LogHook = if quiet {
+        std::sync::Arc::new(|_, _| {})
+    } else {
+        std::sync::Arc::new(|level, message| {
+            let _ = RUNTIME_LOG.send((level, message.to_string()));
+        })
+    };
+    rayon_pool::set_log_hook(Some(hook));
+    py.allow_threads(|| rayon_pool::initialize(num_threads))
+        .map_err(pyo3::exceptions::PyRuntimeError::new_err)?;
+
+    let caps = crate::backend::capabilities::get_capabilities();
+    let dict = pyo3::types::PyDict::new(py);
+    dict.set_item("num_threads", rayon_pool::num_threads())?;
+    dict.set_item("arena_size", caps.arena_size)?;
+    dict.set_item("blas_enabled", caps.blas_enabled)?;
+    dict.set_item("blas_vendor", &caps.blas_vendor)?;
+    dict.set_item("thread_affinity", rayon_pool::get_thread_affinity().mode.name())?;
+    Ok(dict.into())
+}
+
+/// How a parallel loop over `count` elements of `elem_size` bytes is chunked
+#[pyfunction]
+fn get_chunking_info(py: Python, count: usize, elem_size: usize) -> PyResult<PyObject> {
+    if elem_size == 0 {
+        return Err(pyo3::exceptions::PyValueError::new_err("elem_size must be at least 1"));
+    }
+    let info = crate::scheduler::chunking::plan(count, elem_size);
+    let dict = pyo3::types::PyDict::new(py);
+    dict.set_item("count", info.count)?;
+    dict.set_item("elem_size", info.elem_size)?;
+    dict.set_item("num_threads", info.num_threads)?;
+    dict.set_item("target_bytes", info.target_bytes)?;
+    dict.set_item("chunk_len", info.chunk_len)?;
+    dict.set_item("num_chunks", info.num_chunks)?;
+    Ok(dict.into())
+}
+
+/// NUMA nodes (id and CPUs) and the node index each worker is grouped under
+#[pyfunction]
+fn get_numa_info(py: Python) -> PyResult<PyObject> {
+    use crate::scheduler::numa;
+
+    // Worker grouping is decided when the pool starts
+    py.allow_threads(crate::scheduler::rayon_pool::init_thread_pool);
+    let nodes = pyo3::types::PyList::empty(py);
+    for node in &numa::topology().nodes {
+        let entry = pyo3::types::PyDict::new(py);
+        entry.set_item("id", node.id)?;
+        entry.set_item("cpus", node.cpus.clone())?;
+        nodes.append(entry)?;
+    }
+    let dict = pyo3::types::PyDict::new(py);
+    dict.set_item("nodes", nodes)?;
+    dict.set_item("worker_nodes", numa::get_worker_nodes())?;
+    Ok(dict.into())
+}
+
+// Progress callback and the minimum time between its calls
+lazy_static::lazy_static! {
+    static ref PROGRESS_CALLBACK: parking_lot::Mutex<Option<(PyObject, std::time::Duration)>> =
+        parking_lot::Mutex::new(None);
+}
+
+/// Register a callable receiving (done, total) work units while chunked ops
+/// run (None unregisters)
+///
+/// Called from a helper thread holding the GIL, at most every
+/// `min_interval_ms` while the op runs and once when it finishes.
+#[pyfunction]
+#[pyo3(signature = (callback, min_interval_ms=100))]
+fn set_progress_callback(py: Python, callback: Option<PyObject>, min_interval_ms: u64) -> PyResult<()> {
+    if let Some(callback) = &callback {
+        if !callback.as_ref(py).is_callable() {
+            return Err(pyo3::exceptions::PyTypeError::new_err("progress callback must be callable"));
+        }
+    }
+    let interval = std::time::Duration::from_millis(min_interval_ms.max(1));
+    *PROGRESS_CALLBACK.lock() = callback.map(|callback| (callback, interval));
+    Ok(())
+}
+
+/// (done, total) work units of the most recently started chunked operation
+#[pyfunction]
+fn get_operation_progress() -> (u64, u64) {
+    crate::scheduler::progress::get()
+}
+
+/// Pass the current progress to `callback`; exceptions go to sys.unraisablehook
+fn report_progress(py: Python, callback: &PyObject) {
+    if let Err(err) = callback.call1(py, crate::scheduler::progress::get()) {
+        err.write_unraisable(py, Some(callback.as_ref(py)));
+    }
+}
+
+/// py.allow_threads(work), reporting progress to the registered callback
+/// while `work` runs; Ctrl-C meanwhile cancels `work`
+fn allow_threads_with_progress<T: Send>(py: Python, work: impl FnOnce() -> T + Send) -> T {
+    let _interrupt = crate::scheduler::cancel::InterruptGuard::new();
+    let registered = PROGRESS_CALLBACK.lock().as_ref().map(|(callback, interval)| (callback.clone_ref(py), *interval));
+    let Some((callback, interval)) = registered else {
+        return py.allow_threads(work);
+    };
+
+    let finished = (std::sync::Mutex::new(false), std::sync::Condvar::new());
+    let result = py.allow_threads(|| std::thread::scope(|scope| {
+        scope.spawn(|| {
+            let (lock, wake) = &finished;
+            loop {
+                let done = lock.lock().unwrap_or_else(|e| e.into_inner());
+                let (done, waited) = wake.wait_timeout(done, interval).unwrap_or_else(|e| e.into_inner());
+                if *done {
+                    return;
+                }
+                drop(done);
+                if !waited.timed_out() {
+                    continue;
+                }
+                Python::with_gil(|py| report_progress(py, &callback));
+            }
+        });
+        let result = work();
+        *finished.0.lock().unwrap_or_else(|e| e.into_inner()) = true;
+        finished.1.notify_all();
+        result
+    }));
+    report_progress(py, &callback);
+    result
+}
+
+/// Counts of parallel vs serial dispatches since start (or the last reset)
+#[pyfunction]
+fn get_scheduler_stats(py: Python) -> PyResult<PyObject> {
+    let stats = crate::scheduler::stats::get_stats();
+    let dict = pyo3::types::PyDict::new(py);
+    dict.set_item("tasks_spawned", stats.tasks_spawned)?;
+    dict.set_item("parallel_dispatches", stats.parallel_dispatches)?;
+    dict.set_item("serial_dispatches", stats.serial_dispatches)?;
+    dict.set_item("total_parallel_elements", stats.total_parallel_elements)?;
+    dict.set_item("arena_heap_fallbacks", stats.arena_heap_fallbacks)?;
+    Ok(dict.into())
+}
+
+#[pyfunction]
+fn reset_scheduler_stats() {
+    crate::scheduler::stats::reset_stats();
+}
+
+/// Scratch arena usage: process-wide peak / failure / byte totals, plus the
+/// calling thread's own arena ("thread_*")
+#[pyfunction]
+fn get_arena_stats(py: Python) -> PyResult<PyObject> {
+    use crate::scheduler::arena;
+    let (peak_bytes, failed_allocs) = arena::global_arena_peaks();
+    let (arena_bytes, heap_fallback_bytes) = arena::allocation_counters();
+    let local = arena::arena_stats();
+    let dict = pyo3::types::PyDict::new(py);
+    dict.set_item("peak_bytes", peak_bytes)?;
+    dict.set_item("failed_allocations", failed_allocs)?;
+    dict.set_item("arena_bytes_allocated", arena_bytes)?;
+    dict.set_item("heap_fallback_bytes", heap_fallback_bytes)?;
+    dict.set_item("fallback_count", arena::heap_fallback_count())?;
+    dict.set_item("thread_used_bytes", local.used_bytes)?;
+    dict.set_item("thread_capacity", local.capacity)?;
+    dict.set_item("thread_peak_bytes", local.peak_bytes)?;
+    Ok(dict.into())
+}
+
+/// Restart peak_bytes tracking (e.g. before the workload being sized)
+#[pyfunction]
+fn reset_arena_peak() {
+    crate::scheduler::arena::reset_global_peak();
+}
+
+fn pool_error_to_py(err: crate::scheduler::buffer_pool::PoolError) -> PyErr {
+    FfiError::from(err).into()
+}
+
+/// Take a 64-byte-aligned buffer of at least `bytes` bytes from the output
+/// pool; returns a handle for buffer_ptr()/release_buffer()
+#[pyfunction]
+#[pyo3(signature = (bytes, dtype="float32", zeroed=false))]
+fn acquire_buffer(bytes: usize, dtype: &str, zeroed: bool) -> PyResult<u64> {
+    crate::scheduler::buffer_pool::acquire_buffer(bytes, dtype, zeroed).map_err(pool_error_to_py)
+}
+
+/// Give a buffer back to the pool; its address must not be used afterwards
+#[pyfunction]
+fn release_buffer(handle: u64) -> PyResult<()> {
+    crate::scheduler::buffer_pool::release_buffer(handle).map_err(pool_error_to_py)
+}
+
+/// Address of a held pool buffer, for the pointer-based ops
+#[pyfunction]
+fn buffer_ptr(handle: u64) -> PyResult<usize> {
+    crate::scheduler::buffer_pool::buffer_ptr(handle).map_err(pool_error_to_py)
+}
+
+/// Held / retained buffers and bytes, hit / miss / eviction counts
+#[pyfunction]
+fn pool_stats(py: Python) -> PyResult<PyObject> {
+    let stats = crate::scheduler::buffer_pool::pool_stats();
+    let dict = pyo3::types::PyDict::new(py);
+    dict.set_item("held_buffers", stats.held_buffers)?;
+    dict.set_item("held_bytes", stats.held_bytes)?;
+    dict.set_item("retained_buffers", stats.retained_buffers)?;
+    dict.set_item("retained_bytes", stats.retained_bytes)?;
+    dict.set_item("max_retained_bytes", stats.max_retained_bytes)?;
+    dict.set_item("hits", stats.hits)?;
+    dict.set_item("misses", stats.misses)?;
+    dict.set_item("evictions", stats.evictions)?;
+    Ok(dict.into())
+}
+
+fn buffer_error_to_py(err: crate::tensor::buffer::BufferError) -> PyErr {
+    FfiError::from(err).into()
+}
+
+/// Allocate a zeroed, 64-byte-aligned buffer of `nbytes` bytes owned by the
+/// runtime; returns a handle for buffer_address()/free_buffer(). `pinned`
+/// page-locks it (RuntimeWarning, and a pageable buffer, if the OS refuses)
+#[pyfunction]
+#[pyo3(signature = (nbytes, dtype="float32", pinned=false))]
+fn alloc_buffer(py: Python, nbytes: usize, dtype: &str, pinned: bool) -> PyResult<u64> {
+    use crate::tensor::buffer::BufferKind;
+
+    let kind = if pinned { BufferKind::Pinned } else { BufferKind::Pageable };
+    let handle = crate::tensor::buffer::alloc_buffer(nbytes, dtype, kind).map_err(buffer_error_to_py)?;
+    if pinned && nbytes != 0 && !crate::tensor::buffer::buffer_is_pinned(handle).map_err(buffer_error_to_py)? {
+        PyErr::warn(
+            py,
+            py.get_type::<pyo3::exceptions::PyRuntimeWarning>(),
+            "could not page-lock the buffer (RLIMIT_MEMLOCK?); it is allocated as pageable memory",
+            1,
+        )?;
+    }
+    Ok(handle)
+}
+
+/// Whether a runtime-owned buffer is page-locked
+#[pyfunction]
+fn buffer_is_pinned(handle: u64) -> PyResult<bool> {
+    crate::tensor::buffer::buffer_is_pinned(handle).map_err(buffer_error_to_py)
+}
+
+/// Cap the bytes pinned buffers may page-lock (default 1 GiB, or
+/// COREPY_PINNED_LIMIT); pinned allocations past it raise ValueError
+#[pyfunction]
+fn set_pinned_limit(nbytes: usize) {
+    crate::tensor::buffer::set_pinned_limit(nbytes);
+}
+
+/// Pinned bytes, the limit, and how many pinned allocations fell back to
+/// pageable memory
+#[pyfunction]
+fn pinned_memory_stats(py: Python) -> PyResult<PyObject> {
+    let stats = crate::tensor::buffer::pinned_stats();
+    let dict = pyo3::types::PyDict::new(py);
+    dict.set_item("pinned_bytes", stats.pinned_bytes)?;
+    dict.set_item("limit", stats.limit)?;
+    dict.set_item("lock_failures", stats.lock_failures)?;
+    Ok(dict.into())
+}
+
+/// Free a runtime-owned buffer; freeing it twice raises ValueError
+#[pyfunction]
+fn free_buffer(handle: u64) -> PyResult<()> {
+    crate::tensor::buffer::free_buffer(handle).map_err(buffer_error_to_py)
+}
+
+/// Address of a live runtime-owned buffer, for the pointer-based ops
+#[pyfunction]
+fn buffer_address(handle: u64) -> PyResult<usize> {
+    crate::tensor::buffer::buffer_address(handle).map_err(buffer_error_to_py)
+}
+
+// ============================================================================
+// Handle-Based Operations
+// ============================================================================
+// Tensors registered once and then referred to by handle: the registry checks
+// shapes and dtypes and keeps the owning array alive, so a stale handle
+// raises instead of reading freed memory. The raw-pointer entry points above
+// stay for callers that manage lifetimes themselves.
+
+fn tensor_error_to_py(err: crate::tensor::registry::TensorError) -> PyErr {
+    FfiError::from(err).into()
+}
+
+fn parse_dtype(name: &str) -> PyResult<crate::tensor::dtype::DType> {
+    crate::tensor::dtype::DType::from_name(name)
+        .ok_or_else(|| FfiError::DType(format!("unknown dtype '{}'", name)).into())
+}
+
+/// dtype code of a NumPy dtype name, for register_tensor()
+#[pyfunction]
+fn dtype_code(name: &str) -> PyResult<u8> {
+    parse_dtype(name).map(|dtype| dtype.code())
+}
+
+/// Register `shape` elements of `dtype_code` at `ptr`; `owner` (usually the
+/// array itself) is kept alive until unregister_tensor()
+#[pyfunction]
+#[pyo3(signature = (ptr, shape, dtype_code, owner=None))]
+fn register_tensor(ptr: usize, shape: Vec<usize>, dtype_code: u8, owner: Option<PyObject>) -> PyResult<u64> {
+    let owner = owner.map(|owner| Box::new(owner) as crate::tensor::registry::Owner);
+    crate::tensor::register_tensor(ptr, &shape, dtype_code, owner).map_err(tensor_error_to_py)
+}
+
+#[pyfunction]
+fn unregister_tensor(handle: u64) -> PyResult<()> {
+    crate::tensor::unregister_tensor(handle).map_err(tensor_error_to_py)
+}
+
+/// Record where tensor handles are created and unregistered, so using a
+/// stale handle reports both sites (slow; also COREPY_HANDLE_DEBUG=1)
+#[pyfunction]
+fn set_handle_debug(enabled: bool) {
+    crate::tensor::registry::set_handle_debug(enabled);
+}
+
+/// Metadata dict shared by tensor_info() and list_tensors()
+fn tensor_entry_dict<'py>(
+    py: Python<'py>,
+    handle: u64,
+    entry: &crate::tensor::registry::TensorEntry,
+) -> PyResult<&'py pyo3::types::PyDict> {
+    let info = &entry.info;
+    let itemsize = info.dtype.size() as isize;
+    let strides: Vec<isize> = info.layout.strides().iter().map(|&stride| stride * itemsize).collect();
+    let dict = pyo3::types::PyDict::new(py);
+    dict.set_item("handle", handle)?;
+    dict.set_item("shape", pyo3::types::PyTuple::new(py, info.shape().dims()))?;
+    dict.set_item("strides", pyo3::types::PyTuple::new(py, strides))?;
+    dict.set_item("c_contiguous", info.layout.is_c_contiguous())?;
+    dict.set_item("readonly", info.readonly)?;
+    dict.set_item("dtype", info.dtype.name())?;
+    dict.set_item("dtype_code", info.dtype.code())?;
+    dict.set_item("nbytes", info.nbytes())?;
+    dict.set_item("ptr", info.ptr)?;
+    dict.set_item("device", info.device.to_string())?;
+    dict.set_item("ownership", if entry.runtime_owned() { "runtime" } else { "borrowed" })?;
+    Ok(dict)
+}
+
+/// Shape, byte strides, dtype, byte size, address, device and ownership
+/// ("runtime" for constructor/loader results, "borrowed" for caller
+/// memory) of a registered tensor
+#[pyfunction]
+fn tensor_info(py: Python, handle: u64) -> PyResult<PyObject> {
+    let entry = crate::tensor::registry::lookup(handle).map_err(tensor_error_to_py)?;
+    Ok(tensor_entry_dict(py, handle, &entry)?.into())
+}
+
+/// tensor_info() of every live handle, from one consistent snapshot of
+/// the registry; for finding handles that were never unregistered
+#[pyfunction]
+fn list_tensors(py: Python) -> PyResult<Vec<PyObject>> {
+    let snapshot = crate::tensor::list_tensors();
+    snapshot.iter().map(|(handle, entry)| Ok(tensor_entry_dict(py, *handle, entry)?.into())).collect()
+}
+
+/// Bytes in view across all live handles (views of one buffer each count)
+#[pyfunction]
+fn total_registered_bytes() -> usize {
+    crate::tensor::total_registered_bytes()
+}
+
+/// Register any buffer-protocol object (NumPy array, array.array,
+/// memoryview, ...) without copying. The buffer stays acquired, and its
+/// exporter alive, until tensor_release(); read-only buffers can't be
+/// used as outputs.
+#[pyfunction]
+fn tensor_from_buffer(obj: &PyAny) -> PyResult<u64> {
+    use crate::ffi::buffer_protocol::BufferView;
+
+    let view = BufferView::acquire(obj)?;
+    let dtype = crate::tensor::dtype::DType::from_buffer_format(view.format(), view.itemsize()).ok_or_else(|| {
+        pyo3::exceptions::PyValueError::new_err(format!(
+            "unsupported buffer format '{}' ({}-byte items)", view.format(), view.itemsize()
+        ))
+    })?;
+    let layout = crate::tensor::Layout::from_byte_strides(&view.shape(), &view.strides(), view.itemsize())
+        .map_err(|err| pyo3::exceptions::PyValueError::new_err(err.to_string()))?;
+    let (ptr, readonly) = (view.ptr(), view.readonly());
+    crate::tensor::registry::register_strided(ptr, layout, dtype, readonly, Some(Box::new(view)))
+        .map_err(tensor_error_to_py)
+}
+
+/// Unregister a tensor_from_buffer() handle, releasing the buffer once no
+/// view or running op still uses it
+#[pyfunction]
+fn tensor_release(handle: u64) -> PyResult<()> {
+    crate::tensor::unregister_tensor(handle).map_err(tensor_error_to_py)
+}
+
+fn npy_error_to_py(err: crate::tensor::io::NpyError) -> PyErr {
+    match err {
+        crate::tensor::io::NpyError::Io(err) => pyo3::exceptions::PyOSError::new_err(err.to_string()),
+        err => pyo3::exceptions::PyValueError::new_err(err.to_string()),
+    }
+}
+
+/// Write a tensor to `path` as an .npy file, with the GIL released.
+/// `source` is a tensor handle (any layout), or a raw address of
+/// C-contiguous data when `shape` and `dtype_code` are given.
+#[pyfunction]
+#[pyo3(signature = (path, source, shape=None, dtype_code=None))]
+fn save_npy(py: Python, path: std::path::PathBuf, source: u64, shape: Option<Vec<usize>>, dtype_code: Option<u8>) -> PyResult<()> {
+    use crate::tensor::registry::{lookup, TensorError, TensorInfo};
+    use crate::tensor::{Layout, TensorShape};
+
+    // A handle's entry is held until the write finishes, in case it is
+    // unregistered meanwhile
+    let mut entry = None;
+    let info = match (shape, dtype_code) {
+        (None, None) => &entry.insert(lookup(source).map_err(tensor_error_to_py)?).info,
+        (Some(shape), Some(dtype_code)) => {
+            let dtype = crate::tensor::dtype::DType::from_code(dtype_code)
+                .ok_or_else(|| tensor_error_to_py(TensorError::UnknownDtype(dtype_code)))?;
+            let shape = TensorShape::new(&shape).map_err(|err| tensor_error_to_py(TensorError::Shape(err)))?;
+            if source == 0 {
+                return Err(tensor_error_to_py(TensorError::NullPointer));
+            }
+            &TensorInfo {
+                ptr: source as usize,
+                layout: Layout::c_contiguous(shape),
+                dtype,
+                readonly: true,
+                device: crate::tensor::Device::Cpu,
+            }
+        }
+        _ => {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "save_npy: pass both shape and dtype_code with a raw address, or neither with a handle",
+            ))
+        }
+    };
+    py.allow_threads(|| unsafe { crate::tensor::io::save_npy(&path, info.ptr as *const u8, &info.layout, info.dtype) })
+        .map_err(npy_error_to_py)
+}
+
+/// Read an .npy file (GIL released) into a runtime-owned buffer and return
+/// its tensor handle
+#[pyfunction]
+fn load_npy(py: Python, path: std::path::PathBuf) -> PyResult<u64> {
+    let (buffer, shape) = py.allow_threads(|| crate::tensor::io::load_npy(&path)).map_err(npy_error_to_py)?;
+    crate::tensor::registry::register_buffer(buffer, shape).map_err(tensor_error_to_py)
+}
+
+fn safetensors_error_to_py(err: crate::tensor::io::SafetensorsError) -> PyErr {
+    match err {
+        crate::tensor::io::SafetensorsError::Io(err) => pyo3::exceptions::PyOSError::new_err(err.to_string()),
+        err => pyo3::exceptions::PyValueError::new_err(err.to_string()),
+    }
+}
+
+/// Write (name, handle) pairs to `path` as a .safetensors file, with the
+/// GIL released; tensors must be C-contiguous and names unique
+#[pyfunction]
+fn save_safetensors(py: Python, path: std::path::PathBuf, entries: Vec<(String, u64)>) -> PyResult<()> {
+    let held = entries
+        .iter()
+        .map(|(_, handle)| crate::tensor::registry::lookup(*handle))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(tensor_error_to_py)?;
+    let tensors: Vec<_> = entries.iter().zip(&held).map(|((name, _), entry)| (name.as_str(), &entry.info)).collect();
+    py.allow_threads(|| unsafe { crate::tensor::io::save_safetensors(&path, &tensors) })
+        .map_err(safetensors_error_to_py)
+}
+
+/// Read a .safetensors file (GIL released) into runtime-owned buffers;
+/// returns {name: handle}
+#[pyfunction]
+fn load_safetensors(py: Python, path: std::path::PathBuf) -> PyResult<PyObject> {
+    let tensors = py.allow_threads(|| crate::tensor::io::load_safetensors(&path)).map_err(safetensors_error_to_py)?;
+    let dict = pyo3::types::PyDict::new(py);
+    for (name, buffer, shape) in tensors {
+        let handle = crate::tensor::registry::register_buffer(buffer, shape).map_err(tensor_error_to_py)?;
+        dict.set_item(name, handle)?;
+    }
+    Ok(dict.into())
+}
+
+/// New zero-filled tensor handle (runtime-owned; free with unregister_tensor)
+#[pyfunction]
+#[pyo3(signature = (shape, dtype="float64"))]
+fn tensor_zeros(py: Python, shape: Vec<usize>, dtype: &str) -> PyResult<u64> {
+    let dtype = parse_dtype(dtype)?;
+    py.allow_threads(|| crate::tensor::tensor_zeros(&shape, dtype)).map_err(tensor_error_to_py)
+}
+
+/// New tensor handle filled with ones
+#[pyfunction]
+#[pyo3(signature = (shape, dtype="float64"))]
+fn tensor_ones(py: Python, shape: Vec<usize>, dtype: &str) -> PyResult<u64> {
+    let dtype = parse_dtype(dtype)?;
+    py.allow_threads(|| crate::tensor::tensor_ones(&shape, dtype)).map_err(tensor_error_to_py)
+}
+
+/// New 1-D tensor handle with the values of np.arange(start, stop, step)
+#[pyfunction]
+#[pyo3(signature = (start, stop, step=1.0, dtype="float64"))]
+fn tensor_arange(py: Python, start: f64, stop: f64, step: f64, dtype: &str) -> PyResult<u64> {
+    let dtype = parse_dtype(dtype)?;
+    py.allow_threads(|| crate::tensor::tensor_arange(start, stop, step, dtype)).map_err(tensor_error_to_py)
+}
+
+/// New 1-D tensor handle with the values of np.linspace()
+#[pyfunction]
+#[pyo3(signature = (start, stop, num=50, endpoint=true, dtype="float64"))]
+fn tensor_linspace(py: Python, start: f64, stop: f64, num: usize, endpoint: bool, dtype: &str) -> PyResult<u64> {
+    let dtype = parse_dtype(dtype)?;
+    py.allow_threads(|| crate::tensor::tensor_linspace(start, stop, num, endpoint, dtype)).map_err(tensor_error_to_py)
+}
+
+/// New tensor handle of uniform samples from [low, high); the same seed
+/// gives the same tensor for any thread count, None seeds from entropy
+#[pyfunction]
+#[pyo3(signature = (shape, low=0.0, high=1.0, seed=None, dtype="float32", max_threads=None))]
+fn tensor_random_uniform(
+    py: Python,
+    shape: Vec<usize>,
+    low: f64, high: f64,
+    seed: Option<u64>,
+    dtype: &str,
+    max_threads: Option<usize>
+) -> PyResult<u64> {
+    let dtype = parse_dtype(dtype)?;
+    check_max_threads(max_threads)?;
+    py.allow_threads(|| {
+        crate::scheduler::rayon_pool::with_max_threads(max_threads, || {
+            crate::tensor::tensor_random_uniform(&shape, low, high, seed, dtype)
+        })
+    })
+    .map_err(tensor_error_to_py)
+}
+
+/// New tensor handle of normal samples; seeding as tensor_random_uniform()
+#[pyfunction]
+#[pyo3(signature = (shape, mean=0.0, std=1.0, seed=None, dtype="float32", max_threads=None))]
+fn tensor_random_normal(
+    py: Python,
+    shape: Vec<usize>,
+    mean: f64, std: f64,
+    seed: Option<u64>,
+    dtype: &str,
+    max_threads: Option<usize>
+) -> PyResult<u64> {
+    let dtype = parse_dtype(dtype)?;
+    check_max_threads(max_threads)?;
+    py.allow_threads(|| {
+        crate::scheduler::rayon_pool::with_max_threads(max_threads, || {
+            crate::tensor::tensor_random_normal(&shape, mean, std, seed, dtype)
+        })
+    })
+    .map_err(tensor_error_to_py)
+}
+
+/// Fill `count` caller-owned elements at `ptr` with samples of
+/// `distribution` ("uniform" with params (low, high), or "normal" with
+/// (mean, std)); float32 or float64
+#[pyfunction]
+#[pyo3(signature = (ptr, count, distribution, seed=None, params=(0.0, 1.0), dtype="float32", max_threads=None))]
+#[allow(clippy::too_many_arguments)]
+fn tensor_random_fill(
+    py: Python,
+    ptr: usize, count: usize,
+    distribution: &str,
+    seed: Option<u64>,
+    params: (f64, f64),
+    dtype: &str,
+    max_threads: Option<usize>
+) -> PyResult<()> {
+    use crate::tensor::random::{entropy_seed, random_fill, Distribution};
+
+    let distribution = Distribution::from_name(distribution, params).map_err(tensor_error_to_py)?;
+    let dtype = parse_dtype(dtype)?;
+    if ptr == 0 {
+        return Err(FfiError::NullPointer("tensor_random_fill".to_string()).into());
+    }
+    check_pointers("tensor_random_fill", "random_fill", dtype, &[("ptr", ptr)])?;
+    check_extent("tensor_random_fill", count, dtype.size(), &["ptr"])?;
+    check_max_threads(max_threads)?;
+    let seed = seed.unwrap_or_else(entropy_seed);
+    py.allow_threads(|| {
+        crate::scheduler::rayon_pool::with_max_threads(max_threads, || unsafe {
+            random_fill(ptr as *mut u8, count, dtype, distribution, seed)
+        })
+    })
+    .map_err(tensor_error_to_py)
+}
+
+/// New handle onto `sizes[i]` elements of each axis from `offsets[i]`,
+/// every `steps[i]`-th; shares the memory (and owner) of `handle`
+#[pyfunction]
+fn tensor_view(handle: u64, offsets: Vec<usize>, sizes: Vec<usize>, steps: Vec<usize>) -> PyResult<u64> {
+    crate::tensor::tensor_view(handle, &offsets, &sizes, &steps).map_err(tensor_error_to_py)
+}
+
+/// New handle with the same elements in `shape`; the tensor must be
+/// C-contiguous
+#[pyfunction]
+fn tensor_reshape(handle: u64, shape: Vec<usize>) -> PyResult<u64> {
+    crate::tensor::tensor_reshape(handle, &shape).map_err(tensor_error_to_py)
+}
+
+/// New handle with the last two dimensions swapped, without copying
+#[pyfunction]
+fn tensor_transpose_view(handle: u64) -> PyResult<u64> {
+    crate::tensor::tensor_transpose_view(handle).map_err(tensor_error_to_py)
+}
+
+/// Contiguity of a view given NumPy byte strides, so callers can decide
+/// whether to copy before a flat kernel: C / Fortran contiguity and the
+/// elements per contiguous run
+#[pyfunction]
+fn check_contiguity(py: Python, shape: Vec<usize>, strides: Vec<isize>, itemsize: usize) -> PyResult<PyObject> {
+    let layout = crate::tensor::Layout::from_byte_strides(&shape, &strides, itemsize)
+        .map_err(|err| pyo3::exceptions::PyValueError::new_err(err.to_string()))?;
+    let dict = pyo3::types::PyDict::new(py);
+    dict.set_item("c_contiguous", layout.is_c_contiguous())?;
+    dict.set_item("f_contiguous", layout.is_f_contiguous())?;
+    dict.set_item("run_length", layout.run_length())?;
+    Ok(dict.into())
+}
+
+/// Check `out = a <op> b` by handle, then run it over each operand's
+/// layout; views take the contiguous kernel once per contiguous run
+fn elementwise_by_handle(py: Python, fn_name: &'static str, a: u64, b: u64, out: u64, op: crate::ops::broadcast::BinaryOp) -> PyResult<()> {
+    use crate::tensor::registry::{elementwise_operands, lookup};
+    let (a, b, out) = (
+        lookup(a).map_err(tensor_error_to_py)?,
+        lookup(b).map_err(tensor_error_to_py)?,
+        lookup(out).map_err(tensor_error_to_py)?,
+    );
+    let count = elementwise_operands(fn_name, &a.info, &b.info, &out.info).map_err(tensor_error_to_py)?;
+    if count == 0 {
+        return Ok(());
+    }
+
+    // PROFILING
+    let mut scope = crate::profiler::ProfileScope::new(
+        GLOBAL_PROFILER.clone(),
+        op.name().to_string(),
+        "CPU".to_string(),
+        count,
+    );
+
+    // The entries stay held until the kernel returns
+    scope.gil_released(|| py.allow_threads(|| unsafe {
+        crate::ops::broadcast::binary_broadcast_f32(
+            a.info.ptr as *const f32, &a.info.layout,
+            b.info.ptr as *const f32, &b.info.layout,
+            out.info.ptr as *mut f32, &out.info.layout,
+            op,
+        )
+    })).map_err(|err| pyo3::exceptions::PyValueError::new_err(format!("{} in {}", err, fn_name)))?;
+    Ok(())
+}
+
+#[pyfunction]
+fn op_add(py: Python, a: u64, b: u64, out: u64) -> PyResult<()> {
+    elementwise_by_handle(py, "op_add", a, b, out, crate::ops::broadcast::BinaryOp::Add)
+}
+
+#[pyfunction]
+fn op_sub(py: Python, a: u64, b: u64, out: u64) -> PyResult<()> {
+    elementwise_by_handle(py, "op_sub", a, b, out, crate::ops::broadcast::BinaryOp::Sub)
+}
+
+#[pyfunction]
+fn op_mul(py: Python, a: u64, b: u64, out: u64) -> PyResult<()> {
+    elementwise_by_handle(py, "op_mul", a, b, out, crate::ops::broadcast::BinaryOp::Mul)
+}
+
+#[pyfunction]
+fn op_div(py: Python, a: u64, b: u64, out: u64) -> PyResult<()> {
+    elementwise_by_handle(py, "op_div", a, b, out, crate::ops::broadcast::BinaryOp::Div)
+}
+
+/// Address of a contiguous copy of `info`'s elements: the tensor itself
+/// when it already is one, else a gathered `Buffer` held in `copy`
+fn contiguous_ptr(
+    info: &crate::tensor::registry::TensorInfo,
+    copy: &mut Option<crate::tensor::buffer::Buffer>,
+) -> PyResult<usize> {
+    if info.layout.is_c_contiguous() {
+        return Ok(info.ptr);
+    }
+    let buffer = unsafe { crate::tensor::buffer::Buffer::gather(info.ptr as *const u8, &info.layout, info.dtype) }
+        .map_err(|err| pyo3::exceptions::PyValueError::new_err(err.to_string()))?;
+    Ok(copy.insert(buffer).as_ptr() as usize)
+}
+
+/// Reduce every element of a registered tensor (any layout) with the
+/// registered (op, dtype) kernel; op codes as in tensor_reduce()
+#[pyfunction]
+#[pyo3(signature = (handle, op_code, max_threads=None, timeout_ms=None))]
+fn op_reduce(py: Python, handle: u64, op_code: u8, max_threads: Option<usize>, timeout_ms: Option<u64>) -> PyResult<PyObject> {
+    let entry = crate::tensor::registry::lookup(handle).map_err(tensor_error_to_py)?;
+    let info = &entry.info;
+    // Reductions don't care about element order, so Fortran order is as
+    // good as C order
+    let mut copy = None;
+    let ptr = if info.layout.is_f_contiguous() { info.ptr } else { contiguous_ptr(info, &mut copy)? };
+    let count = info.shape().numel();
+    let value = reduce_impl(py, "op_reduce", ptr, count, op_code, info.dtype.code(), max_threads, timeout_ms)?;
+    Ok(scalar_to_py(py, value))
+}
+
+/// out (m x n) = a (m x k) @ b (k x n), all float32
+#[pyfunction]
+#[pyo3(signature = (a, b, out, max_threads=None, timeout_ms=None))]
+fn op_matmul(py: Python, a: u64, b: u64, out: u64, max_threads: Option<usize>, timeout_ms: Option<u64>) -> PyResult<()> {
+    use crate::tensor::registry::{lookup, matmul_operands};
+    let (a, b, out) = (
+        lookup(a).map_err(tensor_error_to_py)?,
+        lookup(b).map_err(tensor_error_to_py)?,
+        lookup(out).map_err(tensor_error_to_py)?,
+    );
+    let (m, k, n) = matmul_operands("op_matmul", &a.info, &b.info, &out.info).map_err(tensor_error_to_py)?;
+    if !out.info.layout.is_c_contiguous() {
+        return Err(tensor_error_to_py(crate::tensor::registry::TensorError::NotContiguous { op: "op_matmul" }));
+    }
+    // Strided inputs are packed first; the kernel reads row-major operands
+    let (mut a_copy, mut b_copy) = (None, None);
+    let a_ptr = contiguous_ptr(&a.info, &mut a_copy)?;
+    let b_ptr = contiguous_ptr(&b.info, &mut b_copy)?;
+    let out_ptr = out.info.ptr;
+    matmul_2d_f32_impl(py, "op_matmul", "matmul", a_ptr, b_ptr, out_ptr, m, k, n, false, max_threads, timeout_ms, None)
+}
+
+// ============================================================================
+// NumPy Array and Buffer Operations
+// ============================================================================
+// Take arrays (or any float32 buffer-protocol exporter) directly instead of
+// addresses: shapes, dtypes and contiguity come from the objects
+// themselves. Strided inputs are rejected unless copy=True packs them;
+// outputs are never copied, so they must be C-contiguous and writable.
+// The tensor_*_buffer variants treat any contiguous float32 buffer
+// (array.array('f'), memoryview.cast('f'), mmap, ...) as a flat vector.
+// Every argument's buffer stays acquired until the op returns, so the
+// exporter can't release or resize the memory mid-kernel.
+
+/// A float32 array argument, packed C-contiguous if it had to be copied
+struct ArrayArg {
+    view: crate::ffi::buffer_protocol::BufferView,
+    dims: Vec<usize>,
+    packed: Option<crate::tensor::buffer::Buffer>,
+}
+
+fn array_error(fn_name: &str, message: String) -> PyErr {
+    pyo3::exceptions::PyValueError::new_err(format!("{}: {}", fn_name, message))
+}
+
+/// Acquire `obj` as a float32 array named `name`, of rank `ndim` if given
+fn float32_view(
+    fn_name: &str,
+    name: &str,
+    obj: &PyAny,
+    ndim: Option<usize>,
+) -> PyResult<(crate::ffi::buffer_protocol::BufferView, crate::tensor::Layout)> {
+    let view = crate::ffi::buffer_protocol::BufferView::acquire(obj)?;
+    if DType::from_buffer_format(view.format(), view.itemsize()) != Some(DType::Float32) {
+        return Err(FfiError::DType(format!(
+            "{}: {} must hold float32 items (format 'f'), got format '{}' ({}-byte items)",
+            fn_name, name, view.format(), view.itemsize()
+        )).into());
+    }
+    check_pointers(fn_name, "element", DType::Float32, &[(name, view.ptr())])?;
+    let dims = view.shape();
+    if let Some(ndim) = ndim.filter(|&ndim| ndim != dims.len()) {
+        return Err(array_error(fn_name, format!("{} must be {}-D, got {}-D", name, ndim, dims.len())));
+    }
+    let layout = crate::tensor::Layout::from_byte_strides(&dims, &view.strides(), view.itemsize())
+        .map_err(|err| array_error(fn_name, format!("{}: {}", name, err)))?;
+    Ok((view, layout))
+}
+
+impl ArrayArg {
+    /// Acquire `obj` as the input `name`, copying it if strided and `copy`
+    fn acquire(fn_name: &str, name: &str, obj: &PyAny, ndim: usize, copy: bool) -> PyResult<Self> {
+        let (view, layout) = float32_view(fn_name, name, obj, Some(ndim))?;
+        let packed = if layout.is_c_contiguous() {
+            None
+        } else if copy {
+            let dtype = crate::tensor::dtype::DType::Float32;
+            let buffer = unsafe { crate::tensor::buffer::Buffer::gather(view.ptr() as *const u8, &layout, dtype) };
+            Some(buffer.map_err(|err| array_error(fn_name, err.to_string()))?)
+        } else {
+            return Err(array_error(fn_name, format!(
+                "{} is not C-contiguous; pass copy=True to copy it, or use np.ascontiguousarray()", name
+            )));
+        };
+        Ok(ArrayArg { view, dims: layout.shape().dims().to_vec(), packed })
+    }
+
+    /// Acquire `obj` as the output `name`, which must have shape `dims`
+    fn output(fn_name: &str, name: &str, obj: &PyAny, dims: &[usize]) -> PyResult<Self> {
+        let (view, layout) = float32_view(fn_name, name, obj, Some(dims.len()))?;
+        if view.readonly() {
+            return Err(array_error(fn_name, format!("{} is read-only", name)));
+        }
+        if layout.shape().dims() != dims {
+            return Err(array_error(fn_name, format!(
+                "{} has shape {:?}, expected {:?}", name, layout.shape().dims(), dims
+            )));
+        }
+        if !layout.is_c_contiguous() {
+            return Err(array_error(fn_name, format!("{} must be C-contiguous (outputs are never copied)", name)));
+        }
+        Ok(ArrayArg { view, dims: dims.to_vec(), packed: None })
+    }
+
+    /// Acquire `obj` as a flat float32 buffer of any shape; it must be
+    /// C-contiguous, and writable when `writable`
+    fn flat(fn_name: &str, name: &str, obj: &PyAny, writable: bool) -> PyResult<Self> {
+        let (view, layout) = float32_view(fn_name, name, obj, None)?;
+        if writable && view.readonly() {
+            return Err(array_error(fn_name, format!(
+                "{} is read-only (e.g. bytes, or an mmap opened with ACCESS_READ); pass a writable buffer", name
+            )));
+        }
+        if !layout.is_c_contiguous() {
+            return Err(array_error(fn_name, format!("{} must be a contiguous buffer", name)));
+        }
+        Ok(ArrayArg { view, dims: vec![layout.shape().numel()], packed: None })
+    }
+
+    /// Address of the C-contiguous elements
+    fn ptr(&self) -> usize {
+        match &self.packed {
+            Some(buffer) => buffer.as_ptr() as usize,
+            None => self.view.ptr(),
+        }
+    }
+}
+
+/// out = a + b for 1-D float32 arrays of equal length
+#[pyfunction]
+#[pyo3(signature = (a, b, out, copy=false))]
+fn np_add_f32(py: Python, a: &PyAny, b: &PyAny, out: &PyAny, copy: bool) -> PyResult<()> {
+    use crate::ops::broadcast::BinaryOp;
+
+    let a = ArrayArg::acquire("np_add_f32", "a", a, 1, copy)?;
+    let b = ArrayArg::acquire("np_add_f32", "b", b, 1, copy)?;
+    crate::ops::same_length(&[("a", a.dims[0]), ("b", b.dims[0])])
+        .map_err(|err| extent_error_to_py("np_add_f32", err))?;
+    let out = ArrayArg::output("np_add_f32", "out", out, &a.dims)?;
+    let dtype = crate::tensor::dtype::DType::Float32.code();
+    binary_impl(py, "np_add_f32", a.ptr(), b.ptr(), out.ptr(), a.dims[0], BinaryOp::Add as u8, dtype, None)
+}
+
+/// Sum of a 1-D float32 array
+#[pyfunction]
+#[pyo3(signature = (a, copy=false, max_threads=None, timeout_ms=None))]
+fn np_sum_f32(py: Python, a: &PyAny, copy: bool, max_threads: Option<usize>, timeout_ms: Option<u64>) -> PyResult<f32> {
+    use crate::ops::registry::{ReduceOp, Scalar};
+
+    let a = ArrayArg::acquire("np_sum_f32", "a", a, 1, copy)?;
+    reduce_typed(py, "np_sum_f32", a.ptr(), a.dims[0], ReduceOp::Sum, DType::Float32, max_threads, timeout_ms, Scalar::as_f32)
+}
+
+/// a (m x k) @ b (k x n) for 2-D float32 arrays, into a new C-ordered
+/// float32 array
+#[pyfunction]
+#[pyo3(signature = (a, b, copy=false, max_threads=None, timeout_ms=None))]
+fn np_matmul_f32(
+    py: Python,
+    a: &PyAny,
+    b: &PyAny,
+    copy: bool,
+    max_threads: Option<usize>,
+    timeout_ms: Option<u64>,
+) -> PyResult<PyObject> {
+    let a = ArrayArg::acquire("np_matmul_f32", "a", a, 2, copy)?;
+    let b = ArrayArg::acquire("np_matmul_f32", "b", b, 2, copy)?;
+    let ((m, k), (k2, n)) = ((a.dims[0], a.dims[1]), (b.dims[0], b.dims[1]));
+    if k != k2 {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "np_matmul_f32: shapes {:?} and {:?} are incompatible", a.dims, b.dims
+        )));
+    }
+    let result = py.import("numpy")?.call_method1("empty", ((m, n), "float32"))?;
+    let out = ArrayArg::output("np_matmul_f32", "out", result, &[m, n])?;
+    let (a_ptr, b_ptr, out_ptr) = (a.ptr(), b.ptr(), out.ptr());
+    let fn_name = "np_matmul_f32";
+    matmul_2d_f32_impl(py, fn_name, "matmul", a_ptr, b_ptr, out_ptr, m, k, n, false, max_threads, timeout_ms, None)?;
+    Ok(result.into())
+}
+
+/// out = a + b over contiguous float32 buffers of equal length
+#[pyfunction]
+fn tensor_add_buffer(py: Python, a: &PyAny, b: &PyAny, out: &PyAny) -> PyResult<()> {
+    use crate::ops::broadcast::BinaryOp;
+
+    let a = ArrayArg::flat("tensor_add_buffer", "a", a, false)?;
+    let b = ArrayArg::flat("tensor_add_buffer", "b", b, false)?;
+    let out = ArrayArg::flat("tensor_add_buffer", "out", out, true)?;
+    let len = crate::ops::same_length(&[("a", a.dims[0]), ("b", b.dims[0]), ("out", out.dims[0])])
+        .map_err(|err| extent_error_to_py("tensor_add_buffer", err))?;
+    let dtype = crate::tensor::dtype::DType::Float32.code();
+    binary_impl(py, "tensor_add_buffer", a.ptr(), b.ptr(), out.ptr(), len, BinaryOp::Add as u8, dtype, None)
+}
+
+/// Sum of a contiguous float32 buffer
+#[pyfunction]
+#[pyo3(signature = (obj, max_threads=None, timeout_ms=None))]
+fn tensor_sum_buffer(py: Python, obj: &PyAny, max_threads: Option<usize>, timeout_ms: Option<u64>) -> PyResult<f32> {
+    use crate::ops::registry::{ReduceOp, Scalar};
+
+    let data = ArrayArg::flat("tensor_sum_buffer", "obj", obj, false)?;
+    let (ptr, count) = (data.ptr(), data.dims[0]);
+    reduce_typed(py, "tensor_sum_buffer", ptr, count, ReduceOp::Sum, DType::Float32, max_threads, timeout_ms, Scalar::as_f32)
+}
+
+/// Stop running chunked operations; they (and any started later) raise
+/// CancelledError until clear_cancel()
+#[pyfunction]
+fn request_cancel() {
+    crate::scheduler::cancel::request_cancel();
+}
+
+#[pyfunction]
+fn clear_cancel() {
+    crate::scheduler::cancel::clear_cancel();
+}
+
+/// Pin corepy's workers to cores: "none", "compact" or "spread" (rebuilds the pool)
+#[pyfunction]
+fn set_thread_affinity(mode: &str) -> PyResult<()> {
+    use crate::scheduler::rayon_pool::{AffinityMode, VALID_AFFINITY_MODES};
+
+    let mode = AffinityMode::from_name(mode).ok_or_else(|| {
+        pyo3::exceptions::PyValueError::new_err(format!(
+            "unknown affinity mode '{}' (expected one of: {})", mode, VALID_AFFINITY_MODES.join(", ")
+        ))
+    })?;
+    crate::scheduler::rayon_pool::set_thread_affinity(mode).map_err(pyo3::exceptions::PyRuntimeError::new_err)
+}
+
+/// Pinning mode and the core of each worker: {"mode": str, "cores": [int]}
+#[pyfunction]
+fn get_thread_affinity(py: Python) -> PyResult<PyObject> {
+    let affinity = crate::scheduler::rayon_pool::get_thread_affinity();
+    let dict = pyo3::types::PyDict::new(py);
+    dict.set_item("mode", affinity.mode.name())?;
+    dict.set_item("cores", affinity.cores)?;
+    Ok(dict.into())
+}
+
+/// Set the DEFAULT-policy crossover for an operation (0 = always BLAS, usize::MAX = never)
+#[pyfunction]
+fn set_dispatch_threshold(op: &str, value: usize) -> PyResult<()> {
+    crate::backend::set_dispatch_threshold(op, value)
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+#[pyfunction]
+fn get_dispatch_threshold(op: &str) -> PyResult<usize> {
+    crate::backend::get_dispatch_threshold(op).ok_or_else(|| {
+        pyo3::exceptions::PyValueError::new_err(format!("No dispatch threshold for operation '{}'", op))
+    })
+}
+
+/// What this build/machine supports: BLAS linkage, SIMD level, thread counts, arena size
+#[pyfunction]
+fn get_backend_capabilities(py: Python) -> PyResult<PyObject> {
+    use pyo3::types::PyDict;
+    
+    let caps = crate::backend::capabilities::get_capabilities();
+    let dict = PyDict::new(py);
+    dict.set_item("blas_enabled", caps.blas_enabled)?;
+    dict.set_item("blas_vendor", &caps.blas_vendor)?;
+    dict.set_item("simd_level", caps.simd_level)?;
+    dict.set_item("rayon_threads", caps.rayon_threads)?;
+    dict.set_item("blas_threads", crate::backend::get_blas_num_threads())?;
+    dict.set_item("arena_size", caps.arena_size)?;
+    Ok(dict.into())
+}
+
+#[pyfunction]
+fn explain_last_dispatch() -> String {
+    crate::backend::get_last_dispatch()
+}
+
+/// Build the Python dict describing one dispatch record
+fn dispatch_info_to_dict<'py>(py: Python<'py>, info: &crate::backend::DispatchInfo) -> PyResult<&'py pyo3::types::PyDict> {
+    use crate::backend::{backend_name, DispatchDims};
+
+    let stats = pyo3::types::PyDict::new(py);
+    stats.set_item("operation", &info.operation)?;
+    stats.set_item("backend", backend_name(info.backend_id))?;
+    stats.set_item("backend_id", info.backend_id)?;
+    stats.set_item("policy", format!("{:?}", info.policy))?;
+    match info.dimensions {
+        DispatchDims::Matrix(m, n, k) => {
+            stats.set_item("m", m)?;
+            stats.set_item("n", n)?;
+            stats.set_item("k", k)?;
+            stats.set_item("dims", (m, n, k))?;
+        }
+        DispatchDims::Elements(count) => {
+            stats.set_item("elements", count)?;
+            stats.set_item("dims", (count,))?;
+        }
+    }
+    stats.set_item("timestamp_us", info.timestamp_us)?;
+    stats.set_item("duration_us", info.duration.as_secs_f64() * 1e6)?;
+    stats.set_item("gflops", info.gflops)?;
+    stats.set_item("gb_per_s", info.gb_per_s)?;
+    stats.set_item("attempted_backend", info.attempted_backend.map(backend_name))?;
+    stats.set_item("num_threads", info.num_threads)?;
+    stats.set_item("nested", info.nested)?;
+    stats.set_item("device", info.device.to_string())?;
+    Ok(stats)
+}
+
+/// Timing and throughput of the last dispatch as a dict (None if nothing dispatched yet)
+#[pyfunction]
+fn get_last_dispatch_stats(py: Python) -> PyResult<PyObject> {
+    match crate::backend::get_last_dispatch_info() {
+        Some(info) => Ok(dispatch_info_to_dict(py, &info)?.into()),
+        None => Ok(py.None()),
+    }
+}
+
+/// Full last dispatch record of this thread as JSON (None if nothing dispatched yet)
+#[pyfunction]
+fn get_last_dispatch_json() -> PyResult<Option<String>> {
+    crate::backend::get_last_dispatch_info()
+        .map(|info| serde_json::to_string(&info))
+        .transpose()
+        .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
+}
+
+// First exception raised by the Python dispatch callback during a drain
+lazy_static::lazy_static! {
+    static ref DISPATCH_CALLBACK_ERROR: parking_lot::Mutex<Option<PyErr>> = parking_lot::Mutex::new(None);
+}
+
+/// Register a callable receiving one dict per dispatch (None unregisters)
+///
+/// Events are buffered and delivered by drain_dispatch_events().
+#[pyfunction]
+fn set_dispatch_callback(callback: Option<PyObject>) -> PyResult<()> {
+    let callback = match callback {
+        Some(cb) => cb,
+        None => {
+            crate::backend::set_dispatch_callback(None);
+            return Ok(());
+        }
+    };
+    Python::with_gil(|py| {
+        if !callback.as_ref(py).is_callable() {
+            return Err(pyo3::exceptions::PyTypeError::new_err("dispatch callback must be callable"));
+        }
+        Ok(())
+    })?;
+
+    crate::backend::set_dispatch_callback(Some(std::sync::Arc::new(move |info| {
+        Python::with_gil(|py| {
+            let result = dispatch_info_to_dict(py, info)
+                .and_then(|event| callback.call1(py, (event,)));
+            if let Err(err) = result {
+                DISPATCH_CALLBACK_ERROR.lock().get_or_insert(err);
+            }
+        });
+    })));
+    Ok(())
+}
+
+/// Deliver buffered dispatch events to the registered callback
+///
+/// Returns the number of events delivered. If the callback raised, every
+/// event is still delivered and the first exception is re-raised afterwards.
+#[pyfunction]
+fn drain_dispatch_events() -> PyResult<usize> {
+    let delivered = crate::backend::drain_dispatch_events();
+    match DISPATCH_CALLBACK_ERROR.lock().take() {
+        Some(err) => Err(err),
+        None => Ok(delivered),
+    }
+}
+
+/// Number of dispatch events dropped because the buffer was full since the last call
+#[pyfunction]
+fn take_dropped_dispatch_events() -> PyResult<u64> {
+    Ok(crate::backend::take_dropped_dispatch_events())
 }
 
 // ============================================================================
@@ -26,11 +26,15 @@ use pyo3::prelude::*;
 // Module declarations
 mod ffi;
 mod ops;
-mod tensor;      // Future: Shape, dtype, buffer management
+mod tensor;      // Shapes, dtypes, owned buffers
 mod scheduler;   // Future: Rayon scheduler
 mod backend;     // Future: Backend dispatch
 mod profiler;    // Performance profiling system
 
+// Reachable for the compile-fail tests in tests/ui only; not a Rust API
+#[doc(hidden)]
+pub use scheduler::arena::{with_arena, ArenaScope};
+
 // ============================================================================
 // PyO3 Module Definition
 // ============================================================================
@@ -40,9 +44,12 @@ mod profiler;    // Performance profiling system
 /// This exports Rust functions to Python via PyO3.
 /// All function signatures use raw pointers for zero-copy performance.
 #[pymodule]
-fn _corepy_rust(_py: Python, m: &PyModule) -> PyResult<()> {
+fn _corepy_rust(py: Python, m: &PyModule) -> PyResult<()> {
     // Register all FFI functions from ffi/python.rs
     ffi::python::register_functions(m)?;
+
+    // COREPY_PROFILE=1 etc. turn profiling on before any user code runs
+    ffi::python::init_profiling_from_env(py)?;
     
     Ok(())
 }
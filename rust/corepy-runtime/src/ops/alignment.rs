@@ -0,0 +1,119 @@
+// ============================================================================
+// Operations: Pointer Alignment Requirements
+// ============================================================================
+// This module says how aligned each kernel's pointers must be, so the FFI can
+// reject a misaligned address (e.g. a view one byte into a bytearray) before
+// it reaches a SIMD kernel.
+//
+// RESPONSIBILITIES:
+// - One table of per-op alignment requirements
+// - Check an address against an op's requirement for a dtype
+//
+// DESIGN:
+// - Every op needs at least its element size; the C++ AVX2 kernels use
+//   unaligned loads, so nothing demands more today. A kernel that switches
+//   to aligned loads raises its entry to Bytes(32)
+// - Ops not in the table get the element-size default
+
+use crate::tensor::dtype::DType;
+
+/// How aligned an op's pointers must be
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(dead_code)]
+pub enum Alignment {
+    /// A multiple of the element size
+    Element,
+    /// A multiple of this many bytes (and of the element size)
+    Bytes(usize),
+}
+
+/// Alignment each op demands, by the op name used for dispatch stats
+const OP_ALIGNMENT: &[(&str, Alignment)] = &[
+    ("sum", Alignment::Element),
+    ("mean", Alignment::Element),
+    ("all", Alignment::Element),
+    ("any", Alignment::Element),
+    ("add", Alignment::Element),
+    ("sub", Alignment::Element),
+    ("mul", Alignment::Element),
+    ("div", Alignment::Element),
+    ("dot_product", Alignment::Element),
+    ("matmul", Alignment::Element),
+    ("matmul_acc", Alignment::Element),
+    ("matmul_f16", Alignment::Element),
+    ("triu", Alignment::Element),
+    ("tril", Alignment::Element),
+    ("frobenius_norm", Alignment::Element),
+    ("row_norms", Alignment::Element),
+    ("eye", Alignment::Element),
+    ("diag", Alignment::Element),
+    ("cast_f16_f32", Alignment::Element),
+    ("cast_f32_f16", Alignment::Element),
+];
+
+/// An address that doesn't meet an op's alignment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Misaligned {
+    pub address: usize,
+    pub required: usize,
+}
+
+impl std::fmt::Display for Misaligned {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "address {:#x} is not {}-byte aligned (address % {} = {})",
+            self.address,
+            self.required,
+            self.required,
+            self.address % self.required
+        )
+    }
+}
+
+/// Bytes `op`'s `dtype` pointers must be aligned to
+pub fn required_alignment(op: &str, dtype: DType) -> usize {
+    let alignment = OP_ALIGNMENT
+        .iter()
+        .find(|(name, _)| *name == op)
+        .map_or(Alignment::Element, |&(_, alignment)| alignment);
+    match alignment {
+        Alignment::Element => dtype.size(),
+        Alignment::Bytes(bytes) => bytes.max(dtype.size()),
+    }
+}
+
+/// Check `address` against `op`'s requirement for `dtype`
+pub fn check_alignment(op: &str, address: usize, dtype: DType) -> Result<(), Misaligned> {
+    let required = required_alignment(op, dtype);
+    if !address.is_multiple_of(required) {
+        return Err(Misaligned { address, required });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_element_alignment_by_dtype() {
+        assert_eq!(required_alignment("add", DType::Float32), 4);
+        assert_eq!(required_alignment("sum", DType::Bool), 1);
+        assert_eq!(required_alignment("matmul_f16", DType::Float16), 2);
+        assert_eq!(required_alignment("not_in_table", DType::Float64), 8);
+
+        assert_eq!(check_alignment("add", 0x1000, DType::Float32), Ok(()));
+        let err = check_alignment("add", 0x1001, DType::Float32).unwrap_err();
+        assert_eq!(err, Misaligned { address: 0x1001, required: 4 });
+        assert_eq!(err.to_string(), "address 0x1001 is not 4-byte aligned (address % 4 = 1)");
+        assert!(check_alignment("sum", 0x1001, DType::Int8).is_ok());
+    }
+
+    #[test]
+    fn test_table_names_are_unique() {
+        for (i, (name, _)) in OP_ALIGNMENT.iter().enumerate() {
+            assert!(OP_ALIGNMENT[i + 1..].iter().all(|(other, _)| other != name), "{} listed twice", name);
+        }
+    }
+}
@@ -0,0 +1,406 @@
+// ============================================================================
+// Operations: N-D Broadcast Execution
+// ============================================================================
+// This module runs element-wise binary ops over operands of different shapes
+// (NumPy broadcasting) and arbitrary strides.
+//
+// RESPONSIBILITIES:
+// - Compute the broadcast output shape and each operand's view of it
+// - Split the output into the longest runs all three operands share
+// - Run the contiguous C++ kernels on unit-stride runs, a strided loop otherwise
+//
+// DESIGN:
+// - Size-1 output axes are dropped, then trailing axes are merged while every
+//   operand steps through them evenly; what's left is iterated like an
+//   odometer, one inner run per step
+// - A run takes the fast path when both inputs have unit stride in it
+//   (e.g. the rows of (M, N) + (N,)); stretched inputs (stride 0) and
+//   strided views use the scalar loop
+// - The output must already have the broadcast shape; it may be strided too
+
+use crate::backend::record_cpu_dispatch;
+use crate::tensor::layout::{Layout, Strides};
+use crate::tensor::shape::{Dims, ShapeError, TensorShape};
+use std::time::Instant;
+
+/// Contiguous kernel: out[i] = a[i] <op> b[i] for i < count
+pub type BinaryKernel = unsafe extern "C" fn(*const f32, *const f32, *mut f32, usize);
+
+/// Element-wise binary op, by FFI op code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(u8)]
+pub enum BinaryOp {
+    Add = 0,
+    Sub = 1,
+    Mul = 2,
+    Div = 3,
+}
+
+impl BinaryOp {
+    pub fn from_code(code: u8) -> Option<BinaryOp> {
+        match code {
+            0 => Some(BinaryOp::Add),
+            1 => Some(BinaryOp::Sub),
+            2 => Some(BinaryOp::Mul),
+            3 => Some(BinaryOp::Div),
+            _ => None,
+        }
+    }
+
+    /// Operation name used for dispatch stats and profiling
+    pub fn name(self) -> &'static str {
+        match self {
+            BinaryOp::Add => "add",
+            BinaryOp::Sub => "sub",
+            BinaryOp::Mul => "mul",
+            BinaryOp::Div => "div",
+        }
+    }
+
+    fn kernel(self) -> BinaryKernel {
+        use super::elementwise::{add_f32_cpu, div_f32_cpu, mul_f32_cpu, sub_f32_cpu};
+        match self {
+            BinaryOp::Add => add_f32_cpu,
+            BinaryOp::Sub => sub_f32_cpu,
+            BinaryOp::Mul => mul_f32_cpu,
+            BinaryOp::Div => div_f32_cpu,
+        }
+    }
+
+    fn scalar(self) -> fn(f32, f32) -> f32 {
+        match self {
+            BinaryOp::Add => |a, b| a + b,
+            BinaryOp::Sub => |a, b| a - b,
+            BinaryOp::Mul => |a, b| a * b,
+            BinaryOp::Div => |a, b| a / b,
+        }
+    }
+}
+
+/// How a broadcast op was executed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BroadcastSummary {
+    /// Output shape
+    pub shape: TensorShape,
+    /// Elements per inner run
+    pub run_length: usize,
+    /// Runs handed to the contiguous kernel
+    pub contiguous_runs: usize,
+    /// Runs computed by the strided loop
+    pub strided_runs: usize,
+}
+
+/// out = a <op> b with NumPy broadcasting; `out_layout` must have the
+/// broadcast shape
+///
+/// # Safety
+/// Caller must ensure:
+/// - a, b and out are valid for every offset their layouts reach
+/// - out doesn't overlap the inputs, and no two of its elements alias
+pub unsafe fn binary_broadcast_f32(
+    a: *const f32,
+    a_layout: &Layout,
+    b: *const f32,
+    b_layout: &Layout,
+    out: *mut f32,
+    out_layout: &Layout,
+    op: BinaryOp,
+) -> Result<BroadcastSummary, ShapeError> {
+    let start = Instant::now();
+    let summary = binary_broadcast_with(op.kernel(), op.scalar(), a, a_layout, b, b_layout, out, out_layout)?;
+    record_cpu_dispatch(op.name(), summary.shape.numel(), false, start);
+    Ok(summary)
+}
+
+/// binary_broadcast_f32() with the contiguous `kernel` and its scalar
+/// equivalent `scalar` passed in
+#[allow(clippy::too_many_arguments)]
+unsafe fn binary_broadcast_with(
+    kernel: BinaryKernel,
+    scalar: fn(f32, f32) -> f32,
+    a: *const f32,
+    a_layout: &Layout,
+    b: *const f32,
+    b_layout: &Layout,
+    out: *mut f32,
+    out_layout: &Layout,
+) -> Result<BroadcastSummary, ShapeError> {
+    let shape = a_layout.shape().broadcast_with(b_layout.shape())?;
+    if *out_layout.shape() != shape {
+        return Err(ShapeError::Mismatch { expected: shape.dims().to_vec(), found: out_layout.shape().dims().to_vec() });
+    }
+    let a_view = a_layout.broadcast_to(&shape)?;
+    let b_view = b_layout.broadcast_to(&shape)?;
+
+    let plan = RunPlan::new(&shape, [a_view.strides(), b_view.strides(), out_layout.strides()]);
+    let mut summary = BroadcastSummary { shape, run_length: plan.run_length, contiguous_runs: 0, strided_runs: 0 };
+    if summary.shape.numel() == 0 {
+        return Ok(summary);
+    }
+
+    let [sa, sb, so] = plan.run_strides;
+    let fast = sa == 1 && sb == 1 && so == 1;
+    let len = plan.run_length;
+    let mut index: Dims = smallvec::smallvec![0; plan.outer_dims.len()];
+    let mut offsets = [0isize; 3];
+    loop {
+        let (ra, rb, ro) = (a.offset(offsets[0]), b.offset(offsets[1]), out.offset(offsets[2]));
+        if fast {
+            kernel(ra, rb, ro, len);
+            summary.contiguous_runs += 1;
+        } else {
+            for i in 0..len as isize {
+                *ro.offset(i * so) = scalar(*ra.offset(i * sa), *rb.offset(i * sb));
+            }
+            summary.strided_runs += 1;
+        }
+
+        // Advance the outer index like an odometer, last axis first
+        let mut axis = plan.outer_dims.len();
+        loop {
+            if axis == 0 {
+                return Ok(summary);
+            }
+            axis -= 1;
+            index[axis] += 1;
+            for (offset, strides) in offsets.iter_mut().zip(&plan.outer_strides) {
+                *offset += strides[axis];
+            }
+            if index[axis] < plan.outer_dims[axis] {
+                break;
+            }
+            for (offset, strides) in offsets.iter_mut().zip(&plan.outer_strides) {
+                *offset -= strides[axis] * plan.outer_dims[axis] as isize;
+            }
+            index[axis] = 0;
+        }
+    }
+}
+
+/// Output axes split into outer axes (iterated) and one merged inner run
+struct RunPlan {
+    outer_dims: Dims,
+    /// Per operand (a, b, out)
+    outer_strides: [Strides; 3],
+    run_length: usize,
+    run_strides: [isize; 3],
+}
+
+impl RunPlan {
+    fn new(shape: &TensorShape, strides: [&[isize]; 3]) -> Self {
+        // Size-1 axes don't move any operand
+        let axes: Vec<usize> = (0..shape.ndim()).filter(|&axis| shape.dims()[axis] != 1).collect();
+        let dims = shape.dims();
+
+        let Some(&last) = axes.last() else {
+            return RunPlan {
+                outer_dims: Dims::new(),
+                outer_strides: Default::default(),
+                run_length: 1,
+                run_strides: [1; 3],
+            };
+        };
+        let run_strides = strides.map(|s| s[last]);
+        let mut run_length = dims[last];
+        let mut inner = axes.len() - 1;
+        // Merge the next axis out while every operand steps over the whole
+        // run per step along it
+        while inner > 0 {
+            let axis = axes[inner - 1];
+            let merges = strides.iter().zip(run_strides).all(|(s, unit)| s[axis] == unit * run_length as isize);
+            if !merges {
+                break;
+            }
+            run_length *= dims[axis];
+            inner -= 1;
+        }
+
+        let outer = &axes[..inner];
+        RunPlan {
+            outer_dims: outer.iter().map(|&axis| dims[axis]).collect(),
+            outer_strides: strides.map(|s| outer.iter().map(|&axis| s[axis]).collect()),
+            run_length,
+            run_strides,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    unsafe extern "C" fn shim_add(a: *const f32, b: *const f32, out: *mut f32, count: usize) {
+        for i in 0..count {
+            *out.add(i) = *a.add(i) + *b.add(i);
+        }
+    }
+
+    fn contiguous(dims: &[usize]) -> Layout {
+        Layout::c_contiguous(TensorShape::new(dims).unwrap())
+    }
+
+    fn strided(dims: &[usize], strides: &[isize]) -> Layout {
+        Layout::new(TensorShape::new(dims).unwrap(), strides).unwrap()
+    }
+
+    fn iota(n: usize) -> Vec<f32> {
+        (0..n).map(|i| i as f32).collect()
+    }
+
+    /// a + b via the broadcast executor; `a`/`b` point at each view's first element
+    fn add(a: &[f32], a_first: usize, a_layout: &Layout, b: &[f32], b_first: usize, b_layout: &Layout) -> (Vec<f32>, BroadcastSummary) {
+        let shape = a_layout.shape().broadcast_with(b_layout.shape()).unwrap();
+        let mut out = vec![f32::NAN; shape.numel()];
+        let out_layout = Layout::c_contiguous(shape);
+        let summary = unsafe {
+            binary_broadcast_with(
+                shim_add,
+                |x, y| x + y,
+                a[a_first..].as_ptr(),
+                a_layout,
+                b[b_first..].as_ptr(),
+                b_layout,
+                out.as_mut_ptr(),
+                &out_layout,
+            )
+        }
+        .unwrap();
+        (out, summary)
+    }
+
+    #[test]
+    fn test_matrix_plus_row_uses_contiguous_runs() {
+        let (m, n) = (3, 4);
+        let a = iota(m * n);
+        let b = vec![10.0, 20.0, 30.0, 40.0];
+        let (out, summary) = add(&a, 0, &contiguous(&[m, n]), &b, 0, &contiguous(&[n]));
+        let expected: Vec<f32> = (0..m * n).map(|i| a[i] + b[i % n]).collect();
+        assert_eq!(out, expected);
+        assert_eq!((summary.run_length, summary.contiguous_runs, summary.strided_runs), (n, m, 0));
+        assert_eq!(summary.shape.dims(), &[m, n]);
+
+        // Same shapes collapse into one run
+        let (_, summary) = add(&a, 0, &contiguous(&[m, n]), &a, 0, &contiguous(&[m, n]));
+        assert_eq!((summary.run_length, summary.contiguous_runs), (m * n, 1));
+    }
+
+    #[test]
+    fn test_column_plus_row() {
+        let col = vec![1.0, 2.0, 3.0];
+        let row = vec![10.0, 20.0, 30.0, 40.0];
+        let (out, summary) = add(&col, 0, &contiguous(&[3, 1]), &row, 0, &contiguous(&[1, 4]));
+        let expected: Vec<f32> = (0..12).map(|i| col[i / 4] + row[i % 4]).collect();
+        assert_eq!(out, expected);
+        assert_eq!(summary.shape.dims(), &[3, 4]);
+        assert_eq!(summary.strided_runs, 3);
+    }
+
+    #[test]
+    fn test_scalar_operands() {
+        let a = iota(6);
+        let (out, summary) = add(&a, 0, &contiguous(&[2, 3]), &[0.5], 0, &contiguous(&[]));
+        assert_eq!(out, vec![0.5, 1.5, 2.5, 3.5, 4.5, 5.5]);
+        // The stretched scalar merges into a single strided run
+        assert_eq!((summary.run_length, summary.strided_runs), (6, 1));
+
+        let (out, summary) = add(&[1.0], 0, &contiguous(&[]), &[2.0], 0, &contiguous(&[]));
+        assert_eq!(out, vec![3.0]);
+        assert!(summary.shape.is_scalar());
+    }
+
+    #[test]
+    fn test_4d_broadcast() {
+        // (2, 1, 3, 1) + (4, 1, 5) -> (2, 4, 3, 5)
+        let a = iota(6);
+        let b = iota(20);
+        let (out, summary) = add(&a, 0, &contiguous(&[2, 1, 3, 1]), &b, 0, &contiguous(&[4, 1, 5]));
+        assert_eq!(summary.shape.dims(), &[2, 4, 3, 5]);
+        let mut expected = Vec::new();
+        for i in 0..2 {
+            for j in 0..4 {
+                for k in 0..3 {
+                    for l in 0..5 {
+                        expected.push(a[i * 3 + k] + b[j * 5 + l]);
+                    }
+                }
+            }
+        }
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn test_strided_and_reversed_inputs() {
+        // Transposed (3, 2) view of a contiguous 2x3 plus a reversed row
+        let a = iota(6);
+        let b = vec![1.0, 2.0];
+        let (out, summary) = add(&a, 0, &strided(&[3, 2], &[1, 3]), &b, 1, &strided(&[2], &[-1]));
+        assert_eq!(out, vec![2.0, 4.0, 3.0, 5.0, 4.0, 6.0]);
+        assert_eq!(summary.contiguous_runs, 0);
+
+        // Every other row of a 4x3 keeps contiguous rows
+        let a = iota(12);
+        let (out, summary) = add(&a, 0, &strided(&[2, 3], &[6, 1]), &a, 0, &contiguous(&[3]));
+        assert_eq!(out, vec![0.0, 2.0, 4.0, 6.0, 8.0, 10.0]);
+        assert_eq!((summary.contiguous_runs, summary.run_length), (2, 3));
+    }
+
+    #[test]
+    fn test_incompatible_and_empty() {
+        let err = unsafe {
+            binary_broadcast_with(
+                shim_add,
+                |x, y| x + y,
+                [0.0f32; 3].as_ptr(),
+                &contiguous(&[3]),
+                [0.0f32; 4].as_ptr(),
+                &contiguous(&[4]),
+                std::ptr::null_mut(),
+                &contiguous(&[4]),
+            )
+        }
+        .unwrap_err();
+        assert!(matches!(err, ShapeError::Incompatible { left: 3, right: 4, .. }));
+
+        let (out, summary) = add(&[], 0, &contiguous(&[0, 3]), &[1.0, 2.0, 3.0], 0, &contiguous(&[3]));
+        assert!(out.is_empty());
+        assert_eq!(summary.contiguous_runs + summary.strided_runs, 0);
+    }
+
+    #[test]
+    fn test_strided_output() {
+        // Write a + b into the transpose of a contiguous 3x2 buffer
+        let a = iota(6);
+        let mut out = vec![0.0f32; 6];
+        let out_layout = strided(&[2, 3], &[1, 2]);
+        let summary = unsafe {
+            binary_broadcast_with(
+                shim_add,
+                |x, y| x + y,
+                a.as_ptr(),
+                &contiguous(&[2, 3]),
+                a.as_ptr(),
+                &contiguous(&[2, 3]),
+                out.as_mut_ptr(),
+                &out_layout,
+            )
+        }
+        .unwrap();
+        assert_eq!(out, vec![0.0, 6.0, 2.0, 8.0, 4.0, 10.0]);
+        assert_eq!(summary.strided_runs, 2);
+
+        let err = unsafe {
+            binary_broadcast_with(
+                shim_add,
+                |x, y| x + y,
+                a.as_ptr(),
+                &contiguous(&[2, 3]),
+                a.as_ptr(),
+                &contiguous(&[3]),
+                out.as_mut_ptr(),
+                &contiguous(&[3, 2]),
+            )
+        }
+        .unwrap_err();
+        assert_eq!(err, ShapeError::Mismatch { expected: vec![2, 3], found: vec![3, 2] });
+    }
+}
@@ -0,0 +1,247 @@
+// ============================================================================
+// Operations: Dtype Conversion
+// ============================================================================
+// This module handles conversion between storage dtypes (f16 <-> f32).
+//
+// RESPONSIBILITIES:
+// - IEEE 754 binary16 <-> binary32 bit conversion (no external crate)
+// - Parallel element-wise cast dispatch for large buffers
+// - Panel conversion helpers used by mixed-precision kernels (matmul f16)
+//
+// f16 values are passed around as raw u16 bit patterns.
+
+use crate::backend::record_cpu_dispatch;
+use std::time::Instant;
+
+/// Threshold for parallel dispatch (elements)
+const PARALLEL_THRESHOLD_CAST: usize = 1_000_000;
+
+/// Convert an f16 bit pattern to f32 (exact; inf/NaN/subnormals preserved)
+#[inline]
+pub fn f16_to_f32(bits: u16) -> f32 {
+    let sign = ((bits & 0x8000) as u32) << 16;
+    let exp = ((bits >> 10) & 0x1f) as u32;
+    let mant = (bits & 0x3ff) as u32;
+
+    let out = if exp == 0 {
+        if mant == 0 {
+            // Signed zero
+            sign
+        } else {
+            // Subnormal: renormalize into the f32 exponent range
+            let mut e = 127 - 15 + 1;
+            let mut m = mant;
+            while m & 0x400 == 0 {
+                m <<= 1;
+                e -= 1;
+            }
+            sign | (e << 23) | ((m & 0x3ff) << 13)
+        }
+    } else if exp == 0x1f {
+        // Inf (mant == 0) or NaN (payload kept in the high mantissa bits)
+        sign | 0x7f80_0000 | (mant << 13)
+    } else {
+        sign | ((exp + 127 - 15) << 23) | (mant << 13)
+    };
+
+    f32::from_bits(out)
+}
+
+/// Convert an f32 to an f16 bit pattern (round-to-nearest-even)
+///
+/// Values beyond the f16 range become ±inf, NaN stays NaN (quiet).
+#[inline]
+pub fn f32_to_f16(value: f32) -> u16 {
+    let x = value.to_bits();
+    let sign = ((x >> 16) & 0x8000) as u16;
+    let exp = ((x >> 23) & 0xff) as i32;
+    let mant = x & 0x7f_ffff;
+
+    if exp == 0xff {
+        // Inf or NaN; force the quiet bit so a NaN never collapses into inf
+        let nan_bits = if mant != 0 { 0x200 | (mant >> 13) as u16 } else { 0 };
+        return sign | 0x7c00 | nan_bits;
+    }
+
+    let e = exp - 127 + 15;
+    if e >= 0x1f {
+        // Overflow
+        return sign | 0x7c00;
+    }
+
+    if e <= 0 {
+        // Subnormal or underflow to zero
+        if e < -10 {
+            return sign;
+        }
+        let m = mant | 0x80_0000;
+        let shift = (14 - e) as u32;
+        let truncated = m >> shift;
+        let rem = m & ((1 << shift) - 1);
+        let halfway = 1 << (shift - 1);
+        let rounded = if rem > halfway || (rem == halfway && truncated & 1 == 1) {
+            truncated + 1
+        } else {
+            truncated
+        };
+        return sign | rounded as u16;
+    }
+
+    let mut h = ((e as u32) << 10) | (mant >> 13);
+    let rem = mant & 0x1fff;
+    if rem > 0x1000 || (rem == 0x1000 && h & 1 == 1) {
+        // A carry into the exponent is correct (rounds up to the next binade / inf)
+        h += 1;
+    }
+    sign | h as u16
+}
+
+/// Convert a slice of f16 bit patterns into an f32 slice of the same length
+#[inline]
+pub fn convert_f16_to_f32(src: &[u16], dst: &mut [f32]) {
+    for (d, &s) in dst.iter_mut().zip(src) {
+        *d = f16_to_f32(s);
+    }
+}
+
+/// Convert a slice of f32 values into f16 bit patterns of the same length
+#[inline]
+pub fn convert_f32_to_f16(src: &[f32], dst: &mut [u16]) {
+    for (d, &s) in dst.iter_mut().zip(src) {
+        *d = f32_to_f16(s);
+    }
+}
+
+/// Dispatch f16 -> f32 cast
+///
+/// # Safety
+/// Caller must ensure:
+/// - src is valid for `count` u16 elements
+/// - dst is valid for `count` f32 elements and does not overlap src
+pub unsafe fn cast_f16_to_f32_dispatch(src: *const u16, dst: *mut f32, count: usize) {
+    let start = Instant::now();
+    let src = std::slice::from_raw_parts(src, count);
+    let dst = std::slice::from_raw_parts_mut(dst, count);
+    let parallel = count >= PARALLEL_THRESHOLD_CAST;
+
+    if parallel {
+        use crate::scheduler::rayon_pool;
+        use rayon::prelude::*;
+        let chunk_size = count.div_ceil(rayon_pool::num_threads());
+        rayon_pool::install(|| {
+            src.par_chunks(chunk_size)
+               .zip(dst.par_chunks_mut(chunk_size))
+               .for_each(|(s, d)| convert_f16_to_f32(s, d))
+        });
+    } else {
+        convert_f16_to_f32(src, dst);
+    }
+    record_cpu_dispatch("cast_f16_f32", count, parallel, start);
+}
+
+/// Dispatch f32 -> f16 cast
+///
+/// # Safety
+/// Caller must ensure:
+/// - src is valid for `count` f32 elements
+/// - dst is valid for `count` u16 elements and does not overlap src
+pub unsafe fn cast_f32_to_f16_dispatch(src: *const f32, dst: *mut u16, count: usize) {
+    let start = Instant::now();
+    let src = std::slice::from_raw_parts(src, count);
+    let dst = std::slice::from_raw_parts_mut(dst, count);
+    let parallel = count >= PARALLEL_THRESHOLD_CAST;
+
+    if parallel {
+        use crate::scheduler::rayon_pool;
+        use rayon::prelude::*;
+        let chunk_size = count.div_ceil(rayon_pool::num_threads());
+        rayon_pool::install(|| {
+            src.par_chunks(chunk_size)
+               .zip(dst.par_chunks_mut(chunk_size))
+               .for_each(|(s, d)| convert_f32_to_f16(s, d))
+        });
+    } else {
+        convert_f32_to_f16(src, dst);
+    }
+    record_cpu_dispatch("cast_f32_f16", count, parallel, start);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_f16_to_f32_known_values() {
+        assert_eq!(f16_to_f32(0x3c00), 1.0);
+        assert_eq!(f16_to_f32(0xc000), -2.0);
+        assert_eq!(f16_to_f32(0x7bff), 65504.0);
+        assert_eq!(f16_to_f32(0x0001), 2f32.powi(-24)); // Smallest subnormal
+        assert_eq!(f16_to_f32(0x0400), 2f32.powi(-14)); // Smallest normal
+        assert_eq!(f16_to_f32(0x8000).to_bits(), (-0.0f32).to_bits());
+    }
+
+    #[test]
+    fn test_f16_special_values() {
+        assert_eq!(f16_to_f32(0x7c00), f32::INFINITY);
+        assert_eq!(f16_to_f32(0xfc00), f32::NEG_INFINITY);
+        assert!(f16_to_f32(0x7e00).is_nan());
+        assert!(f16_to_f32(0x7c01).is_nan()); // Signalling NaN pattern
+
+        assert_eq!(f32_to_f16(f32::INFINITY), 0x7c00);
+        assert_eq!(f32_to_f16(f32::NEG_INFINITY), 0xfc00);
+        assert!(f16_to_f32(f32_to_f16(f32::NAN)).is_nan());
+        // NaN with a payload only in the low mantissa bits must not become inf
+        assert!(f16_to_f32(f32_to_f16(f32::from_bits(0x7f80_0001))).is_nan());
+    }
+
+    #[test]
+    fn test_f32_to_f16_rounding() {
+        assert_eq!(f32_to_f16(1.0), 0x3c00);
+        assert_eq!(f32_to_f16(65504.0), 0x7bff);
+        assert_eq!(f32_to_f16(65520.0), 0x7c00); // Rounds to inf
+        assert_eq!(f32_to_f16(1e-8), 0x0000);    // Underflow
+        assert_eq!(f32_to_f16(2f32.powi(-24)), 0x0001);
+        // 1 + 2^-11 is exactly halfway between 1.0 and 1 + 2^-10: ties to even
+        assert_eq!(f32_to_f16(1.0 + 2f32.powi(-11)), 0x3c00);
+        assert_eq!(f32_to_f16(1.0 + 3.0 * 2f32.powi(-11)), 0x3c02);
+    }
+
+    #[test]
+    fn test_roundtrip_all_finite_f16() {
+        for bits in 0..=u16::MAX {
+            let value = f16_to_f32(bits);
+            if value.is_nan() {
+                continue;
+            }
+            assert_eq!(f32_to_f16(value), bits, "bits {:#06x}", bits);
+        }
+    }
+
+    #[test]
+    fn test_cast_dispatch() {
+        let src: Vec<f32> = vec![0.5, -1.25, 3.0, 1024.0];
+        let mut half = vec![0u16; src.len()];
+        let mut back = vec![0f32; src.len()];
+        unsafe {
+            cast_f32_to_f16_dispatch(src.as_ptr(), half.as_mut_ptr(), src.len());
+            cast_f16_to_f32_dispatch(half.as_ptr(), back.as_mut_ptr(), half.len());
+        }
+        assert_eq!(src, back);
+    }
+
+    #[test]
+    fn test_cast_records_serial_vs_parallel() {
+        use crate::backend::{get_last_dispatch_info, BACKEND_CPU_PARALLEL, BACKEND_CPU_SERIAL};
+
+        let small = [0u16; 16];
+        let mut out = vec![0f32; PARALLEL_THRESHOLD_CAST];
+        unsafe { cast_f16_to_f32_dispatch(small.as_ptr(), out.as_mut_ptr(), small.len()) };
+        let info = get_last_dispatch_info().unwrap();
+        assert_eq!(info.operation, "cast_f16_f32");
+        assert_eq!(info.backend_id, BACKEND_CPU_SERIAL);
+
+        let large = vec![0u16; PARALLEL_THRESHOLD_CAST];
+        unsafe { cast_f16_to_f32_dispatch(large.as_ptr(), out.as_mut_ptr(), large.len()) };
+        assert_eq!(get_last_dispatch_info().unwrap().backend_id, BACKEND_CPU_PARALLEL);
+    }
+}
@@ -8,6 +8,14 @@
 // - Dispatch to appropriate C++ kernel
 // - Handle different data types and backends
 
+use super::SendPtrMut;
+use crate::backend::record_cpu_dispatch;
+use crate::scheduler::stats as scheduler_stats;
+use std::time::Instant;
+
+/// Threshold for parallel fill (elements)
+pub(crate) const PARALLEL_THRESHOLD_FILL: usize = 1_000_000;
+
 // FFI declarations for C++ kernels
 extern "C" {
     // Float32 element-wise operations
@@ -33,20 +41,98 @@ extern "C" {
 /// - out is valid for `count` elements and non-overlapping with inputs
 /// - All pointers' lifetimes exceed this function call
 pub unsafe fn add_f32_cpu_dispatch(a: *const f32, b: *const f32, out: *mut f32, count: usize) {
+    let start = Instant::now();
     add_f32_cpu(a, b, out, count);
+    record_cpu_dispatch("add", count, false, start);
 }
 
 /// Dispatch subtract operation to CPU kernel
 pub unsafe fn sub_f32_cpu_dispatch(a: *const f32, b: *const f32, out: *mut f32, count: usize) {
+    let start = Instant::now();
     sub_f32_cpu(a, b, out, count);
+    record_cpu_dispatch("sub", count, false, start);
 }
 
 /// Dispatch multiply operation to CPU kernel
 pub unsafe fn mul_f32_cpu_dispatch(a: *const f32, b: *const f32, out: *mut f32, count: usize) {
+    let start = Instant::now();
     mul_f32_cpu(a, b, out, count);
+    record_cpu_dispatch("mul", count, false, start);
 }
 
 /// Dispatch divide operation to CPU kernel
 pub unsafe fn div_f32_cpu_dispatch(a: *const f32, b: *const f32, out: *mut f32, count: usize) {
+    let start = Instant::now();
     div_f32_cpu(a, b, out, count);
+    record_cpu_dispatch("div", count, false, start);
+}
+
+/// Fill a buffer with a constant value, in parallel for large buffers
+///
+/// # Safety
+/// Caller must ensure out is valid for `count` elements
+pub unsafe fn fill_f32_cpu_dispatch(out: *mut f32, count: usize, value: f32) {
+    fill_cpu_dispatch(out, count, value);
+}
+
+/// fill_f32_cpu_dispatch() for any element type
+///
+/// # Safety
+/// Caller must ensure out is valid for `count` elements
+pub unsafe fn fill_cpu_dispatch<T: Copy + Send + Sync>(out: *mut T, count: usize, value: T) {
+    use crate::scheduler::rayon_pool;
+    use rayon::prelude::*;
+
+    let out = std::slice::from_raw_parts_mut(out, count);
+    if count >= PARALLEL_THRESHOLD_FILL {
+        let chunk_size = count.div_ceil(rayon_pool::effective_threads());
+        scheduler_stats::record_parallel(count, count.div_ceil(chunk_size));
+        rayon_pool::install(|| out.par_chunks_mut(chunk_size).for_each(|chunk| chunk.fill(value)));
+    } else {
+        out.fill(value);
+    }
+}
+
+/// out[i] = value(i), in parallel for large buffers
+///
+/// # Safety
+/// Caller must ensure out is valid for `count` elements
+pub unsafe fn fill_indexed_cpu_dispatch<T: Send, F: Fn(usize) -> T + Sync>(out: *mut T, count: usize, value: F) {
+    use crate::scheduler::rayon_pool;
+    use rayon::prelude::*;
+
+    let fill = |first: usize, chunk: &mut [T]| {
+        for (i, slot) in chunk.iter_mut().enumerate() {
+            *slot = value(first + i);
+        }
+    };
+    let out = std::slice::from_raw_parts_mut(out, count);
+    if count >= PARALLEL_THRESHOLD_FILL {
+        let chunk_size = count.div_ceil(rayon_pool::effective_threads());
+        scheduler_stats::record_parallel(count, count.div_ceil(chunk_size));
+        rayon_pool::install(|| {
+            out.par_chunks_mut(chunk_size).enumerate().for_each(|(c, chunk)| fill(c * chunk_size, chunk))
+        });
+    } else {
+        fill(0, out);
+    }
+}
+
+/// Fill `rows` strided rows of `cols` elements (row stride `ld`), rows in parallel
+///
+/// # Safety
+/// Caller must ensure out is valid for (rows - 1) * ld + cols elements and ld >= cols
+pub unsafe fn fill_rows_f32_cpu_dispatch(out: *mut f32, rows: usize, cols: usize, ld: usize, value: f32) {
+    use rayon::prelude::*;
+
+    if ld == cols {
+        return fill_f32_cpu_dispatch(out, rows * cols, value);
+    }
+
+    let out_wrap = SendPtrMut(out);
+    scheduler_stats::record_parallel(rows * cols, rows);
+    crate::scheduler::rayon_pool::install(move || (0..rows).into_par_iter().for_each(move |row| {
+        let row_slice = unsafe { std::slice::from_raw_parts_mut(out_wrap.ptr().add(row * ld), cols) };
+        row_slice.fill(value);
+    }));
 }
@@ -0,0 +1,295 @@
+// ============================================================================
+// Operations: Linear Algebra Helpers
+// ============================================================================
+// This module handles structural matrix operations (masking, norms, constructors).
+//
+// RESPONSIBILITIES:
+// - Validate matrix parameters
+// - Row-parallel dispatch via Rayon
+// - Only touch the memory each operation actually needs to write
+
+use super::SendPtrMut;
+use crate::backend::record_cpu_dispatch;
+use std::time::Instant;
+
+/// Minimum elements per Rayon task for flat reductions (amortizes task overhead)
+const MIN_REDUCE_CHUNK: usize = 64 * 1024;
+
+/// Fused sum of squares with f64 accumulation (no temporary buffer)
+#[inline]
+pub fn sum_of_squares_f64(data: &[f32]) -> f64 {
+    data.iter().map(|&x| (x as f64) * (x as f64)).sum()
+}
+
+/// Sum of absolute values with f64 accumulation
+#[inline]
+pub fn sum_of_abs_f64(data: &[f32]) -> f64 {
+    data.iter().map(|&x| (x as f64).abs()).sum()
+}
+
+/// Column span [start, end) of row `row` that triu zeroes (columns below diagonal `k`)
+#[inline]
+fn triu_zero_span(row: usize, cols: usize, k: isize) -> (usize, usize) {
+    // Keep j >= row + k, zero j < row + k
+    let end = (row as isize).saturating_add(k).clamp(0, cols as isize) as usize;
+    (0, end)
+}
+
+/// Column span [start, end) of row `row` that tril zeroes (columns above diagonal `k`)
+#[inline]
+fn tril_zero_span(row: usize, cols: usize, k: isize) -> (usize, usize) {
+    // Keep j <= row + k, zero j > row + k
+    let start = (row as isize).saturating_add(k).saturating_add(1).clamp(0, cols as isize) as usize;
+    (start, cols)
+}
+
+/// Zero a column span of every row in parallel (write-only, kept elements untouched)
+unsafe fn zero_row_spans<F>(ptr: *mut f32, rows: usize, cols: usize, span: F)
+where
+    F: Fn(usize) -> (usize, usize) + Send + Sync,
+{
+    use rayon::prelude::*;
+
+    let ptr_wrap = SendPtrMut(ptr);
+    crate::scheduler::rayon_pool::install(move || (0..rows).into_par_iter().for_each(move |row| {
+        let (start, end) = span(row);
+        if start < end {
+            unsafe {
+                std::ptr::write_bytes(ptr_wrap.ptr().add(row * cols + start), 0, end - start);
+            }
+        }
+    }));
+}
+
+/// Zero everything below the k-th diagonal in place (numpy.triu)
+///
+/// # Safety
+/// Caller must ensure ptr is valid for rows*cols f32 elements (row-major)
+pub unsafe fn triu_f32_cpu_dispatch(ptr: *mut f32, rows: usize, cols: usize, k: isize) {
+    let start = Instant::now();
+    zero_row_spans(ptr, rows, cols, move |row| triu_zero_span(row, cols, k));
+    record_cpu_dispatch("triu", rows * cols, true, start);
+}
+
+/// Zero everything above the k-th diagonal in place (numpy.tril)
+///
+/// # Safety
+/// Caller must ensure ptr is valid for rows*cols f32 elements (row-major)
+pub unsafe fn tril_f32_cpu_dispatch(ptr: *mut f32, rows: usize, cols: usize, k: isize) {
+    let start = Instant::now();
+    zero_row_spans(ptr, rows, cols, move |row| tril_zero_span(row, cols, k));
+    record_cpu_dispatch("tril", rows * cols, true, start);
+}
+
+/// Frobenius norm of a rows x cols matrix: sqrt(sum of squares)
+///
+/// # Safety
+/// Caller must ensure a_ptr is valid for rows*cols f32 elements
+pub unsafe fn frobenius_norm_f32_cpu_dispatch(a_ptr: *const f32, rows: usize, cols: usize) -> f32 {
+    use crate::scheduler::rayon_pool;
+    use rayon::prelude::*;
+
+    let start = Instant::now();
+    let count = rows * cols;
+    let data = std::slice::from_raw_parts(a_ptr, count);
+    let chunk_size = count.div_ceil(rayon_pool::num_threads()).max(MIN_REDUCE_CHUNK);
+
+    let sum: f64 = rayon_pool::install(|| {
+        data.par_chunks(chunk_size)
+            .map(sum_of_squares_f64)
+            .sum()
+    });
+    record_cpu_dispatch("frobenius_norm", count, count > chunk_size, start);
+    sum.sqrt() as f32
+}
+
+/// Per-row vector norms (ord 1 or 2), one Rayon task per row, f64 accumulation
+///
+/// # Safety
+/// Caller must ensure:
+/// - a_ptr is valid for rows*cols f32 elements (row-major)
+/// - out_ptr is valid for rows f32 elements and does not overlap a_ptr
+pub unsafe fn row_norms_f32_cpu_dispatch(
+    a_ptr: *const f32, out_ptr: *mut f32,
+    rows: usize, cols: usize, ord: u32
+) -> Result<(), String> {
+    use rayon::prelude::*;
+
+    let norm: fn(&[f32]) -> f32 = match ord {
+        1 => |row| sum_of_abs_f64(row) as f32,
+        2 => |row| sum_of_squares_f64(row).sqrt() as f32,
+        _ => return Err(format!("Unsupported norm order {} (expected 1 or 2)", ord)),
+    };
+
+    let start = Instant::now();
+    let data = std::slice::from_raw_parts(a_ptr, rows * cols);
+    let out = std::slice::from_raw_parts_mut(out_ptr, rows);
+
+    if cols == 0 {
+        out.fill(0.0);
+    } else {
+        crate::scheduler::rayon_pool::install(|| {
+            out.par_iter_mut()
+               .zip(data.par_chunks(cols))
+               .for_each(|(dst, row)| *dst = norm(row))
+        });
+    }
+    record_cpu_dispatch("row_norms", rows * cols, cols > 0, start);
+    Ok(())
+}
+
+/// Number of f32 elements in an n x n matrix, if its byte size fits in isize
+pub fn square_matrix_len(n: usize) -> Option<usize> {
+    let count = n.checked_mul(n)?;
+    let bytes = count.checked_mul(std::mem::size_of::<f32>())?;
+    if bytes > isize::MAX as usize { None } else { Some(count) }
+}
+
+/// Write an n x n identity matrix with row stride `lda` (only the n leading columns are touched)
+///
+/// # Safety
+/// Caller must ensure out_ptr is valid for (n - 1) * lda + n f32 elements and lda >= n
+pub unsafe fn eye_f32_cpu_dispatch(out_ptr: *mut f32, n: usize, lda: usize) {
+    use super::elementwise::fill_rows_f32_cpu_dispatch;
+
+    let start = Instant::now();
+    fill_rows_f32_cpu_dispatch(out_ptr, n, n, lda, 0.0);
+    for i in 0..n {
+        *out_ptr.add(i * lda + i) = 1.0;
+    }
+    record_cpu_dispatch("eye", n * n, true, start);
+}
+
+/// Write diag(v) as an n x n matrix
+///
+/// # Safety
+/// Caller must ensure:
+/// - v_ptr is valid for n f32 elements
+/// - out_ptr is valid for n*n f32 elements and does not overlap v_ptr
+pub unsafe fn diag_from_vector_f32_cpu_dispatch(v_ptr: *const f32, out_ptr: *mut f32, n: usize) {
+    use super::elementwise::{fill_f32_cpu_dispatch, PARALLEL_THRESHOLD_FILL};
+
+    let start = Instant::now();
+    fill_f32_cpu_dispatch(out_ptr, n * n, 0.0);
+    for i in 0..n {
+        *out_ptr.add(i * n + i) = *v_ptr.add(i);
+    }
+    record_cpu_dispatch("diag", n * n, n * n >= PARALLEL_THRESHOLD_FILL, start);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reference(rows: usize, cols: usize, keep: impl Fn(isize, isize) -> bool) -> Vec<f32> {
+        (0..rows * cols)
+            .map(|idx| {
+                let (i, j) = ((idx / cols) as isize, (idx % cols) as isize);
+                if keep(i, j) { (idx + 1) as f32 } else { 0.0 }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_triu_tril_against_reference() {
+        for &(rows, cols) in &[(4usize, 4usize), (3, 6), (6, 3), (1, 5), (0, 3)] {
+            for k in -7isize..=7 {
+                let mut upper: Vec<f32> = (1..=rows * cols).map(|v| v as f32).collect();
+                let mut lower = upper.clone();
+                unsafe {
+                    triu_f32_cpu_dispatch(upper.as_mut_ptr(), rows, cols, k);
+                    tril_f32_cpu_dispatch(lower.as_mut_ptr(), rows, cols, k);
+                }
+                assert_eq!(upper, reference(rows, cols, |i, j| j >= i + k), "triu {}x{} k={}", rows, cols, k);
+                assert_eq!(lower, reference(rows, cols, |i, j| j <= i + k), "tril {}x{} k={}", rows, cols, k);
+            }
+        }
+    }
+
+    #[test]
+    fn test_extreme_offsets() {
+        assert_eq!(triu_zero_span(3, 5, isize::MAX), (0, 5));
+        assert_eq!(triu_zero_span(3, 5, isize::MIN), (0, 0));
+        assert_eq!(tril_zero_span(3, 5, isize::MAX), (5, 5));
+        assert_eq!(tril_zero_span(3, 5, isize::MIN), (0, 5));
+    }
+
+    #[test]
+    fn test_frobenius_norm() {
+        let a = [3.0f32, 4.0, 0.0, 0.0, 12.0, 0.0];
+        let norm = unsafe { frobenius_norm_f32_cpu_dispatch(a.as_ptr(), 2, 3) };
+        assert_eq!(norm, 13.0);
+    }
+
+    #[test]
+    fn test_row_norms() {
+        let a = [3.0f32, -4.0, 0.0, 0.0, 1.0, -1.0];
+        let mut l1 = [0f32; 3];
+        let mut l2 = [0f32; 3];
+        unsafe {
+            row_norms_f32_cpu_dispatch(a.as_ptr(), l1.as_mut_ptr(), 3, 2, 1).unwrap();
+            row_norms_f32_cpu_dispatch(a.as_ptr(), l2.as_mut_ptr(), 3, 2, 2).unwrap();
+        }
+        assert_eq!(l1, [7.0, 0.0, 2.0]);
+        assert_eq!(l2, [5.0, 0.0, 2f32.sqrt()]);
+    }
+
+    #[test]
+    fn test_row_norms_rejects_unknown_ord() {
+        let a = [1.0f32];
+        let mut out = [0f32];
+        let result = unsafe { row_norms_f32_cpu_dispatch(a.as_ptr(), out.as_mut_ptr(), 1, 1, 3) };
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_eye_with_leading_dimension() {
+        let mut out = vec![7.0f32; 3 * 4];
+        unsafe { eye_f32_cpu_dispatch(out.as_mut_ptr(), 3, 4) };
+        assert_eq!(out, vec![
+            1.0, 0.0, 0.0, 7.0,
+            0.0, 1.0, 0.0, 7.0,
+            0.0, 0.0, 1.0, 7.0,
+        ]);
+    }
+
+    #[test]
+    fn test_large_eye_counts_a_parallel_dispatch() {
+        use crate::scheduler::stats;
+
+        let n = 1_024;
+        let mut out = vec![7.0f32; n * n];
+        let before = stats::get_stats().parallel_dispatches;
+        unsafe { eye_f32_cpu_dispatch(out.as_mut_ptr(), n, n) };
+        assert!(stats::get_stats().parallel_dispatches > before);
+        assert_eq!(out.iter().filter(|&&v| v == 1.0).count(), n);
+        assert_eq!(out.iter().filter(|&&v| v == 0.0).count(), n * n - n);
+    }
+
+    #[test]
+    fn test_diag_from_vector() {
+        let v = [1.0f32, 2.0, 3.0];
+        let mut out = vec![9.0f32; 9];
+        unsafe { diag_from_vector_f32_cpu_dispatch(v.as_ptr(), out.as_mut_ptr(), 3) };
+        assert_eq!(out, vec![1.0, 0.0, 0.0, 0.0, 2.0, 0.0, 0.0, 0.0, 3.0]);
+    }
+
+    #[test]
+    fn test_dispatch_is_recorded() {
+        use crate::backend::{get_last_dispatch_info, DispatchDims, BACKEND_CPU_PARALLEL};
+
+        let mut a = vec![1.0f32; 12];
+        unsafe { triu_f32_cpu_dispatch(a.as_mut_ptr(), 3, 4, 0) };
+        let info = get_last_dispatch_info().unwrap();
+        assert_eq!(info.operation, "triu");
+        assert_eq!(info.backend_id, BACKEND_CPU_PARALLEL);
+        assert_eq!(info.dimensions, DispatchDims::Elements(12));
+    }
+
+    #[test]
+    fn test_square_matrix_len_overflow() {
+        assert_eq!(square_matrix_len(4), Some(16));
+        assert_eq!(square_matrix_len(usize::MAX), None);
+        assert_eq!(square_matrix_len(1 << 31), None); // 2^62 elements * 4 bytes > isize::MAX
+    }
+}
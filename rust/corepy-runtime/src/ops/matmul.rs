@@ -2,6 +2,15 @@
 // Operations: Matrix Multiplication
 // ============================================================================
 
+use super::{DispatchError, SendPtr, SendPtrMut};
+use crate::scheduler::stats as scheduler_stats;
+use crate::scheduler::cancel::Cancelled;
+use crate::tensor::Device;
+use crate::backend::{
+    register_backend, Backend, DispatchDims, PolicyError, BACKEND_NATIVE, BACKEND_OPENBLAS,
+};
+use std::sync::Arc;
+
 // FFI declaration for C++ kernel
 extern "C" {
     /// AVX2-optimized dot product kernel
@@ -10,99 +19,501 @@ extern "C" {
     /// Matrix Multiplication (Native or BLAS)
     pub fn matmul_f32_cpu(a: *const f32, b: *const f32, c: *mut f32, m: usize, k: usize, n: usize);
 
+    /// Accumulating Matrix Multiplication: C += A·B (sgemm beta=1 on BLAS)
+    pub fn matmul_acc_f32_cpu(a: *const f32, b: *const f32, c: *mut f32, m: usize, k: usize, n: usize);
+
+    /// AVX2 Matrix Multiplication, exported even in BLAS builds
+    pub fn matmul_native_f32_cpu(a: *const f32, b: *const f32, c: *mut f32, m: usize, k: usize, n: usize);
+
+    /// AVX2 Accumulating Matrix Multiplication, exported even in BLAS builds
+    pub fn matmul_native_acc_f32_cpu(a: *const f32, b: *const f32, c: *mut f32, m: usize, k: usize, n: usize);
+
     /// Check if BLAS backend is active
     pub fn corepy_is_blas_enabled() -> bool;
 
+    /// Name of the linked BLAS library ("none" for native kernels); static C string
+    pub fn corepy_blas_vendor() -> *const std::os::raw::c_char;
+
     /// Set number of threads for the backend
-    #[allow(dead_code)]
     pub fn corepy_set_num_threads(num_threads: i32);
 }
 
-/// Safety wrapper for pointers to be Send/Sync for Rayon
-struct SendPtr<T>(*const T);
-unsafe impl<T> Send for SendPtr<T> {}
-unsafe impl<T> Sync for SendPtr<T> {}
-impl<T> SendPtr<T> {
-    #[inline]
-    fn ptr(&self) -> *const T { self.0 }
+/// Signature shared by the overwrite and accumulate matmul kernels
+type MatmulKernel = unsafe extern "C" fn(*const f32, *const f32, *mut f32, usize, usize, usize);
+
+/// Minimum K depth of one K-split panel
+const K_SPLIT_MIN_DEPTH: usize = 512;
+
+/// AVX2 kernel, also in BLAS builds (where matmul_f32_cpu is sgemm)
+#[inline]
+fn native_kernel(accumulate: bool) -> MatmulKernel {
+    if accumulate { matmul_native_acc_f32_cpu } else { matmul_native_f32_cpu }
+}
+
+/// Linked BLAS sgemm (the AVX2 kernel when no BLAS is linked)
+#[inline]
+fn blas_kernel(accumulate: bool) -> MatmulKernel {
+    if accumulate { matmul_acc_f32_cpu } else { matmul_f32_cpu }
+}
+
+/// Native AVX2/scalar kernels, rows split across the Rayon pool
+pub struct NativeBackend;
+
+impl Backend for NativeBackend {
+    fn id(&self) -> u8 { BACKEND_NATIVE }
+
+    fn name(&self) -> &'static str { "Corepy AVX2" }
+
+    fn supports(&self, operation: &str, _dims: DispatchDims) -> bool {
+        operation.starts_with("matmul")
+    }
+
+    unsafe fn matmul_f32(
+        &self,
+        a: *const f32, b: *const f32, c: *mut f32,
+        m: usize, k: usize, n: usize,
+        accumulate: bool
+    ) {
+        use crate::profiler::{task_context, with_task_context};
+        use crate::scheduler::arena::with_arena;
+        use crate::scheduler::{cancel, chunking, progress, rayon_pool};
+        use rayon::prelude::*;
+
+        let kernel = native_kernel(accumulate);
+        let a_wrap = SendPtr(a);
+        let b_wrap = SendPtr(b);
+        let c_wrap = SendPtrMut(c);
+        let context = task_context();
+        let deadline = cancel::deadline();
+
+        with_arena(|_scope| {
+            // Row blocks whose A and C rows fit the chunk target
+            let row_bytes = (k + n) * std::mem::size_of::<f32>();
+            let rows_per_chunk = chunking::plan(m, row_bytes).chunk_len;
+            let splits = k_split_count(m.div_ceil(rows_per_chunk), k, rayon_pool::effective_threads());
+            if splits > 1 {
+                k_split_matmul(kernel, a, b, c, m, k, n, accumulate, splits);
+                return;
+            }
+            scheduler_stats::record_parallel(m * n, m.div_ceil(rows_per_chunk));
+            progress::begin(m.div_ceil(rows_per_chunk));
+
+            rayon_pool::install(move || {
+                (0..m).into_par_iter()
+                      .chunks(rows_per_chunk)
+                      .for_each(move |row_indices| with_task_context(context.clone(), || {
+                          if cancel::check_deadline(deadline).is_err() {
+                              return;
+                          }
+                          let start_row = row_indices[0];
+                          let num_rows = row_indices.len();
+                          
+                          unsafe {
+                              kernel(
+                                  a_wrap.ptr().add(start_row * k),
+                                  b_wrap.ptr(),
+                                  c_wrap.ptr().add(start_row * n),
+                                  num_rows, k, n
+                              );
+                          }
+                          progress::advance(1);
+                      }))
+            });
+        });
+    }
+}
+
+/// Number of K panels for a product with `row_blocks` row blocks: more than
+/// one only when there are too few row blocks to occupy the workers and K is
+/// deep enough to split
+fn k_split_count(row_blocks: usize, k: usize, threads: usize) -> usize {
+    if row_blocks >= threads {
+        return 1;
+    }
+    (k / K_SPLIT_MIN_DEPTH).clamp(1, threads)
+}
+
+/// C (+)= A·B split along K: each task packs its A columns into a panel and
+/// multiplies it into a partial C, both in its worker's arena, then adds the
+/// partial into C
+///
+/// For short, deep products (few rows, large K) where a row split leaves
+/// workers idle.
+///
+/// # Safety
+/// Same contract as matmul_f32_cpu_dispatch.
+#[allow(clippy::too_many_arguments)]
+unsafe fn k_split_matmul(
+    kernel: MatmulKernel,
+    a: *const f32, b: *const f32, c: *mut f32,
+    m: usize, k: usize, n: usize,
+    accumulate: bool,
+    splits: usize,
+) {
+    use crate::profiler::{task_context, with_task_context};
+    use crate::scheduler::arena::with_arena;
+    use crate::scheduler::{cancel, progress, rayon_pool};
+    use rayon::prelude::*;
+
+    let depth = k.div_ceil(splits);
+    let splits = k.div_ceil(depth);
+    scheduler_stats::record_parallel(m * n, splits);
+    progress::begin(splits);
+    if !accumulate {
+        std::ptr::write_bytes(c, 0, m * n);
+    }
+
+    let a_wrap = SendPtr(a);
+    let b_wrap = SendPtr(b);
+    let c_wrap = SendPtrMut(c);
+    let merge = std::sync::Mutex::new(());
+    let context = task_context();
+    let deadline = cancel::deadline();
+
+    rayon_pool::install(|| {
+        (0..splits).into_par_iter().for_each(|split| with_task_context(context.clone(), || {
+            if cancel::check_deadline(deadline).is_err() {
+                return;
+            }
+            let k0 = split * depth;
+            let kb = depth.min(k - k0);
+
+            with_arena(|scope| unsafe {
+                let mut panel = scope.alloc_or_heap::<f32>(m * kb);
+                let mut partial = scope.alloc_or_heap::<f32>(m * n);
+
+                let a = std::slice::from_raw_parts(a_wrap.ptr(), m * k);
+                for (row, dst) in panel.chunks_exact_mut(kb).enumerate() {
+                    dst.copy_from_slice(&a[row * k + k0..row * k + k0 + kb]);
+                }
+                kernel(panel.as_ptr(), b_wrap.ptr().add(k0 * n), partial.as_mut_ptr(), m, kb, n);
+
+                let _merge = merge.lock().unwrap_or_else(|e| e.into_inner());
+                let c = std::slice::from_raw_parts_mut(c_wrap.ptr(), m * n);
+                for (dst, src) in c.iter_mut().zip(partial.iter()) {
+                    *dst += *src;
+                }
+            });
+            progress::advance(1);
+        }))
+    });
+}
+
+/// Linked BLAS library (OpenBLAS handles its own threading)
+pub struct BlasBackend;
+
+impl Backend for BlasBackend {
+    fn id(&self) -> u8 { BACKEND_OPENBLAS }
+
+    fn name(&self) -> &'static str { "OpenBLAS" }
+
+    fn supports(&self, operation: &str, _dims: DispatchDims) -> bool {
+        operation.starts_with("matmul") && unsafe { corepy_is_blas_enabled() }
+    }
+
+    unsafe fn matmul_f32(
+        &self,
+        a: *const f32, b: *const f32, c: *mut f32,
+        m: usize, k: usize, n: usize,
+        accumulate: bool
+    ) {
+        use crate::scheduler::arena::with_arena;
+
+        let kernel = blas_kernel(accumulate);
+        with_arena(|_scope| {
+            kernel(a, b, c, m, k, n);
+        });
+    }
 }
 
-struct SendPtrMut<T>(*mut T);
-unsafe impl<T> Send for SendPtrMut<T> {}
-unsafe impl<T> Sync for SendPtrMut<T> {}
-impl<T> SendPtrMut<T> {
-    #[inline]
-    fn ptr(&self) -> *mut T { self.0 }
+/// Register the native and BLAS backends (idempotent)
+pub fn register_builtin_backends() {
+    static REGISTER: std::sync::Once = std::sync::Once::new();
+    REGISTER.call_once(|| {
+        register_backend(Arc::new(NativeBackend));
+        register_backend(Arc::new(BlasBackend));
+    });
 }
 
 /// Dispatch dot product operation to CPU kernel
 pub unsafe fn dot_product_f32_cpu_dispatch(a: *const f32, b: *const f32, count: usize) -> f32 {
     use crate::scheduler::arena::with_arena;
-    with_arena(|_arena| {
+    use crate::backend::record_cpu_dispatch;
+
+    let start = std::time::Instant::now();
+    let result = with_arena(|_scope| {
         dot_product_f32_cpu(a, b, count)
-    })
+    });
+    record_cpu_dispatch("dot_product", count, false, start);
+    result
 }
 
 /// Dispatch 2D matrix multiplication to CPU kernel
+///
+/// With `accumulate` set, the product is added into the existing contents of C
+/// (C += A·B) instead of overwriting it.
+///
+/// Fails (without touching C) if the effective policy names a backend that
+/// cannot run here, e.g. CUDA forced through a per-operation override.
+///
+/// `max_threads` caps the Rayon workers of the native path; Some(1) runs the
+/// whole product as one sequential kernel call, as do calls made from a Rayon
+/// worker thread. OpenBLAS keeps its own thread setting.
+///
+/// Native row blocks stop once cancellation is requested or the calling
+/// thread's deadline (cancel::with_deadline) passes; the dispatch then fails
+/// with DispatchError::Cancelled and C holds unspecified contents. The BLAS
+/// sgemm and the single sequential kernel call cannot be interrupted, so
+/// they only check before and after running.
+///
+/// # Safety
+/// Caller must ensure:
+/// - a is valid for m*k f32 elements, b for k*n f32 elements
+/// - c is valid for m*n f32 elements and does not overlap the inputs
+#[allow(clippy::too_many_arguments)]
 pub unsafe fn matmul_f32_cpu_dispatch(
     a: *const f32, b: *const f32, c: *mut f32,
+    m: usize, k: usize, n: usize,
+    accumulate: bool,
+    max_threads: Option<usize>
+) -> Result<(), DispatchError> {
+    use crate::backend::{
+        get_policy_for, select_backend, BackendPolicy, matmul_prefers_blas, record_dispatch,
+        record_detailed_dispatch, record_unavailable_dispatch, take_pending_blas_num_threads,
+        BACKEND_CUDA,
+    };
+    use crate::backend::autotune::{auto_tune_enabled, choose_backend, record_auto_tune_sample};
+    use crate::backend::dispatch_table::lookup as lookup_dispatch_table;
+    use crate::scheduler::{cancel, progress};
+    use crate::scheduler::rayon_pool::{in_worker_thread, with_max_threads};
+
+    register_builtin_backends();
+    cancel::check()?;
+
+    let operation = if accumulate { "matmul_acc" } else { "matmul" };
+    let dims = DispatchDims::Matrix(m, n, k);
+    let policy = get_policy_for(operation);
+
+    // Set when auto-tuning is still timing both backends for this size bucket
+    let mut exploring = false;
+
+    let target = match policy {
+        BackendPolicy::BLAS => BACKEND_OPENBLAS,     // User forced BLAS
+        BackendPolicy::OPENBLAS => BACKEND_OPENBLAS, // User forced OpenBLAS
+        BackendPolicy::DEFAULT if auto_tune_enabled() && corepy_is_blas_enabled() => {
+            // Learned per-bucket choice (alternates backends while exploring)
+            let (backend_id, explore) = choose_backend(operation, m, n, k);
+            exploring = explore;
+            backend_id
+        }
+        BackendPolicy::DEFAULT => {
+            // Table learned from profiler reports, else the benchmark heuristic
+            // (configurable flip point, default 256)
+            lookup_dispatch_table(operation, m, n, k).unwrap_or_else(|| {
+                if matmul_prefers_blas(m, n, k) { BACKEND_OPENBLAS } else { BACKEND_NATIVE }
+            })
+        }
+        BackendPolicy::CUDA => BACKEND_CUDA,
+    };
+
+    // BLAS requests fall back to the native kernels when no BLAS is linked;
+    // anything else missing (e.g. CUDA) fails loudly rather than quietly running on CPU
+    let backend = match select_backend(target, operation, dims) {
+        Some(backend) => backend,
+        None if target == BACKEND_OPENBLAS => select_backend(BACKEND_NATIVE, operation, dims)
+            .expect("native backend is always registered"),
+        None => {
+            record_unavailable_dispatch(target, operation, m, n, k, policy);
+            return Err(PolicyError::Unavailable(policy).into());
+        }
+    };
+
+    // Apply a thread-count change requested since the last dispatch
+    if let Some(num_threads) = take_pending_blas_num_threads() {
+        corepy_set_num_threads(num_threads.min(i32::MAX as usize) as i32);
+    }
+
+    let backend_id = backend.id();
+    // Already on a worker thread: don't nest another parallel split
+    let max_threads = if in_worker_thread() { Some(1) } else { max_threads };
+    with_max_threads(max_threads, || {
+        let start = std::time::Instant::now();
+        record_dispatch(backend_id);
+        if backend_id == BACKEND_NATIVE && max_threads == Some(1) {
+            // Strictly sequential: no Rayon tasks at all
+            scheduler_stats::record_serial();
+            progress::begin(1);
+            crate::scheduler::arena::with_arena(|_scope| native_kernel(accumulate)(a, b, c, m, k, n));
+            progress::advance(1);
+        } else if backend_id != BACKEND_NATIVE {
+            // BLAS and other backends thread themselves, if at all; one opaque unit
+            scheduler_stats::record_serial();
+            progress::begin(1);
+            backend.matmul_f32(a, b, c, m, k, n, accumulate);
+            progress::advance(1);
+        } else {
+            backend.matmul_f32(a, b, c, m, k, n, accumulate);
+        }
+
+        if exploring && cancel::check().is_ok() {
+            record_auto_tune_sample(operation, m, n, k, backend_id, start.elapsed());
+        }
+        record_detailed_dispatch(backend_id, operation, m, n, k, policy, Device::Cpu, start);
+    });
+    cancel::check()?;
+    Ok(())
+}
+
+/// Rows of A converted per block in the f16 path
+const F16_BLOCK_ROWS: usize = 64;
+
+/// Dispatch 2D matrix multiplication on f16 inputs with f32 accumulation
+///
+/// B is converted to f32 once; A is converted block-by-block into the worker's
+/// arena (heap fallback when the arena is too small) and fed to the f32 kernel.
+///
+/// # Safety
+/// Caller must ensure:
+/// - a is valid for m*k u16 elements, b for k*n u16 elements
+/// - c is valid for m*n f32 elements and does not overlap the inputs
+pub unsafe fn matmul_f16_f32_cpu_dispatch(
+    a: *const u16, b: *const u16, c: *mut f32,
     m: usize, k: usize, n: usize
-) {
+) -> Result<(), Cancelled> {
+    use crate::ops::cast::convert_f16_to_f32;
     use crate::scheduler::arena::with_arena;
-    use crate::backend::{get_policy, BackendPolicy, record_dispatch, record_detailed_dispatch};
-    
+    use crate::backend::{get_policy, record_dispatch, record_detailed_dispatch, BACKEND_NATIVE};
+    use crate::profiler::{task_context, with_task_context};
+    use crate::scheduler::{cancel, progress, rayon_pool};
+    use rayon::prelude::*;
+
+    cancel::check()?;
     let policy = get_policy();
-    
-    let use_blas = match policy {
-        BackendPolicy::BLAS => true,     // User forced BLAS
-        BackendPolicy::OPENBLAS => true, // User forced OpenBLAS
-        BackendPolicy::DEFAULT => {
-            // Heuristic flip point based on benchmarks (Large matrices > 256x256)
-            m > 256 || n > 256 || k > 256
+    let start = std::time::Instant::now();
+    record_dispatch(BACKEND_NATIVE);
+
+    let b_half = std::slice::from_raw_parts(b, k * n);
+    let b_f32: Vec<f32> = rayon_pool::install(|| {
+        let mut out = vec![0f32; k * n];
+        out.par_chunks_mut(n.max(1))
+           .zip(b_half.par_chunks(n.max(1)))
+           .for_each(|(dst, src)| convert_f16_to_f32(src, dst));
+        out
+    });
+
+    scheduler_stats::record_parallel(m * n, m.div_ceil(F16_BLOCK_ROWS));
+    progress::begin(m.div_ceil(F16_BLOCK_ROWS));
+    let a_wrap = SendPtr(a);
+    let b_wrap = SendPtr(b_f32.as_ptr());
+    let c_wrap = SendPtrMut(c);
+    let context = task_context();
+    let deadline = cancel::deadline();
+
+    rayon_pool::install(move || {
+        (0..m.div_ceil(F16_BLOCK_ROWS)).into_par_iter().for_each(move |block| with_task_context(context.clone(), || {
+            if cancel::check_deadline(deadline).is_err() {
+                return;
+            }
+            let start_row = block * F16_BLOCK_ROWS;
+            let num_rows = F16_BLOCK_ROWS.min(m - start_row);
+            let a_half = std::slice::from_raw_parts(a_wrap.ptr().add(start_row * k), num_rows * k);
+
+            with_arena(|scope| {
+                let mut panel = scope.alloc_or_heap::<f32>(num_rows * k);
+                convert_f16_to_f32(a_half, &mut panel);
+
+                // Blocks already run on Rayon; sgemm would nest its own threads
+                matmul_native_f32_cpu(
+                    panel.as_ptr(),
+                    b_wrap.ptr(),
+                    c_wrap.ptr().add(start_row * n),
+                    num_rows, k, n
+                );
+            });
+            progress::advance(1);
+        }))
+    });
+
+    record_detailed_dispatch(BACKEND_NATIVE, "matmul_f16", m, n, k, policy, Device::Cpu, start);
+    cancel::check()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Naive row-major kernel with the C++ kernel's signature
+    unsafe extern "C" fn shim_matmul(a: *const f32, b: *const f32, c: *mut f32, m: usize, k: usize, n: usize) {
+        for i in 0..m {
+            for j in 0..n {
+                let mut sum = 0f32;
+                for p in 0..k {
+                    sum += *a.add(i * k + p) * *b.add(p * n + j);
+                }
+                *c.add(i * n + j) = sum;
+            }
         }
-        _ => false, // CUDA etc not handled here yet
-    };
+    }
 
-    // Check if we should use BLAS or native Rayon dispatch
-    if use_blas && corepy_is_blas_enabled() {
-        record_dispatch(1); // OpenBLAS ID (Mapping: 1=OpenBLAS)
-        record_detailed_dispatch(1, "matmul", m, n, k, policy);
-        
-        // Direct BLAS call - OpenBLAS handles its own threading efficiently
-        with_arena(|_arena| {
-            matmul_f32_cpu(a, b, c, m, k, n);
-        });
-    } else {
-        record_dispatch(0); // Corepy ID
-        record_detailed_dispatch(0, "matmul", m, n, k, policy);
-        
-        // Fallback to naive Rayon parallel dispatch for custom AVX2/Scalar kernels
-        // Fallback to naive Rayon parallel dispatch for custom AVX2/Scalar kernels
-        use rayon::prelude::*;
-        
-        let a_wrap = SendPtr(a);
-        let b_wrap = SendPtr(b);
-        let c_wrap = SendPtrMut(c);
+    #[test]
+    fn test_k_split_count() {
+        // Enough row blocks: split rows as before
+        assert_eq!(k_split_count(8, 100_000, 8), 1);
+        // Few rows, shallow K: nothing to gain
+        assert_eq!(k_split_count(1, 600, 8), 1);
+        assert_eq!(k_split_count(1, 4 * K_SPLIT_MIN_DEPTH, 8), 4);
+        assert_eq!(k_split_count(2, 100_000, 8), 8);
+    }
 
-        with_arena(|_arena| {
-            let num_threads = num_cpus::get();
-            let rows_per_thread = (m + num_threads - 1) / num_threads;
-
-            (0..m).into_par_iter()
-                  .chunks(rows_per_thread)
-                  .for_each(move |row_indices| {
-                      let start_row = row_indices[0];
-                      let num_rows = row_indices.len();
-                      
-                      unsafe {
-                          matmul_f32_cpu(
-                              a_wrap.ptr().add(start_row * k),
-                              b_wrap.ptr(),
-                              c_wrap.ptr().add(start_row * n),
-                              num_rows, k, n
-                          );
-                      }
-                  });
-        });
+    #[test]
+    fn test_k_split_matches_reference() {
+        let _guard = crate::scheduler::TEST_STATE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let (m, k, n) = (3, 1501, 5);
+        // Small integers keep every partial sum exact
+        let a: Vec<f32> = (0..m * k).map(|i| (i % 7) as f32 - 3.0).collect();
+        let b: Vec<f32> = (0..k * n).map(|i| (i % 5) as f32 - 2.0).collect();
+        let mut expected = vec![0f32; m * n];
+        unsafe { shim_matmul(a.as_ptr(), b.as_ptr(), expected.as_mut_ptr(), m, k, n) };
+
+        for splits in [2, 3, 4, 7] {
+            let mut c = vec![f32::NAN; m * n];
+            unsafe { k_split_matmul(shim_matmul, a.as_ptr(), b.as_ptr(), c.as_mut_ptr(), m, k, n, false, splits) };
+            assert_eq!(c, expected, "splits {}", splits);
+
+            let mut c = vec![1f32; m * n];
+            unsafe { k_split_matmul(shim_matmul, a.as_ptr(), b.as_ptr(), c.as_mut_ptr(), m, k, n, true, splits) };
+            let accumulated: Vec<f32> = expected.iter().map(|x| x + 1.0).collect();
+            assert_eq!(c, accumulated, "accumulate, splits {}", splits);
+        }
+    }
+
+    #[test]
+    fn test_profile_report_shows_k_split_scratch() {
+        use crate::profiler::{ProfileScope, Profiler};
+
+        let _guard = crate::scheduler::TEST_STATE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let (m, k, n) = (2, 1024, 4);
+        let a = vec![1f32; m * k];
+        let b = vec![1f32; k * n];
+        let mut c = vec![0f32; m * n];
+
+        let profiler = Profiler::new();
+        profiler.enable();
+        for _ in 0..2 {
+            let _scope = ProfileScope::new(profiler.clone(), "k_split".to_string(), "CPU".to_string(), m * k * n);
+            unsafe { k_split_matmul(shim_matmul, a.as_ptr(), b.as_ptr(), c.as_mut_ptr(), m, k, n, false, 2) };
+        }
+        {
+            let _scope = ProfileScope::new(profiler.clone(), "plain".to_string(), "CPU".to_string(), m * k * n);
+            unsafe { shim_matmul(a.as_ptr(), b.as_ptr(), c.as_mut_ptr(), m, k, n) };
+        }
+
+        let report = profiler.generate_report(None, None);
+        let split = &report.operations["k_split"];
+        // Two tasks, each with an m x k/2 panel and an m x n partial
+        let per_call = 2 * (m * k / 2 + m * n) * std::mem::size_of::<f32>();
+        assert_eq!(split.max_arena_bytes, per_call as u64);
+        assert_eq!(split.avg_arena_bytes, per_call as f64);
+        assert_eq!(report.operations["plain"].max_arena_bytes, 0);
     }
 }
@@ -1,3 +1,238 @@
 pub mod elementwise;
 pub mod reduce;
 pub mod matmul;
+pub mod cast;
+pub mod linalg;
+pub mod broadcast;
+pub mod registry;
+pub mod alignment;
+
+use crate::backend::PolicyError;
+use crate::scheduler::cancel::Cancelled;
+
+/// Why a dispatch produced no result
+#[derive(Debug, Clone, PartialEq)]
+pub enum DispatchError {
+    /// The effective policy names a backend that cannot run here
+    Policy(PolicyError),
+    /// Stopped by request_cancel(), Ctrl-C or a deadline; outputs hold
+    /// unspecified contents
+    Cancelled(Cancelled),
+}
+
+impl std::fmt::Display for DispatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DispatchError::Policy(err) => err.fmt(f),
+            DispatchError::Cancelled(reason) => reason.fmt(f),
+        }
+    }
+}
+
+impl From<PolicyError> for DispatchError {
+    fn from(err: PolicyError) -> Self {
+        DispatchError::Policy(err)
+    }
+}
+
+impl From<Cancelled> for DispatchError {
+    fn from(reason: Cancelled) -> Self {
+        DispatchError::Cancelled(reason)
+    }
+}
+
+/// Why an op's element counts can't address its buffers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExtentError {
+    /// `count` elements of `elem_size` bytes span more than isize::MAX
+    /// bytes; the one count sizes every buffer in `args`
+    TooLarge { count: usize, elem_size: usize, args: Vec<String> },
+    /// The product of `dims` overflows usize
+    Overflow { dims: Vec<usize> },
+    /// Buffers that share one count have different lengths
+    Mismatch { args: Vec<String>, lengths: Vec<usize> },
+    /// Output `arg` has room for `capacity` elements but the op writes
+    /// `required`
+    OutTooSmall { arg: String, required: usize, capacity: usize },
+}
+
+impl std::fmt::Display for ExtentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExtentError::TooLarge { count, elem_size, args } => write!(
+                f,
+                "count {} of {}-byte elements spans {} bytes, more than isize::MAX; the count applies to each of {}",
+                count,
+                elem_size,
+                *count as u128 * *elem_size as u128,
+                join_names(args)
+            ),
+            ExtentError::Overflow { dims } => write!(f, "the product of {:?} overflows", dims),
+            ExtentError::Mismatch { args, lengths } => {
+                let lengths: Vec<String> = lengths.iter().map(|len| len.to_string()).collect();
+                write!(
+                    f,
+                    "{} must have the same length (got {}); one count applies to all of them",
+                    join_names(args),
+                    join_names(&lengths)
+                )
+            }
+            ExtentError::OutTooSmall { arg, required, capacity } => write!(
+                f,
+                "{} has room for {} elements but {} are required",
+                arg, capacity, required
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ExtentError {}
+
+/// "a", "a and b", "a, b and c"
+fn join_names(names: &[String]) -> String {
+    match names.split_last() {
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => format!("{} and {}", rest.join(", "), last),
+        None => String::new(),
+    }
+}
+
+fn owned_names(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+/// Bytes spanned by `count` elements of `elem_size` bytes, the extent of
+/// each buffer in `args`; slices and pointer offsets need it <= isize::MAX
+pub fn byte_extent(count: usize, elem_size: usize, args: &[&str]) -> Result<usize, ExtentError> {
+    count
+        .checked_mul(elem_size)
+        .filter(|&bytes| bytes <= isize::MAX as usize)
+        .ok_or_else(|| ExtentError::TooLarge { count, elem_size, args: owned_names(args) })
+}
+
+/// Product of `dims`, or Overflow
+pub fn checked_product(dims: &[usize]) -> Result<usize, ExtentError> {
+    if dims.contains(&0) {
+        return Ok(0);
+    }
+    dims.iter()
+        .try_fold(1usize, |acc, &dim| acc.checked_mul(dim))
+        .ok_or_else(|| ExtentError::Overflow { dims: dims.to_vec() })
+}
+
+/// Check that output `arg`, which the caller says holds `capacity`
+/// elements, can take the `required` an op writes; `None` trusts the caller
+pub fn check_capacity(arg: &str, required: usize, capacity: Option<usize>) -> Result<(), ExtentError> {
+    match capacity {
+        Some(capacity) if capacity < required => {
+            Err(ExtentError::OutTooSmall { arg: arg.to_string(), required, capacity })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Check A (m x k) and B (k x n) of `in_size`-byte elements and C (m x n) of
+/// `out_size`-byte elements, C against `out_capacity` (see check_capacity);
+/// returns the m*k*n multiply-adds
+pub fn matmul_extent(
+    m: usize, k: usize, n: usize,
+    in_size: usize, out_size: usize,
+    out_capacity: Option<usize>
+) -> Result<usize, ExtentError> {
+    byte_extent(checked_product(&[m, k])?, in_size, &["a_ptr"])?;
+    byte_extent(checked_product(&[k, n])?, in_size, &["b_ptr"])?;
+    let out = checked_product(&[m, n])?;
+    byte_extent(out, out_size, &["out_ptr"])?;
+    check_capacity("out_ptr", out, out_capacity)?;
+    checked_product(&[m, k, n])
+}
+
+/// The length shared by every (name, length) in `args`
+pub fn same_length(args: &[(&str, usize)]) -> Result<usize, ExtentError> {
+    let first = args.first().map_or(0, |&(_, len)| len);
+    if args.iter().any(|&(_, len)| len != first) {
+        return Err(ExtentError::Mismatch {
+            args: args.iter().map(|&(name, _)| name.to_string()).collect(),
+            lengths: args.iter().map(|&(_, len)| len).collect(),
+        });
+    }
+    Ok(first)
+}
+
+/// Safety wrapper for pointers to be Send/Sync for Rayon
+pub(crate) struct SendPtr<T>(pub(crate) *const T);
+unsafe impl<T> Send for SendPtr<T> {}
+unsafe impl<T> Sync for SendPtr<T> {}
+impl<T> SendPtr<T> {
+    #[inline]
+    pub(crate) fn ptr(&self) -> *const T { self.0 }
+}
+
+pub(crate) struct SendPtrMut<T>(pub(crate) *mut T);
+unsafe impl<T> Send for SendPtrMut<T> {}
+unsafe impl<T> Sync for SendPtrMut<T> {}
+impl<T> SendPtrMut<T> {
+    #[inline]
+    pub(crate) fn ptr(&self) -> *mut T { self.0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_byte_extent_boundary() {
+        let max = isize::MAX as usize;
+        assert_eq!(byte_extent(max / 4, 4, &["data_ptr"]), Ok(max / 4 * 4));
+        let err = byte_extent(max / 4 + 1, 4, &["a_ptr", "b_ptr", "out_ptr"]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "count {} of 4-byte elements spans {} bytes, more than isize::MAX; \
+                 the count applies to each of a_ptr, b_ptr and out_ptr",
+                max / 4 + 1,
+                (max / 4 + 1) as u128 * 4
+            )
+        );
+        assert!(byte_extent(usize::MAX, 8, &["ptr"]).is_err());
+        assert_eq!(byte_extent(0, 8, &["ptr"]), Ok(0));
+    }
+
+    #[test]
+    fn test_matmul_extent() {
+        assert_eq!(matmul_extent(2, 3, 4, 4, 4, None), Ok(24));
+        assert_eq!(matmul_extent(0, 5, 7, 4, 4, None), Ok(0));
+        assert_eq!(matmul_extent(2, 3, 4, 4, 4, Some(8)), Ok(24));
+
+        let side = 1usize << 22;
+        let err = matmul_extent(side, side, side, 4, 4, None);
+        assert_eq!(err, Err(ExtentError::Overflow { dims: vec![side, side, side] }));
+        assert_eq!(
+            matmul_extent(1 << 31, 1 << 31, 1, 4, 4, None),
+            Err(ExtentError::TooLarge { count: 1 << 62, elem_size: 4, args: vec!["a_ptr".to_string()] })
+        );
+    }
+
+    #[test]
+    fn test_check_capacity() {
+        assert_eq!(check_capacity("out_ptr", 10, None), Ok(()));
+        assert_eq!(check_capacity("out_ptr", 10, Some(10)), Ok(()));
+        assert_eq!(check_capacity("out_ptr", 0, Some(0)), Ok(()));
+        let err = check_capacity("out_ptr", 10, Some(9)).unwrap_err();
+        assert_eq!(err.to_string(), "out_ptr has room for 9 elements but 10 are required");
+        assert_eq!(
+            matmul_extent(3, 2, 5, 4, 4, Some(14)),
+            Err(ExtentError::OutTooSmall { arg: "out_ptr".to_string(), required: 15, capacity: 14 })
+        );
+    }
+
+    #[test]
+    fn test_same_length() {
+        assert_eq!(same_length(&[("a", 3), ("b", 3), ("out", 3)]), Ok(3));
+        let err = same_length(&[("a", 2), ("b", 2), ("out", 3)]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "a, b and out must have the same length (got 2, 2 and 3); one count applies to all of them"
+        );
+    }
+}
@@ -7,6 +7,12 @@
 // - Validate operation parameters
 // - Dispatch to appropriate C++ kernel
 // - Handle different data types and backends
+// - Record which path (serial C++ / Rayon parallel) ran for explain_last_dispatch
+
+use crate::backend::record_cpu_dispatch;
+use crate::scheduler::cancel::{self, Cancelled};
+use crate::scheduler::stats as scheduler_stats;
+use std::time::Instant;
 
 /// Threshold for parallel dispatch (elements)
 /// Below this: sequential C++ kernel  
@@ -58,97 +64,331 @@ pub unsafe fn all_bool_cpu_dispatch(data_ptr: *const u8, count: usize) -> bool {
     // PERFORMANCE: Arena scope ensures thread-local allocations are available
     // for future optimizations (e.g., temporary buffers)
     
-    with_arena(|_arena| {
+    let start = Instant::now();
+    let result = with_arena(|_scope| {
         all_bool_cpu(data_ptr, count)
-    })
+    });
+    record_cpu_dispatch("all", count, false, start);
+    result
 }
 
 /// Dispatch any() operation to CPU kernel
 pub unsafe fn any_bool_cpu_dispatch(data_ptr: *const u8, count: usize) -> bool {
     use crate::scheduler::arena::with_arena;
     
-    with_arena(|_arena| {
+    let start = Instant::now();
+    let result = with_arena(|_scope| {
         any_bool_cpu(data_ptr, count)
-    })
+    });
+    record_cpu_dispatch("any", count, false, start);
+    result
 }
 
+/// Signature shared by the C++ sum kernels
+type SumKernel<T> = unsafe extern "C" fn(*const T, usize) -> T;
+
 /// Dispatch sum() operation to CPU kernel (f32)
-/// Automatically parallelizes for large arrays (>100K elements)
-pub unsafe fn sum_f32_cpu_dispatch(data_ptr: *const f32, count: usize) -> f32 {
+/// Automatically parallelizes for large arrays (>1M elements)
+///
+/// `max_threads` caps the workers used (Some(1) = sequential kernel only);
+/// calls from a Rayon worker thread always run sequentially. Fails with
+/// Cancelled if cancellation is requested, or the calling thread's deadline
+/// (cancel::with_deadline) passes, before or while it runs.
+pub unsafe fn sum_f32_cpu_dispatch(data_ptr: *const f32, count: usize, max_threads: Option<usize>) -> Result<f32, Cancelled> {
+    sum_cpu_dispatch(sum_f32_cpu, data_ptr, count, PARALLEL_THRESHOLD_F32, max_threads)
+}
+
+/// Dispatch sum() operation to CPU kernel (i32)
+/// Automatically parallelizes for large arrays (>1M elements)
+pub unsafe fn sum_i32_cpu_dispatch(data_ptr: *const i32, count: usize, max_threads: Option<usize>) -> Result<i32, Cancelled> {
+    sum_cpu_dispatch(sum_i32_cpu, data_ptr, count, PARALLEL_THRESHOLD_I32, max_threads)
+}
+
+/// Whether a reduction over `count` elements takes the Rayon path
+fn use_parallel(count: usize, threshold: usize, max_threads: Option<usize>) -> bool {
+    count >= threshold && max_threads != Some(1)
+}
+
+/// Thread cap for a dispatch: called from a worker thread (e.g. inside the
+/// caller's own par_iter), run sequentially instead of nesting a split
+fn nested_max_threads(max_threads: Option<usize>) -> Option<usize> {
+    if crate::scheduler::rayon_pool::in_worker_thread() { Some(1) } else { max_threads }
+}
+
+unsafe fn sum_cpu_dispatch<T: Copy + Send + Sync + std::iter::Sum<T>>(
+    kernel: SumKernel<T>,
+    data_ptr: *const T,
+    count: usize,
+    threshold: usize,
+    max_threads: Option<usize>,
+) -> Result<T, Cancelled> {
     use crate::scheduler::arena::with_arena;
+    use crate::scheduler::rayon_pool::with_max_threads;
     
-    with_arena(|_arena| {
-        if count >= PARALLEL_THRESHOLD_F32 {
-            // Parallel path: use Rayon
-            parallel_sum_f32_cpu(data_ptr, count)
-        } else {
-            // Sequential path: direct C++ kernel
-            sum_f32_cpu(data_ptr, count)
-        }
+    cancel::check()?;
+    let start = Instant::now();
+    let max_threads = nested_max_threads(max_threads);
+    let parallel = use_parallel(count, threshold, max_threads);
+    with_max_threads(max_threads, || {
+        let result = with_arena(|_scope| {
+            if parallel {
+                // Parallel path: use Rayon
+                parallel_sum_cpu(kernel, data_ptr, count)
+            } else {
+                // Sequential path: direct C++ kernel
+                scheduler_stats::record_serial();
+                kernel(data_ptr, count)
+            }
+        });
+        record_cpu_dispatch("sum", count, parallel, start);
+        cancel::check().map(|()| result)
     })
 }
 
 /// Parallel sum implementation using Rayon
-unsafe fn parallel_sum_f32_cpu(data_ptr: *const f32, count: usize) -> f32 {
-    use rayon::prelude::*;
+unsafe fn parallel_sum_cpu<T: Copy + Send + Sync + std::iter::Sum<T>>(
+    kernel: SumKernel<T>,
+    data_ptr: *const T,
+    count: usize,
+) -> T {
+    use crate::profiler::{task_context, with_task_context};
+    use crate::scheduler::{chunking, numa, progress, rayon_pool};
     
     let slice = std::slice::from_raw_parts(data_ptr, count);
     
-    // Divide work across CPUs
-    let num_threads = num_cpus::get();
-    let chunk_size = (count + num_threads - 1) / num_threads;
+    // Cache-sized chunks, at least one per worker
+    let chunk_size = chunking::plan(count, std::mem::size_of::<T>()).chunk_len;
+    scheduler_stats::record_parallel(count, count.div_ceil(chunk_size));
+    progress::begin(count.div_ceil(chunk_size));
     
-    // Parallel reduction
-    slice.par_chunks(chunk_size)
-         .map(|chunk| unsafe {
-             // Call C++ AVX2 kernel per chunk instead of scalar Rust sum
-             sum_f32_cpu(chunk.as_ptr(), chunk.len())
-         })
-         .sum()
+    // Parallel reduction over node-sized stripes (workers inherit the
+    // caller's profiling context); the C++ SIMD kernel sums each chunk
+    let context = task_context();
+    let deadline = cancel::deadline();
+    rayon_pool::install(|| {
+        numa::striped_chunk_sum(slice, chunk_size, |chunk| with_task_context(context.clone(), || unsafe {
+            // Cancelled or out of time: skip the rest (the caller discards the total)
+            if cancel::check_deadline(deadline).is_err() {
+                return std::iter::empty::<T>().sum();
+            }
+            let sum = kernel(chunk.as_ptr(), chunk.len());
+            progress::advance(1);
+            sum
+        }))
+    })
 }
 
-/// Dispatch sum() operation to CPU kernel (i32)
-/// Automatically parallelizes for large arrays (>100K elements)
-pub unsafe fn sum_i32_cpu_dispatch(data_ptr: *const i32, count: usize) -> i32 {
+/// Dispatch mean() operation to CPU kernel (f32)
+/// Automatically parallelizes for large arrays (>1M elements)
+pub unsafe fn mean_f32_cpu_dispatch(data_ptr: *const f32, count: usize, max_threads: Option<usize>) -> Result<f32, Cancelled> {
     use crate::scheduler::arena::with_arena;
+    use crate::scheduler::rayon_pool::with_max_threads;
     
-    with_arena(|_arena| {
-        if count >= PARALLEL_THRESHOLD_I32 {
-            parallel_sum_i32_cpu(data_ptr, count)
-        } else {
-            sum_i32_cpu(data_ptr, count)
-        }
+    cancel::check()?;
+    let start = Instant::now();
+    let max_threads = nested_max_threads(max_threads);
+    let parallel = use_parallel(count, PARALLEL_THRESHOLD_F32, max_threads);
+    with_max_threads(max_threads, || {
+        let result = with_arena(|_scope| {
+            if parallel {
+                // Parallel sum + divide
+                let sum = parallel_sum_cpu(sum_f32_cpu, data_ptr, count);
+                sum / (count as f32)
+            } else {
+                scheduler_stats::record_serial();
+                mean_f32_cpu(data_ptr, count)
+            }
+        });
+        record_cpu_dispatch("mean", count, parallel, start);
+        cancel::check().map(|()| result)
     })
 }
 
-/// Parallel sum implementation for i32
-unsafe fn parallel_sum_i32_cpu(data_ptr: *const i32, count: usize) -> i32 {
-    use rayon::prelude::*;
-    
-    let slice = std::slice::from_raw_parts(data_ptr, count);
-    let num_threads = num_cpus::get();
-    let chunk_size = (count + num_threads - 1) / num_threads;
-    
-    slice.par_chunks(chunk_size)
-         .map(|chunk| unsafe {
-             // Call C++ SIMD kernel per chunk
-             sum_i32_cpu(chunk.as_ptr(), chunk.len())
-         })
-         .sum()
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{get_last_dispatch_info, BACKEND_CPU_SERIAL};
+    use std::sync::Mutex;
 
-/// Dispatch mean() operation to CPU kernel (f32)
-/// Automatically parallelizes for large arrays (>100K elements)
-pub unsafe fn mean_f32_cpu_dispatch(data_ptr: *const f32, count: usize) -> f32 {
-    use crate::scheduler::arena::with_arena;
-    
-    with_arena(|_arena| {
-        if count >= PARALLEL_THRESHOLD_F32 {
-            // Parallel sum + divide
-            let sum = parallel_sum_f32_cpu(data_ptr, count);
-            sum / (count as f32)
-        } else {
-            mean_f32_cpu(data_ptr, count)
+    /// rayon::current_thread_index() of every shim_sum call
+    static SHIM_CALLS: Mutex<Vec<Option<usize>>> = Mutex::new(Vec::new());
+
+    /// Serializes tests that read SHIM_CALLS (and the scheduler's global state)
+    use crate::scheduler::TEST_STATE_LOCK as SHIM_LOCK;
+
+    /// Stand-in for the C++ kernel that records where it ran
+    unsafe extern "C" fn shim_sum(data_ptr: *const i32, count: usize) -> i32 {
+        SHIM_CALLS.lock().unwrap().push(rayon::current_thread_index());
+        std::slice::from_raw_parts(data_ptr, count).iter().sum()
+    }
+
+    fn shim_dispatch(data: &[i32], max_threads: Option<usize>) -> (i32, Vec<Option<usize>>) {
+        SHIM_CALLS.lock().unwrap().clear();
+        let sum = unsafe {
+            sum_cpu_dispatch(shim_sum, data.as_ptr(), data.len(), PARALLEL_THRESHOLD_I32, max_threads).unwrap()
+        };
+        (sum, std::mem::take(&mut *SHIM_CALLS.lock().unwrap()))
+    }
+
+    #[test]
+    fn test_max_threads_sum() {
+        let _guard = SHIM_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let data: Vec<i32> = (0..2_000_003).map(|i| i % 7).collect();
+        let expected: i32 = data.iter().sum();
+
+        // Some(1): one kernel call on the calling thread, no Rayon tasks
+        let (sum, calls) = shim_dispatch(&data, Some(1));
+        assert_eq!(sum, expected);
+        assert_eq!(calls, vec![None]);
+        let info = get_last_dispatch_info().unwrap();
+        assert_eq!((info.backend_id, info.num_threads), (BACKEND_CPU_SERIAL, 1));
+
+        for max_threads in [Some(2), Some(3), None] {
+            let (sum, calls) = shim_dispatch(&data, max_threads);
+            assert_eq!(sum, expected);
+            assert!(calls.iter().all(Option::is_some));
+            let info = get_last_dispatch_info().unwrap();
+            assert!(info.num_threads <= max_threads.unwrap_or(usize::MAX));
         }
-    })
+    }
+
+    #[test]
+    fn test_nested_calls_take_serial_path() {
+        use rayon::prelude::*;
+
+        let _guard = SHIM_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        SHIM_CALLS.lock().unwrap().clear();
+        let data: Vec<i32> = (0..1_500_000).map(|i| i % 5).collect();
+        let expected: i32 = data.iter().sum();
+
+        // A caller's own par_iter on rayon's global pool
+        let results: Vec<(i32, u8, bool, usize)> = (0..4).into_par_iter().map(|_| {
+            let sum = unsafe {
+                sum_cpu_dispatch(shim_sum, data.as_ptr(), data.len(), PARALLEL_THRESHOLD_I32, None).unwrap()
+            };
+            let info = get_last_dispatch_info().unwrap();
+            (sum, info.backend_id, info.nested, info.num_threads)
+        }).collect();
+
+        for result in results {
+            assert_eq!(result, (expected, BACKEND_CPU_SERIAL, true, 1));
+        }
+        // One whole-buffer kernel call per nested dispatch
+        assert_eq!(SHIM_CALLS.lock().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_scheduler_stats_count_paths() {
+        use crate::scheduler::stats::{get_stats, reset_stats, SchedulerStats};
+
+        let _guard = SHIM_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let small: Vec<i32> = vec![1; 1000];
+        let huge: Vec<i32> = vec![1; 2_000_000];
+
+        let before = get_stats();
+        assert_eq!(shim_dispatch(&small, None).0, 1000);
+        assert_eq!(shim_dispatch(&huge, None).0, 2_000_000);
+        let after = get_stats();
+        assert_eq!(after.serial_dispatches - before.serial_dispatches, 1);
+        assert_eq!(after.parallel_dispatches - before.parallel_dispatches, 1);
+        assert_eq!(after.total_parallel_elements - before.total_parallel_elements, 2_000_000);
+        assert!(after.tasks_spawned > before.tasks_spawned);
+
+        reset_stats();
+        assert_eq!(get_stats(), SchedulerStats::default());
+    }
+
+    /// shim_sum that takes long enough per chunk for progress to be observed
+    unsafe extern "C" fn slow_shim_sum(data_ptr: *const i32, count: usize) -> i32 {
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        std::slice::from_raw_parts(data_ptr, count).iter().sum()
+    }
+
+    #[test]
+    fn test_progress_increases_monotonically() {
+        use crate::scheduler::progress;
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let _guard = SHIM_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let data: Vec<i32> = vec![1; 4_000_000];
+        let finished = AtomicBool::new(false);
+
+        let readings = std::thread::scope(|scope| {
+            let poller = scope.spawn(|| {
+                let mut readings = Vec::new();
+                while !finished.load(Ordering::Acquire) {
+                    readings.push(progress::get());
+                    std::thread::sleep(std::time::Duration::from_micros(200));
+                }
+                readings.push(progress::get());
+                readings
+            });
+            // Two workers, so chunks finish a couple at a time
+            let sum = unsafe {
+                sum_cpu_dispatch(slow_shim_sum, data.as_ptr(), data.len(), PARALLEL_THRESHOLD_I32, Some(2)).unwrap()
+            };
+            assert_eq!(sum, 4_000_000);
+            finished.store(true, Ordering::Release);
+            poller.join().unwrap()
+        });
+
+        let (done, total) = *readings.last().unwrap();
+        assert!(total >= 2);
+        assert_eq!(done, total);
+        let during: Vec<u64> = readings.iter().filter(|&&(_, t)| t == total).map(|&(d, _)| d).collect();
+        assert!(during.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(during.iter().any(|&d| 0 < d && d < total), "no intermediate reading: {:?}", readings);
+    }
+
+    #[test]
+    fn test_cancel_stops_parallel_sum() {
+        use std::time::{Duration, Instant};
+
+        let _guard = SHIM_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        // ~20 chunks of 5 ms each on two workers would take ~50 ms+; cancel after 10
+        let data: Vec<i32> = vec![1; 4_000_000];
+        let started = Instant::now();
+        let result = std::thread::scope(|scope| {
+            scope.spawn(|| {
+                std::thread::sleep(Duration::from_millis(10));
+                cancel::request_cancel();
+            });
+            unsafe { sum_cpu_dispatch(slow_shim_sum, data.as_ptr(), data.len(), PARALLEL_THRESHOLD_I32, Some(2)) }
+        });
+        assert_eq!(result, Err(Cancelled::Requested));
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        // Sticky until cleared
+        assert_eq!(shim_dispatch_result(&[1, 2, 3]), Err(Cancelled::Requested));
+        cancel::clear_cancel();
+        assert_eq!(shim_dispatch_result(&[1, 2, 3]), Ok(6));
+    }
+
+    #[test]
+    fn test_deadline_stops_parallel_sum() {
+        use crate::scheduler::progress;
+        use std::time::{Duration, Instant};
+
+        let _guard = SHIM_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let data: Vec<i32> = vec![1; 4_000_000];
+        let budget = Duration::from_millis(15);
+        let started = Instant::now();
+        let result = cancel::with_deadline(Some(started + budget), || unsafe {
+            sum_cpu_dispatch(slow_shim_sum, data.as_ptr(), data.len(), PARALLEL_THRESHOLD_I32, Some(2))
+        });
+        let elapsed = started.elapsed();
+        assert_eq!(result, Err(Cancelled::DeadlineExceeded));
+        // In-flight chunks finish, the rest are skipped
+        let (done, total) = progress::get();
+        assert!(done < total, "every chunk ran: {}/{}", done, total);
+        assert!(elapsed >= budget && elapsed < budget + Duration::from_secs(1), "{:?}", elapsed);
+
+        // The deadline belongs to the with_deadline call only
+        assert_eq!(cancel::deadline(), None);
+        assert_eq!(shim_dispatch_result(&[1, 2, 3]), Ok(6));
+    }
+
+    fn shim_dispatch_result(data: &[i32]) -> Result<i32, Cancelled> {
+        unsafe { sum_cpu_dispatch(shim_sum, data.as_ptr(), data.len(), PARALLEL_THRESHOLD_I32, None) }
+    }
 }
@@ -0,0 +1,339 @@
+// ============================================================================
+// Operations: Kernel Registry
+// ============================================================================
+// This module maps (operation, dtype) pairs to their CPU kernels, so the FFI
+// can expose one entry point per operation family instead of one per dtype.
+//
+// RESPONSIBILITIES:
+// - Type-erased reduction and element-wise kernel tables
+// - Lookup with an error that lists what is supported
+// - Empty-input results per reduction (sum 0, all true, ...)
+//
+// DESIGN:
+// - Kernels take untyped pointers; each entry casts to its element type and
+//   calls the existing typed *_dispatch function, so dispatch stats,
+//   parallel thresholds and cancellation behave as in the typed entry points
+// - Tables are built once, on first lookup
+// - Op codes: reductions 0 sum, 1 mean, 2 all, 3 any; binary ops as in
+//   broadcast::BinaryOp (0 add, 1 sub, 2 mul, 3 div). dtype codes are
+//   DType's discriminants
+
+use lazy_static::lazy_static;
+use std::collections::BTreeMap;
+
+use super::broadcast::BinaryOp;
+use crate::scheduler::cancel::Cancelled;
+use crate::tensor::dtype::DType;
+
+/// Reduction, by FFI op code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+pub enum ReduceOp {
+    Sum = 0,
+    Mean = 1,
+    All = 2,
+    Any = 3,
+}
+
+impl ReduceOp {
+    pub fn from_code(code: u8) -> Option<ReduceOp> {
+        match code {
+            0 => Some(ReduceOp::Sum),
+            1 => Some(ReduceOp::Mean),
+            2 => Some(ReduceOp::All),
+            3 => Some(ReduceOp::Any),
+            _ => None,
+        }
+    }
+
+    /// Inverse of name()
+    pub fn from_name(name: &str) -> Option<ReduceOp> {
+        (0..4).filter_map(ReduceOp::from_code).find(|op| op.name() == name)
+    }
+
+    /// Operation name used for dispatch stats and profiling
+    pub fn name(self) -> &'static str {
+        match self {
+            ReduceOp::Sum => "sum",
+            ReduceOp::Mean => "mean",
+            ReduceOp::All => "all",
+            ReduceOp::Any => "any",
+        }
+    }
+}
+
+/// Result of a reduction, in the kernel's own type
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scalar {
+    F32(f32),
+    I32(i32),
+    Bool(bool),
+}
+
+impl Scalar {
+    pub fn as_f32(self) -> Option<f32> {
+        match self {
+            Scalar::F32(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_i32(self) -> Option<i32> {
+        match self {
+            Scalar::I32(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(self) -> Option<bool> {
+        match self {
+            Scalar::Bool(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// Reduce `count` elements at the pointer, with an optional worker cap
+pub type ReduceFn = unsafe fn(*const u8, usize, Option<usize>) -> Result<Scalar, Cancelled>;
+
+/// out[i] = a[i] <op> b[i] for `count` elements
+pub type BinaryFn = unsafe fn(*const u8, *const u8, *mut u8, usize);
+
+/// A registered reduction
+#[derive(Clone, Copy)]
+pub struct ReduceKernel {
+    pub run: ReduceFn,
+    /// Result for zero elements; None if the reduction is undefined there
+    pub empty: Option<Scalar>,
+}
+
+/// Why no kernel was found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KernelError {
+    /// Op code not known at all
+    UnknownOp(u8),
+    /// dtype code not known at all
+    UnknownDtype(u8),
+    /// Both known, but no kernel for the pair; lists the supported pairs
+    Unsupported { op: &'static str, dtype: DType, supported: Vec<String> },
+}
+
+impl std::fmt::Display for KernelError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KernelError::UnknownOp(code) => write!(f, "unknown op code {}", code),
+            KernelError::UnknownDtype(code) => write!(f, "unknown dtype code {}", code),
+            KernelError::Unsupported { op, dtype, supported } => write!(
+                f,
+                "unsupported op/dtype combination {}/{} (supported: {})",
+                op,
+                dtype,
+                supported.join(", ")
+            ),
+        }
+    }
+}
+
+/// (operation, dtype) -> kernel tables
+#[derive(Default)]
+pub struct KernelRegistry {
+    reduce: BTreeMap<(ReduceOp, DType), ReduceKernel>,
+    binary: BTreeMap<(BinaryOp, DType), BinaryFn>,
+}
+
+unsafe fn sum_f32(data: *const u8, count: usize, max_threads: Option<usize>) -> Result<Scalar, Cancelled> {
+    super::reduce::sum_f32_cpu_dispatch(data.cast(), count, max_threads).map(Scalar::F32)
+}
+
+unsafe fn sum_i32(data: *const u8, count: usize, max_threads: Option<usize>) -> Result<Scalar, Cancelled> {
+    super::reduce::sum_i32_cpu_dispatch(data.cast(), count, max_threads).map(Scalar::I32)
+}
+
+unsafe fn mean_f32(data: *const u8, count: usize, max_threads: Option<usize>) -> Result<Scalar, Cancelled> {
+    super::reduce::mean_f32_cpu_dispatch(data.cast(), count, max_threads).map(Scalar::F32)
+}
+
+unsafe fn all_bool(data: *const u8, count: usize, _max_threads: Option<usize>) -> Result<Scalar, Cancelled> {
+    Ok(Scalar::Bool(super::reduce::all_bool_cpu_dispatch(data, count)))
+}
+
+unsafe fn any_bool(data: *const u8, count: usize, _max_threads: Option<usize>) -> Result<Scalar, Cancelled> {
+    Ok(Scalar::Bool(super::reduce::any_bool_cpu_dispatch(data, count)))
+}
+
+unsafe fn add_f32(a: *const u8, b: *const u8, out: *mut u8, count: usize) {
+    super::elementwise::add_f32_cpu_dispatch(a.cast(), b.cast(), out.cast(), count)
+}
+
+unsafe fn sub_f32(a: *const u8, b: *const u8, out: *mut u8, count: usize) {
+    super::elementwise::sub_f32_cpu_dispatch(a.cast(), b.cast(), out.cast(), count)
+}
+
+unsafe fn mul_f32(a: *const u8, b: *const u8, out: *mut u8, count: usize) {
+    super::elementwise::mul_f32_cpu_dispatch(a.cast(), b.cast(), out.cast(), count)
+}
+
+unsafe fn div_f32(a: *const u8, b: *const u8, out: *mut u8, count: usize) {
+    super::elementwise::div_f32_cpu_dispatch(a.cast(), b.cast(), out.cast(), count)
+}
+
+fn dtype_from_code(code: u8) -> Result<DType, KernelError> {
+    DType::from_code(code).ok_or(KernelError::UnknownDtype(code))
+}
+
+fn pair_names<O: Copy>(keys: impl Iterator<Item = (O, DType)>, name: impl Fn(O) -> &'static str) -> Vec<String> {
+    keys.map(|(op, dtype)| format!("{}/{}", name(op), dtype)).collect()
+}
+
+impl KernelRegistry {
+    /// The C++ CPU kernels
+    fn builtin() -> Self {
+        let mut registry = KernelRegistry::default();
+        registry.register_reduce(ReduceOp::Sum, DType::Float32, sum_f32, Some(Scalar::F32(0.0)));
+        registry.register_reduce(ReduceOp::Sum, DType::Int32, sum_i32, Some(Scalar::I32(0)));
+        registry.register_reduce(ReduceOp::Mean, DType::Float32, mean_f32, None);
+        registry.register_reduce(ReduceOp::All, DType::Bool, all_bool, Some(Scalar::Bool(true)));
+        registry.register_reduce(ReduceOp::Any, DType::Bool, any_bool, Some(Scalar::Bool(false)));
+
+        registry.register_binary(BinaryOp::Add, DType::Float32, add_f32);
+        registry.register_binary(BinaryOp::Sub, DType::Float32, sub_f32);
+        registry.register_binary(BinaryOp::Mul, DType::Float32, mul_f32);
+        registry.register_binary(BinaryOp::Div, DType::Float32, div_f32);
+        registry
+    }
+
+    /// Add (or replace) the reduction for (`op`, `dtype`)
+    pub fn register_reduce(&mut self, op: ReduceOp, dtype: DType, run: ReduceFn, empty: Option<Scalar>) {
+        self.reduce.insert((op, dtype), ReduceKernel { run, empty });
+    }
+
+    /// Add (or replace) the element-wise kernel for (`op`, `dtype`)
+    pub fn register_binary(&mut self, op: BinaryOp, dtype: DType, kernel: BinaryFn) {
+        self.binary.insert((op, dtype), kernel);
+    }
+
+    /// Reduction kernel for (`op_code`, `dtype_code`)
+    pub fn reduce(&self, op_code: u8, dtype_code: u8) -> Result<(ReduceOp, ReduceKernel), KernelError> {
+        let op = ReduceOp::from_code(op_code).ok_or(KernelError::UnknownOp(op_code))?;
+        let dtype = dtype_from_code(dtype_code)?;
+        match self.reduce.get(&(op, dtype)) {
+            Some(kernel) => Ok((op, *kernel)),
+            None => Err(KernelError::Unsupported {
+                op: op.name(),
+                dtype,
+                supported: pair_names(self.reduce.keys().copied(), ReduceOp::name),
+            }),
+        }
+    }
+
+    /// Element-wise kernel for (`op_code`, `dtype_code`)
+    pub fn binary(&self, op_code: u8, dtype_code: u8) -> Result<(BinaryOp, BinaryFn), KernelError> {
+        let op = BinaryOp::from_code(op_code).ok_or(KernelError::UnknownOp(op_code))?;
+        let dtype = dtype_from_code(dtype_code)?;
+        match self.binary.get(&(op, dtype)) {
+            Some(kernel) => Ok((op, *kernel)),
+            None => Err(KernelError::Unsupported {
+                op: op.name(),
+                dtype,
+                supported: pair_names(self.binary.keys().copied(), BinaryOp::name),
+            }),
+        }
+    }
+
+    /// Every registered (kind, op, dtype), kind being "reduce" or "binary"
+    pub fn entries(&self) -> Vec<(&'static str, &'static str, DType)> {
+        let reduce = self.reduce.keys().map(|&(op, dtype)| ("reduce", op.name(), dtype));
+        let binary = self.binary.keys().map(|&(op, dtype)| ("binary", op.name(), dtype));
+        reduce.chain(binary).collect()
+    }
+}
+
+lazy_static! {
+    static ref KERNELS: KernelRegistry = KernelRegistry::builtin();
+}
+
+/// The process-wide kernel registry
+pub fn kernels() -> &'static KernelRegistry {
+    &KERNELS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    unsafe fn shim_sum_i32(data: *const u8, count: usize, _max_threads: Option<usize>) -> Result<Scalar, Cancelled> {
+        let data = std::slice::from_raw_parts(data.cast::<i32>(), count);
+        Ok(Scalar::I32(data.iter().sum()))
+    }
+
+    unsafe fn shim_any_bool(data: *const u8, count: usize, _max_threads: Option<usize>) -> Result<Scalar, Cancelled> {
+        Ok(Scalar::Bool(std::slice::from_raw_parts(data, count).iter().any(|&b| b != 0)))
+    }
+
+    unsafe fn shim_add_f32(a: *const u8, b: *const u8, out: *mut u8, count: usize) {
+        for i in 0..count {
+            *out.cast::<f32>().add(i) = *a.cast::<f32>().add(i) + *b.cast::<f32>().add(i);
+        }
+    }
+
+    /// Registry shaped like the builtin one, over Rust shims
+    fn shim_registry() -> KernelRegistry {
+        let mut registry = KernelRegistry::default();
+        registry.register_reduce(ReduceOp::Sum, DType::Int32, shim_sum_i32, Some(Scalar::I32(0)));
+        registry.register_reduce(ReduceOp::Any, DType::Bool, shim_any_bool, Some(Scalar::Bool(false)));
+        registry.register_binary(BinaryOp::Add, DType::Float32, shim_add_f32);
+        registry
+    }
+
+    #[test]
+    fn test_every_registered_pair_runs_and_others_error() {
+        let registry = shim_registry();
+        let entries = registry.entries();
+        assert_eq!(
+            entries,
+            vec![("reduce", "sum", DType::Int32), ("reduce", "any", DType::Bool), ("binary", "add", DType::Float32)]
+        );
+
+        for op in 0..4 {
+            for code in 0..12 {
+                let dtype = DType::from_code(code).unwrap();
+                match registry.reduce(op, code) {
+                    Ok((op, _)) => assert!(entries.contains(&("reduce", op.name(), dtype))),
+                    Err(err) => assert!(matches!(err, KernelError::Unsupported { .. })),
+                }
+                match registry.binary(op, code) {
+                    Ok((op, _)) => assert!(entries.contains(&("binary", op.name(), dtype))),
+                    Err(err) => assert!(matches!(err, KernelError::Unsupported { .. })),
+                }
+            }
+        }
+
+        let ints = [1i32, 2, 3, 4];
+        let (_, sum) = registry.reduce(ReduceOp::Sum as u8, DType::Int32.code()).unwrap();
+        assert_eq!(unsafe { (sum.run)(ints.as_ptr().cast(), 4, None) }, Ok(Scalar::I32(10)));
+        let (_, any) = registry.reduce(ReduceOp::Any as u8, DType::Bool.code()).unwrap();
+        assert_eq!(unsafe { (any.run)([0u8, 0, 1].as_ptr(), 3, None) }, Ok(Scalar::Bool(true)));
+        assert_eq!(any.empty, Some(Scalar::Bool(false)));
+
+        let (a, b, mut out) = ([1f32, 2.0], [10f32, 20.0], [0f32; 2]);
+        let (_, add) = registry.binary(BinaryOp::Add as u8, DType::Float32.code()).unwrap();
+        unsafe { add(a.as_ptr().cast(), b.as_ptr().cast(), out.as_mut_ptr().cast(), 2) };
+        assert_eq!(out, [11.0, 22.0]);
+    }
+
+    #[test]
+    fn test_lookup_errors() {
+        let registry = shim_registry();
+        let err = registry.reduce(ReduceOp::Sum as u8, DType::Float64.code()).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "unsupported op/dtype combination sum/float64 (supported: sum/int32, any/bool)"
+        );
+        let err = registry.binary(BinaryOp::Mul as u8, DType::Float32.code()).err().unwrap();
+        assert_eq!(err.to_string(), "unsupported op/dtype combination mul/float32 (supported: add/float32)");
+        assert_eq!(registry.reduce(9, 0).err(), Some(KernelError::UnknownOp(9)));
+        assert_eq!(ReduceOp::from_name("mean"), Some(ReduceOp::Mean));
+        assert_eq!(ReduceOp::from_name("prod"), None);
+        assert_eq!(registry.binary(0, 99).err(), Some(KernelError::UnknownDtype(99)));
+    }
+}
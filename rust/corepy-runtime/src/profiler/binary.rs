@@ -0,0 +1,265 @@
+//! Compact binary encoding of profiling events
+//!
+//! Layout: the magic bytes `CPEV`, a schema version byte, a format byte, then
+//! the event list in that format. Events go through a fixed-layout wire record
+//! (no skipped fields), so the non-self-describing formats read back exactly.
+
+use super::metrics::{CallSite, EventKind, OperationEvent};
+use bincode::Options;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+/// First bytes of every binary event export
+pub const BINARY_MAGIC: &[u8; 4] = b"CPEV";
+
+/// Wire schema version; bump whenever WireEvent changes
+pub const BINARY_VERSION: u8 = 2;
+
+/// Encoding of the event list after the header (bincode with varint integers)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryFormat {
+    Bincode,
+    MessagePack,
+}
+
+/// Accepted BinaryFormat names
+pub const VALID_BINARY_FORMATS: &[&str] = &["bincode", "msgpack"];
+
+impl BinaryFormat {
+    /// Parse a format name (case-insensitive, see VALID_BINARY_FORMATS)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "bincode" => Some(BinaryFormat::Bincode),
+            "msgpack" | "messagepack" => Some(BinaryFormat::MessagePack),
+            _ => None,
+        }
+    }
+
+    fn tag(self) -> u8 {
+        match self {
+            BinaryFormat::Bincode => 0,
+            BinaryFormat::MessagePack => 1,
+        }
+    }
+
+    fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            0 => Some(BinaryFormat::Bincode),
+            1 => Some(BinaryFormat::MessagePack),
+            _ => None,
+        }
+    }
+}
+
+/// OperationEvent with every field always present, in a fixed order
+#[derive(Serialize, Deserialize)]
+struct WireEvent<'a> {
+    operation: Cow<'a, str>,
+    backend: Cow<'a, str>,
+    data_size: usize,
+    start_time_us: u64,
+    end_time_us: u64,
+    context: Option<Cow<'a, str>>,
+    dims: Option<(usize, usize, usize)>,
+    thread_id: u64,
+    thread_name: Option<Cow<'a, str>>,
+    sample_weight: u32,
+    seq: u64,
+    arena_bytes_used: u64,
+    heap_bytes_allocated: u64,
+    kind: EventKind,
+    metadata: Option<Cow<'a, str>>,
+    warmup: bool,
+    attributes: Cow<'a, [(String, String)]>,
+    gil_held_us: u64,
+    gil_released_us: u64,
+    session: Option<u64>,
+    call_site: Option<Cow<'a, CallSite>>,
+}
+
+impl<'a> From<&'a OperationEvent> for WireEvent<'a> {
+    fn from(e: &'a OperationEvent) -> Self {
+        WireEvent {
+            operation: Cow::Borrowed(&e.operation),
+            backend: Cow::Borrowed(&e.backend),
+            data_size: e.data_size,
+            start_time_us: e.start_time_us,
+            end_time_us: e.end_time_us,
+            context: e.context.as_deref().map(Cow::Borrowed),
+            dims: e.dims,
+            thread_id: e.thread_id,
+            thread_name: e.thread_name.as_deref().map(Cow::Borrowed),
+            sample_weight: e.sample_weight,
+            seq: e.seq,
+            arena_bytes_used: e.arena_bytes_used,
+            heap_bytes_allocated: e.heap_bytes_allocated,
+            kind: e.kind,
+            metadata: e.metadata.as_deref().map(Cow::Borrowed),
+            warmup: e.warmup,
+            attributes: Cow::Borrowed(&e.attributes),
+            gil_held_us: e.gil_held_us,
+            gil_released_us: e.gil_released_us,
+            session: e.session,
+            call_site: e.call_site.as_ref().map(Cow::Borrowed),
+        }
+    }
+}
+
+impl From<WireEvent<'_>> for OperationEvent {
+    fn from(w: WireEvent<'_>) -> Self {
+        OperationEvent {
+            operation: w.operation.into_owned(),
+            backend: w.backend.into_owned(),
+            data_size: w.data_size,
+            start_time_us: w.start_time_us,
+            end_time_us: w.end_time_us,
+            context: w.context.map(Cow::into_owned),
+            dims: w.dims,
+            thread_id: w.thread_id,
+            thread_name: w.thread_name.map(Cow::into_owned),
+            sample_weight: w.sample_weight,
+            seq: w.seq,
+            arena_bytes_used: w.arena_bytes_used,
+            heap_bytes_allocated: w.heap_bytes_allocated,
+            kind: w.kind,
+            metadata: w.metadata.map(Cow::into_owned),
+            warmup: w.warmup,
+            attributes: w.attributes.into_owned(),
+            gil_held_us: w.gil_held_us,
+            gil_released_us: w.gil_released_us,
+            session: w.session,
+            call_site: w.call_site.map(Cow::into_owned),
+        }
+    }
+}
+
+/// Encode `events` with the versioned header
+pub fn encode_events(events: &[OperationEvent], format: BinaryFormat) -> Result<Vec<u8>, String> {
+    let wire: Vec<WireEvent<'_>> = events.iter().map(WireEvent::from).collect();
+    let mut out = Vec::with_capacity(BINARY_MAGIC.len() + 2 + events.len() * 64);
+    out.extend_from_slice(BINARY_MAGIC);
+    out.push(BINARY_VERSION);
+    out.push(format.tag());
+    match format {
+        BinaryFormat::Bincode => bincode::DefaultOptions::new()
+            .serialize_into(&mut out, &wire)
+            .map_err(|e| e.to_string())?,
+        BinaryFormat::MessagePack => rmp_serde::encode::write(&mut out, &wire).map_err(|e| e.to_string())?,
+    }
+    Ok(out)
+}
+
+/// Decode an encode_events() buffer, rejecting unknown headers and versions
+pub fn decode_events(bytes: &[u8]) -> Result<Vec<OperationEvent>, String> {
+    let payload = bytes
+        .strip_prefix(BINARY_MAGIC.as_slice())
+        .ok_or("not a corepy binary event export (bad magic)")?;
+    let (&version, payload) = payload.split_first().ok_or("truncated binary event header")?;
+    if version != BINARY_VERSION {
+        return Err(format!(
+            "unsupported binary event version {} (this build reads version {})", version, BINARY_VERSION
+        ));
+    }
+    let (&tag, payload) = payload.split_first().ok_or("truncated binary event header")?;
+    let format = BinaryFormat::from_tag(tag).ok_or_else(|| format!("unknown binary event format tag {}", tag))?;
+
+    let wire: Vec<WireEvent<'_>> = match format {
+        BinaryFormat::Bincode => bincode::DefaultOptions::new()
+            .deserialize(payload)
+            .map_err(|e| e.to_string())?,
+        BinaryFormat::MessagePack => rmp_serde::from_slice(payload).map_err(|e| e.to_string())?,
+    };
+    Ok(wire.into_iter().map(OperationEvent::from).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profiler::metrics::ProfileReport;
+    use crate::profiler::Profiler;
+
+    fn sample_events(count: u64) -> Vec<OperationEvent> {
+        (0..count)
+            .map(|i| OperationEvent {
+                operation: ["add", "matmul", "sum"][i as usize % 3].to_string(),
+                backend: "CPU-serial".to_string(),
+                data_size: (i as usize % 1000) * 64,
+                start_time_us: 1_700_000_000_000_000 + i * 10,
+                end_time_us: 1_700_000_000_000_000 + i * 10 + i % 97,
+                context: (i % 4 == 0).then(|| "forward".to_string()),
+                dims: (i % 3 == 1).then_some((64, 64, 64)),
+                thread_id: i % 8,
+                thread_name: (i % 8 == 0).then(|| "main".to_string()),
+                sample_weight: 1,
+                seq: i,
+                arena_bytes_used: i % 5 * 4096,
+                heap_bytes_allocated: 0,
+                kind: if i % 1000 == 0 { EventKind::Marker } else { EventKind::Operation },
+                metadata: (i % 1000 == 0).then(|| "epoch".to_string()),
+                warmup: i < 3,
+                attributes: if i % 3 == 1 { vec![("m".to_string(), "64".to_string())] } else { Vec::new() },
+                gil_held_us: i % 97 / 2,
+                gil_released_us: i % 97 - i % 97 / 2,
+                session: (i % 2 == 0).then_some(7),
+                call_site: (i % 5 == 0).then(|| CallSite {
+                    file: "train.py".to_string(),
+                    line: (i % 40) as u32,
+                    function: "step".to_string(),
+                }),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_round_trip_100k_events_smaller_than_json() {
+        let events = sample_events(100_000);
+        let mut report = ProfileReport::new("binary".to_string(), None);
+        report.events = events.clone();
+        let json_len = report.to_json().unwrap().len();
+
+        for format in [BinaryFormat::Bincode, BinaryFormat::MessagePack] {
+            let bytes = encode_events(&events, format).unwrap();
+            assert_eq!(&bytes[..4], BINARY_MAGIC);
+            assert_eq!(bytes[4], BINARY_VERSION);
+            assert_eq!(decode_events(&bytes).unwrap(), events, "{:?}", format);
+            assert!(bytes.len() * 3 < json_len, "{:?}: {} bytes vs {} of JSON", format, bytes.len(), json_len);
+        }
+    }
+
+    #[test]
+    fn test_rejects_bad_headers() {
+        let bytes = encode_events(&sample_events(10), BinaryFormat::MessagePack).unwrap();
+
+        assert!(decode_events(b"{\"events\": []}").unwrap_err().contains("bad magic"));
+        assert!(decode_events(&bytes[..4]).unwrap_err().contains("truncated"));
+        let mut future = bytes.clone();
+        future[4] = BINARY_VERSION + 1;
+        assert!(decode_events(&future).unwrap_err().contains("unsupported binary event version"));
+        let mut unknown = bytes.clone();
+        unknown[5] = 9;
+        assert!(decode_events(&unknown).unwrap_err().contains("format tag 9"));
+        assert!(decode_events(&bytes[..bytes.len() - 3]).is_err());
+
+        assert_eq!(BinaryFormat::from_name("MsgPack"), Some(BinaryFormat::MessagePack));
+        assert_eq!(BinaryFormat::from_name("cbor"), None);
+    }
+
+    #[test]
+    fn test_profiler_rebuilds_report_from_import() {
+        let source = Profiler::new();
+        source.enable();
+        for i in 0..10u64 {
+            source.record_operation("binary_add".to_string(), "CPU".to_string(), 4, i * 100, i * 100 + 25, None);
+        }
+        let bytes = source.export_events_binary(BinaryFormat::Bincode).unwrap();
+
+        // Importing works while the target is disabled
+        let offline = Profiler::new();
+        assert_eq!(offline.import_events_binary(&bytes).unwrap(), 10);
+        let report = offline.generate_report(None, None);
+        assert_eq!(report.operations["binary_add"].count, 10);
+        assert_eq!(report.total_time_ms, source.generate_report(None, None).total_time_ms);
+        assert!(offline.import_events_binary(b"junk").is_err());
+        assert_eq!(offline.event_count(), 10);
+    }
+}
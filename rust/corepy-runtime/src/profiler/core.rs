@@ -4,49 +4,420 @@
 //! multiple threads. Profiling is disabled by default and has zero overhead
 //! when disabled.
 
-use super::metrics::{OperationEvent, ProfileReport};
-use parking_lot::RwLock;
+use super::binary::{decode_events, encode_events, BinaryFormat};
+use super::metrics::{CallSite, EventKind, OperationEvent, ProfileReport};
+use crate::scheduler::arena::{allocation_counters, set_usage_observer, ArenaUsageObserver};
+use lazy_static::lazy_static;
+use parking_lot::{Mutex, MutexGuard, RwLock};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Default event capacity (override with COREPY_PROFILE_MAX_EVENTS)
+pub const DEFAULT_MAX_EVENTS: usize = 1_000_000;
+
+/// Finished sessions kept for get_session_report by default
+pub const DEFAULT_MAX_SESSIONS: usize = 16;
+
+/// One profiling session (see Profiler::start_session)
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionInfo {
+    pub id: u64,
+    pub name: String,
+    /// RFC 3339 start time
+    pub start_timestamp: String,
+    pub active: bool,
+}
+
+/// Active session and archive of finished ones (oldest first)
+#[derive(Default)]
+struct SessionState {
+    active: Option<SessionInfo>,
+    last_id: u64,
+    archive: VecDeque<(SessionInfo, ProfileReport)>,
+}
+
+/// Number of event shards; a thread records into shard `thread_id % EVENT_SHARDS`
+const EVENT_SHARDS: usize = 64;
+
+/// One shard of recorded events (oldest first), aligned so neighbouring
+/// shards' locks never share a cache line
+#[derive(Default)]
+#[repr(align(128))]
+struct EventShard(Mutex<VecDeque<OperationEvent>>);
+
+/// Locks on every shard, taken in index order
+type ShardGuards<'a> = Vec<MutexGuard<'a, VecDeque<OperationEvent>>>;
 
 /// Thread-safe global profiler state
+///
+/// Events are buffered per recording thread (sharded by thread id), so threads
+/// only ever contend on their own shard; readers lock every shard and merge
+/// by `seq`, which restores the global recording order.
+///
+/// At most `max_events` events are kept: once full, each new event evicts an
+/// older one and bumps `dropped_events`, so a long run keeps its most recent
+/// window at bounded memory. The recording thread evicts its own oldest event
+/// when it has one, so with several threads the window is the most recent per
+/// thread rather than strictly global.
 #[derive(Clone)]
 pub struct Profiler {
     /// Whether profiling is currently enabled
-    enabled: Arc<RwLock<bool>>,
+    enabled: Arc<AtomicBool>,
     
-    /// Collected profiling events
-    events: Arc<RwLock<Vec<OperationEvent>>>,
+    /// Collected profiling events, EVENT_SHARDS shards
+    shards: Arc<[EventShard]>,
+
+    /// Events currently stored across all shards (updated under the shard lock)
+    event_count: Arc<AtomicUsize>,
+
+    /// Maximum number of events retained
+    max_events: Arc<AtomicUsize>,
+
+    /// Events evicted since the last clear()
+    dropped_events: Arc<AtomicU64>,
+
+    /// Record one in every N calls per operation (1 = every call)
+    sample_rate: Arc<AtomicU32>,
+
+    /// Sequence number for the next stored event (taken under the shard lock)
+    next_seq: Arc<AtomicU64>,
+
+    /// Buffered events that trigger a spill to disk (0 = not spilling)
+    spill_threshold: Arc<AtomicUsize>,
+
+    /// Spill file state; locked before the shards whenever both are needed
+    spill: Arc<Mutex<Option<SpillState>>>,
+
+    /// Leading calls of each operation flagged as warmup (0 = none)
+    warmup_skip: Arc<AtomicUsize>,
+
+    /// Calls seen per operation while warmup_skip > 0 (reset by clear())
+    warmup_seen: Arc<Mutex<HashMap<String, usize>>>,
+
+    /// Id of the session new events are tagged with (0 = none)
+    active_session: Arc<AtomicU64>,
+
+    /// Session bookkeeping; may be held while taking the spill and shard locks
+    sessions: Arc<Mutex<SessionState>>,
+
+    /// Finished sessions kept in the archive
+    max_sessions: Arc<AtomicUsize>,
+}
+
+/// Event buffer spilled to a newline-delimited JSON file (see Profiler::enable_spill)
+struct SpillState {
+    path: PathBuf,
+    /// Open while spilling; None once disabled or after an IO error
+    writer: Option<BufWriter<File>>,
+    /// Length of the complete batches in the file (a failed batch is cut back to it)
+    committed_len: u64,
+    /// Events in the file
+    spilled: u64,
+    error: Option<String>,
+}
+
+impl SpillState {
+    fn create(path: PathBuf) -> io::Result<Self> {
+        let writer = BufWriter::new(File::create(&path)?);
+        Ok(Self { path, writer: Some(writer), committed_len: 0, spilled: 0, error: None })
+    }
+
+    /// Append one batch; on failure the file is cut back to the previous batch
+    /// and spilling stops
+    fn write_batch(&mut self, events: &[&OperationEvent]) -> io::Result<()> {
+        let Some(writer) = self.writer.as_mut() else {
+            return Err(io::Error::other("spill file is closed"));
+        };
+        let mut written = 0u64;
+        let result = events
+            .iter()
+            .try_for_each(|event| {
+                let mut line = serde_json::to_vec(event)?;
+                line.push(b'\n');
+                writer.write_all(&line)?;
+                written += line.len() as u64;
+                Ok::<(), io::Error>(())
+            })
+            .and_then(|()| writer.flush());
+
+        match result {
+            Ok(()) => {
+                self.committed_len += written;
+                self.spilled += events.len() as u64;
+                Ok(())
+            }
+            Err(e) => {
+                self.fail(&e);
+                Err(e)
+            }
+        }
+    }
+
+    fn fail(&mut self, error: &io::Error) {
+        self.error = Some(format!("{}: {}", self.path.display(), error));
+        if let Some(writer) = self.writer.take() {
+            // into_parts: don't retry the failed flush on drop
+            let (file, _) = writer.into_parts();
+            let _ = file.set_len(self.committed_len);
+        }
+    }
+
+    /// Spilled events, oldest first (stops at the first unreadable line)
+    fn read_back(&mut self) -> Vec<OperationEvent> {
+        if self.spilled == 0 {
+            return Vec::new();
+        }
+        let mut events = Vec::with_capacity(self.spilled as usize);
+        let read = File::open(&self.path).and_then(|file| {
+            for line in BufReader::new(file.take(self.committed_len)).lines() {
+                events.push(serde_json::from_str(&line?)?);
+            }
+            Ok(())
+        });
+        if let Err(e) = read {
+            self.error = Some(format!("{}: {}", self.path.display(), e));
+        }
+        events
+    }
 }
 
 impl Profiler {
     /// Create a new profiler (disabled by default)
+    ///
+    /// Capacity comes from COREPY_PROFILE_MAX_EVENTS, else DEFAULT_MAX_EVENTS.
     pub fn new() -> Self {
+        let max_events = std::env::var("COREPY_PROFILE_MAX_EVENTS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .filter(|&n: &usize| n > 0)
+            .unwrap_or(DEFAULT_MAX_EVENTS);
+        Self::with_capacity(max_events)
+    }
+
+    /// Create a new profiler retaining at most `max_events` events (at least 1)
+    pub fn with_capacity(max_events: usize) -> Self {
         Self {
-            enabled: Arc::new(RwLock::new(false)),
-            events: Arc::new(RwLock::new(Vec::new())),
+            enabled: Arc::new(AtomicBool::new(false)),
+            shards: (0..EVENT_SHARDS).map(|_| EventShard::default()).collect(),
+            event_count: Arc::new(AtomicUsize::new(0)),
+            max_events: Arc::new(AtomicUsize::new(max_events.max(1))),
+            dropped_events: Arc::new(AtomicU64::new(0)),
+            sample_rate: Arc::new(AtomicU32::new(1)),
+            next_seq: Arc::new(AtomicU64::new(0)),
+            spill_threshold: Arc::new(AtomicUsize::new(0)),
+            spill: Arc::new(Mutex::new(None)),
+            warmup_skip: Arc::new(AtomicUsize::new(0)),
+            warmup_seen: Arc::new(Mutex::new(HashMap::new())),
+            active_session: Arc::new(AtomicU64::new(0)),
+            sessions: Arc::new(Mutex::new(SessionState::default())),
+            max_sessions: Arc::new(AtomicUsize::new(DEFAULT_MAX_SESSIONS)),
+        }
+    }
+
+    /// Change the event capacity, evicting the oldest events if over the new limit
+    pub fn set_capacity(&self, max_events: usize) -> Result<(), String> {
+        if max_events == 0 {
+            return Err("profile capacity must be at least 1 event".to_string());
+        }
+        let mut shards = self.lock_shards();
+        self.max_events.store(max_events, Ordering::Relaxed);
+        let excess = self.event_count().saturating_sub(max_events);
+        self.evict_oldest(&mut shards, excess);
+        Ok(())
+    }
+
+    /// Keep one in every `rate` calls per operation (1 = record everything)
+    pub fn set_sample_rate(&self, rate: u32) -> Result<(), String> {
+        if rate == 0 {
+            return Err("profile sample rate must be at least 1".to_string());
+        }
+        self.sample_rate.store(rate, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Current sample rate (1 = exact)
+    #[inline]
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate.load(Ordering::Relaxed)
+    }
+
+    /// Flag the first `n` calls of each operation as warmup (0 = off)
+    ///
+    /// Warmup events stay in the raw export but are left out of the timing
+    /// aggregates; reports count them under warmup_count / warmup_total_ms.
+    /// Calls are counted from the last clear().
+    pub fn set_warmup_skip(&self, n: usize) {
+        self.warmup_skip.store(n, Ordering::Relaxed);
+    }
+
+    /// Leading calls per operation flagged as warmup
+    #[inline]
+    pub fn warmup_skip(&self) -> usize {
+        self.warmup_skip.load(Ordering::Relaxed)
+    }
+
+    /// Count a call of `operation`; true while it is within the warmup window
+    fn take_warmup(&self, operation: &str, skip: usize) -> bool {
+        let mut seen = self.warmup_seen.lock();
+        let calls = match seen.get_mut(operation) {
+            Some(calls) => calls,
+            None => seen.entry(operation.to_string()).or_insert(0),
+        };
+        *calls += 1;
+        *calls <= skip
+    }
+
+    /// Maximum number of events retained
+    pub fn capacity(&self) -> usize {
+        self.max_events.load(Ordering::Relaxed)
+    }
+
+    /// Events evicted since the last clear()
+    pub fn dropped_events(&self) -> u64 {
+        self.dropped_events.load(Ordering::Relaxed)
+    }
+
+    /// Lock every shard in index order (the only order used, so no deadlocks)
+    fn lock_shards(&self) -> ShardGuards<'_> {
+        self.shards.iter().map(|shard| shard.0.lock()).collect()
+    }
+
+    /// Remove up to `max` of the lowest-seq events across the locked shards
+    fn take_oldest(&self, shards: &mut ShardGuards<'_>, max: usize) -> Vec<OperationEvent> {
+        let mut fronts: BinaryHeap<Reverse<(u64, usize)>> = shards
+            .iter()
+            .enumerate()
+            .filter_map(|(index, shard)| shard.front().map(|e| Reverse((e.seq, index))))
+            .collect();
+        let mut taken = Vec::with_capacity(max.min(self.event_count()));
+        while taken.len() < max {
+            let Some(Reverse((_, index))) = fronts.pop() else { break };
+            let shard = &mut shards[index];
+            taken.extend(shard.pop_front());
+            if let Some(next) = shard.front() {
+                fronts.push(Reverse((next.seq, index)));
+            }
+        }
+        self.event_count.fetch_sub(taken.len(), Ordering::Relaxed);
+        taken
+    }
+
+    /// Evict the `count` oldest events, counting them as dropped
+    fn evict_oldest(&self, shards: &mut ShardGuards<'_>, count: usize) {
+        let evicted = self.take_oldest(shards, count).len();
+        self.dropped_events.fetch_add(evicted as u64, Ordering::Relaxed);
+    }
+
+    /// All events in recording (seq) order: spilled ones, then the buffer
+    fn snapshot(&self) -> Vec<OperationEvent> {
+        // Holding the spill lock keeps a batch from moving to disk mid-read
+        let mut spill = self.spill.lock();
+        let mut events = spill.as_mut().map(SpillState::read_back).unwrap_or_default();
+        let spilled = events.len();
+        let shards = self.lock_shards();
+        events.extend(shards.iter().flat_map(|shard| shard.iter().cloned()));
+        drop(shards);
+        drop(spill);
+        events[spilled..].sort_unstable_by_key(|e| e.seq);
+        events
+    }
+
+    /// Spill the event buffer to `path` (newline-delimited JSON, truncated
+    /// first) each time it reaches `threshold_events`, so long runs keep every
+    /// event at bounded memory
+    ///
+    /// Reports and traces read the spilled events back in; drain_events()
+    /// only sees the buffer. Keep the capacity above the threshold or events
+    /// are evicted before they can spill. A write error stops spilling (the
+    /// events stay buffered) and shows up as metadata.spill_error.
+    pub fn enable_spill(&self, path: &Path, threshold_events: usize) -> io::Result<()> {
+        if threshold_events == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "spill threshold must be at least 1 event"));
+        }
+        let mut spill = self.spill.lock();
+        *spill = Some(SpillState::create(path.to_path_buf())?);
+        self.spill_threshold.store(threshold_events, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Stop spilling; events already on disk stay in reports until clear()
+    pub fn disable_spill(&self) {
+        let mut spill = self.spill.lock();
+        self.spill_threshold.store(0, Ordering::Relaxed);
+        if let Some(state) = spill.as_mut() {
+            if let Some(mut writer) = state.writer.take() {
+                if let Err(e) = writer.flush() {
+                    state.fail(&e);
+                }
+            }
+        }
+    }
+
+    /// Move the whole buffer to the spill file if it still holds `threshold` events
+    fn spill(&self, threshold: usize) {
+        let mut spill = self.spill.lock();
+        let Some(state) = spill.as_mut().filter(|state| state.writer.is_some()) else {
+            return;
+        };
+        let mut shards = self.lock_shards();
+        // Another thread may have spilled while we waited
+        if self.event_count() < threshold {
+            return;
+        }
+        let mut events: Vec<&OperationEvent> = shards.iter().flat_map(|shard| shard.iter()).collect();
+        events.sort_unstable_by_key(|e| e.seq);
+        if state.write_batch(&events).is_err() {
+            self.spill_threshold.store(0, Ordering::Relaxed);
+            return;
+        }
+        let spilled = events.len();
+        drop(events);
+        for shard in shards.iter_mut() {
+            shard.clear();
         }
+        self.event_count.fetch_sub(spilled, Ordering::Relaxed);
     }
     
     /// Enable profiling
     pub fn enable(&self) {
-        *self.enabled.write() = true;
+        self.enabled.store(true, Ordering::Release);
     }
     
     /// Disable profiling
     pub fn disable(&self) {
-        *self.enabled.write() = false;
+        self.enabled.store(false, Ordering::Release);
     }
     
     /// Check if profiling is enabled
     #[inline]
     pub fn is_enabled(&self) -> bool {
-        *self.enabled.read()
+        self.enabled.load(Ordering::Acquire)
+    }
+
+    /// Whether a ProfileScope on this profiler would record anything: it is
+    /// enabled or a named profiler is attached. Hot loops check this once
+    /// instead of building scopes that do nothing
+    #[inline]
+    pub fn is_recording(&self) -> bool {
+        self.is_enabled() || active_profiler().is_some()
+    }
+
+    /// Whether both handles share the same event store
+    pub fn same_as(&self, other: &Profiler) -> bool {
+        Arc::ptr_eq(&self.shards, &other.shards)
     }
     
     /// Record an operation event
     ///
     /// This is a hot path function - optimized for minimal overhead
+    #[allow(dead_code)]
     pub fn record_operation(
         &self,
         operation: String,
@@ -61,46 +432,416 @@ impl Profiler {
             return;
         }
         
-        let event = OperationEvent {
+        self.record_event(OperationEvent {
             operation,
             backend,
             data_size,
             start_time_us,
             end_time_us,
             context,
-        };
-        
-        self.events.write().push(event);
+            dims: None,
+            thread_id: current_thread_id(),
+            thread_name: current_thread_name(),
+            sample_weight: 1,
+            seq: 0,
+            arena_bytes_used: 0,
+            heap_bytes_allocated: 0,
+            kind: EventKind::Operation,
+            metadata: None,
+            warmup: false,
+            attributes: Vec::new(),
+            gil_held_us: 0,
+            gil_released_us: 0,
+            session: None,
+            call_site: None,
+        });
+    }
+
+    /// Record a fully built event (no-op when disabled)
+    ///
+    /// Under sampling only every Nth call per operation and thread is kept,
+    /// weighted to stand for the N calls.
+    pub fn record_event(&self, mut event: OperationEvent) {
+        if !self.is_enabled() {
+            return;
+        }
+        let session = self.active_session.load(Ordering::Relaxed);
+        event.session = (session != 0).then_some(session);
+        let skip = self.warmup_skip();
+        if skip > 0 && !event.is_marker() {
+            event.warmup = self.take_warmup(&event.operation, skip);
+        }
+        // Warmup events are few and reported exactly, so they skip sampling
+        let rate = self.sample_rate();
+        if rate > 1 && !event.is_marker() && !event.warmup {
+            if !take_sample(&event.operation, rate) {
+                return;
+            }
+            event.sample_weight = rate;
+        }
+        let mut shard = self.shards[current_thread_id() as usize % EVENT_SHARDS].0.lock();
+        event.seq = self.next_seq.fetch_add(1, Ordering::Relaxed);
+        shard.push_back(event);
+        let stored = self.event_count.fetch_add(1, Ordering::Relaxed) + 1;
+        if stored <= self.capacity() {
+            drop(shard);
+            let threshold = self.spill_threshold.load(Ordering::Relaxed);
+            if threshold > 0 && stored >= threshold {
+                self.spill(threshold);
+            }
+            return;
+        }
+        // Over capacity: drop this thread's oldest event, or when the new event
+        // is all it has, the oldest anywhere (rare slow path over every shard)
+        if shard.len() > 1 {
+            shard.pop_front();
+            self.event_count.fetch_sub(1, Ordering::Relaxed);
+            self.dropped_events.fetch_add(1, Ordering::Relaxed);
+        } else {
+            drop(shard);
+            let mut shards = self.lock_shards();
+            let excess = self.event_count().saturating_sub(self.capacity());
+            self.evict_oldest(&mut shards, excess);
+        }
+    }
+
+    /// Drop a zero-duration marker (e.g. "epoch 3 start") into the timeline
+    /// (no-op when disabled)
+    ///
+    /// Markers are never sampled out and stay out of the operation metrics:
+    /// reports list them under `markers`, Chrome traces show instant events.
+    pub fn record_marker(&self, name: &str, metadata: Option<String>) {
+        if !self.is_enabled() {
+            return;
+        }
+        let now = now_micros();
+        self.record_event(OperationEvent {
+            operation: name.to_string(),
+            backend: String::new(),
+            data_size: 0,
+            start_time_us: now,
+            end_time_us: now,
+            context: get_context(),
+            dims: None,
+            thread_id: current_thread_id(),
+            thread_name: current_thread_name(),
+            sample_weight: 1,
+            seq: 0,
+            arena_bytes_used: 0,
+            heap_bytes_allocated: 0,
+            kind: EventKind::Marker,
+            metadata,
+            warmup: false,
+            attributes: Vec::new(),
+            gil_held_us: 0,
+            gil_released_us: 0,
+            session: None,
+            call_site: None,
+        });
+    }
+
+    /// Remove and return up to `max` of the oldest events
+    ///
+    /// Events recorded concurrently are either returned by this call or left
+    /// for the next one, never lost; their `seq` numbers let a consumer spot
+    /// gaps left by capacity evictions.
+    pub fn drain_events(&self, max: usize) -> Vec<OperationEvent> {
+        let mut shards = self.lock_shards();
+        self.take_oldest(&mut shards, max)
     }
     
-    /// Clear all recorded events
+    /// Clear all recorded events and the dropped-event count
+    ///
+    /// An active spill file is truncated and keeps receiving events; an
+    /// inactive one is forgotten.
     pub fn clear(&self) {
-        self.events.write().clear();
+        let mut spill = self.spill.lock();
+        if let Some(state) = spill.take().filter(|state| state.writer.is_some()) {
+            let path = state.path.clone();
+            drop(state);
+            *spill = Some(SpillState::create(path.clone()).unwrap_or_else(|e| {
+                self.spill_threshold.store(0, Ordering::Relaxed);
+                let mut failed = SpillState { path, writer: None, committed_len: 0, spilled: 0, error: None };
+                failed.fail(&e);
+                failed
+            }));
+        }
+        let mut shards = self.lock_shards();
+        for shard in shards.iter_mut() {
+            shard.clear();
+        }
+        self.event_count.store(0, Ordering::Relaxed);
+        self.dropped_events.store(0, Ordering::Relaxed);
+        self.warmup_seen.lock().clear();
     }
     
+    /// Start a named session; events recorded until end_session() are tagged
+    /// with the returned id
+    ///
+    /// One session is active at a time. Events recorded outside any session
+    /// are untagged and only appear in the regular reports.
+    pub fn start_session(&self, name: &str) -> Result<u64, String> {
+        let mut sessions = self.sessions.lock();
+        if let Some(active) = &sessions.active {
+            return Err(format!("profile session '{}' ({}) is still active", active.name, active.id));
+        }
+        sessions.last_id += 1;
+        let id = sessions.last_id;
+        sessions.active = Some(SessionInfo {
+            id,
+            name: name.to_string(),
+            start_timestamp: chrono::Utc::now().to_rfc3339(),
+            active: true,
+        });
+        self.active_session.store(id, Ordering::Relaxed);
+        Ok(id)
+    }
+
+    /// End the active session and archive its report (oldest archived
+    /// sessions beyond the limit are forgotten)
+    ///
+    /// The session's events stay in the buffer; clear() does not touch the archive.
+    pub fn end_session(&self, session_id: u64) -> Result<ProfileReport, String> {
+        let mut sessions = self.sessions.lock();
+        let mut info = match sessions.active.take() {
+            Some(info) if info.id == session_id => info,
+            other => {
+                sessions.active = other;
+                return Err(format!("no active profile session with id {}", session_id));
+            }
+        };
+        self.active_session.store(0, Ordering::Relaxed);
+        info.active = false;
+        let report = self.session_report(&info);
+        sessions.archive.push_back((info, report.clone()));
+        let excess = sessions.archive.len().saturating_sub(self.max_sessions());
+        sessions.archive.drain(..excess);
+        Ok(report)
+    }
+
+    /// Report of an archived session, or a live one of the active session
+    pub fn get_session_report(&self, session_id: u64) -> Option<ProfileReport> {
+        let sessions = self.sessions.lock();
+        if let Some(active) = sessions.active.as_ref().filter(|info| info.id == session_id) {
+            return Some(self.session_report(active));
+        }
+        sessions
+            .archive
+            .iter()
+            .find(|(info, _)| info.id == session_id)
+            .map(|(_, report)| report.clone())
+    }
+
+    /// Archived sessions (oldest first), then the active one
+    pub fn list_sessions(&self) -> Vec<SessionInfo> {
+        let sessions = self.sessions.lock();
+        sessions
+            .archive
+            .iter()
+            .map(|(info, _)| info.clone())
+            .chain(sessions.active.clone())
+            .collect()
+    }
+
+    /// Change how many finished sessions are archived (at least 1)
+    pub fn set_max_sessions(&self, max_sessions: usize) -> Result<(), String> {
+        if max_sessions == 0 {
+            return Err("profile session limit must be at least 1".to_string());
+        }
+        let mut sessions = self.sessions.lock();
+        self.max_sessions.store(max_sessions, Ordering::Relaxed);
+        let excess = sessions.archive.len().saturating_sub(max_sessions);
+        sessions.archive.drain(..excess);
+        Ok(())
+    }
+
+    /// Finished sessions kept in the archive
+    pub fn max_sessions(&self) -> usize {
+        self.max_sessions.load(Ordering::Relaxed)
+    }
+
+    /// Report over the events tagged with `info`'s session
+    fn session_report(&self, info: &SessionInfo) -> ProfileReport {
+        let events: Vec<OperationEvent> = self
+            .snapshot()
+            .into_iter()
+            .filter(|e| e.session == Some(info.id))
+            .collect();
+        let mut report = self.report_for(&events, None, None);
+        report.metadata.session_id = info.id.to_string();
+        report.metadata.session_name = Some(info.name.clone());
+        report.metadata.start_timestamp = info.start_timestamp.clone();
+        report
+    }
+
     /// Get the number of recorded events
-    #[allow(dead_code)]
     pub fn event_count(&self) -> usize {
-        self.events.read().len()
+        self.event_count.load(Ordering::Relaxed)
     }
     
-    /// Generate a profiling report
-    pub fn generate_report(&self, context_filter: Option<&str>) -> ProfileReport {
-        let events = self.events.read();
-        ProfileReport::from_events(&events, context_filter)
+    /// Generate a profiling report, optionally restricted to a context and/or
+    /// an allowlist of operation names
+    pub fn generate_report(&self, context_filter: Option<&str>, operations: Option<&[String]>) -> ProfileReport {
+        self.report_for(&self.snapshot(), context_filter, operations)
+    }
+
+    fn report_for(&self, events: &[OperationEvent], context_filter: Option<&str>, operations: Option<&[String]>) -> ProfileReport {
+        let mut report = ProfileReport::from_events(events, context_filter, operations);
+        report.metadata.dropped_events = self.dropped_events();
+        report.metadata.sample_rate = self.sample_rate();
+        if let Some(state) = self.spill.lock().as_ref() {
+            report.metadata.spilled_events = state.spilled;
+            report.metadata.spill_error = state.error.clone();
+        }
+        report
     }
     
-    /// Export events as JSON
-    pub fn export_json(&self, context_filter: Option<&str>) -> Result<String, String> {
-        let report = self.generate_report(context_filter);
-        report.to_json().map_err(|e| format!("JSON serialization failed: {}", e))
+    /// Report with the (filtered) raw events attached when requested
+    pub fn report_with_events(
+        &self,
+        context_filter: Option<&str>,
+        operations: Option<&[String]>,
+        include_events: bool,
+    ) -> ProfileReport {
+        let events = self.snapshot();
+        let mut report = self.report_for(&events, context_filter, operations);
+        if include_events {
+            report.events = events.into_iter().filter(|e| e.matches(context_filter, operations)).collect();
+        }
+        report
+    }
+
+    /// Export the report as JSON, optionally with the (filtered) raw events attached
+    pub fn export_json(
+        &self,
+        context_filter: Option<&str>,
+        operations: Option<&[String]>,
+        include_events: bool,
+    ) -> Result<String, String> {
+        self.report_with_events(context_filter, operations, include_events)
+            .to_json()
+            .map_err(|e| format!("JSON serialization failed: {}", e))
+    }
+
+    /// Write the JSON report straight to `path` (buffered), optionally
+    /// creating missing parent directories
+    pub fn export_json_to_file(&self, path: &Path, context_filter: Option<&str>, create_dirs: bool) -> io::Result<()> {
+        let report = self.generate_report(context_filter, None);
+        write_json_file(path, create_dirs, |writer| serde_json::to_writer_pretty(writer, &report))
     }
     
+    /// Trace Event Format document for the recorded events
+    fn chrome_trace(&self, context_filter: Option<&str>) -> serde_json::Value {
+        let events = self.snapshot();
+        let trace_events: Vec<serde_json::Value> = events
+            .iter()
+            .filter(|e| e.matches(context_filter, None))
+            .map(|e| {
+                if e.is_marker() {
+                    // Thread-scoped instant event
+                    return serde_json::json!({
+                        "name": e.operation,
+                        "cat": "marker",
+                        "ph": "i",
+                        "s": "t",
+                        "ts": e.start_time_us,
+                        "pid": CHROME_TRACE_PID,
+                        "tid": e.thread_id,
+                        "args": { "metadata": e.metadata, "context": e.context },
+                    });
+                }
+                let mut args = serde_json::json!({
+                    "backend": e.backend,
+                    "data_size": e.data_size,
+                    "context": e.context,
+                });
+                if let Some((m, n, k)) = e.dims {
+                    args["dims"] = serde_json::json!([m, n, k]);
+                }
+                for (key, value) in &e.attributes {
+                    args[key] = serde_json::json!(value);
+                }
+                serde_json::json!({
+                    "name": e.operation,
+                    "cat": "corepy",
+                    "ph": "X",
+                    "ts": e.start_time_us,
+                    "dur": e.duration_us(),
+                    "pid": CHROME_TRACE_PID,
+                    "tid": e.thread_id,
+                    "args": args,
+                })
+            })
+            .collect();
+
+        serde_json::json!({
+            "traceEvents": trace_events,
+            "displayTimeUnit": "ms",
+        })
+    }
+
+    /// Export events in the Chrome Trace Event Format (chrome://tracing, Perfetto)
+    ///
+    /// Each event becomes one complete ("ph": "X") slice with ts/dur in
+    /// microseconds on its recording thread; markers become instant ("ph": "i")
+    /// events.
+    pub fn export_chrome_trace(&self, context_filter: Option<&str>) -> Result<String, String> {
+        serde_json::to_string(&self.chrome_trace(context_filter))
+            .map_err(|e| format!("JSON serialization failed: {}", e))
+    }
+
+    /// Write the Chrome trace straight to `path` (see export_json_to_file)
+    pub fn export_chrome_trace_to_file(&self, path: &Path, context_filter: Option<&str>, create_dirs: bool) -> io::Result<()> {
+        let trace = self.chrome_trace(context_filter);
+        write_json_file(path, create_dirs, |writer| serde_json::to_writer(writer, &trace))
+    }
+
+    /// Raw events (spilled ones included) in a compact versioned binary form,
+    /// a fraction of the JSON size (see profiler::binary)
+    pub fn export_events_binary(&self, format: BinaryFormat) -> Result<Vec<u8>, String> {
+        encode_events(&self.snapshot(), format)
+    }
+
+    /// Load events from export_events_binary() (e.g. a saved session) so
+    /// reports can be rebuilt offline; returns how many were loaded
+    ///
+    /// Works while disabled. Imported events get new sequence numbers after
+    /// the buffered ones, and the capacity limit applies as when recording.
+    pub fn import_events_binary(&self, bytes: &[u8]) -> Result<usize, String> {
+        let events = decode_events(bytes)?;
+        let count = events.len();
+        let mut shards = self.lock_shards();
+        for mut event in events {
+            event.seq = self.next_seq.fetch_add(1, Ordering::Relaxed);
+            shards[event.thread_id as usize % EVENT_SHARDS].push_back(event);
+        }
+        self.event_count.fetch_add(count, Ordering::Relaxed);
+        let excess = self.event_count().saturating_sub(self.capacity());
+        self.evict_oldest(&mut shards, excess);
+        Ok(count)
+    }
+
     /// Get all events (for advanced use cases)
     #[allow(dead_code)]
     pub fn get_events(&self) -> Vec<OperationEvent> {
-        self.events.read().clone()
+        self.snapshot()
+    }
+}
+
+/// Create `path` (and optionally its parent directories) and stream JSON into it
+fn write_json_file(
+    path: &Path,
+    create_dirs: bool,
+    write: impl FnOnce(&mut BufWriter<File>) -> serde_json::Result<()>,
+) -> io::Result<()> {
+    if create_dirs {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
     }
+    let mut writer = BufWriter::new(File::create(path)?);
+    write(&mut writer)?;
+    writer.flush()
 }
 
 impl Default for Profiler {
@@ -109,9 +850,119 @@ impl Default for Profiler {
     }
 }
 
+lazy_static! {
+    /// Profilers created through get_or_create_profiler(), by name
+    static ref NAMED_PROFILERS: RwLock<HashMap<String, Profiler>> = RwLock::new(HashMap::new());
+
+    /// Named profiler every ProfileScope also records into (see set_active_profiler)
+    static ref ACTIVE_PROFILER: RwLock<Option<(String, Profiler)>> = RwLock::new(None);
+}
+
+/// Fast check so scopes skip the lock while no profiler is attached
+static ACTIVE_PROFILER_SET: AtomicBool = AtomicBool::new(false);
+
+/// Named profiler, created (disabled, default capacity) on first use
+///
+/// Lets several libraries in one process keep separate sessions: each
+/// enables, clears and reports on its own instance.
+pub fn get_or_create_profiler(name: &str) -> Profiler {
+    if let Some(profiler) = NAMED_PROFILERS.read().get(name) {
+        return profiler.clone();
+    }
+    NAMED_PROFILERS.write().entry(name.to_string()).or_default().clone()
+}
+
+/// Attach a named profiler (created if needed) that ops record into alongside
+/// the profiler they were given; None detaches it
+pub fn set_active_profiler(name: Option<&str>) {
+    let attached = name.map(|name| (name.to_string(), get_or_create_profiler(name)));
+    let mut active = ACTIVE_PROFILER.write();
+    ACTIVE_PROFILER_SET.store(attached.is_some(), Ordering::Release);
+    *active = attached;
+}
+
+/// Name of the attached profiler, if any
+pub fn active_profiler_name() -> Option<String> {
+    ACTIVE_PROFILER.read().as_ref().map(|(name, _)| name.clone())
+}
+
+/// The attached profiler, if any
+#[inline]
+fn active_profiler() -> Option<Profiler> {
+    if !ACTIVE_PROFILER_SET.load(Ordering::Acquire) {
+        return None;
+    }
+    ACTIVE_PROFILER.read().as_ref().map(|(_, profiler)| profiler.clone())
+}
+
+/// Process id written into Chrome traces (corepy events share one track group)
+const CHROME_TRACE_PID: u32 = 1;
+
+/// Next id handed out by current_thread_id()
+static NEXT_THREAD_ID: AtomicU64 = AtomicU64::new(1);
+
 // Thread-local profiler instance for zero overhead when disabled
 thread_local! {
-    static PROFILER_CONTEXT: std::cell::RefCell<Option<String>> = std::cell::RefCell::new(None);
+    static PROFILER_CONTEXT: std::cell::RefCell<Option<String>> = const { std::cell::RefCell::new(None) };
+    static THREAD_ID: u64 = NEXT_THREAD_ID.fetch_add(1, Ordering::Relaxed);
+    static THREAD_NAME: Option<String> = std::thread::current().name().map(String::from);
+    static SAMPLE_COUNTERS: std::cell::RefCell<HashMap<String, u32>> = std::cell::RefCell::new(HashMap::new());
+    /// Backend reported by the dispatcher for the innermost open ProfileScope
+    static SCOPE_BACKEND: std::cell::Cell<Option<&'static str>> = const { std::cell::Cell::new(None) };
+    /// Python call site set by the tensor layer when call-site tracking is on
+    static CALL_SITE: std::cell::RefCell<Option<CallSite>> = const { std::cell::RefCell::new(None) };
+    /// Arena byte counter of the innermost open ProfileScope (or the one a
+    /// parallel task inherited through with_task_context)
+    static SCOPE_ARENA_BYTES: std::cell::RefCell<Option<Arc<AtomicU64>>> = const { std::cell::RefCell::new(None) };
+}
+
+/// Credits closing arena regions to the thread's open ProfileScope
+struct ScopeArenaObserver;
+
+impl ArenaUsageObserver for ScopeArenaObserver {
+    fn region_closed(&self, bytes: u64) {
+        SCOPE_ARENA_BYTES.with(|counter| {
+            if let Some(counter) = counter.borrow().as_ref() {
+                counter.fetch_add(bytes, Ordering::Relaxed);
+            }
+        });
+    }
+}
+
+static SCOPE_ARENA_OBSERVER: ScopeArenaObserver = ScopeArenaObserver;
+
+/// Label the calling thread's innermost open ProfileScope with the backend
+/// that actually ran (called from backend dispatch bookkeeping)
+#[inline]
+pub fn report_scope_backend(backend: &'static str) {
+    SCOPE_BACKEND.with(|slot| slot.set(Some(backend)));
+}
+
+/// Advance this thread's call counter for `operation`; true for every `rate`-th call
+/// (starting with the first)
+fn take_sample(operation: &str, rate: u32) -> bool {
+    SAMPLE_COUNTERS.with(|counters| {
+        let mut counters = counters.borrow_mut();
+        let counter = match counters.get_mut(operation) {
+            Some(counter) => counter,
+            None => counters.entry(operation.to_string()).or_insert(0),
+        };
+        let keep = *counter % rate == 0;
+        *counter = (*counter + 1) % rate;
+        keep
+    })
+}
+
+/// Small, stable id for the calling thread (assigned on first use, starting at 1)
+#[inline]
+pub fn current_thread_id() -> u64 {
+    THREAD_ID.with(|id| *id)
+}
+
+/// Name of the calling thread (e.g. "main"), if it has one
+#[inline]
+pub fn current_thread_name() -> Option<String> {
+    THREAD_NAME.with(|name| name.clone())
 }
 
 /// Get current timestamp in microseconds
@@ -125,7 +976,15 @@ pub fn now_micros() -> u64 {
 
 /// RAII guard for profiling a scope
 ///
-/// Automatically records the operation when dropped
+/// Automatically records the operation when dropped, along with the arena
+/// bytes allocated by regions closed in between (on this thread, or in
+/// parallel tasks run under `with_task_context`; nested scopes keep their
+/// own) and the heap-fallback bytes (see `allocation_counters`). The event
+/// also goes to the attached named profiler, if one is set.
+///
+/// The backend passed to `new` is only a default: a dispatch made while the
+/// scope is open reports the backend that ran (see `report_scope_backend`),
+/// and that label wins.
 pub struct ProfileScope {
     profiler: Profiler,
     operation: String,
@@ -133,6 +992,24 @@ pub struct ProfileScope {
     data_size: usize,
     start_time_us: u64,
     context: Option<String>,
+    call_site: Option<CallSite>,
+    dims: Option<(usize, usize, usize)>,
+    thread_id: u64,
+    thread_name: Option<String>,
+    /// Heap fallback counter at construction
+    start_heap_bytes: u64,
+    /// Arena bytes credited to this scope (None while profiling is off)
+    arena_bytes: Option<Arc<AtomicU64>>,
+    /// Enclosing scope's arena counter, restored on drop
+    outer_arena_bytes: Option<Arc<AtomicU64>>,
+    /// Enclosing scope's reported backend, restored on drop
+    outer_backend: Option<&'static str>,
+    /// Key/value pairs added with add_metadata, in insertion order
+    attributes: Vec<(String, String)>,
+    /// Set once the event is recorded (finish() or drop)
+    recorded: bool,
+    /// Time spent inside gil_released() so far (µs)
+    gil_released_us: u64,
 }
 
 impl ProfileScope {
@@ -144,7 +1021,15 @@ impl ProfileScope {
         data_size: usize,
     ) -> Self {
         let context = PROFILER_CONTEXT.with(|ctx: &std::cell::RefCell<Option<String>>| ctx.borrow().clone());
-        
+        let arena_bytes = (profiler.is_enabled() || active_profiler().is_some()).then(|| {
+            set_usage_observer(&SCOPE_ARENA_OBSERVER);
+            Arc::new(AtomicU64::new(0))
+        });
+        let outer_arena_bytes = match &arena_bytes {
+            Some(counter) => SCOPE_ARENA_BYTES.with(|slot| slot.replace(Some(counter.clone()))),
+            None => None,
+        };
+
         Self {
             profiler,
             operation,
@@ -152,22 +1037,107 @@ impl ProfileScope {
             data_size,
             start_time_us: now_micros(),
             context,
+            call_site: get_call_site(),
+            dims: None,
+            thread_id: current_thread_id(),
+            thread_name: current_thread_name(),
+            start_heap_bytes: allocation_counters().1,
+            arena_bytes,
+            outer_arena_bytes,
+            outer_backend: SCOPE_BACKEND.with(|slot| slot.take()),
+            attributes: Vec::new(),
+            recorded: false,
+            gil_released_us: 0,
         }
     }
+
+    /// Run `f` (the FFI wrapper's `py.allow_threads` call) and count its time
+    /// as GIL-released; the rest of the scope counts as GIL-held
+    pub fn gil_released<R>(&mut self, f: impl FnOnce() -> R) -> R {
+        let start = Instant::now();
+        let result = f();
+        self.add_gil_released(start.elapsed());
+        result
+    }
+
+    /// Count `elapsed` of this scope as spent with the GIL released
+    pub fn add_gil_released(&mut self, elapsed: Duration) {
+        self.gil_released_us += elapsed.as_micros() as u64;
+    }
+
+    /// Attach a key/value pair (e.g. shape, dtype) to the recorded event
+    pub fn add_metadata(&mut self, key: &str, value: impl ToString) {
+        self.attributes.push((key.to_string(), value.to_string()));
+    }
+
+    /// Stop timing and record the event now (e.g. before copying results out)
+    pub fn finish(mut self) {
+        self.record();
+    }
+
+    /// Attach matrix dimensions (M, N, K) to the recorded event
+    pub fn set_dims(&mut self, m: usize, n: usize, k: usize) {
+        self.dims = Some((m, n, k));
+    }
 }
 
-impl Drop for ProfileScope {
-    fn drop(&mut self) {
+impl ProfileScope {
+    fn record(&mut self) {
+        if std::mem::replace(&mut self.recorded, true) {
+            return;
+        }
         let end_time_us = now_micros();
+        let heap_bytes = allocation_counters().1;
+        let arena_bytes = match self.arena_bytes.take() {
+            Some(counter) => {
+                SCOPE_ARENA_BYTES.with(|slot| slot.replace(self.outer_arena_bytes.take()));
+                counter.load(Ordering::Relaxed)
+            }
+            None => 0,
+        };
+        let duration_us = end_time_us.saturating_sub(self.start_time_us);
+        let gil_released_us = self.gil_released_us.min(duration_us);
+        let backend = match SCOPE_BACKEND.with(|slot| slot.replace(self.outer_backend)) {
+            Some(reported) => reported.to_string(),
+            None => std::mem::take(&mut self.backend),
+        };
         
-        self.profiler.record_operation(
-            self.operation.clone(),
-            self.backend.clone(),
-            self.data_size,
-            self.start_time_us,
+        let event = OperationEvent {
+            operation: std::mem::take(&mut self.operation),
+            backend,
+            data_size: self.data_size,
+            start_time_us: self.start_time_us,
             end_time_us,
-            self.context.clone(),
-        );
+            context: self.context.take(),
+            dims: self.dims,
+            thread_id: self.thread_id,
+            thread_name: self.thread_name.take(),
+            sample_weight: 1,
+            seq: 0,
+            arena_bytes_used: arena_bytes,
+            heap_bytes_allocated: heap_bytes.saturating_sub(self.start_heap_bytes),
+            kind: EventKind::Operation,
+            metadata: None,
+            warmup: false,
+            attributes: std::mem::take(&mut self.attributes),
+            gil_held_us: duration_us - gil_released_us,
+            gil_released_us,
+            session: None,
+            call_site: self.call_site.take(),
+        };
+
+        if let Some(attached) = active_profiler() {
+            if attached.is_enabled() && !attached.same_as(&self.profiler) {
+                attached.record_event(event.clone());
+            }
+        }
+        self.profiler.record_event(event);
+    }
+}
+
+impl Drop for ProfileScope {
+    fn drop(&mut self) {
+        self.record();
     }
 }
 
@@ -179,29 +1149,160 @@ pub fn set_context(context: Option<String>) {
 }
 
 /// Get the current profiling context
-#[allow(dead_code)]
 pub fn get_context() -> Option<String> {
     PROFILER_CONTEXT.with(|ctx: &std::cell::RefCell<Option<String>>| ctx.borrow().clone())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::thread;
-    use std::time::Duration;
-    
-    #[test]
-    fn test_profiler_enable_disable() {
-        let profiler = Profiler::new();
-        assert!(!profiler.is_enabled());
-        
-        profiler.enable();
-        assert!(profiler.is_enabled());
-        
-        profiler.disable();
-        assert!(!profiler.is_enabled());
-    }
-    
+/// What a parallel task inherits from the dispatching thread: the profiling
+/// context and the open ProfileScope's arena byte counter
+#[derive(Clone, Default)]
+pub struct TaskContext {
+    context: Option<String>,
+    arena_bytes: Option<Arc<AtomicU64>>,
+}
+
+/// Capture the calling thread's TaskContext for with_task_context()
+pub fn task_context() -> TaskContext {
+    TaskContext {
+        context: get_context(),
+        arena_bytes: SCOPE_ARENA_BYTES.with(|slot| slot.borrow().clone()),
+    }
+}
+
+/// with_context() that also credits the task's arena use to the dispatching
+/// thread's ProfileScope
+pub fn with_task_context<R>(task: TaskContext, f: impl FnOnce() -> R) -> R {
+    struct Restore(Option<Arc<AtomicU64>>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            SCOPE_ARENA_BYTES.with(|slot| slot.replace(self.0.take()));
+        }
+    }
+
+    with_context(task.context, || {
+        let _restore = Restore(SCOPE_ARENA_BYTES.with(|slot| slot.replace(task.arena_bytes)));
+        f()
+    })
+}
+
+/// Set the Python call site stamped on this thread's next operations
+///
+/// Opt-in: the Python layer only calls this while call-site tracking is
+/// enabled, so untracked threads keep None and pay only the empty read.
+pub fn set_call_site(call_site: Option<CallSite>) {
+    CALL_SITE.with(|site| *site.borrow_mut() = call_site);
+}
+
+/// Get the current Python call site
+pub fn get_call_site() -> Option<CallSite> {
+    CALL_SITE.with(|site| site.borrow().clone())
+}
+
+/// Run `f` with the profiling context set to `context`, restoring the
+/// thread's previous context afterwards (also on panic)
+///
+/// The context is thread-local, so parallel paths capture get_context() on
+/// the dispatching thread and wrap each Rayon task in this; events recorded
+/// inside the task then carry the caller's context.
+pub fn with_context<R>(context: Option<String>, f: impl FnOnce() -> R) -> R {
+    struct Restore(Option<String>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            set_context(self.0.take());
+        }
+    }
+
+    let previous = PROFILER_CONTEXT.with(|ctx: &std::cell::RefCell<Option<String>>| ctx.replace(context));
+    let _restore = Restore(previous);
+    f()
+}
+
+/// Startup profiling settings read from the environment
+///
+/// - COREPY_PROFILE=1 (or true/yes/on) enables profiling at import
+/// - COREPY_PROFILE_CONTEXT sets the importing thread's initial context
+/// - COREPY_PROFILE_OUTPUT is where the JSON report is written at exit
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EnvProfileConfig {
+    pub enabled: bool,
+    pub context: Option<String>,
+    pub output: Option<PathBuf>,
+}
+
+impl EnvProfileConfig {
+    pub fn from_env() -> Self {
+        Self::from_lookup(|key| std::env::var(key).ok())
+    }
+
+    /// Parse from any variable source (empty values count as unset)
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let var = |key: &str| lookup(key).filter(|value| !value.is_empty());
+        Self {
+            enabled: var("COREPY_PROFILE").is_some_and(|value| {
+                matches!(value.to_ascii_lowercase().as_str(), "1" | "true" | "yes" | "on")
+            }),
+            context: var("COREPY_PROFILE_CONTEXT"),
+            output: var("COREPY_PROFILE_OUTPUT").map(PathBuf::from),
+        }
+    }
+
+    /// Enable `profiler` and set the calling thread's context when profiling
+    /// was requested; returns the exit report path to register, if any
+    pub fn apply(&self, profiler: &Profiler) -> Option<&Path> {
+        if !self.enabled {
+            return None;
+        }
+        profiler.enable();
+        if self.context.is_some() {
+            set_context(self.context.clone());
+        }
+        self.output.as_deref()
+    }
+}
+
+lazy_static! {
+    /// Profiler and path written by write_exit_report()
+    static ref EXIT_REPORT: Mutex<Option<(Profiler, PathBuf)>> = Mutex::new(None);
+}
+
+/// Have write_exit_report() save `profiler`'s JSON report to `path`
+/// (replaces an earlier registration)
+pub fn set_exit_report(profiler: &Profiler, path: PathBuf) {
+    *EXIT_REPORT.lock() = Some((profiler.clone(), path));
+}
+
+/// Write the registered exit report (creating parent directories), at most
+/// once; returns the path written
+pub fn write_exit_report() -> io::Result<Option<PathBuf>> {
+    let Some((profiler, path)) = EXIT_REPORT.lock().take() else {
+        return Ok(None);
+    };
+    profiler
+        .export_json_to_file(&path, None, true)
+        .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))?;
+    Ok(Some(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use std::time::Duration;
+    
+    #[test]
+    fn test_profiler_enable_disable() {
+        let profiler = Profiler::new();
+        assert!(!profiler.is_enabled());
+        
+        profiler.enable();
+        assert!(profiler.is_enabled());
+        
+        profiler.disable();
+        assert!(!profiler.is_enabled());
+    }
+    
     #[test]
     fn test_record_operation() {
         let profiler = Profiler::new();
@@ -283,7 +1384,813 @@ mod tests {
         assert_eq!(events[0].operation, "scoped_op");
         assert!(events[0].duration_us() >= 1000); // At least 1ms
     }
+
+    #[test]
+    fn test_profile_scope_metadata_round_trips() {
+        let profiler = Profiler::new();
+        profiler.enable();
+
+        {
+            let mut scope = ProfileScope::new(profiler.clone(), "matmul".to_string(), "CPU".to_string(), 24);
+            scope.add_metadata("m", 2);
+            scope.add_metadata("dtype", "float32");
+        }
+
+        let events = profiler.get_events();
+        let expected = vec![("m".to_string(), "2".to_string()), ("dtype".to_string(), "float32".to_string())];
+        assert_eq!(events[0].attributes, expected);
+
+        let parsed: ProfileReport = serde_json::from_str(&profiler.export_json(None, None, true).unwrap()).unwrap();
+        assert_eq!(parsed.events[0].attributes, expected);
+
+        let trace: serde_json::Value = serde_json::from_str(&profiler.export_chrome_trace(None).unwrap()).unwrap();
+        let args = &trace["traceEvents"][0]["args"];
+        assert_eq!((args["m"].as_str(), args["dtype"].as_str()), (Some("2"), Some("float32")));
+    }
+
+    #[test]
+    fn test_profile_scope_splits_gil_time() {
+        let profiler = Profiler::new();
+        profiler.enable();
+
+        {
+            let mut scope = ProfileScope::new(profiler.clone(), "gil_op".to_string(), "CPU".to_string(), 1);
+            scope.gil_released(|| thread::sleep(Duration::from_millis(2)));
+        }
+        drop(ProfileScope::new(profiler.clone(), "gil_op".to_string(), "CPU".to_string(), 1));
+
+        let events = profiler.get_events();
+        assert!(events[0].gil_released_us >= 2000);
+        for event in &events {
+            assert_eq!(event.gil_held_us + event.gil_released_us, event.duration_us());
+        }
+        assert_eq!(events[1].gil_released_us, 0);
+
+        // Attributed time never exceeds the scope's own duration
+        {
+            let mut scope = ProfileScope::new(profiler.clone(), "clamped".to_string(), "CPU".to_string(), 1);
+            scope.add_gil_released(Duration::from_secs(60));
+        }
+        let clamped = profiler.get_events().pop().unwrap();
+        assert_eq!((clamped.gil_held_us, clamped.gil_released_us), (0, clamped.duration_us()));
+    }
+
+    #[test]
+    fn test_profile_scope_finish_records_once() {
+        let profiler = Profiler::new();
+        profiler.enable();
+
+        let scope = ProfileScope::new(profiler.clone(), "early".to_string(), "CPU".to_string(), 1);
+        scope.finish();
+        assert_eq!(profiler.event_count(), 1);
+        let end_us = profiler.get_events()[0].end_time_us;
+
+        // Work after finish() is not timed
+        thread::sleep(Duration::from_millis(2));
+        assert_eq!(profiler.event_count(), 1);
+        assert!(end_us < now_micros());
+        assert!(profiler.get_events()[0].attributes.is_empty());
+    }
+
+    #[test]
+    fn test_reported_backend_stays_with_its_scope() {
+        let profiler = Profiler::new();
+        profiler.enable();
+
+        {
+            let _outer = ProfileScope::new(profiler.clone(), "outer".to_string(), "CPU".to_string(), 1);
+            report_scope_backend("CPU-parallel");
+            {
+                let _inner = ProfileScope::new(profiler.clone(), "inner".to_string(), "CPU".to_string(), 1);
+                report_scope_backend("OpenBLAS");
+            }
+        }
+        drop(ProfileScope::new(profiler.clone(), "unreported".to_string(), "CPU".to_string(), 1));
+
+        let backends: Vec<(String, String)> = profiler
+            .get_events()
+            .into_iter()
+            .map(|e| (e.operation, e.backend))
+            .collect();
+        assert_eq!(backends, vec![
+            ("inner".to_string(), "OpenBLAS".to_string()),
+            ("outer".to_string(), "CPU-parallel".to_string()),
+            ("unreported".to_string(), "CPU".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_profile_scope_backend_and_dims() {
+        let profiler = Profiler::new();
+        profiler.enable();
+
+        {
+            let mut scope = ProfileScope::new(profiler.clone(), "matmul".to_string(), "CPU".to_string(), 24);
+            report_scope_backend("OpenBLAS");
+            scope.set_dims(2, 3, 4);
+        }
+
+        let events = profiler.get_events();
+        assert_eq!(events[0].backend, "OpenBLAS");
+        assert_eq!(events[0].dims, Some((2, 3, 4)));
+
+        let json = profiler.export_json(None, None, true).unwrap();
+        let report: ProfileReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(report.events.len(), 1);
+        assert_eq!(report.events[0].dims, Some((2, 3, 4)));
+
+        let json = profiler.export_json(None, None, false).unwrap();
+        assert!(!json.contains("\"events\""));
+    }
     
+    #[test]
+    fn test_markers_are_listed_but_not_aggregated() {
+        let profiler = Profiler::new();
+        profiler.enable();
+        profiler.set_sample_rate(4).unwrap();
+        for epoch in 0..3 {
+            profiler.record_marker(&format!("epoch {} start", epoch), Some(format!("{{\"epoch\": {}}}", epoch)));
+            let start = now_micros();
+            profiler.record_operation("marked_op".to_string(), "CPU".to_string(), 1, start, start + 10, None);
+        }
+
+        let report = profiler.generate_report(None, None);
+        let names: Vec<&str> = report.markers.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, vec!["epoch 0 start", "epoch 1 start", "epoch 2 start"]);
+        assert_eq!(report.markers[1].metadata.as_deref(), Some("{\"epoch\": 1}"));
+        // Only the op shows up in the metrics (sampled at 1/4, markers never are)
+        assert_eq!(report.operations.keys().collect::<Vec<_>>(), vec!["marked_op"]);
+        assert_eq!(report.operations["marked_op"].count, 4);
+        assert_eq!(report.threads.values().map(|t| t.event_count).sum::<usize>(), 4);
+
+        let trace = profiler.chrome_trace(None);
+        let instants: Vec<&serde_json::Value> = trace["traceEvents"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|e| e["ph"] == "i")
+            .collect();
+        assert_eq!(instants.len(), 3);
+        assert_eq!(instants[0]["name"], "epoch 0 start");
+        assert_eq!(instants[0]["s"], "t");
+        assert!(instants[0].get("dur").is_none());
+    }
+
+    #[test]
+    fn test_markers_follow_context_filter() {
+        let profiler = Profiler::new();
+        profiler.enable();
+        with_context(Some("train".to_string()), || profiler.record_marker("train step", None));
+        profiler.record_marker("outside", None);
+
+        let train = profiler.generate_report(Some("train"), Some(&["unrelated".to_string()]));
+        assert_eq!(train.markers.len(), 1);
+        assert_eq!(train.markers[0].context.as_deref(), Some("train"));
+        assert_eq!(profiler.generate_report(None, None).markers.len(), 2);
+    }
+
+    #[test]
+    fn test_chrome_trace_export() {
+        let profiler = Profiler::new();
+        profiler.enable();
+
+        profiler.record_operation("add".to_string(), "CPU".to_string(), 100, 1000, 1250, Some("fwd".to_string()));
+        let worker_tid = thread::spawn({
+            let profiler = profiler.clone();
+            move || {
+                profiler.record_operation("sum".to_string(), "CPU-parallel".to_string(), 50, 2000, 2100, None);
+                current_thread_id()
+            }
+        }).join().unwrap();
+        assert_ne!(worker_tid, current_thread_id());
+
+        let trace: serde_json::Value = serde_json::from_str(&profiler.export_chrome_trace(None).unwrap()).unwrap();
+        let events = trace["traceEvents"].as_array().unwrap();
+        assert_eq!(events.len(), 2);
+        for event in events {
+            for key in ["name", "ph", "ts", "dur", "pid", "tid", "args"] {
+                assert!(event.get(key).is_some(), "missing {}", key);
+            }
+            assert_eq!(event["ph"], "X");
+            assert!(event["args"].get("backend").is_some() && event["args"].get("data_size").is_some());
+        }
+        assert_eq!(events[0]["name"], "add");
+        assert_eq!((events[0]["ts"].as_u64(), events[0]["dur"].as_u64()), (Some(1000), Some(250)));
+        assert_eq!(events[0]["tid"].as_u64(), Some(current_thread_id()));
+        assert_eq!(events[0]["args"]["context"], "fwd");
+        assert_eq!(events[1]["tid"].as_u64(), Some(worker_tid));
+
+        let filtered: serde_json::Value = serde_json::from_str(&profiler.export_chrome_trace(Some("fwd")).unwrap()).unwrap();
+        assert_eq!(filtered["traceEvents"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_per_thread_breakdown() {
+        let profiler = Profiler::new();
+        profiler.enable();
+
+        // Main thread: 2 events, 1ms + 2ms
+        profiler.record_operation("add".to_string(), "CPU".to_string(), 1, 0, 1000, None);
+        profiler.record_operation("add".to_string(), "CPU".to_string(), 1, 0, 2000, None);
+
+        // Named worker: 3 events of 0.5ms each
+        let worker_id = thread::Builder::new()
+            .name("profile-worker".to_string())
+            .spawn({
+                let profiler = profiler.clone();
+                move || {
+                    for _ in 0..3 {
+                        let _scope = ProfileScope::new(profiler.clone(), "sum".to_string(), "CPU".to_string(), 1);
+                    }
+                    profiler.record_operation("sum".to_string(), "CPU".to_string(), 1, 0, 500, None);
+                    current_thread_id()
+                }
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+
+        let events = profiler.get_events();
+        assert!(events[..2].iter().all(|e| e.thread_id == current_thread_id()));
+        assert!(events[2..].iter().all(|e| e.thread_id == worker_id));
+        assert_eq!(events[2].thread_name.as_deref(), Some("profile-worker"));
+
+        let report = profiler.generate_report(None, None);
+        assert_eq!(report.threads.len(), 2);
+        let main = &report.threads[&current_thread_id()];
+        assert_eq!((main.event_count, main.total_time_ms), (2, 3.0));
+        let worker = &report.threads[&worker_id];
+        assert_eq!(worker.event_count, 4);
+        assert_eq!(worker.thread_name.as_deref(), Some("profile-worker"));
+        let worker_total: f64 = events[2..].iter().map(|e| e.duration_ms()).sum();
+        assert!((worker.total_time_ms - worker_total).abs() < 1e-9);
+
+        let json: serde_json::Value = serde_json::from_str(&profiler.export_json(None, None, false).unwrap()).unwrap();
+        assert_eq!(json["threads"][worker_id.to_string()]["event_count"], 4);
+    }
+
+    #[test]
+    fn test_capacity_drops_oldest_events() {
+        let capacity = 1000;
+        let profiler = Profiler::with_capacity(capacity);
+        profiler.enable();
+
+        for i in 0..(capacity as u64 + 100) {
+            profiler.record_operation("add".to_string(), "CPU".to_string(), 1, i, i + 1, None);
+            assert!(profiler.event_count() <= capacity);
+        }
+
+        assert_eq!(profiler.event_count(), capacity);
+        assert_eq!(profiler.dropped_events(), 100);
+        let allocated: usize = profiler.shards.iter().map(|shard| shard.0.lock().capacity()).sum();
+        assert!(allocated < 2 * capacity);
+        // The oldest 100 were evicted
+        assert_eq!(profiler.get_events()[0].start_time_us, 100);
+
+        let report = profiler.generate_report(None, None);
+        assert_eq!(report.metadata.dropped_events, 100);
+        assert_eq!(report.operations["add"].count, capacity);
+
+        profiler.clear();
+        assert_eq!(profiler.dropped_events(), 0);
+    }
+
+    #[test]
+    fn test_capacity_evicts_across_threads() {
+        let profiler = Profiler::with_capacity(5);
+        profiler.enable();
+        for i in 0..5 {
+            profiler.record_operation("filler".to_string(), "CPU".to_string(), 1, i, i + 1, None);
+        }
+        // The other thread has nothing of its own to evict, so the global oldest goes
+        let worker = profiler.clone();
+        std::thread::spawn(move || {
+            worker.record_operation("late".to_string(), "CPU".to_string(), 1, 10, 11, None);
+        }).join().unwrap();
+
+        let seqs: Vec<u64> = profiler.get_events().iter().map(|e| e.seq).collect();
+        assert_eq!(seqs, vec![1, 2, 3, 4, 5]);
+        assert_eq!(profiler.dropped_events(), 1);
+    }
+
+    #[test]
+    fn test_concurrent_recording_stress() {
+        const THREADS: usize = 16;
+        const PER_THREAD: usize = 62_500;
+        let profiler = Profiler::with_capacity(THREADS * PER_THREAD);
+        profiler.enable();
+
+        let handles: Vec<_> = (0..THREADS)
+            .map(|t| {
+                let profiler = profiler.clone();
+                std::thread::spawn(move || {
+                    for i in 0..PER_THREAD as u64 {
+                        profiler.record_operation("stress".to_string(), "CPU".to_string(), t, i, i + 1, None);
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(profiler.event_count(), THREADS * PER_THREAD);
+        assert_eq!(profiler.dropped_events(), 0);
+
+        // Merged view is in seq order with every event present once, and each
+        // thread's events keep their own order
+        let events = profiler.get_events();
+        assert!(events.iter().enumerate().all(|(i, e)| e.seq == i as u64));
+        let mut next_start = [0u64; THREADS];
+        for event in &events {
+            assert_eq!(event.start_time_us, next_start[event.data_size]);
+            next_start[event.data_size] += 1;
+        }
+
+        let report = profiler.generate_report(None, None);
+        assert_eq!(report.operations["stress"].count, THREADS * PER_THREAD);
+        assert_eq!(report.threads.len(), THREADS);
+
+        profiler.clear();
+        assert_eq!(profiler.event_count(), 0);
+        assert!(profiler.get_events().is_empty());
+    }
+
+    /// Recording throughput against a single shared lock (the pre-sharding layout):
+    /// `cargo test --release -- --ignored bench_sharded_recording --nocapture`
+    #[test]
+    #[ignore]
+    fn bench_sharded_recording() {
+        use parking_lot::RwLock;
+        use std::time::Instant;
+
+        const THREADS: usize = 32;
+        const PER_THREAD: usize = 100_000;
+        let event = |i: u64| OperationEvent {
+            operation: "bench".to_string(),
+            backend: "CPU".to_string(),
+            data_size: 1,
+            start_time_us: i,
+            end_time_us: i + 1,
+            context: None,
+            dims: None,
+            thread_id: current_thread_id(),
+            thread_name: None,
+            sample_weight: 1,
+            seq: 0,
+            arena_bytes_used: 0,
+            heap_bytes_allocated: 0,
+            kind: EventKind::Operation,
+            metadata: None,
+            warmup: false,
+            attributes: Vec::new(),
+            gil_held_us: 0,
+            gil_released_us: 0,
+            session: None,
+            call_site: None,
+        };
+        let run = |record: &(dyn Fn(u64) + Sync)| {
+            let start = Instant::now();
+            std::thread::scope(|scope| {
+                for _ in 0..THREADS {
+                    scope.spawn(|| (0..PER_THREAD as u64).for_each(record));
+                }
+            });
+            start.elapsed().as_nanos() as f64 / (THREADS * PER_THREAD) as f64
+        };
+
+        let single: RwLock<VecDeque<OperationEvent>> = RwLock::new(VecDeque::new());
+        let single_ns = run(&|i| single.write().push_back(event(i)));
+
+        let profiler = Profiler::with_capacity(THREADS * PER_THREAD);
+        profiler.enable();
+        let sharded_ns = run(&|i| profiler.record_event(event(i)));
+
+        println!("{} threads: single lock {:.1} ns/event, sharded {:.1} ns/event", THREADS, single_ns, sharded_ns);
+    }
+
+    #[test]
+    fn test_shrinking_capacity_evicts() {
+        let profiler = Profiler::with_capacity(10);
+        profiler.enable();
+        for i in 0..10 {
+            profiler.record_operation("sum".to_string(), "CPU".to_string(), 1, i, i + 1, None);
+        }
+
+        assert!(profiler.set_capacity(0).is_err());
+        profiler.set_capacity(4).unwrap();
+        assert_eq!(profiler.capacity(), 4);
+        assert_eq!(profiler.event_count(), 4);
+        assert_eq!(profiler.dropped_events(), 6);
+        assert_eq!(profiler.get_events()[0].start_time_us, 6);
+    }
+
+    #[test]
+    fn test_export_to_file() {
+        let profiler = Profiler::new();
+        profiler.enable();
+        profiler.record_operation("add".to_string(), "CPU".to_string(), 8, 0, 1000, None);
+
+        let dir = std::env::temp_dir().join(format!("corepy-profile-{}", uuid::Uuid::new_v4()));
+        let report_path = dir.join("nested").join("report.json");
+
+        // Missing parent directory is an error unless asked to create it
+        assert!(profiler.export_json_to_file(&report_path, None, false).is_err());
+        profiler.export_json_to_file(&report_path, None, true).unwrap();
+        let report: ProfileReport = serde_json::from_str(&std::fs::read_to_string(&report_path).unwrap()).unwrap();
+        assert_eq!(report.operations["add"].count, 1);
+
+        let trace_path = dir.join("trace.json");
+        profiler.export_chrome_trace_to_file(&trace_path, None, false).unwrap();
+        let trace: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&trace_path).unwrap()).unwrap();
+        assert_eq!(trace["traceEvents"][0]["name"], "add");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_sampling_scales_report() {
+        let profiler = Profiler::new();
+        profiler.enable();
+        assert!(profiler.set_sample_rate(0).is_err());
+        profiler.set_sample_rate(100).unwrap();
+
+        for i in 0..10_000u64 {
+            profiler.record_operation("sampled_add".to_string(), "CPU".to_string(), 4, i, i + 10, None);
+        }
+
+        let stored = profiler.event_count();
+        assert!((95..=105).contains(&stored), "stored {}", stored);
+
+        let report = profiler.generate_report(None, None);
+        let metrics = &report.operations["sampled_add"];
+        assert!(metrics.estimated);
+        assert!((metrics.count as i64 - 10_000).abs() <= 500, "count {}", metrics.count);
+        assert!((metrics.total_time_ms - 100.0).abs() <= 5.0);
+        assert_eq!(report.metadata.sample_rate, 100);
+
+        // Back to exact recording
+        profiler.set_sample_rate(1).unwrap();
+        profiler.clear();
+        profiler.record_operation("sampled_add".to_string(), "CPU".to_string(), 4, 0, 10, None);
+        let report = profiler.generate_report(None, None);
+        assert_eq!(report.operations["sampled_add"].count, 1);
+        assert!(!report.operations["sampled_add"].estimated);
+    }
+
+    #[test]
+    fn test_warmup_calls_excluded_from_aggregates() {
+        let profiler = Profiler::new();
+        profiler.enable();
+        profiler.set_warmup_skip(3);
+
+        // 3 slow cold calls, then 10 fast ones; another op has its own window
+        for i in 0..3u64 {
+            profiler.record_operation("warm_add".to_string(), "CPU".to_string(), 4, i * 10_000, i * 10_000 + 5_000, None);
+        }
+        for i in 0..10u64 {
+            profiler.record_operation("warm_add".to_string(), "CPU".to_string(), 4, 100_000 + i * 100, 100_000 + i * 100 + 20, None);
+        }
+        profiler.record_operation("warm_mul".to_string(), "CPU".to_string(), 4, 0, 10, None);
+
+        let report = profiler.generate_report(None, None);
+        let add = &report.operations["warm_add"];
+        assert_eq!(add.count, 10);
+        assert_eq!((add.min_time_ms, add.max_time_ms), (0.02, 0.02));
+        assert!((add.avg_time_ms - 0.02).abs() < 1e-12);
+        assert!((add.percentiles["p99"] - 0.02).abs() < 1e-12);
+        assert_eq!(add.warmup_count, 3);
+        assert!((add.warmup_total_ms - 15.0).abs() < 1e-9);
+        assert!((report.total_time_ms - 0.2).abs() < 1e-9);
+
+        let mul = &report.operations["warm_mul"];
+        assert_eq!((mul.count, mul.warmup_count), (0, 1));
+
+        // Raw events keep the warmup calls, flagged
+        let events = profiler.get_events();
+        assert_eq!(events.len(), 14);
+        assert_eq!(events.iter().filter(|e| e.warmup).count(), 4);
+        let json = profiler.export_json(None, None, true).unwrap();
+        let parsed: ProfileReport = serde_json::from_str(&json).unwrap();
+        assert!(parsed.events[0].warmup && !parsed.events[3].warmup);
+
+        // clear() restarts the warmup window
+        profiler.clear();
+        profiler.record_operation("warm_add".to_string(), "CPU".to_string(), 4, 0, 10, None);
+        assert!(profiler.get_events()[0].warmup);
+    }
+
+    #[test]
+    fn test_back_to_back_sessions() {
+        let profiler = Profiler::new();
+        profiler.enable();
+        let record = |operation: &str| profiler.record_operation(operation.to_string(), "CPU".to_string(), 4, 0, 10, None);
+
+        record("before");
+        let first = profiler.start_session("warm").unwrap();
+        assert!(profiler.start_session("overlap").is_err());
+        record("add");
+        record("add");
+        let first_report = profiler.end_session(first).unwrap();
+        assert!(profiler.end_session(first).is_err());
+
+        let second = profiler.start_session("steady").unwrap();
+        record("mul");
+        assert_eq!(profiler.get_session_report(second).unwrap().operations["mul"].count, 1);
+        let second_report = profiler.end_session(second).unwrap();
+        record("after");
+
+        assert_eq!(first_report.operations.keys().collect::<Vec<_>>(), ["add"]);
+        assert_eq!(first_report.operations["add"].count, 2);
+        assert_eq!(first_report.metadata.session_name.as_deref(), Some("warm"));
+        assert_eq!(second_report.operations.keys().collect::<Vec<_>>(), ["mul"]);
+
+        let sessions = profiler.list_sessions();
+        let listed: Vec<(u64, &str, bool)> = sessions.iter().map(|s| (s.id, s.name.as_str(), s.active)).collect();
+        assert_eq!(listed, [(first, "warm", false), (second, "steady", false)]);
+
+        // The regular report still covers everything; the archive survives clear()
+        assert_eq!(profiler.generate_report(None, None).operations.len(), 4);
+        profiler.clear();
+        assert_eq!(profiler.get_session_report(first).unwrap().operations["add"].count, 2);
+
+        profiler.set_max_sessions(1).unwrap();
+        assert!(profiler.get_session_report(first).is_none());
+        assert_eq!(profiler.list_sessions().len(), 1);
+        assert!(profiler.set_max_sessions(0).is_err());
+    }
+
+    #[test]
+    fn test_drain_events_concurrently() {
+        const TOTAL: u64 = 5_000;
+        let profiler = Profiler::with_capacity(TOTAL as usize);
+        profiler.enable();
+
+        let recorder = thread::spawn({
+            let profiler = profiler.clone();
+            move || {
+                for i in 0..TOTAL {
+                    profiler.record_operation("drain_op".to_string(), "CPU".to_string(), 1, i, i + 1, None);
+                }
+            }
+        });
+        let drainer = thread::spawn({
+            let profiler = profiler.clone();
+            move || {
+                let mut drained = Vec::new();
+                while drained.len() < TOTAL as usize {
+                    let batch = profiler.drain_events(64);
+                    assert!(batch.len() <= 64);
+                    drained.extend(batch);
+                }
+                drained
+            }
+        });
+
+        recorder.join().unwrap();
+        let drained = drainer.join().unwrap();
+        assert_eq!(profiler.event_count(), 0);
+
+        // Every event exactly once, in recording order
+        let seqs: Vec<u64> = drained.iter().map(|e| e.seq).collect();
+        assert_eq!(seqs, (0..TOTAL).collect::<Vec<_>>());
+        assert!(drained.iter().enumerate().all(|(i, e)| e.start_time_us == i as u64));
+        assert_eq!(profiler.dropped_events(), 0);
+    }
+
+    #[test]
+    fn test_spill_keeps_every_event_in_report() {
+        let dir = std::env::temp_dir().join(format!("corepy-spill-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("events.ndjson");
+
+        let profiler = Profiler::new();
+        profiler.enable();
+        profiler.enable_spill(&path, 100).unwrap();
+        let workers: Vec<_> = (0..2)
+            .map(|_| {
+                let profiler = profiler.clone();
+                thread::spawn(move || {
+                    for i in 0..525 {
+                        profiler.record_operation("spill_op".to_string(), "CPU".to_string(), 1, i, i + 1, None);
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+
+        // Several batches went to disk, the rest is still buffered
+        let spilled = fs::read_to_string(&path).unwrap().lines().count();
+        assert!(spilled >= 900);
+        assert_eq!(profiler.event_count(), 1050 - spilled);
+
+        let report = profiler.generate_report(None, None);
+        assert_eq!(report.operations["spill_op"].count, 1050);
+        assert_eq!(report.metadata.spilled_events, spilled as u64);
+        assert!(report.metadata.spill_error.is_none());
+        let seqs: Vec<u64> = profiler.get_events().iter().map(|e| e.seq).collect();
+        assert_eq!(seqs, (0..1050).collect::<Vec<_>>());
+
+        // Clearing truncates the file and spilling carries on
+        profiler.clear();
+        assert_eq!(fs::metadata(&path).unwrap().len(), 0);
+        for i in 0..150 {
+            profiler.record_operation("spill_op".to_string(), "CPU".to_string(), 1, i, i + 1, None);
+        }
+        assert_eq!(profiler.generate_report(None, None).operations["spill_op"].count, 150);
+        assert_eq!(profiler.event_count(), 50);
+
+        profiler.disable_spill();
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_spill_write_error_keeps_events_in_memory() {
+        // Every write to /dev/full fails with ENOSPC
+        let profiler = Profiler::new();
+        profiler.enable();
+        profiler.enable_spill(Path::new("/dev/full"), 10).unwrap();
+        for i in 0..25 {
+            profiler.record_operation("full_op".to_string(), "CPU".to_string(), 1, i, i + 1, None);
+        }
+
+        assert_eq!(profiler.event_count(), 25);
+        let report = profiler.generate_report(None, None);
+        assert_eq!(report.operations["full_op"].count, 25);
+        assert_eq!(report.metadata.spilled_events, 0);
+        assert!(report.metadata.spill_error.unwrap().starts_with("/dev/full"));
+        assert!(profiler.enable_spill(Path::new("/dev/full"), 0).is_err());
+    }
+
+    #[test]
+    fn test_sequence_gaps_mark_evictions() {
+        let profiler = Profiler::with_capacity(3);
+        profiler.enable();
+        for i in 0..5 {
+            profiler.record_operation("gap_op".to_string(), "CPU".to_string(), 1, i, i + 1, None);
+        }
+        let seqs: Vec<u64> = profiler.drain_events(usize::MAX).iter().map(|e| e.seq).collect();
+        assert_eq!(seqs, vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn test_scope_records_arena_delta() {
+        use crate::scheduler::arena::{record_heap_fallback, with_arena};
+
+        let profiler = Profiler::new();
+        profiler.enable();
+        {
+            let _scope = ProfileScope::new(profiler.clone(), "arena_op".to_string(), "CPU".to_string(), 512);
+            // Stand-in for a kernel's scratch buffer plus an oversized panel
+            with_arena(|scope| {
+                scope.alloc_uninit::<f32>(512).expect("allocation failed");
+            });
+            record_heap_fallback(64);
+        }
+
+        let event = &profiler.drain_events(usize::MAX)[0];
+        assert_eq!(event.arena_bytes_used, 2048);
+        assert!(event.heap_bytes_allocated >= 64);
+
+        let json = serde_json::to_value(event).unwrap();
+        assert_eq!(json["arena_bytes_used"].as_u64(), Some(2048));
+    }
+
+    #[test]
+    fn test_scope_arena_bytes_follow_nesting_and_tasks() {
+        use crate::scheduler::arena::with_arena;
+        use rayon::prelude::*;
+
+        let profiler = Profiler::new();
+        profiler.enable();
+        {
+            let _outer = ProfileScope::new(profiler.clone(), "outer_op".to_string(), "CPU".to_string(), 1);
+            with_arena(|outer| {
+                outer.alloc_zeroed::<u8>(100).expect("allocation failed");
+                // Nested regions add up; a nested scope keeps its own bytes
+                with_arena(|inner| {
+                    inner.alloc_zeroed::<f32>(10).expect("allocation failed");
+                });
+                let _nested = ProfileScope::new(profiler.clone(), "nested_op".to_string(), "CPU".to_string(), 1);
+                with_arena(|inner| {
+                    inner.alloc_zeroed::<u8>(1000).expect("allocation failed");
+                });
+            });
+            // Parallel tasks credit the dispatching scope
+            let task = task_context();
+            (0..4).into_par_iter().for_each(|_| with_task_context(task.clone(), || {
+                with_arena(|scope| {
+                    scope.alloc_zeroed::<u8>(64).expect("allocation failed");
+                });
+            }));
+        }
+        // Arena use outside any scope is not credited
+        with_arena(|scope| {
+            scope.alloc_zeroed::<u8>(4096).expect("allocation failed");
+        });
+        drop(ProfileScope::new(profiler.clone(), "no_scratch".to_string(), "CPU".to_string(), 1));
+
+        let events = profiler.drain_events(usize::MAX);
+        let bytes = |op: &str| events.iter().find(|e| e.operation == op).unwrap().arena_bytes_used;
+        assert_eq!(bytes("nested_op"), 1000);
+        assert_eq!(bytes("outer_op"), 100 + 40 + 4 * 64);
+        assert_eq!(bytes("no_scratch"), 0);
+    }
+
+    #[test]
+    fn test_named_profilers_are_isolated() {
+        let lib_a = get_or_create_profiler("test_named_a");
+        let lib_b = get_or_create_profiler("test_named_b");
+        assert!(lib_a.same_as(&get_or_create_profiler("test_named_a")));
+        assert!(!lib_a.same_as(&lib_b));
+
+        lib_a.enable();
+        lib_b.clear();
+        lib_a.record_operation("named_op".to_string(), "CPU".to_string(), 1, 0, 5, None);
+        lib_b.record_operation("named_op".to_string(), "CPU".to_string(), 1, 0, 5, None);
+
+        assert_eq!(lib_a.generate_report(None, None).operations["named_op"].count, 1);
+        assert_eq!(lib_b.event_count(), 0);
+        lib_a.clear();
+        lib_a.disable();
+    }
+
+    #[test]
+    fn test_scope_copies_into_active_profiler() {
+        let global = Profiler::new();
+        global.enable();
+        let attached = get_or_create_profiler("test_attached");
+        attached.enable();
+        let idle = get_or_create_profiler("test_attached_idle");
+
+        set_active_profiler(Some("test_attached"));
+        assert_eq!(active_profiler_name().as_deref(), Some("test_attached"));
+        drop(ProfileScope::new(global.clone(), "attached_op".to_string(), "CPU".to_string(), 8));
+        set_active_profiler(None);
+        drop(ProfileScope::new(global.clone(), "attached_op".to_string(), "CPU".to_string(), 8));
+
+        let attached_count = |profiler: &Profiler| {
+            profiler.get_events().iter().filter(|e| e.operation == "attached_op").count()
+        };
+        assert_eq!(global.event_count(), 2);
+        assert_eq!(attached_count(&attached), 1);
+        assert_eq!(attached_count(&idle), 0);
+        attached.disable();
+    }
+
+    #[test]
+    fn test_env_config_parsing() {
+        let config = |vars: &[(&str, &str)]| {
+            let vars: HashMap<String, String> =
+                vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+            EnvProfileConfig::from_lookup(|key| vars.get(key).cloned())
+        };
+
+        assert_eq!(config(&[]), EnvProfileConfig::default());
+        assert!(config(&[("COREPY_PROFILE", "1")]).enabled);
+        assert!(config(&[("COREPY_PROFILE", "On")]).enabled);
+        assert!(!config(&[("COREPY_PROFILE", "0")]).enabled);
+
+        let full = config(&[
+            ("COREPY_PROFILE", "true"),
+            ("COREPY_PROFILE_CONTEXT", "startup"),
+            ("COREPY_PROFILE_OUTPUT", "out/profile.json"),
+        ]);
+        assert_eq!(full.context.as_deref(), Some("startup"));
+        assert_eq!(full.output, Some(PathBuf::from("out/profile.json")));
+        // Output alone does not turn profiling on
+        assert_eq!(config(&[("COREPY_PROFILE_OUTPUT", "p.json")]).apply(&Profiler::new()), None);
+    }
+
+    #[test]
+    fn test_env_startup_writes_exit_report() {
+        let dir = std::env::temp_dir().join(format!("corepy-exit-{}", std::process::id()));
+        let path = dir.join("nested").join("profile.json");
+        let config = EnvProfileConfig {
+            enabled: true,
+            context: Some("env_startup".to_string()),
+            output: Some(path.clone()),
+        };
+
+        let profiler = Profiler::new();
+        let output = config.apply(&profiler).map(Path::to_path_buf);
+        assert!(profiler.is_enabled());
+        assert_eq!(get_context().as_deref(), Some("env_startup"));
+        set_exit_report(&profiler, output.unwrap());
+
+        drop(ProfileScope::new(profiler.clone(), "env_op".to_string(), "CPU".to_string(), 4));
+        assert_eq!(write_exit_report().unwrap(), Some(path.clone()));
+        assert_eq!(write_exit_report().unwrap(), None);
+
+        let report: ProfileReport = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(report.operations["env_op"].count, 1);
+        set_context(None);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[test]
     fn test_context_tracking() {
         set_context(Some("test_context".to_string()));
@@ -293,6 +2200,71 @@ mod tests {
         assert_eq!(get_context(), None);
     }
     
+    #[test]
+    fn test_with_context_propagates_into_rayon_tasks() {
+        use rayon::prelude::*;
+
+        let profiler = Profiler::new();
+        profiler.enable();
+
+        // Dispatch from a non-main thread, as a Python worker thread would
+        thread::spawn({
+            let profiler = profiler.clone();
+            move || {
+                set_context(Some("rayon_ctx".to_string()));
+                let context = get_context();
+                (0..8).into_par_iter().for_each(|i| {
+                    with_context(context.clone(), || {
+                        let _scope = ProfileScope::new(profiler.clone(), "chunk".to_string(), "CPU".to_string(), i);
+                    });
+                });
+                set_context(None);
+            }
+        }).join().unwrap();
+
+        let events = profiler.get_events();
+        assert_eq!(events.len(), 8);
+        assert!(events.iter().all(|e| e.context.as_deref() == Some("rayon_ctx")));
+    }
+
+    #[test]
+    fn test_with_context_restores_previous() {
+        set_context(Some("outer".to_string()));
+        let inner = with_context(Some("inner".to_string()), get_context);
+        assert_eq!(inner.as_deref(), Some("inner"));
+        assert_eq!(get_context().as_deref(), Some("outer"));
+
+        let result = std::panic::catch_unwind(|| with_context(None, || panic!("task failed")));
+        assert!(result.is_err());
+        assert_eq!(get_context().as_deref(), Some("outer"));
+        set_context(None);
+    }
+
+    #[test]
+    fn test_call_sites_grouped_per_operation() {
+        let profiler = Profiler::new();
+        profiler.enable();
+        let site = |line| CallSite { file: "train.py".to_string(), line, function: "step".to_string() };
+
+        // Untracked calls carry no call site
+        drop(ProfileScope::new(profiler.clone(), "site_add".to_string(), "CPU".to_string(), 4));
+        set_call_site(Some(site(10)));
+        for _ in 0..3 {
+            drop(ProfileScope::new(profiler.clone(), "site_add".to_string(), "CPU".to_string(), 4));
+        }
+        set_call_site(Some(site(20)));
+        drop(ProfileScope::new(profiler.clone(), "site_add".to_string(), "CPU".to_string(), 4));
+        set_call_site(None);
+        assert_eq!(get_call_site(), None);
+
+        let report = profiler.generate_report(None, None);
+        let sites = &report.operations["site_add"].call_sites;
+        let counts: Vec<(u32, usize)> = sites.iter().map(|s| (s.call_site.line, s.count)).collect();
+        assert_eq!(counts.len(), 2);
+        assert!(counts.contains(&(10, 3)) && counts.contains(&(20, 1)), "{:?}", counts);
+        assert_eq!(report.operations["site_add"].count, 5);
+    }
+
     #[test]
     fn test_generate_report() {
         let profiler = Profiler::new();
@@ -326,7 +2298,7 @@ mod tests {
             None,
         );
         
-        let report = profiler.generate_report(None);
+        let report = profiler.generate_report(None, None);
         
         assert_eq!(report.operation_count, 2); // "add" and "mul"
         assert_eq!(report.total_time_ms, 3.5); // 1 + 2 + 0.5 ms
@@ -6,9 +6,24 @@
 //! - ProfileReport: Complete profiling session report
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Lower bounds of the data_size buckets after the first ([0, 10) is bucket 0)
+pub const SIZE_BUCKET_BOUNDARIES: &[usize] = &[
+    10, 100, 1_000, 10_000, 100_000, 1_000_000, 10_000_000, 100_000_000,
+];
+
+/// Exclusive upper bounds (µs) of the duration histogram buckets; a final
+/// bucket holds everything from the last bound up (1s+)
+pub const DURATION_BUCKET_BOUNDS_US: &[u64] = &[
+    1, 10, 100, 1_000, 10_000, 100_000, 1_000_000,
+];
+
+/// Latency percentiles reported per operation (serialized as "p50", "p90", ...)
+pub const REPORTED_PERCENTILES: &[f64] = &[50.0, 90.0, 99.0];
 
 /// Represents a single profiled operation event
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct OperationEvent {
     /// Name of the operation (e.g., "add", "matmul", "sum")
     pub operation: String,
@@ -27,6 +42,143 @@ pub struct OperationEvent {
     
     /// Optional context/section name (for ProfileContext)
     pub context: Option<String>,
+
+    /// Problem dimensions (M, N, K) for matrix operations
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dims: Option<(usize, usize, usize)>,
+
+    /// Profiler-assigned id of the recording thread (stable for the thread's lifetime)
+    #[serde(default)]
+    pub thread_id: u64,
+
+    /// Name of the recording thread, if it has one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thread_name: Option<String>,
+
+    /// Number of calls this event stands for (the sample rate it was kept at)
+    #[serde(default = "one", skip_serializing_if = "is_one")]
+    pub sample_weight: u32,
+
+    /// Per-profiler sequence number, assigned when stored; gaps mean events
+    /// were evicted by the capacity limit
+    #[serde(default)]
+    pub seq: u64,
+
+    /// Arena bytes requested by regions closed while the operation ran, its
+    /// parallel tasks included (see ProfileScope)
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub arena_bytes_used: u64,
+
+    /// Heap bytes allocated because an arena was too small
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub heap_bytes_allocated: u64,
+
+    /// Operation call or user marker
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub kind: EventKind,
+
+    /// Free-form payload of a marker
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<String>,
+
+    /// One of the operation's first calls (see Profiler::set_warmup_skip);
+    /// kept in exports, left out of the timing aggregates
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub warmup: bool,
+    /// Extra key/value pairs attached with ProfileScope::add_metadata
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attributes: Vec<(String, String)>,
+
+    /// Part of the call that ran holding the GIL (µs; 0 when not tracked)
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub gil_held_us: u64,
+
+    /// Part of the call that ran with the GIL released (µs)
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub gil_released_us: u64,
+
+    /// Profiler session active when the event was recorded (see Profiler::start_session)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session: Option<u64>,
+
+    /// Python line that issued the call, when call-site tracking is on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub call_site: Option<CallSite>,
+}
+
+/// Python source location of a profiled call (see set_call_site)
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct CallSite {
+    pub file: String,
+    pub line: u32,
+    pub function: String,
+}
+
+/// Call sites listed per operation in OperationMetrics::call_sites
+pub const TOP_CALL_SITES: usize = 5;
+
+/// One call site's share of an operation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CallSiteMetrics {
+    #[serde(flatten)]
+    pub call_site: CallSite,
+    pub count: usize,
+    pub total_time_ms: f64,
+}
+
+/// Busiest TOP_CALL_SITES of (call site -> (count, total ms)), by time
+fn rank_call_sites(groups: BTreeMap<CallSite, (usize, f64)>) -> Vec<CallSiteMetrics> {
+    let mut sites: Vec<CallSiteMetrics> = groups
+        .into_iter()
+        .map(|(call_site, (count, total_time_ms))| CallSiteMetrics { call_site, count, total_time_ms })
+        .collect();
+    sites.sort_by(|a, b| b.total_time_ms.total_cmp(&a.total_time_ms));
+    sites.truncate(TOP_CALL_SITES);
+    sites
+}
+
+/// What an OperationEvent records
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    /// A timed operation call
+    #[default]
+    Operation,
+    /// A zero-duration point in the timeline (record_marker); never aggregated
+    Marker,
+}
+
+/// A user marker as listed in the report, chronologically
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Marker {
+    pub name: String,
+    pub timestamp_us: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    #[serde(default)]
+    pub thread_id: u64,
+}
+
+impl Marker {
+    fn from_event(event: &OperationEvent) -> Self {
+        Self {
+            name: event.operation.clone(),
+            timestamp_us: event.start_time_us,
+            metadata: event.metadata.clone(),
+            context: event.context.clone(),
+            thread_id: event.thread_id,
+        }
+    }
+}
+
+fn one() -> u32 {
+    1
+}
+
+fn is_one(n: &u32) -> bool {
+    *n == 1
 }
 
 impl OperationEvent {
@@ -39,6 +191,99 @@ impl OperationEvent {
     pub fn duration_ms(&self) -> f64 {
         self.duration_us() as f64 / 1000.0
     }
+
+    /// Calls represented by this event (1 unless recorded under sampling)
+    pub fn weight(&self) -> usize {
+        self.sample_weight.max(1) as usize
+    }
+
+    /// Duration scaled by the sample weight (estimated time of the calls it stands for)
+    pub fn weighted_ms(&self) -> f64 {
+        self.duration_ms() * self.weight() as f64
+    }
+
+    pub fn is_marker(&self) -> bool {
+        self.kind == EventKind::Marker
+    }
+
+    /// Whether this event passes the report filters (None = no filter)
+    pub fn matches(&self, context_filter: Option<&str>, operations: Option<&[String]>) -> bool {
+        context_filter.is_none_or(|ctx| self.context.as_deref() == Some(ctx))
+            && operations.is_none_or(|ops| ops.contains(&self.operation))
+    }
+}
+
+/// Call count and timing for one slice of an operation's events
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BreakdownMetrics {
+    pub count: usize,
+    pub total_ms: f64,
+    pub avg_ms: f64,
+}
+
+impl BreakdownMetrics {
+    fn add(&mut self, event: &OperationEvent) {
+        self.count += event.weight();
+        self.total_ms += event.weighted_ms();
+        self.avg_ms = self.total_ms / self.count as f64;
+    }
+
+    fn combine(&mut self, other: &BreakdownMetrics) {
+        self.count += other.count;
+        self.total_ms += other.total_ms;
+        self.avg_ms = if self.count > 0 { self.total_ms / self.count as f64 } else { 0.0 };
+    }
+}
+
+/// Stats for the events whose data_size falls in [min_size, max_size)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SizeBucketMetrics {
+    /// Human-readable range, e.g. "1K-10K" or "100M+"
+    pub label: String,
+    pub min_size: usize,
+    /// Exclusive upper bound, None for the last bucket
+    pub max_size: Option<usize>,
+    #[serde(flatten)]
+    pub stats: BreakdownMetrics,
+}
+
+/// Index of the size bucket holding `data_size`
+pub fn size_bucket_index(data_size: usize) -> usize {
+    SIZE_BUCKET_BOUNDARIES.partition_point(|&bound| bound <= data_size)
+}
+
+/// Index of the duration histogram bucket holding `duration_us`
+pub fn duration_bucket_index(duration_us: u64) -> usize {
+    DURATION_BUCKET_BOUNDS_US.partition_point(|&bound| bound <= duration_us)
+}
+
+/// Empty histogram: (exclusive upper bound in µs, None for the last bucket; count)
+fn empty_duration_histogram() -> Vec<(Option<u64>, usize)> {
+    DURATION_BUCKET_BOUNDS_US
+        .iter()
+        .map(|&bound| Some(bound))
+        .chain(std::iter::once(None))
+        .map(|bound| (bound, 0))
+        .collect()
+}
+
+/// Short decimal size: 1000 -> "1K", 10_000_000 -> "10M"
+fn format_size(size: usize) -> String {
+    match size {
+        s if s >= 1_000_000 && s % 1_000_000 == 0 => format!("{}M", s / 1_000_000),
+        s if s >= 1_000 && s % 1_000 == 0 => format!("{}K", s / 1_000),
+        s => s.to_string(),
+    }
+}
+
+fn size_bucket_metrics(index: usize, stats: BreakdownMetrics) -> SizeBucketMetrics {
+    let min_size = if index == 0 { 0 } else { SIZE_BUCKET_BOUNDARIES[index - 1] };
+    let max_size = SIZE_BUCKET_BOUNDARIES.get(index).copied();
+    let label = match max_size {
+        Some(max) => format!("{}-{}", format_size(min_size), format_size(max)),
+        None => format!("{}+", format_size(min_size)),
+    };
+    SizeBucketMetrics { label, min_size, max_size, stats }
 }
 
 /// Aggregated metrics for a specific operation
@@ -61,20 +306,162 @@ pub struct OperationMetrics {
     
     /// Maximum time observed (milliseconds)
     pub max_time_ms: f64,
+
+    /// Latency percentiles in milliseconds, keyed "p50", "p90", ... (see REPORTED_PERCENTILES)
+    #[serde(flatten)]
+    pub percentiles: BTreeMap<String, f64>,
+
+    /// Population standard deviation of call time (milliseconds)
+    #[serde(default)]
+    pub stddev_ms: f64,
     
     /// Most common backend used
     pub primary_backend: String,
+
+    /// Per-backend split of the calls (backend -> stats)
+    #[serde(default)]
+    pub backends: BTreeMap<String, BreakdownMetrics>,
+
+    /// Split by data_size bucket (see SIZE_BUCKET_BOUNDARIES), smallest first, empty buckets omitted
+    #[serde(default)]
+    pub size_buckets: Vec<SizeBucketMetrics>,
+
+    /// Call count per log-scaled duration bucket, as (upper bound µs, count)
+    /// pairs in DURATION_BUCKET_BOUNDS_US order; the last bound is null (1s+)
+    #[serde(default)]
+    pub duration_histogram: Vec<(Option<u64>, usize)>,
     
     /// Percentage of total execution time
     pub percent_total: f64,
+
+    /// Sum of data_size over all calls (elements; multiply-adds for matmul)
+    #[serde(default)]
+    pub total_elements: u64,
+
+    /// Mean data_size per call
+    #[serde(default)]
+    pub avg_elements_per_call: f64,
+
+    /// Elements processed per second of operation time
+    #[serde(default)]
+    pub elements_per_sec: f64,
+
+    /// Memory throughput, for operations with a known bytes-per-element cost
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gb_per_s: Option<f64>,
+
+    /// Compute throughput, for matmul operations (data_size = m*k*n)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gflops: Option<f64>,
+
+    /// Largest arena allocation of a single call (bytes)
+    #[serde(default)]
+    pub max_arena_bytes: u64,
+
+    /// Mean arena bytes allocated per call
+    #[serde(default)]
+    pub avg_arena_bytes: f64,
+
+    /// Largest heap fallback of a single call (bytes)
+    #[serde(default)]
+    pub max_heap_bytes: u64,
+
+    /// Mean heap fallback bytes per call
+    #[serde(default)]
+    pub avg_heap_bytes: f64,
+
+    /// Warmup calls left out of every other field (see Profiler::set_warmup_skip)
+    #[serde(default)]
+    pub warmup_count: usize,
+
+    /// Time spent in the warmup calls (milliseconds)
+    #[serde(default)]
+    pub warmup_total_ms: f64,
+
+    /// Call time spent holding the GIL (milliseconds, GIL-tracked calls only)
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub gil_held_ms: f64,
+
+    /// Call time spent with the GIL released (milliseconds)
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub gil_released_ms: f64,
+
+    /// gil_released_ms / (gil_held_ms + gil_released_ms); None when no call
+    /// was GIL-tracked. Near 0 means the op still serializes Python threads.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gil_released_fraction: Option<f64>,
+
+    /// Busiest Python call sites of the operation (tracked calls only, see
+    /// set_call_site), at most TOP_CALL_SITES
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub call_sites: Vec<CallSiteMetrics>,
+
+    /// Counts and totals were scaled up from sampled events
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub estimated: bool,
+}
+
+/// Report key for a percentile: 50.0 -> "p50", 99.9 -> "p99.9"
+pub fn percentile_key(p: f64) -> String {
+    format!("p{}", p)
+}
+
+/// Percentile of ascending-sorted samples, interpolating linearly between the
+/// two nearest ranks (rank = p/100 * (n - 1), as numpy's default)
+///
+/// A single sample is every percentile; an empty slice yields 0.
+pub fn percentile(sorted: &[f64], p: f64) -> f64 {
+    match sorted.len() {
+        0 => 0.0,
+        1 => sorted[0],
+        n => {
+            let rank = (p / 100.0).clamp(0.0, 1.0) * (n - 1) as f64;
+            let lower = rank.floor() as usize;
+            let upper = rank.ceil() as usize;
+            sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+        }
+    }
+}
+
+/// Bytes moved per element (reads + writes) for the f32 kernels
+///
+/// None where the traffic depends on more than the element count.
+pub fn bytes_per_element(operation: &str) -> Option<f64> {
+    match operation {
+        "add" | "sub" | "mul" | "div" => Some(12.0),
+        "sum" | "mean" | "frobenius_norm" | "row_norms" => Some(4.0),
+        "dot_product" => Some(8.0),
+        "all" | "any" => Some(1.0),
+        "triu" | "tril" => Some(8.0),
+        "eye" | "diag" => Some(4.0),
+        "cast_f16_f32" | "cast_f32_f16" => Some(6.0),
+        _ => None,
+    }
+}
+
+/// Whether data_size counts multiply-adds (m*k*n) rather than elements
+pub fn is_matmul_operation(operation: &str) -> bool {
+    operation.starts_with("matmul")
 }
 
 impl OperationMetrics {
     /// Create metrics from a list of events for a single operation
+    ///
+    /// Warmup events only feed warmup_count and warmup_total_ms.
     pub fn from_events(operation: &str, events: &[OperationEvent], total_time_ms: f64) -> Self {
-        let count = events.len();
+        if events.iter().any(|e| e.warmup) {
+            let (warmup, measured): (Vec<OperationEvent>, Vec<OperationEvent>) =
+                events.iter().cloned().partition(|e| e.warmup);
+            let mut metrics = Self::from_events(operation, &measured, total_time_ms);
+            metrics.warmup_count = warmup.iter().map(OperationEvent::weight).sum();
+            metrics.warmup_total_ms = warmup.iter().map(OperationEvent::weighted_ms).sum();
+            return metrics;
+        }
+
+        // Sampled events stand for `weight` calls each
+        let count: usize = events.iter().map(OperationEvent::weight).sum();
         
-        if count == 0 {
+        if events.is_empty() {
             return Self {
                 operation: operation.to_string(),
                 count: 0,
@@ -82,26 +469,70 @@ impl OperationMetrics {
                 avg_time_ms: 0.0,
                 min_time_ms: 0.0,
                 max_time_ms: 0.0,
+                percentiles: BTreeMap::new(),
+                stddev_ms: 0.0,
                 primary_backend: "unknown".to_string(),
+                backends: BTreeMap::new(),
+                size_buckets: Vec::new(),
+                duration_histogram: Vec::new(),
                 percent_total: 0.0,
+                total_elements: 0,
+                avg_elements_per_call: 0.0,
+                elements_per_sec: 0.0,
+                gb_per_s: None,
+                gflops: None,
+                max_arena_bytes: 0,
+                avg_arena_bytes: 0.0,
+                max_heap_bytes: 0,
+                avg_heap_bytes: 0.0,
+                warmup_count: 0,
+                warmup_total_ms: 0.0,
+                gil_held_ms: 0.0,
+                gil_released_ms: 0.0,
+                gil_released_fraction: None,
+                call_sites: Vec::new(),
+                estimated: false,
             };
         }
         
-        let durations_ms: Vec<f64> = events.iter().map(|e| e.duration_ms()).collect();
+        let mut durations_ms: Vec<f64> = events.iter().map(|e| e.duration_ms()).collect();
+        durations_ms.sort_by(f64::total_cmp);
         
-        let total = durations_ms.iter().sum::<f64>();
+        let total = events.iter().map(OperationEvent::weighted_ms).sum::<f64>();
         let avg = total / count as f64;
-        let min = durations_ms.iter().cloned().fold(f64::INFINITY, f64::min);
-        let max = durations_ms.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        let min = durations_ms[0];
+        let max = durations_ms[durations_ms.len() - 1];
+        let variance = events.iter()
+            .map(|e| e.weight() as f64 * (e.duration_ms() - avg).powi(2))
+            .sum::<f64>() / count as f64;
+        let percentiles = REPORTED_PERCENTILES
+            .iter()
+            .map(|&p| (percentile_key(p), percentile(&durations_ms, p)))
+            .collect();
         
-        // Find most common backend
-        let mut backend_counts = std::collections::HashMap::new();
+        // Per-backend split; the most common backend is the primary one
+        let mut backends: BTreeMap<String, BreakdownMetrics> = BTreeMap::new();
+        for event in events {
+            backends.entry(event.backend.clone()).or_default().add(event);
+        }
+        let mut buckets = vec![BreakdownMetrics::default(); SIZE_BUCKET_BOUNDARIES.len() + 1];
         for event in events {
-            *backend_counts.entry(&event.backend).or_insert(0) += 1;
+            buckets[size_bucket_index(event.data_size)].add(event);
         }
-        let primary_backend = backend_counts
+        let size_buckets = buckets
             .into_iter()
-            .max_by_key(|(_, count)| *count)
+            .enumerate()
+            .filter(|(_, stats)| stats.count > 0)
+            .map(|(index, stats)| size_bucket_metrics(index, stats))
+            .collect();
+        let mut duration_histogram = empty_duration_histogram();
+        for event in events {
+            duration_histogram[duration_bucket_index(event.duration_us())].1 += event.weight();
+        }
+
+        let primary_backend = backends
+            .iter()
+            .max_by_key(|(_, stats)| stats.count)
             .map(|(backend, _)| backend.clone())
             .unwrap_or_else(|| "unknown".to_string());
         
@@ -110,6 +541,29 @@ impl OperationMetrics {
         } else {
             0.0
         };
+
+        let total_elements: u64 = events.iter().map(|e| (e.data_size * e.weight()) as u64).sum();
+        let (elements_per_sec, gb_per_s, gflops) = throughput(operation, total_elements, total);
+        let per_call = |bytes: fn(&OperationEvent) -> u64| {
+            let max = events.iter().map(bytes).max().unwrap_or(0);
+            let sum: u64 = events.iter().map(|e| bytes(e) * e.weight() as u64).sum();
+            (max, sum as f64 / count as f64)
+        };
+        let (max_arena_bytes, avg_arena_bytes) = per_call(|e| e.arena_bytes_used);
+        let (max_heap_bytes, avg_heap_bytes) = per_call(|e| e.heap_bytes_allocated);
+        let weighted_sum_ms = |us: fn(&OperationEvent) -> u64| {
+            events.iter().map(|e| (us(e) * e.weight() as u64) as f64 / 1000.0).sum::<f64>()
+        };
+        let gil_held_ms = weighted_sum_ms(|e| e.gil_held_us);
+        let gil_released_ms = weighted_sum_ms(|e| e.gil_released_us);
+        let mut call_sites: BTreeMap<CallSite, (usize, f64)> = BTreeMap::new();
+        for event in events {
+            if let Some(call_site) = &event.call_site {
+                let site = call_sites.entry(call_site.clone()).or_default();
+                site.0 += event.weight();
+                site.1 += event.weighted_ms();
+            }
+        }
         
         Self {
             operation: operation.to_string(),
@@ -118,12 +572,262 @@ impl OperationMetrics {
             avg_time_ms: avg,
             min_time_ms: min,
             max_time_ms: max,
+            percentiles,
+            stddev_ms: variance.sqrt(),
             primary_backend,
+            backends,
+            size_buckets,
+            duration_histogram,
             percent_total: percent,
+            total_elements,
+            avg_elements_per_call: total_elements as f64 / count as f64,
+            elements_per_sec,
+            gb_per_s,
+            gflops,
+            max_arena_bytes,
+            avg_arena_bytes,
+            max_heap_bytes,
+            avg_heap_bytes,
+            warmup_count: 0,
+            warmup_total_ms: 0.0,
+            gil_held_ms,
+            gil_released_ms,
+            gil_released_fraction: gil_released_fraction(gil_held_ms, gil_released_ms),
+            call_sites: rank_call_sites(call_sites),
+            estimated: events.iter().any(|e| e.weight() > 1),
+        }
+    }
+
+    /// Combine one operation's metrics from several reports
+    ///
+    /// Counts, totals, min/max, stddev and the backend/size splits combine
+    /// exactly; percentiles are count-weighted means of the inputs' (exact
+    /// values need the raw events).
+    pub fn merge(operation: &str, parts: &[&OperationMetrics], total_time_ms: f64) -> Self {
+        let count: usize = parts.iter().map(|m| m.count).sum();
+        let warmup_count = parts.iter().map(|m| m.warmup_count).sum();
+        let warmup_total_ms = parts.iter().map(|m| m.warmup_total_ms).sum();
+        if count == 0 {
+            return Self { warmup_count, warmup_total_ms, ..Self::from_events(operation, &[], total_time_ms) };
+        }
+        let weight = |m: &OperationMetrics| m.count as f64 / count as f64;
+
+        let total: f64 = parts.iter().map(|m| m.total_time_ms).sum();
+        let avg = total / count as f64;
+        let counted = || parts.iter().filter(|m| m.count > 0);
+        let min = counted().map(|m| m.min_time_ms).fold(f64::INFINITY, f64::min);
+        let max = counted().map(|m| m.max_time_ms).fold(f64::NEG_INFINITY, f64::max);
+
+        // Pooled variance: E[x^2] - mean^2, with E[x^2] = stddev^2 + avg^2 per part
+        let mean_sq: f64 = parts.iter()
+            .map(|m| weight(m) * (m.stddev_ms.powi(2) + m.avg_time_ms.powi(2)))
+            .sum();
+        let stddev = (mean_sq - avg.powi(2)).max(0.0).sqrt();
+
+        let mut percentiles: BTreeMap<String, f64> = BTreeMap::new();
+        for m in parts {
+            for (key, value) in &m.percentiles {
+                *percentiles.entry(key.clone()).or_default() += weight(m) * value;
+            }
+        }
+
+        let mut backends: BTreeMap<String, BreakdownMetrics> = BTreeMap::new();
+        for m in parts {
+            for (backend, stats) in &m.backends {
+                backends.entry(backend.clone()).or_default().combine(stats);
+            }
+        }
+        let primary_backend = backends
+            .iter()
+            .max_by_key(|(_, stats)| stats.count)
+            .map(|(backend, _)| backend.clone())
+            // Inputs without a backend split: take the busiest input's primary
+            .or_else(|| parts.iter().max_by_key(|m| m.count).map(|m| m.primary_backend.clone()))
+            .unwrap_or_else(|| "unknown".to_string());
+
+        let mut buckets: BTreeMap<usize, SizeBucketMetrics> = BTreeMap::new();
+        for m in parts {
+            for bucket in &m.size_buckets {
+                buckets.entry(bucket.min_size)
+                    .or_insert_with(|| SizeBucketMetrics { stats: BreakdownMetrics::default(), ..bucket.clone() })
+                    .stats
+                    .combine(&bucket.stats);
+            }
+        }
+
+        let mut duration_histogram = empty_duration_histogram();
+        for m in parts {
+            for &(bound, count) in &m.duration_histogram {
+                if let Some(bucket) = duration_histogram.iter_mut().find(|(b, _)| *b == bound) {
+                    bucket.1 += count;
+                }
+            }
+        }
+
+        let total_elements: u64 = parts.iter().map(|m| m.total_elements).sum();
+        let (elements_per_sec, gb_per_s, gflops) = throughput(operation, total_elements, total);
+        let avg_of = |avg: fn(&OperationMetrics) -> f64| parts.iter().map(|m| weight(m) * avg(m)).sum::<f64>();
+        let gil_held_ms: f64 = parts.iter().map(|m| m.gil_held_ms).sum();
+        let gil_released_ms: f64 = parts.iter().map(|m| m.gil_released_ms).sum();
+        // Re-ranked from the inputs' top lists, so a site outside all of them is missed
+        let mut call_sites: BTreeMap<CallSite, (usize, f64)> = BTreeMap::new();
+        for site in parts.iter().flat_map(|m| &m.call_sites) {
+            let merged = call_sites.entry(site.call_site.clone()).or_default();
+            merged.0 += site.count;
+            merged.1 += site.total_time_ms;
+        }
+
+        Self {
+            operation: operation.to_string(),
+            count,
+            total_time_ms: total,
+            avg_time_ms: avg,
+            min_time_ms: min,
+            max_time_ms: max,
+            percentiles,
+            stddev_ms: stddev,
+            primary_backend,
+            backends,
+            size_buckets: buckets.into_values().collect(),
+            duration_histogram,
+            percent_total: if total_time_ms > 0.0 { total / total_time_ms * 100.0 } else { 0.0 },
+            total_elements,
+            avg_elements_per_call: total_elements as f64 / count as f64,
+            elements_per_sec,
+            gb_per_s,
+            gflops,
+            max_arena_bytes: parts.iter().map(|m| m.max_arena_bytes).max().unwrap_or(0),
+            avg_arena_bytes: avg_of(|m| m.avg_arena_bytes),
+            max_heap_bytes: parts.iter().map(|m| m.max_heap_bytes).max().unwrap_or(0),
+            avg_heap_bytes: avg_of(|m| m.avg_heap_bytes),
+            warmup_count,
+            warmup_total_ms,
+            gil_held_ms,
+            gil_released_ms,
+            gil_released_fraction: gil_released_fraction(gil_held_ms, gil_released_ms),
+            call_sites: rank_call_sites(call_sites),
+            estimated: parts.iter().any(|m| m.estimated),
         }
     }
 }
 
+/// Released share of the GIL-tracked time (None if nothing was tracked)
+fn gil_released_fraction(held_ms: f64, released_ms: f64) -> Option<f64> {
+    let tracked = held_ms + released_ms;
+    (tracked > 0.0).then(|| released_ms / tracked)
+}
+
+/// (elements/sec, GB/s, GFLOPS) for `total_elements` processed in `total_ms`
+fn throughput(operation: &str, total_elements: u64, total_ms: f64) -> (f64, Option<f64>, Option<f64>) {
+    let total_secs = total_ms / 1000.0;
+    let per_sec = |amount: f64| if total_secs > 0.0 { amount / total_secs } else { 0.0 };
+
+    let gflops = is_matmul_operation(operation)
+        .then(|| per_sec(2.0 * total_elements as f64) / 1e9);
+    let gb_per_s = bytes_per_element(operation)
+        .map(|bytes| per_sec(bytes * total_elements as f64) / 1e9);
+    (per_sec(total_elements as f64), gb_per_s, gflops)
+}
+
+/// Time spent by one recording thread
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ThreadMetrics {
+    /// Thread name, if the thread has one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thread_name: Option<String>,
+
+    /// Number of calls recorded on this thread (scaled up under sampling)
+    pub event_count: usize,
+
+    /// Total time of those events (milliseconds)
+    pub total_time_ms: f64,
+}
+
+/// Key in ProfileReport::contexts for events recorded outside any context
+pub const UNCATEGORIZED_CONTEXT: &str = "uncategorized";
+
+/// Operations listed per context in ProfileReport::contexts
+pub const CONTEXT_TOP_OPERATIONS: usize = 3;
+
+/// Time spent under one profiling context
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ContextMetrics {
+    /// Number of calls recorded under the context (scaled up under sampling)
+    pub event_count: usize,
+
+    /// Total time of those calls (milliseconds)
+    pub total_time_ms: f64,
+
+    /// Busiest operations by total time, at most CONTEXT_TOP_OPERATIONS
+    #[serde(default)]
+    pub top_operations: Vec<ContextOperation>,
+}
+
+/// One operation's share of a context
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContextOperation {
+    pub operation: String,
+    pub count: usize,
+    pub total_time_ms: f64,
+}
+
+/// Sum the reports' context summaries (top operations are re-ranked from the
+/// inputs' top lists, so an operation outside every input's top few is missed)
+fn merge_contexts(reports: &[ProfileReport]) -> BTreeMap<String, ContextMetrics> {
+    let mut groups = ContextGroups::new();
+    for (context, part) in reports.iter().flat_map(|r| &r.contexts) {
+        let (metrics, operations) = groups.entry(context.as_str()).or_default();
+        metrics.event_count += part.event_count;
+        metrics.total_time_ms += part.total_time_ms;
+        for top in &part.top_operations {
+            let operation = operations.entry(top.operation.as_str()).or_default();
+            operation.0 += top.count;
+            operation.1 += top.total_time_ms;
+        }
+    }
+    rank_contexts(groups)
+}
+
+/// Context -> (totals, operation -> (count, total ms)) while summarizing
+type ContextGroups<'a> = BTreeMap<&'a str, (ContextMetrics, BTreeMap<&'a str, (usize, f64)>)>;
+
+/// Finish the summary: keep the CONTEXT_TOP_OPERATIONS busiest operations per context
+fn rank_contexts(groups: ContextGroups<'_>) -> BTreeMap<String, ContextMetrics> {
+    groups
+        .into_iter()
+        .map(|(context, (mut metrics, operations))| {
+            let mut top: Vec<ContextOperation> = operations
+                .into_iter()
+                .map(|(operation, (count, total_time_ms))| ContextOperation {
+                    operation: operation.to_string(),
+                    count,
+                    total_time_ms,
+                })
+                .collect();
+            top.sort_by(|a, b| b.total_time_ms.total_cmp(&a.total_time_ms));
+            top.truncate(CONTEXT_TOP_OPERATIONS);
+            metrics.top_operations = top;
+            (context.to_string(), metrics)
+        })
+        .collect()
+}
+
+/// Per-context summary over `events` (markers already removed; warmup calls
+/// left out like in the other totals)
+fn context_summary(events: &[&OperationEvent]) -> BTreeMap<String, ContextMetrics> {
+    let mut groups = ContextGroups::new();
+    for event in events.iter().filter(|e| !e.warmup) {
+        let context = event.context.as_deref().unwrap_or(UNCATEGORIZED_CONTEXT);
+        let (metrics, operations) = groups.entry(context).or_default();
+        metrics.event_count += event.weight();
+        metrics.total_time_ms += event.weighted_ms();
+        let operation = operations.entry(event.operation.as_str()).or_default();
+        operation.0 += event.weight();
+        operation.1 += event.weighted_ms();
+    }
+    rank_contexts(groups)
+}
+
 /// Complete profiling report for a session
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProfileReport {
@@ -135,9 +839,31 @@ pub struct ProfileReport {
     
     /// Total execution time across all operations (milliseconds)
     pub total_time_ms: f64,
+
+    /// Total time of every recorded event, before context/operation filters
+    #[serde(default)]
+    pub session_total_time_ms: f64,
     
     /// Number of operations profiled
     pub operation_count: usize,
+
+    /// Per-thread breakdown, keyed by thread id
+    #[serde(default)]
+    pub threads: BTreeMap<u64, ThreadMetrics>,
+
+    /// Whole-session split by context (events without one under
+    /// UNCATEGORIZED_CONTEXT); ignores the report's filters, like
+    /// session_total_time_ms
+    #[serde(default)]
+    pub contexts: BTreeMap<String, ContextMetrics>,
+
+    /// User markers in the (context-filtered) session, oldest first
+    #[serde(default)]
+    pub markers: Vec<Marker>,
+
+    /// Raw events (only included when explicitly requested)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<OperationEvent>,
 }
 
 /// Metadata about the profiling session
@@ -154,6 +880,43 @@ pub struct SessionMetadata {
     
     /// Optional context filter (if report is for specific context)
     pub context: Option<String>,
+
+    /// Operation allowlist the report was restricted to, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operations: Option<Vec<String>>,
+
+    /// Events evicted by the profiler's capacity limit; non-zero means the
+    /// report covers only the most recent window
+    #[serde(default)]
+    pub dropped_events: u64,
+
+    /// Profiler sample rate when the report was built (1 = every call recorded)
+    #[serde(default = "one", skip_serializing_if = "is_one")]
+    pub sample_rate: u32,
+
+    /// Number of sessions combined into this report (0 for a single session)
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub merged_sessions: usize,
+
+    /// Distinct versions of the merged sessions, when they disagree
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub versions: Vec<String>,
+
+    /// Events read back from the profiler's spill file (see Profiler::enable_spill)
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub spilled_events: u64,
+
+    /// Why spilling stopped, if it failed; later events stayed in memory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spill_error: Option<String>,
+
+    /// Name given to Profiler::start_session, for a session report
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_name: Option<String>,
+}
+
+fn is_zero<T: Default + PartialEq>(n: &T) -> bool {
+    *n == T::default()
 }
 
 impl ProfileReport {
@@ -167,47 +930,95 @@ impl ProfileReport {
                 start_timestamp: now.to_rfc3339(),
                 version: env!("CARGO_PKG_VERSION").to_string(),
                 context,
+                operations: None,
+                dropped_events: 0,
+                sample_rate: 1,
+                merged_sessions: 0,
+                versions: Vec::new(),
+                spilled_events: 0,
+                spill_error: None,
+                session_name: None,
             },
             operations: std::collections::HashMap::new(),
             total_time_ms: 0.0,
+            session_total_time_ms: 0.0,
             operation_count: 0,
+            threads: BTreeMap::new(),
+            contexts: BTreeMap::new(),
+            markers: Vec::new(),
+            events: Vec::new(),
         }
     }
     
     /// Build a report from a list of events
-    pub fn from_events(events: &[OperationEvent], context_filter: Option<&str>) -> Self {
-        // Filter events by context if specified
-        let filtered_events: Vec<&OperationEvent> = if let Some(ctx) = context_filter {
-            events.iter()
-                .filter(|e| e.context.as_deref() == Some(ctx))
-                .collect()
-        } else {
-            events.iter().collect()
-        };
+    ///
+    /// `operations` restricts the report to the named operations (unknown
+    /// names match nothing); percent_total is relative to the filtered set and
+    /// session_total_time_ms keeps the unfiltered total. Markers only go to
+    /// `markers` (filtered by context alone), never into the metrics. Warmup
+    /// events count towards their operation's warmup fields only, so the
+    /// report and thread totals leave them out too.
+    pub fn from_events(
+        events: &[OperationEvent],
+        context_filter: Option<&str>,
+        operations: Option<&[String]>,
+    ) -> Self {
+        let (marker_events, events): (Vec<&OperationEvent>, Vec<&OperationEvent>) =
+            events.iter().partition(|e| e.is_marker());
+        let mut markers: Vec<Marker> = marker_events
+            .into_iter()
+            .filter(|e| e.matches(context_filter, None))
+            .map(Marker::from_event)
+            .collect();
+        markers.sort_by_key(|m| m.timestamp_us);
+
+        let measured_ms = |e: &&OperationEvent| if e.warmup { 0.0 } else { e.weighted_ms() };
+        let session_total_time_ms: f64 = events.iter().map(measured_ms).sum();
+        let contexts = context_summary(&events);
+
+        // Filter events by context and operation if specified
+        let filtered_events: Vec<&OperationEvent> = events.into_iter()
+            .filter(|e| e.matches(context_filter, operations))
+            .collect();
         
         if filtered_events.is_empty() {
-            return Self::new(
+            let mut report = Self::new(
                 uuid::Uuid::new_v4().to_string(),
                 context_filter.map(String::from),
             );
+            report.metadata.operations = operations.map(<[String]>::to_vec);
+            report.session_total_time_ms = session_total_time_ms;
+            report.contexts = contexts;
+            report.markers = markers;
+            return report;
         }
         
         // Calculate total time
-        let total_time_ms: f64 = filtered_events.iter().map(|e| e.duration_ms()).sum();
+        let total_time_ms: f64 = filtered_events.iter().map(measured_ms).sum();
         
         // Group events by operation
         let mut operation_groups: std::collections::HashMap<String, Vec<OperationEvent>> =
             std::collections::HashMap::new();
+        let mut threads: BTreeMap<u64, ThreadMetrics> = BTreeMap::new();
         
         for event in filtered_events {
             operation_groups
                 .entry(event.operation.clone())
-                .or_insert_with(Vec::new)
-                .push((*event).clone());
+                .or_default()
+                .push(event.clone());
+            if event.warmup {
+                continue;
+            }
+            let thread = threads.entry(event.thread_id).or_default();
+            if thread.thread_name.is_none() {
+                thread.thread_name = event.thread_name.clone();
+            }
+            thread.event_count += event.weight();
+            thread.total_time_ms += event.weighted_ms();
         }
         
         // Create metrics for each operation
-        let operations: std::collections::HashMap<String, OperationMetrics> = operation_groups
+        let operation_metrics: std::collections::HashMap<String, OperationMetrics> = operation_groups
             .iter()
             .map(|(op_name, events)| {
                 let metrics = OperationMetrics::from_events(op_name, events, total_time_ms);
@@ -215,7 +1026,7 @@ impl ProfileReport {
             })
             .collect();
         
-        let operation_count = operations.len();
+        let operation_count = operation_metrics.len();
         
         Self {
             metadata: SessionMetadata {
@@ -223,19 +1034,243 @@ impl ProfileReport {
                 start_timestamp: chrono::Utc::now().to_rfc3339(),
                 version: env!("CARGO_PKG_VERSION").to_string(),
                 context: context_filter.map(String::from),
+                operations: operations.map(<[String]>::to_vec),
+                dropped_events: 0,
+                sample_rate: 1,
+                merged_sessions: 0,
+                versions: Vec::new(),
+                spilled_events: 0,
+                spill_error: None,
+                session_name: None,
             },
-            operations,
+            operations: operation_metrics,
             total_time_ms,
+            session_total_time_ms,
             operation_count,
+            threads,
+            contexts,
+            markers,
+            events: Vec::new(),
         }
     }
     
+    /// Combine reports from several sessions (e.g. one per worker process)
+    ///
+    /// Per-operation metrics are merged with OperationMetrics::merge() and
+    /// percent_total is recomputed over the combined time. Thread ids are only
+    /// unique within a process, so the thread breakdown is summed by id.
+    pub fn merge(reports: &[ProfileReport]) -> ProfileReport {
+        let contexts: Vec<&Option<String>> = reports.iter().map(|r| &r.metadata.context).collect();
+        let context = match contexts.first() {
+            Some(first) if contexts.iter().all(|c| c == first) => (*first).clone(),
+            _ => None,
+        };
+        let mut merged = Self::new(uuid::Uuid::new_v4().to_string(), context);
+
+        let total_time_ms: f64 = reports.iter().map(|r| r.total_time_ms).sum();
+        let mut by_operation: BTreeMap<&str, Vec<&OperationMetrics>> = BTreeMap::new();
+        for report in reports {
+            for (name, metrics) in &report.operations {
+                by_operation.entry(name.as_str()).or_default().push(metrics);
+            }
+        }
+        merged.operations = by_operation
+            .into_iter()
+            .map(|(name, parts)| (name.to_string(), OperationMetrics::merge(name, &parts, total_time_ms)))
+            .collect();
+        merged.operation_count = merged.operations.len();
+        merged.total_time_ms = total_time_ms;
+        merged.session_total_time_ms = reports.iter().map(|r| r.session_total_time_ms).sum();
+
+        for report in reports {
+            for (&id, thread) in &report.threads {
+                let entry = merged.threads.entry(id).or_default();
+                if entry.thread_name.is_none() {
+                    entry.thread_name = thread.thread_name.clone();
+                }
+                entry.event_count += thread.event_count;
+                entry.total_time_ms += thread.total_time_ms;
+            }
+            merged.markers.extend(report.markers.iter().cloned());
+            merged.events.extend(report.events.iter().cloned());
+        }
+        merged.contexts = merge_contexts(reports);
+        merged.markers.sort_by_key(|m| m.timestamp_us);
+
+        let metadata = &mut merged.metadata;
+        if let Some(earliest) = reports.iter().map(|r| &r.metadata.start_timestamp).min() {
+            metadata.start_timestamp = earliest.clone();
+        }
+        metadata.dropped_events = reports.iter().map(|r| r.metadata.dropped_events).sum();
+        metadata.spilled_events = reports.iter().map(|r| r.metadata.spilled_events).sum();
+        metadata.spill_error = reports.iter().find_map(|r| r.metadata.spill_error.clone());
+        metadata.merged_sessions = reports.iter().map(|r| r.metadata.merged_sessions.max(1)).sum();
+
+        let mut versions: Vec<String> = Vec::new();
+        for report in reports {
+            let inputs = if report.metadata.versions.is_empty() {
+                std::slice::from_ref(&report.metadata.version)
+            } else {
+                &report.metadata.versions[..]
+            };
+            for version in inputs {
+                if !versions.contains(version) {
+                    versions.push(version.clone());
+                }
+            }
+        }
+        match versions.len() {
+            0 => {}
+            1 => metadata.version = versions.remove(0),
+            _ => {
+                metadata.version = "mixed".to_string();
+                metadata.versions = versions;
+            }
+        }
+        merged
+    }
+    
     /// Convert report to JSON string
     pub fn to_json(&self) -> Result<String, serde_json::Error> {
         serde_json::to_string_pretty(self)
     }
 }
 
+/// Column a text report is sorted by (see format_text_report)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportSort {
+    Total,
+    Calls,
+    Avg,
+    P99,
+    Percent,
+    Name,
+}
+
+/// Accepted ReportSort names
+pub const VALID_SORT_NAMES: &[&str] = &["total", "calls", "avg", "p99", "percent", "name"];
+
+impl ReportSort {
+    /// Parse a sort column name (case-insensitive, see VALID_SORT_NAMES)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "total" => Some(ReportSort::Total),
+            "calls" | "count" => Some(ReportSort::Calls),
+            "avg" => Some(ReportSort::Avg),
+            "p99" => Some(ReportSort::P99),
+            "percent" => Some(ReportSort::Percent),
+            "name" | "operation" => Some(ReportSort::Name),
+            _ => None,
+        }
+    }
+}
+
+/// Widest operation name shown before truncating with an ellipsis
+const TEXT_REPORT_NAME_WIDTH: usize = 32;
+
+/// Aligned plain-text table of a report's operations, for terminals
+///
+/// Rows are sorted by `sort_by` (largest first; names alphabetically) and cut
+/// to `top_n` (0 = all). The p99 column only appears when the report has it.
+pub fn format_text_report(report: &ProfileReport, sort_by: ReportSort, top_n: usize) -> String {
+    if report.operations.is_empty() {
+        return "No profiled operations yet (is profiling enabled?)".to_string();
+    }
+
+    let p99 = |m: &OperationMetrics| m.percentiles.get("p99").copied();
+    let mut ops: Vec<&OperationMetrics> = report.operations.values().collect();
+    ops.sort_by(|a, b| {
+        let key = |m: &OperationMetrics| match sort_by {
+            ReportSort::Total | ReportSort::Name => m.total_time_ms,
+            ReportSort::Calls => m.count as f64,
+            ReportSort::Avg => m.avg_time_ms,
+            ReportSort::P99 => p99(m).unwrap_or(0.0),
+            ReportSort::Percent => m.percent_total,
+        };
+        match sort_by {
+            ReportSort::Name => a.operation.cmp(&b.operation),
+            _ => key(b).total_cmp(&key(a)).then_with(|| a.operation.cmp(&b.operation)),
+        }
+    });
+    let shown = if top_n == 0 { ops.len() } else { top_n.min(ops.len()) };
+    let show_p99 = ops.iter().any(|m| p99(m).is_some());
+
+    let mut header = vec!["Operation", "Calls", "Total (ms)", "Avg (ms)"];
+    if show_p99 {
+        header.push("P99 (ms)");
+    }
+    header.extend(["% Total", "Backend"]);
+
+    let rows: Vec<Vec<String>> = ops[..shown]
+        .iter()
+        .map(|m| {
+            let mut row = vec![
+                truncate_name(&m.operation),
+                m.count.to_string(),
+                format!("{:.3}", m.total_time_ms),
+                format!("{:.3}", m.avg_time_ms),
+            ];
+            if show_p99 {
+                row.push(p99(m).map_or_else(|| "-".to_string(), |v| format!("{:.3}", v)));
+            }
+            row.push(format!("{:.1}", m.percent_total));
+            row.push(m.primary_backend.clone());
+            row
+        })
+        .collect();
+
+    let widths: Vec<usize> = (0..header.len())
+        .map(|col| {
+            rows.iter()
+                .map(|row| row[col].chars().count())
+                .chain(std::iter::once(header[col].chars().count()))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    // Text columns (first and last) align left, numbers right
+    let last = header.len() - 1;
+    let line = |cells: &[&str]| {
+        cells.iter()
+            .enumerate()
+            .map(|(col, cell)| match col {
+                0 => format!("{:<width$}", cell, width = widths[col]),
+                col if col == last => cell.to_string(),
+                _ => format!("{:>width$}", cell, width = widths[col]),
+            })
+            .collect::<Vec<_>>()
+            .join("  ")
+    };
+
+    let mut out = format!(
+        "Profile summary: {} operations, {:.3} ms total",
+        report.operations.len(),
+        report.total_time_ms
+    );
+    if shown < ops.len() {
+        out.push_str(&format!(" (top {} shown)", shown));
+    }
+    out.push('\n');
+    out.push_str(&line(&header));
+    out.push('\n');
+    out.push_str(&"-".repeat(widths.iter().sum::<usize>() + 2 * last));
+    for row in &rows {
+        out.push('\n');
+        out.push_str(&line(&row.iter().map(String::as_str).collect::<Vec<_>>()));
+    }
+    out
+}
+
+/// Cut `name` to TEXT_REPORT_NAME_WIDTH characters, ending in an ellipsis
+fn truncate_name(name: &str) -> String {
+    if name.chars().count() <= TEXT_REPORT_NAME_WIDTH {
+        return name.to_string();
+    }
+    let mut cut: String = name.chars().take(TEXT_REPORT_NAME_WIDTH - 1).collect();
+    cut.push('…');
+    cut
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -249,6 +1284,21 @@ mod tests {
             start_time_us: 1000,
             end_time_us: 2500,
             context: None,
+            dims: None,
+            thread_id: 0,
+            thread_name: None,
+            sample_weight: 1,
+            seq: 0,
+            arena_bytes_used: 0,
+            heap_bytes_allocated: 0,
+            kind: EventKind::Operation,
+            metadata: None,
+            warmup: false,
+            attributes: Vec::new(),
+            gil_held_us: 0,
+            gil_released_us: 0,
+            session: None,
+            call_site: None,
         };
         
         assert_eq!(event.duration_us(), 1500);
@@ -265,6 +1315,21 @@ mod tests {
                 start_time_us: 0,
                 end_time_us: 1000,  // 1ms
                 context: None,
+                dims: None,
+                thread_id: 0,
+                thread_name: None,
+                sample_weight: 1,
+                seq: 0,
+                arena_bytes_used: 0,
+                heap_bytes_allocated: 0,
+                kind: EventKind::Operation,
+                metadata: None,
+                warmup: false,
+                attributes: Vec::new(),
+                gil_held_us: 0,
+                gil_released_us: 0,
+                session: None,
+                call_site: None,
             },
             OperationEvent {
                 operation: "add".to_string(),
@@ -273,6 +1338,21 @@ mod tests {
                 start_time_us: 0,
                 end_time_us: 2000,  // 2ms
                 context: None,
+                dims: None,
+                thread_id: 0,
+                thread_name: None,
+                sample_weight: 1,
+                seq: 0,
+                arena_bytes_used: 0,
+                heap_bytes_allocated: 0,
+                kind: EventKind::Operation,
+                metadata: None,
+                warmup: false,
+                attributes: Vec::new(),
+                gil_held_us: 0,
+                gil_released_us: 0,
+                session: None,
+                call_site: None,
             },
         ];
         
@@ -284,6 +1364,492 @@ mod tests {
         assert_eq!(metrics.min_time_ms, 1.0);
         assert_eq!(metrics.max_time_ms, 2.0);
         assert_eq!(metrics.primary_backend, "CPU");
+        assert_eq!(metrics.backends.len(), 1);
+        assert_eq!(metrics.backends["CPU"], BreakdownMetrics { count: 2, total_ms: 3.0, avg_ms: 1.5 });
         assert_eq!(metrics.percent_total, 30.0); // 3/10 * 100
+        assert_eq!(metrics.total_elements, 300);
+        assert_eq!(metrics.avg_elements_per_call, 150.0);
+        assert!(metrics.gflops.is_none());
+    }
+
+    fn timed_event(operation: &str, data_size: usize, duration_us: u64) -> OperationEvent {
+        OperationEvent {
+            operation: operation.to_string(),
+            backend: "CPU".to_string(),
+            data_size,
+            start_time_us: 0,
+            end_time_us: duration_us,
+            context: None,
+            dims: None,
+            thread_id: 0,
+            thread_name: None,
+            sample_weight: 1,
+            seq: 0,
+            arena_bytes_used: 0,
+            heap_bytes_allocated: 0,
+            kind: EventKind::Operation,
+            metadata: None,
+            warmup: false,
+            attributes: Vec::new(),
+            gil_held_us: 0,
+            gil_released_us: 0,
+            session: None,
+            call_site: None,
+        }
+    }
+
+    #[test]
+    fn test_throughput_is_total_elements_over_total_time() {
+        // 6M elements in 3ms total
+        let events = vec![
+            timed_event("add", 1_000_000, 1000),
+            timed_event("add", 5_000_000, 2000),
+        ];
+        let metrics = OperationMetrics::from_events("add", &events, 3.0);
+
+        assert_eq!(metrics.total_elements, 6_000_000);
+        assert!((metrics.elements_per_sec - 2e9).abs() < 1.0);
+        // 12 bytes per element for binary elementwise ops
+        assert!((metrics.gb_per_s.unwrap() - 24.0).abs() < 1e-9);
+        assert!(metrics.gflops.is_none());
+    }
+
+    #[test]
+    fn test_backend_breakdown_sums_to_total() {
+        let mut events = vec![
+            timed_event("matmul", 1, 1000),
+            timed_event("matmul", 1, 3000),
+            timed_event("matmul", 1, 500),
+        ];
+        events[2].backend = "GPU".to_string();
+
+        let metrics = OperationMetrics::from_events("matmul", &events, 4.5);
+        assert_eq!(metrics.backends["CPU"], BreakdownMetrics { count: 2, total_ms: 4.0, avg_ms: 2.0 });
+        assert_eq!(metrics.backends["GPU"], BreakdownMetrics { count: 1, total_ms: 0.5, avg_ms: 0.5 });
+        assert_eq!(metrics.primary_backend, "CPU");
+
+        let split_total: f64 = metrics.backends.values().map(|b| b.total_ms).sum();
+        let split_count: usize = metrics.backends.values().map(|b| b.count).sum();
+        assert_eq!(split_total, metrics.total_time_ms);
+        assert_eq!(split_count, metrics.count);
+
+        let json = serde_json::to_value(&metrics).unwrap();
+        assert_eq!(json["backends"]["GPU"]["count"], 1);
+    }
+
+    #[test]
+    fn test_size_bucket_boundaries() {
+        assert_eq!(size_bucket_index(0), 0);
+        assert_eq!(size_bucket_index(9), 0);
+        assert_eq!(size_bucket_index(10), 1);
+        assert_eq!(size_bucket_index(999), 2);
+        assert_eq!(size_bucket_index(1_000), 3);
+        assert_eq!(size_bucket_index(usize::MAX), SIZE_BUCKET_BOUNDARIES.len());
+
+        assert_eq!(size_bucket_metrics(0, BreakdownMetrics::default()).label, "0-10");
+        assert_eq!(size_bucket_metrics(3, BreakdownMetrics::default()).label, "1K-10K");
+        let last = size_bucket_metrics(SIZE_BUCKET_BOUNDARIES.len(), BreakdownMetrics::default());
+        assert_eq!((last.label.as_str(), last.min_size, last.max_size), ("100M+", 100_000_000, None));
+    }
+
+    #[test]
+    fn test_size_buckets_across_four_decades() {
+        let events = vec![
+            timed_event("add", 1_500, 1000),       // 1K-10K
+            timed_event("add", 9_999, 3000),       // 1K-10K
+            timed_event("add", 10_000, 2000),      // 10K-100K
+            timed_event("add", 250_000, 4000),     // 100K-1M
+            timed_event("add", 5_000_000, 8000),   // 1M-10M
+            timed_event("add", 7_000_000, 10000),  // 1M-10M
+        ];
+        let metrics = OperationMetrics::from_events("add", &events, 28.0);
+
+        let summary: Vec<(&str, usize, f64, f64)> = metrics.size_buckets
+            .iter()
+            .map(|b| (b.label.as_str(), b.stats.count, b.stats.total_ms, b.stats.avg_ms))
+            .collect();
+        assert_eq!(summary, vec![
+            ("1K-10K", 2, 4.0, 2.0),
+            ("10K-100K", 1, 2.0, 2.0),
+            ("100K-1M", 1, 4.0, 4.0),
+            ("1M-10M", 2, 18.0, 9.0),
+        ]);
+
+        let json = serde_json::to_value(&metrics).unwrap();
+        let first = &json["size_buckets"][0];
+        assert_eq!((first["min_size"].as_u64(), first["max_size"].as_u64()), (Some(1_000), Some(10_000)));
+        assert_eq!(first["count"], 2);
+    }
+
+    fn session(version: &str, events: &[OperationEvent]) -> ProfileReport {
+        let mut report = ProfileReport::from_events(events, None, None);
+        report.metadata.version = version.to_string();
+        report
+    }
+
+    #[test]
+    fn test_merge_three_reports() {
+        let mut gpu_add = timed_event("add", 100, 4000);
+        gpu_add.backend = "GPU".to_string();
+
+        let a_events = [timed_event("add", 100, 1000), timed_event("add", 100, 3000), timed_event("sum", 10, 500)];
+        let b_events = [timed_event("add", 100_000, 500), gpu_add.clone()];   // add min 0.5 and max 4.0 from here
+        let c_events = [timed_event("sum", 10, 2500), timed_event("matmul", 1000, 2000)];
+        let reports = [
+            session("0.2.0", &a_events),
+            session("0.2.0", &b_events),
+            session("0.3.0", &c_events),
+        ];
+
+        let merged = ProfileReport::merge(&reports);
+        assert_eq!(merged.total_time_ms, 13.5);
+        assert_eq!(merged.operation_count, 3);
+        assert_eq!(merged.metadata.merged_sessions, 3);
+        assert_eq!(merged.metadata.version, "mixed");
+        assert_eq!(merged.metadata.versions, vec!["0.2.0".to_string(), "0.3.0".to_string()]);
+
+        let add = &merged.operations["add"];
+        assert_eq!(add.count, 4);
+        assert_eq!(add.total_time_ms, 8.5);
+        assert_eq!(add.avg_time_ms, 2.125);
+        assert_eq!((add.min_time_ms, add.max_time_ms), (0.5, 4.0));
+        assert!((add.percent_total - 8.5 / 13.5 * 100.0).abs() < 1e-9);
+        assert_eq!(add.backends["CPU"], BreakdownMetrics { count: 3, total_ms: 4.5, avg_ms: 1.5 });
+        assert_eq!(add.backends["GPU"].count, 1);
+        assert_eq!(add.total_elements, 100_300);
+        assert_eq!(add.size_buckets.len(), 2);
+
+        // Pooled stddev matches the stddev of all four durations
+        let all_add = [a_events[0].clone(), a_events[1].clone(), b_events[0].clone(), gpu_add];
+        let direct = OperationMetrics::from_events("add", &all_add, 13.5);
+        assert!((add.stddev_ms - direct.stddev_ms).abs() < 1e-9);
+
+        let sum = &merged.operations["sum"];
+        assert_eq!((sum.count, sum.total_time_ms, sum.min_time_ms, sum.max_time_ms), (2, 3.0, 0.5, 2.5));
+
+        let percent: f64 = merged.operations.values().map(|m| m.percent_total).sum();
+        assert!((percent - 100.0).abs() < 1e-9);
+
+        // Merging merged reports keeps counting sessions
+        let again = ProfileReport::merge(&[merged, session("0.2.0", &a_events)]);
+        assert_eq!(again.metadata.merged_sessions, 4);
+        assert_eq!(again.metadata.versions.len(), 2);
+    }
+
+    #[test]
+    fn test_sampled_events_are_scaled() {
+        let mut events = vec![timed_event("add", 10, 1000), timed_event("add", 10, 3000)];
+        for event in &mut events {
+            event.sample_weight = 100;
+        }
+        let metrics = OperationMetrics::from_events("add", &events, 400.0);
+
+        assert!(metrics.estimated);
+        assert_eq!(metrics.count, 200);
+        assert_eq!(metrics.total_time_ms, 400.0);
+        assert_eq!(metrics.avg_time_ms, 2.0);
+        assert_eq!((metrics.min_time_ms, metrics.max_time_ms), (1.0, 3.0));
+        assert_eq!(metrics.stddev_ms, 1.0);
+        assert_eq!(metrics.total_elements, 2000);
+        assert_eq!(metrics.backends["CPU"].count, 200);
+
+        let exact = OperationMetrics::from_events("add", &[timed_event("add", 10, 1000)], 1.0);
+        assert!(!exact.estimated);
+        assert!(serde_json::to_value(&exact).unwrap().get("estimated").is_none());
+    }
+
+    #[test]
+    fn test_operation_filter() {
+        let events = vec![
+            timed_event("matmul", 1, 3000),
+            timed_event("add", 1, 1000),
+            timed_event("sum", 1, 4000),
+            timed_event("matmul", 1, 1000),
+        ];
+        let allow = vec!["matmul".to_string(), "add".to_string(), "conv2d".to_string()];
+        let report = ProfileReport::from_events(&events, None, Some(&allow));
+
+        let mut names: Vec<&str> = report.operations.keys().map(String::as_str).collect();
+        names.sort();
+        assert_eq!(names, vec!["add", "matmul"]);
+        assert_eq!(report.total_time_ms, 5.0);
+        assert_eq!(report.session_total_time_ms, 9.0);
+        assert_eq!(report.operations["matmul"].percent_total, 80.0);
+        assert_eq!(report.operations["add"].percent_total, 20.0);
+        assert_eq!(report.metadata.operations.as_deref(), Some(&allow[..]));
+
+        let none = ProfileReport::from_events(&events, None, Some(&["conv2d".to_string()]));
+        assert!(none.operations.is_empty());
+        assert_eq!(none.session_total_time_ms, 9.0);
+
+        let all = ProfileReport::from_events(&events, None, None);
+        assert_eq!(all.total_time_ms, all.session_total_time_ms);
+    }
+
+    #[test]
+    fn test_merge_same_version() {
+        let reports = [session("0.2.0", &[timed_event("add", 1, 1000)]), session("0.2.0", &[])];
+        let merged = ProfileReport::merge(&reports);
+        assert_eq!(merged.metadata.version, "0.2.0");
+        assert!(merged.metadata.versions.is_empty());
+        assert_eq!(merged.operations["add"].percent_total, 100.0);
+    }
+
+    #[test]
+    fn test_percentile_interpolation_small_counts() {
+        assert_eq!(percentile(&[], 50.0), 0.0);
+        assert_eq!(percentile(&[7.0], 99.0), 7.0);
+        // Two samples: linear between them
+        assert_eq!(percentile(&[1.0, 3.0], 50.0), 2.0);
+        assert_eq!(percentile(&[1.0, 3.0], 90.0), 2.8);
+        // Endpoints are the min and max
+        assert_eq!(percentile(&[1.0, 2.0, 4.0], 0.0), 1.0);
+        assert_eq!(percentile(&[1.0, 2.0, 4.0], 100.0), 4.0);
+        assert_eq!(percentile(&[1.0, 2.0, 4.0], 75.0), 3.0);
+    }
+
+    #[test]
+    fn test_single_event_percentiles_equal_duration() {
+        let metrics = OperationMetrics::from_events("sum", &[timed_event("sum", 10, 1500)], 1.5);
+        for &p in REPORTED_PERCENTILES {
+            assert_eq!(metrics.percentiles[&percentile_key(p)], 1.5);
+        }
+        assert_eq!(metrics.stddev_ms, 0.0);
+    }
+
+    #[test]
+    fn test_percentiles_of_uniform_distribution() {
+        // Durations 1..=1000 us in shuffled order: rank r holds (r + 1) us
+        let events: Vec<OperationEvent> = (0..1000u64)
+            .map(|i| timed_event("add", 1, (i * 7919) % 1000 + 1))
+            .collect();
+        let metrics = OperationMetrics::from_events("add", &events, 1.0);
+
+        let close = |key: &str, expected_us: f64| {
+            let got = metrics.percentiles[key];
+            assert!((got - expected_us / 1000.0).abs() < 1e-9, "{}: {} != {}", key, got, expected_us);
+        };
+        close("p50", 500.5);   // rank 499.5
+        close("p90", 900.1);   // rank 899.1
+        close("p99", 990.01);  // rank 989.01
+        assert_eq!(metrics.min_time_ms, 0.001);
+        assert_eq!(metrics.max_time_ms, 1.0);
+
+        // Population stddev of 1..=N is sqrt((N^2 - 1) / 12)
+        let expected_stddev = ((1000.0f64.powi(2) - 1.0) / 12.0).sqrt() / 1000.0;
+        assert!((metrics.stddev_ms - expected_stddev).abs() < 1e-9);
+
+        let json = serde_json::to_value(&metrics).unwrap();
+        assert!(json.get("p99").is_some() && json.get("percentiles").is_none());
+        let parsed: OperationMetrics = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.percentiles, metrics.percentiles);
+    }
+
+    #[test]
+    fn test_matmul_gflops_from_mkn() {
+        // 256^3 multiply-adds in 1ms
+        let events = vec![timed_event("matmul", 256 * 256 * 256, 1000)];
+        let metrics = OperationMetrics::from_events("matmul", &events, 1.0);
+
+        let expected = 2.0 * (256.0f64).powi(3) / 1e-3 / 1e9;
+        assert!((metrics.gflops.unwrap() - expected).abs() < 1e-9);
+        assert!(metrics.gb_per_s.is_none());
+    }
+
+    #[test]
+    fn test_memory_max_and_avg_per_call() {
+        let mut events = vec![timed_event("sum", 10, 100), timed_event("sum", 10, 100)];
+        events[0].arena_bytes_used = 4096;
+        events[1].heap_bytes_allocated = 1000;
+        events[1].sample_weight = 3;
+        let metrics = OperationMetrics::from_events("sum", &events, 1.0);
+
+        assert_eq!((metrics.max_arena_bytes, metrics.avg_arena_bytes), (4096, 1024.0));
+        assert_eq!((metrics.max_heap_bytes, metrics.avg_heap_bytes), (1000, 750.0));
+
+        let merged = OperationMetrics::merge("sum", &[&metrics, &OperationMetrics::from_events("sum", &events[..1], 1.0)], 1.0);
+        assert_eq!(merged.max_arena_bytes, 4096);
+        assert!((merged.avg_arena_bytes - 8192.0 / 5.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_report_value_matches_parsed_json() {
+        // get_profile_report_dict converts through serde_json::Value; it must
+        // match what json.loads() sees, including non-finite floats as null
+        let events = vec![timed_event("add", 10, 100), timed_event("add", 20, 300)];
+        let mut report = ProfileReport::from_events(&events, None, None);
+        report.operations.get_mut("add").unwrap().avg_time_ms = f64::NAN;
+        report.threads.insert(7, ThreadMetrics::default());
+
+        // serde_json's default float parsing may be off by an ulp
+        fn same(a: &serde_json::Value, b: &serde_json::Value) -> bool {
+            use serde_json::Value;
+            match (a, b) {
+                (Value::Number(x), Value::Number(y)) => (x.as_f64().unwrap() - y.as_f64().unwrap()).abs() <= 1e-12 * x.as_f64().unwrap().abs().max(1.0),
+                (Value::Array(x), Value::Array(y)) => x.len() == y.len() && x.iter().zip(y).all(|(x, y)| same(x, y)),
+                (Value::Object(x), Value::Object(y)) => x.len() == y.len() && x.iter().all(|(k, v)| y.get(k).is_some_and(|w| same(v, w))),
+                _ => a == b,
+            }
+        }
+
+        let value = serde_json::to_value(&report).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        assert!(same(&value, &parsed));
+        assert!(value["operations"]["add"]["avg_time_ms"].is_null());
+        assert!(value["threads"]["7"].is_object());
+    }
+
+    #[test]
+    fn test_duration_histogram_separates_modes() {
+        // Bimodal: 30 fast BLAS-sized calls around 5µs, 10 slow ones around 20ms
+        let mut events: Vec<OperationEvent> = (0..30).map(|i| timed_event("matmul", 64, 3 + i % 5)).collect();
+        events.extend((0..10).map(|i| timed_event("matmul", 64, 15_000 + i * 1_000)));
+        let metrics = OperationMetrics::from_events("matmul", &events, 1.0);
+
+        let histogram = &metrics.duration_histogram;
+        assert_eq!(histogram.len(), DURATION_BUCKET_BOUNDS_US.len() + 1);
+        assert_eq!(histogram[1], (Some(10), 30));
+        assert_eq!(histogram[5], (Some(100_000), 10));
+        assert_eq!(histogram.iter().map(|(_, count)| count).sum::<usize>(), 40);
+        assert_eq!(histogram.last().unwrap().0, None);
+
+        let json = serde_json::to_value(&metrics).unwrap();
+        assert_eq!(json["duration_histogram"][1], serde_json::json!([10, 30]));
+        assert_eq!(json["duration_histogram"][7], serde_json::json!([null, 0]));
+
+        let merged = OperationMetrics::merge("matmul", &[&metrics, &metrics], 2.0);
+        assert_eq!(merged.duration_histogram[5], (Some(100_000), 20));
+    }
+
+    #[test]
+    fn test_duration_bucket_edges() {
+        assert_eq!(duration_bucket_index(0), 0);
+        assert_eq!(duration_bucket_index(1), 1);
+        assert_eq!(duration_bucket_index(999_999), 6);
+        assert_eq!(duration_bucket_index(1_000_000), 7);
+        assert_eq!(duration_bucket_index(u64::MAX), 7);
+    }
+
+    #[test]
+    fn test_gil_released_fraction() {
+        let split = |held_us: u64, released_us: u64| OperationEvent {
+            gil_held_us: held_us,
+            gil_released_us: released_us,
+            ..timed_event("matmul", 64, held_us + released_us)
+        };
+        // 100µs held + 900µs released, then 300µs held + 700µs released
+        let report = ProfileReport::from_events(&[split(100, 900), split(300, 700), timed_event("add", 4, 10)], None, None);
+
+        let matmul = &report.operations["matmul"];
+        assert!((matmul.gil_held_ms - 0.4).abs() < 1e-12);
+        assert!((matmul.gil_released_ms - 1.6).abs() < 1e-12);
+        assert!((matmul.gil_released_fraction.unwrap() - 0.8).abs() < 1e-12);
+        // Not GIL-tracked: no fraction rather than a misleading 0
+        assert_eq!(report.operations["add"].gil_released_fraction, None);
+
+        let serial = OperationMetrics::from_events("sum", &[split(500, 0)], 1.0);
+        assert_eq!(serial.gil_released_fraction, Some(0.0));
+
+        let merged = OperationMetrics::merge("matmul", &[matmul, &OperationMetrics::from_events("matmul", &[split(1000, 0)], 1.0)], 3.0);
+        assert!((merged.gil_released_fraction.unwrap() - 1.6 / 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_context_summary_covers_session() {
+        let in_context = |operation: &str, context: Option<&str>, duration_us: u64| OperationEvent {
+            context: context.map(String::from),
+            ..timed_event(operation, 4, duration_us)
+        };
+        let events = vec![
+            in_context("matmul", Some("forward"), 3000),
+            in_context("add", Some("forward"), 500),
+            in_context("relu", Some("forward"), 200),
+            in_context("sum", Some("forward"), 100),
+            in_context("matmul", Some("backward"), 6000),
+            in_context("add", None, 250),
+            in_context("add", None, 250),
+        ];
+        let report = ProfileReport::from_events(&events, None, None);
+
+        let names: Vec<&str> = report.contexts.keys().map(String::as_str).collect();
+        assert_eq!(names, ["backward", "forward", UNCATEGORIZED_CONTEXT]);
+        let total: f64 = report.contexts.values().map(|c| c.total_time_ms).sum();
+        assert!((total - report.session_total_time_ms).abs() < 1e-9);
+        assert_eq!(report.contexts.values().map(|c| c.event_count).sum::<usize>(), events.len());
+
+        let forward = &report.contexts["forward"];
+        assert_eq!(forward.event_count, 4);
+        assert!((forward.total_time_ms - 3.8).abs() < 1e-9);
+        let top: Vec<&str> = forward.top_operations.iter().map(|o| o.operation.as_str()).collect();
+        assert_eq!(top, ["matmul", "add", "relu"]);
+        assert_eq!(report.contexts[UNCATEGORIZED_CONTEXT].top_operations[0].count, 2);
+
+        // A filtered report still summarizes the whole session
+        let filtered = ProfileReport::from_events(&events, Some("backward"), None);
+        assert_eq!(filtered.contexts, report.contexts);
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["contexts"]["forward"]["top_operations"][0]["operation"], "matmul");
+
+        let merged = ProfileReport::merge(&[report.clone(), report]);
+        assert_eq!(merged.contexts["forward"].event_count, 8);
+        assert!((merged.contexts["backward"].total_time_ms - 12.0).abs() < 1e-9);
+    }
+
+    fn text_report_fixture() -> ProfileReport {
+        let mut events: Vec<OperationEvent> = (0..3).map(|_| timed_event("add", 4, 100)).collect();
+        events.push(timed_event("matmul", 64, 2000));
+        events.extend((0..2).map(|_| timed_event("a_really_long_operation_name_that_goes_on", 4, 50)));
+        ProfileReport::from_events(&events, None, None)
+    }
+
+    #[test]
+    fn test_text_report_layout() {
+        let text = format_text_report(&text_report_fixture(), ReportSort::Total, 20);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "Profile summary: 3 operations, 2.400 ms total");
+        assert_eq!(
+            lines[1],
+            "Operation                         Calls  Total (ms)  Avg (ms)  P99 (ms)  % Total  Backend"
+        );
+        assert_eq!(lines[2], "-".repeat(lines[1].len()));
+        assert_eq!(
+            lines[3],
+            "matmul                                1       2.000     2.000     2.000     83.3  CPU"
+        );
+        assert_eq!(
+            lines[5],
+            "a_really_long_operation_name_th…      2       0.100     0.050     0.050      4.2  CPU"
+        );
+        // Every row lines up under the header
+        let backend_col = lines[1].find("Backend").unwrap();
+        for row in &lines[3..] {
+            assert_eq!(row.chars().count(), backend_col + "CPU".len());
+        }
+    }
+
+    #[test]
+    fn test_text_report_sorting_and_top_n() {
+        let report = text_report_fixture();
+        let first_column = |sort_by: ReportSort, top_n: usize| -> Vec<String> {
+            format_text_report(&report, sort_by, top_n)
+                .lines()
+                .skip(3)
+                .map(|line| line.split_whitespace().next().unwrap().to_string())
+                .collect()
+        };
+        let long = "a_really_long_operation_name_th…";
+        assert_eq!(first_column(ReportSort::Total, 0), ["matmul", "add", long]);
+        assert_eq!(first_column(ReportSort::Calls, 0), ["add", long, "matmul"]);
+        assert_eq!(first_column(ReportSort::Name, 0), [long, "add", "matmul"]);
+        assert_eq!(first_column(ReportSort::Avg, 1), ["matmul"]);
+        assert!(format_text_report(&report, ReportSort::Total, 1).starts_with(
+            "Profile summary: 3 operations, 2.400 ms total (top 1 shown)"
+        ));
+
+        assert_eq!(ReportSort::from_name(" P99 "), Some(ReportSort::P99));
+        assert_eq!(ReportSort::from_name("bogus"), None);
+        let empty = ProfileReport::new("empty".to_string(), None);
+        assert_eq!(format_text_report(&empty, ReportSort::Total, 20).lines().count(), 1);
     }
 }
@@ -4,5 +4,11 @@
 
 pub mod metrics;
 pub mod core;
+pub mod binary;
 
-pub use self::core::{Profiler, ProfileScope, set_context};
+pub use self::core::{
+    Profiler, ProfileScope, get_context, set_context, with_context, set_call_site,
+    task_context, with_task_context,
+    get_or_create_profiler, set_active_profiler, active_profiler_name,
+    EnvProfileConfig, set_exit_report, write_exit_report,
+};
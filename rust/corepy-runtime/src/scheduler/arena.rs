@@ -12,158 +12,930 @@
 // - Thread-local storage: No synchronization overhead
 // - Configurable arena size via COREPY_ARENA_SIZE env var
 // - Integration with rayon thread pool
+// - Grows instead of failing: when the current block is full a new one is
+//   chained on (each twice the size of the last), up to COREPY_ARENA_MAX_BYTES
+//   per thread; requests bigger than the block size get a block of their own
+// - reset() keeps the largest block (or every block, COREPY_ARENA_RETAIN=all)
+// - Blocks start on a 64-byte boundary; float scratch is 64-byte aligned by
+//   default so AVX2/AVX-512 kernels can use aligned loads
+// - Each arena tracks its peak usage (surviving reset) and failed
+//   allocations; process-wide maxima/totals back the FFI stats
+// - set_arena_size() resizes the calling thread's arena at once; other
+//   threads' arenas pick the new size up at their next with_arena() entry
+//
+// - with_arena() is the safe layer: slices it hands out borrow the scope,
+//   so using one after the arena rewinds does not compile; ArenaRegion and
+//   ThreadArena keep the raw pointer API for FFI-adjacent code
+// - Regions nest: each with_arena()/ArenaRegion marks the arena on entry
+//   and rewinds to exactly that mark on exit (also when unwinding), so an
+//   inner scope never frees the outer one's allocations; the outermost exit
+//   is a full reset()
+// - alloc_or_heap() falls back to an owned heap buffer when the arena is
+//   out of room; the buffer lives as long as its guard, not the region, and
+//   fallbacks are counted (bytes and count) next to the arena bytes
+// - Debug mode (cargo feature "arena-debug" or COREPY_ARENA_DEBUG=1, debug
+//   builds only without the feature) poisons fresh allocations with 0xAB
+//   and follows each with canary bytes, verified on every allocation and
+//   rewind; release builds without the feature compile none of it
+// - Each closing region tells the registered ArenaUsageObserver (the
+//   profiler) how many bytes it allocated itself, nested regions excluded
 //
 // USAGE PATTERN:
-//   with_arena(|arena| {
-//       let buf = arena.alloc::<f32>(1024);
+//   with_arena(|scope| {
+//       let buf = scope.alloc_zeroed::<f32>(1024);
 //       // ... use buffer ...
 //   }); // Arena automatically resets
 
+use std::alloc::{self, Layout};
+use std::any::TypeId;
 use std::cell::RefCell;
 use std::env;
+use std::marker::PhantomData;
+use std::mem::MaybeUninit;
+use std::ops::{Deref, DerefMut};
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::OnceLock;
 
 /// Default arena size per thread: 1 MB
 const DEFAULT_ARENA_SIZE: usize = 1024 * 1024;
 
+/// Default cap on one thread's arena, across all of its blocks: 256 MB
+const DEFAULT_ARENA_MAX_BYTES: usize = 256 * 1024 * 1024;
+
+/// Alignment of every block start and of f32/f64 allocations (AVX-512 width)
+pub const SIMD_ALIGN: usize = 64;
+
+/// Bytes handed out by all arenas since startup (monotonic; read by the profiler)
+static ARENA_BYTES_ALLOCATED: AtomicU64 = AtomicU64::new(0);
+
+/// Bytes allocated on the heap because an arena was too small
+static HEAP_FALLBACK_BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// Heap allocations made because an arena was too small
+static HEAP_FALLBACK_COUNT: AtomicU64 = AtomicU64::new(0);
+
+/// Highest used_bytes() reached by any arena (see reset_global_peak)
+static ARENA_PEAK_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+/// Allocations any arena refused because it hit its byte cap
+static ARENA_FAILED_ALLOCS: AtomicU64 = AtomicU64::new(0);
+
+/// Arena size set by set_arena_size() (0 = not set)
+static ARENA_SIZE_OVERRIDE: AtomicUsize = AtomicUsize::new(0);
+
+/// Bumped by set_arena_size(); arenas built under an older value resize
+static ARENA_SIZE_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Canary bytes following each allocation in debug mode
+#[cfg(any(feature = "arena-debug", debug_assertions))]
+const CANARY_LEN: usize = 16;
+
+#[cfg(any(feature = "arena-debug", debug_assertions))]
+const CANARY_BYTE: u8 = 0xFD;
+
+/// Fill of fresh allocations in debug mode
+#[cfg(any(feature = "arena-debug", debug_assertions))]
+pub const POISON_BYTE: u8 = 0xAB;
+
+/// Receives the bytes each thread-local arena region allocated, as it closes
+///
+/// Lets the profiler attribute scratch to operations without this module
+/// knowing about profiler types.
+pub trait ArenaUsageObserver: Sync {
+    /// Called on the thread that closed the region, with the bytes requested
+    /// through it (nested regions report their own)
+    fn region_closed(&self, bytes: u64);
+}
+
+static USAGE_OBSERVER: OnceLock<&'static dyn ArenaUsageObserver> = OnceLock::new();
+
+/// Install the observer told about closing regions (the first one stays)
+pub fn set_usage_observer(observer: &'static dyn ArenaUsageObserver) {
+    let _ = USAGE_OBSERVER.set(observer);
+}
+
+/// Why an arena operation was rejected
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArenaError {
+    /// resize() while allocations are live (bytes in use)
+    InUse(usize),
+    /// An arena of zero bytes was requested
+    ZeroSize,
+}
+
+impl std::fmt::Display for ArenaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArenaError::InUse(used) => write!(f, "cannot resize an arena with {} bytes in use", used),
+            ArenaError::ZeroSize => write!(f, "arena size must be at least 1 byte"),
+        }
+    }
+}
+
+/// An allocation that wrote past its end, found through its canary
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArenaCorruption {
+    /// Index of the allocation since the arena's last reset (0 = first)
+    pub allocation: usize,
+    /// Bytes the allocation asked for
+    pub size: usize,
+    /// First clobbered canary byte, counted from the allocation's start
+    pub offset: usize,
+}
+
+impl std::fmt::Display for ArenaCorruption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "arena allocation #{} ({} bytes) overran its end: canary clobbered at byte offset {}",
+            self.allocation, self.size, self.offset
+        )
+    }
+}
+
+/// Debug mode for new arenas: always with the "arena-debug" feature, else
+/// COREPY_ARENA_DEBUG=1
+#[cfg(any(feature = "arena-debug", debug_assertions))]
+fn configured_debug() -> bool {
+    cfg!(feature = "arena-debug") || env::var("COREPY_ARENA_DEBUG").is_ok_and(|value| value == "1")
+}
+
+/// Start and requested size of one allocation, its canary right after it
+#[cfg(any(feature = "arena-debug", debug_assertions))]
+struct Canary {
+    data: *mut u8,
+    size: usize,
+}
+
+/// Per-thread arena size: the last set_arena_size(), else COREPY_ARENA_SIZE
+/// env var or DEFAULT_ARENA_SIZE
+pub fn configured_arena_size() -> usize {
+    match ARENA_SIZE_OVERRIDE.load(Ordering::Relaxed) {
+        0 => env::var("COREPY_ARENA_SIZE")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_ARENA_SIZE),
+        size => size,
+    }
+}
+
+/// Per-thread cap on arena growth: COREPY_ARENA_MAX_BYTES env var or
+/// DEFAULT_ARENA_MAX_BYTES
+pub fn configured_arena_max_bytes() -> usize {
+    env::var("COREPY_ARENA_MAX_BYTES")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_ARENA_MAX_BYTES)
+}
+
+/// Blocks kept by ThreadArena::reset()
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetainPolicy {
+    /// Keep only the largest block, freeing the rest
+    Largest,
+    /// Keep every block for the next round
+    All,
+}
+
+/// Reset policy: COREPY_ARENA_RETAIN=all keeps every block, else Largest
+pub fn configured_retain_policy() -> RetainPolicy {
+    match env::var("COREPY_ARENA_RETAIN") {
+        Ok(value) if value.eq_ignore_ascii_case("all") => RetainPolicy::All,
+        _ => RetainPolicy::Largest,
+    }
+}
+
+/// One contiguous, SIMD_ALIGN-aligned buffer of an arena
+struct Block {
+    ptr: NonNull<u8>,
+    len: usize,
+    offset: usize,
+}
+
+// The block owns its allocation outright
+unsafe impl Send for Block {}
+
+impl Block {
+    fn new(size: usize) -> Self {
+        let layout = Self::layout(size);
+        let ptr = unsafe { alloc::alloc_zeroed(layout) };
+        let ptr = NonNull::new(ptr).unwrap_or_else(|| alloc::handle_alloc_error(layout));
+        Block { ptr, len: size, offset: 0 }
+    }
+
+    fn layout(size: usize) -> Layout {
+        Layout::from_size_align(size.max(1), SIMD_ALIGN).expect("arena block size overflows")
+    }
+
+    /// Offset at which `size` bytes aligned to `align` would start, if they fit
+    fn fit(&self, size: usize, align: usize) -> Option<usize> {
+        let base = self.ptr.as_ptr() as usize;
+        let aligned = ((base + self.offset).checked_add(align - 1)? & !(align - 1)) - base;
+        (aligned.checked_add(size)? <= self.len).then_some(aligned)
+    }
+}
+
+impl Drop for Block {
+    fn drop(&mut self) {
+        unsafe { alloc::dealloc(self.ptr.as_ptr(), Self::layout(self.len)) }
+    }
+}
+
 /// Thread-local arena for temporary allocations
 /// 
 /// Uses bump allocation: allocations are O(1), all freed at once when arena resets.
 /// Perfect for temporary buffers needed during tensor operations.
-#[allow(dead_code)]
+///
+/// Blocks before `current` are retired for this round, blocks after it are
+/// still untouched. Oversized requests live in `dedicated`, in allocation
+/// order.
 pub struct ThreadArena {
-    #[allow(dead_code)]
-    buffer: Vec<u8>,
-    offset: usize,
+    blocks: Vec<Block>,
+    current: usize,
+    dedicated: Vec<Block>,
+    /// Open ArenaRegions on this arena (innermost = this depth)
+    regions: usize,
+    /// Bytes in use across blocks, alignment padding included
+    used: usize,
+    /// Highest `used` since creation or reset_peak()
+    peak: usize,
+    failed_allocs: u64,
+    block_size: usize,
+    max_bytes: usize,
+    retain: RetainPolicy,
+    /// ARENA_SIZE_GENERATION this arena's size follows
+    size_generation: u64,
+    /// Bytes requested since the innermost open region started (or since the
+    /// last region closed), for ArenaUsageObserver
+    unreported: u64,
+    /// Live allocations, in order, while debug mode is on
+    #[cfg(any(feature = "arena-debug", debug_assertions))]
+    canaries: Option<Vec<Canary>>,
 }
 
 impl ThreadArena {
     /// Create a new arena with the specified size
     pub fn new(size: usize) -> Self {
+        Self::with_max_bytes(size, configured_arena_max_bytes())
+    }
+
+    /// Create an arena of `size` bytes that may grow to `max_bytes` in total
+    pub fn with_max_bytes(size: usize, max_bytes: usize) -> Self {
         ThreadArena {
-            buffer: vec![0u8; size],
-            offset: 0,
+            blocks: vec![Block::new(size)],
+            current: 0,
+            dedicated: Vec::new(),
+            regions: 0,
+            used: 0,
+            peak: 0,
+            failed_allocs: 0,
+            block_size: size,
+            max_bytes: max_bytes.max(size),
+            retain: configured_retain_policy(),
+            size_generation: 0,
+            unreported: 0,
+            #[cfg(any(feature = "arena-debug", debug_assertions))]
+            canaries: configured_debug().then(Vec::new),
         }
     }
 
     /// Create arena with size from environment variable or default
     pub fn with_default_size() -> Self {
-        let size = env::var("COREPY_ARENA_SIZE")
-            .ok()
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(DEFAULT_ARENA_SIZE);
-        
-        Self::new(size)
+        let generation = ARENA_SIZE_GENERATION.load(Ordering::Acquire);
+        let mut arena = Self::new(configured_arena_size());
+        arena.size_generation = generation;
+        arena
+    }
+
+    /// Replace the arena's blocks with one block of `new_size` bytes
+    ///
+    /// Only allowed while nothing is allocated and no region is open; raises
+    /// the growth cap to `new_size` if it was lower.
+    pub fn resize(&mut self, new_size: usize) -> Result<(), ArenaError> {
+        if new_size == 0 {
+            return Err(ArenaError::ZeroSize);
+        }
+        let used = self.used_bytes();
+        if used != 0 || self.regions != 0 {
+            return Err(ArenaError::InUse(used));
+        }
+        self.blocks = vec![Block::new(new_size)];
+        self.current = 0;
+        self.dedicated.clear();
+        self.block_size = new_size;
+        self.max_bytes = self.max_bytes.max(new_size);
+        Ok(())
+    }
+
+    /// Follow a set_arena_size() made since this arena was sized
+    fn apply_pending_resize(&mut self) {
+        // Acquire: pairs with set_arena_size() so the new size is visible
+        let generation = ARENA_SIZE_GENERATION.load(Ordering::Acquire);
+        if generation != self.size_generation && self.resize(configured_arena_size()).is_ok() {
+            self.size_generation = generation;
+        }
+    }
+
+    /// Choose which blocks reset() keeps
+    pub fn set_retain_policy(&mut self, retain: RetainPolicy) {
+        self.retain = retain;
+    }
+
+    /// Turn canaries and poisoning on or off; only while nothing is allocated
+    #[cfg(any(feature = "arena-debug", debug_assertions))]
+    pub fn set_debug(&mut self, enabled: bool) {
+        assert_eq!(self.used, 0, "arena debug mode can only change while the arena is empty");
+        self.canaries = enabled.then(Vec::new);
+    }
+
+    /// Find the first allocation whose canary was overwritten
+    ///
+    /// Always Ok outside debug mode.
+    pub fn check_integrity(&self) -> Result<(), ArenaCorruption> {
+        #[cfg(any(feature = "arena-debug", debug_assertions))]
+        for (allocation, canary) in self.canaries.iter().flatten().enumerate() {
+            let bytes = unsafe { std::slice::from_raw_parts(canary.data.add(canary.size), CANARY_LEN) };
+            if let Some(i) = bytes.iter().position(|&byte| byte != CANARY_BYTE) {
+                return Err(ArenaCorruption { allocation, size: canary.size, offset: canary.size + i });
+            }
+        }
+        Ok(())
+    }
+
+    /// Panic naming the overrun allocation, unless already unwinding
+    #[cfg(any(feature = "arena-debug", debug_assertions))]
+    fn verify_canaries(&self) {
+        if std::thread::panicking() {
+            return;
+        }
+        if let Err(corruption) = self.check_integrity() {
+            panic!("{}", corruption);
+        }
     }
 
     /// Allocate bytes from the arena
     /// 
-    /// Returns raw pointer to allocated memory.
-    /// Memory is NOT initialized (for performance).
+    /// Returns raw pointer to allocated memory, or None once growing would
+    /// exceed the arena's byte cap.
+    /// Memory is NOT initialized (for performance; debug mode fills it
+    /// with POISON_BYTE).
     /// 
     /// # Safety
     /// - Caller must not use pointer after arena reset
     /// - Caller must ensure proper alignment for type T
-    #[allow(dead_code)]
     pub unsafe fn alloc_bytes(&mut self, size: usize, align: usize) -> Option<*mut u8> {
-        // Align the current offset
-        let aligned_offset = (self.offset + align - 1) & !(align - 1);
-        
-        let end = aligned_offset + size;
-        if end > self.buffer.len() {
+        #[cfg(any(feature = "arena-debug", debug_assertions))]
+        if self.canaries.is_some() {
+            self.verify_canaries();
+            let ptr = self.bump_alloc(size, CANARY_LEN, align)?;
+            std::ptr::write_bytes(ptr, POISON_BYTE, size);
+            std::ptr::write_bytes(ptr.add(size), CANARY_BYTE, CANARY_LEN);
+            if let Some(canaries) = &mut self.canaries {
+                canaries.push(Canary { data: ptr, size });
+            }
+            return Some(ptr);
+        }
+        self.bump_alloc(size, 0, align)
+    }
+
+    /// Take `size` bytes plus `reserve` trailing bytes (the canary), which
+    /// occupy the block but stay out of the used/peak/region accounting
+    unsafe fn bump_alloc(&mut self, size: usize, reserve: usize, align: usize) -> Option<*mut u8> {
+        if let Some(ptr) = self.bump(self.current, size, reserve, align) {
+            return Some(ptr);
+        }
+
+        // Blocks kept from earlier rounds
+        let span = size.checked_add(reserve)?;
+        let kept = (self.current + 1..self.blocks.len()).find(|&i| self.blocks[i].fit(span, align).is_some());
+        if let Some(index) = kept {
+            self.blocks.swap(self.current + 1, index);
+            self.current += 1;
+            return self.bump(self.current, size, reserve, align);
+        }
+
+        // Block starts already satisfy alignments up to SIMD_ALIGN
+        let needed = if align > SIMD_ALIGN { span.checked_add(align - 1)? } else { span };
+        let remaining = self.max_bytes.saturating_sub(self.capacity());
+        if needed > remaining {
             // Arena exhausted
+            self.failed_allocs += 1;
+            ARENA_FAILED_ALLOCS.fetch_add(1, Ordering::Relaxed);
             return None;
         }
+        if needed > self.block_size {
+            // Oversized: a dedicated block, so the current block keeps
+            // serving small requests
+            let mut block = Block::new(needed);
+            let start = block.fit(span, align)?;
+            block.offset = start + span;
+            let ptr = block.ptr.as_ptr().add(start);
+            self.dedicated.push(block);
+            self.record_use(start + size, size);
+            return Some(ptr);
+        }
+        let grown = (self.blocks[self.current].len * 2).max(needed).min(remaining);
+        self.blocks.insert(self.current + 1, Block::new(grown));
+        self.current += 1;
+        self.bump(self.current, size, reserve, align)
+    }
 
-        let ptr = self.buffer.as_mut_ptr().add(aligned_offset);
-        self.offset = end;
+    unsafe fn bump(&mut self, index: usize, size: usize, reserve: usize, align: usize) -> Option<*mut u8> {
+        let block = &mut self.blocks[index];
+        let start = block.fit(size.checked_add(reserve)?, align)?;
+        let consumed = start + size - block.offset;
+        block.offset = start + size + reserve;
+        let ptr = block.ptr.as_ptr().add(start);
+        self.record_use(consumed, size);
         Some(ptr)
     }
 
+    /// Account `consumed` bytes (padding included) for a `size`-byte request
+    fn record_use(&mut self, consumed: usize, size: usize) {
+        self.used += consumed;
+        if self.used > self.peak {
+            self.peak = self.used;
+            ARENA_PEAK_BYTES.fetch_max(self.used, Ordering::Relaxed);
+        }
+        ARENA_BYTES_ALLOCATED.fetch_add(size as u64, Ordering::Relaxed);
+        self.unreported += size as u64;
+    }
+
     /// Allocate typed slice from arena
     /// 
-    /// Returns None if arena doesn't have enough space.
+    /// Returns None if the byte size overflows or the arena can't grow
+    /// enough. f32 and f64 buffers are
+    /// SIMD_ALIGN-aligned, other types get their natural alignment.
     /// 
     /// # Safety
     /// - Returned slice is valid until arena reset
     /// - Memory is uninitialized
-    #[allow(dead_code)]
-    pub unsafe fn alloc<T>(&mut self, count: usize) -> Option<*mut T> {
-        let size = count * std::mem::size_of::<T>();
-        let align = std::mem::align_of::<T>();
+    pub unsafe fn alloc<T: 'static>(&mut self, count: usize) -> Option<*mut T> {
+        let is_float = TypeId::of::<T>() == TypeId::of::<f32>() || TypeId::of::<T>() == TypeId::of::<f64>();
+        let align = if is_float { SIMD_ALIGN } else { std::mem::align_of::<T>() };
+        self.alloc_aligned(count, align)
+    }
+
+    /// Allocate `count` T's starting on an `align`-byte boundary
+    ///
+    /// Returns None if the byte size overflows or the arena can't grow enough.
+    ///
+    /// # Panics
+    /// If `align` is not a power of two or is below T's natural alignment.
+    ///
+    /// # Safety
+    /// - Returned slice is valid until arena reset
+    /// - Memory is uninitialized
+    pub unsafe fn alloc_aligned<T>(&mut self, count: usize, align: usize) -> Option<*mut T> {
+        assert!(
+            align.is_power_of_two() && align >= std::mem::align_of::<T>(),
+            "arena alignment {} must be a power of two >= {}",
+            align,
+            std::mem::align_of::<T>()
+        );
+        let size = count.checked_mul(std::mem::size_of::<T>())?;
         
         self.alloc_bytes(size, align)
             .map(|ptr| ptr as *mut T)
     }
 
+    /// Position to rewind() back to
+    fn mark(&self) -> ArenaMark {
+        ArenaMark {
+            current: self.current,
+            offset: self.blocks[self.current].offset,
+            dedicated: self.dedicated.len(),
+            used: self.used,
+            #[cfg(any(feature = "arena-debug", debug_assertions))]
+            canaries: self.canaries.as_ref().map_or(0, Vec::len),
+        }
+    }
+
+    /// Free everything allocated since `mark`, keeping what came before
+    ///
+    /// Blocks used since the mark are kept for reuse; dedicated ones join
+    /// the untouched blocks after `current`.
+    fn rewind(&mut self, mark: ArenaMark) {
+        #[cfg(any(feature = "arena-debug", debug_assertions))]
+        {
+            self.verify_canaries();
+            if let Some(canaries) = &mut self.canaries {
+                canaries.truncate(mark.canaries);
+            }
+        }
+        for block in &mut self.blocks[mark.current + 1..=self.current] {
+            block.offset = 0;
+        }
+        self.blocks[mark.current].offset = mark.offset;
+        self.current = mark.current;
+        for mut block in self.dedicated.drain(mark.dedicated..) {
+            block.offset = 0;
+            self.blocks.push(block);
+        }
+        self.used = mark.used;
+    }
+
     /// Reset the arena, invalidating all previous allocations
     /// 
-    /// This is O(1) - just resets the offset pointer.
+    /// Rewinds every kept block and frees the others (see RetainPolicy).
     /// Memory is not cleared for performance.
     pub fn reset(&mut self) {
-        self.offset = 0;
+        self.rewind(ArenaMark {
+            current: 0,
+            offset: 0,
+            dedicated: 0,
+            used: 0,
+            #[cfg(any(feature = "arena-debug", debug_assertions))]
+            canaries: 0,
+        });
+        if self.retain == RetainPolicy::Largest && self.blocks.len() > 1 {
+            let largest = (0..self.blocks.len())
+                .max_by_key(|&i| self.blocks[i].len)
+                .unwrap_or(0);
+            let block = self.blocks.swap_remove(largest);
+            self.blocks = vec![block];
+        }
     }
 
-    /// Get current memory usage
-    #[allow(dead_code)]
+    /// Get current memory usage (all blocks, alignment padding included)
     pub fn used_bytes(&self) -> usize {
-        self.offset
+        self.used
     }
 
-    /// Get total arena capacity
-    #[allow(dead_code)]
+    /// Highest used_bytes() since creation or the last reset_peak()
+    ///
+    /// Survives reset(), so it shows the scratch a workload really needed.
+    pub fn peak_bytes(&self) -> usize {
+        self.peak
+    }
+
+    /// Restart peak tracking from the current usage
+    pub fn reset_peak(&mut self) {
+        self.peak = self.used;
+    }
+
+    /// Allocations refused because the arena hit its byte cap
+    pub fn failed_allocs(&self) -> u64 {
+        self.failed_allocs
+    }
+
+    /// Get total arena capacity (all blocks)
     pub fn capacity(&self) -> usize {
-        self.buffer.len()
+        self.blocks.iter().chain(&self.dedicated).map(|block| block.len).sum()
     }
 
-    /// Get remaining space
-    #[allow(dead_code)]
+    /// Get remaining space in the current block and the untouched blocks
+    /// after it (growth up to the byte cap not included)
     pub fn available_bytes(&self) -> usize {
-        self.buffer.len() - self.offset
+        self.blocks[self.current..].iter().map(|block| block.len - block.offset).sum()
+    }
+
+    /// Number of blocks currently held
+    pub fn block_count(&self) -> usize {
+        self.blocks.len() + self.dedicated.len()
     }
 }
 
+/// Arena position saved when a region opens
+#[derive(Debug, Clone, Copy)]
+struct ArenaMark {
+    current: usize,
+    offset: usize,
+    dedicated: usize,
+    used: usize,
+    #[cfg(any(feature = "arena-debug", debug_assertions))]
+    canaries: usize,
+}
+
 // Thread-local storage for arena
 thread_local! {
     static ARENA: RefCell<ThreadArena> = RefCell::new(ThreadArena::with_default_size());
 }
 
-/// Execute function with access to thread-local arena
-/// 
-/// Arena is automatically reset after the function completes.
+/// One nesting level of the thread's arena
+///
+/// Marks the arena when entered and rewinds it to that mark when dropped,
+/// so allocations made through it are freed and everything allocated
+/// before it stays put. Regions must close innermost first; dropping an
+/// outer region also closes the regions inside it.
+pub struct ArenaRegion {
+    mark: ArenaMark,
+    depth: usize,
+    /// Enclosing region's unreported bytes, restored on close
+    outer_unreported: u64,
+    // Tied to the thread whose arena it marked
+    _not_send: PhantomData<*const ()>,
+}
+
+impl ArenaRegion {
+    /// Open a region on the calling thread's arena
+    ///
+    /// The outermost region first applies any pending set_arena_size().
+    pub fn enter() -> Self {
+        ARENA.with(|arena| {
+            let mut arena = arena.borrow_mut();
+            if arena.regions == 0 {
+                arena.apply_pending_resize();
+            }
+            arena.regions += 1;
+            ArenaRegion {
+                mark: arena.mark(),
+                depth: arena.regions,
+                outer_unreported: std::mem::take(&mut arena.unreported),
+                _not_send: PhantomData,
+            }
+        })
+    }
+
+    /// Nesting depth of this region (1 = outermost)
+    #[allow(dead_code)]
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Allocate `count` T's in this region (see ThreadArena::alloc)
+    ///
+    /// # Panics
+    /// If a region opened inside this one is still open.
+    ///
+    /// # Safety
+    /// - Returned memory is valid until this region drops
+    /// - Memory is uninitialized
+    pub unsafe fn alloc<T: 'static>(&self, count: usize) -> Option<*mut T> {
+        self.with_innermost(|arena| arena.alloc::<T>(count))
+    }
+
+    /// Allocate `count` T's on an `align`-byte boundary in this region (see
+    /// ThreadArena::alloc_aligned)
+    ///
+    /// # Safety
+    /// As for alloc().
+    #[allow(dead_code)]
+    pub unsafe fn alloc_aligned<T>(&self, count: usize, align: usize) -> Option<*mut T> {
+        self.with_innermost(|arena| arena.alloc_aligned::<T>(count, align))
+    }
+
+    /// Bytes in use in the thread's arena, this and outer regions included
+    pub fn used_bytes(&self) -> usize {
+        ARENA.with(|arena| arena.borrow().used_bytes())
+    }
+
+    fn with_innermost<R>(&self, f: impl FnOnce(&mut ThreadArena) -> R) -> R {
+        ARENA.with(|arena| {
+            let mut arena = arena.borrow_mut();
+            // An inner region's rewind would free this allocation early
+            assert_eq!(
+                arena.regions, self.depth,
+                "arena allocation from an outer region while an inner region is open"
+            );
+            f(&mut arena)
+        })
+    }
+}
+
+impl Drop for ArenaRegion {
+    fn drop(&mut self) {
+        // try_with: the arena may already be gone during thread exit
+        let allocated = ARENA.try_with(|arena| {
+            let Ok(mut arena) = arena.try_borrow_mut() else { return 0 };
+            if self.depth > arena.regions {
+                // Already closed by an outer region
+                return 0;
+            }
+            arena.regions = self.depth - 1;
+            if arena.regions == 0 {
+                arena.reset();
+            } else {
+                arena.rewind(self.mark);
+            }
+            std::mem::replace(&mut arena.unreported, self.outer_unreported)
+        });
+        // Outside the borrow: the observer may use the arena itself
+        if let (Ok(bytes @ 1..), Some(observer)) = (allocated, USAGE_OBSERVER.get()) {
+            observer.region_closed(bytes);
+        }
+    }
+}
+
+/// Run `f` in a new region of the thread-local arena
 /// 
-/// # Example
-/// ```
-/// with_arena(|arena| {
-///     let temp_buffer = unsafe { arena.alloc::<f32>(1000) };
-///     // ... use buffer ...
-/// });
-/// // temp_buffer is now invalid
-/// ```
+/// Everything allocated through the scope is freed when `f` returns (or
+/// panics); allocations of enclosing with_arena() calls are left alone.
+/// Slices from the scope can't leave the closure (tests/ui/arena_scope_escape.rs).
 pub fn with_arena<F, R>(f: F) -> R
 where
-    F: FnOnce(&mut ThreadArena) -> R,
+    F: for<'a> FnOnce(&ArenaScope<'a>) -> R,
 {
+    let scope = ArenaScope { backing: ScopeBacking::Region(ArenaRegion::enter()) };
+    f(&scope)
+}
+
+/// Element types for which all-zero bytes (and any bit pattern) is a valid value
+///
+/// # Safety
+/// Implement only for plain numeric types without padding or invariants.
+pub unsafe trait Zeroable: Copy + 'static {}
+
+macro_rules! impl_zeroable {
+    ($($t:ty),*) => { $(unsafe impl Zeroable for $t {})* };
+}
+impl_zeroable!(u8, i8, u16, i16, u32, i32, u64, i64, usize, isize, f32, f64);
+
+/// Safe handle on an arena for one with_arena() call
+///
+/// Slices borrow the scope, so they can't outlive the rewind that ends it.
+/// Allocations never move or overlap, so any number may be live at once.
+pub struct ArenaScope<'a> {
+    backing: ScopeBacking<'a>,
+}
+
+enum ScopeBacking<'a> {
+    /// A region of the thread-local arena
+    Region(ArenaRegion),
+    /// A caller-owned arena
+    Arena(RefCell<&'a mut ThreadArena>),
+}
+
+impl<'a> ArenaScope<'a> {
+    /// Scope over `arena`, which stays borrowed (so can't reset) while any
+    /// slice from the scope is alive
+    pub fn new(arena: &'a mut ThreadArena) -> Self {
+        ArenaScope { backing: ScopeBacking::Arena(RefCell::new(arena)) }
+    }
+
+    unsafe fn alloc_raw<T: 'static>(&self, len: usize) -> Option<*mut T> {
+        match &self.backing {
+            ScopeBacking::Region(region) => region.alloc::<T>(len),
+            ScopeBacking::Arena(arena) => arena.borrow_mut().alloc::<T>(len),
+        }
+    }
+
+    /// `len` zeroed T's, or None if the arena can't grow enough
+    pub fn alloc_zeroed<T: Zeroable>(&self, len: usize) -> Option<&'a mut [T]> {
+        unsafe {
+            let ptr = self.alloc_raw::<T>(len)?;
+            std::ptr::write_bytes(ptr, 0, len);
+            Some(std::slice::from_raw_parts_mut(ptr, len))
+        }
+    }
+
+    /// `len` uninitialized T's, or None if the arena can't grow enough
+    pub fn alloc_uninit<T: 'static>(&self, len: usize) -> Option<&'a mut [MaybeUninit<T>]> {
+        unsafe {
+            let ptr = self.alloc_raw::<T>(len)?;
+            Some(std::slice::from_raw_parts_mut(ptr as *mut MaybeUninit<T>, len))
+        }
+    }
+
+    /// `len` zeroed T's from the arena, or from the heap (counted as a
+    /// fallback) when the arena can't grow enough
+    pub fn alloc_or_heap<T: Zeroable>(&self, len: usize) -> ScratchBuf<'a, T> {
+        match self.alloc_zeroed::<T>(len) {
+            Some(slice) => ScratchBuf::Arena(slice),
+            None => {
+                let bytes = len.checked_mul(std::mem::size_of::<T>()).expect("scratch buffer size overflows usize");
+                record_heap_fallback(bytes);
+                crate::scheduler::stats::record_heap_fallback();
+                ScratchBuf::Heap(vec![unsafe { std::mem::zeroed::<T>() }; len])
+            }
+        }
+    }
+
+    /// Bytes in use in the underlying arena
+    pub fn used_bytes(&self) -> usize {
+        match &self.backing {
+            ScopeBacking::Region(region) => region.used_bytes(),
+            ScopeBacking::Arena(arena) => arena.borrow().used_bytes(),
+        }
+    }
+}
+
+/// Scratch from ArenaScope::alloc_or_heap(), used as a slice
+///
+/// A heap buffer is owned by the guard and freed when it drops, whatever
+/// the arena does in the meantime.
+pub enum ScratchBuf<'a, T> {
+    Arena(&'a mut [T]),
+    Heap(Vec<T>),
+}
+
+impl<T> ScratchBuf<'_, T> {
+    /// Whether the arena was too small and the buffer came from the heap
+    pub fn is_heap(&self) -> bool {
+        matches!(self, ScratchBuf::Heap(_))
+    }
+}
+
+impl<T> Deref for ScratchBuf<'_, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        match self {
+            ScratchBuf::Arena(slice) => slice,
+            ScratchBuf::Heap(vec) => vec,
+        }
+    }
+}
+
+impl<T> DerefMut for ScratchBuf<'_, T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        match self {
+            ScratchBuf::Arena(slice) => slice,
+            ScratchBuf::Heap(vec) => vec,
+        }
+    }
+}
+
+/// Get arena statistics of the calling thread
+pub fn arena_stats() -> ArenaStats {
     ARENA.with(|arena| {
-        let mut arena = arena.borrow_mut();
-        let result = f(&mut arena);
-        arena.reset(); // Auto-cleanup
-        result
+        let arena = arena.borrow();
+        ArenaStats {
+            used_bytes: arena.used_bytes(),
+            capacity: arena.capacity(),
+            available_bytes: arena.available_bytes(),
+            peak_bytes: arena.peak_bytes(),
+            failed_allocs: arena.failed_allocs(),
+        }
     })
 }
 
-/// Get arena statistics for debugging
-#[allow(dead_code)]
-pub fn arena_stats() -> (usize, usize, usize) {
+/// Usage of one thread's arena
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ArenaStats {
+    pub used_bytes: usize,
+    pub capacity: usize,
+    pub available_bytes: usize,
+    pub peak_bytes: usize,
+    pub failed_allocs: u64,
+}
+
+/// (highest peak of any arena, failed allocations across all arenas)
+pub fn global_arena_peaks() -> (usize, u64) {
+    (ARENA_PEAK_BYTES.load(Ordering::Relaxed), ARENA_FAILED_ALLOCS.load(Ordering::Relaxed))
+}
+
+/// Restart the process-wide peak and the calling thread's arena peak
+pub fn reset_global_peak() {
+    ARENA_PEAK_BYTES.store(0, Ordering::Relaxed);
     ARENA.with(|arena| {
-        let arena = arena.borrow();
-        (arena.used_bytes(), arena.capacity(), arena.available_bytes())
+        if let Ok(mut arena) = arena.try_borrow_mut() {
+            arena.reset_peak();
+        }
+    });
+}
+
+/// Resize the calling thread's arena now and make `bytes` the size of
+/// every other arena (existing ones switch at their next outermost with_arena())
+pub fn set_arena_size(bytes: usize) -> Result<(), ArenaError> {
+    if bytes == 0 {
+        return Err(ArenaError::ZeroSize);
+    }
+    ARENA.with(|arena| {
+        let mut arena = arena.borrow_mut();
+        arena.resize(bytes)?;
+        ARENA_SIZE_OVERRIDE.store(bytes, Ordering::Relaxed);
+        arena.size_generation = ARENA_SIZE_GENERATION.fetch_add(1, Ordering::Release) + 1;
+        Ok(())
     })
 }
 
+/// Note a heap allocation made in place of an arena allocation that did not fit
+pub fn record_heap_fallback(bytes: usize) {
+    HEAP_FALLBACK_BYTES.fetch_add(bytes as u64, Ordering::Relaxed);
+    HEAP_FALLBACK_COUNT.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Heap allocations made in place of arena allocations since startup
+pub fn heap_fallback_count() -> u64 {
+    HEAP_FALLBACK_COUNT.load(Ordering::Relaxed)
+}
+
+/// Cumulative (arena bytes, heap fallback bytes) across all threads
+///
+/// Both only grow; callers diff two readings to measure a region. Worker
+/// threads count too, so concurrent operations inflate each other's deltas.
+pub fn allocation_counters() -> (u64, u64) {
+    (
+        ARENA_BYTES_ALLOCATED.load(Ordering::Relaxed),
+        HEAP_FALLBACK_BYTES.load(Ordering::Relaxed),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Arena with canaries off, for tests that check exact offsets and fit
+    fn layout_arena(size: usize, max_bytes: usize) -> ThreadArena {
+        #[allow(unused_mut)]
+        let mut arena = ThreadArena::with_max_bytes(size, max_bytes);
+        #[cfg(any(feature = "arena-debug", debug_assertions))]
+        arena.set_debug(false);
+        arena
+    }
+
     #[test]
     fn test_arena_basic_allocation() {
         let mut arena = ThreadArena::new(1024);
@@ -180,18 +952,130 @@ mod tests {
 
     #[test]
     fn test_arena_exhaustion() {
-        let mut arena = ThreadArena::new(100);
+        let mut arena = layout_arena(100, 100);
         
         unsafe {
             // Allocate almost all space
             let _ptr1 = arena.alloc::<u8>(90).expect("allocation failed");
             
-            // This should fail
+            // This should fail: the cap leaves no room to grow
             let ptr2 = arena.alloc::<u8>(20);
             assert!(ptr2.is_none());
         }
     }
 
+    #[test]
+    fn test_arena_rejects_overflowing_sizes() {
+        let mut arena = ThreadArena::with_max_bytes(1024, 1 << 20);
+        unsafe {
+            // count * size_of::<T>() and size + alignment padding wrap usize
+            assert!(arena.alloc::<f32>(usize::MAX / 2).is_none());
+            assert!(arena.alloc_bytes(usize::MAX, SIMD_ALIGN).is_none());
+            arena.alloc::<u8>(3).expect("allocation failed");
+            assert!(arena.alloc::<u8>(usize::MAX - 2).is_none());
+        }
+
+        let scope = ArenaScope::new(&mut arena);
+        assert!(scope.alloc_zeroed::<f64>(usize::MAX / 4 + 1).is_none());
+        assert!(scope.alloc_uninit::<u32>(usize::MAX).is_none());
+        assert_eq!(scope.alloc_zeroed::<f32>(4).expect("allocation failed"), &[0.0; 4]);
+    }
+
+    #[test]
+    fn test_arena_grows_into_new_blocks() {
+        let mut arena = layout_arena(1024, 1 << 20);
+
+        unsafe {
+            let ptr1 = arena.alloc::<u8>(900).expect("allocation failed");
+            let ptr2 = arena.alloc::<u8>(900).expect("allocation failed");
+            std::ptr::write_bytes(ptr1, 1, 900);
+            std::ptr::write_bytes(ptr2, 2, 900);
+            assert_eq!(*ptr1.add(899), 1);
+
+            // Second block is twice the first
+            assert_eq!(arena.block_count(), 2);
+            assert_eq!(arena.capacity(), 1024 + 2048);
+            assert_eq!(arena.used_bytes(), 1800);
+            assert_eq!(arena.available_bytes(), 2048 - 900);
+        }
+    }
+
+    #[test]
+    fn test_arena_growth_respects_cap() {
+        let mut arena = ThreadArena::with_max_bytes(1024, 3000);
+
+        unsafe {
+            arena.alloc::<u8>(1000).expect("allocation failed");
+            // Doubling would pass the cap, so the block is clipped to it
+            arena.alloc::<u8>(1000).expect("allocation failed");
+            assert_eq!(arena.capacity(), 3000);
+            arena.alloc::<u8>(900).expect("allocation failed");
+            assert!(arena.alloc::<u8>(200).is_none());
+        }
+    }
+
+    #[test]
+    fn test_arena_oversized_request_gets_dedicated_block() {
+        let mut arena = layout_arena(1024, 1 << 20);
+
+        unsafe {
+            let small1 = arena.alloc::<u8>(100).expect("allocation failed");
+            let big = arena.alloc::<u8>(10_000).expect("allocation failed");
+            std::ptr::write_bytes(big, 7, 10_000);
+            let small2 = arena.alloc::<u8>(100).expect("allocation failed");
+
+            // Small requests keep filling the first block
+            assert_eq!(small2, small1.add(100));
+            assert_eq!(arena.block_count(), 2);
+            assert_eq!(arena.used_bytes(), 10_200);
+            assert_eq!(arena.available_bytes(), 1024 - 200);
+        }
+    }
+
+    #[test]
+    fn test_arena_reset_keeps_largest_block() {
+        let mut arena = layout_arena(1024, 1 << 20);
+        arena.set_retain_policy(RetainPolicy::Largest);
+
+        unsafe {
+            arena.alloc::<u8>(1000).expect("allocation failed");
+            arena.alloc::<u8>(1000).expect("allocation failed");
+            arena.alloc::<u8>(50_000).expect("allocation failed");
+            assert_eq!(arena.block_count(), 3);
+
+            arena.reset();
+            assert_eq!(arena.block_count(), 1);
+            assert_eq!(arena.capacity(), 50_000);
+            assert_eq!(arena.used_bytes(), 0);
+
+            // The kept block serves the next round without growing
+            arena.alloc::<u8>(40_000).expect("allocation failed");
+            assert_eq!(arena.block_count(), 1);
+        }
+    }
+
+    #[test]
+    fn test_arena_reset_can_keep_all_blocks() {
+        let mut arena = ThreadArena::with_max_bytes(1024, 1 << 20);
+        arena.set_retain_policy(RetainPolicy::All);
+
+        unsafe {
+            arena.alloc::<u8>(1000).expect("allocation failed");
+            arena.alloc::<u8>(1000).expect("allocation failed");
+            let capacity = arena.capacity();
+
+            arena.reset();
+            assert_eq!(arena.block_count(), 2);
+            assert_eq!(arena.used_bytes(), 0);
+            assert_eq!(arena.available_bytes(), capacity);
+
+            // Same pattern again reuses both blocks
+            arena.alloc::<u8>(1000).expect("allocation failed");
+            arena.alloc::<u8>(1000).expect("allocation failed");
+            assert_eq!(arena.capacity(), capacity);
+        }
+    }
+
     #[test]
     fn test_arena_reset() {
         let mut arena = ThreadArena::new(1024);
@@ -210,21 +1094,408 @@ mod tests {
 
     #[test]
     fn test_with_arena() {
-        let result = with_arena(|arena| {
-            unsafe {
-                let ptr = arena.alloc::<i32>(10).expect("allocation failed");
-                // Write some data
-                for i in 0..10 {
-                    *ptr.add(i) = i as i32;
-                }
-                42
+        let result = with_arena(|scope| {
+            let buf = scope.alloc_zeroed::<i32>(10).expect("allocation failed");
+            // Write some data
+            for (i, x) in buf.iter_mut().enumerate() {
+                *x = i as i32;
             }
+            42
         });
         
         assert_eq!(result, 42);
         
         // Arena should be reset
-        let (used, _, _) = arena_stats();
-        assert_eq!(used, 0);
+        assert_eq!(arena_stats().used_bytes, 0);
+    }
+
+    #[test]
+    fn test_arena_simd_alignment() {
+        let mut arena = ThreadArena::with_max_bytes(4096, 1 << 20);
+
+        unsafe {
+            // Block start
+            let first = arena.alloc::<u8>(3).expect("allocation failed");
+            assert_eq!(first as usize % SIMD_ALIGN, 0);
+
+            // Mixed sizes and alignments, including across a new block
+            for (count, align) in [(5, 32), (17, 64), (1, 8), (1000, 64), (3, 128), (900, 32)] {
+                let ptr = arena.alloc_aligned::<f32>(count, align).expect("allocation failed");
+                assert_eq!(ptr as usize % align, 0, "count {} align {}", count, align);
+            }
+            assert!(arena.block_count() > 1);
+
+            // Float scratch defaults to SIMD alignment, other types don't pay for it
+            arena.alloc::<u8>(1).expect("allocation failed");
+            let floats = arena.alloc::<f64>(3).expect("allocation failed");
+            assert_eq!(floats as usize % SIMD_ALIGN, 0);
+        }
+    }
+
+    #[test]
+    fn test_arena_used_bytes_include_padding() {
+        let mut arena = layout_arena(1024, 1024);
+
+        unsafe {
+            arena.alloc::<u8>(1).expect("allocation failed");
+            arena.alloc::<f32>(1).expect("allocation failed");
+            assert_eq!(arena.used_bytes(), SIMD_ALIGN + 4);
+            assert_eq!(arena.available_bytes(), 1024 - SIMD_ALIGN - 4);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "power of two")]
+    fn test_arena_rejects_bad_alignment() {
+        let mut arena = ThreadArena::new(1024);
+        unsafe {
+            arena.alloc_aligned::<f32>(4, 48);
+        }
+    }
+
+    #[test]
+    fn test_arena_peak_survives_reset() {
+        let mut arena = ThreadArena::with_max_bytes(4096, 1 << 20);
+
+        unsafe {
+            arena.alloc::<u8>(3000).expect("allocation failed");
+            arena.alloc::<u8>(2000).expect("allocation failed");
+            assert_eq!(arena.peak_bytes(), 5000);
+            arena.reset();
+            assert_eq!(arena.used_bytes(), 0);
+
+            arena.alloc::<u8>(100).expect("allocation failed");
+            assert_eq!(arena.peak_bytes(), 5000);
+            assert!(global_arena_peaks().0 >= 5000);
+
+            arena.reset_peak();
+            assert_eq!(arena.peak_bytes(), 100);
+        }
+    }
+
+    #[test]
+    fn test_arena_counts_failed_allocations() {
+        let mut arena = layout_arena(100, 100);
+        let global_before = global_arena_peaks().1;
+
+        unsafe {
+            arena.alloc::<u8>(90).expect("allocation failed");
+            assert_eq!(arena.failed_allocs(), 0);
+            assert!(arena.alloc::<u8>(20).is_none());
+            assert!(arena.alloc::<u8>(200).is_none());
+        }
+        assert_eq!(arena.failed_allocs(), 2);
+        assert!(global_arena_peaks().1 - global_before >= 2);
+    }
+
+    #[test]
+    fn test_arena_resize_up_and_down() {
+        let mut arena = ThreadArena::with_max_bytes(1024, 1024);
+
+        unsafe {
+            assert!(arena.alloc::<u8>(4096).is_none());
+            arena.resize(8192).expect("resize failed");
+            assert_eq!(arena.capacity(), 8192);
+            arena.alloc::<u8>(4096).expect("allocation failed");
+            arena.reset();
+
+            arena.resize(512).expect("resize failed");
+            assert_eq!(arena.capacity(), 512);
+            assert_eq!(arena.block_count(), 1);
+            arena.alloc::<u8>(500).expect("allocation failed");
+        }
+    }
+
+    #[test]
+    fn test_arena_resize_rejected_while_in_use() {
+        let mut arena = ThreadArena::new(1024);
+
+        unsafe {
+            arena.alloc::<u8>(100).expect("allocation failed");
+        }
+        assert_eq!(arena.resize(4096), Err(ArenaError::InUse(100)));
+        assert_eq!(arena.capacity(), 1024);
+        arena.reset();
+        assert_eq!(arena.resize(0), Err(ArenaError::ZeroSize));
+        assert!(arena.resize(4096).is_ok());
+
+        // The thread's own arena is busy inside with_arena
+        with_arena(|_arena| assert_eq!(set_arena_size(4096), Err(ArenaError::InUse(0))));
+    }
+
+    #[test]
+    fn test_set_arena_size_reaches_other_threads() {
+        use std::sync::Barrier;
+
+        let previous = configured_arena_size();
+        let new_size = previous + 4096;
+        let sized = Barrier::new(2);
+        let resized = Barrier::new(2);
+        std::thread::scope(|scope| {
+            let worker = scope.spawn(|| {
+                // Arena built at the old size
+                with_arena(|_arena| ());
+                sized.wait();
+                resized.wait();
+                with_arena(|_scope| arena_stats().capacity)
+            });
+            sized.wait();
+            set_arena_size(new_size).expect("set_arena_size failed");
+            assert_eq!(arena_stats().capacity, new_size);
+            resized.wait();
+            assert_eq!(worker.join().unwrap(), new_size);
+        });
+        assert_eq!(configured_arena_size(), new_size);
+        set_arena_size(previous).expect("set_arena_size failed");
+    }
+
+    #[test]
+    fn test_arena_scope_slices() {
+        let sum = with_arena(|scope| {
+            let a = scope.alloc_zeroed::<f32>(1000).expect("allocation failed");
+            let b = scope.alloc_zeroed::<i32>(10).expect("allocation failed");
+            assert!(a.iter().all(|&x| x == 0.0));
+            assert_eq!(a.as_ptr() as usize % SIMD_ALIGN, 0);
+
+            // Both slices live and writable at once
+            a.iter_mut().enumerate().for_each(|(i, x)| *x = i as f32);
+            b.fill(7);
+            assert!(scope.used_bytes() >= 4040);
+            a.iter().sum::<f32>() + b.iter().sum::<i32>() as f32
+        });
+        assert_eq!(sum, 499_500.0 + 70.0);
+        assert_eq!(arena_stats().used_bytes, 0);
+
+        // Zeroed even where the last scope left data
+        with_arena(|scope| {
+            let a = scope.alloc_zeroed::<f32>(1000).expect("allocation failed");
+            assert!(a.iter().all(|&x| x == 0.0));
+        });
+    }
+
+    #[test]
+    fn test_arena_scope_uninit() {
+        let total = with_arena(|scope| {
+            let buf = scope.alloc_uninit::<u64>(64).expect("allocation failed");
+            for (i, slot) in buf.iter_mut().enumerate() {
+                slot.write(i as u64);
+            }
+            buf.iter().map(|slot| unsafe { slot.assume_init() }).sum::<u64>()
+        });
+        assert_eq!(total, 2016);
+    }
+
+    #[test]
+    fn test_nested_with_arena_keeps_outer_allocations() {
+        with_arena(|outer| {
+            let kept = outer.alloc_zeroed::<u32>(256).expect("allocation failed");
+            kept.fill(0xABCD);
+            let used = arena_stats().used_bytes;
+
+            with_arena(|inner| {
+                let scratch = inner.alloc_zeroed::<u32>(100_000).expect("allocation failed");
+                scratch.fill(1);
+                assert!(arena_stats().used_bytes >= used + 400_000);
+            });
+
+            // Back to exactly the outer scope's usage, its data untouched
+            assert_eq!(arena_stats().used_bytes, used);
+            assert!(kept.iter().all(|&x| x == 0xABCD));
+            let more = outer.alloc_zeroed::<u32>(16).expect("allocation failed");
+            more.fill(2);
+            assert!(kept.iter().all(|&x| x == 0xABCD));
+        });
+        assert_eq!(arena_stats().used_bytes, 0);
+    }
+
+    #[test]
+    fn test_arena_rewind_restores_offsets() {
+        let mut arena = layout_arena(1024, 1 << 20);
+
+        unsafe {
+            let first = arena.alloc::<u8>(100).expect("allocation failed");
+            let mark = arena.mark();
+            // Fill the block, chain a new one and add a dedicated block
+            arena.alloc::<u8>(900).expect("allocation failed");
+            arena.alloc::<u8>(900).expect("allocation failed");
+            arena.alloc::<u8>(5000).expect("allocation failed");
+            assert_eq!(arena.block_count(), 3);
+
+            arena.rewind(mark);
+            assert_eq!(arena.used_bytes(), 100);
+            // The next allocation continues right after the first
+            assert_eq!(arena.alloc::<u8>(1).expect("allocation failed"), first.add(100));
+            // Blocks used after the mark are kept for reuse
+            assert_eq!(arena.block_count(), 3);
+            arena.alloc::<u8>(4000).expect("allocation failed");
+            assert_eq!(arena.block_count(), 3);
+        }
+    }
+
+    #[test]
+    fn test_arena_region_rewinds_on_panic() {
+        with_arena(|outer| {
+            let kept = outer.alloc_zeroed::<u8>(64).expect("allocation failed");
+            kept.fill(7);
+            let used = arena_stats().used_bytes;
+
+            let result = std::panic::catch_unwind(|| {
+                with_arena(|inner| {
+                    inner.alloc_zeroed::<u8>(4096).expect("allocation failed");
+                    panic!("kernel failed");
+                })
+            });
+            assert!(result.is_err());
+            assert_eq!(arena_stats().used_bytes, used);
+            assert!(kept.iter().all(|&x| x == 7));
+        });
+
+        let result = std::panic::catch_unwind(|| {
+            with_arena(|scope| {
+                scope.alloc_zeroed::<u8>(4096).expect("allocation failed");
+                panic!("kernel failed");
+            })
+        });
+        assert!(result.is_err());
+        assert_eq!(arena_stats().used_bytes, 0);
+        // No region left open
+        assert_eq!(ArenaRegion::enter().depth(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "outer region")]
+    fn test_arena_region_rejects_outer_alloc_while_inner_open() {
+        let outer = ArenaRegion::enter();
+        let _inner = ArenaRegion::enter();
+        unsafe {
+            outer.alloc::<u8>(16);
+        }
+    }
+
+    #[test]
+    fn test_alloc_or_heap_uses_arena_then_heap() {
+        let _guard = crate::scheduler::TEST_STATE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut arena = ThreadArena::with_max_bytes(1024, 1024);
+        let scope = ArenaScope::new(&mut arena);
+
+        let (arena_before, heap_before) = allocation_counters();
+        let count_before = heap_fallback_count();
+        let mut fits = scope.alloc_or_heap::<f32>(100);
+        assert!(!fits.is_heap());
+        assert_eq!(fits.len(), 100);
+        assert!(scope.used_bytes() >= 400);
+        let (arena_mid, heap_mid) = allocation_counters();
+        assert!(arena_mid - arena_before >= 400);
+        assert_eq!(heap_fallback_count(), count_before);
+
+        let fallbacks_before = crate::scheduler::stats::get_stats().arena_heap_fallbacks;
+        let mut spilled = scope.alloc_or_heap::<f32>(1000);
+        assert!(spilled.is_heap());
+        assert_eq!(spilled.len(), 1000);
+        assert!(spilled.iter().all(|&x| x == 0.0));
+        assert!(allocation_counters().1 - heap_mid >= 4000);
+        assert!(heap_mid >= heap_before);
+        assert!(heap_fallback_count() > count_before);
+        assert!(crate::scheduler::stats::get_stats().arena_heap_fallbacks > fallbacks_before);
+
+        fits.fill(1.0);
+        spilled.fill(2.0);
+        assert!(fits.iter().all(|&x| x == 1.0));
+    }
+
+    #[test]
+    fn test_heap_scratch_outlives_its_arena() {
+        let _guard = crate::scheduler::TEST_STATE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut spilled = {
+            let mut arena = ThreadArena::with_max_bytes(256, 256);
+            let scope = ArenaScope::new(&mut arena);
+            match scope.alloc_or_heap::<u8>(4096) {
+                ScratchBuf::Heap(vec) => vec,
+                ScratchBuf::Arena(_) => panic!("expected a heap fallback"),
+            }
+        };
+        // Arena and scope are gone; the heap buffer is the guard's own
+        spilled[4095] = 3;
+        assert_eq!(spilled.iter().map(|&x| x as u32).sum::<u32>(), 3);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn test_arena_debug_detects_overflow() {
+        let mut arena = ThreadArena::new(1024);
+        arena.set_debug(true);
+
+        unsafe {
+            let first = arena.alloc::<u8>(32).expect("allocation failed");
+            let second = arena.alloc::<u8>(24).expect("allocation failed");
+            // Fresh memory is poisoned, not zero
+            assert!((0..24).all(|i| *second.add(i) == POISON_BYTE));
+            assert_eq!(arena.check_integrity(), Ok(()));
+
+            // Write three bytes past the end of `second`
+            std::ptr::write_bytes(second.add(24), 0, 3);
+            *first.add(31) = 1;
+            assert_eq!(
+                arena.check_integrity(),
+                Err(ArenaCorruption { allocation: 1, size: 24, offset: 24 })
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "arena allocation #0 (10 bytes) overran its end: canary clobbered at byte offset 12")]
+    fn test_arena_debug_panics_on_next_allocation() {
+        let mut arena = ThreadArena::new(1024);
+        arena.set_debug(true);
+
+        unsafe {
+            let ptr = arena.alloc::<u8>(10).expect("allocation failed");
+            *ptr.add(12) = 0;
+            arena.alloc::<u8>(10);
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "arena allocation #0")]
+    fn test_arena_debug_checks_on_reset() {
+        let mut arena = ThreadArena::new(1024);
+        arena.set_debug(true);
+
+        unsafe {
+            let ptr = arena.alloc::<u32>(4).expect("allocation failed");
+            *ptr.add(4) = 0;
+        }
+        arena.reset();
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn test_arena_debug_keeps_canaries_out_of_used_bytes() {
+        let mut arena = ThreadArena::with_max_bytes(1024, 1024);
+        arena.set_debug(true);
+
+        unsafe {
+            arena.alloc::<u8>(10).expect("allocation failed");
+            arena.alloc::<u8>(6).expect("allocation failed");
+        }
+        // Only the requested bytes count; each canary still takes block space
+        assert_eq!(arena.used_bytes(), 10 + 6);
+        assert_eq!(arena.available_bytes(), 1024 - (10 + 6 + 2 * CANARY_LEN));
+        assert_eq!(arena.peak_bytes(), arena.used_bytes());
+    }
+
+    #[test]
+    fn test_allocation_counters_grow() {
+        let (arena_before, heap_before) = allocation_counters();
+        with_arena(|scope| {
+            scope.alloc_uninit::<f32>(256).expect("allocation failed");
+        });
+        record_heap_fallback(100);
+
+        let (arena_after, heap_after) = allocation_counters();
+        assert!(arena_after - arena_before >= 1024);
+        assert!(heap_after - heap_before >= 100);
     }
 }
@@ -0,0 +1,201 @@
+// ============================================================================
+// Asynchronous Operation Submission
+// ============================================================================
+//
+// RESPONSIBILITIES:
+// - Run submitted operations on corepy's pool without blocking the caller
+// - Hand out integer handles and keep each result until it is collected
+//
+// DESIGN:
+// - One slot (Mutex + Condvar) per handle in a global table
+// - wait()/is_ready() never consume; take_result() removes the handle, and
+//   only once the operation has finished
+// - Whatever the caller attaches as `keep_alive` (the Python arrays behind the
+//   raw pointers) is dropped with the handle, so buffers outlive the work
+// - Jobs run on a worker thread, so each op takes its sequential path;
+//   concurrency comes from submitting several
+
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// Value produced by an asynchronous operation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AsyncValue {
+    /// Output written into caller buffers (matmul)
+    Unit,
+    F32(f32),
+}
+
+/// Completed result, or why the operation failed
+pub type AsyncResult = Result<AsyncValue, String>;
+
+/// Why a handle operation was rejected
+#[derive(Debug, Clone, PartialEq)]
+pub enum AsyncError {
+    /// Never handed out, or already collected
+    UnknownHandle(u64),
+    /// take_result() before the operation finished
+    StillRunning(u64),
+}
+
+impl std::fmt::Display for AsyncError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AsyncError::UnknownHandle(handle) => write!(f, "unknown or already collected async handle {}", handle),
+            AsyncError::StillRunning(handle) => {
+                write!(f, "async operation {} is still running; wait() for it first", handle)
+            }
+        }
+    }
+}
+
+/// Result slot shared by the worker and waiters
+#[derive(Default)]
+struct Slot {
+    result: Mutex<Option<AsyncResult>>,
+    done: Condvar,
+}
+
+/// Table entry: the slot plus what must live until the handle is collected
+struct Pending {
+    slot: Arc<Slot>,
+    _keep_alive: Option<Box<dyn Send>>,
+}
+
+lazy_static! {
+    static ref ASYNC_OPS: Mutex<HashMap<u64, Pending>> = Mutex::new(HashMap::new());
+}
+
+/// Next handle handed out by submit()
+static NEXT_HANDLE: AtomicU64 = AtomicU64::new(1);
+
+fn slot(handle: u64) -> Result<Arc<Slot>, AsyncError> {
+    ASYNC_OPS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&handle)
+        .map(|pending| pending.slot.clone())
+        .ok_or(AsyncError::UnknownHandle(handle))
+}
+
+/// Queue `job` on corepy's pool and return its handle
+///
+/// `keep_alive` is held until the handle is collected with take_result().
+pub fn submit<F>(job: F, keep_alive: Option<Box<dyn Send>>) -> u64
+where
+    F: FnOnce() -> AsyncResult + Send + 'static,
+{
+    let handle = NEXT_HANDLE.fetch_add(1, Ordering::Relaxed);
+    let slot = Arc::new(Slot::default());
+    ASYNC_OPS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(handle, Pending { slot: slot.clone(), _keep_alive: keep_alive });
+
+    crate::scheduler::rayon_pool::spawn(move || {
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(job))
+            .unwrap_or_else(|_| Err("async operation panicked".to_string()));
+        *slot.result.lock().unwrap_or_else(|e| e.into_inner()) = Some(result);
+        slot.done.notify_all();
+    });
+    handle
+}
+
+/// Whether the operation behind `handle` has finished
+pub fn is_ready(handle: u64) -> Result<bool, AsyncError> {
+    let slot = slot(handle)?;
+    let ready = slot.result.lock().unwrap_or_else(|e| e.into_inner()).is_some();
+    Ok(ready)
+}
+
+/// Block until the operation finishes or `timeout` passes; true when finished
+///
+/// The result stays in place either way.
+pub fn wait(handle: u64, timeout: Option<Duration>) -> Result<bool, AsyncError> {
+    let slot = slot(handle)?;
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    let mut result = slot.result.lock().unwrap_or_else(|e| e.into_inner());
+    while result.is_none() {
+        result = match deadline {
+            None => slot.done.wait(result).unwrap_or_else(|e| e.into_inner()),
+            Some(deadline) => {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if remaining.is_zero() {
+                    return Ok(false);
+                }
+                slot.done.wait_timeout(result, remaining).unwrap_or_else(|e| e.into_inner()).0
+            }
+        };
+    }
+    Ok(true)
+}
+
+/// Collect a finished operation's result, releasing its handle
+///
+/// Fails (and keeps the handle) while the operation is still running.
+pub fn take_result(handle: u64) -> Result<AsyncResult, AsyncError> {
+    let mut table = ASYNC_OPS.lock().unwrap_or_else(|e| e.into_inner());
+    let pending = table.get(&handle).ok_or(AsyncError::UnknownHandle(handle))?;
+    let result = pending.slot.result.lock().unwrap_or_else(|e| e.into_inner()).take();
+    match result {
+        Some(result) => {
+            // Release keep_alive outside the table lock (it may run Python finalizers)
+            let pending = table.remove(&handle);
+            drop(table);
+            drop(pending);
+            Ok(result)
+        }
+        None => Err(AsyncError::StillRunning(handle)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn test_submit_poll_and_collect() {
+        let handles: Vec<u64> = (0..4)
+            .map(|i| submit(move || Ok(AsyncValue::F32(i as f32 * 1.5)), None))
+            .collect();
+
+        for (i, &handle) in handles.iter().enumerate() {
+            assert!(wait(handle, None).unwrap());
+            assert!(is_ready(handle).unwrap());
+            assert_eq!(take_result(handle).unwrap(), Ok(AsyncValue::F32(i as f32 * 1.5)));
+            assert_eq!(take_result(handle).unwrap_err(), AsyncError::UnknownHandle(handle));
+        }
+    }
+
+    #[test]
+    fn test_timeout_keeps_result() {
+        let (release, gate) = mpsc::channel::<()>();
+        let handle = submit(move || {
+            gate.recv().unwrap();
+            Ok(AsyncValue::Unit)
+        }, None);
+
+        assert!(!wait(handle, Some(Duration::from_millis(20))).unwrap());
+        assert!(!is_ready(handle).unwrap());
+        assert_eq!(take_result(handle).unwrap_err(), AsyncError::StillRunning(handle));
+
+        release.send(()).unwrap();
+        assert!(wait(handle, Some(Duration::from_secs(10))).unwrap());
+        assert_eq!(take_result(handle).unwrap(), Ok(AsyncValue::Unit));
+    }
+
+    #[test]
+    fn test_keep_alive_dropped_on_collect() {
+        let token = Arc::new(());
+        let handle = submit(|| Err("bad shape".to_string()), Some(Box::new(token.clone())));
+        assert!(wait(handle, None).unwrap());
+        assert_eq!(Arc::strong_count(&token), 2);
+
+        assert_eq!(take_result(handle).unwrap(), Err("bad shape".to_string()));
+        assert_eq!(Arc::strong_count(&token), 1);
+    }
+}
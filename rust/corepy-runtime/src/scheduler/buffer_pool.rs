@@ -0,0 +1,407 @@
+// ============================================================================
+// Output Buffer Pool
+// ============================================================================
+//
+// RESPONSIBILITIES:
+// - Hand out reusable, 64-byte-aligned output buffers by integer handle, so
+//   chains of element-wise calls don't allocate a fresh array per temporary
+// - Keep released buffers for reuse, within a retained-bytes cap
+//
+// DESIGN:
+// - Requests round up to a size class (the next power of two, at least
+//   SIMD_ALIGN); released buffers wait on their class's free list
+// - acquire() takes from the free list when it can and zeroes only when asked
+// - A buffer's address never changes while it is held; buffer_ptr() feeds it
+//   to the pointer-based ops
+// - Released bytes are capped by COREPY_BUFFER_POOL_MAX_BYTES (64 MB by
+//   default); over the cap, whole size classes are freed least recently
+//   used first
+// - One global Mutex; acquire/release are O(1) apart from evictions
+
+use lazy_static::lazy_static;
+use std::alloc::{self, Layout};
+use std::collections::HashMap;
+use std::env;
+use std::ptr::NonNull;
+use std::sync::Mutex;
+
+use crate::scheduler::arena::SIMD_ALIGN;
+use crate::tensor::dtype::DType;
+
+/// Default cap on bytes kept in free lists: 64 MB
+const DEFAULT_POOL_MAX_BYTES: usize = 64 * 1024 * 1024;
+
+/// Why a pool operation was rejected
+#[derive(Debug, Clone, PartialEq)]
+pub enum PoolError {
+    /// Never handed out, or already released
+    UnknownHandle(u64),
+    /// A buffer of zero bytes was requested
+    ZeroSize,
+    /// dtype name not recognised
+    UnknownDtype(String),
+    /// Byte count is not a whole number of elements (bytes, element size)
+    PartialElement(usize, usize),
+    /// No size class or allocation can hold this many bytes
+    TooLarge(usize),
+}
+
+impl std::fmt::Display for PoolError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PoolError::UnknownHandle(handle) => write!(f, "unknown or already released buffer handle {}", handle),
+            PoolError::ZeroSize => write!(f, "buffer size must be at least 1 byte"),
+            PoolError::UnknownDtype(dtype) => write!(f, "unknown dtype '{}'", dtype),
+            PoolError::PartialElement(bytes, size) => {
+                write!(f, "{} bytes is not a whole number of {}-byte elements", bytes, size)
+            }
+            PoolError::TooLarge(bytes) => write!(f, "buffer of {} bytes is too large to allocate", bytes),
+        }
+    }
+}
+
+/// Retained-bytes cap: COREPY_BUFFER_POOL_MAX_BYTES env var or
+/// DEFAULT_POOL_MAX_BYTES
+pub fn configured_pool_max_bytes() -> usize {
+    env::var("COREPY_BUFFER_POOL_MAX_BYTES")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_POOL_MAX_BYTES)
+}
+
+/// Size class serving a `bytes`-byte request, None past the largest power of two
+fn size_class(bytes: usize) -> Option<usize> {
+    bytes.checked_next_power_of_two().map(|class| class.max(SIMD_ALIGN))
+}
+
+/// One SIMD_ALIGN-aligned allocation of a whole size class
+struct Buffer {
+    ptr: NonNull<u8>,
+    len: usize,
+}
+
+// The buffer owns its allocation outright
+unsafe impl Send for Buffer {}
+
+impl Buffer {
+    fn new(len: usize, zeroed: bool) -> Result<Self, PoolError> {
+        let layout = Self::layout(len)?;
+        let ptr = unsafe {
+            if zeroed {
+                alloc::alloc_zeroed(layout)
+            } else {
+                alloc::alloc(layout)
+            }
+        };
+        let ptr = NonNull::new(ptr).unwrap_or_else(|| alloc::handle_alloc_error(layout));
+        Ok(Buffer { ptr, len })
+    }
+
+    fn layout(len: usize) -> Result<Layout, PoolError> {
+        Layout::from_size_align(len, SIMD_ALIGN).map_err(|_| PoolError::TooLarge(len))
+    }
+}
+
+impl Drop for Buffer {
+    fn drop(&mut self) {
+        let layout = Self::layout(self.len).expect("layout was valid at allocation");
+        unsafe { alloc::dealloc(self.ptr.as_ptr(), layout) }
+    }
+}
+
+/// Released buffers of one size class
+#[derive(Default)]
+struct FreeList {
+    buffers: Vec<Buffer>,
+    /// Pool clock at the class's last acquire or release
+    last_used: u64,
+}
+
+/// Counter values at one point in time
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PoolStats {
+    /// Buffers handed out and not yet released
+    pub held_buffers: usize,
+    pub held_bytes: usize,
+    /// Released buffers kept for reuse
+    pub retained_buffers: usize,
+    pub retained_bytes: usize,
+    pub max_retained_bytes: usize,
+    /// acquire() calls served from a free list
+    pub hits: u64,
+    /// acquire() calls that allocated
+    pub misses: u64,
+    /// Buffers freed to stay under the cap
+    pub evictions: u64,
+}
+
+/// Handle table and free lists
+pub struct BufferPool {
+    held: HashMap<u64, Buffer>,
+    free: HashMap<usize, FreeList>,
+    next_handle: u64,
+    /// Bumped by every acquire/release; orders size classes for eviction
+    clock: u64,
+    held_bytes: usize,
+    retained_bytes: usize,
+    max_retained_bytes: usize,
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+}
+
+impl BufferPool {
+    /// Pool keeping at most `max_retained_bytes` of released buffers
+    pub fn new(max_retained_bytes: usize) -> Self {
+        BufferPool {
+            held: HashMap::new(),
+            free: HashMap::new(),
+            next_handle: 1,
+            clock: 0,
+            held_bytes: 0,
+            retained_bytes: 0,
+            max_retained_bytes,
+            hits: 0,
+            misses: 0,
+            evictions: 0,
+        }
+    }
+
+    /// Hand out a buffer of at least `bytes` bytes holding whole `dtype`
+    /// elements, zeroed if `zeroed` (else with unspecified contents)
+    pub fn acquire(&mut self, bytes: usize, dtype: &str, zeroed: bool) -> Result<u64, PoolError> {
+        let element = DType::from_name(dtype).map(DType::size).ok_or_else(|| PoolError::UnknownDtype(dtype.to_string()))?;
+        if bytes == 0 {
+            return Err(PoolError::ZeroSize);
+        }
+        if !bytes.is_multiple_of(element) {
+            return Err(PoolError::PartialElement(bytes, element));
+        }
+
+        let class = size_class(bytes).ok_or(PoolError::TooLarge(bytes))?;
+        self.clock += 1;
+        let reused = self.free.get_mut(&class).and_then(|list| {
+            list.last_used = self.clock;
+            list.buffers.pop()
+        });
+        let buffer = match reused {
+            Some(buffer) => {
+                self.hits += 1;
+                self.retained_bytes -= class;
+                if zeroed {
+                    unsafe { std::ptr::write_bytes(buffer.ptr.as_ptr(), 0, buffer.len) };
+                }
+                buffer
+            }
+            None => {
+                let buffer = Buffer::new(class, zeroed).map_err(|_| PoolError::TooLarge(bytes))?;
+                self.misses += 1;
+                buffer
+            }
+        };
+
+        let handle = self.next_handle;
+        self.next_handle += 1;
+        self.held_bytes += class;
+        self.held.insert(handle, buffer);
+        Ok(handle)
+    }
+
+    /// Return a buffer to its free list; its address must not be used again
+    pub fn release(&mut self, handle: u64) -> Result<(), PoolError> {
+        let buffer = self.held.remove(&handle).ok_or(PoolError::UnknownHandle(handle))?;
+        let class = buffer.len;
+        self.held_bytes -= class;
+        self.retained_bytes += class;
+        self.clock += 1;
+        let list = self.free.entry(class).or_default();
+        list.last_used = self.clock;
+        list.buffers.push(buffer);
+        self.evict();
+        Ok(())
+    }
+
+    /// Address of a held buffer
+    pub fn ptr(&self, handle: u64) -> Result<usize, PoolError> {
+        self.held
+            .get(&handle)
+            .map(|buffer| buffer.ptr.as_ptr() as usize)
+            .ok_or(PoolError::UnknownHandle(handle))
+    }
+
+    /// Free least recently used size classes until under the cap
+    fn evict(&mut self) {
+        while self.retained_bytes > self.max_retained_bytes {
+            let Some(class) = self
+                .free
+                .iter()
+                .filter(|(_, list)| !list.buffers.is_empty())
+                .min_by_key(|(_, list)| list.last_used)
+                .map(|(&class, _)| class)
+            else {
+                break;
+            };
+            if let Some(list) = self.free.remove(&class) {
+                self.retained_bytes -= class * list.buffers.len();
+                self.evictions += list.buffers.len() as u64;
+            }
+        }
+    }
+
+    pub fn stats(&self) -> PoolStats {
+        PoolStats {
+            held_buffers: self.held.len(),
+            held_bytes: self.held_bytes,
+            retained_buffers: self.free.values().map(|list| list.buffers.len()).sum(),
+            retained_bytes: self.retained_bytes,
+            max_retained_bytes: self.max_retained_bytes,
+            hits: self.hits,
+            misses: self.misses,
+            evictions: self.evictions,
+        }
+    }
+}
+
+lazy_static! {
+    static ref POOL: Mutex<BufferPool> = Mutex::new(BufferPool::new(configured_pool_max_bytes()));
+}
+
+fn pool() -> std::sync::MutexGuard<'static, BufferPool> {
+    POOL.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Acquire a buffer from the global pool (see BufferPool::acquire)
+pub fn acquire_buffer(bytes: usize, dtype: &str, zeroed: bool) -> Result<u64, PoolError> {
+    pool().acquire(bytes, dtype, zeroed)
+}
+
+/// Release a buffer to the global pool
+pub fn release_buffer(handle: u64) -> Result<(), PoolError> {
+    pool().release(handle)
+}
+
+/// Address of a buffer held from the global pool
+pub fn buffer_ptr(handle: u64) -> Result<usize, PoolError> {
+    pool().ptr(handle)
+}
+
+pub fn pool_stats() -> PoolStats {
+    pool().stats()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_acquire_release_cycle() {
+        let mut pool = BufferPool::new(1 << 20);
+        let handle = pool.acquire(1000, "float32", true).unwrap();
+        let ptr = pool.ptr(handle).unwrap();
+        assert_eq!(ptr % SIMD_ALIGN, 0);
+        let buf = unsafe { std::slice::from_raw_parts(ptr as *const f32, 250) };
+        assert!(buf.iter().all(|&x| x == 0.0));
+
+        let stats = pool.stats();
+        assert_eq!((stats.held_buffers, stats.held_bytes), (1, 1024));
+
+        pool.release(handle).unwrap();
+        assert_eq!(pool.release(handle), Err(PoolError::UnknownHandle(handle)));
+        assert_eq!(pool.ptr(handle), Err(PoolError::UnknownHandle(handle)));
+        let stats = pool.stats();
+        assert_eq!((stats.held_buffers, stats.retained_buffers, stats.retained_bytes), (0, 1, 1024));
+    }
+
+    #[test]
+    fn test_pointer_stable_while_held() {
+        let mut pool = BufferPool::new(1 << 20);
+        let handle = pool.acquire(4096, "float64", false).unwrap();
+        let ptr = pool.ptr(handle).unwrap();
+        unsafe { std::ptr::write_bytes(ptr as *mut u8, 7, 4096) };
+
+        // Other traffic on the same and other classes
+        for bytes in [4096, 100, 1 << 16] {
+            let other = pool.acquire(bytes, "uint8", true).unwrap();
+            pool.release(other).unwrap();
+        }
+        assert_eq!(pool.ptr(handle).unwrap(), ptr);
+        let buf = unsafe { std::slice::from_raw_parts(ptr as *const u8, 4096) };
+        assert!(buf.iter().all(|&x| x == 7));
+    }
+
+    #[test]
+    fn test_released_buffer_reused_and_zeroed_on_demand() {
+        let mut pool = BufferPool::new(1 << 20);
+        let first = pool.acquire(3000, "int32", false).unwrap();
+        let ptr = pool.ptr(first).unwrap();
+        unsafe { std::ptr::write_bytes(ptr as *mut u8, 0xFF, 3000) };
+        pool.release(first).unwrap();
+
+        // Same size class (4096), so the same allocation comes back
+        let second = pool.acquire(4000, "int32", true).unwrap();
+        assert_ne!(second, first);
+        assert_eq!(pool.ptr(second).unwrap(), ptr);
+        let buf = unsafe { std::slice::from_raw_parts(ptr as *const u8, 4096) };
+        assert!(buf.iter().all(|&x| x == 0));
+
+        let stats = pool.stats();
+        assert_eq!((stats.hits, stats.misses), (1, 1));
+    }
+
+    #[test]
+    fn test_cap_evicts_least_recently_used_class() {
+        let mut pool = BufferPool::new(4096 + 2048);
+        let a = pool.acquire(4096, "uint8", false).unwrap();
+        let b = pool.acquire(2048, "uint8", false).unwrap();
+        let c = pool.acquire(1024, "uint8", false).unwrap();
+
+        pool.release(a).unwrap();
+        pool.release(b).unwrap();
+        assert_eq!(pool.stats().evictions, 0);
+
+        // Over the cap: the 4096 class was used least recently
+        pool.release(c).unwrap();
+        let stats = pool.stats();
+        assert_eq!(stats.evictions, 1);
+        assert_eq!(stats.retained_bytes, 2048 + 1024);
+        assert!(stats.retained_bytes <= stats.max_retained_bytes);
+
+        // 4096 has to allocate again, 2048 is still pooled
+        pool.acquire(2048, "uint8", false).unwrap();
+        pool.acquire(4096, "uint8", false).unwrap();
+        let stats = pool.stats();
+        assert_eq!((stats.hits, stats.misses), (1, 4));
+    }
+
+    #[test]
+    fn test_rejects_bad_requests() {
+        let mut pool = BufferPool::new(1 << 20);
+        assert_eq!(pool.acquire(0, "float32", false), Err(PoolError::ZeroSize));
+        assert_eq!(pool.acquire(6, "float32", false), Err(PoolError::PartialElement(6, 4)));
+        assert_eq!(
+            pool.acquire(8, "complex64", false),
+            Err(PoolError::UnknownDtype("complex64".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_rejects_sizes_without_a_class() {
+        let mut pool = BufferPool::new(1 << 20);
+        // No power of two holds it
+        let huge = usize::MAX - 7;
+        assert_eq!(pool.acquire(huge, "uint8", false), Err(PoolError::TooLarge(huge)));
+        // Class 2^63 exceeds isize::MAX, so no Layout describes it
+        let past_isize = (1 << 62) + 1;
+        assert_eq!(pool.acquire(past_isize, "uint8", false), Err(PoolError::TooLarge(past_isize)));
+        assert_eq!(pool.stats().misses, 0);
+    }
+
+    #[test]
+    fn test_global_pool_functions() {
+        let handle = acquire_buffer(256, "float32", true).unwrap();
+        assert_ne!(buffer_ptr(handle).unwrap(), 0);
+        assert!(pool_stats().held_buffers >= 1);
+        release_buffer(handle).unwrap();
+        assert!(buffer_ptr(handle).is_err());
+    }
+}
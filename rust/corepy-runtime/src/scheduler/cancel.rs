@@ -0,0 +1,209 @@
+// ============================================================================
+// Cooperative Cancellation
+// ============================================================================
+//
+// RESPONSIBILITIES:
+// - Let callers stop in-flight chunked operations (request_cancel)
+// - Turn Ctrl-C during a long operation into a cancellation
+// - Stop an operation once its per-call deadline passes (with_deadline)
+//
+// DESIGN:
+// - One process-wide flag, checked between chunks with a Relaxed load; it
+//   stays set (failing every new op) until clear_cancel()
+// - Chunks that see the flag skip their work, so output buffers of a
+//   cancelled op hold unspecified contents
+// - While an InterruptGuard is alive (Linux), SIGINT also sets a separate
+//   flag and is then passed on to the previous handler (Python's), so the
+//   interpreter still raises KeyboardInterrupt; that flag clears when the
+//   last guard drops
+// - Deadlines are per calling thread; dispatch reads deadline() once and
+//   hands it to its chunks, which pay one Instant comparison each (none
+//   without a deadline)
+
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+/// Set by request_cancel(), cleared by clear_cancel()
+static CANCEL_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Set by SIGINT while an InterruptGuard is alive
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    /// Deadline of operations dispatched from this thread (see with_deadline)
+    static DEADLINE: Cell<Option<Instant>> = const { Cell::new(None) };
+}
+
+/// Why an operation stopped early
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cancelled {
+    /// request_cancel() or Ctrl-C
+    Requested,
+    /// The calling thread's deadline passed
+    DeadlineExceeded,
+}
+
+impl std::fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Cancelled::Requested => write!(f, "operation cancelled (call clear_cancel() before running more work)"),
+            Cancelled::DeadlineExceeded => write!(f, "operation exceeded its timeout"),
+        }
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+/// Ask running (and later) chunked operations to stop
+pub fn request_cancel() {
+    CANCEL_REQUESTED.store(true, Ordering::Relaxed);
+}
+
+/// Allow operations to run again after request_cancel()
+pub fn clear_cancel() {
+    CANCEL_REQUESTED.store(false, Ordering::Relaxed);
+}
+
+/// Whether chunked operations should stop now
+#[inline]
+pub fn is_cancelled() -> bool {
+    CANCEL_REQUESTED.load(Ordering::Relaxed) || INTERRUPTED.load(Ordering::Relaxed)
+}
+
+/// Err once cancellation has been requested or this thread's deadline passed
+#[inline]
+pub fn check() -> Result<(), Cancelled> {
+    check_deadline(deadline())
+}
+
+/// Err once cancellation has been requested or `deadline` passed
+///
+/// For chunks running on workers, with the deadline read on the dispatching
+/// thread.
+#[inline]
+pub fn check_deadline(deadline: Option<Instant>) -> Result<(), Cancelled> {
+    if is_cancelled() {
+        Err(Cancelled::Requested)
+    } else if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+        Err(Cancelled::DeadlineExceeded)
+    } else {
+        Ok(())
+    }
+}
+
+/// Deadline of operations dispatched from this thread
+pub fn deadline() -> Option<Instant> {
+    DEADLINE.with(Cell::get)
+}
+
+/// Run `f` with operations dispatched from this thread failing with
+/// DeadlineExceeded after `deadline` (None = no deadline)
+///
+/// Nested calls keep the earlier of the two deadlines.
+pub fn with_deadline<R>(deadline: Option<Instant>, f: impl FnOnce() -> R) -> R {
+    struct Restore(Option<Instant>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            DEADLINE.with(|cell| cell.set(self.0));
+        }
+    }
+
+    let previous = self::deadline();
+    let effective = match (previous, deadline) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    };
+    let _restore = Restore(previous);
+    DEADLINE.with(|cell| cell.set(effective));
+    f()
+}
+
+/// While alive, SIGINT cancels running operations (no-op off Linux)
+pub struct InterruptGuard(());
+
+impl InterruptGuard {
+    pub fn new() -> Self {
+        #[cfg(target_os = "linux")]
+        sigint::acquire();
+        InterruptGuard(())
+    }
+}
+
+impl Default for InterruptGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for InterruptGuard {
+    fn drop(&mut self) {
+        #[cfg(target_os = "linux")]
+        sigint::release();
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod sigint {
+    use super::INTERRUPTED;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    /// Live guards, and the handler to restore when the last one drops
+    static GUARDS: Mutex<(usize, Option<libc::sigaction>)> = Mutex::new((0, None));
+
+    /// Previous handler, readable from the signal handler
+    static PREVIOUS_HANDLER: AtomicUsize = AtomicUsize::new(libc::SIG_DFL);
+    static PREVIOUS_SIGINFO: AtomicBool = AtomicBool::new(false);
+
+    extern "C" fn on_sigint(signum: libc::c_int, info: *mut libc::siginfo_t, context: *mut libc::c_void) {
+        INTERRUPTED.store(true, Ordering::Relaxed);
+        match PREVIOUS_HANDLER.load(Ordering::Relaxed) {
+            libc::SIG_DFL | libc::SIG_IGN => {}
+            handler if PREVIOUS_SIGINFO.load(Ordering::Relaxed) => unsafe {
+                let handler: extern "C" fn(libc::c_int, *mut libc::siginfo_t, *mut libc::c_void) =
+                    std::mem::transmute(handler);
+                handler(signum, info, context);
+            },
+            handler => unsafe {
+                let handler: extern "C" fn(libc::c_int) = std::mem::transmute(handler);
+                handler(signum);
+            },
+        }
+    }
+
+    pub(super) fn acquire() {
+        let mut guards = GUARDS.lock().unwrap_or_else(|e| e.into_inner());
+        if guards.0 == 0 {
+            unsafe {
+                let mut previous: libc::sigaction = std::mem::zeroed();
+                let mut action: libc::sigaction = std::mem::zeroed();
+                action.sa_sigaction = on_sigint as *const () as usize;
+                action.sa_flags = libc::SA_SIGINFO;
+                libc::sigemptyset(&mut action.sa_mask);
+                // Publish the previous handler before ours can run
+                if libc::sigaction(libc::SIGINT, std::ptr::null(), &mut previous) == 0 {
+                    PREVIOUS_HANDLER.store(previous.sa_sigaction, Ordering::Relaxed);
+                    PREVIOUS_SIGINFO.store(previous.sa_flags & libc::SA_SIGINFO != 0, Ordering::Relaxed);
+                    if libc::sigaction(libc::SIGINT, &action, std::ptr::null_mut()) == 0 {
+                        guards.1 = Some(previous);
+                    }
+                }
+            }
+        }
+        guards.0 += 1;
+    }
+
+    pub(super) fn release() {
+        let mut guards = GUARDS.lock().unwrap_or_else(|e| e.into_inner());
+        guards.0 -= 1;
+        if guards.0 == 0 {
+            if let Some(previous) = guards.1.take() {
+                unsafe {
+                    libc::sigaction(libc::SIGINT, &previous, std::ptr::null_mut());
+                }
+            }
+            INTERRUPTED.store(false, Ordering::Relaxed);
+        }
+    }
+}
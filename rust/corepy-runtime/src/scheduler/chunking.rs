@@ -0,0 +1,144 @@
+// ============================================================================
+// Cache-Aware Chunk Sizing
+// ============================================================================
+//
+// RESPONSIBILITIES:
+// - Decide how parallel reductions and row loops are split into tasks
+//
+// DESIGN:
+// - Target bytes per chunk: COREPY_CHUNK_BYTES env var, else the detected L2
+//   size, else DEFAULT_CHUNK_BYTES (resolved once per process)
+// - Chunks never exceed the target, so each one streams through cache
+// - Inputs with at least one element per worker always get one chunk per
+//   worker or more, so no worker sits idle
+
+use lazy_static::lazy_static;
+
+/// Chunk target when COREPY_CHUNK_BYTES is unset and no L2 size is detected
+pub const DEFAULT_CHUNK_BYTES: usize = 256 * 1024;
+
+lazy_static! {
+    static ref CHUNK_BYTES: usize = std::env::var("COREPY_CHUNK_BYTES")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .filter(|&bytes| bytes > 0)
+        .or_else(detect_l2_cache_bytes)
+        .unwrap_or(DEFAULT_CHUNK_BYTES);
+}
+
+/// Bytes each parallel chunk aims for
+pub fn target_chunk_bytes() -> usize {
+    *CHUNK_BYTES
+}
+
+/// Per-core L2 data cache size, where the OS reports it
+pub fn detect_l2_cache_bytes() -> Option<usize> {
+    #[cfg(target_os = "linux")]
+    {
+        let caches = std::fs::read_dir("/sys/devices/system/cpu/cpu0/cache").ok()?;
+        for entry in caches.flatten() {
+            let read = |name: &str| std::fs::read_to_string(entry.path().join(name)).ok();
+            let is_l2 = read("level").is_some_and(|level| level.trim() == "2");
+            let is_data = read("type").is_some_and(|kind| kind.trim() != "Instruction");
+            if is_l2 && is_data {
+                return read("size").as_deref().and_then(parse_cache_size);
+            }
+        }
+        None
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        None
+    }
+}
+
+/// Parse a sysfs cache size such as "512K" or "2M"
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_cache_size(text: &str) -> Option<usize> {
+    let text = text.trim();
+    let (digits, scale) = match text.char_indices().last()? {
+        (i, 'K') | (i, 'k') => (&text[..i], 1024),
+        (i, 'M') | (i, 'm') => (&text[..i], 1024 * 1024),
+        _ => (text, 1),
+    };
+    digits.parse::<usize>().ok().map(|n| n * scale).filter(|&bytes| bytes > 0)
+}
+
+/// Elements per chunk: at most `target_bytes`, and small enough that there are
+/// at least `num_threads` chunks
+pub fn chunk_len(count: usize, elem_size: usize, num_threads: usize, target_bytes: usize) -> usize {
+    let by_cache = (target_bytes / elem_size.max(1)).max(1);
+    let per_thread = count.div_ceil(num_threads.max(1)).max(1);
+    by_cache.min(per_thread)
+}
+
+/// How a parallel loop over `count` items would be split (get_chunking_info)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChunkingInfo {
+    pub count: usize,
+    pub elem_size: usize,
+    pub num_threads: usize,
+    pub target_bytes: usize,
+    pub chunk_len: usize,
+    pub num_chunks: usize,
+}
+
+/// Split `count` items of `elem_size` bytes for corepy's pool (or the
+/// capped pool under with_max_threads)
+pub fn plan(count: usize, elem_size: usize) -> ChunkingInfo {
+    let num_threads = crate::scheduler::rayon_pool::effective_threads();
+    let target_bytes = target_chunk_bytes();
+    let chunk_len = chunk_len(count, elem_size, num_threads, target_bytes);
+    ChunkingInfo {
+        count,
+        elem_size,
+        num_threads,
+        target_bytes,
+        chunk_len,
+        num_chunks: count.div_ceil(chunk_len),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_len_regimes() {
+        const KB256: usize = 256 * 1024;
+
+        // Barely above the parallel threshold: cache-sized chunks, not one per thread
+        let len = chunk_len(1_100_000, 4, 8, KB256);
+        assert_eq!(len, 65_536);
+        assert_eq!(1_100_000usize.div_ceil(len), 17);
+
+        // 2 GB of f32: still cache-sized chunks instead of 256 MB ones
+        assert_eq!(chunk_len(512 * 1024 * 1024, 4, 8, KB256), 65_536);
+
+        // Small inputs still give every worker a chunk
+        assert_eq!(chunk_len(1000, 4, 8, KB256), 125);
+        assert_eq!(chunk_len(3, 4, 8, KB256), 1);
+        assert_eq!(chunk_len(0, 4, 8, KB256), 1);
+
+        // Whole rows bigger than the target: one row per chunk
+        assert_eq!(chunk_len(64, 1 << 20, 4, KB256), 1);
+    }
+
+    #[test]
+    fn test_parse_cache_size() {
+        assert_eq!(parse_cache_size("512K\n"), Some(512 * 1024));
+        assert_eq!(parse_cache_size("2M"), Some(2 * 1024 * 1024));
+        assert_eq!(parse_cache_size("4096"), Some(4096));
+        assert_eq!(parse_cache_size("0K"), None);
+        assert_eq!(parse_cache_size("big"), None);
+    }
+
+    #[test]
+    fn test_plan_covers_count() {
+        let info = plan(10_000_000, 4);
+        assert!(info.chunk_len * 4 <= info.target_bytes.max(4));
+        assert!(info.num_chunks >= info.num_threads);
+        assert!(info.chunk_len * info.num_chunks >= info.count);
+    }
+}
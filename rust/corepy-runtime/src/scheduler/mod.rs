@@ -6,9 +6,28 @@
 // MODULES:
 // - rayon_pool: Thread pool management and GIL-aware execution
 // - arena: Thread-local memory arenas for temporary allocations
+// - chunking: Cache-aware chunk sizes for parallel loops
+// - async_ops: Background operations with pollable handles
+// - numa: NUMA topology and node-aware work placement
+// - stats: Parallel vs serial dispatch counters
+// - progress: Completed/total work units of the running operation
+// - cancel: Cooperative cancellation of chunked operations
+// - buffer_pool: Reusable output buffers handed out by handle
 
 pub mod rayon_pool;
 pub mod arena;
+pub mod chunking;
+pub mod async_ops;
+pub mod numa;
+pub mod stats;
+pub mod progress;
+pub mod cancel;
+pub mod buffer_pool;
+
+/// Serializes tests that run chunked ops or read the process-wide stats,
+/// progress and cancel state
+#[cfg(test)]
+pub(crate) static TEST_STATE_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
 
 // Re-export commonly used functions
 
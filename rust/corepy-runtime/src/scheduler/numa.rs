@@ -0,0 +1,251 @@
+// ============================================================================
+// NUMA Topology
+// ============================================================================
+//
+// RESPONSIBILITIES:
+// - Detect NUMA nodes and their CPUs (Linux sysfs; one node elsewhere)
+// - Group corepy's workers by node
+// - Split reductions into node-sized contiguous stripes
+//
+// DESIGN:
+// - Topology is read once per process
+// - Workers are spread over nodes in contiguous groups (or follow their
+//   pinned core); unpinned workers are bound to their node's CPUs by the
+//   pool's start handler on multi-node machines
+// - A stripe is reduced depth-first by whichever worker picks it up, so
+//   a worker tends to stay within one node's slice of the buffer instead of
+//   interleaving chunks from across the whole array
+
+use lazy_static::lazy_static;
+use std::ops::Range;
+use std::sync::Mutex;
+
+/// One NUMA node and the CPUs attached to it
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumaNode {
+    pub id: usize,
+    pub cpus: Vec<usize>,
+}
+
+/// Nodes of this machine, ordered by id (never empty)
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumaTopology {
+    pub nodes: Vec<NumaNode>,
+}
+
+lazy_static! {
+    static ref TOPOLOGY: NumaTopology = detect();
+}
+
+/// Node index (into NumaTopology::nodes) of each worker of the current pool
+static WORKER_NODES: Mutex<Vec<usize>> = Mutex::new(Vec::new());
+
+/// The cached topology
+pub fn topology() -> &'static NumaTopology {
+    &TOPOLOGY
+}
+
+/// Read the topology from /sys/devices/system/node, falling back to one
+/// node holding every CPU
+pub fn detect() -> NumaTopology {
+    #[cfg(target_os = "linux")]
+    {
+        if let Some(topology) = detect_sysfs() {
+            return topology;
+        }
+    }
+    NumaTopology {
+        nodes: vec![NumaNode { id: 0, cpus: (0..num_cpus::get()).collect() }],
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn detect_sysfs() -> Option<NumaTopology> {
+    let mut nodes = Vec::new();
+    for entry in std::fs::read_dir("/sys/devices/system/node").ok()?.flatten() {
+        let name = entry.file_name();
+        let Some(id) = name.to_str().and_then(|name| name.strip_prefix("node")).and_then(|id| id.parse().ok()) else {
+            continue;
+        };
+        let cpus = std::fs::read_to_string(entry.path().join("cpulist")).ok()
+            .and_then(|list| parse_cpulist(&list))?;
+        // Memory-only nodes have no workers to place
+        if !cpus.is_empty() {
+            nodes.push(NumaNode { id, cpus });
+        }
+    }
+    nodes.sort_by_key(|node| node.id);
+    (!nodes.is_empty()).then_some(NumaTopology { nodes })
+}
+
+/// Parse a kernel CPU list such as "0-3,8-11" (empty string = no CPUs)
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_cpulist(text: &str) -> Option<Vec<usize>> {
+    let mut cpus = Vec::new();
+    for part in text.trim().split(',').filter(|part| !part.is_empty()) {
+        match part.split_once('-') {
+            Some((first, last)) => {
+                let (first, last): (usize, usize) = (first.parse().ok()?, last.parse().ok()?);
+                if first > last {
+                    return None;
+                }
+                cpus.extend(first..=last);
+            }
+            None => cpus.push(part.parse().ok()?),
+        }
+    }
+    Some(cpus)
+}
+
+impl NumaTopology {
+    /// Node index holding `cpu`
+    pub fn node_of_cpu(&self, cpu: usize) -> Option<usize> {
+        self.nodes.iter().position(|node| node.cpus.contains(&cpu))
+    }
+
+    /// Node index for each of `num_threads` workers: the node of its pinned
+    /// core when `pinned` is given, else contiguous equal-sized groups
+    pub fn worker_nodes(&self, num_threads: usize, pinned: &[usize]) -> Vec<usize> {
+        (0..num_threads)
+            .map(|worker| match pinned.get(worker) {
+                Some(&core) => self.node_of_cpu(core).unwrap_or(0),
+                None => worker * self.nodes.len() / num_threads.max(1),
+            })
+            .collect()
+    }
+
+    /// Restrict the calling thread to the CPUs of node index `node`
+    pub fn bind_current_thread(&self, node: usize) -> bool {
+        let Some(node) = self.nodes.get(node) else {
+            return false;
+        };
+        #[cfg(target_os = "linux")]
+        unsafe {
+            let mut set: libc::cpu_set_t = std::mem::zeroed();
+            for &cpu in node.cpus.iter().filter(|&&cpu| cpu < libc::CPU_SETSIZE as usize) {
+                libc::CPU_SET(cpu, &mut set);
+            }
+            libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) == 0
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            let _ = node;
+            false
+        }
+    }
+}
+
+/// Remember the node grouping of a newly built pool (see get_worker_nodes)
+pub fn record_worker_nodes(nodes: Vec<usize>) {
+    *WORKER_NODES.lock().unwrap_or_else(|e| e.into_inner()) = nodes;
+}
+
+/// Node index of each worker of the current pool
+pub fn get_worker_nodes() -> Vec<usize> {
+    WORKER_NODES.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Split `count` elements into `nodes` contiguous stripes whose boundaries
+/// fall on multiples of `chunk_len`
+pub fn node_stripes(count: usize, chunk_len: usize, nodes: usize) -> Vec<Range<usize>> {
+    let chunk_len = chunk_len.max(1);
+    let chunks = count.div_ceil(chunk_len);
+    let nodes = nodes.clamp(1, chunks.max(1));
+    (0..nodes)
+        .map(|node| {
+            let start = (chunks * node / nodes * chunk_len).min(count);
+            let end = (chunks * (node + 1) / nodes * chunk_len).min(count);
+            start..end
+        })
+        .collect()
+}
+
+/// Sum `f` over `chunk_len`-sized chunks of `data`, stripe by stripe
+///
+/// Run inside corepy's pool (rayon_pool::install).
+pub fn striped_chunk_sum<T, R, F>(data: &[T], chunk_len: usize, f: F) -> R
+where
+    T: Sync,
+    R: Send + std::iter::Sum<R>,
+    F: Fn(&[T]) -> R + Sync + Send,
+{
+    use rayon::prelude::*;
+
+    let stripes = node_stripes(data.len(), chunk_len, topology().nodes.len());
+    stripes
+        .into_par_iter()
+        .map(|stripe| data[stripe].par_chunks(chunk_len.max(1)).map(&f).sum::<R>())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn two_nodes() -> NumaTopology {
+        NumaTopology {
+            nodes: vec![
+                NumaNode { id: 0, cpus: vec![0, 1, 2, 3] },
+                NumaNode { id: 1, cpus: vec![4, 5, 6, 7] },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_parse_cpulist() {
+        assert_eq!(parse_cpulist("0-3,8-9,12\n"), Some(vec![0, 1, 2, 3, 8, 9, 12]));
+        assert_eq!(parse_cpulist("\n"), Some(Vec::new()));
+        assert_eq!(parse_cpulist("3-1"), None);
+        assert_eq!(parse_cpulist("a-b"), None);
+    }
+
+    #[test]
+    fn test_worker_grouping() {
+        let topology = two_nodes();
+        assert_eq!(topology.worker_nodes(4, &[]), vec![0, 0, 1, 1]);
+        assert_eq!(topology.worker_nodes(3, &[]), vec![0, 0, 1]);
+        assert_eq!(topology.worker_nodes(2, &[5, 1]), vec![1, 0]);
+        assert_eq!(topology.node_of_cpu(9), None);
+    }
+
+    #[test]
+    fn test_node_stripes_are_contiguous_and_aligned() {
+        let stripes = node_stripes(1000, 64, 2);
+        assert_eq!(stripes, vec![0..512, 512..1000]);
+
+        let stripes = node_stripes(10_000, 100, 3);
+        assert_eq!(stripes.first().unwrap().start, 0);
+        assert_eq!(stripes.last().unwrap().end, 10_000);
+        for pair in stripes.windows(2) {
+            assert_eq!(pair[0].end, pair[1].start);
+            assert_eq!(pair[0].end % 100, 0);
+        }
+
+        // Fewer chunks than nodes: no empty stripes
+        assert_eq!(node_stripes(10, 64, 4), vec![0..10]);
+        assert_eq!(node_stripes(0, 64, 2), vec![0..0]);
+    }
+
+    #[test]
+    fn test_striped_sum_matches_serial() {
+        let data: Vec<u64> = (0..100_003).collect();
+        let expected: u64 = data.iter().sum();
+        let sum = crate::scheduler::rayon_pool::install(|| {
+            striped_chunk_sum(&data, 4096, |chunk| chunk.iter().sum::<u64>())
+        });
+        assert_eq!(sum, expected);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_sysfs_topology_parses() {
+        if !std::path::Path::new("/sys/devices/system/node/node0").exists() {
+            return;
+        }
+        let topology = detect_sysfs().expect("node directories present but unparsable");
+        assert!(topology.nodes.iter().all(|node| !node.cpus.is_empty()));
+        let cpus: usize = topology.nodes.iter().map(|node| node.cpus.len()).sum();
+        assert!(cpus >= 1);
+        assert_eq!(topology, *super::topology());
+    }
+}
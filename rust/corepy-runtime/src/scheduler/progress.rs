@@ -0,0 +1,36 @@
+// ============================================================================
+// Operation Progress
+// ============================================================================
+//
+// RESPONSIBILITIES:
+// - Track completed vs total work units of the running chunked operation
+//
+// DESIGN:
+// - Two process-wide Relaxed atomics: begin() resets them when a tracked op
+//   starts, advance() is the only hot-path cost (one add per chunk)
+// - One op is tracked at a time; concurrent tracked ops overwrite each
+//   other's totals, so readings are only meaningful for a single caller
+// - Reporting (the Python callback) lives in the FFI layer and only reads
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static DONE: AtomicU64 = AtomicU64::new(0);
+static TOTAL: AtomicU64 = AtomicU64::new(0);
+
+/// Start tracking an operation made of `total` work units
+pub fn begin(total: usize) {
+    DONE.store(0, Ordering::Relaxed);
+    TOTAL.store(total as u64, Ordering::Relaxed);
+}
+
+/// Mark `units` more work units complete
+#[inline]
+pub fn advance(units: usize) {
+    DONE.fetch_add(units as u64, Ordering::Relaxed);
+}
+
+/// (done, total) of the most recently started operation
+pub fn get() -> (u64, u64) {
+    let total = TOTAL.load(Ordering::Relaxed);
+    (DONE.load(Ordering::Relaxed).min(total), total)
+}
@@ -3,51 +3,373 @@
 // ============================================================================
 //
 // RESPONSIBILITIES:
-// - Initialize global thread pool for parallel execution
+// - Own corepy's thread pool for parallel execution
 // - Provide work-stealing task dispatch
 // - Integrate with Python's GIL (release during compute)
 // - NUMA-aware thread affinity (future)
 //
 // DESIGN:
-// - Lazy initialization via lazy_static
-// - Thread count: num_cpus or COREPY_NUM_THREADS env var
+// - Dedicated rayon::ThreadPool, built lazily and never the global pool, so
+//   other rayon users (polars, ...) can configure theirs independently
+// - Every parallel path runs through install(); set_num_threads() swaps in
+//   a freshly built pool at any time
+// - Thread count: set_num_threads(), COREPY_NUM_THREADS or num_cpus
+// - Per-call thread caps (with_max_threads) run the parallel part on a
+//   small cache of smaller pools instead of resizing the main one
+// - Optional core pinning (COREPY_PIN_THREADS / set_thread_affinity), applied
+//   by the pool's start handler; best effort where the OS refuses it
 // - Each thread has arena allocator via thread_local
 // - Panic handler for Rust panics in worker threads
+// - Notices (pool started, pinning failed, worker panicked) go to the log
+//   hook when one is set; otherwise warnings go to stderr and info is dropped
 
 use rayon;
-use std::sync::Once;
+use lazy_static::lazy_static;
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
 use pyo3::prelude::*;
 
-#[allow(dead_code)]
-static INIT: Once = Once::new();
+/// corepy's pool; ops clone the Arc out, so a rebuild never waits on them
+static POOL: RwLock<Option<Arc<rayon::ThreadPool>>> = RwLock::new(None);
+
+/// Severity of a runtime notice
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogLevel {
+    Info,
+    Warning,
+}
+
+/// Receiver for runtime notices (see set_log_hook)
+pub type LogHook = Arc<dyn Fn(LogLevel, &str) + Send + Sync>;
+
+/// Installed by set_log_hook()
+static LOG_HOOK: Mutex<Option<LogHook>> = Mutex::new(None);
+
+/// Held while initialize() runs; true once it has
+static INITIALIZED: Mutex<bool> = Mutex::new(false);
+
+/// Capped pools kept for per-call thread limits, most recently used last
+static LIMITED_POOLS: Mutex<Vec<(usize, Arc<rayon::ThreadPool>)>> = Mutex::new(Vec::new());
+
+/// Capped pools kept alive at once
+const MAX_LIMITED_POOLS: usize = 4;
+
+thread_local! {
+    /// Thread cap for install() on this thread (with_max_threads; set for
+    /// good on the workers of capped pools so nested installs stay there)
+    static THREAD_LIMIT: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+/// Size from the last set_num_threads() (0 = not set)
+static REQUESTED_THREADS: AtomicUsize = AtomicUsize::new(0);
+
+/// Size the next pool is built with
+fn configured_threads() -> usize {
+    match REQUESTED_THREADS.load(Ordering::Relaxed) {
+        0 => std::env::var("COREPY_NUM_THREADS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .filter(|&n| n > 0)
+            .unwrap_or_else(num_cpus::get),
+        requested => requested,
+    }
+}
+
+/// How worker threads are pinned to cores
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AffinityMode {
+    /// Let the OS schedule workers freely (default)
+    Unpinned,
+    /// Worker i on the i-th available core, packing neighbouring cores
+    Compact,
+    /// Workers spaced evenly over all available cores (e.g. across sockets)
+    Spread,
+}
+
+/// Accepted AffinityMode names
+pub const VALID_AFFINITY_MODES: &[&str] = &["none", "compact", "spread"];
+
+impl AffinityMode {
+    /// Parse a mode name (case-insensitive, see VALID_AFFINITY_MODES)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "none" => Some(AffinityMode::Unpinned),
+            "compact" => Some(AffinityMode::Compact),
+            "spread" => Some(AffinityMode::Spread),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AffinityMode::Unpinned => "none",
+            AffinityMode::Compact => "compact",
+            AffinityMode::Spread => "spread",
+        }
+    }
+
+    /// Mode requested by COREPY_PIN_THREADS ("1"/"true" = compact, or a mode name)
+    fn from_env() -> Self {
+        match std::env::var("COREPY_PIN_THREADS") {
+            Ok(value) => match value.trim().to_ascii_lowercase().as_str() {
+                "1" | "true" | "yes" | "on" => AffinityMode::Compact,
+                other => AffinityMode::from_name(other).unwrap_or(AffinityMode::Unpinned),
+            },
+            Err(_) => AffinityMode::Unpinned,
+        }
+    }
+}
+
+/// Core for each of `num_threads` workers under `mode` (empty = no pinning)
+fn core_assignments(mode: AffinityMode, cores: &[usize], num_threads: usize) -> Vec<usize> {
+    if cores.is_empty() {
+        return Vec::new();
+    }
+    match mode {
+        AffinityMode::Unpinned => Vec::new(),
+        AffinityMode::Compact => (0..num_threads).map(|i| cores[i % cores.len()]).collect(),
+        AffinityMode::Spread if num_threads <= cores.len() => {
+            (0..num_threads).map(|i| cores[i * cores.len() / num_threads]).collect()
+        }
+        // More workers than cores: every core is used anyway
+        AffinityMode::Spread => core_assignments(AffinityMode::Compact, cores, num_threads),
+    }
+}
+
+/// Pinning mode and the cores of the current pool's workers
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThreadAffinity {
+    pub mode: AffinityMode,
+    /// cores[i] is worker i's core; empty when unpinned
+    pub cores: Vec<usize>,
+}
+
+lazy_static! {
+    static ref AFFINITY: Mutex<ThreadAffinity> = Mutex::new(ThreadAffinity {
+        mode: AffinityMode::from_env(),
+        cores: Vec::new(),
+    });
+}
 
-/// Initialize the global Rayon thread pool
+/// Route runtime notices to `hook` (None restores the stderr default)
+///
+/// The hook runs wherever the notice is raised, including worker start-up
+/// and under the pool locks, so it must not block on other threads.
+pub fn set_log_hook(hook: Option<LogHook>) {
+    *LOG_HOOK.lock().unwrap_or_else(|e| e.into_inner()) = hook;
+}
+
+/// Report a runtime notice through the log hook
+fn notice(level: LogLevel, message: &str) {
+    let hook = LOG_HOOK.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    match (hook, level) {
+        (Some(hook), _) => hook(level, message),
+        (None, LogLevel::Warning) => eprintln!("Corepy: {}", message),
+        (None, LogLevel::Info) => {}
+    }
+}
+
+fn build_pool(num_threads: usize) -> Result<rayon::ThreadPool, String> {
+    let mut affinity = AFFINITY.lock().unwrap_or_else(|e| e.into_inner());
+    let available: Vec<usize> = match affinity.mode {
+        AffinityMode::Unpinned => Vec::new(),
+        _ => core_affinity::get_core_ids().unwrap_or_default().into_iter().map(|core| core.id).collect(),
+    };
+    if affinity.mode != AffinityMode::Unpinned && available.is_empty() {
+        notice(LogLevel::Warning, "No cores reported for thread affinity; workers are not pinned");
+    }
+    let cores = core_assignments(affinity.mode, &available, num_threads);
+
+    // Group workers by NUMA node; unpinned ones are bound to their node's CPUs
+    let topology = crate::scheduler::numa::topology();
+    let worker_nodes = topology.worker_nodes(num_threads, &cores);
+    let bind_nodes = Arc::new(if cores.is_empty() && topology.nodes.len() > 1 {
+        worker_nodes.clone()
+    } else {
+        Vec::new()
+    });
+
+    let pin_cores = Arc::new(cores.clone());
+    let warned = Arc::new(AtomicBool::new(false));
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(num_threads)
+        .thread_name(|idx| format!("corepy-worker-{}", idx))
+        .start_handler(move |idx| {
+            if let Some(&id) = pin_cores.get(idx) {
+                if !core_affinity::set_for_current(core_affinity::CoreId { id })
+                    && !warned.swap(true, Ordering::Relaxed)
+                {
+                    notice(LogLevel::Warning, "Could not pin worker threads to cores; continuing unpinned");
+                }
+            } else if let Some(&node) = bind_nodes.get(idx) {
+                topology.bind_current_thread(node);
+            }
+        })
+        .panic_handler(|_| {
+            notice(LogLevel::Warning, "Worker thread panicked");
+        })
+        .build()
+        .map_err(|e| format!("failed to build thread pool with {} threads: {}", num_threads, e))?;
+    affinity.cores = cores;
+    crate::scheduler::numa::record_worker_nodes(worker_nodes);
+    notice(LogLevel::Info, &format!(
+        "Started thread pool with {} workers (affinity: {})", num_threads, affinity.mode.name()
+    ));
+    Ok(pool)
+}
+
+/// corepy's pool, built on first use
+fn pool() -> Arc<rayon::ThreadPool> {
+    if let Some(pool) = POOL.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        return pool.clone();
+    }
+    let mut slot = POOL.write().unwrap_or_else(|e| e.into_inner());
+    slot.get_or_insert_with(|| {
+        let pool = build_pool(configured_threads())
+            .or_else(|_| build_pool(1))
+            .expect("failed to start any corepy worker thread");
+        Arc::new(pool)
+    })
+    .clone()
+}
+
+/// Initialize corepy's thread pool
 /// 
 /// Called lazily on first use. Thread count determined by:
-/// 1. COREPY_NUM_THREADS env var
-/// 2. num_cpus::get() (default)
+/// 1. set_num_threads()
+/// 2. COREPY_NUM_THREADS env var
+/// 3. num_cpus::get() (default)
 /// 
 /// This sets up the work-stealing scheduler that will be used
 /// for all parallel tensor operations.
-#[allow(dead_code)]
 pub fn init_thread_pool() {
-    INIT.call_once(|| {
-        let num_threads = std::env::var("COREPY_NUM_THREADS")
-            .ok()
-            .and_then(|s| s.parse().ok())
-            .unwrap_or_else(num_cpus::get);
+    pool();
+}
 
-        rayon::ThreadPoolBuilder::new()
+/// Build the pool now instead of on first use, optionally with
+/// `num_threads` workers
+///
+/// Only the first successful call does anything; it returns true, later
+/// calls return false and leave the pool as it is.
+pub fn initialize(num_threads: Option<usize>) -> Result<bool, String> {
+    let mut initialized = INITIALIZED.lock().unwrap_or_else(|e| e.into_inner());
+    if *initialized {
+        return Ok(false);
+    }
+    match num_threads {
+        Some(num_threads) => set_num_threads(num_threads)?,
+        None => init_thread_pool(),
+    }
+    *initialized = true;
+    Ok(true)
+}
+
+/// Run `op` inside corepy's pool, so its parallel iterators use corepy's
+/// workers rather than rayon's global pool
+///
+/// Under with_max_threads() the op runs on a pool of the capped size instead.
+pub fn install<R, F>(op: F) -> R
+where
+    F: FnOnce() -> R + Send,
+    R: Send,
+{
+    match THREAD_LIMIT.with(Cell::get) {
+        Some(limit) if limit < num_threads() => limited_pool(limit).install(op),
+        _ => pool().install(op),
+    }
+}
+
+/// Cached pool of `num_threads` workers for capped calls
+fn limited_pool(num_threads: usize) -> Arc<rayon::ThreadPool> {
+    let mut pools = LIMITED_POOLS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(i) = pools.iter().position(|&(size, _)| size == num_threads) {
+        let entry = pools.remove(i);
+        pools.push(entry);
+    } else {
+        let pool = rayon::ThreadPoolBuilder::new()
             .num_threads(num_threads)
-            .thread_name(|idx| format!("corepy-worker-{}", idx))
+            .thread_name(move |idx| format!("corepy-worker-cap{}-{}", num_threads, idx))
+            .start_handler(move |_| THREAD_LIMIT.with(|limit| limit.set(Some(num_threads))))
             .panic_handler(|_| {
-                eprintln!("Corepy worker thread panicked!");
+                notice(LogLevel::Warning, "Worker thread panicked");
             })
-            .build_global()
-            .expect("Failed to initialize Rayon thread pool");
+            .build()
+            .map(Arc::new)
+            // Out of threads: run capped calls on the main pool instead
+            .unwrap_or_else(|_| pool());
+        if pools.len() == MAX_LIMITED_POOLS {
+            pools.remove(0);
+        }
+        pools.push((num_threads, pool));
+    }
+    pools.last().expect("pool just inserted").1.clone()
+}
 
-        eprintln!("Corepy: Initialized thread pool with {} workers", num_threads);
-    });
+/// Run `f` with install() capped at `max_threads` workers on this thread
+///
+/// None leaves the pool as configured. Callers handle Some(1) themselves by
+/// taking their sequential path; under the cap install() still works, on a
+/// single-worker pool.
+pub fn with_max_threads<R>(max_threads: Option<usize>, f: impl FnOnce() -> R) -> R {
+    let Some(limit) = max_threads else {
+        return f();
+    };
+    struct Restore(Option<usize>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            THREAD_LIMIT.with(|limit| limit.set(self.0));
+        }
+    }
+    let _restore = Restore(THREAD_LIMIT.with(|current| current.replace(Some(limit.max(1)))));
+    f()
+}
+
+/// Workers a parallel op started on this thread would use
+pub fn effective_threads() -> usize {
+    let pool_size = num_threads();
+    THREAD_LIMIT.with(Cell::get).map_or(pool_size, |limit| limit.min(pool_size))
+}
+
+/// Run `op` on corepy's pool in the background
+pub fn spawn<F>(op: F)
+where
+    F: FnOnce() + Send + 'static,
+{
+    pool().spawn(op)
+}
+
+/// Rebuild the pool with `num_threads` workers
+///
+/// Operations already running finish on the old pool; later ones use the
+/// new one.
+pub fn set_num_threads(num_threads: usize) -> Result<(), String> {
+    if num_threads == 0 {
+        return Err("thread count must be at least 1".to_string());
+    }
+    let pool = Arc::new(build_pool(num_threads)?);
+    REQUESTED_THREADS.store(num_threads, Ordering::Relaxed);
+    *POOL.write().unwrap_or_else(|e| e.into_inner()) = Some(pool);
+    Ok(())
+}
+
+/// Pin (or unpin) corepy's workers, rebuilding the pool if it is running
+pub fn set_thread_affinity(mode: AffinityMode) -> Result<(), String> {
+    AFFINITY.lock().unwrap_or_else(|e| e.into_inner()).mode = mode;
+    let running = POOL.read().unwrap_or_else(|e| e.into_inner()).as_ref().map(|pool| pool.current_num_threads());
+    match running {
+        Some(num_threads) => {
+            let pool = Arc::new(build_pool(num_threads)?);
+            *POOL.write().unwrap_or_else(|e| e.into_inner()) = Some(pool);
+            Ok(())
+        }
+        None => Ok(()),
+    }
+}
+
+/// Current pinning mode and per-worker cores
+pub fn get_thread_affinity() -> ThreadAffinity {
+    AFFINITY.lock().unwrap_or_else(|e| e.into_inner()).clone()
 }
 
 /// Execute a parallel operation with GIL released
@@ -75,11 +397,8 @@ where
     F: FnOnce() -> R + Send,
     R: Send,
 {
-    // Ensure pool is initialized
-    init_thread_pool();
-
-    // Release GIL and execute
-    py.allow_threads(|| f())
+    // Release GIL and execute in corepy's pool
+    py.allow_threads(|| install(f))
 }
 
 /// Execute parallel iterator operation
@@ -100,12 +419,10 @@ where
     T: Send + Sync,
     F: Fn(&T) + Send + Sync,
 {
-    init_thread_pool();
-    
-    py.allow_threads(|| {
+    py.allow_threads(|| install(|| {
         use rayon::prelude::*;
-        data.par_iter().for_each(|item| f(item));
-    });
+        data.par_iter().for_each(f);
+    }));
 }
 
 /// Execute parallel map operation
@@ -123,22 +440,28 @@ where
     R: Send,
     F: Fn(&T) -> R + Send + Sync,
 {
-    init_thread_pool();
-    
-    py.allow_threads(|| {
+    py.allow_threads(|| install(|| {
         use rayon::prelude::*;
-        data.par_iter().map(|item| f(item)).collect()
-    })
+        data.par_iter().map(f).collect()
+    }))
 }
 
-/// Get number of threads in the pool
-#[allow(dead_code)]
+/// Get number of threads in corepy's pool
+///
+/// Before first use this is the size the pool will be built with; it does
+/// not build the pool.
 pub fn num_threads() -> usize {
-    rayon::current_num_threads()
+    match POOL.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        Some(pool) => pool.current_num_threads(),
+        None => configured_threads(),
+    }
 }
 
-/// Check if currently executing in a Rayon worker thread
-#[allow(dead_code)]
+/// Check if currently executing on a Rayon worker thread: one of corepy's
+/// pools, rayon's global pool or any other (e.g. inside a caller's par_iter)
+///
+/// Dispatch functions run their sequential kernels here rather than nesting
+/// another parallel split under an already parallel caller.
 pub fn in_worker_thread() -> bool {
     rayon::current_thread_index().is_some()
 }
@@ -146,49 +469,200 @@ pub fn in_worker_thread() -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::Mutex;
+
+    /// Serializes tests that resize the pool or assert on its size
+    static POOL_SIZE_LOCK: Mutex<()> = Mutex::new(());
 
     #[test]
     fn test_thread_pool_init() {
+        let _guard = POOL_SIZE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
         init_thread_pool();
         let count = num_threads();
         assert!(count > 0);
         assert!(count <= num_cpus::get() * 2); // Sanity check
     }
 
+    #[test]
+    fn test_rebuild_pool_mid_process() {
+        let _guard = POOL_SIZE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let original = num_threads();
+        assert!(set_num_threads(0).is_err());
+
+        for size in [3, 2] {
+            set_num_threads(size).unwrap();
+            assert_eq!(num_threads(), size);
+            assert_eq!(install(rayon::current_num_threads), size);
+        }
+        set_num_threads(original).unwrap();
+    }
+
+    #[test]
+    fn test_max_threads_caps_install() {
+        let _guard = POOL_SIZE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let original = num_threads();
+        set_num_threads(4).unwrap();
+
+        for cap in [2, 3, 2] {
+            let (size, nested) = with_max_threads(Some(cap), || {
+                assert_eq!(effective_threads(), cap);
+                install(|| (rayon::current_num_threads(), install(rayon::current_num_threads)))
+            });
+            assert_eq!((size, nested), (cap, cap));
+        }
+        assert!(LIMITED_POOLS.lock().unwrap().len() <= MAX_LIMITED_POOLS);
+
+        // Caps at or above the pool size, and no cap, use the main pool
+        assert_eq!(with_max_threads(Some(16), || install(rayon::current_num_threads)), 4);
+        assert_eq!(with_max_threads(None, effective_threads), 4);
+        assert_eq!(install(rayon::current_num_threads), 4);
+        set_num_threads(original).unwrap();
+    }
+
+    #[test]
+    fn test_initialize_once_and_log_hook() {
+        let _guard = POOL_SIZE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let original = num_threads();
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let sink = messages.clone();
+        set_log_hook(Some(Arc::new(move |level, message: &str| {
+            sink.lock().unwrap().push((level, message.to_string()));
+        })));
+
+        assert!(initialize(Some(3)).unwrap());
+        assert_eq!(num_threads(), 3);
+        assert!(!initialize(Some(5)).unwrap());
+        assert!(!initialize(None).unwrap());
+        assert_eq!(num_threads(), 3);
+        assert!(messages.lock().unwrap().iter().any(|(level, message)| {
+            *level == LogLevel::Info && message.starts_with("Started thread pool with 3 workers")
+        }));
+
+        set_log_hook(None);
+        set_num_threads(original).unwrap();
+    }
+
+    #[test]
+    fn test_core_assignments() {
+        let cores = [0, 1, 2, 3, 4, 5, 6, 7];
+        assert!(core_assignments(AffinityMode::Unpinned, &cores, 4).is_empty());
+        assert_eq!(core_assignments(AffinityMode::Compact, &cores, 4), vec![0, 1, 2, 3]);
+        assert_eq!(core_assignments(AffinityMode::Spread, &cores, 4), vec![0, 2, 4, 6]);
+        assert_eq!(core_assignments(AffinityMode::Spread, &cores[..2], 3), vec![0, 1, 0]);
+        assert!(core_assignments(AffinityMode::Compact, &[], 4).is_empty());
+
+        assert_eq!(AffinityMode::from_name(" Spread "), Some(AffinityMode::Spread));
+        assert_eq!(AffinityMode::from_name("numa"), None);
+        for name in VALID_AFFINITY_MODES {
+            assert_eq!(AffinityMode::from_name(name).unwrap().name(), *name);
+        }
+    }
+
+    #[test]
+    fn test_thread_affinity_round_trip() {
+        let _guard = POOL_SIZE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        init_thread_pool();
+        set_thread_affinity(AffinityMode::Compact).unwrap();
+        let affinity = get_thread_affinity();
+        assert_eq!(affinity.mode, AffinityMode::Compact);
+        assert_eq!(affinity.cores.len(), num_threads());
+
+        #[cfg(target_os = "linux")]
+        {
+            use rayon::prelude::*;
+
+            // Each worker's kernel CPU mask is exactly its assigned core
+            let masks: Vec<(usize, Vec<usize>)> = install(|| {
+                (0..64).into_par_iter()
+                    .map(|_| (rayon::current_thread_index().unwrap(), current_cpu_mask()))
+                    .collect()
+            });
+            for (worker, mask) in masks {
+                assert_eq!(mask, vec![affinity.cores[worker]]);
+            }
+        }
+
+        set_thread_affinity(AffinityMode::Unpinned).unwrap();
+        assert_eq!(get_thread_affinity(), ThreadAffinity { mode: AffinityMode::Unpinned, cores: Vec::new() });
+    }
+
+    #[cfg(target_os = "linux")]
+    fn current_cpu_mask() -> Vec<usize> {
+        unsafe {
+            let mut set: libc::cpu_set_t = std::mem::zeroed();
+            assert_eq!(libc::sched_getaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &mut set), 0);
+            (0..libc::CPU_SETSIZE as usize).filter(|&cpu| libc::CPU_ISSET(cpu, &set)).collect()
+        }
+    }
+
+    #[test]
+    fn test_parallel_reduction_uses_pool_size() {
+        use rayon::prelude::*;
+
+        let _guard = POOL_SIZE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let data = vec![1u64; 10_000];
+        let sizes: Vec<usize> = install(|| data.par_chunks(100).map(|_| rayon::current_num_threads()).collect());
+        assert!(sizes.iter().all(|&n| n == num_threads()));
+    }
+
+    #[test]
+    fn test_unaffected_by_global_pool() {
+        let _guard = POOL_SIZE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        // Another library claiming the global pool (may already be built by
+        // other tests; either way corepy must not care)
+        let _ = rayon::ThreadPoolBuilder::new().num_threads(1).build_global();
+
+        let original = num_threads();
+        set_num_threads(2).unwrap();
+        let names: Vec<String> = install(|| {
+            use rayon::prelude::*;
+            (0..64).into_par_iter()
+                .map(|_| std::thread::current().name().unwrap_or_default().to_string())
+                .collect()
+        });
+        assert!(names.iter().all(|name| name.starts_with("corepy-worker-")), "{:?}", names);
+        assert!(!in_worker_thread());
+        assert!(install(in_worker_thread));
+        set_num_threads(original).unwrap();
+    }
+
     #[test]
     fn test_parallel_execution() {
         use std::sync::atomic::{AtomicUsize, Ordering};
         use std::sync::Arc;
         
-        init_thread_pool();
-        
         let counter = Arc::new(AtomicUsize::new(0));
         let counter_clone = counter.clone();
         
-        rayon::scope(|s| {
+        install(|| rayon::scope(|s| {
             for _ in 0..100 {
                 let counter = counter_clone.clone();
                 s.spawn(move |_| {
                     counter.fetch_add(1, Ordering::SeqCst);
                 });
             }
-        });
+        }));
         
         assert_eq!(counter.load(Ordering::SeqCst), 100);
     }
 
     #[test]
     fn test_in_worker_thread() {
+        let _guard = POOL_SIZE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
         init_thread_pool();
         
         // Main thread should not be a worker
         assert!(!in_worker_thread());
         
-        // Inside rayon scope should be a worker
-        rayon::scope(|s| {
+        // Inside corepy's pool should be a worker
+        install(|| rayon::scope(|s| {
             s.spawn(|_| {
                 assert!(in_worker_thread());
             });
-        });
+        }));
+
+        // So is any other pool's worker
+        let foreign = rayon::ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+        assert!(foreign.install(in_worker_thread));
     }
 }
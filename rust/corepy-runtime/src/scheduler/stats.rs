@@ -0,0 +1,69 @@
+// ============================================================================
+// Scheduler Statistics
+// ============================================================================
+//
+// RESPONSIBILITIES:
+// - Count how often dispatch sites take their parallel vs serial paths
+//
+// DESIGN:
+// - Plain Relaxed atomics: no locks on the hot path, and a snapshot taken
+//   while ops run may mix counts from before and after a dispatch
+// - Counted at the reduction and matmul dispatch sites; "parallel" means
+//   the work was split over corepy's Rayon pool (BLAS threads itself and
+//   counts as serial here)
+// - Also counts scratch buffers that fell back from the arena to the heap
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static TASKS_SPAWNED: AtomicU64 = AtomicU64::new(0);
+static PARALLEL_DISPATCHES: AtomicU64 = AtomicU64::new(0);
+static SERIAL_DISPATCHES: AtomicU64 = AtomicU64::new(0);
+static TOTAL_PARALLEL_ELEMENTS: AtomicU64 = AtomicU64::new(0);
+static ARENA_HEAP_FALLBACKS: AtomicU64 = AtomicU64::new(0);
+
+/// Counter values at one point in time
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SchedulerStats {
+    /// Rayon tasks (chunks / row blocks) handed to the pool
+    pub tasks_spawned: u64,
+    pub parallel_dispatches: u64,
+    pub serial_dispatches: u64,
+    /// Elements processed by parallel dispatches
+    pub total_parallel_elements: u64,
+    /// Scratch buffers taken from the heap because the arena was too small
+    pub arena_heap_fallbacks: u64,
+}
+
+/// Count a dispatch that split `elements` into `tasks` pool tasks
+pub fn record_parallel(elements: usize, tasks: usize) {
+    PARALLEL_DISPATCHES.fetch_add(1, Ordering::Relaxed);
+    TOTAL_PARALLEL_ELEMENTS.fetch_add(elements as u64, Ordering::Relaxed);
+    TASKS_SPAWNED.fetch_add(tasks as u64, Ordering::Relaxed);
+}
+
+/// Count a dispatch that ran on the calling thread
+pub fn record_serial() {
+    SERIAL_DISPATCHES.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Count a scratch buffer allocated on the heap instead of the arena
+pub fn record_heap_fallback() {
+    ARENA_HEAP_FALLBACKS.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn get_stats() -> SchedulerStats {
+    SchedulerStats {
+        tasks_spawned: TASKS_SPAWNED.load(Ordering::Relaxed),
+        parallel_dispatches: PARALLEL_DISPATCHES.load(Ordering::Relaxed),
+        serial_dispatches: SERIAL_DISPATCHES.load(Ordering::Relaxed),
+        total_parallel_elements: TOTAL_PARALLEL_ELEMENTS.load(Ordering::Relaxed),
+        arena_heap_fallbacks: ARENA_HEAP_FALLBACKS.load(Ordering::Relaxed),
+    }
+}
+
+/// Zero every counter
+pub fn reset_stats() {
+    for counter in [&TASKS_SPAWNED, &PARALLEL_DISPATCHES, &SERIAL_DISPATCHES, &TOTAL_PARALLEL_ELEMENTS, &ARENA_HEAP_FALLBACKS] {
+        counter.store(0, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,534 @@
+// ============================================================================
+// Runtime-Owned Buffers
+// ============================================================================
+//
+// RESPONSIBILITIES:
+// - Own aligned tensor memory on the Rust side (temporaries, views, pooling)
+// - Let Python hold such memory by integer handle and pass its address to
+//   the pointer-based ops
+//
+// DESIGN:
+// - One std::alloc allocation per Buffer, SIMD_ALIGN-aligned, freed with the
+//   same Layout on drop; empty buffers allocate nothing
+// - The registry is a global handle table (like async_ops): free_buffer()
+//   removes the handle, so a second free is an error instead of a double free
+// - Addresses stay valid until the handle is freed; the registry never moves
+//   a buffer's memory
+// - Pinned buffers are page-locked (mlock / VirtualLock) so future device
+//   copies can run asynchronously. They get whole PIN_ALIGN blocks of their
+//   own, since unlocking a page shared with another pinned buffer would
+//   unpin it too. Locked bytes count against a process-wide limit; when the
+//   OS refuses the lock (e.g. RLIMIT_MEMLOCK) the buffer stays usable but
+//   unpinned, and the failure is counted
+
+use lazy_static::lazy_static;
+use std::alloc;
+use std::collections::HashMap;
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use super::dtype::DType;
+use super::layout::Layout;
+use super::Device;
+use crate::scheduler::arena::SIMD_ALIGN;
+
+/// Why a buffer operation was rejected
+#[derive(Debug, Clone, PartialEq)]
+pub enum BufferError {
+    /// Never handed out, or already freed
+    UnknownHandle(u64),
+    /// dtype name not recognised
+    UnknownDtype(String),
+    /// Byte count is not a whole number of elements (bytes, element size)
+    PartialElement(usize, usize),
+    /// More bytes than one allocation can hold
+    TooLarge(usize),
+    /// Pinning would take the process over its pinned-memory limit
+    PinnedLimit { requested: usize, pinned: usize, limit: usize },
+}
+
+impl std::fmt::Display for BufferError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BufferError::UnknownHandle(handle) => write!(f, "unknown or already freed buffer handle {}", handle),
+            BufferError::UnknownDtype(dtype) => write!(f, "unknown dtype '{}'", dtype),
+            BufferError::PartialElement(bytes, size) => {
+                write!(f, "{} bytes is not a whole number of {}-byte elements", bytes, size)
+            }
+            BufferError::TooLarge(bytes) => write!(f, "buffer of {} bytes is too large to allocate", bytes),
+            BufferError::PinnedLimit { requested, pinned, limit } => write!(
+                f,
+                "pinning {} bytes would exceed the pinned memory limit ({} of {} bytes already pinned)",
+                requested, pinned, limit
+            ),
+        }
+    }
+}
+
+/// How a Buffer's memory is allocated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BufferKind {
+    /// Ordinary heap memory
+    #[default]
+    Pageable,
+    /// Page-locked memory (falls back to pageable if the OS refuses)
+    Pinned,
+}
+
+/// Alignment and size granularity of pinned allocations; a multiple of
+/// every common page size (4 KiB, 16 KiB, 64 KiB)
+pub const PIN_ALIGN: usize = 64 * 1024;
+
+/// Default cap on page-locked bytes (COREPY_PINNED_LIMIT overrides)
+const DEFAULT_PINNED_LIMIT: usize = 1 << 30;
+
+/// Owned, SIMD_ALIGN-aligned memory for `len` elements of `dtype`
+pub struct Buffer {
+    ptr: NonNull<u8>,
+    len: usize,
+    dtype: DType,
+    device: Device,
+    kind: BufferKind,
+    /// Whether the allocation is currently page-locked
+    pinned: bool,
+}
+
+// The buffer owns its allocation outright; shared access only hands out
+// a *const pointer
+unsafe impl Send for Buffer {}
+unsafe impl Sync for Buffer {}
+
+impl Buffer {
+    /// `len` zeroed elements
+    pub fn zeroed(dtype: DType, len: usize) -> Result<Self, BufferError> {
+        Self::allocate(dtype, len, true, BufferKind::Pageable)
+    }
+
+    /// `len` elements with unspecified contents
+    pub fn uninit(dtype: DType, len: usize) -> Result<Self, BufferError> {
+        Self::allocate(dtype, len, false, BufferKind::Pageable)
+    }
+
+    /// `len` zeroed elements allocated as `kind`
+    pub fn zeroed_with_kind(dtype: DType, len: usize, kind: BufferKind) -> Result<Self, BufferError> {
+        Self::allocate(dtype, len, true, kind)
+    }
+
+    fn allocate(dtype: DType, len: usize, zeroed: bool, kind: BufferKind) -> Result<Self, BufferError> {
+        let bytes = len.checked_mul(dtype.size()).ok_or(BufferError::TooLarge(usize::MAX))?;
+        let layout = Self::layout(bytes, kind)?;
+        // Claim the limit before allocating, so a refused pin costs nothing
+        let pin = kind == BufferKind::Pinned && bytes != 0;
+        if pin {
+            reserve_pinned(layout.size())?;
+        }
+        let ptr = if bytes == 0 {
+            // Aligned and never dereferenced or freed
+            NonNull::new(SIMD_ALIGN as *mut u8).expect("SIMD_ALIGN is non-zero")
+        } else {
+            let ptr = unsafe {
+                if zeroed {
+                    alloc::alloc_zeroed(layout)
+                } else {
+                    alloc::alloc(layout)
+                }
+            };
+            NonNull::new(ptr).unwrap_or_else(|| alloc::handle_alloc_error(layout))
+        };
+        let pinned = pin && unsafe { sys::lock(ptr.as_ptr(), layout.size()) };
+        if pin && !pinned {
+            PINNED_BYTES.fetch_sub(layout.size(), Ordering::SeqCst);
+            PIN_FAILURES.fetch_add(1, Ordering::Relaxed);
+        }
+        Ok(Buffer { ptr, len, dtype, device: Device::Cpu, kind, pinned })
+    }
+
+    fn layout(bytes: usize, kind: BufferKind) -> Result<alloc::Layout, BufferError> {
+        let layout = match kind {
+            BufferKind::Pageable => alloc::Layout::from_size_align(bytes, SIMD_ALIGN),
+            BufferKind::Pinned => alloc::Layout::from_size_align(bytes, PIN_ALIGN).map(|layout| layout.pad_to_align()),
+        };
+        layout.map_err(|_| BufferError::TooLarge(bytes))
+    }
+
+    pub fn as_ptr(&self) -> *const u8 {
+        self.ptr.as_ptr()
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut u8 {
+        self.ptr.as_ptr()
+    }
+
+    /// Number of elements
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn dtype(&self) -> DType {
+        self.dtype
+    }
+
+    /// Always Cpu: buffers are host allocations until device allocators land
+    pub fn device(&self) -> Device {
+        self.device
+    }
+
+    /// Page-locked right now; false for a Pinned buffer whose lock the OS
+    /// refused
+    pub fn is_pinned(&self) -> bool {
+        self.pinned
+    }
+
+    pub fn len_bytes(&self) -> usize {
+        self.len * self.dtype.size()
+    }
+
+    /// Contiguous (row-major) copy of the strided view at `ptr`
+    ///
+    /// # Safety
+    /// `ptr` must be valid for every element `layout` reaches
+    pub unsafe fn gather(ptr: *const u8, layout: &Layout, dtype: DType) -> Result<Self, BufferError> {
+        let mut buffer = Self::uninit(dtype, layout.shape().numel())?;
+        let size = dtype.size() as isize;
+        let mut dst = buffer.as_mut_ptr();
+        for (offset, len) in layout.iter_contiguous_runs() {
+            let bytes = len * dtype.size();
+            std::ptr::copy_nonoverlapping(ptr.offset(offset * size), dst, bytes);
+            dst = dst.add(bytes);
+        }
+        Ok(buffer)
+    }
+}
+
+impl Drop for Buffer {
+    fn drop(&mut self) {
+        let bytes = self.len_bytes();
+        if bytes != 0 {
+            let layout = Self::layout(bytes, self.kind).expect("layout was valid at allocation");
+            if self.pinned {
+                unsafe { sys::unlock(self.ptr.as_ptr(), layout.size()) };
+                PINNED_BYTES.fetch_sub(layout.size(), Ordering::SeqCst);
+            }
+            unsafe { alloc::dealloc(self.ptr.as_ptr(), layout) }
+        }
+    }
+}
+
+#[cfg(unix)]
+mod sys {
+    use std::ffi::{c_int, c_void};
+
+    extern "C" {
+        fn mlock(addr: *const c_void, len: usize) -> c_int;
+        fn munlock(addr: *const c_void, len: usize) -> c_int;
+    }
+
+    pub unsafe fn lock(ptr: *mut u8, len: usize) -> bool {
+        mlock(ptr.cast(), len) == 0
+    }
+
+    pub unsafe fn unlock(ptr: *mut u8, len: usize) {
+        munlock(ptr.cast(), len);
+    }
+}
+
+#[cfg(windows)]
+mod sys {
+    use std::ffi::c_void;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn VirtualLock(addr: *mut c_void, len: usize) -> i32;
+        fn VirtualUnlock(addr: *mut c_void, len: usize) -> i32;
+    }
+
+    pub unsafe fn lock(ptr: *mut u8, len: usize) -> bool {
+        VirtualLock(ptr.cast(), len) != 0
+    }
+
+    pub unsafe fn unlock(ptr: *mut u8, len: usize) {
+        VirtualUnlock(ptr.cast(), len);
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod sys {
+    pub unsafe fn lock(_ptr: *mut u8, _len: usize) -> bool {
+        false
+    }
+
+    pub unsafe fn unlock(_ptr: *mut u8, _len: usize) {}
+}
+
+/// Bytes currently page-locked by pinned buffers (whole PIN_ALIGN blocks)
+static PINNED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+/// Pinned allocations that fell back to pageable memory
+static PIN_FAILURES: AtomicU64 = AtomicU64::new(0);
+
+lazy_static! {
+    static ref BUFFERS: Mutex<HashMap<u64, Buffer>> = Mutex::new(HashMap::new());
+
+    /// Cap on PINNED_BYTES
+    static ref PINNED_LIMIT: AtomicUsize = AtomicUsize::new(
+        std::env::var("COREPY_PINNED_LIMIT")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_PINNED_LIMIT)
+    );
+}
+
+/// Count `bytes` against the pinned limit
+fn reserve_pinned(bytes: usize) -> Result<(), BufferError> {
+    let limit = PINNED_LIMIT.load(Ordering::SeqCst);
+    PINNED_BYTES
+        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |pinned| {
+            pinned.checked_add(bytes).filter(|&total| total <= limit)
+        })
+        .map(|_| ())
+        .map_err(|pinned| BufferError::PinnedLimit { requested: bytes, pinned, limit })
+}
+
+/// Cap the bytes pinned buffers may lock; buffers already pinned keep
+/// their memory even if now over the limit
+pub fn set_pinned_limit(bytes: usize) {
+    PINNED_LIMIT.store(bytes, Ordering::SeqCst);
+}
+
+/// Pinned-memory accounting
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PinnedStats {
+    /// Bytes page-locked right now
+    pub pinned_bytes: usize,
+    /// Cap on pinned_bytes
+    pub limit: usize,
+    /// Pinned allocations the OS refused to lock (they fell back to pageable)
+    pub lock_failures: u64,
+}
+
+pub fn pinned_stats() -> PinnedStats {
+    PinnedStats {
+        pinned_bytes: PINNED_BYTES.load(Ordering::SeqCst),
+        limit: PINNED_LIMIT.load(Ordering::SeqCst),
+        lock_failures: PIN_FAILURES.load(Ordering::Relaxed),
+    }
+}
+
+/// Next handle handed out by alloc_buffer()
+static NEXT_HANDLE: AtomicU64 = AtomicU64::new(1);
+
+/// Allocate a zeroed buffer of `nbytes` holding whole `dtype` elements and
+/// return its handle
+pub fn alloc_buffer(nbytes: usize, dtype: &str, kind: BufferKind) -> Result<u64, BufferError> {
+    let dtype = DType::from_name(dtype).ok_or_else(|| BufferError::UnknownDtype(dtype.to_string()))?;
+    if !nbytes.is_multiple_of(dtype.size()) {
+        return Err(BufferError::PartialElement(nbytes, dtype.size()));
+    }
+    let buffer = Buffer::zeroed_with_kind(dtype, nbytes / dtype.size(), kind)?;
+    let handle = NEXT_HANDLE.fetch_add(1, Ordering::Relaxed);
+    BUFFERS.lock().unwrap_or_else(|e| e.into_inner()).insert(handle, buffer);
+    Ok(handle)
+}
+
+/// Free a buffer; its address must not be used afterwards
+pub fn free_buffer(handle: u64) -> Result<(), BufferError> {
+    let buffer = BUFFERS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&handle)
+        .ok_or(BufferError::UnknownHandle(handle))?;
+    // Deallocate outside the table lock
+    drop(buffer);
+    Ok(())
+}
+
+/// Address of a live buffer
+pub fn buffer_address(handle: u64) -> Result<usize, BufferError> {
+    BUFFERS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get_mut(&handle)
+        .map(|buffer| buffer.as_mut_ptr() as usize)
+        .ok_or(BufferError::UnknownHandle(handle))
+}
+
+/// Whether a live buffer is page-locked
+pub fn buffer_is_pinned(handle: u64) -> Result<bool, BufferError> {
+    BUFFERS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&handle)
+        .map(Buffer::is_pinned)
+        .ok_or(BufferError::UnknownHandle(handle))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffer_alignment_and_zeroing() {
+        for len in [1, 3, 17, 1000] {
+            let buffer = Buffer::zeroed(DType::Float32, len).unwrap();
+            assert_eq!(buffer.as_ptr() as usize % SIMD_ALIGN, 0);
+            let bytes = unsafe { std::slice::from_raw_parts(buffer.as_ptr(), buffer.len_bytes()) };
+            assert!(bytes.iter().all(|&b| b == 0));
+        }
+        let mut buffer = Buffer::uninit(DType::Float64, 8).unwrap();
+        assert_eq!(buffer.as_mut_ptr() as usize % SIMD_ALIGN, 0);
+
+        let empty = Buffer::zeroed(DType::Int8, 0).unwrap();
+        assert!(empty.is_empty());
+        assert_eq!(empty.as_ptr() as usize % SIMD_ALIGN, 0);
+    }
+
+    #[test]
+    fn test_buffer_dtype_size_accounting() {
+        let cases = [(DType::Bool, 1), (DType::Float16, 2), (DType::Int32, 4), (DType::Float64, 8)];
+        for (dtype, size) in cases {
+            let buffer = Buffer::zeroed(dtype, 10).unwrap();
+            assert_eq!((buffer.len(), buffer.dtype(), buffer.len_bytes()), (10, dtype, 10 * size));
+            assert_eq!(DType::from_name(dtype.name()), Some(dtype));
+        }
+        assert!(matches!(Buffer::zeroed(DType::Int64, usize::MAX / 4), Err(BufferError::TooLarge(_))));
+    }
+
+    #[test]
+    fn test_gather_strided_view() {
+        use crate::tensor::TensorShape;
+
+        // Columns 1.. of every other row of a 4x3 int32 matrix, reversed
+        let data: Vec<i32> = (0..12).collect();
+        let layout = Layout::new(TensorShape::new(&[2, 2]).unwrap(), &[-6, 1]).unwrap();
+        let first = unsafe { data.as_ptr().add(7) };
+        let buffer = unsafe { Buffer::gather(first.cast(), &layout, DType::Int32) }.unwrap();
+        let values = unsafe { std::slice::from_raw_parts(buffer.as_ptr().cast::<i32>(), buffer.len()) };
+        assert_eq!(values, &[7, 8, 1, 2]);
+    }
+
+    #[test]
+    fn test_registry_rejects_double_free() {
+        let handle = alloc_buffer(64, "float32", BufferKind::Pageable).unwrap();
+        let address = buffer_address(handle).unwrap();
+        assert_eq!(address % SIMD_ALIGN, 0);
+        assert_eq!(buffer_address(handle).unwrap(), address);
+
+        free_buffer(handle).unwrap();
+        assert_eq!(free_buffer(handle), Err(BufferError::UnknownHandle(handle)));
+        assert_eq!(buffer_address(handle), Err(BufferError::UnknownHandle(handle)));
+
+        assert_eq!(alloc_buffer(6, "int32", BufferKind::Pageable), Err(BufferError::PartialElement(6, 4)));
+        assert_eq!(alloc_buffer(8, "complex64", BufferKind::Pageable), Err(BufferError::UnknownDtype("complex64".to_string())));
+    }
+
+    /// Locked memory of this process per the kernel, when it reports it
+    fn vm_locked_kb() -> Option<usize> {
+        let status = std::fs::read_to_string("/proc/self/status").ok()?;
+        let line = status.lines().find(|line| line.starts_with("VmLck:"))?;
+        line.split_whitespace().nth(1)?.parse().ok()
+    }
+
+    #[test]
+    fn test_pinned_buffers_report_and_unlock_on_free() {
+        let _guard = crate::scheduler::TEST_STATE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let before = pinned_stats();
+        let locked_before = vm_locked_kb();
+
+        let normal = alloc_buffer(4096, "float32", BufferKind::Pageable).unwrap();
+        let pinned = alloc_buffer(4096, "float32", BufferKind::Pinned).unwrap();
+        assert!(!buffer_is_pinned(normal).unwrap());
+        assert_eq!(buffer_address(pinned).unwrap() % PIN_ALIGN, 0);
+
+        let during = pinned_stats();
+        if buffer_is_pinned(pinned).unwrap() {
+            // The whole PIN_ALIGN block is locked and counted
+            assert_eq!(during.pinned_bytes, before.pinned_bytes + PIN_ALIGN);
+            assert_eq!(during.lock_failures, before.lock_failures);
+            if let (Some(locked_before), Some(locked)) = (locked_before, vm_locked_kb()) {
+                assert!(locked >= locked_before + PIN_ALIGN / 1024, "{} -> {} kB", locked_before, locked);
+            }
+        } else {
+            // RLIMIT_MEMLOCK (or the platform) refused: pageable, nothing counted
+            assert_eq!(during.pinned_bytes, before.pinned_bytes);
+            assert_eq!(during.lock_failures, before.lock_failures + 1);
+        }
+        // Still ordinary zeroed, writable memory either way
+        let address = buffer_address(pinned).unwrap();
+        unsafe { std::ptr::write_bytes(address as *mut u8, 7, 4096) };
+
+        free_buffer(pinned).unwrap();
+        free_buffer(normal).unwrap();
+        assert_eq!(pinned_stats().pinned_bytes, before.pinned_bytes);
+        assert_eq!(vm_locked_kb(), locked_before);
+        assert_eq!(buffer_is_pinned(pinned), Err(BufferError::UnknownHandle(pinned)));
+
+        // Empty pinned buffers lock nothing
+        let empty = Buffer::zeroed_with_kind(DType::Float32, 0, BufferKind::Pinned).unwrap();
+        assert!(!empty.is_pinned());
+        assert_eq!(pinned_stats().pinned_bytes, before.pinned_bytes);
+    }
+
+    #[test]
+    fn test_pinned_limit_enforced() {
+        let _guard = crate::scheduler::TEST_STATE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let before = pinned_stats();
+        set_pinned_limit(before.pinned_bytes + PIN_ALIGN);
+
+        // Rounded up to one block, so it fits exactly
+        let first = Buffer::zeroed_with_kind(DType::UInt8, 100, BufferKind::Pinned).unwrap();
+        if first.is_pinned() {
+            let err = Buffer::zeroed_with_kind(DType::UInt8, 1, BufferKind::Pinned).err().unwrap();
+            let pinned = before.pinned_bytes + PIN_ALIGN;
+            assert_eq!(err, BufferError::PinnedLimit { requested: PIN_ALIGN, pinned, limit: pinned });
+            assert!(err.to_string().starts_with("pinning 65536 bytes would exceed the pinned memory limit"));
+        }
+        let err = alloc_buffer(2 * PIN_ALIGN, "uint8", BufferKind::Pinned).unwrap_err();
+        assert!(matches!(err, BufferError::PinnedLimit { requested, .. } if requested == 2 * PIN_ALIGN));
+        // Pageable allocations ignore the limit
+        drop(Buffer::zeroed(DType::UInt8, 4 * PIN_ALIGN).unwrap());
+
+        drop(first);
+        assert_eq!(pinned_stats().pinned_bytes, before.pinned_bytes);
+        set_pinned_limit(before.limit);
+    }
+
+    #[test]
+    fn test_registry_concurrent_allocation() {
+        let handles: Vec<Vec<(u64, usize)>> = std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..8)
+                .map(|t| {
+                    scope.spawn(move || {
+                        (0..50)
+                            .map(|i| {
+                                let nbytes = 8 * (t * 50 + i + 1);
+                                let handle = alloc_buffer(nbytes, "float64", BufferKind::Pageable).unwrap();
+                                let address = buffer_address(handle).unwrap();
+                                // Each thread writes its own buffers
+                                unsafe { std::ptr::write_bytes(address as *mut u8, t as u8, nbytes) };
+                                (handle, nbytes)
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            workers.into_iter().map(|worker| worker.join().unwrap()).collect()
+        });
+
+        let mut seen = std::collections::HashSet::new();
+        for (t, owned) in handles.iter().enumerate() {
+            for &(handle, nbytes) in owned {
+                assert!(seen.insert(handle), "handle {} handed out twice", handle);
+                let address = buffer_address(handle).unwrap();
+                let bytes = unsafe { std::slice::from_raw_parts(address as *const u8, nbytes) };
+                assert!(bytes.iter().all(|&b| b == t as u8));
+                free_buffer(handle).unwrap();
+            }
+        }
+    }
+}
@@ -0,0 +1,317 @@
+// ============================================================================
+// Tensor Constructors
+// ============================================================================
+//
+// RESPONSIBILITIES:
+// - zeros / ones / arange / linspace straight into runtime-owned Buffers,
+//   handed back as registered tensor handles
+//
+// DESIGN:
+// - Filling goes through the parallel fill kernels in ops::elementwise
+// - arange matches NumPy bit for bit: length ceil((stop - start) / step),
+//   the first two values cast from float64, and element i computed as
+//   first + i * (second - first) in the dtype's own arithmetic, as NumPy's
+//   per-dtype fill functions do
+// - linspace computes start + i * step in float64 and casts (flooring for
+//   integer dtypes, like NumPy 2); with endpoint=True the last element is
+//   `stop` exactly
+
+use super::buffer::Buffer;
+use super::dtype::DType;
+use super::registry::{register_buffer, TensorError};
+use super::shape::TensorShape;
+use crate::ops::cast::{f16_to_f32, f32_to_f16};
+use crate::ops::elementwise::{fill_cpu_dispatch, fill_indexed_cpu_dispatch};
+
+/// float16 storage (bit pattern)
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+struct Half(u16);
+
+/// Storage type of a dtype and how NumPy produces its values
+trait Element: Copy + Send + Sync {
+    /// Cast a float64 the way assigning one into this dtype does
+    fn from_f64(value: f64) -> Self;
+
+    /// Element `i` of an arange whose first two elements are `first` and
+    /// `second`
+    fn arange_at(first: Self, second: Self, i: usize) -> Self;
+}
+
+impl Element for bool {
+    fn from_f64(value: f64) -> Self {
+        value != 0.0
+    }
+
+    // NumPy has no bool fill, so a bool arange stops at two elements
+    fn arange_at(first: Self, second: Self, i: usize) -> Self {
+        if i == 0 {
+            first
+        } else {
+            second
+        }
+    }
+}
+
+macro_rules! int_element {
+    ($($t:ty),*) => {$(
+        impl Element for $t {
+            fn from_f64(value: f64) -> Self {
+                value as $t
+            }
+
+            fn arange_at(first: Self, second: Self, i: usize) -> Self {
+                first.wrapping_add((i as $t).wrapping_mul(second.wrapping_sub(first)))
+            }
+        }
+    )*};
+}
+
+int_element!(i8, u8, i16, u16, i32, u32, i64, u64);
+
+impl Element for f32 {
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+
+    fn arange_at(first: Self, second: Self, i: usize) -> Self {
+        first + i as f32 * (second - first)
+    }
+}
+
+impl Element for f64 {
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+
+    fn arange_at(first: Self, second: Self, i: usize) -> Self {
+        first + i as f64 * (second - first)
+    }
+}
+
+// NumPy's float16 fill works in float32 between the rounded endpoints
+impl Element for Half {
+    fn from_f64(value: f64) -> Self {
+        Half(f32_to_f16(value as f32))
+    }
+
+    fn arange_at(first: Self, second: Self, i: usize) -> Self {
+        let (first, second) = (f16_to_f32(first.0), f16_to_f32(second.0));
+        Half(f32_to_f16(first + i as f32 * (second - first)))
+    }
+}
+
+/// Run `$body` with `$T` bound to the storage type of `$dtype`
+macro_rules! with_element {
+    ($dtype:expr, $T:ident => $body:expr) => {
+        match $dtype {
+            DType::Bool => {
+                type $T = bool;
+                $body
+            }
+            DType::Int8 => {
+                type $T = i8;
+                $body
+            }
+            DType::UInt8 => {
+                type $T = u8;
+                $body
+            }
+            DType::Int16 => {
+                type $T = i16;
+                $body
+            }
+            DType::UInt16 => {
+                type $T = u16;
+                $body
+            }
+            DType::Float16 => {
+                type $T = Half;
+                $body
+            }
+            DType::Int32 => {
+                type $T = i32;
+                $body
+            }
+            DType::UInt32 => {
+                type $T = u32;
+                $body
+            }
+            DType::Float32 => {
+                type $T = f32;
+                $body
+            }
+            DType::Int64 => {
+                type $T = i64;
+                $body
+            }
+            DType::UInt64 => {
+                type $T = u64;
+                $body
+            }
+            DType::Float64 => {
+                type $T = f64;
+                $body
+            }
+        }
+    };
+}
+
+/// Zero-filled tensor
+pub fn tensor_zeros(dims: &[usize], dtype: DType) -> Result<u64, TensorError> {
+    let shape = TensorShape::new(dims)?;
+    register_buffer(Buffer::zeroed(dtype, shape.numel())?, shape)
+}
+
+/// Tensor of ones (true for bool)
+pub fn tensor_ones(dims: &[usize], dtype: DType) -> Result<u64, TensorError> {
+    let shape = TensorShape::new(dims)?;
+    let count = shape.numel();
+    let mut buffer = Buffer::uninit(dtype, count)?;
+    with_element!(dtype, T => unsafe { fill_cpu_dispatch(buffer.as_mut_ptr().cast::<T>(), count, T::from_f64(1.0)) });
+    register_buffer(buffer, shape)
+}
+
+/// Number of elements of `np.arange(start, stop, step)`
+pub fn arange_len(start: f64, stop: f64, step: f64) -> Result<usize, TensorError> {
+    if step == 0.0 {
+        return Err(TensorError::BadRange("arange", "step must not be zero".to_string()));
+    }
+    let len = ((stop - start) / step).ceil();
+    if len.is_nan() || len >= isize::MAX as f64 {
+        return Err(TensorError::BadRange(
+            "arange",
+            format!("cannot compute the length of [{}, {}) in steps of {}", start, stop, step),
+        ));
+    }
+    Ok(len.max(0.0) as usize)
+}
+
+/// 1-D tensor of `start, start + step, ...` up to (excluding) `stop`
+pub fn tensor_arange(start: f64, stop: f64, step: f64, dtype: DType) -> Result<u64, TensorError> {
+    let len = arange_len(start, stop, step)?;
+    if dtype == DType::Bool && len > 2 {
+        return Err(TensorError::BadRange("arange", format!("a bool range holds at most 2 elements, not {}", len)));
+    }
+    let shape = TensorShape::new(&[len])?;
+    let mut buffer = Buffer::uninit(dtype, len)?;
+    with_element!(dtype, T => {
+        let (first, second) = (T::from_f64(start), T::from_f64(start + step));
+        let value = |i: usize| match i {
+            0 => first,
+            1 => second,
+            _ => T::arange_at(first, second, i),
+        };
+        unsafe { fill_indexed_cpu_dispatch(buffer.as_mut_ptr().cast::<T>(), len, value) }
+    });
+    register_buffer(buffer, shape)
+}
+
+/// 1-D tensor of `num` evenly spaced values from `start` to `stop`
+/// (included when `endpoint`)
+pub fn tensor_linspace(start: f64, stop: f64, num: usize, endpoint: bool, dtype: DType) -> Result<u64, TensorError> {
+    let div = if endpoint { num.saturating_sub(1) } else { num };
+    let delta = stop - start;
+    let step = delta / div as f64;
+    let at = move |i: usize| -> f64 {
+        if endpoint && div > 0 && i == div {
+            stop
+        } else if div == 0 {
+            start
+        } else if step == 0.0 {
+            // Underflowed step; scale the fraction instead, as NumPy does
+            i as f64 / div as f64 * delta + start
+        } else {
+            i as f64 * step + start
+        }
+    };
+    let floor = dtype.is_integer();
+
+    let shape = TensorShape::new(&[num])?;
+    let mut buffer = Buffer::uninit(dtype, num)?;
+    with_element!(dtype, T => {
+        let value = |i: usize| T::from_f64(if floor { at(i).floor() } else { at(i) });
+        unsafe { fill_indexed_cpu_dispatch(buffer.as_mut_ptr().cast::<T>(), num, value) }
+    });
+    register_buffer(buffer, shape)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tensor::registry::tensor_info;
+    use crate::tensor::unregister_tensor;
+
+    /// Contents of a constructed tensor, unregistering it
+    fn take<T: Copy>(handle: u64) -> Vec<T> {
+        let info = tensor_info(handle).unwrap();
+        let values = unsafe { std::slice::from_raw_parts(info.ptr as *const T, info.shape().numel()) }.to_vec();
+        unregister_tensor(handle).unwrap();
+        values
+    }
+
+    #[test]
+    fn test_zeros_and_ones() {
+        let handle = tensor_zeros(&[2, 3], DType::Int64).unwrap();
+        assert_eq!(tensor_info(handle).unwrap().shape().dims(), &[2, 3]);
+        assert_eq!(take::<i64>(handle), vec![0; 6]);
+        assert_eq!(take::<f32>(tensor_ones(&[4], DType::Float32).unwrap()), vec![1.0; 4]);
+        assert_eq!(take::<bool>(tensor_ones(&[2], DType::Bool).unwrap()), vec![true; 2]);
+        assert_eq!(take::<u16>(tensor_ones(&[1], DType::Float16).unwrap()), vec![0x3C00]);
+        assert!(take::<f64>(tensor_ones(&[0, 5], DType::Float64).unwrap()).is_empty());
+    }
+
+    #[test]
+    fn test_arange_matches_numpy() {
+        // np.arange(0.1, 1.0, 0.2) and friends, values from NumPy
+        assert_eq!(arange_len(0.1, 1.0, 0.2), Ok(5));
+        assert_eq!(arange_len(0.0, 1.0, 0.1), Ok(10));
+        assert_eq!(arange_len(5.0, 1.0, -1.5), Ok(3));
+        assert_eq!(arange_len(1.0, 5.0, -1.0), Ok(0));
+        assert_eq!(arange_len(0.0, 0.0, 1.0), Ok(0));
+        assert_eq!(
+            arange_len(0.0, 1.0, 0.0).unwrap_err().to_string(),
+            "arange: step must not be zero"
+        );
+        assert!(arange_len(0.0, f64::INFINITY, 1.0).is_err());
+        assert!(arange_len(0.0, f64::NAN, 1.0).is_err());
+
+        let values = take::<f64>(tensor_arange(0.1, 1.0, 0.2, DType::Float64).unwrap());
+        let delta = (0.1 + 0.2) - 0.1;
+        let expected: Vec<f64> = [0.1, 0.1 + 0.2].into_iter().chain((2..5).map(|i| 0.1 + i as f64 * delta)).collect();
+        assert_eq!(values, expected);
+        assert_eq!(take::<i32>(tensor_arange(5.0, -4.0, -3.0, DType::Int32).unwrap()), vec![5, 2, -1]);
+        // NumPy casts the first two values, then steps by their difference
+        assert_eq!(take::<i32>(tensor_arange(0.0, 5.0, 1.5, DType::Int32).unwrap()), vec![0, 1, 2, 3]);
+        assert_eq!(take::<f32>(tensor_arange(0.0, 2.0, 0.5, DType::Float32).unwrap()), vec![0.0, 0.5, 1.0, 1.5]);
+
+        assert_eq!(take::<bool>(tensor_arange(0.0, 2.0, 1.0, DType::Bool).unwrap()), vec![false, true]);
+        assert!(matches!(tensor_arange(0.0, 3.0, 1.0, DType::Bool), Err(TensorError::BadRange(..))));
+    }
+
+    #[test]
+    fn test_linspace_endpoints() {
+        let values = take::<f64>(tensor_linspace(0.0, 1.0, 7, true, DType::Float64).unwrap());
+        assert_eq!((values[0], values[6]), (0.0, 1.0));
+        assert_eq!(values[3], 3.0 * (1.0 / 6.0));
+        let values = take::<f64>(tensor_linspace(0.0, 1.0, 4, false, DType::Float64).unwrap());
+        assert_eq!(values, vec![0.0, 0.25, 0.5, 0.75]);
+        let values = take::<f32>(tensor_linspace(-1.0, 1.0, 5, true, DType::Float32).unwrap());
+        assert_eq!(values, vec![-1.0, -0.5, 0.0, 0.5, 1.0]);
+
+        assert_eq!(take::<f64>(tensor_linspace(2.5, 9.0, 1, true, DType::Float64).unwrap()), vec![2.5]);
+        assert_eq!(take::<f64>(tensor_linspace(2.5, 9.0, 1, false, DType::Float64).unwrap()), vec![2.5]);
+        assert!(take::<f64>(tensor_linspace(2.5, 9.0, 0, true, DType::Float64).unwrap()).is_empty());
+        assert_eq!(take::<f64>(tensor_linspace(3.0, 3.0, 3, true, DType::Float64).unwrap()), vec![3.0; 3]);
+
+        // Integer dtypes floor, so negative values round down
+        assert_eq!(take::<i32>(tensor_linspace(-2.0, 1.0, 4, false, DType::Int32).unwrap()), vec![-2, -2, -1, 0]);
+    }
+
+    #[test]
+    fn test_large_fill_uses_every_index() {
+        let count = crate::ops::elementwise::PARALLEL_THRESHOLD_FILL + 17;
+        let values = take::<i64>(tensor_arange(0.0, count as f64, 1.0, DType::Int64).unwrap());
+        assert!(values.iter().enumerate().all(|(i, &v)| v == i as i64));
+    }
+}
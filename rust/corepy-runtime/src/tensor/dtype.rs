@@ -0,0 +1,180 @@
+// ============================================================================
+// Element Types
+// ============================================================================
+//
+// RESPONSIBILITIES:
+// - Name and size the element types the runtime hands buffers out for
+//
+// DESIGN:
+// - Names follow NumPy ("float32", "int64", ...), so Python passes
+//   `array.dtype.name` straight through
+// - Buffer-protocol exporters describe elements with `struct` format codes
+//   instead; from_buffer_format() maps native-order ones onto the same set
+
+/// Element type of a buffer
+///
+/// The discriminants are the dtype codes passed across the FFI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+pub enum DType {
+    Bool = 0,
+    Int8 = 1,
+    UInt8 = 2,
+    Int16 = 3,
+    UInt16 = 4,
+    Float16 = 5,
+    Int32 = 6,
+    UInt32 = 7,
+    Float32 = 8,
+    Int64 = 9,
+    UInt64 = 10,
+    Float64 = 11,
+}
+
+impl DType {
+    /// Every dtype, in code order
+    pub const ALL: [DType; 12] = [
+        DType::Bool,
+        DType::Int8,
+        DType::UInt8,
+        DType::Int16,
+        DType::UInt16,
+        DType::Float16,
+        DType::Int32,
+        DType::UInt32,
+        DType::Float32,
+        DType::Int64,
+        DType::UInt64,
+        DType::Float64,
+    ];
+
+    /// DType for an FFI dtype code, if valid
+    pub fn from_code(code: u8) -> Option<DType> {
+        Self::ALL.get(code as usize).copied()
+    }
+
+    /// FFI dtype code
+    pub fn code(self) -> u8 {
+        self as u8
+    }
+
+    /// DType for a NumPy dtype name, if supported
+    pub fn from_name(name: &str) -> Option<DType> {
+        Some(match name {
+            "bool" => DType::Bool,
+            "int8" => DType::Int8,
+            "uint8" => DType::UInt8,
+            "int16" => DType::Int16,
+            "uint16" => DType::UInt16,
+            "float16" => DType::Float16,
+            "int32" => DType::Int32,
+            "uint32" => DType::UInt32,
+            "float32" => DType::Float32,
+            "int64" => DType::Int64,
+            "uint64" => DType::UInt64,
+            "float64" => DType::Float64,
+            _ => return None,
+        })
+    }
+
+    /// DType for a buffer-protocol (`struct` module) format of `itemsize`
+    /// bytes; only native byte order is supported. `l`, `q`, `n` and
+    /// friends are resolved by size, since C `long` differs by platform.
+    pub fn from_buffer_format(format: &str, itemsize: usize) -> Option<DType> {
+        let code = match format.as_bytes() {
+            [code] => *code,
+            [b'@' | b'=', code] => *code,
+            [b'<', code] if cfg!(target_endian = "little") => *code,
+            [b'>' | b'!', code] if cfg!(target_endian = "big") => *code,
+            _ => return None,
+        };
+        let dtype = match (code, itemsize) {
+            (b'?', _) => DType::Bool,
+            (b'b', _) => DType::Int8,
+            (b'B' | b'c', _) => DType::UInt8,
+            (b'e', _) => DType::Float16,
+            (b'f', _) => DType::Float32,
+            (b'd', _) => DType::Float64,
+            (b'h' | b'i' | b'l' | b'q' | b'n', 2) => DType::Int16,
+            (b'h' | b'i' | b'l' | b'q' | b'n', 4) => DType::Int32,
+            (b'h' | b'i' | b'l' | b'q' | b'n', 8) => DType::Int64,
+            (b'H' | b'I' | b'L' | b'Q' | b'N', 2) => DType::UInt16,
+            (b'H' | b'I' | b'L' | b'Q' | b'N', 4) => DType::UInt32,
+            (b'H' | b'I' | b'L' | b'Q' | b'N', 8) => DType::UInt64,
+            _ => return None,
+        };
+        // An exporter whose itemsize disagrees with its format is broken
+        Some(dtype).filter(|dtype| dtype.size() == itemsize)
+    }
+
+    /// NumPy name
+    pub fn name(self) -> &'static str {
+        match self {
+            DType::Bool => "bool",
+            DType::Int8 => "int8",
+            DType::UInt8 => "uint8",
+            DType::Int16 => "int16",
+            DType::UInt16 => "uint16",
+            DType::Float16 => "float16",
+            DType::Int32 => "int32",
+            DType::UInt32 => "uint32",
+            DType::Float32 => "float32",
+            DType::Int64 => "int64",
+            DType::UInt64 => "uint64",
+            DType::Float64 => "float64",
+        }
+    }
+
+    /// Signed or unsigned integer (not bool)
+    pub fn is_integer(self) -> bool {
+        matches!(
+            self,
+            DType::Int8 | DType::UInt8 | DType::Int16 | DType::UInt16 | DType::Int32 | DType::UInt32 | DType::Int64 | DType::UInt64
+        )
+    }
+
+    /// Bytes per element
+    pub fn size(self) -> usize {
+        match self {
+            DType::Bool | DType::Int8 | DType::UInt8 => 1,
+            DType::Int16 | DType::UInt16 | DType::Float16 => 2,
+            DType::Int32 | DType::UInt32 | DType::Float32 => 4,
+            DType::Int64 | DType::UInt64 | DType::Float64 => 8,
+        }
+    }
+}
+
+impl std::fmt::Display for DType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffer_format_mapping() {
+        let cases = [
+            ("f", 4, Some(DType::Float32)),
+            ("=d", 8, Some(DType::Float64)),
+            ("?", 1, Some(DType::Bool)),
+            ("B", 1, Some(DType::UInt8)),
+            ("e", 2, Some(DType::Float16)),
+            // C long is 8 bytes on LP64 and 4 on Windows
+            ("l", 8, Some(DType::Int64)),
+            ("l", 4, Some(DType::Int32)),
+            ("Q", 8, Some(DType::UInt64)),
+            ("f", 8, None),
+            ("Zf", 8, None),
+            ("T{f:x:}", 4, None),
+            ("", 4, None),
+        ];
+        for (format, itemsize, expected) in cases {
+            assert_eq!(DType::from_buffer_format(format, itemsize), expected, "format {:?}", format);
+        }
+        let swapped = if cfg!(target_endian = "little") { ">i" } else { "<i" };
+        assert_eq!(DType::from_buffer_format(swapped, 4), None);
+    }
+}
@@ -0,0 +1,710 @@
+// ============================================================================
+// NPY Files
+// ============================================================================
+//
+// RESPONSIBILITIES:
+// - Write tensors (any layout) as NumPy .npy files
+// - Read .npy files into runtime-owned Buffers
+// - Write and read named tensor collections as .safetensors files
+//
+// DESIGN (npy):
+// - Format versions 1.0 and 2.0: magic, version, header length (u16 / u32
+//   little-endian), then a Python dict literal with descr, fortran_order and
+//   shape, space-padded so the data starts 64-byte aligned
+// - We write 1.0 unless the header doesn't fit its 16-bit length
+// - Only little-endian (or byte-order-free) C-order data for now; anything
+//   else is rejected rather than silently byte-swapped or transposed
+// - Strided tensors are written run by run, so saving a view never copies it
+//   first
+//
+// DESIGN (safetensors):
+// - u64 little-endian header length, a JSON header mapping each name to
+//   dtype, shape and [begin, end) data offsets, then the payloads back to
+//   back in header order; the header is space-padded to 8 bytes
+// - Every check (names, contiguity) runs before the file is created, so a
+//   rejected save leaves nothing behind
+// - Loading validates that the offsets tile the payload exactly, as the
+//   reference implementation does, and reads each tensor into its own Buffer
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use super::buffer::{Buffer, BufferError};
+use super::dtype::DType;
+use super::layout::Layout;
+use super::registry::TensorInfo;
+use super::shape::{ShapeError, TensorShape};
+
+const MAGIC: &[u8; 6] = b"\x93NUMPY";
+/// Data offset alignment NumPy has used since 1.x
+const HEADER_ALIGN: usize = 64;
+
+/// Why a .npy file couldn't be written or read
+#[derive(Debug)]
+pub enum NpyError {
+    Io(io::Error),
+    /// Not an .npy file
+    BadMagic,
+    /// Format version other than 1.0 or 2.0
+    UnsupportedVersion(u8, u8),
+    /// Header dict we can't parse
+    BadHeader(String),
+    /// descr names a type the runtime has no dtype for
+    UnsupportedDescr(String),
+    /// Big-endian data (descr given)
+    BigEndian(String),
+    FortranOrder,
+    /// File ends before all elements (bytes expected, bytes present)
+    Truncated(u64, u64),
+    Shape(ShapeError),
+    Buffer(BufferError),
+}
+
+impl std::fmt::Display for NpyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NpyError::Io(err) => write!(f, "{}", err),
+            NpyError::BadMagic => write!(f, "not an .npy file (bad magic string)"),
+            NpyError::UnsupportedVersion(major, minor) => {
+                write!(f, "unsupported .npy format version {}.{} (expected 1.0 or 2.0)", major, minor)
+            }
+            NpyError::BadHeader(reason) => write!(f, "malformed .npy header: {}", reason),
+            NpyError::UnsupportedDescr(descr) => write!(f, "unsupported .npy dtype '{}'", descr),
+            NpyError::BigEndian(descr) => {
+                write!(f, "big-endian .npy data ('{}') is not supported; save it little-endian", descr)
+            }
+            NpyError::FortranOrder => {
+                write!(f, "fortran_order=True .npy files are not supported; save a C-ordered array")
+            }
+            NpyError::Truncated(expected, found) => {
+                write!(f, ".npy file truncated: header promises {} data bytes, found {}", expected, found)
+            }
+            NpyError::Shape(err) => write!(f, "{}", err),
+            NpyError::Buffer(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl From<io::Error> for NpyError {
+    fn from(err: io::Error) -> Self {
+        NpyError::Io(err)
+    }
+}
+
+impl From<ShapeError> for NpyError {
+    fn from(err: ShapeError) -> Self {
+        NpyError::Shape(err)
+    }
+}
+
+impl From<BufferError> for NpyError {
+    fn from(err: BufferError) -> Self {
+        NpyError::Buffer(err)
+    }
+}
+
+/// NumPy descr of `dtype` in little-endian byte order
+fn descr(dtype: DType) -> &'static str {
+    match dtype {
+        DType::Bool => "|b1",
+        DType::Int8 => "|i1",
+        DType::UInt8 => "|u1",
+        DType::Int16 => "<i2",
+        DType::UInt16 => "<u2",
+        DType::Float16 => "<f2",
+        DType::Int32 => "<i4",
+        DType::UInt32 => "<u4",
+        DType::Float32 => "<f4",
+        DType::Int64 => "<i8",
+        DType::UInt64 => "<u8",
+        DType::Float64 => "<f8",
+    }
+}
+
+fn dtype_from_descr(descr_str: &str) -> Result<DType, NpyError> {
+    let (order, kind) = descr_str.split_at(descr_str.len().min(1));
+    let little = match order {
+        "<" | "|" => true,
+        "=" => cfg!(target_endian = "little"),
+        ">" => false,
+        _ => return Err(NpyError::UnsupportedDescr(descr_str.to_string())),
+    };
+    let dtype = DType::ALL
+        .into_iter()
+        .find(|&dtype| &descr(dtype)[1..] == kind)
+        .ok_or_else(|| NpyError::UnsupportedDescr(descr_str.to_string()))?;
+    // Single-byte types have no byte order; '>b1' is as good as '|b1'
+    if !little && dtype.size() > 1 {
+        return Err(NpyError::BigEndian(descr_str.to_string()));
+    }
+    Ok(dtype)
+}
+
+/// Header dict text for `shape`, padded and newline-terminated so the data
+/// after `prefix_len` bytes of magic, version and length starts aligned
+fn header_text(dtype: DType, shape: &TensorShape, prefix_len: usize) -> Vec<u8> {
+    let dims: Vec<String> = shape.dims().iter().map(|dim| dim.to_string()).collect();
+    let tuple = match dims.len() {
+        1 => format!("({},)", dims[0]),
+        _ => format!("({})", dims.join(", ")),
+    };
+    let mut text = format!("{{'descr': '{}', 'fortran_order': False, 'shape': {}, }}", descr(dtype), tuple);
+    let unpadded = prefix_len + text.len() + 1;
+    text.extend(std::iter::repeat_n(' ', unpadded.next_multiple_of(HEADER_ALIGN) - unpadded));
+    text.push('\n');
+    text.into_bytes()
+}
+
+/// Write the `layout` view at `ptr` to `path` as a C-ordered .npy file
+///
+/// # Safety
+/// `ptr` must be valid for every element `layout` reaches
+pub unsafe fn save_npy(path: &Path, ptr: *const u8, layout: &Layout, dtype: DType) -> Result<(), NpyError> {
+    // magic + version + u16 length
+    let mut header = header_text(dtype, layout.shape(), MAGIC.len() + 2 + 2);
+    let version = match u16::try_from(header.len()) {
+        Ok(_) => 1,
+        Err(_) => {
+            header = header_text(dtype, layout.shape(), MAGIC.len() + 2 + 4);
+            2
+        }
+    };
+
+    let mut out = BufWriter::new(File::create(path)?);
+    out.write_all(MAGIC)?;
+    out.write_all(&[version, 0])?;
+    match version {
+        1 => out.write_all(&(header.len() as u16).to_le_bytes())?,
+        _ => out.write_all(&(header.len() as u32).to_le_bytes())?,
+    }
+    out.write_all(&header)?;
+
+    let size = dtype.size();
+    for (offset, len) in layout.iter_contiguous_runs() {
+        let run = std::slice::from_raw_parts(ptr.offset(offset * size as isize), len * size);
+        out.write_all(run)?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// Read an .npy file into a new Buffer
+pub fn load_npy(path: &Path) -> Result<(Buffer, TensorShape), NpyError> {
+    let file = File::open(path)?;
+    let file_len = file.metadata()?.len();
+    let mut input = BufReader::new(file);
+
+    let mut preamble = [0u8; 8];
+    input.read_exact(&mut preamble).map_err(|_| NpyError::BadMagic)?;
+    if &preamble[..6] != MAGIC {
+        return Err(NpyError::BadMagic);
+    }
+    let header_len = match (preamble[6], preamble[7]) {
+        (1, 0) => {
+            let mut len = [0u8; 2];
+            input.read_exact(&mut len)?;
+            u16::from_le_bytes(len) as usize
+        }
+        (2, 0) => {
+            let mut len = [0u8; 4];
+            input.read_exact(&mut len)?;
+            u32::from_le_bytes(len) as usize
+        }
+        (major, minor) => return Err(NpyError::UnsupportedVersion(major, minor)),
+    };
+    let prefix_len = 8 + if preamble[6] == 1 { 2 } else { 4 };
+
+    let mut header = vec![0u8; header_len];
+    input.read_exact(&mut header).map_err(|_| NpyError::BadHeader("file ends inside the header".to_string()))?;
+    let header = std::str::from_utf8(&header).map_err(|_| NpyError::BadHeader("header is not ASCII".to_string()))?;
+    let (dtype, shape) = parse_header(header)?;
+
+    // Check the header's size against the file before allocating for it
+    let expected = shape.numel().checked_mul(dtype.size()).ok_or(BufferError::TooLarge(usize::MAX))? as u64;
+    let available = file_len.saturating_sub((prefix_len + header_len) as u64);
+    if available < expected {
+        return Err(NpyError::Truncated(expected, available));
+    }
+    let mut buffer = Buffer::uninit(dtype, shape.numel())?;
+    let data = unsafe { std::slice::from_raw_parts_mut(buffer.as_mut_ptr(), buffer.len_bytes()) };
+    input.read_exact(data)?;
+    Ok((buffer, shape))
+}
+
+/// dtype and shape from a header dict such as
+/// `{'descr': '<f4', 'fortran_order': False, 'shape': (3, 4), }`
+fn parse_header(header: &str) -> Result<(DType, TensorShape), NpyError> {
+    let bad = |reason: &str| NpyError::BadHeader(reason.to_string());
+    let body = header
+        .trim_end()
+        .strip_prefix('{')
+        .and_then(|rest| rest.strip_suffix('}'))
+        .ok_or_else(|| bad("expected a dict"))?;
+
+    let (mut descr_str, mut fortran_order, mut dims) = (None, None, None);
+    let mut rest = body.trim_start();
+    while !rest.is_empty() {
+        let (key, after) = quoted(rest).ok_or_else(|| bad("expected a quoted key"))?;
+        let after = after.trim_start().strip_prefix(':').ok_or_else(|| bad("expected ':' after a key"))?;
+        let after = after.trim_start();
+        rest = match key {
+            "descr" => {
+                let (value, after) = quoted(after).ok_or_else(|| bad("descr must be a string"))?;
+                descr_str = Some(value);
+                after
+            }
+            "fortran_order" => {
+                let (value, after) = if let Some(after) = after.strip_prefix("True") {
+                    (true, after)
+                } else if let Some(after) = after.strip_prefix("False") {
+                    (false, after)
+                } else {
+                    return Err(bad("fortran_order must be True or False"));
+                };
+                fortran_order = Some(value);
+                after
+            }
+            "shape" => {
+                let end = after.find(')').ok_or_else(|| bad("shape must be a tuple"))?;
+                let inner = after[..end].strip_prefix('(').ok_or_else(|| bad("shape must be a tuple"))?;
+                let parsed = inner
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|dim| !dim.is_empty())
+                    .map(|dim| dim.parse::<usize>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| bad("shape entries must be non-negative integers"))?;
+                dims = Some(parsed);
+                &after[end + 1..]
+            }
+            other => return Err(NpyError::BadHeader(format!("unexpected key '{}'", other))),
+        };
+        rest = rest.trim_start();
+        rest = rest.strip_prefix(',').unwrap_or(rest).trim_start();
+    }
+
+    let descr_str = descr_str.ok_or_else(|| bad("missing 'descr'"))?;
+    let dims = dims.ok_or_else(|| bad("missing 'shape'"))?;
+    let dtype = dtype_from_descr(descr_str)?;
+    if fortran_order.ok_or_else(|| bad("missing 'fortran_order'"))? {
+        return Err(NpyError::FortranOrder);
+    }
+    Ok((dtype, TensorShape::new(&dims)?))
+}
+
+/// A single- or double-quoted string at the start of `text`, and what follows
+fn quoted(text: &str) -> Option<(&str, &str)> {
+    let quote = text.chars().next().filter(|&c| c == '\'' || c == '"')?;
+    let end = text[1..].find(quote)? + 1;
+    Some((&text[1..end], &text[end + 1..]))
+}
+
+/// Key the safetensors header reserves for free-form metadata
+const SAFETENSORS_METADATA: &str = "__metadata__";
+/// Largest header we accept; the reference loader uses the same limit
+const SAFETENSORS_MAX_HEADER: u64 = 100 * 1024 * 1024;
+
+/// Why a .safetensors file couldn't be written or read
+#[derive(Debug)]
+pub enum SafetensorsError {
+    Io(io::Error),
+    /// Two tensors with the same name
+    DuplicateName(String),
+    /// Name the format reserves
+    ReservedName(String),
+    /// Tensor (named) isn't C-contiguous; payloads are raw row-major bytes
+    NotContiguous(String),
+    /// dtype string the runtime has no dtype for (name, dtype)
+    UnsupportedDtype(String, String),
+    /// Header we can't parse
+    BadHeader(String),
+    /// Data offsets that don't tile the payload
+    BadOffsets(String),
+    Shape(ShapeError),
+    Buffer(BufferError),
+}
+
+impl std::fmt::Display for SafetensorsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SafetensorsError::Io(err) => write!(f, "{}", err),
+            SafetensorsError::DuplicateName(name) => write!(f, "duplicate tensor name '{}'", name),
+            SafetensorsError::ReservedName(name) => write!(f, "tensor name '{}' is reserved by safetensors", name),
+            SafetensorsError::NotContiguous(name) => {
+                write!(f, "tensor '{}' is not C-contiguous; copy it before saving", name)
+            }
+            SafetensorsError::UnsupportedDtype(name, dtype) => {
+                write!(f, "tensor '{}' has unsupported safetensors dtype '{}'", name, dtype)
+            }
+            SafetensorsError::BadHeader(reason) => write!(f, "malformed safetensors header: {}", reason),
+            SafetensorsError::BadOffsets(reason) => write!(f, "invalid safetensors data offsets: {}", reason),
+            SafetensorsError::Shape(err) => write!(f, "{}", err),
+            SafetensorsError::Buffer(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl From<io::Error> for SafetensorsError {
+    fn from(err: io::Error) -> Self {
+        SafetensorsError::Io(err)
+    }
+}
+
+impl From<ShapeError> for SafetensorsError {
+    fn from(err: ShapeError) -> Self {
+        SafetensorsError::Shape(err)
+    }
+}
+
+impl From<BufferError> for SafetensorsError {
+    fn from(err: BufferError) -> Self {
+        SafetensorsError::Buffer(err)
+    }
+}
+
+/// safetensors name of `dtype`
+fn safetensors_dtype(dtype: DType) -> &'static str {
+    match dtype {
+        DType::Bool => "BOOL",
+        DType::Int8 => "I8",
+        DType::UInt8 => "U8",
+        DType::Int16 => "I16",
+        DType::UInt16 => "U16",
+        DType::Float16 => "F16",
+        DType::Int32 => "I32",
+        DType::UInt32 => "U32",
+        DType::Float32 => "F32",
+        DType::Int64 => "I64",
+        DType::UInt64 => "U64",
+        DType::Float64 => "F64",
+    }
+}
+
+/// Write `tensors` to `path` in the given order
+///
+/// # Safety
+/// Each tensor's `ptr` must be valid for its `nbytes()`
+pub unsafe fn save_safetensors(path: &Path, tensors: &[(&str, &TensorInfo)]) -> Result<(), SafetensorsError> {
+    let mut seen = std::collections::HashSet::new();
+    let mut header = serde_json::Map::new();
+    let mut offset = 0usize;
+    for &(name, info) in tensors {
+        if name == SAFETENSORS_METADATA {
+            return Err(SafetensorsError::ReservedName(name.to_string()));
+        }
+        if !seen.insert(name) {
+            return Err(SafetensorsError::DuplicateName(name.to_string()));
+        }
+        if !info.layout.is_c_contiguous() {
+            return Err(SafetensorsError::NotContiguous(name.to_string()));
+        }
+        let end = offset + info.nbytes();
+        header.insert(
+            name.to_string(),
+            serde_json::json!({
+                "dtype": safetensors_dtype(info.dtype),
+                "shape": info.shape().dims(),
+                "data_offsets": [offset, end],
+            }),
+        );
+        offset = end;
+    }
+
+    let mut header = serde_json::to_vec(&header).expect("header is plain JSON");
+    header.resize(header.len().next_multiple_of(8), b' ');
+
+    let mut out = BufWriter::new(File::create(path)?);
+    out.write_all(&(header.len() as u64).to_le_bytes())?;
+    out.write_all(&header)?;
+    for (_, info) in tensors {
+        out.write_all(std::slice::from_raw_parts(info.ptr as *const u8, info.nbytes()))?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// Tensors of a .safetensors file, in payload order
+pub fn load_safetensors(path: &Path) -> Result<Vec<(String, Buffer, TensorShape)>, SafetensorsError> {
+    let bad = |reason: String| SafetensorsError::BadHeader(reason);
+    let file = File::open(path)?;
+    let file_len = file.metadata()?.len();
+    let mut input = BufReader::new(file);
+
+    let mut len = [0u8; 8];
+    input.read_exact(&mut len).map_err(|_| bad("file is shorter than the header length".to_string()))?;
+    let header_len = u64::from_le_bytes(len);
+    if header_len > SAFETENSORS_MAX_HEADER || header_len > file_len - 8 {
+        return Err(bad(format!("header length {} exceeds the file", header_len)));
+    }
+    let mut header = vec![0u8; header_len as usize];
+    input.read_exact(&mut header)?;
+    let header: serde_json::Map<String, serde_json::Value> =
+        serde_json::from_slice(&header).map_err(|err| bad(err.to_string()))?;
+
+    let mut tensors = Vec::with_capacity(header.len());
+    for (name, entry) in header {
+        if name == SAFETENSORS_METADATA {
+            continue;
+        }
+        let field = |key: &str| entry.get(key).ok_or_else(|| bad(format!("tensor '{}' has no '{}'", name, key)));
+        let dtype_name = field("dtype")?.as_str().ok_or_else(|| bad(format!("tensor '{}': dtype must be a string", name)))?;
+        let dtype = DType::ALL
+            .into_iter()
+            .find(|&dtype| safetensors_dtype(dtype) == dtype_name)
+            .ok_or_else(|| SafetensorsError::UnsupportedDtype(name.clone(), dtype_name.to_string()))?;
+        let dims: Vec<usize> = serde_json::from_value(field("shape")?.clone())
+            .map_err(|_| bad(format!("tensor '{}': shape must be a list of sizes", name)))?;
+        let [begin, end]: [u64; 2] = serde_json::from_value(field("data_offsets")?.clone())
+            .map_err(|_| bad(format!("tensor '{}': data_offsets must be [begin, end]", name)))?;
+        let shape = TensorShape::new(&dims)?;
+        if end.checked_sub(begin) != Some((shape.numel() * dtype.size()) as u64) {
+            return Err(SafetensorsError::BadOffsets(format!(
+                "tensor '{}' spans [{}, {}) but {:?} {} needs {} bytes",
+                name, begin, end, dims, dtype, shape.numel() * dtype.size()
+            )));
+        }
+        tensors.push((begin, end, name, dtype, shape));
+    }
+
+    tensors.sort_by_key(|&(begin, end, ..)| (begin, end));
+    let payload = file_len - 8 - header_len;
+    let mut expected = 0;
+    for (begin, end, name, ..) in &tensors {
+        if *begin != expected {
+            return Err(SafetensorsError::BadOffsets(format!(
+                "tensor '{}' starts at {}, expected {}", name, begin, expected
+            )));
+        }
+        expected = *end;
+    }
+    if expected != payload {
+        return Err(SafetensorsError::BadOffsets(format!(
+            "tensors cover {} bytes but the payload has {}", expected, payload
+        )));
+    }
+
+    tensors
+        .into_iter()
+        .map(|(_, _, name, dtype, shape)| {
+            let mut buffer = Buffer::uninit(dtype, shape.numel())?;
+            let data = unsafe { std::slice::from_raw_parts_mut(buffer.as_mut_ptr(), buffer.len_bytes()) };
+            input.read_exact(data)?;
+            Ok((name, buffer, shape))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("corepy-npy-{}-{}.npy", name, uuid::Uuid::new_v4()))
+    }
+
+    fn round_trip<T: Copy + PartialEq + std::fmt::Debug>(values: &[T], dims: &[usize], dtype: DType) {
+        let path = scratch(dtype.name());
+        let layout = Layout::c_contiguous(TensorShape::new(dims).unwrap());
+        unsafe { save_npy(&path, values.as_ptr().cast(), &layout, dtype) }.unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        let header_len = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+        assert_eq!((10 + header_len) % HEADER_ALIGN, 0);
+
+        let (buffer, shape) = load_npy(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!((shape.dims(), buffer.dtype()), (dims, dtype));
+        let loaded = unsafe { std::slice::from_raw_parts(buffer.as_ptr().cast::<T>(), buffer.len()) };
+        assert_eq!(loaded, values);
+    }
+
+    #[test]
+    fn test_round_trip_dtypes_and_shapes() {
+        let floats: Vec<f32> = (0..24).map(|i| i as f32 * 0.5).collect();
+        round_trip(&floats, &[24], DType::Float32);
+        round_trip(&floats, &[2, 3, 4], DType::Float32);
+        round_trip(&floats[..1], &[], DType::Float32);
+        round_trip(&floats[..0], &[0, 5], DType::Float32);
+        let ints: Vec<i32> = (-6..6).collect();
+        round_trip(&ints, &[3, 4], DType::Int32);
+        let doubles: Vec<f64> = (0..7).map(|i| 1.0 / (i + 1) as f64).collect();
+        round_trip(&doubles, &[7, 1], DType::Float64);
+    }
+
+    #[test]
+    fn test_strided_view_written_in_logical_order() {
+        let data: Vec<i32> = (0..12).collect();
+        // Transpose of a 3x4 matrix
+        let layout = Layout::new(TensorShape::new(&[4, 3]).unwrap(), &[1, 4]).unwrap();
+        let path = scratch("strided");
+        unsafe { save_npy(&path, data.as_ptr().cast(), &layout, DType::Int32) }.unwrap();
+        let (buffer, shape) = load_npy(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(shape.dims(), &[4, 3]);
+        let loaded = unsafe { std::slice::from_raw_parts(buffer.as_ptr().cast::<i32>(), 12) };
+        assert_eq!(loaded, &[0, 4, 8, 1, 5, 9, 2, 6, 10, 3, 7, 11]);
+    }
+
+    #[test]
+    fn test_header_parsing() {
+        let (dtype, shape) = parse_header("{'descr': '<f8', 'fortran_order': False, 'shape': (2, 3), }    \n").unwrap();
+        assert_eq!((dtype, shape.dims()), (DType::Float64, &[2usize, 3][..]));
+        // Key order and trailing comma are free
+        let (dtype, shape) = parse_header("{\"shape\": (5,), \"fortran_order\": False, \"descr\": \"|u1\"}").unwrap();
+        assert_eq!((dtype, shape.dims()), (DType::UInt8, &[5usize][..]));
+
+        let header = "{'descr': '>f4', 'fortran_order': False, 'shape': (2,), }";
+        let err = parse_header(header).unwrap_err();
+        assert_eq!(err.to_string(), "big-endian .npy data ('>f4') is not supported; save it little-endian");
+        let header = "{'descr': '<f4', 'fortran_order': True, 'shape': (2, 2), }";
+        assert!(matches!(parse_header(header), Err(NpyError::FortranOrder)));
+        let header = "{'descr': '<c8', 'fortran_order': False, 'shape': (2,), }";
+        assert!(matches!(parse_header(header), Err(NpyError::UnsupportedDescr(_))));
+        assert!(matches!(parse_header("{'descr': '<f4', 'shape': (2,)}"), Err(NpyError::BadHeader(_))));
+        assert!(matches!(parse_header("{'descr': '<f4', 'fortran_order': False, 'shape': (-1,)}"), Err(NpyError::BadHeader(_))));
+    }
+
+    #[test]
+    fn test_version_2_and_corrupt_files() {
+        // A version 2.0 file as NumPy writes it for very long headers
+        let text = "{'descr': '<i4', 'fortran_order': False, 'shape': (2,), }";
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend([2, 0]);
+        bytes.extend((text.len() as u32 + 1).to_le_bytes());
+        bytes.extend(text.bytes());
+        bytes.push(b'\n');
+        bytes.extend(7i32.to_le_bytes());
+        bytes.extend((-1i32).to_le_bytes());
+        let path = scratch("v2");
+        std::fs::write(&path, &bytes).unwrap();
+        let (buffer, shape) = load_npy(&path).unwrap();
+        assert_eq!(shape.dims(), &[2]);
+        assert_eq!(unsafe { std::slice::from_raw_parts(buffer.as_ptr().cast::<i32>(), 2) }, &[7, -1]);
+
+        std::fs::write(&path, &bytes[..bytes.len() - 2]).unwrap();
+        let err = load_npy(&path).err().unwrap();
+        assert_eq!(err.to_string(), ".npy file truncated: header promises 8 data bytes, found 6");
+
+        bytes[6] = 3;
+        std::fs::write(&path, &bytes).unwrap();
+        assert!(matches!(load_npy(&path), Err(NpyError::UnsupportedVersion(3, 0))));
+        std::fs::write(&path, b"PK\x03\x04").unwrap();
+        assert!(matches!(load_npy(&path), Err(NpyError::BadMagic)));
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(load_npy(&path), Err(NpyError::Io(_))));
+    }
+
+    #[test]
+    fn test_oversized_header_shape_rejected_before_allocating() {
+        // 4 TiB promised by the header, 8 bytes in the file
+        let text = "{'descr': '<f4', 'fortran_order': False, 'shape': (1099511627776,), }";
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend([1, 0]);
+        bytes.extend((text.len() as u16 + 1).to_le_bytes());
+        bytes.extend(text.bytes());
+        bytes.push(b'\n');
+        bytes.extend([0u8; 8]);
+        let path = scratch("oversized");
+        std::fs::write(&path, &bytes).unwrap();
+        let result = load_npy(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(result, Err(NpyError::Truncated(4_398_046_511_104, 8))));
+    }
+
+    fn scratch_safetensors() -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("corepy-st-{}.safetensors", uuid::Uuid::new_v4()))
+    }
+
+    fn strided_info<T>(values: &[T], dims: &[usize], dtype: DType) -> TensorInfo {
+        let layout = Layout::c_contiguous(TensorShape::new(dims).unwrap());
+        TensorInfo { ptr: values.as_ptr() as usize, layout, dtype, readonly: true, device: crate::tensor::Device::Cpu }
+    }
+
+    #[test]
+    fn test_round_trip_multiple_tensors() {
+        let weights: Vec<f32> = (0..12).map(|i| i as f32 / 4.0).collect();
+        let bias = [1.5f64, -2.0, 0.25];
+        let steps = [7i32];
+        let mask = [1u8, 0, 1, 1, 0];
+        let tensors = [
+            ("weights", &strided_info(&weights, &[3, 4], DType::Float32)),
+            ("bias", &strided_info(&bias, &[3], DType::Float64)),
+            ("step", &strided_info(&steps, &[], DType::Int32)),
+            ("mask", &strided_info(&mask, &[5], DType::Bool)),
+        ];
+        let path = scratch_safetensors();
+        unsafe { save_safetensors(&path, &tensors) }.unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        let header_len = u64::from_le_bytes(bytes[..8].try_into().unwrap()) as usize;
+        assert_eq!(header_len % 8, 0);
+        let header: serde_json::Value = serde_json::from_slice(&bytes[8..8 + header_len]).unwrap();
+        assert_eq!(header["bias"], serde_json::json!({"dtype": "F64", "shape": [3], "data_offsets": [48, 72]}));
+
+        let loaded = load_safetensors(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let names: Vec<&str> = loaded.iter().map(|(name, ..)| name.as_str()).collect();
+        assert_eq!(names, ["weights", "bias", "step", "mask"]);
+        for ((name, buffer, shape), (_, original)) in loaded.iter().zip(tensors) {
+            assert_eq!((shape, buffer.dtype()), (original.shape(), original.dtype), "{}", name);
+            let expected = unsafe { std::slice::from_raw_parts(original.ptr as *const u8, original.nbytes()) };
+            let actual = unsafe { std::slice::from_raw_parts(buffer.as_ptr(), buffer.len_bytes()) };
+            assert_eq!(actual, expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_rejected_before_writing() {
+        let data = [0f32; 6];
+        let x = strided_info(&data, &[2, 3], DType::Float32);
+        let transposed = TensorInfo { layout: Layout::new(TensorShape::new(&[3, 2]).unwrap(), &[1, 3]).unwrap(), ..x.clone() };
+        let cases = [
+            (vec![("a", &x), ("a", &x)], "duplicate tensor name 'a'"),
+            (vec![("a", &x), ("t", &transposed)], "tensor 't' is not C-contiguous; copy it before saving"),
+            (vec![("__metadata__", &x)], "tensor name '__metadata__' is reserved by safetensors"),
+        ];
+        for (tensors, message) in cases {
+            let path = scratch_safetensors();
+            let err = unsafe { save_safetensors(&path, &tensors) }.unwrap_err();
+            assert_eq!(err.to_string(), message);
+            assert!(!path.exists());
+        }
+    }
+
+    fn write_raw(header: &str, payload: &[u8]) -> std::path::PathBuf {
+        let path = scratch_safetensors();
+        let mut bytes = (header.len() as u64).to_le_bytes().to_vec();
+        bytes.extend(header.bytes());
+        bytes.extend(payload);
+        std::fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_load_validation() {
+        let cases = [
+            (r#"{"a": {"dtype": "BF16", "shape": [2], "data_offsets": [0, 4]}}"#, 4, "unsupported safetensors dtype 'BF16'"),
+            (r#"{"a": {"dtype": "F32", "shape": [2], "data_offsets": [0, 4]}}"#, 4, "needs 8 bytes"),
+            (r#"{"a": {"dtype": "U8", "shape": [2], "data_offsets": [2, 4]}}"#, 4, "starts at 2, expected 0"),
+            (r#"{"a": {"dtype": "U8", "shape": [2], "data_offsets": [0, 2]}}"#, 4, "cover 2 bytes but the payload has 4"),
+            (r#"{"a": {"dtype": "U8", "data_offsets": [0, 2]}}"#, 2, "has no 'shape'"),
+            ("not json", 0, "malformed safetensors header"),
+        ];
+        for (header, payload, message) in cases {
+            let path = write_raw(header, &vec![0u8; payload]);
+            let err = load_safetensors(&path).err().unwrap();
+            std::fs::remove_file(&path).unwrap();
+            assert!(err.to_string().contains(message), "{} does not mention {:?}", err, message);
+        }
+
+        // Metadata is skipped; payload order follows the offsets
+        let header = r#"{"__metadata__": {"format": "pt"}, "b": {"dtype": "U8", "shape": [1], "data_offsets": [1, 2]}, "a": {"dtype": "U8", "shape": [1], "data_offsets": [0, 1]}}"#;
+        let path = write_raw(header, &[5, 6]);
+        let loaded = load_safetensors(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let values: Vec<(&str, u8)> =
+            loaded.iter().map(|(name, buffer, _)| (name.as_str(), unsafe { *buffer.as_ptr() })).collect();
+        assert_eq!(values, [("a", 5), ("b", 6)]);
+    }
+}
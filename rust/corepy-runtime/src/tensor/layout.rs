@@ -0,0 +1,379 @@
+// ============================================================================
+// Strided Layouts
+// ============================================================================
+//
+// RESPONSIBILITIES:
+// - Pair a TensorShape with per-axis strides
+// - Contiguity checks (C and Fortran order) and element offsets
+// - Split a strided view into the longest runs a flat kernel can process
+//
+// DESIGN:
+// - Strides are in elements and signed: negative strides come from reversed
+//   slices, zero strides from broadcast dimensions
+// - Contiguity follows NumPy: axes of size 1 don't constrain their stride,
+//   and an empty tensor is contiguous in both orders
+// - Offsets are relative to the view's first element, so they can be
+//   negative when a stride is
+
+use smallvec::SmallVec;
+
+use super::shape::{ShapeError, TensorShape};
+
+/// Per-axis strides in elements, inline up to 4D
+pub type Strides = SmallVec<[isize; 4]>;
+
+/// Why a layout could not be built or indexed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayoutError {
+    /// Shape does not fit in isize elements
+    Shape(ShapeError),
+    /// Number of strides differs from the number of dimensions
+    RankMismatch { ndim: usize, strides: usize },
+    /// A byte stride is not a multiple of the element size
+    Misaligned { axis: usize, stride: isize, itemsize: usize },
+    /// Index has the wrong number of coordinates
+    IndexRank { ndim: usize, index: usize },
+    /// Coordinate past the end of its axis
+    OutOfBounds { axis: usize, index: usize, dim: usize },
+}
+
+impl std::fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LayoutError::Shape(err) => write!(f, "{}", err),
+            LayoutError::RankMismatch { ndim, strides } => {
+                write!(f, "{} strides given for a {}-dimensional shape", strides, ndim)
+            }
+            LayoutError::Misaligned { axis, stride, itemsize } => write!(
+                f,
+                "stride {} of axis {} is not a multiple of the {}-byte element size",
+                stride, axis, itemsize
+            ),
+            LayoutError::IndexRank { ndim, index } => {
+                write!(f, "index has {} coordinates for a {}-dimensional layout", index, ndim)
+            }
+            LayoutError::OutOfBounds { axis, index, dim } => {
+                write!(f, "index {} is out of bounds for axis {} of size {}", index, axis, dim)
+            }
+        }
+    }
+}
+
+impl From<ShapeError> for LayoutError {
+    fn from(err: ShapeError) -> Self {
+        LayoutError::Shape(err)
+    }
+}
+
+/// Shape plus element strides of a (possibly non-contiguous) view
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layout {
+    shape: TensorShape,
+    strides: Strides,
+}
+
+impl Layout {
+    /// `shape` viewed with `strides`
+    pub fn new(shape: TensorShape, strides: &[isize]) -> Result<Self, LayoutError> {
+        if strides.len() != shape.ndim() {
+            return Err(LayoutError::RankMismatch { ndim: shape.ndim(), strides: strides.len() });
+        }
+        Ok(Layout { shape, strides: Strides::from_slice(strides) })
+    }
+
+    /// Layout from NumPy-style byte strides
+    pub fn from_byte_strides(dims: &[usize], byte_strides: &[isize], itemsize: usize) -> Result<Self, LayoutError> {
+        let shape = TensorShape::new(dims)?;
+        let strides = byte_strides
+            .iter()
+            .enumerate()
+            .map(|(axis, &stride)| match stride.checked_rem(itemsize as isize) {
+                Some(0) => Ok(stride / itemsize as isize),
+                _ => Err(LayoutError::Misaligned { axis, stride, itemsize }),
+            })
+            .collect::<Result<Strides, _>>()?;
+        Layout::new(shape, &strides)
+    }
+
+    /// Row-major layout: the last axis varies fastest
+    pub fn c_contiguous(shape: TensorShape) -> Self {
+        let strides = shape.contiguous_strides().iter().map(|&s| s as isize).collect();
+        Layout { shape, strides }
+    }
+
+    /// Column-major layout: the first axis varies fastest
+    #[allow(dead_code)]
+    pub fn f_contiguous(shape: TensorShape) -> Self {
+        let mut strides: Strides = smallvec::smallvec![0; shape.ndim()];
+        let mut stride = 1isize;
+        for (slot, &dim) in strides.iter_mut().zip(shape.dims()) {
+            *slot = stride;
+            stride *= dim as isize;
+        }
+        Layout { shape, strides }
+    }
+
+    pub fn shape(&self) -> &TensorShape {
+        &self.shape
+    }
+
+    pub fn strides(&self) -> &[isize] {
+        &self.strides
+    }
+
+    /// This view read as `target`: prepended and stretched axes get
+    /// stride 0 (see TensorShape::broadcast_strides)
+    pub fn broadcast_to(&self, target: &TensorShape) -> Result<Layout, ShapeError> {
+        // Validates that `target` is reachable by broadcasting alone
+        self.shape.broadcast_strides(target)?;
+        let offset = target.ndim() - self.shape.ndim();
+        let strides = (0..target.ndim())
+            .map(|axis| match axis.checked_sub(offset) {
+                Some(i) if self.shape.dims()[i] == target.dims()[axis] => self.strides[i],
+                _ => 0,
+            })
+            .collect();
+        Ok(Layout { shape: target.clone(), strides })
+    }
+
+    pub fn is_c_contiguous(&self) -> bool {
+        self.is_dense((0..self.shape.ndim()).rev())
+    }
+
+    pub fn is_f_contiguous(&self) -> bool {
+        self.is_dense(0..self.shape.ndim())
+    }
+
+    /// Whether walking `axes` (fastest first) visits consecutive elements
+    fn is_dense(&self, axes: impl Iterator<Item = usize>) -> bool {
+        if self.shape.numel() == 0 {
+            return true;
+        }
+        let dims = self.shape.dims();
+        let mut expected = 1isize;
+        for axis in axes {
+            if dims[axis] == 1 {
+                continue;
+            }
+            if self.strides[axis] != expected {
+                return false;
+            }
+            expected *= dims[axis] as isize;
+        }
+        true
+    }
+
+    /// Element offset of `index` from the view's first element
+    #[allow(dead_code)]
+    pub fn offset_of(&self, index: &[usize]) -> Result<isize, LayoutError> {
+        let dims = self.shape.dims();
+        if index.len() != dims.len() {
+            return Err(LayoutError::IndexRank { ndim: dims.len(), index: index.len() });
+        }
+        let mut offset = 0isize;
+        for (axis, (&i, &dim)) in index.iter().zip(dims).enumerate() {
+            if i >= dim {
+                return Err(LayoutError::OutOfBounds { axis, index: i, dim });
+            }
+            offset += i as isize * self.strides[axis];
+        }
+        Ok(offset)
+    }
+
+    /// Elements per run: the product of the trailing axes that are laid
+    /// out contiguously after one another (1 if the last stride isn't 1)
+    pub fn run_length(&self) -> usize {
+        self.run_axes().1
+    }
+
+    /// (first axis of the run, run length)
+    fn run_axes(&self) -> (usize, usize) {
+        let dims = self.shape.dims();
+        let mut first = dims.len();
+        let mut len = 1usize;
+        while first > 0 {
+            let axis = first - 1;
+            if dims[axis] != 1 && self.strides[axis] != len as isize {
+                break;
+            }
+            len *= dims[axis];
+            first = axis;
+        }
+        (first, len)
+    }
+
+    /// (offset, length) of each contiguous run, in row-major order of the
+    /// view; runs of a C-contiguous layout collapse into one
+    pub fn iter_contiguous_runs(&self) -> ContiguousRuns<'_> {
+        let (outer, len) = self.run_axes();
+        let runs = if self.shape.numel() == 0 { 0 } else { self.shape.dims()[..outer].iter().product() };
+        ContiguousRuns {
+            layout: self,
+            outer,
+            len,
+            index: smallvec::smallvec![0; outer],
+            offset: 0,
+            remaining: runs,
+        }
+    }
+}
+
+/// Iterator returned by Layout::iter_contiguous_runs()
+pub struct ContiguousRuns<'a> {
+    layout: &'a Layout,
+    /// Axes iterated over; the rest form each run
+    outer: usize,
+    len: usize,
+    index: SmallVec<[usize; 4]>,
+    offset: isize,
+    remaining: usize,
+}
+
+impl Iterator for ContiguousRuns<'_> {
+    type Item = (isize, usize);
+
+    fn next(&mut self) -> Option<(isize, usize)> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let run = (self.offset, self.len);
+
+        // Advance the outer index like an odometer, last axis first
+        let dims = self.layout.shape.dims();
+        for axis in (0..self.outer).rev() {
+            self.index[axis] += 1;
+            self.offset += self.layout.strides[axis];
+            if self.index[axis] < dims[axis] {
+                break;
+            }
+            self.offset -= self.layout.strides[axis] * dims[axis] as isize;
+            self.index[axis] = 0;
+        }
+        Some(run)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for ContiguousRuns<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout(dims: &[usize], strides: &[isize]) -> Layout {
+        Layout::new(TensorShape::new(dims).unwrap(), strides).unwrap()
+    }
+
+    fn runs(layout: &Layout) -> Vec<(isize, usize)> {
+        layout.iter_contiguous_runs().collect()
+    }
+
+    #[test]
+    fn test_contiguous_constructors() {
+        let shape = TensorShape::new(&[2, 3, 4]).unwrap();
+        let c = Layout::c_contiguous(shape.clone());
+        assert_eq!(c.strides(), &[12, 4, 1]);
+        assert!(c.is_c_contiguous() && !c.is_f_contiguous());
+        assert_eq!(runs(&c), vec![(0, 24)]);
+
+        let f = Layout::f_contiguous(shape);
+        assert_eq!(f.strides(), &[1, 2, 6]);
+        assert!(f.is_f_contiguous() && !f.is_c_contiguous());
+        assert_eq!(f.offset_of(&[1, 2, 3]), Ok(1 + 4 + 18));
+
+        // Size-1 axes don't pin their stride
+        assert!(layout(&[1, 5], &[99, 1]).is_c_contiguous());
+        assert!(layout(&[0, 5], &[7, 3]).is_c_contiguous());
+        let scalar = Layout::c_contiguous(TensorShape::scalar());
+        assert!(scalar.is_c_contiguous() && scalar.is_f_contiguous());
+        assert_eq!(runs(&scalar), vec![(0, 1)]);
+        assert_eq!(runs(&layout(&[0, 5], &[5, 1])), vec![]);
+    }
+
+    #[test]
+    fn test_transposed_2d() {
+        // Transpose of a contiguous 3x4
+        let t = layout(&[4, 3], &[1, 4]);
+        assert!(!t.is_c_contiguous());
+        assert!(t.is_f_contiguous());
+        assert_eq!(t.offset_of(&[2, 1]), Ok(6));
+        assert_eq!(t.run_length(), 1);
+        let all = runs(&t);
+        assert_eq!(all.len(), 12);
+        assert_eq!(&all[..4], &[(0, 1), (4, 1), (8, 1), (1, 1)]);
+    }
+
+    #[test]
+    fn test_sliced_step_view() {
+        // a[:, ::2] of a contiguous 3x8: rows are strided, so runs are single elements
+        let every_other = layout(&[3, 4], &[8, 2]);
+        assert!(!every_other.is_c_contiguous() && !every_other.is_f_contiguous());
+        assert_eq!(runs(&every_other)[..5], [(0, 1), (2, 1), (4, 1), (6, 1), (8, 1)]);
+
+        // a[::2, :] keeps whole rows contiguous
+        let every_other_row = layout(&[2, 8], &[16, 1]);
+        assert_eq!(runs(&every_other_row), vec![(0, 8), (16, 8)]);
+
+        // a[:, 1:3, :] of a contiguous 2x4x5: runs of the last two axes
+        let inner = layout(&[2, 2, 5], &[20, 5, 1]);
+        assert_eq!(runs(&inner), vec![(0, 10), (20, 10)]);
+    }
+
+    #[test]
+    fn test_negative_strides() {
+        // a[::-1] of a contiguous 3x4, offsets relative to the last row
+        let flipped = layout(&[3, 4], &[-4, 1]);
+        assert!(!flipped.is_c_contiguous());
+        assert_eq!(flipped.offset_of(&[2, 3]), Ok(-8 + 3));
+        assert_eq!(runs(&flipped), vec![(0, 4), (-4, 4), (-8, 4)]);
+
+        let reversed = layout(&[4], &[-1]);
+        assert_eq!(runs(&reversed), vec![(0, 1), (-1, 1), (-2, 1), (-3, 1)]);
+    }
+
+    #[test]
+    fn test_broadcast_zero_strides() {
+        // A row broadcast over 3 rows
+        let rows = layout(&[3, 4], &[0, 1]);
+        assert!(!rows.is_c_contiguous());
+        assert_eq!(rows.offset_of(&[2, 1]), Ok(1));
+        assert_eq!(runs(&rows), vec![(0, 4), (0, 4), (0, 4)]);
+
+        // A column broadcast over 4 columns
+        let cols = layout(&[3, 4], &[1, 0]);
+        assert_eq!(runs(&cols)[..5], [(0, 1), (0, 1), (0, 1), (0, 1), (1, 1)]);
+    }
+
+    #[test]
+    fn test_broadcast_to_keeps_view_strides() {
+        let flipped_row = layout(&[4], &[-2]);
+        let target = TensorShape::new(&[3, 4]).unwrap();
+        assert_eq!(flipped_row.broadcast_to(&target).unwrap().strides(), &[0, -2]);
+        let column = layout(&[3, 1], &[5, 1]);
+        assert_eq!(column.broadcast_to(&target).unwrap().strides(), &[5, 0]);
+        assert!(layout(&[2], &[1]).broadcast_to(&target).is_err());
+    }
+
+    #[test]
+    fn test_errors() {
+        let c = Layout::c_contiguous(TensorShape::new(&[2, 3]).unwrap());
+        assert_eq!(c.offset_of(&[2, 0]), Err(LayoutError::OutOfBounds { axis: 0, index: 2, dim: 2 }));
+        assert_eq!(c.offset_of(&[1]), Err(LayoutError::IndexRank { ndim: 2, index: 1 }));
+        assert_eq!(
+            Layout::new(TensorShape::new(&[2, 3]).unwrap(), &[3]),
+            Err(LayoutError::RankMismatch { ndim: 2, strides: 1 })
+        );
+
+        let from_bytes = Layout::from_byte_strides(&[3, 4], &[-16, 4], 4).unwrap();
+        assert_eq!(from_bytes.strides(), &[-4, 1]);
+        assert_eq!(
+            Layout::from_byte_strides(&[3, 4], &[16, 6], 4),
+            Err(LayoutError::Misaligned { axis: 1, stride: 6, itemsize: 4 })
+        );
+        assert!(Layout::from_byte_strides(&[3], &[4], 0).is_err());
+    }
+}
@@ -1,12 +1,60 @@
 // ============================================================================
 // Tensor: Internal Representation
 // ============================================================================
-// This module will contain tensor metadata, shape validation, and buffer management.
+// This module contains tensor metadata, shape validation, and buffer management.
 //
-// PLANNED STRUCTURE:
-// - shape.rs: Shape validation and broadcasting rules
+// STRUCTURE:
+// - shape: Shape validation and broadcasting rules
+// - layout: Strides, contiguity checks and contiguous runs of strided views
+// - dtype: Element types and their sizes
+// - buffer: Owned aligned allocations and the handle registry behind them
+// - creation: zeros / ones / arange / linspace constructors
+// - io: NumPy .npy and .safetensors reading and writing
+// - random: Seeded, thread-count-independent random tensors
+// - registry: Opaque tensor handles (and views onto them) for the handle-based
+//   FFI ops
+// - Device (here): where a tensor's memory lives; only Cpu is constructible
+//   until device backends land, but ops already refuse mixed devices
+//
+// PLANNED:
 // - dtype.rs: Type promotion and conversion
-// - buffer.rs: Arena-managed memory allocation
 
-// Placeholder for future implementation
-// TODO: Implement TensorShape, DType, Buffer types
+pub mod buffer;
+pub mod creation;
+pub mod dtype;
+pub mod io;
+pub mod layout;
+pub mod random;
+pub mod registry;
+pub mod shape;
+
+use serde::{Deserialize, Serialize};
+
+pub use self::creation::{tensor_arange, tensor_linspace, tensor_ones, tensor_zeros};
+pub use self::layout::Layout;
+pub use self::random::{tensor_random_normal, tensor_random_uniform};
+pub use self::registry::{
+    list_tensors, register_tensor, tensor_reshape, tensor_transpose_view, tensor_view, total_registered_bytes,
+    unregister_tensor,
+};
+pub use self::shape::TensorShape;
+
+/// Where a tensor's memory lives
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum Device {
+    #[default]
+    Cpu,
+    /// CUDA device ordinal
+    Cuda(u32),
+    Metal,
+}
+
+impl std::fmt::Display for Device {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Device::Cpu => write!(f, "cpu"),
+            Device::Cuda(ordinal) => write!(f, "cuda:{}", ordinal),
+            Device::Metal => write!(f, "metal"),
+        }
+    }
+}
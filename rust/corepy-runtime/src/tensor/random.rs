@@ -0,0 +1,210 @@
+// ============================================================================
+// Seeded Random Tensors
+// ============================================================================
+//
+// RESPONSIBILITIES:
+// - Fill runtime-owned or caller-owned float buffers with uniform or normal
+//   samples, without the GIL
+//
+// DESIGN:
+// - Counter-based: element i depends only on (seed, i), through SplitMix64
+//   of the seed and the counter. Any split of the buffer across workers
+//   therefore yields the same tensor, whatever the thread count
+// - Normal samples use Box-Muller on the two uniforms drawn for counters 2i
+//   and 2i + 1, keeping only the cosine branch so each element stays
+//   independent of its neighbours
+// - Samples are drawn in float64 and rounded to the buffer's dtype
+// - No seed means one drawn from the OS-seeded std hasher
+
+use std::hash::{BuildHasher, Hasher};
+
+use super::buffer::Buffer;
+use super::dtype::DType;
+use super::registry::{register_buffer, TensorError};
+use super::shape::TensorShape;
+use crate::ops::elementwise::fill_indexed_cpu_dispatch;
+
+/// Distribution to sample from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Distribution {
+    /// [low, high)
+    Uniform { low: f64, high: f64 },
+    Normal { mean: f64, std: f64 },
+}
+
+impl Distribution {
+    /// Distribution for an FFI name and its two parameters: (low, high)
+    /// for "uniform", (mean, std) for "normal"
+    pub fn from_name(name: &str, params: (f64, f64)) -> Result<Self, TensorError> {
+        let distribution = match name {
+            "uniform" => Distribution::Uniform { low: params.0, high: params.1 },
+            "normal" => Distribution::Normal { mean: params.0, std: params.1 },
+            _ => {
+                return Err(TensorError::BadRange(
+                    "random",
+                    format!("unknown distribution '{}' (expected uniform or normal)", name),
+                ))
+            }
+        };
+        distribution.validate()?;
+        Ok(distribution)
+    }
+
+    fn validate(&self) -> Result<(), TensorError> {
+        let reason = match *self {
+            Distribution::Uniform { low, high } if !(low.is_finite() && high.is_finite() && low <= high) => {
+                format!("uniform bounds must be finite with low <= high, got [{}, {})", low, high)
+            }
+            Distribution::Normal { mean, std } if !(mean.is_finite() && std.is_finite() && std >= 0.0) => {
+                format!("normal needs a finite mean and std >= 0, got mean {} std {}", mean, std)
+            }
+            _ => return Ok(()),
+        };
+        Err(TensorError::BadRange("random", reason))
+    }
+
+    /// Sample `i` of the stream for `seed`
+    fn sample(&self, seed: u64, i: u64) -> f64 {
+        match *self {
+            Distribution::Uniform { low, high } => low + (high - low) * unit(seed, i),
+            Distribution::Normal { mean, std } => {
+                // 1 - u keeps the log argument in (0, 1]
+                let radius = (-2.0 * (1.0 - unit(seed, 2 * i)).ln()).sqrt();
+                let angle = std::f64::consts::TAU * unit(seed, 2 * i + 1);
+                mean + std * radius * angle.cos()
+            }
+        }
+    }
+}
+
+/// SplitMix64 finaliser of `seed`'s stream at `counter`
+fn mix(seed: u64, counter: u64) -> u64 {
+    let mut z = seed.wrapping_add(counter.wrapping_add(1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Uniform in [0, 1) with 53 random bits
+fn unit(seed: u64, counter: u64) -> f64 {
+    (mix(seed, counter) >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+}
+
+/// A fresh seed from OS entropy
+pub fn entropy_seed() -> u64 {
+    std::collections::hash_map::RandomState::new().build_hasher().finish()
+}
+
+/// Fill `count` elements at `out` with samples of `distribution`
+///
+/// # Safety
+/// `out` must be valid for `count` elements of `dtype`
+pub unsafe fn random_fill(
+    out: *mut u8,
+    count: usize,
+    dtype: DType,
+    distribution: Distribution,
+    seed: u64,
+) -> Result<(), TensorError> {
+    distribution.validate()?;
+    let sample = move |i: usize| distribution.sample(seed, i as u64);
+    match dtype {
+        DType::Float32 => fill_indexed_cpu_dispatch(out.cast::<f32>(), count, |i| sample(i) as f32),
+        DType::Float64 => fill_indexed_cpu_dispatch(out.cast::<f64>(), count, sample),
+        other => {
+            return Err(TensorError::BadRange("random", format!("random tensors are float32 or float64, not {}", other)))
+        }
+    }
+    Ok(())
+}
+
+fn random_tensor(dims: &[usize], dtype: DType, distribution: Distribution, seed: Option<u64>) -> Result<u64, TensorError> {
+    let shape = TensorShape::new(dims)?;
+    let mut buffer = Buffer::uninit(dtype, shape.numel())?;
+    let seed = seed.unwrap_or_else(entropy_seed);
+    unsafe { random_fill(buffer.as_mut_ptr(), shape.numel(), dtype, distribution, seed)? };
+    register_buffer(buffer, shape)
+}
+
+/// New tensor of samples from [low, high)
+pub fn tensor_random_uniform(dims: &[usize], low: f64, high: f64, seed: Option<u64>, dtype: DType) -> Result<u64, TensorError> {
+    random_tensor(dims, dtype, Distribution::Uniform { low, high }, seed)
+}
+
+/// New tensor of normal samples
+pub fn tensor_random_normal(dims: &[usize], mean: f64, std: f64, seed: Option<u64>, dtype: DType) -> Result<u64, TensorError> {
+    random_tensor(dims, dtype, Distribution::Normal { mean, std }, seed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduler::rayon_pool::with_max_threads;
+
+    fn sampled(count: usize, distribution: Distribution, seed: u64) -> Vec<f64> {
+        let mut out = vec![0f64; count];
+        unsafe { random_fill(out.as_mut_ptr().cast(), count, DType::Float64, distribution, seed) }.unwrap();
+        out
+    }
+
+    fn moments(values: &[f64]) -> (f64, f64) {
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let var = values.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / n;
+        (mean, var)
+    }
+
+    #[test]
+    fn test_same_tensor_for_any_thread_count() {
+        let count = crate::ops::elementwise::PARALLEL_THRESHOLD_FILL + 1001;
+        let normal = Distribution::Normal { mean: 0.0, std: 1.0 };
+        let serial = with_max_threads(Some(1), || sampled(count, normal, 42));
+        for threads in [Some(2), Some(3), None] {
+            assert!(with_max_threads(threads, || sampled(count, normal, 42)) == serial, "{:?} threads", threads);
+        }
+        assert!(serial.iter().enumerate().all(|(i, &v)| v == normal.sample(42, i as u64)));
+    }
+
+    #[test]
+    fn test_seeds() {
+        let uniform = Distribution::Uniform { low: 0.0, high: 1.0 };
+        assert_eq!(sampled(64, uniform, 7), sampled(64, uniform, 7));
+        assert_ne!(sampled(64, uniform, 7), sampled(64, uniform, 8));
+        assert_ne!(entropy_seed(), entropy_seed());
+    }
+
+    #[test]
+    fn test_moments_within_tolerance() {
+        let n = 200_000;
+        // Standard errors: uniform mean sqrt(1/12/n) ~ 6.5e-4, normal mean
+        // 3 / sqrt(n) ~ 6.7e-3; allow about 5 of them
+        let values = sampled(n, Distribution::Uniform { low: -2.0, high: 4.0 }, 1);
+        assert!(values.iter().all(|&v| (-2.0..4.0).contains(&v)));
+        let (mean, var) = moments(&values);
+        assert!((mean - 1.0).abs() < 0.02, "uniform mean {}", mean);
+        assert!((var - 3.0).abs() < 0.05, "uniform variance {}", var);
+
+        let (mean, var) = moments(&sampled(n, Distribution::Normal { mean: 5.0, std: 3.0 }, 2));
+        assert!((mean - 5.0).abs() < 0.035, "normal mean {}", mean);
+        assert!((var - 9.0).abs() < 0.15, "normal variance {}", var);
+    }
+
+    #[test]
+    fn test_validation() {
+        assert_eq!(
+            Distribution::from_name("normal", (0.0, 2.0)),
+            Ok(Distribution::Normal { mean: 0.0, std: 2.0 })
+        );
+        let err = Distribution::from_name("poisson", (0.0, 1.0)).unwrap_err();
+        assert_eq!(err.to_string(), "random: unknown distribution 'poisson' (expected uniform or normal)");
+        assert!(Distribution::from_name("uniform", (1.0, 0.0)).is_err());
+        assert!(Distribution::from_name("normal", (0.0, -1.0)).is_err());
+        assert!(Distribution::from_name("uniform", (0.0, f64::INFINITY)).is_err());
+
+        let err = tensor_random_uniform(&[4], 0.0, 1.0, Some(1), DType::Int32).unwrap_err();
+        assert_eq!(err.to_string(), "random: random tensors are float32 or float64, not int32");
+        let handle = tensor_random_normal(&[3, 4], 0.0, 1.0, None, DType::Float32).unwrap();
+        assert_eq!(crate::tensor::registry::tensor_info(handle).unwrap().shape().dims(), &[3, 4]);
+        crate::tensor::unregister_tensor(handle).unwrap();
+    }
+}
@@ -0,0 +1,841 @@
+// ============================================================================
+// Tensor Handle Registry
+// ============================================================================
+//
+// RESPONSIBILITIES:
+// - Hand Python opaque handles for (pointer, layout, dtype) triples
+// - Keep the memory's owner (e.g. the NumPy array) alive while registered
+// - Zero-copy views: slices, reshapes and transposes as new handles
+// - Snapshot the live handles and their bytes for leak hunting
+// - Check operand shapes and dtypes before the handle-based ops dispatch
+//
+// DESIGN:
+// - Slot table with a free list; a handle is (generation << 32) | slot, and
+//   a slot's generation is bumped when it is freed, so a handle used after
+//   unregister_tensor() is reported as stale even once the slot is reused
+// - Entries are Arc'd: an op holds its operands for the whole kernel call,
+//   so a concurrent unregister can't free the memory underneath it
+// - Owners are dropped outside the table lock; dropping a Python object can
+//   run arbitrary code, including another unregister
+// - A view shares its base's owner, so the memory outlives every handle
+//   onto it; `ptr` is always the view's first element
+// - Handle debug mode (COREPY_HANDLE_DEBUG=1 or set_handle_debug()) records
+//   where each handle was created and freed, and a stale-handle error then
+//   carries both backtraces. Only the last freed generation of a slot is
+//   remembered
+// - A tensor is runtime-owned when its owner is a Buffer (constructors,
+//   loaders); everything else borrows memory the caller manages. Byte
+//   accounting counts each handle's view, so views of one buffer add up
+
+use lazy_static::lazy_static;
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use super::buffer::{Buffer, BufferError};
+use super::dtype::DType;
+use super::layout::Layout;
+use super::shape::{ShapeError, TensorShape};
+use super::Device;
+
+/// Whatever keeps a registered tensor's memory alive
+pub type Owner = Box<dyn Any + Send + Sync>;
+
+/// Why a tensor could not be registered, looked up or used
+#[derive(Debug, Clone, PartialEq)]
+pub enum TensorError {
+    /// Registered with a null data pointer
+    NullPointer,
+    /// dtype code outside the DType table
+    UnknownDtype(u8),
+    /// Shape does not fit in isize elements
+    Shape(ShapeError),
+    /// Never handed out by register_tensor()
+    InvalidHandle(u64),
+    /// Handed out, then unregistered
+    StaleHandle(u64),
+    /// StaleHandle in handle debug mode, with where the handle was created
+    /// and unregistered
+    StaleHandleTraced { handle: u64, created: String, freed: String },
+    /// Operands live on different devices
+    DeviceMismatch { op: &'static str, left: Device, right: Device },
+    /// An operand has a dtype the op has no kernel for
+    DtypeMismatch { op: &'static str, expected: DType, found: DType },
+    /// Operand shapes don't fit together
+    ShapeMismatch { op: &'static str, left: Vec<usize>, right: Vec<usize> },
+    /// The op needs 2-D operands
+    NotMatrix { op: &'static str, shape: Vec<usize> },
+    /// The op needs at least `min` dimensions
+    TooFewDims { op: &'static str, ndim: usize, min: usize },
+    /// Slice arguments don't have one entry per dimension
+    SliceRank { ndim: usize, offsets: usize, sizes: usize, steps: usize },
+    /// Slice step of 0
+    ZeroStep { axis: usize },
+    /// Slice reaches past the end of `axis`
+    SliceOutOfBounds { axis: usize, offset: usize, size: usize, step: usize, dim: usize },
+    /// The op needs a C-contiguous tensor
+    NotContiguous { op: &'static str },
+    /// Reshape to a different element count
+    ReshapeSize { from: Vec<usize>, to: Vec<usize> },
+    /// The op would write into read-only memory
+    ReadOnly { op: &'static str },
+    /// Allocating a runtime-owned tensor failed
+    Alloc(BufferError),
+    /// Constructor arguments that describe no valid range (op, reason)
+    BadRange(&'static str, String),
+}
+
+impl std::fmt::Display for TensorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TensorError::NullPointer => write!(f, "cannot register a tensor with a null data pointer"),
+            TensorError::UnknownDtype(code) => write!(f, "unknown dtype code {}", code),
+            TensorError::Shape(err) => write!(f, "{}", err),
+            TensorError::InvalidHandle(handle) => write!(f, "invalid tensor handle {}", handle),
+            TensorError::StaleHandle(handle) => {
+                write!(f, "stale tensor handle {}: the tensor was unregistered", handle)
+            }
+            TensorError::StaleHandleTraced { handle, created, freed } => write!(
+                f,
+                "stale tensor handle {}: the tensor was unregistered\n\ncreated at:\n{}\nunregistered at:\n{}",
+                handle, created, freed
+            ),
+            TensorError::DeviceMismatch { op, left, right } => {
+                write!(f, "cannot {} tensor on {} with tensor on {}", op, left, right)
+            }
+            TensorError::DtypeMismatch { op, expected, found } => {
+                write!(f, "{}: expected {} operands, got {}", op, expected, found)
+            }
+            TensorError::ShapeMismatch { op, left, right } => {
+                write!(f, "{}: shapes {:?} and {:?} are incompatible", op, left, right)
+            }
+            TensorError::NotMatrix { op, shape } => write!(f, "{}: expected a 2-D tensor, got shape {:?}", op, shape),
+            TensorError::TooFewDims { op, ndim, min } => {
+                write!(f, "{}: needs at least {} dimensions, got {}", op, min, ndim)
+            }
+            TensorError::SliceRank { ndim, offsets, sizes, steps } => write!(
+                f,
+                "slice of a {}-dimensional tensor needs {} offsets, sizes and steps (got {}, {} and {})",
+                ndim, ndim, offsets, sizes, steps
+            ),
+            TensorError::ZeroStep { axis } => write!(f, "slice step of axis {} must be at least 1", axis),
+            TensorError::SliceOutOfBounds { axis, offset, size, step, dim } => write!(
+                f,
+                "slice out of bounds: {} elements from {} with step {} overrun axis {} of size {}",
+                size, offset, step, axis, dim
+            ),
+            TensorError::NotContiguous { op } => {
+                write!(f, "{}: needs a C-contiguous tensor; copy the view first", op)
+            }
+            TensorError::ReshapeSize { from, to } => write!(
+                f,
+                "cannot reshape {:?} ({} elements) into {:?} ({} elements)",
+                from,
+                from.iter().product::<usize>(),
+                to,
+                to.iter().product::<usize>()
+            ),
+            TensorError::ReadOnly { op } => write!(f, "{}: output tensor is read-only", op),
+            TensorError::Alloc(err) => write!(f, "{}", err),
+            TensorError::BadRange(op, reason) => write!(f, "{}: {}", op, reason),
+        }
+    }
+}
+
+impl From<ShapeError> for TensorError {
+    fn from(err: ShapeError) -> Self {
+        TensorError::Shape(err)
+    }
+}
+
+impl From<BufferError> for TensorError {
+    fn from(err: BufferError) -> Self {
+        TensorError::Alloc(err)
+    }
+}
+
+/// What a handle refers to
+#[derive(Debug, Clone, PartialEq)]
+pub struct TensorInfo {
+    /// Address of the first element of the view
+    pub ptr: usize,
+    pub layout: Layout,
+    pub dtype: DType,
+    /// Registered from a read-only buffer; never written by an op
+    pub readonly: bool,
+    pub device: Device,
+}
+
+impl TensorInfo {
+    pub fn shape(&self) -> &TensorShape {
+        self.layout.shape()
+    }
+
+    /// Bytes of the elements in view (not of the underlying buffer)
+    pub fn nbytes(&self) -> usize {
+        self.shape().numel() * self.dtype.size()
+    }
+}
+
+/// A registered tensor; holding it keeps the owner alive
+pub struct TensorEntry {
+    pub info: TensorInfo,
+    owner: Option<Arc<Owner>>,
+}
+
+impl TensorEntry {
+    /// The memory is a runtime Buffer freed with the last handle onto it,
+    /// rather than borrowed from the caller
+    pub fn runtime_owned(&self) -> bool {
+        self.owner.as_ref().is_some_and(|owner| (***owner).is::<Buffer>())
+    }
+}
+
+struct Slot {
+    generation: u32,
+    entry: Option<Arc<TensorEntry>>,
+    /// Where the live entry was registered (debug mode)
+    created: Option<String>,
+    /// The last generation freed from this slot (debug mode)
+    freed: Option<FreedTrace>,
+}
+
+struct FreedTrace {
+    generation: u32,
+    created: String,
+    freed: String,
+}
+
+#[derive(Default)]
+struct Registry {
+    slots: Vec<Slot>,
+    free: Vec<u32>,
+}
+
+impl Registry {
+    fn insert(&mut self, entry: Arc<TensorEntry>, created: Option<String>) -> u64 {
+        let index = match self.free.pop() {
+            Some(index) => index,
+            None => {
+                let index = u32::try_from(self.slots.len()).expect("tensor registry exhausted");
+                self.slots.push(Slot { generation: 1, entry: None, created: None, freed: None });
+                index
+            }
+        };
+        let slot = &mut self.slots[index as usize];
+        slot.entry = Some(entry);
+        slot.created = created;
+        encode(slot.generation, index)
+    }
+
+    /// Free `handle`'s slot, returning its entry for the caller to drop
+    /// outside the lock
+    fn remove(&mut self, handle: u64, freed: Option<String>) -> Result<Option<Arc<TensorEntry>>, TensorError> {
+        let index = self.live_slot(handle)?;
+        let slot = &mut self.slots[index];
+        let entry = slot.entry.take();
+        if let (Some(created), Some(freed)) = (slot.created.take(), freed) {
+            slot.freed = Some(FreedTrace { generation: slot.generation, created, freed });
+        }
+        slot.generation += 1;
+        // A slot whose generation would wrap is retired, so old handles
+        // can never become valid again
+        if slot.generation != u32::MAX {
+            self.free.push(index as u32);
+        }
+        Ok(entry)
+    }
+
+    /// Slot index of a live handle
+    fn live_slot(&self, handle: u64) -> Result<usize, TensorError> {
+        let (generation, index) = decode(handle);
+        match self.slots.get(index) {
+            Some(slot) if generation != 0 && generation == slot.generation && slot.entry.is_some() => Ok(index),
+            Some(slot) if generation != 0 && generation < slot.generation => match &slot.freed {
+                Some(trace) if trace.generation == generation => Err(TensorError::StaleHandleTraced {
+                    handle,
+                    created: trace.created.clone(),
+                    freed: trace.freed.clone(),
+                }),
+                _ => Err(TensorError::StaleHandle(handle)),
+            },
+            _ => Err(TensorError::InvalidHandle(handle)),
+        }
+    }
+
+    /// Every live handle with its entry, in slot order
+    fn snapshot(&self) -> Vec<(u64, Arc<TensorEntry>)> {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(index, slot)| {
+                let entry = slot.entry.as_ref()?;
+                Some((encode(slot.generation, index as u32), entry.clone()))
+            })
+            .collect()
+    }
+
+    fn total_bytes(&self) -> usize {
+        self.slots.iter().filter_map(|slot| slot.entry.as_ref()).map(|entry| entry.info.nbytes()).sum()
+    }
+}
+
+lazy_static! {
+    static ref TENSORS: Mutex<Registry> = Mutex::new(Registry::default());
+    static ref HANDLE_DEBUG: AtomicBool =
+        AtomicBool::new(std::env::var("COREPY_HANDLE_DEBUG").is_ok_and(|value| value == "1"));
+}
+
+/// Record creation and free backtraces for new handles (slow; for tracking
+/// down use-after-unregister bugs)
+pub fn set_handle_debug(enabled: bool) {
+    HANDLE_DEBUG.store(enabled, Ordering::Relaxed);
+}
+
+pub fn handle_debug() -> bool {
+    HANDLE_DEBUG.load(Ordering::Relaxed)
+}
+
+/// Backtrace of the caller, when handle debug mode is on
+fn debug_trace() -> Option<String> {
+    handle_debug().then(|| Backtrace::force_capture().to_string())
+}
+
+fn encode(generation: u32, slot: u32) -> u64 {
+    ((generation as u64) << 32) | slot as u64
+}
+
+fn decode(handle: u64) -> (u32, usize) {
+    ((handle >> 32) as u32, handle as u32 as usize)
+}
+
+/// Register `shape` elements of `dtype_code` at `ptr`, keeping `owner`
+/// alive until the handle is unregistered
+pub fn register_tensor(ptr: usize, shape: &[usize], dtype_code: u8, owner: Option<Owner>) -> Result<u64, TensorError> {
+    let dtype = DType::from_code(dtype_code).ok_or(TensorError::UnknownDtype(dtype_code))?;
+    let layout = Layout::c_contiguous(TensorShape::new(shape)?);
+    register_strided(ptr, layout, dtype, false, owner)
+}
+
+/// Register the elements `layout` reaches from `ptr` (the first element,
+/// whatever the stride signs); `readonly` tensors are rejected as outputs
+pub fn register_strided(
+    ptr: usize,
+    layout: Layout,
+    dtype: DType,
+    readonly: bool,
+    owner: Option<Owner>,
+) -> Result<u64, TensorError> {
+    if ptr == 0 {
+        return Err(TensorError::NullPointer);
+    }
+    let info = TensorInfo { ptr, layout, dtype, readonly, device: Device::Cpu };
+    insert(TensorEntry { info, owner: owner.map(Arc::new) })
+}
+
+/// Register a runtime-owned buffer as a C-contiguous tensor of `shape`;
+/// the handle owns the buffer
+pub fn register_buffer(buffer: Buffer, shape: TensorShape) -> Result<u64, TensorError> {
+    debug_assert_eq!(buffer.len(), shape.numel());
+    let info = TensorInfo {
+        ptr: buffer.as_ptr() as usize,
+        layout: Layout::c_contiguous(shape),
+        dtype: buffer.dtype(),
+        readonly: false,
+        device: buffer.device(),
+    };
+    insert(TensorEntry { info, owner: Some(Arc::new(Box::new(buffer))) })
+}
+
+/// Register `dims` elements on `device` with no owner; stands in for
+/// device tensors until a device allocator exists
+#[cfg(test)]
+pub(crate) fn register_on_device(ptr: usize, dims: &[usize], dtype: DType, device: Device) -> Result<u64, TensorError> {
+    let layout = Layout::c_contiguous(TensorShape::new(dims)?);
+    insert(TensorEntry { info: TensorInfo { ptr, layout, dtype, readonly: false, device }, owner: None })
+}
+
+fn insert(entry: TensorEntry) -> Result<u64, TensorError> {
+    let entry = Arc::new(entry);
+    let created = debug_trace();
+    Ok(TENSORS.lock().unwrap_or_else(|e| e.into_inner()).insert(entry, created))
+}
+
+/// Drop a handle (and its owner, unless an op is still using it)
+pub fn unregister_tensor(handle: u64) -> Result<(), TensorError> {
+    let freed = debug_trace();
+    let entry = TENSORS.lock().unwrap_or_else(|e| e.into_inner()).remove(handle, freed)?;
+    drop(entry);
+    Ok(())
+}
+
+/// The live tensor behind `handle`
+pub fn lookup(handle: u64) -> Result<Arc<TensorEntry>, TensorError> {
+    let registry = TENSORS.lock().unwrap_or_else(|e| e.into_inner());
+    let index = registry.live_slot(handle)?;
+    Ok(registry.slots[index].entry.clone().expect("live slot has an entry"))
+}
+
+/// Pointer, layout and dtype behind `handle`
+#[allow(dead_code)]
+pub fn tensor_info(handle: u64) -> Result<TensorInfo, TensorError> {
+    lookup(handle).map(|entry| entry.info.clone())
+}
+
+/// Every live handle and its entry, taken in one pass under the registry
+/// lock so concurrent registrations can't tear the listing
+pub fn list_tensors() -> Vec<(u64, Arc<TensorEntry>)> {
+    TENSORS.lock().unwrap_or_else(|e| e.into_inner()).snapshot()
+}
+
+/// Sum of nbytes() over every live handle
+pub fn total_registered_bytes() -> usize {
+    TENSORS.lock().unwrap_or_else(|e| e.into_inner()).total_bytes()
+}
+
+/// Register a view of `base` starting `offset` elements past its first
+/// element
+fn insert_view(base: &TensorEntry, offset: isize, layout: Layout) -> Result<u64, TensorError> {
+    let ptr = (base.info.ptr as isize + offset * base.info.dtype.size() as isize) as usize;
+    let info = TensorInfo { ptr, layout, ..base.info.clone() };
+    insert(TensorEntry { info, owner: base.owner.clone() })
+}
+
+/// View of `sizes[i]` elements along each axis, starting at `offsets[i]`
+/// and taking every `steps[i]`-th element; shares `handle`'s memory
+pub fn tensor_view(handle: u64, offsets: &[usize], sizes: &[usize], steps: &[usize]) -> Result<u64, TensorError> {
+    let base = lookup(handle)?;
+    let dims = base.info.shape().dims();
+    let strides = base.info.layout.strides();
+    if offsets.len() != dims.len() || sizes.len() != dims.len() || steps.len() != dims.len() {
+        return Err(TensorError::SliceRank {
+            ndim: dims.len(),
+            offsets: offsets.len(),
+            sizes: sizes.len(),
+            steps: steps.len(),
+        });
+    }
+
+    let mut first = 0isize;
+    let mut view_strides = Vec::with_capacity(dims.len());
+    for axis in 0..dims.len() {
+        let (offset, size, step, dim) = (offsets[axis], sizes[axis], steps[axis], dims[axis]);
+        if step == 0 {
+            return Err(TensorError::ZeroStep { axis });
+        }
+        let last = match size {
+            0 => Some(offset).filter(|&offset| offset <= dim),
+            _ => (size - 1).checked_mul(step).and_then(|span| span.checked_add(offset)).filter(|&last| last < dim),
+        };
+        if last.is_none() {
+            return Err(TensorError::SliceOutOfBounds { axis, offset, size, step, dim });
+        }
+        first += offset as isize * strides[axis];
+        view_strides.push(strides[axis] * step as isize);
+    }
+    let shape = TensorShape::new(sizes)?;
+    // An empty view is never dereferenced; keep its pointer in bounds
+    if shape.numel() == 0 {
+        first = 0;
+    }
+    let layout = Layout::new(shape, &view_strides).expect("one stride per dimension");
+    insert_view(&base, first, layout)
+}
+
+/// The same elements with shape `dims`; only C-contiguous tensors can be
+/// reshaped without a copy
+pub fn tensor_reshape(handle: u64, dims: &[usize]) -> Result<u64, TensorError> {
+    let base = lookup(handle)?;
+    if !base.info.layout.is_c_contiguous() {
+        return Err(TensorError::NotContiguous { op: "reshape" });
+    }
+    let shape = TensorShape::new(dims)?;
+    if shape.numel() != base.info.shape().numel() {
+        return Err(TensorError::ReshapeSize { from: base.info.shape().dims().to_vec(), to: dims.to_vec() });
+    }
+    insert_view(&base, 0, Layout::c_contiguous(shape))
+}
+
+/// View with the last two dimensions (and their strides) swapped
+pub fn tensor_transpose_view(handle: u64) -> Result<u64, TensorError> {
+    let base = lookup(handle)?;
+    let ndim = base.info.shape().ndim();
+    if ndim < 2 {
+        return Err(TensorError::TooFewDims { op: "transpose", ndim, min: 2 });
+    }
+    let mut dims = base.info.shape().dims().to_vec();
+    let mut strides = base.info.layout.strides().to_vec();
+    dims.swap(ndim - 2, ndim - 1);
+    strides.swap(ndim - 2, ndim - 1);
+    let layout = Layout::new(TensorShape::new(&dims)?, &strides).expect("one stride per dimension");
+    insert_view(&base, 0, layout)
+}
+
+fn check_dtype(op: &'static str, info: &TensorInfo, expected: DType) -> Result<(), TensorError> {
+    if info.dtype != expected {
+        return Err(TensorError::DtypeMismatch { op, expected, found: info.dtype });
+    }
+    Ok(())
+}
+
+fn check_same_shape(op: &'static str, left: &TensorInfo, right: &TensorInfo) -> Result<(), TensorError> {
+    if left.shape() != right.shape() {
+        return Err(TensorError::ShapeMismatch {
+            op,
+            left: left.shape().dims().to_vec(),
+            right: right.shape().dims().to_vec(),
+        });
+    }
+    Ok(())
+}
+
+fn matrix_dims(op: &'static str, info: &TensorInfo) -> Result<(usize, usize), TensorError> {
+    match *info.shape().dims() {
+        [rows, cols] => Ok((rows, cols)),
+        _ => Err(TensorError::NotMatrix { op, shape: info.shape().dims().to_vec() }),
+    }
+}
+
+/// Element count of a float32 `out = a <op> b` over equal shapes
+pub fn elementwise_operands(
+    op: &'static str,
+    a: &TensorInfo,
+    b: &TensorInfo,
+    out: &TensorInfo,
+) -> Result<usize, TensorError> {
+    check_same_device(op, a, b)?;
+    check_same_device(op, a, out)?;
+    for operand in [a, b, out] {
+        check_dtype(op, operand, DType::Float32)?;
+    }
+    check_same_shape(op, a, b)?;
+    check_same_shape(op, a, out)?;
+    check_writable(op, out)?;
+    Ok(a.shape().numel())
+}
+
+fn check_same_device(op: &'static str, a: &TensorInfo, b: &TensorInfo) -> Result<(), TensorError> {
+    if a.device != b.device {
+        return Err(TensorError::DeviceMismatch { op, left: a.device, right: b.device });
+    }
+    Ok(())
+}
+
+fn check_writable(op: &'static str, out: &TensorInfo) -> Result<(), TensorError> {
+    if out.readonly {
+        return Err(TensorError::ReadOnly { op });
+    }
+    Ok(())
+}
+
+/// (m, k, n) of a float32 `out = a @ b`
+pub fn matmul_operands(
+    op: &'static str,
+    a: &TensorInfo,
+    b: &TensorInfo,
+    out: &TensorInfo,
+) -> Result<(usize, usize, usize), TensorError> {
+    check_same_device(op, a, b)?;
+    check_same_device(op, a, out)?;
+    for operand in [a, b, out] {
+        check_dtype(op, operand, DType::Float32)?;
+    }
+    let (m, k) = matrix_dims(op, a)?;
+    let (k2, n) = matrix_dims(op, b)?;
+    if k != k2 {
+        return Err(TensorError::ShapeMismatch { op, left: vec![m, k], right: vec![k2, n] });
+    }
+    if matrix_dims(op, out)? != (m, n) {
+        return Err(TensorError::ShapeMismatch { op, left: vec![m, n], right: out.shape().dims().to_vec() });
+    }
+    check_writable(op, out)?;
+    Ok((m, k, n))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const F32: u8 = DType::Float32 as u8;
+
+    #[test]
+    fn test_register_and_info() {
+        let data = [0f32; 6];
+        let handle = register_tensor(data.as_ptr() as usize, &[2, 3], F32, None).unwrap();
+        let info = tensor_info(handle).unwrap();
+        assert_eq!(info.ptr, data.as_ptr() as usize);
+        assert_eq!((info.shape().dims(), info.dtype, info.nbytes()), (&[2usize, 3][..], DType::Float32, 24));
+        unregister_tensor(handle).unwrap();
+
+        assert_eq!(register_tensor(0, &[2], F32, None).unwrap_err(), TensorError::NullPointer);
+        assert_eq!(register_tensor(8, &[2], 200, None).unwrap_err(), TensorError::UnknownDtype(200));
+        let big = 1usize << 32;
+        assert!(matches!(register_tensor(8, &[big, big], F32, None), Err(TensorError::Shape(_))));
+    }
+
+    #[test]
+    fn test_stale_handle_detected_after_slot_reuse() {
+        // Handle debug mode would turn StaleHandle into StaleHandleTraced
+        let _guard = crate::scheduler::TEST_STATE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let first = register_tensor(64, &[4], F32, None).unwrap();
+        unregister_tensor(first).unwrap();
+        assert_eq!(tensor_info(first).unwrap_err(), TensorError::StaleHandle(first));
+        assert_eq!(unregister_tensor(first).unwrap_err(), TensorError::StaleHandle(first));
+
+        // Other tests may take the freed slot first; keep registering until
+        // one lands on it
+        let (_, slot) = decode(first);
+        let mut held = Vec::new();
+        let reused = loop {
+            let handle = register_tensor(128, &[4], F32, None).unwrap();
+            if decode(handle).1 == slot {
+                break handle;
+            }
+            held.push(handle);
+        };
+        assert_ne!(reused, first);
+        assert_eq!(tensor_info(reused).unwrap().ptr, 128);
+        assert_eq!(tensor_info(first).unwrap_err(), TensorError::StaleHandle(first));
+
+        for handle in held.into_iter().chain([reused]) {
+            unregister_tensor(handle).unwrap();
+        }
+        assert!(matches!(tensor_info(0), Err(TensorError::InvalidHandle(0))));
+        let never = encode(1, u32::MAX);
+        assert_eq!(tensor_info(never).unwrap_err(), TensorError::InvalidHandle(never));
+    }
+
+    #[test]
+    fn test_debug_mode_reports_creation_and_free_sites() {
+        let _guard = crate::scheduler::TEST_STATE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        set_handle_debug(true);
+        let first = register_tensor(64, &[4], F32, None).unwrap();
+        unregister_tensor(first).unwrap();
+        let (_, slot) = decode(first);
+        let mut held = Vec::new();
+        let reused = loop {
+            let handle = register_tensor(128, &[2], F32, None).unwrap();
+            if decode(handle).1 == slot {
+                break handle;
+            }
+            held.push(handle);
+        };
+        set_handle_debug(false);
+
+        assert_eq!(tensor_info(reused).unwrap().ptr, 128);
+        let err = tensor_info(first).unwrap_err();
+        assert!(matches!(&err, TensorError::StaleHandleTraced { handle, .. } if *handle == first));
+        let message = err.to_string();
+        assert!(message.starts_with(&format!("stale tensor handle {}", first)), "{}", message);
+        assert!(message.contains("created at:") && message.contains("unregistered at:"), "{}", message);
+        assert!(message.contains("test_debug_mode_reports_creation_and_free_sites"), "{}", message);
+
+        // Handles freed with debug mode off carry no trace
+        for handle in held.into_iter().chain([reused]) {
+            unregister_tensor(handle).unwrap();
+        }
+        assert_eq!(tensor_info(reused).unwrap_err(), TensorError::StaleHandle(reused));
+    }
+
+    #[test]
+    fn test_owner_lives_until_last_user() {
+        let owner = Arc::new(());
+        let handle = register_tensor(64, &[1], F32, Some(Box::new(owner.clone()))).unwrap();
+        let in_flight = lookup(handle).unwrap();
+        unregister_tensor(handle).unwrap();
+        assert_eq!(Arc::strong_count(&owner), 2);
+        drop(in_flight);
+        assert_eq!(Arc::strong_count(&owner), 1);
+    }
+
+    #[test]
+    fn test_view_slices_and_transposes() {
+        let data: Vec<f32> = (0..24).map(|i| i as f32).collect();
+        let owner = Arc::new(());
+        let base = register_tensor(data.as_ptr() as usize, &[4, 6], F32, Some(Box::new(owner.clone()))).unwrap();
+
+        // data[1::2, 1::2]: rows 1 and 3, columns 1, 3 and 5
+        let view = tensor_view(base, &[1, 1], &[2, 3], &[2, 2]).unwrap();
+        unregister_tensor(base).unwrap();
+        assert_eq!(Arc::strong_count(&owner), 2, "the view keeps the owner alive");
+        let info = tensor_info(view).unwrap();
+        assert_eq!(info.ptr, data.as_ptr() as usize + 7 * 4);
+        assert_eq!((info.shape().dims(), info.layout.strides()), (&[2usize, 3][..], &[12isize, 2][..]));
+        let gathered = unsafe { crate::tensor::buffer::Buffer::gather(info.ptr as *const u8, &info.layout, info.dtype) };
+        let gathered = gathered.unwrap();
+        let values = unsafe { std::slice::from_raw_parts(gathered.as_ptr().cast::<f32>(), 6) };
+        assert_eq!(values, &[7.0, 9.0, 11.0, 19.0, 21.0, 23.0]);
+
+        let transposed = tensor_transpose_view(view).unwrap();
+        let info = tensor_info(transposed).unwrap();
+        assert_eq!((info.shape().dims(), info.layout.strides()), (&[3usize, 2][..], &[2isize, 12][..]));
+        assert!(!info.layout.is_c_contiguous());
+        assert_eq!(tensor_reshape(transposed, &[6]).unwrap_err(), TensorError::NotContiguous { op: "reshape" });
+
+        unregister_tensor(view).unwrap();
+        unregister_tensor(transposed).unwrap();
+        assert_eq!(Arc::strong_count(&owner), 1);
+    }
+
+    #[test]
+    fn test_view_and_reshape_errors() {
+        let data = [0f32; 12];
+        let base = register_tensor(data.as_ptr() as usize, &[3, 4], F32, None).unwrap();
+
+        assert!(matches!(tensor_view(base, &[0], &[1], &[1]), Err(TensorError::SliceRank { ndim: 2, .. })));
+        assert_eq!(tensor_view(base, &[0, 0], &[1, 1], &[1, 0]).unwrap_err(), TensorError::ZeroStep { axis: 1 });
+        // Columns 1, 3, 5 of a 4-column row
+        let err = tensor_view(base, &[0, 1], &[3, 3], &[1, 2]).unwrap_err();
+        assert_eq!(err, TensorError::SliceOutOfBounds { axis: 1, offset: 1, size: 3, step: 2, dim: 4 });
+        assert_eq!(err.to_string(), "slice out of bounds: 3 elements from 1 with step 2 overrun axis 1 of size 4");
+        let err = tensor_view(base, &[0, 0], &[1, 2], &[1, usize::MAX]).unwrap_err();
+        assert!(matches!(err, TensorError::SliceOutOfBounds { axis: 1, .. }));
+
+        // An empty slice may start at the end, and points at the base
+        let empty = tensor_view(base, &[3, 0], &[0, 4], &[1, 1]).unwrap();
+        assert_eq!(tensor_info(empty).unwrap().ptr, data.as_ptr() as usize);
+        assert!(tensor_view(base, &[4, 0], &[0, 4], &[1, 1]).is_err());
+
+        let flat = tensor_reshape(base, &[2, 6]).unwrap();
+        assert_eq!(tensor_info(flat).unwrap().layout.strides(), &[6, 1]);
+        let err = tensor_reshape(base, &[5, 2]).unwrap_err();
+        assert_eq!(err.to_string(), "cannot reshape [3, 4] (12 elements) into [5, 2] (10 elements)");
+
+        let vector = tensor_reshape(base, &[12]).unwrap();
+        assert_eq!(
+            tensor_transpose_view(vector).unwrap_err(),
+            TensorError::TooFewDims { op: "transpose", ndim: 1, min: 2 }
+        );
+
+        for handle in [base, empty, flat, vector] {
+            unregister_tensor(handle).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_listing_and_byte_totals_track_registrations() {
+        // A private table, so concurrently running tests don't move the totals
+        let mut registry = Registry::default();
+        let entry = |dims: &[usize], dtype| Arc::new(TensorEntry { info: info(dims, dtype), owner: None });
+        let a = registry.insert(entry(&[2, 3], DType::Float32), None);
+        let b = registry.insert(entry(&[10], DType::Float64), None);
+        let c = registry.insert(entry(&[4], DType::UInt8), None);
+        assert_eq!(registry.total_bytes(), 24 + 80 + 4);
+
+        registry.remove(b, None).unwrap();
+        let listed: Vec<_> = registry.snapshot().iter().map(|(h, e)| (*h, e.info.nbytes())).collect();
+        assert_eq!(listed, vec![(a, 24), (c, 4)]);
+        assert_eq!(registry.total_bytes(), 28);
+
+        // The freed slot comes back under a new handle
+        let d = registry.insert(entry(&[0, 5], DType::Float32), None);
+        assert_eq!(decode(d).1, decode(b).1);
+        let handles: Vec<_> = registry.snapshot().iter().map(|(h, _)| *h).collect();
+        assert_eq!(handles, vec![a, d, c]);
+        for handle in [a, c, d] {
+            registry.remove(handle, None).unwrap();
+        }
+        assert!(registry.snapshot().is_empty());
+        assert_eq!(registry.total_bytes(), 0);
+    }
+
+    #[test]
+    fn test_list_tensors_reports_ownership() {
+        let data = [0f32; 8];
+        let borrowed = register_tensor(data.as_ptr() as usize, &[8], F32, None).unwrap();
+        let buffer = Buffer::zeroed(DType::Float64, 6).unwrap();
+        let owned = register_buffer(buffer, TensorShape::new(&[2, 3]).unwrap()).unwrap();
+        let view = tensor_transpose_view(owned).unwrap();
+
+        let listed: Vec<_> = list_tensors()
+            .into_iter()
+            .filter(|(handle, _)| [borrowed, owned, view].contains(handle))
+            .map(|(handle, entry)| (handle, entry.info.nbytes(), entry.runtime_owned()))
+            .collect();
+        assert_eq!(listed.len(), 3);
+        assert!(listed.contains(&(borrowed, 32, false)));
+        assert!(listed.contains(&(owned, 48, true)));
+        assert!(listed.contains(&(view, 48, true)), "views inherit the base's ownership");
+
+        for handle in [borrowed, owned, view] {
+            unregister_tensor(handle).unwrap();
+        }
+        assert!(list_tensors().iter().all(|(handle, _)| ![borrowed, owned, view].contains(handle)));
+    }
+
+    #[test]
+    fn test_ops_reject_operands_on_different_devices() {
+        let data = [0f32; 6];
+        let cpu = register_tensor(data.as_ptr() as usize, &[2, 3], F32, None).unwrap();
+        let gpu = register_on_device(64, &[2, 3], DType::Float32, Device::Cuda(0)).unwrap();
+        let (cpu_info, gpu_info) = (tensor_info(cpu).unwrap(), tensor_info(gpu).unwrap());
+        assert_eq!((cpu_info.device, gpu_info.device), (Device::Cpu, Device::Cuda(0)));
+
+        let err = elementwise_operands("add", &gpu_info, &cpu_info, &cpu_info).unwrap_err();
+        assert_eq!(err, TensorError::DeviceMismatch { op: "add", left: Device::Cuda(0), right: Device::Cpu });
+        assert_eq!(err.to_string(), "cannot add tensor on cuda:0 with tensor on cpu");
+        let err = elementwise_operands("mul", &cpu_info, &cpu_info, &gpu_info).unwrap_err();
+        assert_eq!(err.to_string(), "cannot mul tensor on cpu with tensor on cuda:0");
+        assert_eq!(elementwise_operands("add", &gpu_info, &gpu_info, &gpu_info), Ok(6));
+
+        let square = register_on_device(64, &[3, 3], DType::Float32, Device::Metal).unwrap();
+        let square_info = tensor_info(square).unwrap();
+        let err = matmul_operands("matmul", &cpu_info, &square_info, &cpu_info).unwrap_err();
+        assert_eq!(err.to_string(), "cannot matmul tensor on cpu with tensor on metal");
+
+        // Views stay on their base's device
+        let view = tensor_transpose_view(gpu).unwrap();
+        assert_eq!(tensor_info(view).unwrap().device, Device::Cuda(0));
+        let buffer = Buffer::zeroed(DType::Float32, 2).unwrap();
+        let owned = register_buffer(buffer, TensorShape::new(&[2]).unwrap()).unwrap();
+        assert_eq!(tensor_info(owned).unwrap().device, Device::Cpu);
+
+        for handle in [cpu, gpu, square, view, owned] {
+            unregister_tensor(handle).unwrap();
+        }
+    }
+
+    fn info(dims: &[usize], dtype: DType) -> TensorInfo {
+        let layout = Layout::c_contiguous(TensorShape::new(dims).unwrap());
+        TensorInfo { ptr: 64, layout, dtype, readonly: false, device: Device::Cpu }
+    }
+
+    #[test]
+    fn test_operand_validation() {
+        let f32 = DType::Float32;
+        let x = info(&[2, 3], f32);
+        assert_eq!(elementwise_operands("add", &x, &x, &x), Ok(6));
+        assert_eq!(
+            elementwise_operands("add", &x, &info(&[3, 2], f32), &x),
+            Err(TensorError::ShapeMismatch { op: "add", left: vec![2, 3], right: vec![3, 2] })
+        );
+        assert!(matches!(
+            elementwise_operands("add", &x, &x, &info(&[6], f32)),
+            Err(TensorError::ShapeMismatch { .. })
+        ));
+        let err = elementwise_operands("add", &x, &info(&[2, 3], DType::Float64), &x).unwrap_err();
+        assert_eq!(err.to_string(), "add: expected float32 operands, got float64");
+
+        let a = info(&[2, 3], f32);
+        let b = info(&[3, 4], f32);
+        assert_eq!(matmul_operands("matmul", &a, &b, &info(&[2, 4], f32)), Ok((2, 3, 4)));
+        let err = matmul_operands("matmul", &a, &a, &info(&[2, 3], f32)).unwrap_err();
+        assert_eq!(err.to_string(), "matmul: shapes [2, 3] and [2, 3] are incompatible");
+        assert!(matches!(
+            matmul_operands("matmul", &a, &b, &info(&[4, 2], f32)),
+            Err(TensorError::ShapeMismatch { .. })
+        ));
+        assert!(matches!(
+            matmul_operands("matmul", &info(&[6], f32), &b, &b),
+            Err(TensorError::NotMatrix { .. })
+        ));
+
+        let frozen = TensorInfo { readonly: true, ..x.clone() };
+        assert_eq!(elementwise_operands("add", &frozen, &frozen, &x), Ok(6));
+        let err = elementwise_operands("add", &x, &x, &frozen).unwrap_err();
+        assert_eq!(err.to_string(), "add: output tensor is read-only");
+        let out = TensorInfo { readonly: true, ..info(&[2, 4], f32) };
+        assert_eq!(matmul_operands("matmul", &a, &b, &out), Err(TensorError::ReadOnly { op: "matmul" }));
+    }
+}
@@ -0,0 +1,257 @@
+// ============================================================================
+// Tensor Shapes and Broadcasting
+// ============================================================================
+//
+// RESPONSIBILITIES:
+// - Validate tensor dimensions once, at the FFI boundary
+// - NumPy broadcasting between two shapes
+// - Strides for contiguous and broadcast (zero-stride) views
+//
+// DESIGN:
+// - Dims live inline for up to 4 dimensions (SmallVec), the common case
+// - A shape is only built if its element count fits in isize, so numel()
+//   and byte offsets derived from it never overflow pointer arithmetic
+// - Strides are in elements, not bytes
+
+use smallvec::SmallVec;
+
+/// Dimension sizes or strides, inline up to 4D
+pub type Dims = SmallVec<[usize; 4]>;
+
+/// Why a shape could not be built or broadcast
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShapeError {
+    /// The element count of `dims` does not fit in isize
+    Overflow(Vec<usize>),
+    /// Dimensions differ and neither is 1 (`axis` counts from the left of
+    /// the broadcast result)
+    Incompatible {
+        axis: usize,
+        left: usize,
+        right: usize,
+        left_shape: Vec<usize>,
+        right_shape: Vec<usize>,
+    },
+    /// An output's shape differs from the shape being computed
+    Mismatch { expected: Vec<usize>, found: Vec<usize> },
+}
+
+impl std::fmt::Display for ShapeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShapeError::Overflow(dims) => write!(f, "shape {:?} overflows: more than isize::MAX elements", dims),
+            ShapeError::Incompatible { axis, left, right, left_shape, right_shape } => write!(
+                f,
+                "shapes {:?} and {:?} cannot be broadcast: dimension {} is {} vs {}",
+                left_shape, right_shape, axis, left, right
+            ),
+            ShapeError::Mismatch { expected, found } => write!(f, "expected shape {:?}, got {:?}", expected, found),
+        }
+    }
+}
+
+impl std::error::Error for ShapeError {}
+
+/// Validated dimensions of a row-major tensor
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TensorShape {
+    dims: Dims,
+}
+
+impl TensorShape {
+    /// Shape with `dims`, rejecting element counts beyond isize::MAX
+    pub fn new(dims: &[usize]) -> Result<Self, ShapeError> {
+        let numel = if dims.contains(&0) {
+            Some(0)
+        } else {
+            dims.iter().try_fold(1usize, |acc, &dim| acc.checked_mul(dim))
+        };
+        match numel {
+            Some(numel) if numel <= isize::MAX as usize => Ok(TensorShape { dims: Dims::from_slice(dims) }),
+            _ => Err(ShapeError::Overflow(dims.to_vec())),
+        }
+    }
+
+    /// Rows x cols matrix
+    pub fn matrix(rows: usize, cols: usize) -> Result<Self, ShapeError> {
+        Self::new(&[rows, cols])
+    }
+
+    /// Zero-dimensional shape (one element)
+    #[allow(dead_code)]
+    pub fn scalar() -> Self {
+        TensorShape { dims: Dims::new() }
+    }
+
+    pub fn dims(&self) -> &[usize] {
+        &self.dims
+    }
+
+    pub fn ndim(&self) -> usize {
+        self.dims.len()
+    }
+
+    /// Number of elements (1 for a scalar)
+    pub fn numel(&self) -> usize {
+        self.dims.iter().product()
+    }
+
+    #[allow(dead_code)]
+    pub fn is_scalar(&self) -> bool {
+        self.dims.is_empty()
+    }
+
+    /// Row-major strides of a contiguous tensor of this shape
+    pub fn contiguous_strides(&self) -> Dims {
+        let mut strides: Dims = smallvec::smallvec![0; self.dims.len()];
+        let mut stride = 1;
+        for (slot, &dim) in strides.iter_mut().zip(&self.dims).rev() {
+            *slot = stride;
+            stride *= dim;
+        }
+        strides
+    }
+
+    /// Shape of an elementwise result of `self` and `other` (NumPy rules):
+    /// trailing dimensions line up, and 1 stretches to the other size
+    pub fn broadcast_with(&self, other: &TensorShape) -> Result<TensorShape, ShapeError> {
+        let ndim = self.ndim().max(other.ndim());
+        let mut dims: Dims = smallvec::smallvec![1; ndim];
+        for (axis, slot) in dims.iter_mut().enumerate() {
+            let left = self.dim_aligned(axis, ndim);
+            let right = other.dim_aligned(axis, ndim);
+            *slot = match (left, right) {
+                (l, r) if l == r => l,
+                (1, r) => r,
+                (l, 1) => l,
+                (left, right) => {
+                    return Err(ShapeError::Incompatible {
+                        axis,
+                        left,
+                        right,
+                        left_shape: self.dims.to_vec(),
+                        right_shape: other.dims.to_vec(),
+                    })
+                }
+            };
+        }
+        // Each output dim is one of the inputs', so the count can still
+        // exceed isize::MAX (e.g. [N, 1] with [1, N])
+        TensorShape::new(&dims)
+    }
+
+    /// Strides that read a contiguous tensor of this shape as `target`:
+    /// stretched and prepended dimensions get stride 0
+    ///
+    /// Fails if this shape does not broadcast to `target`.
+    pub fn broadcast_strides(&self, target: &TensorShape) -> Result<Dims, ShapeError> {
+        if self.broadcast_with(target)? != *target {
+            // Broadcasting would grow `target` itself
+            let ndim = self.ndim().max(target.ndim());
+            let axis = (0..ndim)
+                .find(|&axis| self.dim_aligned(axis, ndim) != target.dim_aligned(axis, ndim))
+                .unwrap_or(0);
+            return Err(ShapeError::Incompatible {
+                axis,
+                left: self.dim_aligned(axis, ndim),
+                right: target.dim_aligned(axis, ndim),
+                left_shape: self.dims.to_vec(),
+                right_shape: target.dims.to_vec(),
+            });
+        }
+
+        let own = self.contiguous_strides();
+        let offset = target.ndim() - self.ndim();
+        Ok((0..target.ndim())
+            .map(|axis| match axis.checked_sub(offset) {
+                Some(i) if self.dims[i] == target.dims[axis] => own[i],
+                _ => 0,
+            })
+            .collect())
+    }
+
+    /// Size of `axis` once this shape is right-aligned to `ndim` dimensions
+    fn dim_aligned(&self, axis: usize, ndim: usize) -> usize {
+        match axis.checked_sub(ndim - self.ndim()) {
+            Some(i) => self.dims[i],
+            None => 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shape(dims: &[usize]) -> TensorShape {
+        TensorShape::new(dims).unwrap()
+    }
+
+    #[test]
+    fn test_numel_and_scalar() {
+        assert_eq!(shape(&[2, 3, 4]).numel(), 24);
+        assert_eq!(shape(&[5, 0]).numel(), 0);
+        assert!(TensorShape::scalar().is_scalar());
+        assert_eq!(TensorShape::scalar().numel(), 1);
+        assert_eq!(shape(&[]), TensorShape::scalar());
+        assert_eq!(shape(&[2, 3, 4]).contiguous_strides().as_slice(), &[12, 4, 1]);
+    }
+
+    #[test]
+    fn test_overflow_rejected() {
+        let big = 1usize << 32;
+        assert_eq!(TensorShape::new(&[big, big]), Err(ShapeError::Overflow(vec![big, big])));
+        // Fits usize but not isize
+        assert!(TensorShape::new(&[isize::MAX as usize + 1]).is_err());
+        assert!(TensorShape::new(&[isize::MAX as usize]).is_ok());
+        // Zero anywhere keeps the count small
+        assert!(TensorShape::new(&[big, big, 0]).is_ok());
+        assert!(TensorShape::matrix(big, big).unwrap_err().to_string().contains("overflows"));
+    }
+
+    #[test]
+    fn test_broadcast_scalar_with_nd() {
+        let nd = shape(&[2, 3, 4]);
+        assert_eq!(TensorShape::scalar().broadcast_with(&nd).unwrap(), nd);
+        assert_eq!(nd.broadcast_with(&TensorShape::scalar()).unwrap(), nd);
+        assert_eq!(TensorShape::scalar().broadcast_strides(&nd).unwrap().as_slice(), &[0, 0, 0]);
+    }
+
+    #[test]
+    fn test_broadcast_mismatched_ranks() {
+        let result = shape(&[8, 1, 6, 1]).broadcast_with(&shape(&[7, 1, 5])).unwrap();
+        assert_eq!(result.dims(), &[8, 7, 6, 5]);
+
+        let row = shape(&[3]);
+        let matrix = shape(&[4, 3]);
+        assert_eq!(row.broadcast_with(&matrix).unwrap(), matrix);
+        assert_eq!(row.broadcast_strides(&matrix).unwrap().as_slice(), &[0, 1]);
+
+        let column = shape(&[4, 1]);
+        assert_eq!(column.broadcast_strides(&matrix).unwrap().as_slice(), &[1, 0]);
+        assert_eq!(matrix.broadcast_strides(&matrix).unwrap().as_slice(), &[3, 1]);
+    }
+
+    #[test]
+    fn test_broadcast_incompatible_names_dims() {
+        let err = shape(&[2, 3]).broadcast_with(&shape(&[4, 2, 4])).unwrap_err();
+        assert_eq!(
+            err,
+            ShapeError::Incompatible { axis: 2, left: 3, right: 4, left_shape: vec![2, 3], right_shape: vec![4, 2, 4] }
+        );
+        assert_eq!(err.to_string(), "shapes [2, 3] and [4, 2, 4] cannot be broadcast: dimension 2 is 3 vs 4");
+
+        // A view can't grow its target
+        let err = shape(&[4, 3]).broadcast_strides(&shape(&[1, 3])).unwrap_err();
+        assert!(matches!(err, ShapeError::Incompatible { axis: 0, left: 4, right: 1, .. }));
+        let err = shape(&[2, 3]).broadcast_strides(&shape(&[3])).unwrap_err();
+        assert!(matches!(err, ShapeError::Incompatible { axis: 0, left: 2, right: 1, .. }));
+    }
+
+    #[test]
+    fn test_broadcast_result_overflow() {
+        let big = 1usize << 32;
+        let err = shape(&[big, 1]).broadcast_with(&shape(&[1, big])).unwrap_err();
+        assert_eq!(err, ShapeError::Overflow(vec![big, big]));
+    }
+}
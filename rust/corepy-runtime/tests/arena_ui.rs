@@ -0,0 +1,10 @@
+// Compile-fail checks for the scratch arena's borrow rules
+//
+// Each tests/ui/*.rs must be rejected with the error in its .stderr;
+// regenerate those with TRYBUILD=overwrite after a compiler upgrade.
+
+#[test]
+fn arena_scope_borrows_do_not_escape() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
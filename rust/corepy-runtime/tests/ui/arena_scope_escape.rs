@@ -0,0 +1,11 @@
+// A slice from with_arena()'s scope must not outlive the closure: the region
+// is rewound when it returns
+use _corepy_rust::with_arena;
+
+fn main() {
+    let mut kept: &mut [f32] = &mut [];
+    with_arena(|scope| {
+        kept = scope.alloc_zeroed::<f32>(16).unwrap();
+    });
+    kept[0] = 1.0;
+}
@@ -0,0 +1,71 @@
+"""
+Tests for pointer alignment validation at the FFI boundary
+(check_alignment and the checks in the tensor_* wrappers).
+"""
+
+import numpy as np
+import pytest
+
+from corepy import _corepy_rust
+
+FLOAT32 = _corepy_rust.dtype_code("float32")
+
+
+def _misaligned_f32(count):
+    """float32 view starting one byte into its buffer"""
+    raw = np.zeros(count * 4 + 4, dtype=np.uint8)
+    view = raw[1 : count * 4 + 1].view(np.float32)
+    assert view.ctypes.data % 4 == 1
+    return view
+
+
+def test_check_alignment():
+    aligned = np.zeros(4, dtype=np.float32)
+    _corepy_rust.check_alignment(aligned.ctypes.data, FLOAT32)
+    _corepy_rust.check_alignment(aligned.ctypes.data + 1, _corepy_rust.dtype_code("int8"))
+
+    ptr = _misaligned_f32(4).ctypes.data
+    with pytest.raises(ValueError, match=f"ptr address {ptr:#x} is not 4-byte aligned \\(address % 4 = 1\\)"):
+        _corepy_rust.check_alignment(ptr, FLOAT32)
+    with pytest.raises(ValueError, match="unknown dtype code 200"):
+        _corepy_rust.check_alignment(aligned.ctypes.data, 200)
+
+
+@pytest.mark.parametrize("arg", ["a_ptr", "b_ptr", "out_ptr"])
+def test_binary_names_misaligned_argument(arg):
+    arrays = {name: np.ones(8, dtype=np.float32) for name in ("a_ptr", "b_ptr", "out_ptr")}
+    arrays[arg] = _misaligned_f32(8)
+    ptrs = {name: array.ctypes.data for name, array in arrays.items()}
+    with pytest.raises(ValueError, match=f"tensor_add_f32: {arg} address {ptrs[arg]:#x} .*% 4 = 1"):
+        _corepy_rust.tensor_add_f32(ptrs["a_ptr"], ptrs["b_ptr"], ptrs["out_ptr"], 8)
+
+
+def test_reduce_and_matmul_reject_misaligned_pointers():
+    data = _misaligned_f32(16)
+    with pytest.raises(ValueError, match="tensor_sum_f32: data_ptr address .* is not 4-byte aligned"):
+        _corepy_rust.tensor_sum_f32(data.ctypes.data, 16)
+
+    a = np.ones((4, 4), dtype=np.float32)
+    out = np.empty((4, 4), dtype=np.float32)
+    with pytest.raises(ValueError, match="tensor_matmul_2d_f32: b_ptr address .* \\(matmul on float32\\)"):
+        _corepy_rust.tensor_matmul_2d_f32(a.ctypes.data, data.ctypes.data, out.ctypes.data, 4, 4, 4)
+
+
+def test_buffer_entry_points_reject_misaligned_views():
+    raw = bytearray(4 * 8 + 1)
+    view = memoryview(raw)[1:].cast("f")
+    aligned = memoryview(bytearray(4 * 8)).cast("f")
+    with pytest.raises(ValueError, match="tensor_add_buffer: b address .* is not 4-byte aligned"):
+        _corepy_rust.tensor_add_buffer(aligned, view, aligned)
+    with pytest.raises(ValueError, match="np_sum_f32: a address .* is not 4-byte aligned"):
+        _corepy_rust.np_sum_f32(_misaligned_f32(8))
+
+
+@pytest.mark.parametrize("op, code", [("sub", 1), ("mul", 2), ("div", 3)])
+def test_broadcast_errors_name_the_requested_op(op, code):
+    a, out = np.ones((2, 4), dtype=np.float32), np.empty((2, 4), dtype=np.float32)
+    b = _misaligned_f32(4)
+    with pytest.raises(ValueError, match=f"tensor_broadcast_binary_f32: b_ptr address .* \\({op} on float32\\)"):
+        _corepy_rust.tensor_broadcast_binary_f32(
+            a.ctypes.data, [2, 4], list(a.strides), b.ctypes.data, [4], [4], out.ctypes.data, code
+        )
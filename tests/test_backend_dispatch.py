@@ -0,0 +1,1153 @@
+"""
+Tests for backend dispatch control and introspection exposed by the Rust runtime.
+"""
+
+import ctypes
+import warnings
+
+import numpy as np
+import pytest
+
+from corepy import _corepy_rust
+
+
+def _matmul(a, b):
+    """Run tensor_matmul_2d_f32 on two C-contiguous float32 arrays."""
+    m, k = a.shape
+    _, n = b.shape
+    out = np.zeros((m, n), dtype=np.float32)
+    _corepy_rust.tensor_matmul_2d_f32(a.ctypes.data, b.ctypes.data, out.ctypes.data, m, k, n)
+    return out
+
+
+def test_last_dispatch_stats_after_matmul():
+    a = np.random.rand(128, 64).astype(np.float32)
+    b = np.random.rand(64, 32).astype(np.float32)
+    out = _matmul(a, b)
+    np.testing.assert_allclose(out, a @ b, rtol=1e-4)
+
+    stats = _corepy_rust.get_last_dispatch_stats()
+    assert stats["operation"] == "matmul"
+    assert (stats["m"], stats["n"], stats["k"]) == (128, 32, 64)
+    assert stats["device"] == "cpu"
+    assert stats["duration_us"] > 0
+    assert stats["gflops"] > 0
+    assert stats["gb_per_s"] > 0
+
+
+def test_explain_last_dispatch_includes_throughput():
+    a = np.random.rand(16, 16).astype(np.float32)
+    _matmul(a, a)
+    explanation = _corepy_rust.explain_last_dispatch()
+    assert "GFLOPS" in explanation
+    assert "GB/s" in explanation
+
+
+def test_backend_num_threads_roundtrip():
+    original = _corepy_rust.get_backend_num_threads()
+    try:
+        _corepy_rust.set_backend_num_threads(2)
+        assert _corepy_rust.get_backend_num_threads() == 2
+        _corepy_rust.set_backend_num_threads(0)
+        assert _corepy_rust.get_backend_num_threads() == 0
+    finally:
+        _corepy_rust.set_backend_num_threads(original)
+
+
+def test_backend_num_threads_rejects_negative():
+    with pytest.raises(ValueError):
+        _corepy_rust.set_backend_num_threads(-1)
+
+
+def test_single_blas_thread_large_matmul():
+    original = _corepy_rust.get_backend_num_threads()
+    try:
+        _corepy_rust.set_backend_num_threads(1)
+        a = np.random.rand(512, 512).astype(np.float32)
+        b = np.random.rand(512, 512).astype(np.float32)
+        out = _matmul(a, b)
+        np.testing.assert_allclose(out, a @ b, rtol=1e-3)
+
+        stats = _corepy_rust.get_last_dispatch_stats()
+        assert stats["operation"] == "matmul"
+        assert (stats["m"], stats["n"], stats["k"]) == (512, 512, 512)
+    finally:
+        _corepy_rust.set_backend_num_threads(original)
+
+
+def test_num_threads_set_before_first_use():
+    # Needs a fresh process: checks the size the pool is first built with
+    import subprocess
+    import sys
+
+    script = (
+        "import numpy as np\n"
+        "import _corepy_rust as rt\n"
+        "rt.set_num_threads(3)\n"
+        "assert rt.get_num_threads() == 3\n"
+        "data = np.ones(2_000_000, dtype=np.float32)\n"
+        "assert rt.tensor_sum_f32(data.ctypes.data, data.size) == data.size\n"
+        "print(rt.get_num_threads())\n"
+    )
+    result = subprocess.run([sys.executable, "-c", script], check=True,
+                            capture_output=True, text=True)
+    assert result.stdout.strip() == "3"
+
+
+def test_initialize_runtime_before_first_op():
+    # Needs a fresh process: the first call is the one that builds the pool
+    import subprocess
+    import sys
+
+    script = (
+        "import numpy as np\n"
+        "import _corepy_rust as rt\n"
+        "config = rt.initialize_runtime(num_threads=3)\n"
+        "assert config['num_threads'] == rt.get_num_threads() == 3\n"
+        "assert config['arena_size'] > 0 and isinstance(config['blas_enabled'], bool)\n"
+        "assert rt.initialize_runtime(num_threads=5) == config\n"
+        "data = np.ones(2_000_000, dtype=np.float32)\n"
+        "assert rt.tensor_sum_f32(data.ctypes.data, data.size) == data.size\n"
+        "print(rt.get_num_threads())\n"
+    )
+    result = subprocess.run([sys.executable, "-c", script], check=True,
+                            capture_output=True, text=True)
+    assert result.stdout.strip() == "3"
+    assert result.stderr == ""
+
+
+def test_num_threads_rebuild_mid_process():
+    original = _corepy_rust.get_num_threads()
+    data = np.ones(2_000_000, dtype=np.float32)
+    try:
+        for size in (2, 1):
+            _corepy_rust.set_num_threads(size)
+            assert _corepy_rust.get_num_threads() == size
+            assert _corepy_rust.tensor_sum_f32(data.ctypes.data, data.size) == data.size
+    finally:
+        _corepy_rust.set_num_threads(original)
+
+
+def test_num_threads_rejects_zero():
+    with pytest.raises(ValueError):
+        _corepy_rust.set_num_threads(0)
+
+
+def test_chunking_info():
+    info = _corepy_rust.get_chunking_info(1_100_000, 4)
+    assert info["chunk_len"] * 4 <= info["target_bytes"]
+    assert info["num_chunks"] >= info["num_threads"]
+    assert info["chunk_len"] * info["num_chunks"] >= 1_100_000
+    with pytest.raises(ValueError):
+        _corepy_rust.get_chunking_info(10, 0)
+
+
+def test_max_threads_results_match():
+    a = np.random.rand(300, 200).astype(np.float32)
+    b = np.random.rand(200, 150).astype(np.float32)
+    expected = _matmul(a, b)
+    for max_threads in (1, 2, 4, None):
+        out = np.zeros((300, 150), dtype=np.float32)
+        _corepy_rust.tensor_matmul_2d_f32(a.ctypes.data, b.ctypes.data, out.ctypes.data,
+                                          300, 200, 150, max_threads=max_threads)
+        np.testing.assert_allclose(out, expected, rtol=1e-5)
+
+    data = np.arange(2_000_003, dtype=np.int32) % 7
+    for max_threads in (1, 2, 4, None):
+        assert _corepy_rust.tensor_sum_i32(data.ctypes.data, data.size, max_threads) == int(data.sum())
+        stats = _corepy_rust.get_last_dispatch_stats()
+        if max_threads == 1:
+            assert (stats["backend"], stats["num_threads"]) == ("CPU-serial", 1)
+        else:
+            assert stats["backend"] == "CPU-parallel"
+            assert 1 <= stats["num_threads"] <= (max_threads or _corepy_rust.get_num_threads())
+
+    with pytest.raises(ValueError, match="max_threads"):
+        _corepy_rust.tensor_sum_f32(data.ctypes.data, 10, 0)
+
+
+def test_scheduler_stats_count_paths():
+    small = np.ones(1000, dtype=np.float32)
+    huge = np.ones(2_000_000, dtype=np.float32)
+    before = _corepy_rust.get_scheduler_stats()
+    assert _corepy_rust.tensor_sum_f32(small.ctypes.data, small.size) == small.size
+    assert _corepy_rust.tensor_sum_f32(huge.ctypes.data, huge.size) == huge.size
+    after = _corepy_rust.get_scheduler_stats()
+
+    assert after["serial_dispatches"] - before["serial_dispatches"] == 1
+    assert after["parallel_dispatches"] - before["parallel_dispatches"] == 1
+    assert after["total_parallel_elements"] - before["total_parallel_elements"] == huge.size
+    assert after["tasks_spawned"] > before["tasks_spawned"]
+
+    _corepy_rust.reset_scheduler_stats()
+    assert set(_corepy_rust.get_scheduler_stats().values()) == {0}
+
+
+def test_progress_callback_monotonic():
+    # Fresh process with tiny chunks, so the sum has many work units
+    import os
+    import subprocess
+    import sys
+
+    script = (
+        "import numpy as np\n"
+        "import _corepy_rust as rt\n"
+        "readings = []\n"
+        "rt.set_progress_callback(lambda done, total: readings.append((done, total)), 1)\n"
+        "data = np.ones(16_000_000, dtype=np.float32)\n"
+        "assert rt.tensor_sum_f32(data.ctypes.data, data.size) == data.size\n"
+        "rt.set_progress_callback(None)\n"
+        "done, total = rt.get_operation_progress()\n"
+        "assert total > 100 and done == total\n"
+        "assert readings[-1] == (total, total)\n"
+        "assert all(a[0] <= b[0] for a, b in zip(readings, readings[1:]))\n"
+        "assert all(t == total for _, t in readings)\n"
+        "print(len(readings))\n"
+    )
+    env = dict(os.environ, COREPY_CHUNK_BYTES="4096")
+    result = subprocess.run([sys.executable, "-c", script], env=env, check=True,
+                            capture_output=True, text=True)
+    assert int(result.stdout) >= 1
+
+
+def test_request_cancel_stops_running_op():
+    # Fresh process: the cancel flag is process-wide
+    import subprocess
+    import sys
+
+    script = (
+        "import threading, time\n"
+        "import numpy as np\n"
+        "import _corepy_rust as rt\n"
+        "assert issubclass(rt.CorepyCancelled, RuntimeError)\n"
+        "n = 1024\n"
+        "a = np.ones((n, n), dtype=np.float32)\n"
+        "b = np.ones((n, n), dtype=np.float32)\n"
+        "out = np.empty((n, n), dtype=np.float32)\n"
+        "errors = []\n"
+        "def worker():\n"
+        "    try:\n"
+        "        # Keep the pool busy until the cancel lands\n"
+        "        while True:\n"
+        "            rt.tensor_matmul_2d_f32(a.ctypes.data, b.ctypes.data, out.ctypes.data, n, n, n)\n"
+        "    except rt.CorepyCancelled as err:\n"
+        "        errors.append(err)\n"
+        "thread = threading.Thread(target=worker)\n"
+        "thread.start()\n"
+        "time.sleep(0.2)\n"
+        "try:\n"
+        "    rt.request_cancel()\n"
+        "    thread.join(30)\n"
+        "    assert not thread.is_alive() and len(errors) == 1\n"
+        "    # Sticky until cleared; out holds unspecified contents\n"
+        "    data = np.ones(1000, dtype=np.float32)\n"
+        "    try:\n"
+        "        rt.tensor_sum_f32(data.ctypes.data, data.size)\n"
+        "        raise AssertionError('expected CorepyCancelled')\n"
+        "    except rt.CorepyCancelled:\n"
+        "        pass\n"
+        "finally:\n"
+        "    rt.clear_cancel()\n"
+        "assert rt.tensor_sum_f32(data.ctypes.data, data.size) == 1000\n"
+    )
+    subprocess.run([sys.executable, "-c", script], check=True, timeout=120)
+
+
+def test_timeout_stops_native_matmul():
+    import time
+
+    if _corepy_rust.get_backend_capabilities()["blas_enabled"]:
+        pytest.skip("BLAS sgemm is only checked before and after the kernel")
+    n = 2048
+    a = np.ones((n, n), dtype=np.float32)
+    b = np.ones((n, n), dtype=np.float32)
+    out = np.empty((n, n), dtype=np.float32)
+    started = time.perf_counter()
+    with pytest.raises(TimeoutError):
+        _corepy_rust.tensor_matmul_2d_f32(a.ctypes.data, b.ctypes.data, out.ctypes.data, n, n, n,
+                                          timeout_ms=50)
+    # Remaining row blocks are skipped once the deadline passes
+    elapsed = time.perf_counter() - started
+    assert 0.05 <= elapsed < 1.0
+
+    # A generous budget changes nothing, and the deadline doesn't outlive the call
+    data = np.ones(2_000_000, dtype=np.float32)
+    assert _corepy_rust.tensor_sum_f32(data.ctypes.data, data.size, timeout_ms=60_000) == data.size
+    assert _corepy_rust.tensor_mean_f32(data.ctypes.data, data.size) == 1.0
+
+
+def test_set_arena_size_updates_capabilities():
+    previous = _corepy_rust.get_backend_capabilities()["arena_size"]
+    try:
+        _corepy_rust.set_arena_size(64 * 1024 * 1024)
+        assert _corepy_rust.get_backend_capabilities()["arena_size"] == 64 * 1024 * 1024
+        # Workers resize at their next operation
+        data = np.ones(2_000_000, dtype=np.float32)
+        assert _corepy_rust.tensor_sum_f32(data.ctypes.data, data.size) == data.size
+        with pytest.raises(ValueError):
+            _corepy_rust.set_arena_size(0)
+    finally:
+        _corepy_rust.set_arena_size(previous)
+
+
+def test_k_split_matmul_arena_and_heap_scratch_agree():
+    # Short, deep products split K; each run in a fresh process so the arena
+    # env vars apply to every worker
+    import json
+    import os
+    import subprocess
+    import sys
+
+    if _corepy_rust.get_backend_capabilities()["blas_enabled"]:
+        pytest.skip("K-split is a native-backend strategy")
+    script = (
+        "import json\n"
+        "import numpy as np\n"
+        "import _corepy_rust as rt\n"
+        "rng = np.random.default_rng(7)\n"
+        "a = rng.integers(-3, 4, (2, 8192)).astype(np.float32)\n"
+        "b = rng.integers(-3, 4, (8192, 64)).astype(np.float32)\n"
+        "out = np.empty((2, 64), dtype=np.float32)\n"
+        "rt.reset_scheduler_stats()\n"
+        "rt.tensor_matmul_2d_f32(a.ctypes.data, b.ctypes.data, out.ctypes.data, 2, 8192, 64)\n"
+        "assert np.array_equal(out, a @ b)\n"
+        "stats = rt.get_scheduler_stats()\n"
+        "print(json.dumps({'out': out.tolist(), 'fallbacks': stats['arena_heap_fallbacks'],\n"
+        "                  'threads': rt.get_num_threads()}))\n"
+    )
+
+    def run(**env):
+        env = dict(os.environ, **env)
+        result = subprocess.run([sys.executable, "-c", script], env=env, check=True,
+                                capture_output=True, text=True)
+        return json.loads(result.stdout)
+
+    arena = run()
+    heap = run(COREPY_ARENA_SIZE="1024", COREPY_ARENA_MAX_BYTES="1024")
+    assert arena["out"] == heap["out"]
+    assert arena["fallbacks"] == 0
+    if heap["threads"] > 1:
+        assert heap["fallbacks"] > 0
+
+
+def test_arena_stats_report_peak():
+    _corepy_rust.reset_arena_peak()
+    # The f16 matmul converts A into arena scratch on each worker
+    a = np.ones((64, 128), dtype=np.float16)
+    b = np.ones((128, 32), dtype=np.float16)
+    out = np.empty((64, 32), dtype=np.float32)
+    _corepy_rust.tensor_matmul_2d_f16(a.ctypes.data, b.ctypes.data, out.ctypes.data, 64, 128, 32)
+    assert np.all(out == 128)
+
+    stats = _corepy_rust.get_arena_stats()
+    for key in ("peak_bytes", "failed_allocations", "arena_bytes_allocated", "heap_fallback_bytes",
+                "fallback_count", "thread_used_bytes", "thread_capacity", "thread_peak_bytes"):
+        assert key in stats
+    assert stats["peak_bytes"] > 0
+    assert stats["thread_used_bytes"] == 0
+
+
+def test_buffer_pool_reuses_released_buffers():
+    handle = _corepy_rust.acquire_buffer(4000, "float32", zeroed=True)
+    ptr = _corepy_rust.buffer_ptr(handle)
+    assert ptr % 64 == 0
+    out = np.ctypeslib.as_array((ctypes.c_float * 1000).from_address(ptr))
+    assert np.all(out == 0)
+
+    # Use the pooled buffer as an op output
+    a = np.ones((10, 20), dtype=np.float32)
+    b = np.ones((20, 100), dtype=np.float32)
+    _corepy_rust.tensor_matmul_2d_f32(a.ctypes.data, b.ctypes.data, ptr, 10, 20, 100)
+    assert np.all(out == 20)
+    assert _corepy_rust.buffer_ptr(handle) == ptr
+
+    before = _corepy_rust.pool_stats()
+    _corepy_rust.release_buffer(handle)
+    with pytest.raises(ValueError):
+        _corepy_rust.buffer_ptr(handle)
+    again = _corepy_rust.acquire_buffer(4096, "float32")
+    assert _corepy_rust.buffer_ptr(again) == ptr
+    assert _corepy_rust.pool_stats()["hits"] == before["hits"] + 1
+    _corepy_rust.release_buffer(again)
+
+    with pytest.raises(ValueError):
+        _corepy_rust.acquire_buffer(6, "float32")
+    with pytest.raises(_corepy_rust.AllocationError, match="too large"):
+        _corepy_rust.acquire_buffer(2**63 + 1, "uint8")
+
+
+def test_owned_buffer_lifecycle():
+    handle = _corepy_rust.alloc_buffer(400, "float32")
+    address = _corepy_rust.buffer_address(handle)
+    assert address % 64 == 0
+    view = np.ctypeslib.as_array((ctypes.c_float * 100).from_address(address))
+    assert np.all(view == 0)
+
+    _corepy_rust.free_buffer(handle)
+    with pytest.raises(ValueError, match="already freed"):
+        _corepy_rust.free_buffer(handle)
+    with pytest.raises(ValueError):
+        _corepy_rust.buffer_address(handle)
+    with pytest.raises(ValueError, match="unknown dtype"):
+        _corepy_rust.alloc_buffer(8, "complex64")
+
+
+def test_pinned_buffer_flag_and_limit():
+    before = _corepy_rust.pinned_memory_stats()
+    normal = _corepy_rust.alloc_buffer(4096)
+    assert not _corepy_rust.buffer_is_pinned(normal)
+
+    with warnings.catch_warnings(record=True) as caught:
+        warnings.simplefilter("always")
+        pinned = _corepy_rust.alloc_buffer(4096, pinned=True)
+    stats = _corepy_rust.pinned_memory_stats()
+    if _corepy_rust.buffer_is_pinned(pinned):
+        assert not caught
+        assert stats["pinned_bytes"] > before["pinned_bytes"]
+    else:
+        # The OS refused the lock (RLIMIT_MEMLOCK); the buffer is pageable
+        assert [w.category for w in caught] == [RuntimeWarning]
+        assert stats["lock_failures"] == before["lock_failures"] + 1
+    view = np.ctypeslib.as_array((ctypes.c_float * 1024).from_address(_corepy_rust.buffer_address(pinned)))
+    assert np.all(view == 0)
+
+    for handle in (normal, pinned):
+        _corepy_rust.free_buffer(handle)
+    assert _corepy_rust.pinned_memory_stats()["pinned_bytes"] == before["pinned_bytes"]
+
+    _corepy_rust.set_pinned_limit(before["pinned_bytes"])
+    try:
+        with pytest.raises(ValueError, match="pinned memory limit"):
+            _corepy_rust.alloc_buffer(4096, pinned=True)
+        _corepy_rust.free_buffer(_corepy_rust.alloc_buffer(4096))
+    finally:
+        _corepy_rust.set_pinned_limit(before["limit"])
+
+
+def _register(array):
+    code = _corepy_rust.dtype_code(array.dtype.name)
+    return _corepy_rust.register_tensor(array.ctypes.data, list(array.shape), code, array)
+
+
+def test_tensor_handles_end_to_end():
+    a = np.arange(12, dtype=np.float32).reshape(3, 4)
+    b = np.full((3, 4), 2.0, dtype=np.float32)
+    out = np.empty((3, 4), dtype=np.float32)
+    ha, hb, hout = _register(a), _register(b), _register(out)
+
+    info = _corepy_rust.tensor_info(ha)
+    assert info["shape"] == (3, 4)
+    assert info["dtype"] == "float32"
+    assert info["nbytes"] == 48
+    assert info["ptr"] == a.ctypes.data
+
+    _corepy_rust.op_add(ha, hb, hout)
+    np.testing.assert_allclose(out, a + b)
+    _corepy_rust.op_mul(ha, hb, hout)
+    np.testing.assert_allclose(out, a * b)
+
+    w = np.ones((4, 5), dtype=np.float32)
+    prod = np.empty((3, 5), dtype=np.float32)
+    hw, hprod = _register(w), _register(prod)
+    _corepy_rust.op_matmul(ha, hw, hprod)
+    np.testing.assert_allclose(prod, a @ w)
+
+    # Shape and dtype mismatches are caught before any kernel runs
+    with pytest.raises(ValueError, match="incompatible"):
+        _corepy_rust.op_add(ha, hw, hout)
+    with pytest.raises(ValueError, match="incompatible"):
+        _corepy_rust.op_matmul(ha, hb, hprod)
+    wide = np.zeros((3, 4), dtype=np.float64)
+    hwide = _register(wide)
+    with pytest.raises(ValueError, match="expected float32"):
+        _corepy_rust.op_add(ha, hwide, hout)
+
+    for handle in (ha, hb, hout, hw, hprod, hwide):
+        _corepy_rust.unregister_tensor(handle)
+
+
+def test_tensor_handle_keeps_owner_alive_and_detects_staleness():
+    import gc
+    import weakref
+
+    class Owned(np.ndarray):
+        pass
+
+    array = np.ones(8, dtype=np.float32).view(Owned)
+    alive = weakref.ref(array)
+    handle = _register(array)
+    del array
+    gc.collect()
+    assert alive() is not None
+
+    _corepy_rust.unregister_tensor(handle)
+    gc.collect()
+    assert alive() is None
+
+    with pytest.raises(ValueError, match="stale"):
+        _corepy_rust.tensor_info(handle)
+    with pytest.raises(ValueError, match="stale"):
+        _corepy_rust.op_add(handle, handle, handle)
+    with pytest.raises(ValueError, match="stale"):
+        _corepy_rust.unregister_tensor(handle)
+    with pytest.raises(ValueError, match="invalid"):
+        _corepy_rust.tensor_info(0)
+
+
+def _register_until_slot_reused(stale, make):
+    """Register tensors until one lands in `stale`'s slot (low 32 bits)"""
+    held = []
+    while True:
+        handle = make()
+        if handle & 0xFFFFFFFF == stale & 0xFFFFFFFF:
+            return handle, held
+        held.append(handle)
+
+
+@pytest.mark.parametrize("debug", [False, True])
+def test_reused_slot_rejects_stale_handle(debug):
+    _corepy_rust.set_handle_debug(debug)
+    try:
+        old_array = np.arange(4, dtype=np.float32)
+        old = _register(old_array)
+        _corepy_rust.unregister_tensor(old)
+
+        new_array = np.full(4, 7.0, dtype=np.float32)
+        new, held = _register_until_slot_reused(old, lambda: _register(new_array))
+        assert new != old
+        assert _corepy_rust.tensor_info(new)["ptr"] == new_array.ctypes.data
+
+        for use in (_corepy_rust.tensor_info, _corepy_rust.unregister_tensor,
+                    lambda h: _corepy_rust.op_add(h, new, new),
+                    lambda h: _corepy_rust.op_reduce(h, 0),
+                    lambda h: _corepy_rust.tensor_view(h, [0], [1], [1])):
+            with pytest.raises(ValueError, match="stale tensor handle") as excinfo:
+                use(old)
+            assert ("created at:" in str(excinfo.value)) == debug
+
+        # The new tensor is unaffected
+        assert _corepy_rust.op_reduce(new, 0) == pytest.approx(28.0)
+        for handle in held + [new]:
+            _corepy_rust.unregister_tensor(handle)
+    finally:
+        _corepy_rust.set_handle_debug(False)
+
+
+def test_tensor_views_match_numpy():
+    a = np.arange(48, dtype=np.float32).reshape(6, 8)
+    ha = _register(a)
+
+    # a[1:6:2, 2:8:3]
+    hs = _corepy_rust.tensor_view(ha, [1, 2], [3, 2], [2, 3])
+    sliced = a[1:6:2, 2:8:3]
+    info = _corepy_rust.tensor_info(hs)
+    assert info["shape"] == sliced.shape
+    assert info["strides"] == sliced.strides
+    assert info["ptr"] == sliced.ctypes.data
+    assert not info["c_contiguous"]
+    assert _corepy_rust.op_reduce(hs, 0) == pytest.approx(float(sliced.sum()))
+    assert _corepy_rust.op_reduce(hs, 1) == pytest.approx(float(sliced.mean()))
+
+    ht = _corepy_rust.tensor_transpose_view(ha)
+    assert _corepy_rust.tensor_info(ht)["strides"] == a.T.strides
+    out = np.empty((8, 6), dtype=np.float32)
+    hout = _register(out)
+    _corepy_rust.op_add(ht, ht, hout)
+    np.testing.assert_array_equal(out, a.T + a.T)
+
+    w = np.ones((6, 3), dtype=np.float32)
+    prod = np.empty((8, 3), dtype=np.float32)
+    hw, hprod = _register(w), _register(prod)
+    _corepy_rust.op_matmul(ht, hw, hprod)
+    np.testing.assert_allclose(prod, a.T @ w)
+
+    hr = _corepy_rust.tensor_reshape(ha, [4, 12])
+    assert _corepy_rust.tensor_info(hr)["shape"] == (4, 12)
+    assert _corepy_rust.op_reduce(hr, 0) == pytest.approx(float(a.sum()))
+
+    with pytest.raises(ValueError, match="out of bounds"):
+        _corepy_rust.tensor_view(ha, [4, 0], [2, 8], [2, 1])
+    with pytest.raises(ValueError, match="C-contiguous"):
+        _corepy_rust.tensor_reshape(ht, [48])
+    with pytest.raises(ValueError, match="cannot reshape"):
+        _corepy_rust.tensor_reshape(ha, [5, 10])
+
+    for handle in (ha, hs, ht, hout, hw, hprod, hr):
+        _corepy_rust.unregister_tensor(handle)
+
+
+def test_numa_info_and_striped_sums():
+    info = _corepy_rust.get_numa_info()
+    assert info["nodes"]
+    assert all(node["cpus"] for node in info["nodes"])
+    assert len(info["worker_nodes"]) == _corepy_rust.get_num_threads()
+    assert all(0 <= node < len(info["nodes"]) for node in info["worker_nodes"])
+
+    # Node-striped reductions give the same results as a serial sum
+    data = np.arange(3_000_001, dtype=np.int32) % 7
+    assert _corepy_rust.tensor_sum_i32(data.ctypes.data, data.size) == int(data.sum())
+    data = np.ones(3_000_001, dtype=np.float32)
+    assert _corepy_rust.tensor_sum_f32(data.ctypes.data, data.size) == data.size
+
+
+def test_thread_affinity_round_trip():
+    try:
+        _corepy_rust.set_thread_affinity("spread")
+        affinity = _corepy_rust.get_thread_affinity()
+        assert affinity["mode"] == "spread"
+        assert len(affinity["cores"]) in (0, _corepy_rust.get_num_threads())
+        data = np.ones(2_000_000, dtype=np.float32)
+        assert _corepy_rust.tensor_sum_f32(data.ctypes.data, data.size) == data.size
+    finally:
+        _corepy_rust.set_thread_affinity("none")
+    assert _corepy_rust.get_thread_affinity() == {"mode": "none", "cores": []}
+
+    with pytest.raises(ValueError, match="expected one of"):
+        _corepy_rust.set_thread_affinity("numa")
+
+
+def test_async_submit_and_collect():
+    arrays = [np.full(100_000, i, dtype=np.float32) for i in range(4)]
+    handles = [_corepy_rust.submit_sum_f32(x.ctypes.data, x.size, keep_alive=x) for x in arrays]
+    a = np.random.rand(64, 32).astype(np.float32)
+    b = np.random.rand(32, 16).astype(np.float32)
+    out = np.zeros((64, 16), dtype=np.float32)
+    mm = _corepy_rust.submit_matmul_2d_f32(a.ctypes.data, b.ctypes.data, out.ctypes.data, 64, 32, 16,
+                                           keep_alive=(a, b, out))
+
+    for i, handle in enumerate(handles):
+        assert _corepy_rust.wait(handle)
+        assert _corepy_rust.is_ready(handle)
+        assert _corepy_rust.get_result_f32(handle) == pytest.approx(i * 100_000)
+    assert _corepy_rust.wait(mm, 10_000)
+    _corepy_rust.release_handle(mm)
+    np.testing.assert_allclose(out, a @ b, rtol=1e-4)
+
+    # Collected handles are gone
+    with pytest.raises(ValueError, match="already collected"):
+        _corepy_rust.is_ready(mm)
+
+
+def test_async_wait_timeout_keeps_result():
+    a = np.random.rand(1024, 1024).astype(np.float32)
+    out = np.zeros_like(a)
+    handle = _corepy_rust.submit_matmul_2d_f32(a.ctypes.data, a.ctypes.data, out.ctypes.data,
+                                               1024, 1024, 1024, keep_alive=(a, out))
+    if not _corepy_rust.wait(handle, 0):
+        with pytest.raises(RuntimeError, match="still running"):
+            _corepy_rust.release_handle(handle)
+    # A timed-out wait leaves the handle to be collected later
+    assert _corepy_rust.wait(handle)
+    _corepy_rust.release_handle(handle)
+    np.testing.assert_allclose(out, a @ a, rtol=1e-3)
+
+
+def test_matmul_releases_gil():
+    import threading
+
+    a = np.random.rand(1024, 1024).astype(np.float32)
+    ticks = 0
+    done = threading.Event()
+
+    def count():
+        nonlocal ticks
+        while not done.is_set():
+            ticks += 1
+
+    counter = threading.Thread(target=count)
+    counter.start()
+    try:
+        # Let the counter get going, then measure only the matmul window
+        while ticks == 0:
+            pass
+        before = ticks
+        _matmul(a, a)
+        during = ticks - before
+    finally:
+        done.set()
+        counter.join()
+    # With the GIL held the counter would be frozen for the whole matmul
+    assert during > 1000
+
+
+def test_profiling_across_threads_with_gil_released():
+    from concurrent.futures import ThreadPoolExecutor
+
+    data = np.ones(100_000, dtype=np.float32)
+    _corepy_rust.clear_profile()
+    _corepy_rust.enable_profiling()
+    try:
+        with ThreadPoolExecutor(max_workers=4) as pool:
+            sums = list(pool.map(lambda _: _corepy_rust.tensor_sum_f32(data.ctypes.data, data.size), range(8)))
+        report = _corepy_rust.get_profile_report_dict(None, True)
+    finally:
+        _corepy_rust.disable_profiling()
+    assert sums == [data.size] * 8
+    assert report["operations"]["sum"]["count"] == 8
+    sum_events = [e for e in report["events"] if e["operation"] == "sum"]
+    assert all(e["gil_held_us"] + e["gil_released_us"] == e["end_time_us"] - e["start_time_us"]
+               for e in sum_events)
+
+
+@pytest.mark.parametrize("m,k,n", [(8, 8, 8), (64, 32, 48), (300, 300, 300)])
+def test_matmul_accumulate_mode(m, k, n):
+    a1 = np.random.rand(m, k).astype(np.float32)
+    b1 = np.random.rand(k, n).astype(np.float32)
+    a2 = np.random.rand(m, k).astype(np.float32)
+    b2 = np.random.rand(k, n).astype(np.float32)
+    c = np.zeros((m, n), dtype=np.float32)
+
+    _corepy_rust.tensor_matmul_2d_f32_acc(a1.ctypes.data, b1.ctypes.data, c.ctypes.data, m, k, n)
+    _corepy_rust.tensor_matmul_2d_f32_acc(a2.ctypes.data, b2.ctypes.data, c.ctypes.data, m, k, n)
+
+    np.testing.assert_allclose(c, _matmul(a1, b1) + _matmul(a2, b2), rtol=1e-4)
+    assert _corepy_rust.get_last_dispatch_stats()["operation"] == "matmul_acc"
+
+
+def test_matmul_accumulate_into_existing_values():
+    a = np.random.rand(4, 3).astype(np.float32)
+    b = np.random.rand(3, 5).astype(np.float32)
+    c = np.full((4, 5), 2.0, dtype=np.float32)
+    _corepy_rust.tensor_matmul_2d_f32_acc(a.ctypes.data, b.ctypes.data, c.ctypes.data, 4, 3, 5)
+    np.testing.assert_allclose(c, 2.0 + a @ b, rtol=1e-5)
+
+
+@pytest.fixture
+def restore_policy():
+    original = _corepy_rust.get_backend_policy()
+    yield
+    _corepy_rust.set_backend_policy(original)
+
+
+@pytest.mark.parametrize("name,canonical", [
+    ("auto", "default"),
+    ("default", "default"),
+    ("openblas", "openblas"),
+    ("blas", "blas"),
+    ("AUTO", "default"),
+    ("OpenBLAS", "openblas"),
+    ("Blas", "blas"),
+])
+def test_backend_policy_names(restore_policy, name, canonical):
+    _corepy_rust.set_backend_policy_name(name)
+    assert _corepy_rust.get_backend_policy_name() == canonical
+
+
+def test_backend_policy_name_rejects_typos(restore_policy):
+    _corepy_rust.set_backend_policy_name("blas")
+    with pytest.raises(ValueError, match="openblas"):
+        _corepy_rust.set_backend_policy_name("openbals")
+    # A failed set leaves the policy untouched
+    assert _corepy_rust.get_backend_policy_name() == "blas"
+
+
+def test_numeric_backend_policy_rejects_unknown(restore_policy):
+    with pytest.raises(ValueError):
+        _corepy_rust.set_backend_policy(7)
+
+
+@pytest.mark.parametrize("value", [4, 7, 255, 256, -1])
+def test_numeric_backend_policy_rejects_each_invalid_value(restore_policy, value):
+    _corepy_rust.set_backend_policy(2)
+    with pytest.raises(ValueError, match="Unknown backend policy"):
+        _corepy_rust.set_backend_policy(value)
+    assert _corepy_rust.get_backend_policy() == 2
+
+
+@pytest.mark.parametrize("setter,arg", [
+    ("set_backend_policy", 3),
+    ("set_backend_policy_name", "cuda"),
+    ("set_backend_policy_name", "CUDA"),
+])
+def test_cuda_policy_not_implemented(restore_policy, setter, arg):
+    _corepy_rust.set_backend_policy(0)
+    with pytest.raises(NotImplementedError, match="cuda"):
+        getattr(_corepy_rust, setter)(arg)
+    assert _corepy_rust.get_backend_policy() == 0
+
+
+def test_last_dispatch_is_tracked_per_thread():
+    import threading
+
+    sizes = [8, 24, 40, 72, 136]
+    barrier = threading.Barrier(len(sizes))
+    failures = []
+
+    def worker(size):
+        a = np.random.rand(size, size).astype(np.float32)
+        barrier.wait()
+        for _ in range(20):
+            _matmul(a, a)
+            explanation = _corepy_rust.explain_last_dispatch()
+            if f"size={size}x{size}x{size}" not in explanation:
+                failures.append((size, explanation))
+
+    threads = [threading.Thread(target=worker, args=(s,)) for s in sizes]
+    for t in threads:
+        t.start()
+    for t in threads:
+        t.join()
+
+    assert failures == []
+
+
+def test_backend_capabilities_keys():
+    caps = _corepy_rust.get_backend_capabilities()
+    for key in ("blas_enabled", "blas_vendor", "simd_level", "rayon_threads", "blas_threads", "arena_size"):
+        assert key in caps
+    assert isinstance(caps["blas_enabled"], bool)
+    assert caps["rayon_threads"] > 0
+    assert caps["arena_size"] > 0
+
+
+def test_backend_capabilities_blas_consistency():
+    caps = _corepy_rust.get_backend_capabilities()
+    # The vendor string comes from the same C++ translation unit as corepy_is_blas_enabled
+    assert caps["blas_enabled"] == (caps["blas_vendor"] != "none")
+    # Cached: repeated calls report the same detection
+    assert _corepy_rust.get_backend_capabilities()["simd_level"] == caps["simd_level"]
+
+
+@pytest.fixture
+def restore_matmul_threshold():
+    original = _corepy_rust.get_dispatch_threshold("matmul")
+    yield
+    _corepy_rust.set_dispatch_threshold("matmul", original)
+
+
+def test_matmul_threshold_flips_dispatch(restore_policy, restore_matmul_threshold):
+    if not _corepy_rust.get_backend_capabilities()["blas_enabled"]:
+        pytest.skip("BLAS not compiled in")
+    _corepy_rust.set_backend_policy(0)
+    a = np.random.rand(64, 64).astype(np.float32)
+
+    _corepy_rust.set_dispatch_threshold("matmul", 63)
+    _matmul(a, a)
+    assert _corepy_rust.get_last_dispatch_stats()["backend_id"] == 1
+
+    _corepy_rust.set_dispatch_threshold("matmul", 64)
+    _matmul(a, a)
+    assert _corepy_rust.get_last_dispatch_stats()["backend_id"] == 0
+
+    _corepy_rust.set_dispatch_threshold("matmul", 0)
+    _matmul(a, a)
+    assert _corepy_rust.get_last_dispatch_stats()["backend_id"] == 1
+
+    _corepy_rust.set_dispatch_threshold("matmul", 2**64 - 1)
+    _matmul(a, a)
+    assert _corepy_rust.get_last_dispatch_stats()["backend_id"] == 0
+
+
+def test_dispatch_threshold_rejects_unknown_op(restore_matmul_threshold):
+    with pytest.raises(ValueError, match="matmul"):
+        _corepy_rust.set_dispatch_threshold("conv", 10)
+    with pytest.raises(ValueError):
+        _corepy_rust.get_dispatch_threshold("conv")
+
+
+def test_policy_push_pop_nesting(restore_policy):
+    _corepy_rust.set_backend_policy(0)
+    _corepy_rust.push_backend_policy(2)
+    assert _corepy_rust.get_backend_policy() == 2
+    _corepy_rust.push_backend_policy(1)
+    assert _corepy_rust.get_backend_policy() == 1
+
+    assert _corepy_rust.pop_backend_policy() == 1
+    assert _corepy_rust.get_backend_policy() == 2
+    assert _corepy_rust.pop_backend_policy() == 2
+    assert _corepy_rust.get_backend_policy() == 0
+
+
+def test_policy_pop_empty_raises():
+    with pytest.raises(RuntimeError, match="empty"):
+        _corepy_rust.pop_backend_policy()
+
+
+def test_policy_push_rejects_invalid():
+    with pytest.raises(ValueError):
+        _corepy_rust.push_backend_policy(9)
+    with pytest.raises(NotImplementedError):
+        _corepy_rust.push_backend_policy(3)
+
+
+def test_backend_policy_context_manager_restores_on_error(restore_policy):
+    from corepy.backend import BackendPolicy, backend_policy
+
+    _corepy_rust.set_backend_policy(0)
+    with pytest.raises(KeyError):
+        with backend_policy(BackendPolicy.BLAS):
+            assert _corepy_rust.get_backend_policy() == 2
+            raise KeyError("boom")
+    assert _corepy_rust.get_backend_policy() == 0
+
+
+def test_cuda_not_available():
+    assert _corepy_rust.cuda_available() is False
+
+
+@pytest.fixture
+def clear_matmul_override():
+    yield
+    _corepy_rust.set_operation_policy("matmul", None)
+
+
+def test_forced_cuda_matmul_raises(clear_matmul_override):
+    a = np.random.rand(16, 16).astype(np.float32)
+    out = np.full((16, 16), 7.0, dtype=np.float32)
+    _corepy_rust.set_operation_policy("matmul", 3)
+    with pytest.raises(NotImplementedError, match="cuda"):
+        _corepy_rust.tensor_matmul_2d_f32(a.ctypes.data, a.ctypes.data, out.ctypes.data, 16, 16, 16)
+    # Nothing ran, and the failed attempt is visible in the diagnostics
+    assert (out == 7.0).all()
+    stats = _corepy_rust.get_last_dispatch_stats()
+    assert stats["attempted_backend"] == "CUDA"
+    assert "unavailable" in _corepy_rust.explain_last_dispatch()
+
+    _corepy_rust.set_operation_policy("matmul", None)
+    _matmul(a, a)
+    assert _corepy_rust.get_last_dispatch_stats()["attempted_backend"] is None
+
+
+def test_sum_records_serial_vs_parallel():
+    small = np.ones(1_000, dtype=np.float32)
+    _corepy_rust.tensor_sum_f32(small.ctypes.data, small.size)
+    small_stats = _corepy_rust.get_last_dispatch_stats()
+    assert small_stats["operation"] == "sum"
+    assert small_stats["elements"] == 1_000
+    assert small_stats["backend"] == "CPU-serial"
+
+    huge = np.ones(4_000_000, dtype=np.float32)
+    _corepy_rust.tensor_sum_f32(huge.ctypes.data, huge.size)
+    huge_stats = _corepy_rust.get_last_dispatch_stats()
+    assert huge_stats["elements"] == 4_000_000
+    assert huge_stats["backend"] == "CPU-parallel"
+    assert huge_stats["backend_id"] != small_stats["backend_id"]
+
+
+def test_elementwise_dispatch_is_explained():
+    a = np.ones(256, dtype=np.float32)
+    out = np.empty_like(a)
+    _corepy_rust.tensor_add_f32(a.ctypes.data, a.ctypes.data, out.ctypes.data, a.size)
+    assert _corepy_rust.explain_last_dispatch().startswith("add → CPU-serial (elements=256")
+
+
+@pytest.fixture
+def clear_dispatch_callback():
+    yield
+    _corepy_rust.set_dispatch_callback(None)
+
+
+def test_dispatch_callback_collects_events(clear_dispatch_callback):
+    events = []
+    _corepy_rust.set_dispatch_callback(events.append)
+    _corepy_rust.drain_dispatch_events()
+    events.clear()
+
+    for size in (8, 16, 32):
+        a = np.random.rand(size, size).astype(np.float32)
+        _matmul(a, a)
+    assert events == []  # Buffered until drained
+
+    assert _corepy_rust.drain_dispatch_events() == 3
+    assert [e["dims"] for e in events] == [(8, 8, 8), (16, 16, 16), (32, 32, 32)]
+    for event in events:
+        assert event["operation"] == "matmul"
+        assert {"backend", "policy"} <= event.keys()
+
+
+def test_dispatch_callback_unregister(clear_dispatch_callback):
+    events = []
+    _corepy_rust.set_dispatch_callback(events.append)
+    _corepy_rust.set_dispatch_callback(None)
+    a = np.ones((4, 4), dtype=np.float32)
+    _matmul(a, a)
+    assert _corepy_rust.drain_dispatch_events() == 0
+    assert events == []
+
+
+def test_dispatch_callback_errors_surface_on_drain(clear_dispatch_callback):
+    def boom(event):
+        raise RuntimeError("logger failed")
+
+    _corepy_rust.set_dispatch_callback(boom)
+    a = np.ones((4, 4), dtype=np.float32)
+    _matmul(a, a)
+    with pytest.raises(RuntimeError, match="logger failed"):
+        _corepy_rust.drain_dispatch_events()
+
+
+def test_dispatch_callback_rejects_non_callable(clear_dispatch_callback):
+    with pytest.raises(TypeError):
+        _corepy_rust.set_dispatch_callback(42)
+
+
+@pytest.fixture
+def auto_tune():
+    _corepy_rust.reset_auto_tune()
+    yield
+    _corepy_rust.disable_auto_tune()
+    _corepy_rust.reset_auto_tune()
+
+
+def test_auto_tune_locks_in_a_backend(restore_policy, auto_tune):
+    if not _corepy_rust.get_backend_capabilities()["blas_enabled"]:
+        pytest.skip("BLAS not compiled in")
+    _corepy_rust.set_backend_policy(0)
+    _corepy_rust.enable_auto_tune(4)
+
+    a = np.random.rand(96, 96).astype(np.float32)
+    backends = []
+    for _ in range(8):
+        np.testing.assert_allclose(_matmul(a, a), a @ a, rtol=1e-4)
+        backends.append(_corepy_rust.get_last_dispatch_stats()["backend_id"])
+
+    # Exploration alternates, then a single backend is used
+    assert backends[:4] == [0, 1, 0, 1]
+    assert len(set(backends[4:])) == 1
+
+    (entry,) = [e for e in _corepy_rust.get_auto_tune_table() if e["operation"] == "matmul"]
+    assert entry["bucket"] == 128
+    assert entry["choice"] is not None
+
+
+def test_auto_tune_off_by_default(auto_tune):
+    a = np.ones((8, 8), dtype=np.float32)
+    _matmul(a, a)
+    assert _corepy_rust.get_auto_tune_table() == []
+
+
+def test_list_backends_includes_builtins():
+    backends = {b["name"]: b for b in _corepy_rust.list_backends()}
+    assert backends["Corepy AVX2"]["id"] == 0
+    assert backends["Corepy AVX2"]["available"] is True
+    assert backends["OpenBLAS"]["id"] == 1
+    caps = _corepy_rust.get_backend_capabilities()
+    assert backends["OpenBLAS"]["available"] == caps["blas_enabled"]
+
+
+@pytest.fixture
+def restore_backend_config():
+    saved = _corepy_rust.export_backend_config()
+    yield
+    _corepy_rust.import_backend_config(saved)
+
+
+def test_backend_config_round_trip(restore_backend_config, auto_tune):
+    import json
+
+    _corepy_rust.set_backend_policy_name("blas")
+    _corepy_rust.set_operation_policy("matmul_acc", 0)
+    _corepy_rust.set_dispatch_threshold("matmul", 123)
+    _corepy_rust.enable_auto_tune(6)
+    exported = _corepy_rust.export_backend_config()
+
+    # Mutate everything, then restore
+    _corepy_rust.set_backend_policy_name("auto")
+    _corepy_rust.set_operation_policy("matmul_acc", None)
+    _corepy_rust.set_dispatch_threshold("matmul", 999)
+    _corepy_rust.disable_auto_tune()
+    assert _corepy_rust.export_backend_config() != exported
+
+    _corepy_rust.import_backend_config(exported)
+    assert _corepy_rust.export_backend_config() == exported
+    config = json.loads(exported)
+    assert config["policy"] == "blas"
+    assert config["operation_policies"] == {"matmul_acc": "default"}
+    assert config["thresholds"] == {"matmul": 123}
+    assert config["auto_tune"] == {"enabled": True, "trials": 6, "table": []}
+
+
+@pytest.mark.parametrize("patch,path", [
+    ({"policy": "fastest"}, "policy"),
+    ({"operation_policies": {"conv2d": "blas"}}, "operation_policies.conv2d"),
+    ({"thresholds": {"sum": 10}}, "thresholds.sum"),
+])
+def test_backend_config_import_rejects_bad_fields(restore_backend_config, patch, path):
+    import json
+
+    _corepy_rust.set_dispatch_threshold("matmul", 77)
+    config = json.loads(_corepy_rust.export_backend_config())
+    config["thresholds"]["matmul"] = 5  # Must not be applied when another field is bad
+    config.update(patch)
+    with pytest.raises(ValueError, match=path.replace(".", r"\.")):
+        _corepy_rust.import_backend_config(json.dumps(config))
+    assert _corepy_rust.get_dispatch_threshold("matmul") == 77
+
+
+def test_last_dispatch_json_has_absolute_timestamp():
+    import json
+    import time
+
+    before_us = int(time.time() * 1e6)
+    a = np.ones((8, 8), dtype=np.float32)
+    _matmul(a, a)
+    after_us = int(time.time() * 1e6)
+
+    record = json.loads(_corepy_rust.get_last_dispatch_json())
+    assert record["operation"] == "matmul"
+    assert record["dimensions"] == {"Matrix": [8, 8, 8]}
+    # Allow a little clock granularity slack on either side
+    assert before_us - 1_000 <= record["timestamp_us"] <= after_us + 1_000
+    assert _corepy_rust.get_last_dispatch_stats()["timestamp_us"] == record["timestamp_us"]
+
+
+@pytest.fixture
+def dispatch_table():
+    _corepy_rust.clear_dispatch_table()
+    yield
+    _corepy_rust.clear_dispatch_table()
+
+
+def _synthetic_report(events):
+    import json
+
+    report = {
+        "metadata": {"session_id": "synthetic", "start_timestamp": "", "version": "", "context": None},
+        "operations": {},
+        "total_time_ms": 0.0,
+        "operation_count": 0,
+        "events": [
+            {"operation": op, "backend": backend, "data_size": 0, "start_time_us": 0,
+             "end_time_us": us, "context": None, "dims": [size, size, size]}
+            for op, backend, size, us in events
+        ],
+    }
+    return json.dumps(report)
+
+
+def test_dispatch_table_routes_by_size_bucket(restore_policy, dispatch_table):
+    report = _synthetic_report([
+        ("matmul", "Corepy AVX2", 16, 5),
+        ("matmul", "OpenBLAS", 16, 50),
+        ("matmul", "Corepy AVX2", 32, 500),
+        ("matmul", "OpenBLAS", 32, 50),
+    ])
+    assert _corepy_rust.build_dispatch_table_from_report(report) == 2
+    table = [(e["bucket"], e["backend"]) for e in _corepy_rust.get_dispatch_table()]
+    assert table == [(16, "Corepy AVX2"), (32, "OpenBLAS")]
+
+    if not _corepy_rust.get_backend_capabilities()["blas_enabled"]:
+        return
+    _corepy_rust.set_backend_policy(0)
+    for size, expected in [(16, 0), (32, 1)]:
+        a = np.random.rand(size, size).astype(np.float32)
+        np.testing.assert_allclose(_matmul(a, a), a @ a, rtol=1e-4)
+        assert _corepy_rust.get_last_dispatch_stats()["backend_id"] == expected
+
+
+def test_dispatch_table_from_live_profile(dispatch_table):
+    _corepy_rust.clear_profile()
+    _corepy_rust.enable_profiling()
+    try:
+        a = np.ones((8, 8), dtype=np.float32)
+        _matmul(a, a)
+        report = _corepy_rust.get_profile_report(None, True)
+    finally:
+        _corepy_rust.disable_profiling()
+    assert _corepy_rust.build_dispatch_table_from_report(report) >= 1
+    assert any(e["operation"] == "matmul" and e["bucket"] == 8 for e in _corepy_rust.get_dispatch_table())
+
+
+def test_dispatch_table_rejects_report_without_events(dispatch_table):
+    with pytest.raises(ValueError, match="no events"):
+        _corepy_rust.build_dispatch_table_from_report(_synthetic_report([]))
@@ -0,0 +1,69 @@
+"""
+Tests for N-D broadcast execution of element-wise ops (tensor_broadcast_binary_f32).
+"""
+
+import numpy as np
+import pytest
+
+from corepy import _corepy_rust
+
+OPS = {0: np.add, 1: np.subtract, 2: np.multiply, 3: np.divide}
+
+
+def broadcast(a, b, op_code):
+    out = np.empty(np.broadcast_shapes(a.shape, b.shape), dtype=np.float32)
+    summary = _corepy_rust.tensor_broadcast_binary_f32(
+        a.ctypes.data, list(a.shape), list(a.strides),
+        b.ctypes.data, list(b.shape), list(b.strides),
+        out.ctypes.data, op_code,
+    )
+    assert summary["shape"] == out.shape
+    return out, summary
+
+
+def rand(*shape):
+    return np.asarray(np.random.random_sample(shape) + 0.5, dtype=np.float32)
+
+
+@pytest.mark.parametrize("op_code", sorted(OPS))
+@pytest.mark.parametrize(
+    "a_shape, b_shape",
+    [
+        ((64, 33), (33,)),
+        ((64, 1), (1, 33)),
+        ((5, 7), ()),
+        ((), (5, 7)),
+        ((2, 1, 3, 1), (4, 1, 5)),
+        ((3, 4), (3, 4)),
+    ],
+)
+def test_matches_numpy_broadcasting(a_shape, b_shape, op_code):
+    a, b = rand(*a_shape), rand(*b_shape)
+    out, _ = broadcast(a, b, op_code)
+    np.testing.assert_allclose(out, OPS[op_code](a, b), rtol=1e-6)
+
+
+def test_strided_views():
+    base = rand(8, 6)
+    for a in (base.T, base[::2, ::3], base[::-1]):
+        b = rand(a.shape[-1])
+        out, _ = broadcast(a, b, 0)
+        np.testing.assert_allclose(out, a + b, rtol=1e-6)
+
+
+def test_contiguous_run_fast_path():
+    _, summary = broadcast(rand(64, 33), rand(33), 0)
+    assert summary["run_length"] == 33
+    assert summary["contiguous_runs"] == 64
+    assert summary["strided_runs"] == 0
+
+    _, summary = broadcast(rand(8, 9), rand(8, 9), 2)
+    assert (summary["contiguous_runs"], summary["run_length"]) == (1, 72)
+
+
+def test_rejects_bad_operands():
+    a, b = rand(3), rand(4)
+    with pytest.raises(ValueError, match="cannot be broadcast"):
+        broadcast(a, b, 0)
+    with pytest.raises(ValueError, match="Unknown binary op code"):
+        broadcast(a, a, 9)
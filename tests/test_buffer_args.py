@@ -0,0 +1,73 @@
+"""
+Tests for the buffer-protocol op variants (tensor_add_buffer /
+tensor_sum_buffer) with non-NumPy exporters.
+"""
+
+import array
+import mmap
+import struct
+
+import pytest
+
+from corepy import _corepy_rust
+
+
+def _floats(values):
+    return memoryview(bytearray(struct.pack(f"{len(values)}f", *values))).cast("f")
+
+
+def test_array_array():
+    a = array.array("f", [float(i) for i in range(1000)])
+    b = array.array("f", [0.5] * 1000)
+    out = array.array("f", [0.0] * 1000)
+    _corepy_rust.tensor_add_buffer(a, b, out)
+    assert list(out) == [x + 0.5 for x in a]
+    assert _corepy_rust.tensor_sum_buffer(a) == pytest.approx(sum(a))
+
+
+def test_memoryview_over_bytearray():
+    a = _floats([1.0, 2.0, 3.0])
+    b = _floats([10.0, 20.0, 30.0])
+    out = _floats([0.0] * 3)
+    _corepy_rust.tensor_add_buffer(a, b, out)
+    assert out.tolist() == [11.0, 22.0, 33.0]
+    assert _corepy_rust.tensor_sum_buffer(out) == 66.0
+
+    # Multi-dimensional but contiguous views are summed as flat vectors
+    assert _corepy_rust.tensor_sum_buffer(_floats([1.0, 2.0, 3.0, 4.0]).cast("B").cast("f", [2, 2])) == 10.0
+
+
+def test_mmap_backed_buffers(tmp_path):
+    path = tmp_path / "data.f32"
+    count = 4096
+    path.write_bytes(struct.pack(f"{count}f", *range(count)))
+
+    with open(path, "r+b") as f, mmap.mmap(f.fileno(), 0) as mapped:
+        view = memoryview(mapped).cast("f")
+        assert _corepy_rust.tensor_sum_buffer(view) == pytest.approx(count * (count - 1) / 2)
+        _corepy_rust.tensor_add_buffer(view, view, view)
+        assert view[count - 1] == 2.0 * (count - 1)
+        view.release()
+
+    with open(path, "rb") as f, mmap.mmap(f.fileno(), 0, access=mmap.ACCESS_READ) as mapped:
+        view = memoryview(mapped).cast("f")
+        with pytest.raises(ValueError, match="tensor_add_buffer: out is read-only .*ACCESS_READ"):
+            _corepy_rust.tensor_add_buffer(view, view, view)
+        assert _corepy_rust.tensor_sum_buffer(view) == pytest.approx(count * (count - 1))
+        view.release()
+
+
+def test_targeted_errors():
+    floats = array.array("f", [1.0, 2.0])
+    with pytest.raises(ValueError, match="obj must hold float32 items \\(format 'f'\\), got format 'B' \\(1-byte items\\)"):
+        _corepy_rust.tensor_sum_buffer(bytearray(8))
+    with pytest.raises(ValueError, match="got format 'd' \\(8-byte items\\)"):
+        _corepy_rust.tensor_sum_buffer(array.array("d", [1.0]))
+    with pytest.raises(ValueError, match="out is read-only"):
+        _corepy_rust.tensor_add_buffer(floats, floats, memoryview(bytes(8)).cast("f"))
+    with pytest.raises(ValueError, match="same length \\(got 2, 2 and 3\\)"):
+        _corepy_rust.tensor_add_buffer(floats, floats, array.array("f", [0.0] * 3))
+    with pytest.raises(ValueError, match="b must be a contiguous buffer"):
+        _corepy_rust.tensor_add_buffer(floats, _floats([0.0] * 4)[::2], floats)
+    with pytest.raises(TypeError):
+        _corepy_rust.tensor_sum_buffer([1.0, 2.0])
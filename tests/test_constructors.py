@@ -0,0 +1,83 @@
+"""
+Tests for runtime-side constructors (tensor_zeros / tensor_ones /
+tensor_arange / tensor_linspace) against their NumPy equivalents.
+"""
+
+import ctypes
+
+import numpy as np
+import pytest
+
+from corepy import _corepy_rust
+
+
+def _take(handle):
+    info = _corepy_rust.tensor_info(handle)
+    out = np.empty(info["shape"], dtype=info["dtype"])
+    ctypes.memmove(out.ctypes.data, info["ptr"], info["nbytes"])
+    _corepy_rust.unregister_tensor(handle)
+    return out
+
+
+@pytest.mark.parametrize("dtype", ["float32", "float64", "int32", "uint8", "bool", "float16"])
+@pytest.mark.parametrize("shape", [(5,), (2, 3), (), (0, 4)])
+def test_zeros_and_ones(dtype, shape):
+    zeros = _take(_corepy_rust.tensor_zeros(list(shape), dtype))
+    np.testing.assert_array_equal(zeros, np.zeros(shape, dtype=dtype))
+    assert zeros.dtype == np.dtype(dtype)
+    ones = _take(_corepy_rust.tensor_ones(list(shape), dtype))
+    np.testing.assert_array_equal(ones, np.ones(shape, dtype=dtype))
+
+
+@pytest.mark.parametrize("start,stop,step", [
+    (0, 10, 1),
+    (0.1, 1.0, 0.2),
+    (0, 1, 0.1),
+    (5, -4, -3),
+    (1, 5, -1),
+    (-2.5, 2.5, 0.25),
+    (0, 5, 1.5),
+])
+@pytest.mark.parametrize("dtype", ["float64", "float32", "int32", "int64"])
+def test_arange_matches_numpy(start, stop, step, dtype):
+    ours = _take(_corepy_rust.tensor_arange(start, stop, step, dtype))
+    np.testing.assert_array_equal(ours, np.arange(start, stop, step, dtype=dtype))
+
+
+def test_arange_large_parallel_fill():
+    n = 3_000_000
+    np.testing.assert_array_equal(_take(_corepy_rust.tensor_arange(0, n, 1, "int64")), np.arange(n))
+
+
+def test_arange_rejects_bad_ranges():
+    with pytest.raises(ValueError, match="step must not be zero"):
+        _corepy_rust.tensor_arange(0, 1, 0)
+    with pytest.raises(ValueError, match="cannot compute the length"):
+        _corepy_rust.tensor_arange(0, float("inf"), 1)
+    with pytest.raises(ValueError, match="unknown dtype"):
+        _corepy_rust.tensor_arange(0, 1, 1, "complex64")
+
+
+@pytest.mark.parametrize("start,stop,num,endpoint", [
+    (0, 1, 50, True),
+    (0, 1, 7, False),
+    (-3.3, 7.7, 11, True),
+    (2.5, 9, 1, True),
+    (2.5, 9, 1, False),
+    (2.5, 9, 0, True),
+    (4, 4, 5, True),
+    (1e-310, 2e-310, 3, True),
+])
+@pytest.mark.parametrize("dtype", ["float64", "float32"])
+def test_linspace_matches_numpy(start, stop, num, endpoint, dtype):
+    ours = _take(_corepy_rust.tensor_linspace(start, stop, num, endpoint, dtype))
+    expected = np.linspace(start, stop, num, endpoint=endpoint, dtype=dtype)
+    np.testing.assert_array_equal(ours, expected)
+    if endpoint and num > 1:
+        assert ours[0] == np.array(start, dtype=dtype)
+        assert ours[-1] == np.array(stop, dtype=dtype)
+
+
+def test_linspace_integer_dtype_floors():
+    ours = _take(_corepy_rust.tensor_linspace(-2, 1, 4, False, "int32"))
+    np.testing.assert_array_equal(ours, [-2, -2, -1, 0])
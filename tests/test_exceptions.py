@@ -0,0 +1,65 @@
+"""
+Tests for the runtime's exception classes: CorepyError and its subclasses,
+and the builtins they still derive from for compatibility.
+"""
+
+import array
+
+import pytest
+
+from corepy import _corepy_rust
+
+CLASSES = ["ShapeError", "DTypeError", "BackendError", "AllocationError", "CancelledError"]
+
+
+def _address(buffer):
+    return buffer.buffer_info()[0]
+
+
+def test_hierarchy():
+    base = _corepy_rust.CorepyError
+    assert issubclass(base, ValueError)
+    for name in CLASSES:
+        cls = getattr(_corepy_rust, name)
+        assert issubclass(cls, base) and issubclass(cls, ValueError), name
+        assert cls.__module__ == "_corepy_rust"
+    assert issubclass(_corepy_rust.BackendError, NotImplementedError)
+    assert issubclass(_corepy_rust.BackendError, RuntimeError)
+    assert issubclass(_corepy_rust.CancelledError, RuntimeError)
+    assert _corepy_rust.CorepyCancelled is _corepy_rust.CancelledError
+
+
+@pytest.mark.parametrize(
+    "call, cls, match",
+    [
+        (lambda: _corepy_rust.tensor_triu_f32(0, 2, 2, 0), "CorepyError", "Null pointer passed to tensor_triu_f32"),
+        (lambda: _corepy_rust.tensor_add_f32(0, 0, 0, 4), "CorepyError", "Null pointer passed to tensor_add_f32"),
+        (lambda: _corepy_rust.tensor_mean_f32(_address(array.array("f", [0.0])), 0), "ShapeError", "empty tensor"),
+        (lambda: _corepy_rust.tensor_eye_f32(8, 1 << 40), "ShapeError", "overflows in tensor_eye_f32"),
+        (lambda: _corepy_rust.tensor_zeros([2], "complex64"), "DTypeError", "unknown dtype 'complex64'"),
+        (lambda: _corepy_rust.tensor_reduce(8, 4, 0, 200), "DTypeError", "unknown dtype code 200"),
+        (lambda: _corepy_rust.tensor_sum_buffer(bytearray(8)), "DTypeError", "must hold float32 items"),
+        (lambda: _corepy_rust.set_backend_policy_name("nope"), "BackendError", "Unknown backend policy 'nope'"),
+        (lambda: _corepy_rust.free_buffer(2**63), "AllocationError", "buffer handle"),
+        (lambda: _corepy_rust.release_buffer(2**63), "AllocationError", "buffer handle"),
+        (lambda: _corepy_rust.tensor_info(2**63), "CorepyError", "invalid tensor handle"),
+    ],
+)
+def test_sites_raise_their_class(call, cls, match):
+    with pytest.raises(getattr(_corepy_rust, cls), match=match) as excinfo:
+        call()
+    assert type(excinfo.value).__name__ == cls
+    assert isinstance(excinfo.value, (ValueError, RuntimeError))
+
+
+def test_backend_errors_keep_their_builtin_classes():
+    with pytest.raises(RuntimeError):
+        _corepy_rust.pop_backend_policy()
+    with pytest.raises(_corepy_rust.BackendError, match="stack is empty"):
+        _corepy_rust.pop_backend_policy()
+
+
+def test_shape_mismatch_between_buffers():
+    floats = array.array("f", [1.0, 2.0])
+    with pytest.raises(_corepy_rust.ShapeError, match="same length"):
+        _corepy_rust.tensor_add_buffer(floats, floats, array.array("f", [0.0]))
@@ -0,0 +1,108 @@
+"""
+Tests for execute_batch, which runs a list of small ops in one FFI call.
+"""
+
+import array
+import builtins
+
+import pytest
+
+from corepy import _corepy_rust
+
+N = 1000
+
+
+def _floats(values):
+    return array.array("f", values)
+
+
+def _address(buffer):
+    return buffer.buffer_info()[0]
+
+
+def _tensors():
+    a = _floats([float(i % 17) for i in range(N)])
+    b = _floats([0.25 * (i % 5) for i in range(N)])
+    return a, b
+
+
+def _mixed_ops(a, b, outs):
+    # 20 ops: pairs of add/mul chained through outs, with sums interleaved
+    ops, pa, pb = [], _address(a), _address(b)
+    for i, out in enumerate(outs):
+        po = _address(out)
+        ops.append({"op": "add" if i % 2 == 0 else "mul", "inputs": [pa, pb], "out": po, "count": N})
+        ops.append({"op": "sum", "inputs": [po], "count": N})
+        pa = po
+    return ops
+
+
+def test_matches_individual_calls():
+    a, b = _tensors()
+    batch_outs = [_floats([0.0] * N) for _ in range(10)]
+    results = _corepy_rust.execute_batch(_mixed_ops(a, b, batch_outs))
+    assert len(results) == 20
+
+    pa, pb = _address(a), _address(b)
+    for i, out in enumerate([_floats([0.0] * N) for _ in range(10)]):
+        op = _corepy_rust.tensor_add_f32 if i % 2 == 0 else _corepy_rust.tensor_mul_f32
+        op(pa, pb, _address(out), N)
+        assert out == batch_outs[i]
+        assert results[2 * i] is None
+        assert results[2 * i + 1] == _corepy_rust.tensor_sum_f32(_address(out), N)
+        pa = _address(out)
+
+
+def test_matmul_and_dtype_default():
+    a = _floats([1.0, 2.0, 3.0, 4.0, 5.0, 6.0])
+    b = _floats([1.0, 0.0, 0.0, 1.0, 1.0, 1.0])
+    out = _floats([0.0] * 4)
+    flags = array.array("B", [0, 1, 0])
+    results = _corepy_rust.execute_batch([
+        {"op": "matmul", "inputs": [_address(a), _address(b)], "out": _address(out), "dims": (2, 3, 2)},
+        {"op": "any", "inputs": [_address(flags)], "count": 3, "dtype": "bool"},
+        {"op": "sum", "inputs": [_address(a)], "count": 0},
+    ])
+    assert list(out) == [4.0, 5.0, 10.0, 11.0]
+    assert results == [None, True, 0.0]
+    assert _corepy_rust.execute_batch([]) == []
+
+
+@pytest.mark.parametrize(
+    "spec, cls, match",
+    [
+        ({"op": "pow", "inputs": [], "count": 1}, "CorepyError", "unknown op 'pow'"),
+        ({"op": "add", "inputs": ["a"], "count": 1}, "TypeError", ""),
+        ({"op": "add", "inputs": [8, 8], "count": 1}, "CorepyError", "add needs 'out'"),
+        ({"op": "sum", "inputs": [8], "out": 8, "count": 1}, "CorepyError", "omit 'out'"),
+        ({"op": "sum", "inputs": [8], "count": 1, "stride": 2}, "CorepyError", "unknown key 'stride'"),
+        ({"op": "add", "inputs": [0, 8], "out": 8, "count": 1}, "CorepyError", "Null pointer passed to execute_batch"),
+        ({"op": "add", "inputs": [9, 8], "out": 8, "count": 1}, "CorepyError", "inputs\\[0\\]"),
+        ({"op": "mean", "inputs": [8], "count": 0}, "ShapeError", "mean of empty tensor"),
+        ({"op": "add", "inputs": [8, 8], "out": 8, "count": 1, "dtype": "complex64"}, "DTypeError", "complex64"),
+        ({"op": "matmul", "inputs": [8, 8], "out": 8, "dims": (2, 3, 2), "dtype": "float64"}, "DTypeError", "float32"),
+    ],
+)
+def test_errors_name_the_op_and_run_nothing(spec, cls, match):
+    a, b = _tensors()
+    out = _floats([0.0] * N)
+    first = {"op": "add", "inputs": [_address(a), _address(b)], "out": _address(out), "count": N}
+    expected = getattr(_corepy_rust, cls, None) or getattr(builtins, cls)
+    with pytest.raises(expected, match=f"^execute_batch: op 1: .*{match}"):
+        _corepy_rust.execute_batch([first, spec])
+    # Checked as a whole before running: op 0 never wrote its output
+    assert set(out) == {0.0}
+
+
+def test_one_profile_event_per_op():
+    a, b = _tensors()
+    outs = [_floats([0.0] * N) for _ in range(10)]
+    _corepy_rust.clear_profile()
+    _corepy_rust.enable_profiling()
+    try:
+        _corepy_rust.execute_batch(_mixed_ops(a, b, outs))
+        operations = _corepy_rust.get_profile_report_dict()["operations"]
+    finally:
+        _corepy_rust.disable_profiling()
+        _corepy_rust.clear_profile()
+    assert {name: operations[name]["count"] for name in ("add", "mul", "sum")} == {"add": 5, "mul": 5, "sum": 10}
@@ -0,0 +1,63 @@
+"""
+Tests for element-count validation at the FFI boundary: counts whose byte
+size passes isize::MAX, overflowing matmul dimensions, and buffers of
+different lengths sharing one count.
+"""
+
+import array
+import sys
+
+import numpy as np
+import pytest
+
+from corepy import _corepy_rust
+
+ISIZE_MAX = sys.maxsize
+
+
+@pytest.fixture
+def data():
+    return np.ones(16, dtype=np.float32)
+
+
+def test_count_at_the_boundary_passes_the_size_check(data):
+    # max_threads=0 is rejected just after the size check, so the kernel
+    # never runs over the (far too short) buffer
+    with pytest.raises(ValueError, match="max_threads must be at least 1"):
+        _corepy_rust.tensor_sum_f32(data.ctypes.data, ISIZE_MAX // 4, max_threads=0)
+    with pytest.raises(ValueError, match="max_threads must be at least 1"):
+        _corepy_rust.tensor_random_fill(data.ctypes.data, ISIZE_MAX // 8, "uniform", dtype="float64", max_threads=0)
+
+
+def test_count_past_the_boundary_raises_overflow(data):
+    ptr = data.ctypes.data
+    with pytest.raises(OverflowError, match="tensor_sum_f32: count .* of 4-byte elements .* more than isize::MAX"):
+        _corepy_rust.tensor_sum_f32(ptr, ISIZE_MAX // 4 + 1)
+    with pytest.raises(OverflowError, match="the count applies to each of a_ptr, b_ptr and out_ptr"):
+        _corepy_rust.tensor_add_f32(ptr, ptr, ptr, ISIZE_MAX // 4 + 1)
+    with pytest.raises(OverflowError, match="tensor_dot_product_f32: .*each of a_ptr and b_ptr"):
+        _corepy_rust.tensor_dot_product_f32(ptr, ptr, ISIZE_MAX // 4 + 1)
+    with pytest.raises(OverflowError, match="tensor_random_fill: count .* of 8-byte elements"):
+        _corepy_rust.tensor_random_fill(ptr, ISIZE_MAX // 8 + 1, "uniform", dtype="float64")
+    with pytest.raises(OverflowError, match="submit_sum_f32"):
+        _corepy_rust.submit_sum_f32(ptr, ISIZE_MAX)
+    with pytest.raises(OverflowError):
+        _corepy_rust.tensor_cast_f16_to_f32(ptr, ptr, ISIZE_MAX // 2)
+
+
+def test_matmul_dimension_products(data):
+    ptr = data.ctypes.data
+    side = 1 << 22
+    with pytest.raises(OverflowError, match="tensor_matmul_2d_f32: the product of \\[4194304, 4194304, 4194304\\] overflows"):
+        _corepy_rust.tensor_matmul_2d_f32(ptr, ptr, ptr, side, side, side)
+    with pytest.raises(OverflowError, match="submit_matmul_2d_f32: .*each of a_ptr"):
+        _corepy_rust.submit_matmul_2d_f32(ptr, ptr, ptr, 1 << 31, 1 << 31, 1)
+    # Element counts past isize::MAX are still shape errors
+    with pytest.raises(ValueError, match="overflows.*tensor_matmul_2d_f16"):
+        _corepy_rust.tensor_matmul_2d_f16(ptr, ptr, ptr, 1 << 40, 1 << 40, 1)
+
+
+def test_buffers_sharing_one_count_must_agree():
+    floats = array.array("f", [1.0, 2.0])
+    with pytest.raises(ValueError, match="a, b and out must have the same length \\(got 2, 2 and 3\\); one count applies"):
+        _corepy_rust.tensor_add_buffer(floats, floats, array.array("f", [0.0] * 3))
@@ -0,0 +1,82 @@
+"""
+Tests for f16 storage: casts and f16 matmul with f32 accumulation.
+"""
+
+import numpy as np
+import pytest
+
+from corepy import _corepy_rust
+
+
+def _cast_f16_to_f32(arr_f16):
+    out = np.empty(arr_f16.shape, dtype=np.float32)
+    _corepy_rust.tensor_cast_f16_to_f32(arr_f16.ctypes.data, out.ctypes.data, arr_f16.size)
+    return out
+
+
+def _cast_f32_to_f16(arr_f32):
+    out = np.empty(arr_f32.shape, dtype=np.float16)
+    _corepy_rust.tensor_cast_f32_to_f16(arr_f32.ctypes.data, out.ctypes.data, arr_f32.size)
+    return out
+
+
+def test_cast_f16_to_f32_matches_numpy():
+    src = (np.random.randn(10_000) * 100).astype(np.float16)
+    np.testing.assert_array_equal(_cast_f16_to_f32(src), src.astype(np.float32))
+
+
+def test_cast_f32_to_f16_matches_numpy():
+    src = (np.random.randn(10_000) * 1000).astype(np.float32)
+    # Includes values beyond the f16 range, which must round to inf like NumPy
+    src[:3] = [70000.0, -70000.0, 1e-9]
+    np.testing.assert_array_equal(_cast_f32_to_f16(src).view(np.uint16),
+                                  src.astype(np.float16).view(np.uint16))
+
+
+def test_cast_special_values():
+    src = np.array([np.inf, -np.inf, np.nan, -0.0, 0.0], dtype=np.float16)
+    out = _cast_f16_to_f32(src)
+    assert out[0] == np.inf
+    assert out[1] == -np.inf
+    assert np.isnan(out[2])
+    assert np.signbit(out[3]) and out[3] == 0.0
+
+    back = _cast_f32_to_f16(out)
+    assert np.isinf(back[0]) and np.isinf(back[1])
+    assert np.isnan(back[2])
+
+
+def test_cast_all_f16_bit_patterns_roundtrip():
+    bits = np.arange(0, 2**16, dtype=np.uint16)
+    halves = bits.view(np.float16)
+    as_f32 = _cast_f16_to_f32(halves)
+    np.testing.assert_array_equal(as_f32, halves.astype(np.float32))
+
+    finite = ~np.isnan(halves)
+    back = _cast_f32_to_f16(as_f32)
+    np.testing.assert_array_equal(back.view(np.uint16)[finite], bits[finite])
+
+
+@pytest.mark.parametrize("m,k,n", [(1, 1, 1), (7, 13, 5), (130, 64, 33), (300, 200, 100)])
+def test_matmul_2d_f16_matches_f32_reference(m, k, n):
+    a = np.random.randn(m, k).astype(np.float16)
+    b = np.random.randn(k, n).astype(np.float16)
+    out = np.zeros((m, n), dtype=np.float32)
+
+    _corepy_rust.tensor_matmul_2d_f16(a.ctypes.data, b.ctypes.data, out.ctypes.data, m, k, n)
+
+    expected = a.astype(np.float32) @ b.astype(np.float32)
+    np.testing.assert_allclose(out, expected, rtol=1e-4, atol=1e-4)
+
+
+def test_matmul_2d_f16_propagates_inf_and_nan():
+    a = np.ones((2, 2), dtype=np.float16)
+    a[0, 0] = np.inf
+    a[1, 0] = np.nan
+    b = np.ones((2, 2), dtype=np.float16)
+    out = np.zeros((2, 2), dtype=np.float32)
+
+    _corepy_rust.tensor_matmul_2d_f16(a.ctypes.data, b.ctypes.data, out.ctypes.data, 2, 2, 2)
+
+    assert np.all(np.isinf(out[0]))
+    assert np.all(np.isnan(out[1]))
@@ -0,0 +1,95 @@
+"""
+Tests for dtype-generic dispatch through the kernel registry
+(tensor_reduce / tensor_binary / list_kernels).
+"""
+
+import numpy as np
+import pytest
+
+from corepy import _corepy_rust
+
+REDUCE_CODES = {"sum": 0, "mean": 1, "all": 2, "any": 3}
+BINARY_CODES = {"add": 0, "sub": 1, "mul": 2, "div": 3}
+REDUCE_REFERENCE = {"sum": np.sum, "mean": np.mean, "all": np.all, "any": np.any}
+BINARY_REFERENCE = {"add": np.add, "sub": np.subtract, "mul": np.multiply, "div": np.divide}
+DTYPES = ["bool", "int8", "uint8", "int16", "uint16", "float16",
+          "int32", "uint32", "float32", "int64", "uint64", "float64"]
+
+KERNELS = _corepy_rust.list_kernels()
+
+
+def sample(dtype, n=1000, seed=0):
+    rng = np.random.default_rng(seed)
+    if dtype == "bool":
+        return rng.random(n) < 0.5
+    return (rng.random(n) * 10 + 1).astype(dtype)
+
+
+def test_registry_lists_existing_kernels():
+    assert ("reduce", "sum", "float32") in KERNELS
+    assert ("reduce", "sum", "int32") in KERNELS
+    assert ("reduce", "mean", "float32") in KERNELS
+    assert ("reduce", "any", "bool") in KERNELS
+    assert ("binary", "add", "float32") in KERNELS
+
+
+@pytest.mark.parametrize("kind, op, dtype", KERNELS)
+def test_registered_kernel_executes(kind, op, dtype):
+    code = _corepy_rust.dtype_code(dtype)
+    a = sample(dtype)
+    if kind == "reduce":
+        result = _corepy_rust.tensor_reduce(a.ctypes.data, a.size, REDUCE_CODES[op], code)
+        np.testing.assert_allclose(result, REDUCE_REFERENCE[op](a), rtol=1e-4)
+    else:
+        b = sample(dtype, seed=1)
+        out = np.empty_like(a)
+        _corepy_rust.tensor_binary(a.ctypes.data, b.ctypes.data, out.ctypes.data, a.size, BINARY_CODES[op], code)
+        np.testing.assert_allclose(out, BINARY_REFERENCE[op](a, b), rtol=1e-6)
+
+
+@pytest.mark.parametrize("dtype", DTYPES)
+def test_unregistered_combinations_error(dtype):
+    code = _corepy_rust.dtype_code(dtype)
+    a = sample(dtype, n=4)
+    for op, op_code in REDUCE_CODES.items():
+        if ("reduce", op, dtype) not in KERNELS:
+            with pytest.raises(ValueError, match=f"unsupported op/dtype combination {op}/{dtype}.*supported: "):
+                _corepy_rust.tensor_reduce(a.ctypes.data, a.size, op_code, code)
+    for op, op_code in BINARY_CODES.items():
+        if ("binary", op, dtype) not in KERNELS:
+            with pytest.raises(ValueError, match=f"unsupported op/dtype combination {op}/{dtype}"):
+                _corepy_rust.tensor_binary(a.ctypes.data, a.ctypes.data, a.ctypes.data, a.size, op_code, code)
+
+
+def test_typed_wrappers_match_generic_entry_points():
+    a = sample("float32")
+    assert _corepy_rust.tensor_sum_f32(a.ctypes.data, a.size) == pytest.approx(
+        _corepy_rust.tensor_reduce(a.ctypes.data, a.size, 0, _corepy_rust.dtype_code("float32")))
+    flags = np.array([False, False, True])
+    assert _corepy_rust.tensor_any(flags.ctypes.data, flags.size) is True
+    assert _corepy_rust.tensor_reduce(flags.ctypes.data, 0, 2, _corepy_rust.dtype_code("bool")) is True
+    with pytest.raises(ValueError, match="empty"):
+        _corepy_rust.tensor_mean_f32(a.ctypes.data, 0)
+    with pytest.raises(ValueError, match="unknown op code"):
+        _corepy_rust.tensor_reduce(a.ctypes.data, a.size, 7, _corepy_rust.dtype_code("float32"))
+
+
+RESULT_TYPES = {"sum": {"int32": int, "float32": float}, "mean": {"float32": float}, "all": {"bool": bool}, "any": {"bool": bool}}
+
+
+@pytest.mark.parametrize("op, dtype", [(op, dtype) for kind, op, dtype in KERNELS if kind == "reduce"])
+def test_reduce_by_name_returns_python_type(op, dtype):
+    a = sample(dtype)
+    code = _corepy_rust.dtype_code(dtype)
+    result = _corepy_rust.tensor_reduce(a.ctypes.data, a.size, op, code)
+    assert type(result) is RESULT_TYPES[op][dtype]
+    assert result == _corepy_rust.tensor_reduce(a.ctypes.data, a.size, REDUCE_CODES[op], code)
+
+
+@pytest.mark.parametrize("op, dtype", [("sum", "float64"), ("mean", "int32"), ("all", "float32"), ("sum", "uint8")])
+def test_reduce_by_name_rejects_unsupported_dtypes(op, dtype):
+    a = sample(dtype, n=4)
+    with pytest.raises(_corepy_rust.DTypeError, match=f"{op}/{dtype} \\(supported: .*sum/float32"):
+        _corepy_rust.tensor_reduce(a.ctypes.data, a.size, op, _corepy_rust.dtype_code(dtype))
+    with pytest.raises(_corepy_rust.CorepyError, match="unknown reduction 'prod'"):
+        _corepy_rust.tensor_reduce(a.ctypes.data, a.size, "prod", _corepy_rust.dtype_code(dtype))
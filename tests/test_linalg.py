@@ -0,0 +1,105 @@
+"""
+Tests for the Rust runtime linear algebra helpers (masking, norms, constructors).
+"""
+
+import numpy as np
+import pytest
+
+from corepy import _corepy_rust
+
+SHAPES = [(5, 5), (3, 7), (7, 3), (1, 4), (64, 129)]
+OFFSETS = [-3, -1, 0, 1, 2, 10]
+
+
+@pytest.mark.parametrize("shape", SHAPES)
+@pytest.mark.parametrize("k", OFFSETS)
+def test_triu_matches_numpy(shape, k):
+    a = np.random.rand(*shape).astype(np.float32)
+    expected = np.triu(a, k)
+    _corepy_rust.tensor_triu_f32(a.ctypes.data, shape[0], shape[1], k)
+    np.testing.assert_array_equal(a, expected)
+
+
+@pytest.mark.parametrize("shape", SHAPES)
+@pytest.mark.parametrize("k", OFFSETS)
+def test_tril_matches_numpy(shape, k):
+    a = np.random.rand(*shape).astype(np.float32)
+    expected = np.tril(a, k)
+    _corepy_rust.tensor_tril_f32(a.ctypes.data, shape[0], shape[1], k)
+    np.testing.assert_array_equal(a, expected)
+
+
+def test_triu_null_pointer():
+    with pytest.raises(ValueError):
+        _corepy_rust.tensor_triu_f32(0, 2, 2, 0)
+
+
+@pytest.mark.parametrize("shape", [(1, 1), (4, 9), (300, 500), (1, 200_000)])
+def test_frobenius_norm_matches_numpy(shape):
+    a = np.random.randn(*shape).astype(np.float32)
+    result = _corepy_rust.tensor_frobenius_norm_f32(a.ctypes.data, shape[0], shape[1])
+    expected = np.linalg.norm(a.astype(np.float64), "fro")
+    assert result == pytest.approx(expected, rel=1e-6)
+
+
+@pytest.mark.parametrize("ord", [1, 2])
+def test_row_norms_match_numpy(ord):
+    a = np.random.randn(257, 33).astype(np.float32)
+    a[3] = 0.0  # Row of zeros
+    out = np.empty(a.shape[0], dtype=np.float32)
+    _corepy_rust.tensor_row_norms_f32(a.ctypes.data, out.ctypes.data, a.shape[0], a.shape[1], ord)
+
+    expected = np.linalg.norm(a.astype(np.float64), ord=ord, axis=1)
+    np.testing.assert_allclose(out, expected, rtol=1e-6)
+    assert out[3] == 0.0
+
+
+def test_row_norms_rejects_invalid_ord():
+    a = np.ones((2, 2), dtype=np.float32)
+    out = np.empty(2, dtype=np.float32)
+    with pytest.raises(ValueError, match="norm order"):
+        _corepy_rust.tensor_row_norms_f32(a.ctypes.data, out.ctypes.data, 2, 2, 3)
+
+
+@pytest.mark.parametrize("n", [0, 1, 5, 130])
+def test_eye_matches_numpy(n):
+    out = np.full((n, n), 7.0, dtype=np.float32)
+    if n:
+        _corepy_rust.tensor_eye_f32(out.ctypes.data, n)
+    np.testing.assert_array_equal(out, np.eye(n, dtype=np.float32))
+
+
+@pytest.mark.parametrize("n", [1, 4, 257])
+def test_diag_matches_numpy(n):
+    v = np.random.rand(n).astype(np.float32)
+    out = np.full((n, n), -1.0, dtype=np.float32)
+    _corepy_rust.tensor_diag_f32(v.ctypes.data, out.ctypes.data, n)
+    np.testing.assert_array_equal(out, np.diag(v))
+
+
+def test_eye_rejects_overflowing_size():
+    buf = np.zeros(1, dtype=np.float32)
+    with pytest.raises(ValueError, match="overflows"):
+        _corepy_rust.tensor_eye_f32(buf.ctypes.data, 2**40)
+
+
+def test_2d_ops_reject_overflowing_shapes():
+    buf = np.zeros(1, dtype=np.float32)
+    with pytest.raises(ValueError, match=r"shape \[1099511627776, 1099511627776\] overflows.*tensor_triu_f32"):
+        _corepy_rust.tensor_triu_f32(buf.ctypes.data, 2**40, 2**40, 0)
+    with pytest.raises(ValueError, match="overflows.*tensor_matmul_2d_f32"):
+        _corepy_rust.tensor_matmul_2d_f32(buf.ctypes.data, buf.ctypes.data, buf.ctypes.data, 1, 2**40, 2**40)
+
+
+def test_large_eye_uses_parallel_fill():
+    # Just over the 1M-element parallel fill threshold
+    n = 1_024
+    out = np.empty((n, n), dtype=np.float32)
+    before = _corepy_rust.get_scheduler_stats()
+    _corepy_rust.tensor_eye_f32(out.ctypes.data, n)
+    after = _corepy_rust.get_scheduler_stats()
+
+    assert after["parallel_dispatches"] > before["parallel_dispatches"]
+    assert out[0, 0] == 1.0 and out[n - 1, n - 1] == 1.0
+    assert out[0, 1] == 0.0 and out[n - 1, 0] == 0.0
+    assert np.count_nonzero(out[:: n // 8]) == len(out[:: n // 8])
@@ -0,0 +1,89 @@
+"""
+Tests for .npy checkpointing from the runtime (save_npy / load_npy).
+"""
+
+import ctypes
+
+import numpy as np
+import pytest
+
+from corepy import _corepy_rust
+
+CASES = [
+    (np.float32, (12,)),
+    (np.float32, (3, 4)),
+    (np.int32, (2, 3, 4)),
+    (np.float64, (5, 1)),
+    (np.float64, ()),
+    (np.float32, (0, 3)),
+]
+
+
+def _array(dtype, shape):
+    return (np.arange(int(np.prod(shape)), dtype=np.float64) * 1.5 - 4).astype(dtype).reshape(shape)
+
+
+def _to_numpy(handle):
+    info = _corepy_rust.tensor_info(handle)
+    out = np.empty(info["shape"], dtype=info["dtype"])
+    ctypes.memmove(out.ctypes.data, info["ptr"], info["nbytes"])
+    return out
+
+
+@pytest.mark.parametrize("dtype,shape", CASES)
+def test_numpy_reads_our_files(tmp_path, dtype, shape):
+    a = _array(dtype, shape)
+    path = tmp_path / "ours.npy"
+    code = _corepy_rust.dtype_code(a.dtype.name)
+    _corepy_rust.save_npy(str(path), a.ctypes.data, list(a.shape), code)
+    loaded = np.load(path)
+    assert loaded.dtype == a.dtype
+    np.testing.assert_array_equal(loaded, a)
+
+
+@pytest.mark.parametrize("dtype,shape", CASES)
+def test_we_read_numpy_files(tmp_path, dtype, shape):
+    a = _array(dtype, shape)
+    path = tmp_path / "numpy.npy"
+    np.save(path, a)
+    handle = _corepy_rust.load_npy(str(path))
+    info = _corepy_rust.tensor_info(handle)
+    assert (info["shape"], info["dtype"]) == (a.shape, a.dtype.name)
+    np.testing.assert_array_equal(_to_numpy(handle), a)
+    _corepy_rust.unregister_tensor(handle)
+
+
+def test_save_handle_and_strided_view(tmp_path):
+    a = _array(np.float32, (4, 6))
+    handle = _corepy_rust.tensor_from_buffer(a)
+    _corepy_rust.save_npy(str(tmp_path / "whole.npy"), handle)
+    np.testing.assert_array_equal(np.load(tmp_path / "whole.npy"), a)
+
+    transposed = _corepy_rust.tensor_transpose_view(handle)
+    _corepy_rust.save_npy(str(tmp_path / "t.npy"), transposed)
+    np.testing.assert_array_equal(np.load(tmp_path / "t.npy"), a.T)
+
+    for h in (handle, transposed):
+        _corepy_rust.tensor_release(h)
+
+
+def test_rejected_files(tmp_path):
+    big = tmp_path / "big.npy"
+    np.save(big, np.arange(4, dtype=">f4"))
+    with pytest.raises(ValueError, match="big-endian"):
+        _corepy_rust.load_npy(str(big))
+
+    fortran = tmp_path / "fortran.npy"
+    np.save(fortran, np.asfortranarray(np.ones((3, 2), dtype=np.float32)))
+    with pytest.raises(ValueError, match="fortran_order"):
+        _corepy_rust.load_npy(str(fortran))
+
+    not_npy = tmp_path / "plain.txt"
+    not_npy.write_text("hello")
+    with pytest.raises(ValueError, match="not an .npy file"):
+        _corepy_rust.load_npy(str(not_npy))
+    with pytest.raises(OSError):
+        _corepy_rust.load_npy(str(tmp_path / "missing.npy"))
+
+    with pytest.raises(ValueError, match="shape and dtype_code"):
+        _corepy_rust.save_npy(str(tmp_path / "x.npy"), 1234, [3])
@@ -0,0 +1,88 @@
+"""
+Tests for the NumPy-array entry points (np_add_f32 / np_sum_f32 /
+np_matmul_f32), which take arrays instead of raw addresses.
+"""
+
+import numpy as np
+import pytest
+
+from corepy import _corepy_rust
+
+
+def test_add_contiguous_and_in_place():
+    a = np.arange(1000, dtype=np.float32)
+    b = np.full(1000, 0.5, dtype=np.float32)
+    out = np.empty_like(a)
+    _corepy_rust.np_add_f32(a, b, out)
+    np.testing.assert_array_equal(out, a + b)
+
+    _corepy_rust.np_add_f32(a, b, a)
+    np.testing.assert_array_equal(a, out)
+
+
+def test_add_sliced_inputs_need_copy():
+    base = np.arange(20, dtype=np.float32)
+    a, b = base[::2], base[1::2]
+    out = np.empty(10, dtype=np.float32)
+    with pytest.raises(ValueError, match="np_add_f32: a is not C-contiguous; pass copy=True"):
+        _corepy_rust.np_add_f32(a, b, out)
+    _corepy_rust.np_add_f32(a, b, out, copy=True)
+    np.testing.assert_array_equal(out, a + b)
+
+
+def test_add_rejects_bad_arguments():
+    a = np.ones(4, dtype=np.float32)
+    with pytest.raises(ValueError, match="same length \\(got 4 and 5\\)"):
+        _corepy_rust.np_add_f32(a, np.ones(5, dtype=np.float32), a)
+    with pytest.raises(ValueError, match="b must hold float32 items \\(format 'f'\\), got format 'd'"):
+        _corepy_rust.np_add_f32(a, np.ones(4), a)
+    with pytest.raises(ValueError, match="out has shape \\[3\\], expected \\[4\\]"):
+        _corepy_rust.np_add_f32(a, a, np.empty(3, dtype=np.float32))
+    with pytest.raises(ValueError, match="a must be 1-D, got 2-D"):
+        _corepy_rust.np_add_f32(a.reshape(2, 2), a, a)
+    frozen = np.empty(4, dtype=np.float32)
+    frozen.flags.writeable = False
+    with pytest.raises(ValueError, match="out is read-only"):
+        _corepy_rust.np_add_f32(a, a, frozen)
+    with pytest.raises(ValueError, match="out must be C-contiguous"):
+        _corepy_rust.np_add_f32(a, a, np.empty(8, dtype=np.float32)[::2], copy=True)
+    with pytest.raises(TypeError):
+        _corepy_rust.np_add_f32([1.0, 2.0], a, a)
+
+
+def test_sum_contiguous_and_sliced():
+    a = np.linspace(-1, 3, 10_001, dtype=np.float32)
+    assert _corepy_rust.np_sum_f32(a) == pytest.approx(float(a.sum(dtype=np.float64)), rel=1e-5)
+    reversed_view = a[::-1]
+    with pytest.raises(ValueError, match="not C-contiguous"):
+        _corepy_rust.np_sum_f32(reversed_view)
+    assert _corepy_rust.np_sum_f32(reversed_view, copy=True) == pytest.approx(float(a.sum()), rel=1e-5)
+    assert _corepy_rust.np_sum_f32(a, max_threads=1) == _corepy_rust.np_sum_f32(a)
+
+
+@pytest.mark.parametrize("order", ["C", "F"])
+def test_matmul_allocates_output(order):
+    rng = np.random.default_rng(0)
+    a = np.asarray(rng.standard_normal((33, 17)), dtype=np.float32, order=order)
+    b = np.asarray(rng.standard_normal((17, 9)), dtype=np.float32, order=order)
+    if order == "F":
+        with pytest.raises(ValueError, match="np_matmul_f32: a is not C-contiguous"):
+            _corepy_rust.np_matmul_f32(a, b)
+    out = _corepy_rust.np_matmul_f32(a, b, copy=True)
+    assert out.shape == (33, 9) and out.dtype == np.float32 and out.flags.c_contiguous
+    np.testing.assert_allclose(out, a @ b, rtol=1e-4, atol=1e-5)
+
+
+def test_matmul_sliced_and_transposed_inputs():
+    base = np.arange(64, dtype=np.float32).reshape(8, 8)
+    a = base[1:7:2, ::3]
+    b = base[::2, :3].T
+    out = _corepy_rust.np_matmul_f32(a, b, copy=True)
+    np.testing.assert_allclose(out, a @ b, rtol=1e-5)
+
+
+def test_matmul_rejects_mismatched_shapes():
+    with pytest.raises(ValueError, match="shapes \\[2, 3\\] and \\[2, 3\\] are incompatible"):
+        _corepy_rust.np_matmul_f32(np.ones((2, 3), np.float32), np.ones((2, 3), np.float32))
+    with pytest.raises(ValueError, match="b must be 2-D, got 1-D"):
+        _corepy_rust.np_matmul_f32(np.ones((2, 3), np.float32), np.ones(3, np.float32))
@@ -0,0 +1,83 @@
+"""
+Tests for the out_capacity argument of the write-out entry points: an
+output buffer the caller says is too small is rejected before anything is
+written.
+"""
+
+import array
+
+import pytest
+
+from corepy import _corepy_rust
+
+SENTINEL = -7.0
+
+
+def _floats(values):
+    return array.array("f", values)
+
+
+def _address(buffer):
+    return buffer.buffer_info()[0]
+
+
+# Inputs of every call below; module-level so they outlive the lambdas, and
+# large enough for the accumulate case's 3x5 B
+_A, _B = _floats([1.0] * 16), _floats([2.0] * 16)
+
+
+def _calls(out):
+    pa, pb, po = _address(_A), _address(_B), _address(out)
+    return {
+        # name: (call taking out_capacity, elements it writes)
+        "tensor_add_f32": (lambda cap: _corepy_rust.tensor_add_f32(pa, pb, po, 8, out_capacity=cap), 8),
+        "tensor_div_f32": (lambda cap: _corepy_rust.tensor_div_f32(pa, pb, po, 8, out_capacity=cap), 8),
+        "tensor_binary": (
+            lambda cap: _corepy_rust.tensor_binary(pa, pb, po, 8, 2, _corepy_rust.dtype_code("float32"), cap), 8
+        ),
+        "tensor_broadcast_binary_f32": (
+            lambda cap: _corepy_rust.tensor_broadcast_binary_f32(pa, [3, 1], [4, 4], pb, [4], [4], po, 0, cap), 12
+        ),
+        "tensor_matmul_2d_f32": (
+            lambda cap: _corepy_rust.tensor_matmul_2d_f32(pa, pb, po, 3, 2, 4, out_capacity=cap), 12
+        ),
+        "tensor_matmul_2d_f32_acc": (
+            lambda cap: _corepy_rust.tensor_matmul_2d_f32_acc(pa, pb, po, 2, 3, 5, out_capacity=cap), 10
+        ),
+        "tensor_row_norms_f32": (lambda cap: _corepy_rust.tensor_row_norms_f32(pa, po, 4, 3, 2, out_capacity=cap), 4),
+        "tensor_eye_f32": (lambda cap: _corepy_rust.tensor_eye_f32(po, 3, out_capacity=cap), 9),
+        "tensor_diag_f32": (lambda cap: _corepy_rust.tensor_diag_f32(pa, po, 3, out_capacity=cap), 9),
+    }
+
+
+@pytest.mark.parametrize("name", list(_calls(_floats([0.0]))))
+def test_undersized_capacity_raises_before_writing(name):
+    out = _floats([SENTINEL] * 16)
+    call, required = _calls(out)[name]
+    with pytest.raises(
+        _corepy_rust.ShapeError,
+        match=f"^{name}: out_ptr has room for {required - 1} elements but {required} are required$",
+    ):
+        call(required - 1)
+    assert set(out) == {SENTINEL}
+
+    call(required)
+    assert out[required - 1] != SENTINEL
+    assert out[required:] == _floats([SENTINEL] * (16 - required))
+
+
+def test_capacity_defaults_to_trusting_the_caller():
+    out = _floats([SENTINEL] * 4)
+    a = _floats([1.0, 2.0, 3.0, 4.0])
+    _corepy_rust.tensor_add_f32(_address(a), _address(a), _address(out), 4)
+    assert list(out) == [2.0, 4.0, 6.0, 8.0]
+    # Larger than needed is fine
+    _corepy_rust.tensor_mul_f32(_address(a), _address(a), _address(out), 4, out_capacity=100)
+    assert list(out) == [1.0, 4.0, 9.0, 16.0]
+
+
+def test_submitted_matmul_checks_capacity():
+    a, out = _floats([1.0] * 6), _floats([SENTINEL] * 4)
+    with pytest.raises(_corepy_rust.ShapeError, match="room for 3 elements but 4 are required"):
+        _corepy_rust.submit_matmul_2d_f32(_address(a), _address(a), _address(out), 2, 3, 2, out_capacity=3)
+    assert set(out) == {SENTINEL}
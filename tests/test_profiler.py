@@ -10,8 +10,11 @@ from corepy.profiler import (
     detect_bottlenecks,
     disable_profiling,
     enable_profiling,
+    profile_marker,
     profile_operation,
     profile_report,
+    set_active_profiler,
+    track_call_sites,
 )
 
 
@@ -124,6 +127,537 @@ def test_metrics_accuracy():
     assert add_ops['count'] == loops
     assert add_ops['total_time_ms'] >= 0.0
 
+def test_throughput_metrics():
+    """Test that element counts and throughput are reported per operation."""
+    enable_profiling()
+    t = cp.Tensor([1.0, 2.0, 3.0, 4.0])
+
+    for _ in range(5):
+        _ = t + t
+
+    add_ops = profile_report(format='dict')['operations']['add']
+    assert add_ops['total_elements'] == 20
+    assert add_ops['avg_elements_per_call'] == 4.0
+    assert add_ops['elements_per_sec'] >= 0.0
+    assert 'gb_per_s' in add_ops
+    assert 'gflops' not in add_ops
+
+def test_report_operation_filter():
+    """Test restricting a report to named operations."""
+    enable_profiling()
+    t = cp.Tensor([1.0, 2.0])
+    _ = t + t
+    _ = t * t
+    _ = t.sum()
+
+    report = profile_report(format='dict', operations=['add', 'mul', 'no_such_op'])
+    assert set(report['operations']) == {'add', 'mul'}
+    if report['total_time_ms'] > 0:
+        assert sum(op['percent_total'] for op in report['operations'].values()) == pytest.approx(100.0)
+    assert report['session_total_time_ms'] >= report['total_time_ms']
+
+    assert profile_report(format='dict', operations=['no_such_op'])['operations'] == {}
+
+def test_size_buckets():
+    """Test that calls are split by data size."""
+    import numpy as np
+
+    enable_profiling()
+    small = cp.Tensor(np.ones(50, dtype=np.float32))
+    large = cp.Tensor(np.ones(5000, dtype=np.float32))
+    _ = small + small
+    _ = large + large
+    _ = large + large
+
+    buckets = profile_report(format='dict')['operations']['add']['size_buckets']
+    assert [(b['label'], b['count']) for b in buckets] == [("10-100", 1), ("1K-10K", 2)]
+
+def test_save_profile_report(tmp_path):
+    """Test that reports are written to disk from Rust and parse back."""
+    from corepy import _corepy_rust
+    from corepy.profiler import export_profile
+
+    enable_profiling()
+    t = cp.Tensor([1.0, 2.0])
+    _ = t + t
+
+    path = tmp_path / "nested" / "report.json"
+    _corepy_rust.save_profile_report(str(path), create_dirs=True)
+    assert json.loads(path.read_text())['operations']['add']['count'] == 1
+
+    export_profile(str(tmp_path / "report.json"))
+    assert json.loads((tmp_path / "report.json").read_text())['operations']['add']['count'] == 1
+
+    missing = tmp_path / "missing" / "report.json"
+    with pytest.raises(IOError, match="missing"):
+        _corepy_rust.save_profile_report(str(missing))
+    with pytest.raises(ValueError):
+        _corepy_rust.save_profile_report(str(tmp_path / "x"), format="xml")
+
+def test_merge_profile_reports():
+    """Test that reports from separate sessions merge into one aggregate."""
+    from corepy import _corepy_rust
+
+    enable_profiling()
+    t = cp.Tensor([1.0, 2.0])
+    _ = t + t
+    first = profile_report(format='json')
+
+    clear_profile()
+    for _ in range(3):
+        _ = t + t
+    second = profile_report(format='json')
+
+    merged = json.loads(_corepy_rust.merge_profile_reports([first, second]))
+    assert merged['metadata']['merged_sessions'] == 2
+    assert merged['operations']['add']['count'] == 4
+    assert merged['total_time_ms'] == pytest.approx(
+        json.loads(first)['total_time_ms'] + json.loads(second)['total_time_ms'])
+
+    with pytest.raises(ValueError, match=r"report\[1\]"):
+        _corepy_rust.merge_profile_reports([first, "{}"])
+
+def test_chrome_trace_export(tmp_path):
+    """Test that chrome_tracing export writes one complete event per call."""
+    from corepy.profiler import export_profile
+
+    enable_profiling()
+    t = cp.Tensor([1.0, 2.0])
+    with ProfileContext("trace_section"):
+        _ = t + t
+    _ = t * t
+
+    path = tmp_path / "trace.json"
+    export_profile(str(path), format='chrome_tracing')
+    events = json.loads(path.read_text())["traceEvents"]
+
+    assert [e["name"] for e in events if e["name"] in ("add", "mul")] == ["add", "mul"]
+    for event in events:
+        assert event["ph"] == "X"
+        assert event["dur"] >= 0
+        assert {"ts", "pid", "tid", "args"} <= event.keys()
+        assert {"backend", "data_size", "context"} <= event["args"].keys()
+    add = next(e for e in events if e["name"] == "add")
+    assert add["args"]["context"] == "trace_section"
+
+def test_per_thread_breakdown():
+    """Test that events from different threads are attributed separately."""
+    import threading
+
+    enable_profiling()
+    t = cp.Tensor([1.0, 2.0])
+    _ = t + t
+
+    worker = threading.Thread(target=lambda: [t * t for _ in range(3)])
+    worker.start()
+    worker.join()
+
+    threads = profile_report(format='dict')['threads']
+    assert len(threads) >= 2
+    counts = sorted(info['event_count'] for info in threads.values())
+    assert 1 in counts and 3 in counts
+    total = sum(info['total_time_ms'] for info in threads.values())
+    assert total == pytest.approx(profile_report(format='dict')['total_time_ms'])
+
+def test_parallel_sum_keeps_context_off_main_thread():
+    """Test that a parallel sum dispatched from a worker thread keeps its context."""
+    import threading
+
+    import numpy as np
+
+    enable_profiling()
+    t = cp.Tensor(np.ones(2_000_000, dtype=np.float32))  # Above the parallel threshold
+
+    def worker():
+        with ProfileContext("worker_section"):
+            assert t.sum() == 2_000_000
+
+    thread = threading.Thread(target=worker)
+    thread.start()
+    thread.join()
+
+    report = profile_report(context="worker_section", format='dict')
+    assert report['operations']['sum']['count'] == 1
+
+def test_sampling_estimates_counts():
+    """Test that a sampled profile scales counts back up and flags them."""
+    from corepy import _corepy_rust
+
+    assert _corepy_rust.get_profile_sample_rate() == 1
+    try:
+        _corepy_rust.set_profile_sample_rate(10)
+        enable_profiling()
+        t = cp.Tensor([1.0])
+        for _ in range(200):
+            _ = t + t
+
+        report = profile_report(format='dict')
+        add_ops = report['operations']['add']
+        assert add_ops['estimated'] is True
+        assert abs(add_ops['count'] - 200) <= 10
+        assert report['metadata']['sample_rate'] == 10
+
+        with pytest.raises(ValueError):
+            _corepy_rust.set_profile_sample_rate(0)
+    finally:
+        _corepy_rust.set_profile_sample_rate(1)
+
+def test_drain_profile_events():
+    """Test that drained events are removed and returned oldest first."""
+    from corepy import _corepy_rust
+
+    enable_profiling()
+    t = cp.Tensor([1.0, 2.0])
+    for _ in range(5):
+        _ = t + t
+
+    first = _corepy_rust.drain_profile_events(3)
+    rest = _corepy_rust.drain_profile_events(100)
+    assert [e['operation'] for e in first + rest] == ['add'] * 5
+    seqs = [e['seq'] for e in first + rest]
+    assert seqs == list(range(seqs[0], seqs[0] + 5))
+    assert first[0]['data_size'] == 2
+    assert _corepy_rust.drain_profile_events(100) == []
+    assert profile_report(format='dict')['operations'] == {}
+
+def test_arena_bytes_per_operation():
+    """Test that f16 matmul reports the arena scratch it converts A into."""
+    import numpy as np
+    from corepy import _corepy_rust
+
+    a = np.ones((8, 16), dtype=np.float16)
+    b = np.ones((16, 4), dtype=np.float16)
+    out = np.zeros((8, 4), dtype=np.float32)
+
+    t = cp.Tensor([1.0, 2.0])
+    enable_profiling()
+    _corepy_rust.tensor_matmul_2d_f16(a.ctypes.data, b.ctypes.data, out.ctypes.data, 8, 16, 4)
+    _ = t + t
+    disable_profiling()
+
+    # One 8x16 f32 panel, converted on a worker but credited to the call
+    operations = profile_report(format='dict')['operations']
+    metrics = operations['matmul_f16']
+    assert metrics['max_arena_bytes'] >= 8 * 16 * 4
+    assert metrics['avg_arena_bytes'] > 0
+    assert metrics['max_heap_bytes'] >= 0
+    # Element-wise add needs no scratch
+    assert operations['add']['max_arena_bytes'] == 0
+
+def test_named_profilers_are_isolated():
+    """Test that only the enabled, attached named profiler sees ops."""
+    for name in ("lib_a", "lib_b"):
+        clear_profile(profiler=name)
+    enable_profiling(profiler="lib_a")
+    set_active_profiler("lib_a")
+    try:
+        t = cp.Tensor([1.0, 2.0])
+        _ = t + t
+        _ = t * t
+    finally:
+        set_active_profiler(None)
+        disable_profiling(profiler="lib_a")
+
+    ops_a = profile_report(format='dict', profiler="lib_a")['operations']
+    assert ops_a['add']['count'] == 1
+    assert ops_a['mul']['count'] == 1
+    assert profile_report(format='dict', profiler="lib_b")['operations'] == {}
+    # Global profiler was never enabled
+    assert profile_report(format='dict')['operations'] == {}
+
+    clear_profile(profiler="lib_a")
+    assert profile_report(format='dict', profiler="lib_a")['operations'] == {}
+
+def test_profile_enabled_from_environment(tmp_path):
+    """Test that COREPY_PROFILE* vars profile a script with no profiler calls."""
+    import os
+    import subprocess
+    import sys
+
+    output = tmp_path / "reports" / "profile.json"
+    env = dict(os.environ,
+               COREPY_PROFILE="1",
+               COREPY_PROFILE_CONTEXT="startup",
+               COREPY_PROFILE_OUTPUT=str(output))
+    script = (
+        "import corepy as cp\n"
+        "from corepy.profiler import profile_report\n"
+        "t = cp.Tensor([1.0, 2.0])\n"
+        "_ = t + t\n"
+        "print(profile_report(context='startup', format='json'))\n"
+    )
+    result = subprocess.run([sys.executable, "-c", script], env=env, check=True,
+                            capture_output=True, text=True)
+
+    # Recorded under the initial context, and flushed to disk at exit
+    assert json.loads(result.stdout)['operations']['add']['count'] == 1
+    report = json.loads(output.read_text())
+    assert report['operations']['add']['count'] == 1
+
+def test_profile_markers():
+    """Test that markers are listed in order and kept out of op metrics."""
+    enable_profiling()
+    t = cp.Tensor([1.0, 2.0])
+    for epoch in range(3):
+        profile_marker(f"epoch {epoch} start", metadata=str(epoch))
+        _ = t + t
+    disable_profiling()
+
+    report = profile_report(format='dict')
+    assert [m['name'] for m in report['markers']] == [f"epoch {i} start" for i in range(3)]
+    assert [m['metadata'] for m in report['markers']] == ['0', '1', '2']
+    timestamps = [m['timestamp_us'] for m in report['markers']]
+    assert timestamps == sorted(timestamps)
+    assert set(report['operations']) == {'add'}
+    assert report['operations']['add']['count'] == 3
+
+    from corepy import _corepy_rust
+    trace = json.loads(_corepy_rust.get_chrome_trace())
+    assert [e['name'] for e in trace['traceEvents'] if e['ph'] == 'i'] == [f"epoch {i} start" for i in range(3)]
+
+def test_profile_spill_to_disk(tmp_path):
+    """Test that spilled events still count in the report."""
+    from corepy import _corepy_rust
+
+    spill_file = tmp_path / "events.ndjson"
+    _corepy_rust.enable_profile_spill(str(spill_file), 10)
+    try:
+        enable_profiling()
+        t = cp.Tensor([1.0, 2.0])
+        for _ in range(35):
+            _ = t + t
+        disable_profiling()
+
+        report = profile_report(format='dict')
+        assert report['operations']['add']['count'] == 35
+        assert report['metadata']['spilled_events'] == 30
+        assert len(spill_file.read_text().splitlines()) == 30
+    finally:
+        _corepy_rust.disable_profile_spill()
+
+    with pytest.raises(ValueError):
+        _corepy_rust.enable_profile_spill(str(spill_file), 0)
+
+def test_profile_report_dict_matches_json():
+    """Test that the native dict report equals the parsed JSON report."""
+    from corepy import _corepy_rust
+
+    enable_profiling()
+    a = cp.Tensor([1.0, 2.0, 3.0, 4.0])
+    with ProfileContext("dict_section"):
+        _ = a + a
+        _ = a.sum()
+    _ = a * a
+    disable_profiling()
+
+    for kwargs in ({}, {"context": "dict_section", "include_events": True}):
+        native = _corepy_rust.get_profile_report_dict(**kwargs)
+        parsed = json.loads(_corepy_rust.get_profile_report(**kwargs))
+        # Each call starts a fresh session id/timestamp
+        for report in (native, parsed):
+            del report['metadata']['session_id']
+            del report['metadata']['start_timestamp']
+        assert native == parsed
+
+    assert isinstance(native['operations']['add']['count'], int)
+    assert len(native['events']) == 2
+
+def test_profile_warmup_excluded():
+    """Test that the first calls per operation are reported as warmup."""
+    from corepy import _corepy_rust
+
+    assert _corepy_rust.get_profile_warmup() == 0
+    try:
+        _corepy_rust.set_profile_warmup(3)
+        enable_profiling()
+        t = cp.Tensor([1.0, 2.0])
+        for _ in range(8):
+            _ = t + t
+
+        add = profile_report(format='dict')['operations']['add']
+        assert add['count'] == 5
+        assert add['warmup_count'] == 3
+        assert add['warmup_total_ms'] >= 0
+
+        events = _corepy_rust.drain_profile_events()
+        assert [e['warmup'] for e in events if e['operation'] == 'add'] == [True] * 3 + [False] * 5
+    finally:
+        _corepy_rust.set_profile_warmup(0)
+
+def test_matmul_event_shape_metadata():
+    """Test that matmul events carry their shape and dtype as attributes."""
+    import numpy as np
+
+    from corepy import _corepy_rust
+
+    enable_profiling()
+    a = np.ones((4, 3), dtype=np.float32)
+    b = np.ones((3, 5), dtype=np.float32)
+    out = np.zeros((4, 5), dtype=np.float32)
+    _corepy_rust.tensor_matmul_2d_f32(a.ctypes.data, b.ctypes.data, out.ctypes.data, 4, 3, 5)
+
+    events = [e for e in _corepy_rust.drain_profile_events() if e['operation'] == 'matmul']
+    assert len(events) == 1
+    assert events[0]['attributes'] == {'m': '4', 'k': '3', 'n': '5', 'dtype': 'float32'}
+
+def test_matmul_reports_gil_released_fraction():
+    """Test that matmul runs its kernel with the GIL released and reports the split."""
+    import numpy as np
+
+    from corepy import _corepy_rust
+
+    enable_profiling()
+    a = np.ones((256, 256), dtype=np.float32)
+    out = np.zeros((256, 256), dtype=np.float32)
+    _corepy_rust.tensor_matmul_2d_f32(a.ctypes.data, a.ctypes.data, out.ctypes.data, 256, 256, 256)
+    t = cp.Tensor([1.0])
+    _ = t + t
+
+    ops = profile_report(format='dict')['operations']
+    assert 0.0 < ops['matmul']['gil_released_fraction'] <= 1.0
+    # Still holds the GIL throughout (no fraction if the call took under 1µs)
+    assert ops['add'].get('gil_released_fraction') in (None, 0.0)
+
+    event = [e for e in _corepy_rust.drain_profile_events() if e['operation'] == 'matmul'][0]
+    assert event['gil_held_us'] + event['gil_released_us'] == event['duration_us']
+
+def test_report_contexts_summary():
+    """Test that one unfiltered report splits the session by context."""
+    enable_profiling()
+    t = cp.Tensor([1.0, 2.0])
+    with ProfileContext("phase_a"):
+        _ = t + t
+    with ProfileContext("phase_b"):
+        _ = t * t
+        _ = t * t
+    _ = t - t
+
+    report = profile_report(format='dict')
+    contexts = report['contexts']
+    assert set(contexts) == {'phase_a', 'phase_b', 'uncategorized'}
+    assert contexts['phase_b']['event_count'] == 2
+    assert contexts['phase_b']['top_operations'][0]['operation'] == 'mul'
+    total = sum(c['total_time_ms'] for c in contexts.values())
+    assert total == pytest.approx(report['session_total_time_ms'])
+
+def _add_from_site_one(t):
+    return t + t
+
+def _add_from_site_two(t):
+    return t + t
+
+def test_call_sites_grouped_per_operation():
+    """Test that tracked calls are grouped by the Python line that issued them."""
+    enable_profiling()
+    t = cp.Tensor([1.0, 2.0])
+    track_call_sites()
+    try:
+        for _ in range(3):
+            _add_from_site_one(t)
+        _add_from_site_two(t)
+    finally:
+        track_call_sites(False)
+    _ = t + t  # untracked
+
+    report = profile_report(format='dict')
+    sites = {s['function']: s for s in report['operations']['add']['call_sites']}
+    assert set(sites) == {'_add_from_site_one', '_add_from_site_two'}
+    assert sites['_add_from_site_one']['count'] == 3
+    assert sites['_add_from_site_two']['count'] == 1
+    assert sites['_add_from_site_one']['file'].endswith('test_profiler.py')
+    assert report['operations']['add']['count'] == 5
+
+def test_profile_sessions():
+    """Test that back-to-back sessions report only their own events."""
+    from corepy import _corepy_rust
+
+    enable_profiling()
+    t = cp.Tensor([1.0, 2.0])
+
+    first = _corepy_rust.start_profile_session("first")
+    _ = t + t
+    first_report = json.loads(_corepy_rust.end_profile_session(first))
+
+    second = _corepy_rust.start_profile_session("second")
+    _ = t * t
+    _ = t * t
+    _corepy_rust.end_profile_session(second)
+    second_report = json.loads(_corepy_rust.get_profile_session_report(second))
+
+    assert set(first_report['operations']) == {'add'}
+    assert set(second_report['operations']) == {'mul'}
+    assert second_report['operations']['mul']['count'] == 2
+    assert second_report['metadata']['session_name'] == "second"
+
+    names = [s['name'] for s in _corepy_rust.list_profile_sessions() if s['id'] in (first, second)]
+    assert names == ["first", "second"]
+    with pytest.raises(ValueError):
+        _corepy_rust.end_profile_session(second)
+
+def test_print_profile_summary():
+    """Test the terminal summary table from the Rust runtime."""
+    from corepy import _corepy_rust
+
+    assert _corepy_rust.print_profile_summary().startswith("No profiled operations")
+
+    enable_profiling()
+    t = cp.Tensor([1.0, 2.0])
+    for _ in range(3):
+        _ = t + t
+    _ = t * t
+
+    lines = _corepy_rust.print_profile_summary(sort_by="calls").splitlines()
+    assert lines[1].split()[:2] == ["Operation", "Calls"]
+    assert lines[3].split()[:2] == ["add", "3"]
+    assert len(_corepy_rust.print_profile_summary(top_n=1).splitlines()) == 4
+    with pytest.raises(ValueError):
+        _corepy_rust.print_profile_summary(sort_by="bogus")
+
+@pytest.mark.parametrize("fmt", ["msgpack", "bincode"])
+def test_profile_events_binary_round_trip(fmt):
+    """Test that binary event exports rebuild the same report elsewhere."""
+    from corepy import _corepy_rust
+
+    enable_profiling()
+    t = cp.Tensor([1.0, 2.0])
+    for _ in range(5):
+        _ = t + t
+
+    data = _corepy_rust.export_profile_events_binary(fmt)
+    assert isinstance(data, bytes) and data[:4] == b"CPEV"
+    assert len(data) < len(_corepy_rust.get_profile_report(include_events=True))
+
+    assert _corepy_rust.import_profile_events_binary(data, profiler="offline_binary") == 5
+    offline = profile_report(format='dict', profiler="offline_binary")
+    assert offline['operations']['add']['count'] == 5
+    clear_profile(profiler="offline_binary")
+
+    with pytest.raises(ValueError):
+        _corepy_rust.import_profile_events_binary(b"not an export")
+    with pytest.raises(ValueError):
+        _corepy_rust.export_profile_events_binary("cbor")
+
+def test_profile_capacity_drops_oldest():
+    """Test that a capped profiler evicts old events and reports the drop count."""
+    from corepy import _corepy_rust
+
+    original = _corepy_rust.get_profile_capacity()
+    try:
+        _corepy_rust.set_profile_capacity(10)
+        enable_profiling()
+        t = cp.Tensor([1.0])
+        for _ in range(25):
+            _ = t + t
+
+        report = profile_report(format='dict')
+        assert report['operations']['add']['count'] == 10
+        assert report['metadata']['dropped_events'] == 15
+
+        with pytest.raises(ValueError):
+            _corepy_rust.set_profile_capacity(0)
+    finally:
+        _corepy_rust.set_profile_capacity(original)
+
 def test_matmul_profiling():
     """Test that matmul (FFI dispatch) is profiled."""
     enable_profiling()
@@ -138,7 +672,37 @@ def test_matmul_profiling():
     assert ('matmul' in ops) or ('dot_product' in ops)
     op_key = 'matmul' if 'matmul' in ops else 'dot_product'
     assert ops[op_key]['count'] == 1
-    assert ops[op_key]['primary_backend'] == 'CPU'
+    # The event carries the path that actually ran, not a generic 'CPU'
+    assert ops[op_key]['primary_backend'] == 'CPU-serial'
+    assert ops[op_key]['backends']['CPU-serial']['count'] == 1
+
+def test_profile_records_dispatched_backend():
+    """Test that each forced dispatch path shows up as its own backend."""
+    import numpy as np
+
+    from corepy import _corepy_rust
+    from corepy.backend import BackendPolicy, backend_policy
+
+    enable_profiling()
+    with ProfileContext("serial"):
+        cp.Tensor(np.ones(16, dtype=np.float32)).sum()
+    with ProfileContext("parallel"):
+        cp.Tensor(np.ones(2_000_000, dtype=np.float32)).sum()  # Above the parallel threshold
+
+    a = np.ones((64, 64), dtype=np.float32)
+    out = np.zeros((64, 64), dtype=np.float32)
+    with ProfileContext("blas"), backend_policy(BackendPolicy.OPENBLAS):
+        _corepy_rust.tensor_matmul_2d_f32(a.ctypes.data, a.ctypes.data, out.ctypes.data, 64, 64, 64)
+    # Forced BLAS falls back to the native kernels when no BLAS is linked
+    expected = _corepy_rust.get_last_dispatch_stats()['backend']
+    assert expected in ('OpenBLAS', 'Corepy AVX2')
+
+    def backends(context, op):
+        return set(profile_report(context=context, format='dict')['operations'][op]['backends'])
+
+    assert backends("serial", "sum") == {'CPU-serial'}
+    assert backends("parallel", "sum") == {'CPU-parallel'}
+    assert backends("blas", "matmul") == {expected}
 
 def test_bottleneck_detection():
     """Test bottleneck detection logic."""
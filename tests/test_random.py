@@ -0,0 +1,68 @@
+"""
+Tests for seeded random tensors (tensor_random_uniform /
+tensor_random_normal / tensor_random_fill).
+"""
+
+import ctypes
+
+import numpy as np
+import pytest
+
+from corepy import _corepy_rust
+
+
+def _take(handle):
+    info = _corepy_rust.tensor_info(handle)
+    out = np.empty(info["shape"], dtype=info["dtype"])
+    ctypes.memmove(out.ctypes.data, info["ptr"], info["nbytes"])
+    _corepy_rust.unregister_tensor(handle)
+    return out
+
+
+@pytest.mark.parametrize("make", [_corepy_rust.tensor_random_uniform, _corepy_rust.tensor_random_normal])
+def test_same_seed_same_tensor_for_any_thread_count(make):
+    shape = [1500, 1000]
+    reference = _take(make(shape, seed=1234, max_threads=1))
+    for threads in (2, 3, None):
+        np.testing.assert_array_equal(_take(make(shape, seed=1234, max_threads=threads)), reference)
+    assert not np.array_equal(_take(make(shape, seed=1235)), reference)
+
+
+def test_unseeded_draws_differ():
+    a = _take(_corepy_rust.tensor_random_uniform([256]))
+    b = _take(_corepy_rust.tensor_random_uniform([256]))
+    assert not np.array_equal(a, b)
+
+
+def test_uniform_moments():
+    low, high = -2.0, 4.0
+    values = _take(_corepy_rust.tensor_random_uniform([400_000], low, high, seed=5, dtype="float64"))
+    assert values.min() >= low and values.max() < high
+    assert values.mean() == pytest.approx((low + high) / 2, abs=0.02)
+    assert values.var() == pytest.approx((high - low) ** 2 / 12, abs=0.05)
+
+
+def test_normal_moments():
+    values = _take(_corepy_rust.tensor_random_normal([400_000], 5.0, 3.0, seed=6, dtype="float64"))
+    assert values.mean() == pytest.approx(5.0, abs=0.03)
+    assert values.std() == pytest.approx(3.0, abs=0.03)
+    # About 68% within one standard deviation
+    assert np.mean(np.abs(values - 5.0) < 3.0) == pytest.approx(0.6827, abs=0.005)
+
+
+def test_fill_caller_owned_memory_matches_tensor():
+    out = np.empty(10_000, dtype=np.float32)
+    _corepy_rust.tensor_random_fill(out.ctypes.data, out.size, "normal", seed=9, params=(1.0, 0.5))
+    expected = _take(_corepy_rust.tensor_random_normal([10_000], 1.0, 0.5, seed=9))
+    np.testing.assert_array_equal(out, expected)
+
+
+def test_invalid_arguments():
+    with pytest.raises(ValueError, match="unknown distribution"):
+        _corepy_rust.tensor_random_fill(8, 1, "poisson")
+    with pytest.raises(ValueError, match="low <= high"):
+        _corepy_rust.tensor_random_uniform([4], 1.0, 0.0)
+    with pytest.raises(ValueError, match="std >= 0"):
+        _corepy_rust.tensor_random_normal([4], 0.0, -1.0)
+    with pytest.raises(ValueError, match="float32 or float64"):
+        _corepy_rust.tensor_random_uniform([4], dtype="int32")
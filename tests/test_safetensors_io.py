@@ -0,0 +1,85 @@
+"""
+Tests for .safetensors export and import of named tensor collections
+(save_safetensors / load_safetensors).
+"""
+
+import ctypes
+
+import numpy as np
+import pytest
+
+from corepy import _corepy_rust
+
+
+def _to_numpy(handle):
+    info = _corepy_rust.tensor_info(handle)
+    out = np.empty(info["shape"], dtype=info["dtype"])
+    ctypes.memmove(out.ctypes.data, info["ptr"], info["nbytes"])
+    return out
+
+
+@pytest.fixture
+def tensors():
+    arrays = {
+        "embed.weight": np.random.default_rng(0).standard_normal((8, 4)).astype(np.float32),
+        "embed.bias": np.linspace(-1, 1, 4),
+        "positions": np.arange(6, dtype=np.int32).reshape(2, 3),
+        "scale": np.array(0.5, dtype=np.float32),
+    }
+    handles = {name: _corepy_rust.tensor_from_buffer(array) for name, array in arrays.items()}
+    yield arrays, handles
+    for handle in handles.values():
+        _corepy_rust.tensor_release(handle)
+
+
+def test_round_trip(tmp_path, tensors):
+    arrays, handles = tensors
+    path = tmp_path / "model.safetensors"
+    _corepy_rust.save_safetensors(str(path), list(handles.items()))
+
+    loaded = _corepy_rust.load_safetensors(str(path))
+    assert set(loaded) == set(arrays)
+    for name, handle in loaded.items():
+        np.testing.assert_array_equal(_to_numpy(handle), arrays[name])
+        assert _to_numpy(handle).dtype == arrays[name].dtype
+        _corepy_rust.unregister_tensor(handle)
+
+
+def test_official_library_reads_our_files(tmp_path, tensors):
+    safetensors_numpy = pytest.importorskip("safetensors.numpy")
+    arrays, handles = tensors
+    path = tmp_path / "model.safetensors"
+    _corepy_rust.save_safetensors(str(path), list(handles.items()))
+
+    loaded = safetensors_numpy.load_file(str(path))
+    assert set(loaded) == set(arrays)
+    for name, array in arrays.items():
+        np.testing.assert_array_equal(loaded[name], array)
+
+
+def test_we_read_official_files(tmp_path, tensors):
+    safetensors_numpy = pytest.importorskip("safetensors.numpy")
+    arrays, _ = tensors
+    path = tmp_path / "official.safetensors"
+    safetensors_numpy.save_file(arrays, str(path), metadata={"format": "np"})
+
+    loaded = _corepy_rust.load_safetensors(str(path))
+    for name, handle in loaded.items():
+        np.testing.assert_array_equal(_to_numpy(handle), arrays[name])
+        _corepy_rust.unregister_tensor(handle)
+
+
+def test_invalid_collections_write_nothing(tmp_path, tensors):
+    arrays, handles = tensors
+    path = tmp_path / "bad.safetensors"
+    weight = handles["embed.weight"]
+
+    with pytest.raises(ValueError, match="duplicate tensor name"):
+        _corepy_rust.save_safetensors(str(path), [("w", weight), ("w", weight)])
+
+    transposed = _corepy_rust.tensor_transpose_view(weight)
+    with pytest.raises(ValueError, match="not C-contiguous"):
+        _corepy_rust.save_safetensors(str(path), [("w", weight), ("wt", transposed)])
+    _corepy_rust.unregister_tensor(transposed)
+
+    assert not path.exists()
@@ -0,0 +1,68 @@
+"""
+Tests for tensor metadata inspection and memory accounting
+(tensor_info / list_tensors / total_registered_bytes).
+"""
+
+import threading
+
+import numpy as np
+
+from corepy import _corepy_rust
+
+
+def _listed(handles):
+    return {entry["handle"]: entry for entry in _corepy_rust.list_tensors() if entry["handle"] in handles}
+
+
+def test_info_reports_device_and_ownership():
+    a = np.arange(12, dtype=np.float32).reshape(3, 4)
+    borrowed = _corepy_rust.tensor_from_buffer(a)
+    owned = _corepy_rust.tensor_zeros([2, 5], "float64")
+    view = _corepy_rust.tensor_transpose_view(owned)
+
+    info = _corepy_rust.tensor_info(borrowed)
+    assert (info["shape"], info["strides"], info["dtype"], info["nbytes"]) == ((3, 4), a.strides, "float32", 48)
+    assert (info["device"], info["ownership"], info["handle"]) == ("cpu", "borrowed", borrowed)
+    assert _corepy_rust.tensor_info(owned)["ownership"] == "runtime"
+    assert _corepy_rust.tensor_info(view)["ownership"] == "runtime"
+
+    for handle in (borrowed, owned, view):
+        _corepy_rust.unregister_tensor(handle)
+
+
+def test_listing_and_totals_track_registrations():
+    # Other live handles may exist; only compare against our own
+    before = _corepy_rust.total_registered_bytes()
+    handles = [_corepy_rust.tensor_zeros([n], "float32") for n in (10, 20, 30)]
+    listed = _listed(handles)
+    assert {h: e["nbytes"] for h, e in listed.items()} == dict(zip(handles, (40, 80, 120)))
+    assert _corepy_rust.total_registered_bytes() == before + 240
+
+    _corepy_rust.unregister_tensor(handles[1])
+    assert set(_listed(handles)) == {handles[0], handles[2]}
+    assert _corepy_rust.total_registered_bytes() == before + 160
+
+    for handle in (handles[0], handles[2]):
+        _corepy_rust.unregister_tensor(handle)
+    assert _listed(handles) == {}
+    assert _corepy_rust.total_registered_bytes() == before
+
+
+def test_listing_is_safe_during_concurrent_registration():
+    stop = threading.Event()
+
+    def churn():
+        while not stop.is_set():
+            _corepy_rust.unregister_tensor(_corepy_rust.tensor_zeros([64], "float32"))
+
+    workers = [threading.Thread(target=churn) for _ in range(4)]
+    for worker in workers:
+        worker.start()
+    try:
+        for _ in range(200):
+            for entry in _corepy_rust.list_tensors():
+                assert entry["nbytes"] == int(np.prod(entry["shape"])) * np.dtype(entry["dtype"]).itemsize
+    finally:
+        stop.set()
+        for worker in workers:
+            worker.join()
@@ -0,0 +1,111 @@
+"""
+Tests for zero-copy tensor handles over buffer-protocol objects
+(tensor_from_buffer / tensor_release).
+"""
+
+import array
+import sys
+
+import numpy as np
+import pytest
+
+from corepy import _corepy_rust
+
+
+def test_contiguous_array_round_trip():
+    a = np.arange(12, dtype=np.float32).reshape(3, 4)
+    b = np.full((3, 4), 0.5, dtype=np.float32)
+    out = np.zeros((3, 4), dtype=np.float32)
+    ha, hb, hout = (_corepy_rust.tensor_from_buffer(x) for x in (a, b, out))
+
+    info = _corepy_rust.tensor_info(ha)
+    assert info["shape"] == (3, 4)
+    assert info["strides"] == a.strides
+    assert info["dtype"] == "float32"
+    assert info["ptr"] == a.ctypes.data
+    assert not info["readonly"]
+
+    _corepy_rust.op_add(ha, hb, hout)
+    np.testing.assert_array_equal(out, a + b)
+
+    for handle in (ha, hb, hout):
+        _corepy_rust.tensor_release(handle)
+
+
+def test_strided_and_reversed_arrays():
+    base = np.arange(40, dtype=np.float32).reshape(5, 8)
+    for view in (base[::2, 1::3], base[::-1], base.T):
+        handle = _corepy_rust.tensor_from_buffer(view)
+        info = _corepy_rust.tensor_info(handle)
+        assert info["shape"] == view.shape
+        assert info["strides"] == view.strides
+        assert info["ptr"] == view.ctypes.data
+        assert _corepy_rust.op_reduce(handle, 0) == pytest.approx(float(view.sum()))
+        _corepy_rust.tensor_release(handle)
+
+
+def test_array_module_and_memoryview():
+    values = array.array("i", range(10))
+    handle = _corepy_rust.tensor_from_buffer(values)
+    info = _corepy_rust.tensor_info(handle)
+    assert (info["shape"], info["dtype"]) == ((10,), "int32")
+    assert info["ptr"] == values.buffer_info()[0]
+    assert _corepy_rust.op_reduce(handle, 0) == sum(values)
+    _corepy_rust.tensor_release(handle)
+
+    doubles = array.array("d", [1.5, 2.5, 3.5, 4.5])
+    handle = _corepy_rust.tensor_from_buffer(memoryview(doubles)[::2])
+    info = _corepy_rust.tensor_info(handle)
+    assert (info["shape"], info["strides"], info["dtype"]) == ((2,), (16,), "float64")
+    _corepy_rust.tensor_release(handle)
+
+
+def test_readonly_buffer_rejected_as_output():
+    a = np.ones(6, dtype=np.float32)
+    frozen = np.zeros(6, dtype=np.float32)
+    frozen.flags.writeable = False
+    ha, hfrozen = _corepy_rust.tensor_from_buffer(a), _corepy_rust.tensor_from_buffer(frozen)
+    assert _corepy_rust.tensor_info(hfrozen)["readonly"]
+
+    # Reading from it is fine
+    out = np.empty(6, dtype=np.float32)
+    hout = _corepy_rust.tensor_from_buffer(out)
+    _corepy_rust.op_add(hfrozen, ha, hout)
+    np.testing.assert_array_equal(out, a)
+
+    with pytest.raises(ValueError, match="read-only"):
+        _corepy_rust.op_add(ha, ha, hfrozen)
+    view = _corepy_rust.tensor_view(hfrozen, [0], [3], [2])
+    with pytest.raises(ValueError, match="read-only"):
+        _corepy_rust.op_mul(view, view, view)
+    np.testing.assert_array_equal(frozen, 0)
+
+    hbytes = _corepy_rust.tensor_from_buffer(b"\x00" * 8)
+    assert _corepy_rust.tensor_info(hbytes)["readonly"]
+
+    for handle in (ha, hfrozen, hout, view, hbytes):
+        _corepy_rust.tensor_release(handle)
+
+
+def test_buffer_held_while_registered():
+    a = np.zeros(16, dtype=np.float32)
+    before = sys.getrefcount(a)
+    handle = _corepy_rust.tensor_from_buffer(a)
+    assert sys.getrefcount(a) > before
+    # A registered buffer can't be resized under the handle
+    with pytest.raises(ValueError):
+        a.resize(32)
+
+    _corepy_rust.tensor_release(handle)
+    assert sys.getrefcount(a) == before
+    with pytest.raises(ValueError, match="stale"):
+        _corepy_rust.tensor_release(handle)
+
+
+def test_unsupported_buffers():
+    with pytest.raises(TypeError):
+        _corepy_rust.tensor_from_buffer([1.0, 2.0])
+    with pytest.raises(ValueError, match="unsupported buffer format"):
+        _corepy_rust.tensor_from_buffer(np.zeros(3, dtype=np.complex64))
+    with pytest.raises(ValueError, match="unsupported buffer format"):
+        _corepy_rust.tensor_from_buffer(np.zeros(3, dtype=">f4"))